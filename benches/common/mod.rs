@@ -0,0 +1,61 @@
+//! Harness shared by benchmarks.
+//!
+//! Benchmarks that execute real blocks use the fixtures in `tests/fixtures/snapshots`
+//! or in the directory in the `BENCH_FIXTURES_DIR` environment variable.
+//! The repository includes a small fixture, and fixtures of imported blocks can be
+//! exported with `just importer-offline-fixture <block>`.
+//! When no fixtures are found, these benchmarks are skipped.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::path::PathBuf;
+
+use fake::Fake;
+use fake::Faker;
+use stratus::eth::external_rpc::ExternalBlockFixture;
+use stratus::eth::primitives::Block;
+use stratus::eth::primitives::BlockNumber;
+
+const DEFAULT_FIXTURES_DIR: &str = "tests/fixtures/snapshots";
+
+/// Loads all block fixtures ordered by block number.
+pub fn load_fixtures() -> Vec<ExternalBlockFixture> {
+    let dir = env::var("BENCH_FIXTURES_DIR").unwrap_or_else(|_| DEFAULT_FIXTURES_DIR.to_owned());
+    let pattern = PathBuf::from(dir).join("*.json");
+
+    let mut paths = glob::glob(&pattern.to_string_lossy())
+        .expect("fixtures pattern must be valid")
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let fixtures = paths
+        .into_iter()
+        .map(|path| ExternalBlockFixture::load(path).expect("fixture must be valid"))
+        .collect::<Vec<_>>();
+    if fixtures.is_empty() {
+        eprintln!("no block fixtures found in {}, skipping benchmarks that depend on them", pattern.display());
+    }
+    fixtures
+}
+
+/// Creates blocks with random transactions and logs numbered sequentially.
+pub fn fake_blocks(count: u64) -> Vec<Block> {
+    (0..count)
+        .map(|number| {
+            let number = BlockNumber::from(number);
+            let mut block: Block = Faker.fake();
+            block.header.number = number;
+            for tx in &mut block.transactions {
+                tx.block_number = number;
+                tx.block_hash = block.header.hash;
+                for log in &mut tx.logs {
+                    log.block_number = number;
+                    log.block_hash = block.header.hash;
+                }
+            }
+            block
+        })
+        .collect()
+}
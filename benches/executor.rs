@@ -0,0 +1,82 @@
+//! Benchmarks of the executor hot paths.
+//!
+//! Run with `cargo bench --bench executor`. Blocks are executed from the fixtures
+//! described in `benches/common/mod.rs`, each one over a fresh storage seeded
+//! with the fixture minimal state.
+
+mod common;
+
+use std::sync::Arc;
+
+use clap::Parser;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::Criterion;
+use stratus::eth::executor::Executor;
+use stratus::eth::executor::ExecutorConfig;
+use stratus::eth::external_rpc::ExternalBlockFixture;
+use stratus::eth::miner::MinerConfig;
+use stratus::eth::miner::MinerMode;
+use stratus::eth::primitives::ExternalReceipts;
+use stratus::eth::storage::InMemoryPermanentStorage;
+use stratus::eth::storage::InMemoryTemporaryStorage;
+use stratus::eth::storage::StratusStorage;
+use stratus::eth::storage::WriteStorage;
+use tokio::runtime::Runtime;
+
+use crate::common::load_fixtures;
+
+/// Chain ID used when the fixture transactions do not specify one.
+const DEFAULT_CHAIN_ID: u64 = 2008;
+
+fn execute_external_block(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("execute_external_block");
+    group.sample_size(10);
+    for fixture in load_fixtures() {
+        group.bench_function(fixture.block.number().to_string(), |b| {
+            b.iter_batched(
+                || {
+                    (
+                        init_executor(&runtime, &fixture),
+                        fixture.block.clone(),
+                        ExternalReceipts::from(fixture.receipts.clone()),
+                    )
+                },
+                |(executor, block, receipts)| executor.execute_external_block(block, receipts).unwrap(),
+                BatchSize::PerIteration,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Creates an executor over an in-memory storage seeded with the fixture state.
+fn init_executor(runtime: &Runtime, fixture: &ExternalBlockFixture) -> Arc<Executor> {
+    let state_block = fixture.state_block();
+    let perm = InMemoryPermanentStorage::default();
+    perm.set_mined_block_number(state_block.number()).unwrap();
+    perm.save_block(state_block).unwrap();
+    let temp = InMemoryTemporaryStorage::new(fixture.block.number());
+    let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+
+    let chain_id = fixture
+        .block
+        .transactions
+        .iter()
+        .find_map(|tx| tx.chain_id)
+        .map_or(DEFAULT_CHAIN_ID, |chain_id| chain_id.as_u64());
+    let executor_config = ExecutorConfig::parse_from(["executor", "--executor-chain-id", &chain_id.to_string()]);
+    let miner_config = MinerConfig::parse_from(["miner"]);
+
+    let _guard = runtime.enter();
+    let miner = runtime
+        .block_on(miner_config.init_with_mode(MinerMode::External, Arc::clone(&storage)))
+        .unwrap();
+    executor_config.init(storage, miner)
+}
+
+criterion_group!(benches, execute_external_block);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! Benchmarks of the gRPC block encoding compared to the JSON-RPC one.
+//!
+//! Run with `cargo bench --bench grpc`. Blocks are serialized to bytes and
+//! parsed back the way a client of each API would, so the time includes building
+//! the response message and the size of the encoded block is printed before the
+//! benchmarks run.
+
+use criterion::criterion_group;
+use criterion::BatchSize;
+use criterion::Criterion;
+use fake::Fake;
+use fake::Faker;
+use prost::Message;
+use stratus::eth::primitives::Block;
+use stratus::eth::primitives::BlockHeader;
+use stratus::eth::primitives::TransactionMined;
+use stratus::eth::rpc::grpc;
+
+/// Number of transactions of the benchmarked block.
+const TRANSACTIONS: usize = 100;
+
+/// Creates a block with random transactions.
+fn fake_block() -> Block {
+    Block {
+        header: Faker.fake::<BlockHeader>(),
+        transactions: (0..TRANSACTIONS).map(|_| Faker.fake::<TransactionMined>()).collect(),
+    }
+}
+
+fn encode_grpc(block: Block) -> Vec<u8> {
+    grpc::Block::from(block).encode_to_vec()
+}
+
+fn encode_json_rpc(block: Block) -> Vec<u8> {
+    serde_json::to_vec(&block.to_json_rpc_with_full_transactions()).unwrap()
+}
+
+/// Prints the encoded size of the same block in each API.
+fn report_sizes() {
+    let block = fake_block();
+    let grpc = encode_grpc(block.clone());
+    let json_rpc = encode_json_rpc(block);
+
+    println!("{:<30} {:>12}", "encoding", "bytes");
+    println!("{:<30} {:>12}", "grpc", grpc.len());
+    println!("{:<30} {:>12}", "json_rpc", json_rpc.len());
+    println!();
+}
+
+// -----------------------------------------------------------------------------
+// Benchmarks
+// -----------------------------------------------------------------------------
+
+fn block_encoding(c: &mut Criterion) {
+    let block = fake_block();
+
+    let mut group = c.benchmark_group("block_encode");
+    group.bench_function("grpc", |b| {
+        b.iter_batched(|| block.clone(), encode_grpc, BatchSize::SmallInput);
+    });
+    group.bench_function("json_rpc", |b| {
+        b.iter_batched(|| block.clone(), encode_json_rpc, BatchSize::SmallInput);
+    });
+    group.finish();
+}
+
+fn block_decoding(c: &mut Criterion) {
+    let block = fake_block();
+    let grpc = encode_grpc(block.clone());
+    let json_rpc = encode_json_rpc(block);
+
+    let mut group = c.benchmark_group("block_decode");
+    group.bench_function("grpc", |b| b.iter(|| grpc::Block::decode(grpc.as_slice()).unwrap()));
+    group.bench_function("json_rpc", |b| b.iter(|| serde_json::from_slice::<serde_json::Value>(&json_rpc).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, block_encoding, block_decoding);
+
+fn main() {
+    report_sizes();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
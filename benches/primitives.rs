@@ -0,0 +1,174 @@
+//! Benchmarks of primitives conversions that happen for every executed transaction.
+//!
+//! Run with `cargo bench --bench primitives`. Data sizes match the largest
+//! contract bytecode allowed by EIP-170 and a typical contract call input, so
+//! regressions that copy the underlying buffers show up as time proportional to
+//! the size.
+//!
+//! Before the benchmarks run, the number of heap allocations and allocated bytes
+//! of each conversion is printed, counted by a global allocator that wraps the
+//! system one, so copies can be compared directly instead of inferred from time.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::hint::black_box;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use criterion::criterion_group;
+use criterion::BatchSize;
+use criterion::Criterion;
+use fake::Fake;
+use fake::Faker;
+use stratus::alias::RevmAccountInfo;
+use stratus::alias::RevmBytecode;
+use stratus::alias::RevmBytes;
+use stratus::eth::primitives::Account;
+use stratus::eth::primitives::Address;
+use stratus::eth::primitives::Bytes;
+use stratus::eth::primitives::EvmInput;
+
+/// Max contract bytecode size (EIP-170).
+const BYTECODE_SIZE: usize = 24_576;
+
+/// Size of a contract call input with a few dynamic arguments.
+const INPUT_SIZE: usize = 1_024;
+
+// -----------------------------------------------------------------------------
+// Allocations
+// -----------------------------------------------------------------------------
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// System allocator that counts allocations and allocated bytes.
+///
+/// Reallocations are counted as new allocations of the new size, because growing a buffer may copy it.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Counts the allocations and allocated bytes of an operation.
+///
+/// The operation result is dropped only after counting, so its deallocation is not mixed with the next operation.
+fn count_allocations<T>(operation: impl FnOnce() -> T) -> (usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let result = black_box(operation());
+    let counted = (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    );
+    drop(result);
+    counted
+}
+
+/// Prints the allocations of each benchmarked conversion.
+///
+/// Inputs consumed by a conversion are cloned before counting, so only the conversion itself is measured.
+fn report_allocations() {
+    let bytecode = Bytes::from(vec![0x60; BYTECODE_SIZE]);
+    let revm_bytecode = RevmBytecode::from(bytecode.clone());
+
+    let mut account = Account::new_empty(Address::new([1; 20]));
+    account.bytecode = Some(bytecode.clone());
+
+    let mut input: EvmInput = Faker.fake();
+    input.data = Bytes::from(vec![0xab; INPUT_SIZE]);
+    input.blob_hashes.clear();
+    input.overlay = Default::default();
+
+    let to_revm_bytes = bytecode.clone();
+    let to_revm_bytecode = bytecode.clone();
+    let from_revm_bytecode = revm_bytecode.clone();
+    let measurements = [
+        ("bytes/clone", count_allocations(|| bytecode.clone())),
+        ("bytes/to_revm_bytes", count_allocations(|| RevmBytes::from(to_revm_bytes))),
+        ("bytes/to_revm_bytecode", count_allocations(|| RevmBytecode::from(to_revm_bytecode))),
+        ("bytes/from_revm_bytecode", count_allocations(|| Bytes::from(from_revm_bytecode))),
+        ("account/to_revm_account_info", count_allocations(|| RevmAccountInfo::from(&account))),
+        ("evm_input/clone", count_allocations(|| input.clone())),
+    ];
+
+    println!("{:<30} {:>12} {:>12}", "conversion", "allocations", "bytes");
+    for (name, (allocations, bytes)) in measurements {
+        println!("{:<30} {:>12} {:>12}", name, allocations, bytes);
+    }
+    println!();
+}
+
+// -----------------------------------------------------------------------------
+// Benchmarks
+// -----------------------------------------------------------------------------
+
+fn bytes_conversions(c: &mut Criterion) {
+    let bytecode = Bytes::from(vec![0x60; BYTECODE_SIZE]);
+
+    let mut group = c.benchmark_group("bytes");
+    group.bench_function("clone", |b| b.iter(|| bytecode.clone()));
+    group.bench_function("to_revm_bytes", |b| {
+        b.iter_batched(|| bytecode.clone(), RevmBytes::from, BatchSize::SmallInput);
+    });
+    group.bench_function("to_revm_bytecode", |b| {
+        b.iter_batched(|| bytecode.clone(), RevmBytecode::from, BatchSize::SmallInput);
+    });
+    group.bench_function("from_revm_bytecode", |b| {
+        let revm_bytecode = RevmBytecode::from(bytecode.clone());
+        b.iter_batched(|| revm_bytecode.clone(), Bytes::from, BatchSize::SmallInput);
+    });
+    group.finish();
+}
+
+fn account_conversions(c: &mut Criterion) {
+    let mut account = Account::new_empty(Address::new([1; 20]));
+    account.bytecode = Some(Bytes::from(vec![0x60; BYTECODE_SIZE]));
+
+    let mut group = c.benchmark_group("account");
+    group.bench_function("to_revm_account_info", |b| b.iter(|| RevmAccountInfo::from(&account)));
+    group.finish();
+}
+
+fn evm_input_clone(c: &mut Criterion) {
+    let mut input: EvmInput = Faker.fake();
+    input.data = Bytes::from(vec![0xab; INPUT_SIZE]);
+    input.blob_hashes.clear();
+    input.overlay = Default::default();
+
+    let mut group = c.benchmark_group("evm_input");
+    group.bench_function("clone", |b| b.iter(|| input.clone()));
+    group.finish();
+}
+
+criterion_group!(benches, bytes_conversions, account_conversions, evm_input_clone);
+
+fn main() {
+    report_allocations();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,90 @@
+//! Benchmarks of the permanent storage hot paths in each backend.
+//!
+//! Run with `cargo bench --bench storage`. RocksDB is created in a temporary
+//! directory and Redis is only benchmarked when the `BENCH_REDIS_URL`
+//! environment variable points to a server whose data can be overwritten.
+
+mod common;
+
+use std::env;
+use std::time::Duration;
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::Criterion;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::primitives::LogFilter;
+use stratus::eth::primitives::LogFilterInput;
+use stratus::eth::storage::permanent::RedisPermanentStorage;
+use stratus::eth::storage::permanent::RocksPermanentStorage;
+use stratus::eth::storage::InMemoryPermanentStorage;
+use stratus::eth::storage::PermanentStorage;
+use stratus::eth::storage::ReadStorage;
+use stratus::eth::storage::WriteStorage;
+use tempfile::TempDir;
+
+use crate::common::fake_blocks;
+
+/// Number of blocks saved before benchmarking reads.
+const BLOCKS_COUNT: u64 = 1_000;
+
+/// Number of blocks covered by the benchmarked log filter.
+const LOGS_BLOCK_RANGE: u64 = 100;
+
+/// Creates an empty storage for each available backend.
+fn storages() -> Vec<(Box<dyn PermanentStorage>, Option<TempDir>)> {
+    let dir = tempfile::tempdir().unwrap();
+    let prefix = dir.path().join("bench").to_string_lossy().into_owned();
+    let rocks = RocksPermanentStorage::new(Some(prefix), Duration::from_secs(240), None, false, false, false).unwrap();
+
+    let mut storages: Vec<(Box<dyn PermanentStorage>, Option<TempDir>)> =
+        vec![(Box::new(InMemoryPermanentStorage::default()), None), (Box::new(rocks), Some(dir))];
+
+    if let Ok(url) = env::var("BENCH_REDIS_URL") {
+        let redis = RedisPermanentStorage::new(&url, None, false).unwrap();
+        storages.push((Box::new(redis), None));
+    }
+
+    storages
+}
+
+fn save_block(c: &mut Criterion) {
+    let blocks = fake_blocks(BLOCKS_COUNT);
+
+    let mut group = c.benchmark_group("save_block");
+    for (storage, _dir) in storages() {
+        let mut blocks = blocks.iter().cycle();
+        group.bench_function(storage.kind().to_string(), |b| {
+            b.iter_batched(
+                || blocks.next().unwrap().clone(),
+                |block| storage.save_block(block).unwrap(),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn read_logs(c: &mut Criterion) {
+    let blocks = fake_blocks(BLOCKS_COUNT);
+    let filter = LogFilter {
+        from_block: BlockNumber::from(BLOCKS_COUNT - LOGS_BLOCK_RANGE),
+        to_block: Some(BlockNumber::from(BLOCKS_COUNT - 1)),
+        addresses: vec![],
+        original_input: LogFilterInput::default(),
+    };
+
+    let mut group = c.benchmark_group("read_logs");
+    for (storage, _dir) in storages() {
+        storage.save_block_batch(blocks.clone()).unwrap();
+        storage.set_mined_block_number(BlockNumber::from(BLOCKS_COUNT - 1)).unwrap();
+        group.bench_function(storage.kind().to_string(), |b| {
+            b.iter(|| storage.read_logs(&filter).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, save_block, read_logs);
+criterion_main!(benches);
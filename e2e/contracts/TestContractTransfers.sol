@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+contract TestContractTransfers {
+
+    mapping(address => uint256) public balances;
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+
+    /// @dev Create tokens for an account, emitting an ERC-20 transfer from the zero address.
+    function mint(address to, uint256 value) public {
+        balances[to] += value;
+        emit Transfer(address(0), to, value);
+    }
+
+    /// @dev Transfer tokens from the sender to an account, emitting an ERC-20 transfer.
+    /// @return If the transfer succeeded.
+    function transfer(address to, uint256 value) public returns (bool) {
+        require(balances[msg.sender] >= value, "Insufficient balance");
+
+        balances[msg.sender] -= value;
+        balances[to] += value;
+        emit Transfer(msg.sender, to, value);
+
+        return true;
+    }
+
+    /// @dev Forward the received value to an account, executing an internal transfer.
+    function forward(address payable to) public payable {
+        to.transfer(msg.value);
+    }
+}
@@ -0,0 +1,335 @@
+import { expect } from "chai";
+import { TransactionReceipt } from "ethers";
+
+import { TestContractTransfers } from "../../typechain-types";
+import { CHARLIE, DAVE, EVE, FERDIE } from "../helpers/account";
+import { isStratus } from "../helpers/network";
+import {
+    CHAIN_ID,
+    HASH_ZERO,
+    INVALID_PARAMS_CODE,
+    deployTestContractTransfers,
+    describeStratusMethods,
+    prepareSignedTx,
+    send,
+    sendAndGetError,
+    sendReset,
+    toHex,
+} from "../helpers/rpc";
+
+// Canonical address of the EntryPoint v0.6 contract, which is not enabled in the tested node.
+const ENTRY_POINT = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
+
+const USER_OPERATION = {
+    sender: DAVE.address,
+    nonce: "0x0",
+    initCode: "0x",
+    callData: "0x",
+    callGasLimit: "0x0",
+    verificationGasLimit: "0x0",
+    preVerificationGas: "0x0",
+    maxFeePerGas: "0x0",
+    maxPriorityFeePerGas: "0x0",
+    paymasterAndData: "0x",
+    signature: "0x",
+};
+
+const MINTED = 100;
+const TRANSFERRED = 40;
+const FORWARDED = 1000;
+
+describe("JSON-RPC Stratus methods", () => {
+    let contract: TestContractTransfers;
+    let contractAddress: string;
+    let deployHash: string;
+    let transferReceipt: TransactionReceipt;
+    let forwardReceipt: TransactionReceipt;
+
+    // mints and transfers tokens, and forwards value from DAVE to EVE through the contract
+    before(async () => {
+        if (isStratus) {
+            await sendReset();
+            contract = await deployTestContractTransfers();
+            contractAddress = (await contract.getAddress()).toLowerCase();
+            deployHash = contract.deploymentTransaction()!.hash;
+
+            const dave = contract.connect(DAVE.signer());
+            await (await dave.mint(DAVE.address, MINTED)).wait();
+            transferReceipt = (await (await dave.transfer(EVE.address, TRANSFERRED)).wait())!;
+            forwardReceipt = (await (await dave.forward(EVE.address, { value: FORWARDED })).wait())!;
+        }
+    });
+
+    describeStratusMethods("State", [
+        {
+            title: "returns contract slots in pages",
+            method: "stratus_getStorageRange",
+            params: () => [contractAddress, null, 1],
+            result: (result) => {
+                expect(result.slots).length(1);
+                expect(result.nextKey).to.be.a("string");
+            },
+        },
+        {
+            title: "rejects limits above the maximum",
+            method: "stratus_getStorageRange",
+            params: () => [contractAddress, null, 1025],
+            error: INVALID_PARAMS_CODE,
+        },
+        {
+            title: "returns the changes of a block",
+            method: "stratus_getStateDiff",
+            params: () => [toHex(transferReceipt.blockNumber)],
+            result: (result) => {
+                expect(result.blockNumber).eq(toHex(transferReceipt.blockNumber));
+                expect(result.stateDiff[contractAddress]).to.have.property("storage").that.is.not.empty;
+            },
+        },
+        {
+            title: "returns null for blocks not mined",
+            method: "stratus_getStateDiff",
+            params: () => ["0xffffff"],
+            result: (result) => expect(result).to.be.null,
+        },
+    ]);
+
+    describeStratusMethods("History", [
+        {
+            title: "returns the balance changes of an account",
+            method: "stratus_getBalanceHistory",
+            params: () => [EVE.address],
+            result: (result) => {
+                const change = result.changes.find((change: any) => change.transactionHash == forwardReceipt.hash);
+                expect(change.delta).eq(FORWARDED.toString());
+                expect(BigInt(change.balance) - BigInt(change.previousBalance)).eq(BigInt(FORWARDED));
+                expect(result.nextBlock).to.be.null;
+            },
+        },
+        {
+            title: "rejects limits above the maximum",
+            method: "stratus_getBalanceHistory",
+            params: () => [EVE.address, null, 1001],
+            error: INVALID_PARAMS_CODE,
+        },
+        {
+            title: "returns the internal transfers of a block",
+            method: "stratus_getInternalTransactions",
+            params: () => [toHex(forwardReceipt.blockNumber)],
+            result: (result) => {
+                const transfer = result.transfers.find((transfer: any) => transfer.depth == 1);
+                expect(transfer).to.include({
+                    transactionHash: forwardReceipt.hash,
+                    type: "call",
+                    from: contractAddress,
+                    to: EVE.address.toLowerCase(),
+                    value: toHex(FORWARDED),
+                });
+            },
+        },
+        {
+            title: "returns the internal transfers of an account",
+            method: "stratus_getInternalTransactions",
+            params: () => [EVE.address],
+            result: (result) => {
+                const transfer = result.transfers.find(
+                    (transfer: any) => transfer.transactionHash == forwardReceipt.hash,
+                );
+                expect(transfer.from).eq(contractAddress);
+                expect(transfer.value).eq(toHex(FORWARDED));
+            },
+        },
+        {
+            title: "returns the token transfers of an account",
+            method: "stratus_getTokenTransfers",
+            params: () => [{ account: EVE.address }],
+            result: (result) => {
+                expect(result.transfers).length(1);
+                expect(result.transfers[0]).to.include({
+                    transactionHash: transferReceipt.hash,
+                    standard: "erc20",
+                    token: contractAddress,
+                    from: DAVE.address.toLowerCase(),
+                    to: EVE.address.toLowerCase(),
+                    value: toHex(TRANSFERRED),
+                });
+            },
+        },
+        {
+            title: "returns the token transfers of a token",
+            method: "stratus_getTokenTransfers",
+            params: () => [{ token: contractAddress }],
+            result: (result) => {
+                expect(result.transfers.map((transfer: any) => transfer.value)).deep.eq([
+                    toHex(MINTED),
+                    toHex(TRANSFERRED),
+                ]);
+            },
+        },
+        {
+            title: "rejects filters without account or token",
+            method: "stratus_getTokenTransfers",
+            params: () => [{}],
+            error: INVALID_PARAMS_CODE,
+        },
+        {
+            title: "returns the transaction that deployed a contract",
+            method: "stratus_getContractCreation",
+            params: () => [contractAddress],
+            result: (result) => {
+                expect(result.contractAddress).eq(contractAddress);
+                expect(result.creator).eq(CHARLIE.address.toLowerCase());
+                expect(result.transactionHash).eq(deployHash);
+            },
+        },
+        {
+            title: "returns null for accounts that are not contracts",
+            method: "stratus_getContractCreation",
+            params: () => [FERDIE.address],
+            result: (result) => expect(result).to.be.null,
+        },
+        {
+            title: "returns the proof of a mined receipt",
+            method: "stratus_getReceiptProof",
+            params: () => [forwardReceipt.hash],
+            result: async (result) => {
+                const block = await send("eth_getBlockByNumber", [toHex(forwardReceipt.blockNumber), false]);
+                expect(result.transactionHash).eq(forwardReceipt.hash);
+                expect(result.blockHash).eq(block.hash);
+                expect(result.receiptsRoot).eq(block.receiptsRoot);
+                expect(result.proof).to.be.an("array").that.is.not.empty;
+            },
+        },
+        {
+            title: "returns null for unknown transactions",
+            method: "stratus_getReceiptProof",
+            params: () => [HASH_ZERO],
+            result: (result) => expect(result).to.be.null,
+        },
+    ]);
+
+    describeStratusMethods("Simulation", [
+        {
+            title: "executes transactions without mining them",
+            method: "stratus_simulateBundle",
+            params: async () => {
+                const signedTx = await prepareSignedTx({
+                    contract,
+                    account: DAVE,
+                    methodName: "transfer",
+                    methodParameters: [EVE.address, 1],
+                });
+                return [[signedTx]];
+            },
+            result: async (result) => {
+                expect(result).length(1);
+                expect(result[0].result).eq("success");
+                expect(result[0].stateDiff[contractAddress]).to.have.property("storage");
+                expect(await contract.balances(EVE.address)).eq(BigInt(TRANSFERRED));
+            },
+        },
+        {
+            title: "rejects bundles above the maximum size",
+            method: "stratus_simulateBundle",
+            params: async () => {
+                const signedTx = await prepareSignedTx({
+                    contract,
+                    account: DAVE,
+                    methodName: "transfer",
+                    methodParameters: [EVE.address, 1],
+                });
+                return [Array(101).fill(signedTx)];
+            },
+            error: INVALID_PARAMS_CODE,
+        },
+    ]);
+
+    describeStratusMethods("Node", [
+        {
+            title: "returns the chain identity",
+            method: "stratus_chainInfo",
+            result: async (result) => {
+                const genesis = await send("eth_getBlockByNumber", ["0x0", false]);
+                expect(result.chainId).eq(CHAIN_ID);
+                expect(result.genesisHash).eq(genesis.hash);
+                expect(result.hardfork).to.be.a("string");
+                expect(result.maxCodeSize).eq(24576);
+                expect(result.maxInitcodeSize).eq(49152);
+            },
+        },
+        {
+            title: "returns the running tasks",
+            method: "stratus_tasks",
+            result: (result) => {
+                expect(result).to.be.an("array").that.is.not.empty;
+                for (const task of result) {
+                    expect(task.name).to.be.a("string");
+                    expect(task.kind).to.be.oneOf(["async", "blocking", "thread"]);
+                }
+            },
+        },
+        {
+            title: "returns the gas used by contracts",
+            method: "stratus_gasStats",
+            result: (result) => {
+                expect(result.transactions).to.be.greaterThan(0);
+                expect(result.contracts.map((contract: any) => contract.address)).to.include(contractAddress);
+            },
+        },
+        {
+            title: "returns the state statistics",
+            method: "stratus_stateStats",
+            params: async () => {
+                // statistics are calculated periodically, so the first calculation may not have finished yet
+                for (let i = 0; i < 50 && (await sendAndGetError("stratus_stateStats")); i++) {
+                    await new Promise((resolve) => setTimeout(resolve, 100));
+                }
+                return [];
+            },
+            result: (result) => {
+                expect(result.accounts).to.be.greaterThan(0);
+                expect(result.contracts).to.be.an("array");
+            },
+        },
+        {
+            title: "returns the admin operations",
+            method: "stratus_getAuditLog",
+            params: () => [0, 1000],
+            result: (result) => {
+                expect(result.entries.map((entry: any) => entry.method)).to.include("stratus_reset");
+                expect(result.nextOffset).to.be.null;
+            },
+        },
+        {
+            title: "rejects limits above the maximum",
+            method: "stratus_getAuditLog",
+            params: () => [0, 1001],
+            error: INVALID_PARAMS_CODE,
+        },
+    ]);
+
+    describeStratusMethods("Account abstraction", [
+        {
+            title: "returns no entry points when none is enabled",
+            method: "eth_supportedEntryPoints",
+            result: (result) => expect(result).deep.eq([]),
+        },
+        {
+            title: "rejects entry points that are not enabled",
+            method: "eth_sendUserOperation",
+            params: () => [USER_OPERATION, ENTRY_POINT],
+            error: INVALID_PARAMS_CODE,
+        },
+        {
+            title: "rejects entry points that are not enabled",
+            method: "debug_simulateUserOperationValidation",
+            params: () => [USER_OPERATION, ENTRY_POINT],
+            error: INVALID_PARAMS_CODE,
+        },
+        {
+            title: "rejects entry points that are not enabled",
+            method: "stratus_pendingUserOperations",
+            params: () => [ENTRY_POINT],
+            error: INVALID_PARAMS_CODE,
+        },
+    ]);
+});
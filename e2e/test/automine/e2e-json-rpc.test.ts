@@ -43,6 +43,11 @@ describe("JSON-RPC", () => {
         it("eth_chainId", async () => {
             (await sendExpect("eth_chainId")).eq(CHAIN_ID);
         });
+        it("eth_coinbase", async () => {
+            if (isStratus) {
+                (await sendExpect("eth_coinbase")).eq("0x00000000000000000000000000000000000000ff");
+            }
+        });
         it("net_listening", async () => {
             (await sendExpect("net_listening")).eq(true);
         });
@@ -66,6 +71,12 @@ describe("JSON-RPC", () => {
                 gasPrice.not.eq(ZERO);
             }
         });
+        it("eth_maxPriorityFeePerGas", async () => {
+            let priorityFee = await sendExpect("eth_maxPriorityFeePerGas");
+            if (isStratus) {
+                priorityFee.eq(ZERO);
+            }
+        });
         it("eth_estimateGas", async () => {
             let tx = { from: ALICE.address, to: BOB.address, value: "0x1" };
             let gas = await send("eth_estimateGas", [tx]);
@@ -120,6 +131,13 @@ describe("JSON-RPC", () => {
                 let block = await send("eth_getBlockByNumber", [NON_EXISTANT_BLOCK, true]);
                 expect(block).to.be.null;
             });
+            it("fetches pending block on top of latest block", async () => {
+                const latest: Block = await send("eth_getBlockByNumber", ["latest", false]);
+                const pending: Block = await send("eth_getBlockByNumber", ["pending", false]);
+                expect(pending.hash).to.be.null;
+                expect(pending.parentHash).eq(latest.hash);
+                expect(Number(pending.number)).eq(Number(latest.number) + 1);
+            });
         });
         describe("eth_getBlockByHash", () => {
             it("fetches genesis block correctly", async () => {
@@ -218,6 +236,37 @@ describe("JSON-RPC", () => {
                 expect(currentAliceBalance).eq(expectedAliceBalance);
             });
         });
+
+        describe("stratus_callMany", () => {
+            it("applies the changes of each call to the next ones", async () => {
+                if (isStratus) {
+                    const contract = await deployTestContractBalances();
+                    const add = contract.interface.encodeFunctionData("add", [ALICE.address, 5]);
+                    const get = contract.interface.encodeFunctionData("get", [ALICE.address]);
+                    const calls = [{ to: contract.target, data: add }, { to: contract.target, data: get }];
+
+                    const results = await send("stratus_callMany", [calls, "latest"]);
+                    expect(results).length(2);
+                    expect(results[1].output).eq(toPaddedHex(5, 32));
+
+                    // state diff of the call that changed the contract storage
+                    const contractDiff = results[0].stateDiff[(contract.target as string).toLowerCase()];
+                    expect(contractDiff).to.have.property("storage").that.is.an("object").that.is.not.empty;
+                }
+            });
+            it("executes each call over the same state when changes are not applied", async () => {
+                if (isStratus) {
+                    const contract = await deployTestContractBalances();
+                    const add = contract.interface.encodeFunctionData("add", [ALICE.address, 5]);
+                    const get = contract.interface.encodeFunctionData("get", [ALICE.address]);
+                    const calls = [{ to: contract.target, data: add }, { to: contract.target, data: get }];
+
+                    const results = await send("stratus_callMany", [calls, "latest", false]);
+                    expect(results[0].stateDiff).to.not.be.empty;
+                    expect(results[1].output).eq(toPaddedHex(0, 32));
+                }
+            });
+        });
     });
 
     describe("Evm", () => {
@@ -20,6 +20,7 @@ import {
     TestContractBlockTimestamp,
     TestContractCounter,
     TestContractDenseStorage,
+    TestContractTransfers,
     TestEvmInput,
 } from "../../typechain-types";
 import { Account, CHARLIE } from "./account";
@@ -48,6 +49,9 @@ export const NATIVE_TRANSFER_GAS = "0x5208"; // 21000
 export const SUCCESS = 1;
 export const REVERSAL = 0;
 
+// JSON-RPC error codes
+export const INVALID_PARAMS_CODE = -32602;
+
 // Special hashes
 export const HASH_ZERO = ethers.ZeroHash;
 export const HASH_EMPTY_UNCLES = "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
@@ -152,6 +156,40 @@ export async function sendAndGetError(method: string, params: any[] = []): Promi
     return response.data.error;
 }
 
+// Case of a Stratus RPC method checked by `describeStratusMethods`.
+export interface StratusMethodCase {
+    // Describes what the case checks.
+    title: string;
+    method: string;
+    // Params are built when the test runs, so they can use values created by `before` hooks.
+    params?: () => any[] | Promise<any[]>;
+    // Checks the result of a successful call.
+    result?: (result: any) => unknown;
+    // Expected error code of a failed call.
+    error?: number;
+}
+
+// Generates one test for each case, calling the method and checking its result or error code.
+// Only runs in Stratus, because the methods do not exist in other networks.
+export function describeStratusMethods(title: string, cases: StratusMethodCase[]) {
+    describe(title, () => {
+        for (const testCase of cases) {
+            it(`${testCase.method} ${testCase.title}`, async () => {
+                if (isStratus) {
+                    const params = testCase.params ? await testCase.params() : [];
+                    const response = (await sendAndGetFullResponse(testCase.method, params)).data;
+                    if (testCase.error !== undefined) {
+                        expect(response.error?.code).eq(testCase.error, JSON.stringify(response));
+                    } else {
+                        expect(response.error, JSON.stringify(response.error)).to.be.undefined;
+                        await testCase.result?.(response.result);
+                    }
+                }
+            });
+        }
+    });
+}
+
 // Sends an RPC request to the blockchain and applies the expect function to the result.
 export async function sendExpect(method: string, params: any[] = []): Promise<Chai.Assertion> {
     return expect(await send(method, params));
@@ -187,6 +225,12 @@ export async function deployTestContractDenseStorage(): Promise<TestContractDens
     return await testContractFactory.connect(CHARLIE.signer()).deploy();
 }
 
+// Deploys the "TestContractTransfers" contract.
+export async function deployTestContractTransfers(): Promise<TestContractTransfers> {
+    const testContractFactory = await ethers.getContractFactory("TestContractTransfers");
+    return await testContractFactory.connect(CHARLIE.signer()).deploy();
+}
+
 // Converts a number to Blockchain hex representation (prefixed with 0x).
 export function toHex(number: number | bigint): string {
     return "0x" + number.toString(16);
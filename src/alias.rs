@@ -24,12 +24,12 @@ pub type EthersTransaction = ethers_core::types::Transaction;
 // -----------------------------------------------------------------------------
 // REVM
 // -----------------------------------------------------------------------------
-pub type RevmAccountInfo = revm::primitives::AccountInfo;
-pub type RevmAddress = revm::primitives::Address;
-pub type RevmB256 = revm::primitives::B256;
-pub type RevmBytecode = revm::primitives::Bytecode;
-pub type RevmBytes = revm::primitives::Bytes;
-pub type RevmLog = revm::primitives::Log;
-pub type RevmOutput = revm::primitives::Output;
-pub type RevmState = revm::primitives::State;
-pub type RevmU256 = revm::primitives::U256;
+pub type RevmAccountInfo = revm_primitives::AccountInfo;
+pub type RevmAddress = revm_primitives::Address;
+pub type RevmB256 = revm_primitives::B256;
+pub type RevmBytecode = revm_primitives::Bytecode;
+pub type RevmBytes = revm_primitives::Bytes;
+pub type RevmLog = revm_primitives::Log;
+pub type RevmOutput = revm_primitives::Output;
+pub type RevmState = revm_primitives::State;
+pub type RevmU256 = revm_primitives::U256;
@@ -0,0 +1,154 @@
+//! Block-Replayer binary.
+//!
+//! It reads mined blocks from Stratus storage, re-executes their transactions
+//! over the state of the previous block and compares the resulting account
+//! changes with the persisted state, reporting every divergence found. It is
+//! meant to validate EVM and storage refactors against real history.
+
+use std::sync::Arc;
+
+use itertools::Itertools;
+use stratus::config::BlockReplayerConfig;
+use stratus::eth::executor::replay_block;
+use stratus::eth::executor::BlockReplay;
+use stratus::eth::executor::Divergence;
+use stratus::eth::executor::Evm;
+use stratus::eth::primitives::Address;
+use stratus::eth::primitives::BlockFilter;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::primitives::Bytes;
+use stratus::eth::primitives::ExecutionAccountChanges;
+use stratus::eth::primitives::ExecutionChanges;
+use stratus::eth::primitives::PointInTime;
+use stratus::eth::storage::StorageReader;
+use stratus::eth::storage::StratusStorage;
+use stratus::ext::not;
+use stratus::infra::tracing::TracingExt;
+use stratus::log_and_err;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+
+/// Number of replayed blocks between progress logs.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<BlockReplayerConfig>::init();
+    let _runtime = global_services.runtime.enter();
+    run(global_services.config)
+}
+
+fn run(config: BlockReplayerConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "block-replayer";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // init services
+    let storage = config.storage.init()?;
+    let mut evm = Evm::new(Arc::clone(&storage), config.executor.clone());
+
+    // init block range
+    let block_start = BlockNumber::from(config.block_start.max(1));
+    let block_end = match config.block_end {
+        Some(end) => BlockNumber::from(end),
+        None => storage.read_mined_block_number()?,
+    };
+    if block_start > block_end {
+        return log_and_err!(payload = (block_start, block_end), "block start must not be greater than block end");
+    }
+    tracing::info!(%block_start, %block_end, "replaying blocks");
+
+    // replay blocks
+    let mut summary = Summary::default();
+    for number in block_start.as_u64()..=block_end.as_u64() {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            break;
+        }
+
+        let Some(block) = storage.read_block(BlockFilter::Number(number.into()))? else {
+            return log_and_err!(payload = number, "block not found in storage");
+        };
+
+        let BlockReplay { changes, mut divergences } = replay_block(&storage, &mut evm, &block, ExecutionChanges::default())?;
+
+        // compare modified values with the state persisted for the block
+        let point_in_time = PointInTime::MinedPast(block.number());
+        for (address, changes) in changes.iter().sorted_by_key(|(address, _)| **address) {
+            compare_account(&storage, *address, changes, point_in_time, &mut divergences)?;
+        }
+
+        summary.blocks += 1;
+        summary.transactions += block.transactions.len() as u64;
+        if not(divergences.is_empty()) {
+            summary.divergent_blocks += 1;
+            for divergence in divergences {
+                tracing::error!(
+                    block_number = %number,
+                    tx_hash = %divergence.tx_hash.or_empty(),
+                    field = %divergence.field,
+                    persisted = %divergence.persisted,
+                    replayed = %divergence.replayed,
+                    "replayed block diverged"
+                );
+            }
+        }
+
+        if number % PROGRESS_INTERVAL == 0 {
+            tracing::info!(%number, %block_end, divergent_blocks = %summary.divergent_blocks, "replayed blocks");
+        }
+    }
+
+    tracing::info!(blocks = %summary.blocks, transactions = %summary.transactions, divergent_blocks = %summary.divergent_blocks, "finished replaying blocks");
+    if summary.divergent_blocks > 0 {
+        return log_and_err!(payload = summary.divergent_blocks, "replayed blocks diverged from persisted ones");
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Comparison
+// -----------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Summary {
+    blocks: u64,
+    transactions: u64,
+    divergent_blocks: u64,
+}
+
+/// Compares the modified values of an account with its persisted state.
+fn compare_account(
+    storage: &StratusStorage,
+    address: Address,
+    changes: &ExecutionAccountChanges,
+    point_in_time: PointInTime,
+    divergences: &mut Vec<Divergence>,
+) -> anyhow::Result<()> {
+    let mut compare = |field: &str, persisted: String, replayed: String| {
+        if persisted != replayed {
+            divergences.push(Divergence::new(None, format!("{}.{}", address, field), persisted, replayed));
+        }
+    };
+
+    if changes.is_account_modified() {
+        let account = storage.read_account(address, point_in_time)?;
+        if let Some(nonce) = changes.nonce.take_modified_ref() {
+            compare("nonce", account.nonce.to_string(), nonce.to_string());
+        }
+        if let Some(balance) = changes.balance.take_modified_ref() {
+            compare("balance", account.balance.to_string(), balance.to_string());
+        }
+        if let Some(bytecode) = changes.bytecode.take_modified_ref() {
+            let to_string = |bytecode: Option<&Bytes>| bytecode.map(ToString::to_string).unwrap_or_else(|| "null".to_owned());
+            compare("bytecode", to_string(account.bytecode.as_ref()), to_string(bytecode.as_ref()));
+        }
+    }
+
+    for (index, slot) in changes.slots.iter().sorted_by_key(|(index, _)| **index) {
+        if let Some(slot) = slot.take_modified_ref() {
+            let persisted = storage.read_slot(address, *index, point_in_time)?;
+            compare(&format!("slot.{}", index), persisted.value.to_string(), slot.value.to_string());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Chain-Exporter binary.
+//!
+//! It reads mined blocks from Stratus storage and writes them to a file in the
+//! RLP chain format used by `geth export` and `erigon export`, so other clients
+//! and third-party tools can import Stratus history.
+//!
+//! Blocks mined by Stratus do not have a state root, so by default the export
+//! stops at the first of them instead of writing a state root that clients
+//! would fail to verify.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+
+use stratus::config::ChainExporterConfig;
+use stratus::eth::primitives::BlockFilter;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::storage::StorageReader;
+use stratus::ext::not;
+use stratus::log_and_err;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+
+/// Number of exported blocks between progress logs.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ChainExporterConfig>::init();
+    let _runtime = global_services.runtime.enter();
+    run(global_services.config)
+}
+
+fn run(config: ChainExporterConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "chain-exporter";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // init services
+    let storage = config.storage.init()?;
+
+    // init block range
+    let block_start = BlockNumber::from(config.block_start);
+    let block_end = match config.block_end {
+        Some(end) => BlockNumber::from(end),
+        None => storage.read_mined_block_number()?,
+    };
+    if block_start > block_end {
+        return log_and_err!(payload = (block_start, block_end), "block start must not be greater than block end");
+    }
+    tracing::info!(%block_start, %block_end, output = %config.output, "exporting blocks");
+
+    // export blocks
+    let file = match File::create(&config.output) {
+        Ok(file) => file,
+        Err(e) => return log_and_err!(reason = e, "failed to create chain export file"),
+    };
+    let mut writer = BufWriter::new(file);
+
+    for number in block_start.as_u64()..=block_end.as_u64() {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            break;
+        }
+
+        let Some(block) = storage.read_block(BlockFilter::Number(number.into()))? else {
+            return log_and_err!(payload = number, "block not found in storage");
+        };
+        // genesis is not imported by clients, they create it from their own genesis spec
+        let is_genesis = block.number().is_zero();
+        if not(is_genesis) && not(block.header.has_state_root()) && not(config.allow_missing_state_root) {
+            return log_and_err!(
+                payload = number,
+                "block was mined without a state root, use --allow-missing-state-root to export it with the empty trie root"
+            );
+        }
+        writer.write_all(&rlp::encode(&block))?;
+
+        if number % PROGRESS_INTERVAL == 0 {
+            tracing::info!(%number, %block_end, "exported blocks");
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
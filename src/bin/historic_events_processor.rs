@@ -6,22 +6,15 @@ use chrono::TimeZone;
 use chrono::Timelike;
 use indicatif::ProgressBar;
 use rocksdb::properties::ESTIMATE_NUM_KEYS;
+use stratus::eth::primitives::Block;
 use stratus::eth::storage::permanent::rocks::types::BlockRocksdb;
-use stratus::eth::storage::permanent::rocks::types::TransactionMinedRocksdb;
-use stratus::eth::storage::permanent::rocks::types::UnixTimeRocksdb;
 use stratus::eth::storage::permanent::rocks::RocksStorageState;
 use stratus::ledger::events::transaction_to_events;
-use stratus::ledger::events::AccountTransfers;
 use stratus::ledger::events::Event;
 
 /// Database timeout duration in seconds
 const TIMEOUT: Duration = Duration::from_secs(5);
 
-/// Converts a mined transaction from RocksDB to account transfer events
-fn transaction_mined_rocks_db_to_events(block_timestamp: UnixTimeRocksdb, tx: TransactionMinedRocksdb) -> Vec<AccountTransfers> {
-    transaction_to_events(block_timestamp.into(), std::borrow::Cow::Owned(tx.into()))
-}
-
 /// Returns total count of blocks and transactions from RocksDB state
 fn get_total_blocks_and_transactions(state: &RocksStorageState) -> (u64, u64) {
     let total_blocks = state
@@ -68,11 +61,12 @@ fn create_progress_bar(state: &RocksStorageState) -> (ProgressBar, ProgressBar)
 
 /// Processes all transactions in a block and returns their event strings
 fn process_block_events(block: BlockRocksdb) -> Vec<String> {
+    let block = Block::from(block);
     let timestamp = block.header.timestamp;
     block
         .transactions
         .into_iter()
-        .flat_map(|tx| transaction_mined_rocks_db_to_events(timestamp, tx))
+        .flat_map(|tx| transaction_to_events(timestamp, std::borrow::Cow::Owned(tx)))
         .map(|event| event.event_payload().unwrap())
         .collect()
 }
@@ -80,7 +74,7 @@ fn process_block_events(block: BlockRocksdb) -> Vec<String> {
 /// Main function that processes blockchain data and generates events
 fn main() -> Result<(), anyhow::Error> {
     tracing_subscriber::fmt::init();
-    let state = RocksStorageState::new("data/rocksdb".to_string(), TIMEOUT, Some(0.1), false).context("failed to create rocksdb state")?;
+    let state = RocksStorageState::new("data/rocksdb".to_string(), TIMEOUT, Some(0.1), false, false, false).context("failed to create rocksdb state")?;
 
     let (b_pb, tx_pb) = create_progress_bar(&state);
 
@@ -9,6 +9,7 @@
 //! arrive.
 
 use std::cmp::min;
+use std::collections::HashSet;
 use std::sync::mpsc;
 use std::sync::Arc;
 
@@ -17,6 +18,7 @@ use futures::StreamExt;
 use itertools::Itertools;
 use stratus::config::ImporterOfflineConfig;
 use stratus::eth::executor::Executor;
+use stratus::eth::external_rpc::ExternalBlockFixture;
 use stratus::eth::external_rpc::ExternalBlockWithReceipts;
 use stratus::eth::external_rpc::ExternalRpc;
 use stratus::eth::miner::Miner;
@@ -26,6 +28,7 @@ use stratus::eth::primitives::BlockNumber;
 use stratus::eth::primitives::ExternalReceipts;
 use stratus::eth::primitives::ExternalTransaction;
 use stratus::eth::storage::Storage;
+use stratus::eth::storage::StorageReader;
 use stratus::ext::spawn_named;
 use stratus::ext::spawn_thread;
 use stratus::log_and_err;
@@ -114,9 +117,14 @@ async fn run(config: ImporterOfflineConfig) -> anyhow::Result<()> {
         }
     });
 
+    let fixtures = FixturesExport {
+        blocks: config.export_fixtures.iter().copied().map(BlockNumber::from).collect(),
+        dir: config.export_fixtures_dir.clone(),
+    };
+
     let miner_clone = Arc::clone(&miner);
     spawn_thread("block-executor", || {
-        if let Err(e) = run_external_block_executor(executor, miner_clone, fetch_to_execute_rx, execute_to_save_tx) {
+        if let Err(e) = run_external_block_executor(executor, miner_clone, fixtures, fetch_to_execute_rx, execute_to_save_tx) {
             tracing::error!(reason = ?e, "'block-executor' task failed");
         }
     });
@@ -193,6 +201,7 @@ async fn run_rpc_block_fetcher(
 fn run_external_block_executor(
     executor: Arc<Executor>,
     miner: Arc<Miner>,
+    fixtures: FixturesExport,
     mut from_fetcher_rx: async_mpsc::Receiver<BlocksToExecute>,
     to_saver_tx: mpsc::SyncSender<BlocksToSave>,
 ) -> anyhow::Result<()> {
@@ -242,9 +251,17 @@ fn run_external_block_executor(
                 // fill missing transaction_type with `v`
                 block.transactions.iter_mut().for_each(ExternalTransaction::fill_missing_transaction_type);
 
+                // keep a copy of the external data of blocks that will be exported as fixtures
+                let fixture_data = fixtures.blocks.contains(&block.number()).then(|| (block.clone(), receipts.clone()));
+
                 // TODO: remove clone
                 executor.execute_external_block(block.clone(), ExternalReceipts::from(receipts))?;
                 let mined_block = miner.mine_external(block)?;
+
+                if let Some((block, receipts)) = fixture_data {
+                    let path = ExternalBlockFixture::new(block, receipts, &mined_block).save(&fixtures.dir)?;
+                    tracing::info!(parent: None, number = %mined_block.number(), path = %path.display(), "exported block fixture");
+                }
                 executed_batch.push(mined_block);
             }
 
@@ -269,6 +286,12 @@ fn run_external_block_executor(
     }
 }
 
+/// Blocks to be exported as test fixtures while they are imported.
+struct FixturesExport {
+    blocks: HashSet<BlockNumber>,
+    dir: String,
+}
+
 fn run_block_saver(miner: Arc<Miner>, from_executor_rx: mpsc::Receiver<BlocksToSave>) -> anyhow::Result<()> {
     const TASK_NAME: &str = "block-saver";
     let _timer = DropTimer::start("importer-offline::run_block_saver");
@@ -0,0 +1,41 @@
+//! Relayer binary.
+//!
+//! It relays the transactions mined by Stratus to a target chain with the same
+//! chain id, comparing the receipts of both chains and saving the transactions
+//! whose receipts differ (see `ExternalRelayer`).
+//!
+//! With `--reconcile`, relayed blocks are first reconciled with the blocks in
+//! Stratus, so blocks Stratus lost are relayed again when it mines them. Add
+//! `--reconcile-dry-run` to only report what the reconciliation would change.
+//!
+//! The relay status of recent blocks and the relay lag are served by the
+//! `stratus_relayerStatus` JSON-RPC method.
+
+use std::sync::Arc;
+
+use stratus::config::ExternalRelayerConfig;
+use stratus::eth::relayer::serve_relayer_rpc;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ExternalRelayerConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: ExternalRelayerConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "relayer";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // init services
+    let relayer = Arc::new(config.relayer.init().await?);
+
+    // reconcile before relaying, so blocks deleted by the reconciliation are relayed again
+    if config.relayer.reconcile {
+        relayer.reconcile().await?;
+    }
+
+    let server = serve_relayer_rpc(Arc::clone(&relayer), config.relayer.address);
+    tokio::try_join!(relayer.run(), server)?;
+    Ok(())
+}
@@ -121,6 +121,9 @@ async fn download_blocks(rpc_storage: Arc<dyn ExternalRpc>, chain: Arc<Blockchai
         }
     }
 
+    // write blocks still buffered by the storage
+    rpc_storage.flush().await?;
+
     tracing::info!("download finished");
     Ok(())
 }
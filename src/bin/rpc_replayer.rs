@@ -0,0 +1,116 @@
+//! RPC-Replayer binary.
+//!
+//! It reads requests captured by a node with `--rpc-capture-file`, sends them
+//! to another node and compares the responses with the captured ones,
+//! reporting every divergence found. It is meant to validate a new node
+//! version with real traffic before it receives production requests.
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::time::Instant;
+
+use anyhow::Context;
+use stratus::alias::JsonValue;
+use stratus::config::RpcReplayerConfig;
+use stratus::eth::rpc::CapturedRequest;
+use stratus::ext::not;
+use stratus::ext::to_json_string;
+use stratus::ext::to_json_value;
+use stratus::infra::BlockchainClient;
+use stratus::log_and_err;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+
+/// Number of replayed requests between progress logs.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<RpcReplayerConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: RpcReplayerConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "rpc-replayer";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // init services
+    let file = File::open(&config.input).with_context(|| format!("failed to open rpc capture file {}", config.input))?;
+    let client = BlockchainClient::new_http(&config.target_rpc, config.target_rpc_timeout).await?;
+    tracing::info!(input = %config.input, target_rpc = %config.target_rpc, "replaying captured requests");
+
+    // replay requests
+    let mut summary = Summary::default();
+    let mut first_timestamp = None;
+    let start = Instant::now();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            break;
+        }
+
+        let line = line.context("failed to read rpc capture file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: CapturedRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => return log_and_err!(reason = e, payload = index + 1, "failed to parse captured request"),
+        };
+        if not(config.methods.is_empty()) && not(config.methods.contains(&request.method)) {
+            continue;
+        }
+
+        // wait for the interval between the captured requests
+        if let Some(speed) = config.speed {
+            let first_timestamp = *first_timestamp.get_or_insert(request.timestamp);
+            let offset = (request.timestamp - first_timestamp).to_std().unwrap_or_default().div_f64(speed);
+            if let Some(wait) = offset.checked_sub(start.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        // replay and compare
+        let replayed = client.send_request(&request.method, request.params.clone()).await?;
+        let (captured_response, replayed_response) = match replayed {
+            Ok(result) => (response("result", request.result.as_ref()), response("result", Some(&result))),
+            Err(error) => (response("error", request.error.as_ref()), response("error", Some(&to_json_value(&error)))),
+        };
+        summary.requests += 1;
+        if captured_response != replayed_response {
+            summary.divergent_requests += 1;
+            tracing::error!(
+                method = %request.method,
+                params = %to_json_string(&request.params),
+                timestamp = %request.timestamp,
+                captured = %captured_response,
+                replayed = %replayed_response,
+                "replayed request diverged"
+            );
+        }
+
+        if summary.requests % PROGRESS_INTERVAL == 0 {
+            tracing::info!(requests = %summary.requests, divergent_requests = %summary.divergent_requests, "replayed requests");
+        }
+    }
+
+    tracing::info!(requests = %summary.requests, divergent_requests = %summary.divergent_requests, elapsed = ?start.elapsed(), "finished replaying requests");
+    if summary.divergent_requests > 0 {
+        return log_and_err!(payload = summary.divergent_requests, "replayed requests diverged from captured ones");
+    }
+    Ok(())
+}
+
+/// Formats the result or error of a response, so a success and a failure never compare as equal.
+fn response(kind: &str, value: Option<&JsonValue>) -> String {
+    match value {
+        Some(value) => format!("{}: {}", kind, to_json_string(value)),
+        None => format!("no {}", kind),
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    requests: u64,
+    divergent_requests: u64,
+}
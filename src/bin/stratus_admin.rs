@@ -0,0 +1,151 @@
+//! Stratus-Admin binary.
+//!
+//! It runs one-off operations over the RocksDB permanent storage of a node, like resetting the chain to a previous block or compacting the
+//! database. The storage is opened for writing, so the node that owns it must be stopped before running any command.
+
+use std::fs;
+use std::time::Duration;
+
+use itertools::Itertools;
+use serde_json::json;
+use stratus::config::AdminCommand;
+use stratus::config::StratusAdminConfig;
+use stratus::eth::primitives::Account;
+use stratus::eth::primitives::Address;
+use stratus::eth::primitives::BlockBundle;
+use stratus::eth::primitives::BlockFilter;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::primitives::BundleAccount;
+use stratus::eth::primitives::PointInTime;
+use stratus::eth::primitives::Slot;
+use stratus::eth::primitives::SlotIndex;
+use stratus::eth::storage::permanent::check_integrity;
+use stratus::eth::storage::permanent::RocksPermanentStorage;
+use stratus::eth::storage::ReadStorage;
+use stratus::eth::storage::WriteStorage;
+use stratus::ext::to_json_string_pretty;
+use stratus::log_and_err;
+use stratus::GlobalServices;
+
+/// Max time waiting for RocksDB background jobs when the storage is dropped.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(4 * 60);
+
+/// Number of slots read at once when dumping an account.
+const DUMP_SLOTS_PAGE_SIZE: usize = 10_000;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<StratusAdminConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: StratusAdminConfig) -> anyhow::Result<()> {
+    tracing::info!(command = ?config.command, "running admin command");
+    let storage = RocksPermanentStorage::new(config.rocks_path_prefix.clone(), SHUTDOWN_TIMEOUT, None, true, false, false)?;
+
+    match config.command {
+        AdminCommand::LatestBlock => latest_block(&storage),
+        AdminCommand::ResetToBlock { block_number } => reset_to_block(&storage, block_number.into()),
+        AdminCommand::RecomputeCounters => recompute_counters(&storage),
+        AdminCommand::DumpAccount { address } => dump_account(&storage, address),
+        AdminCommand::VerifyChain => check_integrity(&storage, u64::MAX, false),
+        AdminCommand::ExportBlockBundle { block_number, output } => export_block_bundle(&storage, block_number.into(), &output),
+        AdminCommand::Compact => {
+            storage.state.compact();
+            Ok(())
+        }
+    }
+}
+
+fn latest_block(storage: &RocksPermanentStorage) -> anyhow::Result<()> {
+    let mined_number = storage.read_mined_block_number()?;
+    let header = storage.read_block(BlockFilter::Latest)?.map(|block| block.header);
+    println!("{}", to_json_string_pretty(&json!({ "mined_number": mined_number, "header": header })));
+    Ok(())
+}
+
+fn reset_to_block(storage: &RocksPermanentStorage, number: BlockNumber) -> anyhow::Result<()> {
+    let mined_number = storage.read_mined_block_number()?;
+    if number > mined_number {
+        return log_and_err!(payload = (number, mined_number), "cannot reset to a block after the mined block");
+    }
+    if storage.read_block(BlockFilter::Number(number))?.is_none() {
+        return log_and_err!(payload = number, "cannot reset to a block that is not saved");
+    }
+
+    storage.reset_to_block(number)?;
+    tracing::info!(%number, previous_mined_number = %mined_number, "reset storage to block");
+    Ok(())
+}
+
+fn recompute_counters(storage: &RocksPermanentStorage) -> anyhow::Result<()> {
+    check_integrity(storage, 0, true)?;
+
+    let mined_number = storage.read_mined_block_number()?;
+    let count = storage.state.read_state_count()?;
+    let slots: u64 = count.contracts.values().map(|contract| contract.slots).sum();
+    let bytecode_size: u64 = count.contracts.values().map(|contract| contract.bytecode_size).sum();
+    println!(
+        "{}",
+        to_json_string_pretty(&json!({
+            "mined_number": mined_number,
+            "accounts": count.accounts,
+            "contracts": count.contracts.len(),
+            "slots": slots,
+            "bytecode_size": bytecode_size,
+        }))
+    );
+    Ok(())
+}
+
+fn dump_account(storage: &RocksPermanentStorage, address: Address) -> anyhow::Result<()> {
+    let Some(account) = storage.read_account(address, PointInTime::Mined)? else {
+        return log_and_err!(payload = address, "account not found");
+    };
+
+    // reads one extra slot per page to find where the next page starts
+    let mut slots = Vec::new();
+    let mut start = SlotIndex::ZERO;
+    loop {
+        let mut page = storage.state.read_slots_range(address, start, DUMP_SLOTS_PAGE_SIZE + 1)?;
+        let next = (page.len() > DUMP_SLOTS_PAGE_SIZE).then(|| page.pop()).flatten();
+        slots.extend(page);
+        match next {
+            Some(next) => start = next.index,
+            None => break,
+        }
+    }
+
+    println!("{}", to_json_string_pretty(&json!({ "account": account, "slots": slots })));
+    Ok(())
+}
+
+fn export_block_bundle(storage: &RocksPermanentStorage, number: BlockNumber, output: &str) -> anyhow::Result<()> {
+    let Some(parent) = number.prev() else {
+        return log_and_err!("cannot export the genesis block because it has no previous state");
+    };
+    let Some(block) = storage.read_block(BlockFilter::Number(number))? else {
+        return log_and_err!(payload = number, "block not found");
+    };
+    let Some(state_diff) = storage.read_state_diff(number)? else {
+        return log_and_err!(payload = number, "block state diff not recorded");
+    };
+
+    // the state diff contains every account and slot read by the block, so their values in the parent block are the exact prestate
+    let point_in_time = PointInTime::MinedPast(parent);
+    let mut prestate = Vec::with_capacity(state_diff.len());
+    for changes in &state_diff {
+        let address = changes.address;
+        let account = storage.read_account(address, point_in_time)?.unwrap_or_else(|| Account::new_empty(address));
+        let mut slots = Vec::with_capacity(changes.slots.len());
+        for index in changes.slots.keys().sorted() {
+            let slot = storage.read_slot(address, *index, point_in_time)?.unwrap_or_else(|| Slot::new_empty(*index));
+            slots.push(slot);
+        }
+        prestate.push(BundleAccount { account, slots });
+    }
+
+    let bundle = BlockBundle { block, prestate, state_diff };
+    fs::write(output, to_json_string_pretty(&bundle))?;
+    tracing::info!(%number, %output, accounts = %bundle.prestate.len(), transactions = %bundle.block.transactions.len(), "exported block bundle");
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Stratus-Archive binary.
+//!
+//! It opens the permanent storage of another node in read-only mode and serves the read RPC methods over its historical data. It does not import,
+//! mine or accept transactions, so it can be scaled independently of the leader and followers to answer historical queries.
+//!
+//! RocksDB is opened as a read-only instance, so blocks saved by the writer node after the archive started are not visible until it is restarted.
+
+use std::sync::Arc;
+
+use stratus::config::ArchiveConfig;
+use stratus::eth::rpc::serve_rpc;
+use stratus::ext::not;
+use stratus::infra::sentry::set_sentry_tag;
+use stratus::log_and_err;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+use stratus::NodeMode;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ArchiveConfig>::init();
+    GlobalState::set_node_mode(NodeMode::Archive);
+    GlobalState::set_transactions_enabled(false);
+    set_sentry_tag("chain_id", global_services.config.executor.executor_chain_id);
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: ArchiveConfig) -> anyhow::Result<()> {
+    if not(config.storage.perm_storage.perm_storage_read_only) {
+        return log_and_err!("archive nodes require a read-only permanent storage, enable --perm-storage-read-only");
+    }
+
+    // Init services
+    let storage = config.storage.init()?;
+    let miner = config.miner.init(Arc::clone(&storage)).await?;
+    let executor = config.executor.init(Arc::clone(&storage), Arc::clone(&miner));
+
+    // Init RPC server
+    serve_rpc(
+        // Services
+        storage.reader(),
+        executor,
+        miner,
+        None,
+        // Config
+        config.clone(),
+        config.rpc_server,
+        config.executor.executor_chain_id.into(),
+    )
+    .await?;
+
+    // Explicitly block the `main` thread to drop the storage.
+    drop(storage);
+
+    Ok(())
+}
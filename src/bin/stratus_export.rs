@@ -0,0 +1,40 @@
+//! Stratus-Export binary.
+//!
+//! It reads mined blocks from Stratus storage and writes transactions, logs and
+//! account diffs as Parquet files that can be loaded into data warehouses.
+//!
+//! Output is split in chunks of blocks, each chunk written to its own files, so
+//! an interrupted export can be resumed by running it again with the same
+//! parameters. Files are placed in a directory named after the schema version,
+//! so consumers are never surprised by columns changing.
+
+use stratus::config::StratusExportConfig;
+use stratus::eth::analytics::AnalyticsExporter;
+use stratus::eth::storage::StorageReader;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<StratusExportConfig>::init();
+    let _runtime = global_services.runtime.enter();
+    run(global_services.config)
+}
+
+fn run(config: StratusExportConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "stratus-export";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // init services
+    let storage = config.storage.init()?;
+    let exporter = AnalyticsExporter::new(storage.reader(), &config.output, config.chunk_size)?;
+
+    // init block range
+    let block_start = config.block_start;
+    let block_end = match config.block_end {
+        Some(end) => end,
+        None => storage.read_mined_block_number()?.as_u64(),
+    };
+    tracing::info!(%block_start, %block_end, output = %exporter.output().display(), chunk_size = %config.chunk_size, "exporting blocks");
+
+    exporter.export(TASK_NAME, block_start, block_end)
+}
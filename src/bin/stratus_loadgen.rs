@@ -0,0 +1,345 @@
+//! Stratus-Loadgen binary.
+//!
+//! It signs and submits a configurable mix of transactions (native transfers,
+//! ERC-20 transfers and contract deployments) at a target rate against a node
+//! RPC, reporting the achieved TPS and the latency percentiles of
+//! `eth_sendRawTransaction`, that only responds after the transaction is
+//! executed.
+//!
+//! Each account submits its transactions sequentially to keep its nonces in
+//! order, so the target rate is split between the accounts and more accounts
+//! are needed to reach higher rates.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use ethers_core::abi;
+use ethers_core::abi::Token;
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::Bytes as EthersBytes;
+use ethers_core::types::Signature as EthersSignature;
+use ethers_core::types::TransactionRequest;
+use ethers_core::types::H160;
+use ethers_core::types::U256;
+use ethers_core::types::U64;
+use ethers_core::utils::secret_key_to_address;
+use futures::future::join_all;
+use rand::Rng;
+use serde_json::json;
+use stratus::config::LoadgenConfig;
+use stratus::ext::spawn_named;
+use stratus::ext::to_json_value;
+use stratus::infra::BlockchainClient;
+use stratus::log_and_err;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+use tokio::time::Instant;
+
+/// Selector of ERC-20 `transfer(address,uint256)`.
+const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// Init code of a contract whose runtime code returns 42.
+const DEPLOY_INIT_CODE: &str = "0x600a600c600039600a6000f3602a60005260206000f3";
+
+const TRANSFER_GAS: u64 = 21_000;
+const ERC20_TRANSFER_GAS: u64 = 100_000;
+const DEPLOY_GAS: u64 = 200_000;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<LoadgenConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: LoadgenConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "stratus-loadgen";
+    let _timer = DropTimer::start(TASK_NAME);
+
+    // validate config
+    if config.tps == 0 {
+        return log_and_err!("tps must be greater than zero");
+    }
+    let mix = TransactionMix::new(&config)?;
+
+    // init services
+    let client = Arc::new(BlockchainClient::new_http(&config.target_rpc, config.target_rpc_timeout).await?);
+    let chain_id = fetch_chain_id(&client).await?;
+    let mut accounts = Vec::with_capacity(config.private_keys.len());
+    for private_key in &config.private_keys {
+        accounts.push(Account::new(private_key, &client).await?);
+    }
+    let recipients = accounts.iter().map(|account| account.address).collect::<Vec<_>>();
+
+    // submit transactions from all accounts in parallel
+    let interval = Duration::from_secs_f64(accounts.len() as f64 / config.tps as f64);
+    tracing::info!(target_rpc = %config.target_rpc, tps = %config.tps, accounts = %accounts.len(), duration = ?config.duration, "generating load");
+
+    let start = Instant::now();
+    let deadline = start + config.duration;
+    let mut tasks = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let submitter = Submitter {
+            client: Arc::clone(&client),
+            account,
+            chain_id,
+            gas_price: config.gas_price.into(),
+            erc20_address: config.erc20_address.map(Into::into),
+            recipients: recipients.clone(),
+            mix: mix.clone(),
+        };
+        tasks.push(spawn_named("loadgen::submitter", submitter.run(interval, deadline)));
+    }
+
+    let mut report = Report::default();
+    for result in join_all(tasks).await {
+        report.merge(result??);
+    }
+    report.log(start.elapsed());
+    Ok(())
+}
+
+async fn fetch_chain_id(client: &BlockchainClient) -> anyhow::Result<U64> {
+    match client.send_request("eth_chainId", None).await? {
+        Ok(chain_id) => serde_json::from_value(chain_id).context("failed to parse chain id"),
+        Err(e) => log_and_err!(reason = e, "failed to fetch chain id"),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Transactions
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+enum TransactionKind {
+    #[strum(to_string = "transfer")]
+    Transfer,
+
+    #[strum(to_string = "erc20")]
+    Erc20Transfer,
+
+    #[strum(to_string = "deploy")]
+    Deploy,
+}
+
+/// Weighted mix of transactions kinds to be submitted.
+#[derive(Debug, Clone)]
+struct TransactionMix {
+    weights: Vec<(TransactionKind, u32)>,
+    total_weight: u32,
+}
+
+impl TransactionMix {
+    fn new(config: &LoadgenConfig) -> anyhow::Result<Self> {
+        if config.erc20_weight > 0 && config.erc20_address.is_none() {
+            return log_and_err!("erc20 address must be configured when erc20 weight is not zero");
+        }
+
+        let weights = vec![
+            (TransactionKind::Transfer, config.transfer_weight),
+            (TransactionKind::Erc20Transfer, config.erc20_weight),
+            (TransactionKind::Deploy, config.deploy_weight),
+        ];
+        let total_weight = weights.iter().map(|(_, weight)| weight).sum();
+        if total_weight == 0 {
+            return log_and_err!("at least one transaction kind must have weight greater than zero");
+        }
+
+        Ok(Self { weights, total_weight })
+    }
+
+    /// Picks a random transaction kind proportionally to the weights.
+    fn pick(&self) -> TransactionKind {
+        let mut target = rand::thread_rng().gen_range(0..self.total_weight);
+        for (kind, weight) in &self.weights {
+            if target < *weight {
+                return *kind;
+            }
+            target -= weight;
+        }
+        unreachable!("target is always smaller than the total weight")
+    }
+}
+
+/// Account that signs transactions and tracks its next nonce.
+struct Account {
+    key: SigningKey,
+    address: H160,
+    nonce: U256,
+}
+
+impl Account {
+    async fn new(private_key: &str, client: &BlockchainClient) -> anyhow::Result<Self> {
+        let key_bytes = const_hex::decode(private_key.trim()).context("failed to decode private key")?;
+        let key = SigningKey::from_slice(&key_bytes).context("failed to parse private key")?;
+        let address = secret_key_to_address(&key);
+        let mut account = Self {
+            key,
+            address,
+            nonce: U256::zero(),
+        };
+        account.sync_nonce(client).await?;
+        Ok(account)
+    }
+
+    /// Reloads the next nonce from the node, used when a submitted transaction fails and the local sequence may be out of sync.
+    async fn sync_nonce(&mut self, client: &BlockchainClient) -> anyhow::Result<()> {
+        self.nonce = client.fetch_transaction_count(self.address.into(), None).await?.into();
+        Ok(())
+    }
+
+    /// Signs a transaction with EIP-155 replay protection and encodes it to be submitted.
+    fn sign(&self, tx: TypedTransaction, chain_id: U64) -> anyhow::Result<EthersBytes> {
+        let sighash = tx.sighash();
+        let (signature, recovery_id) = self.key.sign_prehash_recoverable(sighash.as_bytes()).context("failed to sign transaction")?;
+        let signature = EthersSignature {
+            r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+            s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+            v: recovery_id.to_byte() as u64 + 35 + chain_id.as_u64() * 2,
+        };
+        Ok(tx.rlp_signed(&signature))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Submitter
+// -----------------------------------------------------------------------------
+
+/// Submits transactions signed by a single account.
+struct Submitter {
+    client: Arc<BlockchainClient>,
+    account: Account,
+    chain_id: U64,
+    gas_price: U256,
+    erc20_address: Option<H160>,
+    recipients: Vec<H160>,
+    mix: TransactionMix,
+}
+
+impl Submitter {
+    async fn run(mut self, interval: Duration, deadline: Instant) -> anyhow::Result<Report> {
+        const TASK_NAME: &str = "loadgen::submitter";
+
+        let mut report = Report::default();
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if Instant::now() >= deadline || GlobalState::is_shutdown_warn(TASK_NAME) {
+                return Ok(report);
+            }
+
+            let kind = self.mix.pick();
+            let tx = self.account.sign(self.build_transaction(kind), self.chain_id)?;
+
+            let start = Instant::now();
+            let result = self.client.send_request("eth_sendRawTransaction", Some(json!([to_json_value(tx)]))).await?;
+            let latency = start.elapsed();
+
+            match result {
+                Ok(_) => {
+                    self.account.nonce += U256::one();
+                    report.succeeded(kind, latency);
+                }
+                Err(e) => {
+                    tracing::warn!(address = ?self.account.address, %kind, reason = ?e, "transaction failed");
+                    report.failed(kind);
+                    self.account.sync_nonce(&self.client).await?;
+                }
+            }
+        }
+    }
+
+    fn build_transaction(&self, kind: TransactionKind) -> TypedTransaction {
+        let request = TransactionRequest::new()
+            .from(self.account.address)
+            .nonce(self.account.nonce)
+            .gas_price(self.gas_price)
+            .chain_id(self.chain_id);
+
+        let recipient = self.recipients[rand::thread_rng().gen_range(0..self.recipients.len())];
+        let request = match kind {
+            TransactionKind::Transfer => request.to(recipient).value(U256::one()).gas(TRANSFER_GAS),
+            TransactionKind::Erc20Transfer => {
+                let erc20_address = self.erc20_address.expect("erc20 address is validated when the mix is created");
+                let mut data = ERC20_TRANSFER_SELECTOR.to_vec();
+                data.extend(abi::encode(&[Token::Address(recipient), Token::Uint(U256::one())]));
+                request.to(erc20_address).data(data).gas(ERC20_TRANSFER_GAS)
+            }
+            TransactionKind::Deploy => {
+                let init_code = const_hex::decode(DEPLOY_INIT_CODE).expect("init code is valid hex");
+                request.data(init_code).gas(DEPLOY_GAS)
+            }
+        };
+        TypedTransaction::Legacy(request)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Report
+// -----------------------------------------------------------------------------
+
+/// Results of the submitted transactions.
+#[derive(Default)]
+struct Report {
+    latencies: Vec<Duration>,
+    by_kind: HashMap<TransactionKind, KindReport>,
+}
+
+#[derive(Default)]
+struct KindReport {
+    succeeded: u64,
+    failed: u64,
+}
+
+impl Report {
+    fn succeeded(&mut self, kind: TransactionKind, latency: Duration) {
+        self.latencies.push(latency);
+        self.by_kind.entry(kind).or_default().succeeded += 1;
+    }
+
+    fn failed(&mut self, kind: TransactionKind) {
+        self.by_kind.entry(kind).or_default().failed += 1;
+    }
+
+    fn merge(&mut self, other: Report) {
+        self.latencies.extend(other.latencies);
+        for (kind, other) in other.by_kind {
+            let report = self.by_kind.entry(kind).or_default();
+            report.succeeded += other.succeeded;
+            report.failed += other.failed;
+        }
+    }
+
+    fn log(mut self, elapsed: Duration) {
+        self.latencies.sort_unstable();
+        let succeeded = self.latencies.len();
+        let failed = self.by_kind.values().map(|report| report.failed).sum::<u64>();
+        let tps = succeeded as f64 / elapsed.as_secs_f64();
+
+        for (kind, report) in &self.by_kind {
+            tracing::info!(%kind, succeeded = %report.succeeded, failed = %report.failed, "submitted transactions by kind");
+        }
+        tracing::info!(
+            %succeeded,
+            %failed,
+            tps = format_args!("{tps:.2}"),
+            p50 = ?percentile(&self.latencies, 0.50),
+            p90 = ?percentile(&self.latencies, 0.90),
+            p99 = ?percentile(&self.latencies, 0.99),
+            max = ?self.latencies.last().copied().unwrap_or_default(),
+            ?elapsed,
+            "finished generating load"
+        );
+    }
+}
+
+/// Returns the percentile of sorted latencies using the nearest-rank method.
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percentile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
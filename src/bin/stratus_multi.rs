@@ -0,0 +1,45 @@
+//! Stratus-Multi binary.
+//!
+//! It hosts multiple independent leader chains in a single process. Each chain has its own chain id, storage and RPC address, configured in a
+//! TOML file, while the Tokio runtime, tracing and metrics are shared by all chains.
+//!
+//! Each chain has its own state: shutting down a chain, changing its node mode or enabling and disabling its transactions and unknown clients
+//! through the admin RPC does not affect the others. Metrics are labeled with the chain name.
+
+use futures::future::join_all;
+use stratus::config::MultiChainConfig;
+use stratus::node;
+use stratus::ChainState;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+use stratus::NodeMode;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<MultiChainConfig>::init();
+    GlobalState::set_node_mode(NodeMode::Leader);
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: MultiChainConfig) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "stratus-multi";
+
+    let chains = config.chains()?;
+    tracing::info!(chains = ?chains.iter().map(|(name, _)| name).collect::<Vec<_>>(), "starting chains");
+
+    // a failing chain shuts down the others, so the process does not keep running partially
+    let tasks = chains.into_iter().map(|(name, chain_config)| async move {
+        tracing::info!(%name, chain_id = %chain_config.executor.executor_chain_id, address = %chain_config.rpc_server.rpc_address, "starting chain");
+        let chain = ChainState::new(&name, NodeMode::Leader, chain_config.common.unknown_client_enabled);
+        let result = chain.scope(node::run(chain_config)).await;
+        if let Err(ref e) = result {
+            tracing::error!(reason = ?e, %name, "chain failed");
+            GlobalState::shutdown_from(TASK_NAME, &format!("chain {} failed", name));
+        }
+        result
+    });
+
+    for result in join_all(tasks).await {
+        result?;
+    }
+    Ok(())
+}
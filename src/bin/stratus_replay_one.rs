@@ -0,0 +1,71 @@
+//! Stratus-Replay-One binary.
+//!
+//! It re-executes a single block bundle exported by `stratus-admin export-block-bundle` over an empty in-memory storage, reading all accounts
+//! and slots from the prestate included in the bundle, and compares the resulting account changes with the ones persisted when the block was
+//! mined. It makes a divergent block reproducible on any machine, without access to the storage of the node that mined it.
+
+use std::fs;
+use std::sync::Arc;
+
+use stratus::config::StratusReplayOneConfig;
+use stratus::eth::executor::compare_state_diff;
+use stratus::eth::executor::replay_block;
+use stratus::eth::executor::Evm;
+use stratus::eth::primitives::BlockBundle;
+use stratus::eth::storage::InMemoryPermanentStorage;
+use stratus::eth::storage::InMemoryTemporaryStorage;
+use stratus::eth::storage::StratusStorage;
+use stratus::ext::not;
+use stratus::infra::tracing::TracingExt;
+use stratus::log_and_err;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<StratusReplayOneConfig>::init();
+    let _runtime = global_services.runtime.enter();
+    run(global_services.config)
+}
+
+fn run(config: StratusReplayOneConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("stratus-replay-one");
+
+    // read bundle
+    let bundle: BlockBundle = match fs::read_to_string(&config.input).map(|json| serde_json::from_str(&json)) {
+        Ok(Ok(bundle)) => bundle,
+        Ok(Err(e)) => return log_and_err!(reason = e, payload = config.input, "failed to parse block bundle"),
+        Err(e) => return log_and_err!(reason = e, payload = config.input, "failed to read block bundle"),
+    };
+    let number = bundle.block.number();
+    tracing::info!(%number, accounts = %bundle.prestate.len(), transactions = %bundle.block.transactions.len(), "replaying block bundle");
+
+    // init services over an empty storage, so all state comes from the bundle
+    let storage = Arc::new(StratusStorage::new(
+        Box::new(InMemoryTemporaryStorage::new(number)),
+        Box::new(InMemoryPermanentStorage::default()),
+        None,
+    )?);
+    let mut evm = Evm::new(Arc::clone(&storage), config.executor.clone());
+
+    // replay block
+    let replay = replay_block(&storage, &mut evm, &bundle.block, bundle.prestate_changes())?;
+    let mut divergences = replay.divergences;
+    divergences.extend(compare_state_diff(&bundle.state_diff, &replay.changes));
+
+    if not(divergences.is_empty()) {
+        for divergence in &divergences {
+            tracing::error!(
+                block_number = %number,
+                tx_hash = %divergence.tx_hash.or_empty(),
+                field = %divergence.field,
+                persisted = %divergence.persisted,
+                replayed = %divergence.replayed,
+                "replayed block diverged"
+            );
+        }
+        return log_and_err!(payload = divergences.len(), "replayed block diverged from persisted one");
+    }
+
+    tracing::info!(%number, "replayed block matches persisted one");
+    Ok(())
+}
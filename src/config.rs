@@ -1,15 +1,21 @@
 //! Application configuration.
 
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::env;
+use std::fs;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
 use clap::ArgGroup;
 use clap::Parser;
 use display_json::DebugAsJson;
+use itertools::Itertools;
 use strum::VariantNames;
 use tokio::runtime::Builder;
 use tokio::runtime::Runtime;
@@ -19,14 +25,23 @@ use crate::eth::external_rpc::ExternalRpcConfig;
 use crate::eth::follower::importer::ImporterConfig;
 use crate::eth::miner::MinerConfig;
 use crate::eth::primitives::Address;
+#[cfg(feature = "postgres")]
+use crate::eth::relayer::ExternalRelayerServerConfig;
 use crate::eth::rpc::RpcServerConfig;
+use crate::eth::selector_registry::SelectorRegistryConfig;
+use crate::eth::storage::PermanentStorageKind;
 use crate::eth::storage::StorageConfig;
+use crate::ext::not;
 use crate::ext::parse_duration;
+use crate::infra::alerting::AlertingConfig;
 use crate::infra::build_info;
+use crate::infra::event_bus::EventBusConfig;
 use crate::infra::kafka::KafkaConfig;
 use crate::infra::metrics::MetricsConfig;
 use crate::infra::sentry::SentryConfig;
+use crate::infra::supervisor::SupervisorConfig;
 use crate::infra::tracing::TracingConfig;
+use crate::infra::watchdog::WatchdogConfig;
 
 /// Loads .env files according to the binary and environment.
 pub fn load_dotenv_file() {
@@ -105,9 +120,15 @@ pub struct CommonConfig {
     #[clap(flatten)]
     pub sentry: Option<SentryConfig>,
 
+    #[clap(flatten)]
+    pub alerting: Option<AlertingConfig>,
+
     #[clap(flatten)]
     pub metrics: MetricsConfig,
 
+    #[clap(flatten)]
+    pub selector_registry: SelectorRegistryConfig,
+
     /// Prevents clap from breaking when passing `nocapture` options in tests.
     #[arg(long = "nocapture")]
     pub nocapture: bool,
@@ -207,6 +228,15 @@ pub struct StratusConfig {
 
     #[clap(flatten)]
     pub kafka_config: Option<KafkaConfig>,
+
+    #[clap(flatten)]
+    pub event_bus: EventBusConfig,
+
+    #[clap(flatten)]
+    pub supervisor: SupervisorConfig,
+
+    #[clap(flatten)]
+    pub watchdog: WatchdogConfig,
 }
 
 impl WithCommonConfig for StratusConfig {
@@ -215,6 +245,155 @@ impl WithCommonConfig for StratusConfig {
     }
 }
 
+// -----------------------------------------------------------------------------
+// Config: Archive
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-archive` binary.
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct ArchiveConfig {
+    #[clap(flatten)]
+    pub rpc_server: RpcServerConfig,
+
+    #[clap(flatten)]
+    pub storage: StorageConfig,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[clap(flatten)]
+    pub miner: MinerConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for ArchiveConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: MultiChain
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-multi` binary.
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct MultiChainConfig {
+    /// TOML file with the chains hosted by the process.
+    ///
+    /// Each `[chains.<name>]` table sets the options of a chain using the names of the command line options, like `chain-id = 2008`. Options not set
+    /// in the table are read from the environment like in the `stratus` binary.
+    #[arg(long = "chains-file", env = "CHAINS_FILE")]
+    pub chains_file: String,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for MultiChainConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+impl MultiChainConfig {
+    /// Reads the chains file and parses the configuration of each chain, ordered by name.
+    ///
+    /// Only leader chains are supported, because the importer is shared by the whole process.
+    pub fn chains(&self) -> anyhow::Result<Vec<(String, StratusConfig)>> {
+        #[derive(serde::Deserialize)]
+        struct ChainsFile {
+            chains: BTreeMap<String, toml::Table>,
+        }
+
+        let content = fs::read_to_string(&self.chains_file).with_context(|| format!("failed to read chains file {}", self.chains_file))?;
+        let file: ChainsFile = toml::from_str(&content).with_context(|| format!("failed to parse chains file {}", self.chains_file))?;
+        if file.chains.is_empty() {
+            bail!("chains file {} does not declare any chain", self.chains_file);
+        }
+
+        let mut chains = Vec::with_capacity(file.chains.len());
+        for (name, options) in file.chains {
+            let args = chain_args(&name, options)?;
+            let config = StratusConfig::try_parse_from(args).with_context(|| format!("invalid options for chain {}", name))?;
+            chains.push((name, config));
+        }
+        check_chains_isolation(&chains)?;
+        Ok(chains)
+    }
+}
+
+/// Converts the options of a chain to command line arguments.
+fn chain_args(name: &str, options: toml::Table) -> anyhow::Result<Vec<String>> {
+    let mut args = vec![format!("stratus-{}", name), "--leader".to_owned()];
+    for (option, value) in options {
+        if ["leader", "follower", "fake-leader"].contains(&option.as_str()) {
+            bail!("chain {} sets option {}, but all chains run as leaders", name, option);
+        }
+        let value = match value {
+            toml::Value::Boolean(true) => {
+                args.push(format!("--{}", option));
+                continue;
+            }
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            toml::Value::Float(value) => value.to_string(),
+            toml::Value::Array(values) => values
+                .iter()
+                .map(|value| value.as_str().map(str::to_owned).unwrap_or_else(|| value.to_string()))
+                .join(","),
+            value => bail!("chain {} sets option {} to unsupported value {}", name, option, value),
+        };
+        args.push(format!("--{}={}", option, value));
+    }
+    Ok(args)
+}
+
+/// Checks chains do not share resources that must be exclusive to each chain.
+fn check_chains_isolation(chains: &[(String, StratusConfig)]) -> anyhow::Result<()> {
+    let mut chain_ids = HashSet::new();
+    let mut rpc_addresses = HashSet::new();
+    let mut storages = HashSet::new();
+    for (name, config) in chains {
+        if not(chain_ids.insert(config.executor.executor_chain_id)) {
+            bail!(
+                "chain {} uses chain id {} that is already used by another chain",
+                name,
+                config.executor.executor_chain_id
+            );
+        }
+        if not(rpc_addresses.insert(config.rpc_server.rpc_address)) {
+            bail!(
+                "chain {} uses RPC address {} that is already used by another chain",
+                name,
+                config.rpc_server.rpc_address
+            );
+        }
+
+        let perm = &config.storage.perm_storage;
+        let storage = match perm.perm_storage_kind {
+            PermanentStorageKind::InMemory => None,
+            PermanentStorageKind::Redis => Some(format!("redis:{}", perm.perm_storage_url.clone().unwrap_or_default())),
+            #[cfg(feature = "rocks")]
+            PermanentStorageKind::Rocks => Some(format!("rocks:{}", perm.rocks_path_prefix.clone().unwrap_or_default())),
+        };
+        if let Some(storage) = storage {
+            if not(storages.insert(storage)) {
+                bail!("chain {} uses a permanent storage that is already used by another chain", name);
+            }
+        }
+
+        if config.miner.block_timestamp_delta.is_some() {
+            bail!("chain {} sets block-timestamp-delta, but the block clock is shared by all chains", name);
+        }
+    }
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Config: RpcDownloader
 // -----------------------------------------------------------------------------
@@ -283,6 +462,14 @@ pub struct ImporterOfflineConfig {
     #[arg(short = 'b', long = "blocks-by-fetch", env = "BLOCKS_BY_FETCH", default_value = "10000")]
     pub blocks_by_fetch: usize,
 
+    /// Blocks exported as test fixtures after being imported.
+    #[arg(long = "export-fixtures", env = "EXPORT_FIXTURES", value_delimiter = ',')]
+    pub export_fixtures: Vec<u64>,
+
+    /// Directory where test fixtures are exported.
+    #[arg(long = "export-fixtures-dir", env = "EXPORT_FIXTURES_DIR", default_value = "tests/fixtures/snapshots")]
+    pub export_fixtures_dir: String,
+
     #[clap(flatten)]
     pub executor: ExecutorConfig,
 
@@ -307,28 +494,324 @@ impl WithCommonConfig for ImporterOfflineConfig {
 }
 
 // -----------------------------------------------------------------------------
-// Config: RocksRevertToBlockConfig
+// Config: BlockReplayer
 // -----------------------------------------------------------------------------
 
-#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
-pub struct RocksRevertToBlockConfig {
-    /// Block number to revert to.
-    #[arg(long = "block", env = "BLOCK")]
-    pub block_number: u64,
+/// Configuration for `block-replayer` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct BlockReplayerConfig {
+    /// Initial block number to be replayed. The genesis block has no previous state, so it is never replayed.
+    #[arg(long = "block-start", env = "BLOCK_START", default_value = "1")]
+    pub block_start: u64,
+
+    /// Final block number to be replayed. Defaults to the last mined block.
+    #[arg(long = "block-end", env = "BLOCK_END")]
+    pub block_end: Option<u64>,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[clap(flatten)]
+    pub storage: StorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for BlockReplayerConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: StratusReplayOne
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-replay-one` binary.
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct StratusReplayOneConfig {
+    /// File with a block bundle exported by `stratus-admin export-block-bundle`.
+    #[arg(short = 'i', long = "input", env = "INPUT")]
+    pub input: String,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for StratusReplayOneConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: RpcReplayer
+// -----------------------------------------------------------------------------
 
-    #[arg(long = "rocks-path-prefix", env = "ROCKS_PATH_PREFIX")]
+/// Configuration for `rpc-replayer` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct RpcReplayerConfig {
+    /// File with requests captured with `--rpc-capture-file`.
+    #[arg(short = 'i', long = "input", env = "INPUT")]
+    pub input: String,
+
+    /// RPC endpoint of the node where captured requests are replayed.
+    #[arg(long = "target-rpc", env = "TARGET_RPC")]
+    pub target_rpc: String,
+
+    /// Timeout for requests sent to the target node.
+    #[arg(long = "target-rpc-timeout", value_parser=parse_duration, env = "TARGET_RPC_TIMEOUT", default_value = "10s")]
+    pub target_rpc_timeout: Duration,
+
+    /// Methods to replay. Defaults to all captured methods.
+    #[arg(long = "methods", env = "METHODS", value_delimiter = ',')]
+    pub methods: Vec<String>,
+
+    /// Replays requests respecting the intervals between them, divided by this factor. Defaults to replaying them as fast as possible.
+    #[arg(long = "speed", env = "SPEED")]
+    pub speed: Option<f64>,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for RpcReplayerConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: Loadgen
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-loadgen` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct LoadgenConfig {
+    /// RPC endpoint of the node receiving the transactions.
+    #[arg(long = "target-rpc", env = "TARGET_RPC")]
+    pub target_rpc: String,
+
+    /// Timeout for requests sent to the target node.
+    #[arg(long = "target-rpc-timeout", value_parser=parse_duration, env = "TARGET_RPC_TIMEOUT", default_value = "10s")]
+    pub target_rpc_timeout: Duration,
+
+    /// Private keys of funded accounts that sign the transactions. Each account submits its transactions sequentially.
+    #[arg(long = "private-keys", env = "PRIVATE_KEYS", value_delimiter = ',', required = true)]
+    #[serde(skip)]
+    pub private_keys: Vec<String>,
+
+    /// Target number of transactions submitted per second by all accounts.
+    #[arg(long = "tps", env = "TPS", default_value = "100")]
+    pub tps: u64,
+
+    /// How long transactions are submitted.
+    #[arg(long = "duration", value_parser=parse_duration, env = "DURATION", default_value = "60s")]
+    pub duration: Duration,
+
+    /// Relative weight of native transfers in the transaction mix.
+    #[arg(long = "transfer-weight", env = "TRANSFER_WEIGHT", default_value = "100")]
+    pub transfer_weight: u32,
+
+    /// Relative weight of ERC-20 transfers in the transaction mix.
+    #[arg(long = "erc20-weight", env = "ERC20_WEIGHT", default_value = "0")]
+    pub erc20_weight: u32,
+
+    /// Relative weight of contract deployments in the transaction mix.
+    #[arg(long = "deploy-weight", env = "DEPLOY_WEIGHT", default_value = "0")]
+    pub deploy_weight: u32,
+
+    /// ERC-20 contract called by ERC-20 transfers. The accounts must hold balance of the token.
+    #[arg(long = "erc20-address", env = "ERC20_ADDRESS")]
+    pub erc20_address: Option<Address>,
+
+    /// Gas price of the submitted transactions.
+    #[arg(long = "gas-price", env = "GAS_PRICE", default_value = "0")]
+    pub gas_price: u64,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for LoadgenConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: ChainExporter
+// -----------------------------------------------------------------------------
+
+/// Configuration for `chain-exporter` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct ChainExporterConfig {
+    /// Initial block number to be exported.
+    #[arg(long = "block-start", env = "BLOCK_START", default_value = "0")]
+    pub block_start: u64,
+
+    /// Final block number to be exported. Defaults to the last mined block.
+    #[arg(long = "block-end", env = "BLOCK_END")]
+    pub block_end: Option<u64>,
+
+    /// File where RLP encoded blocks are written.
+    #[arg(short = 'o', long = "output", env = "OUTPUT")]
+    pub output: String,
+
+    /// Exports blocks mined without a state root, encoding the empty trie root presented in JSON-RPC.
+    ///
+    /// Clients that execute imported blocks reject them, so it is only useful for tools that read the blocks without verifying the state.
+    #[arg(long = "allow-missing-state-root", env = "ALLOW_MISSING_STATE_ROOT", default_value = "false")]
+    pub allow_missing_state_root: bool,
+
+    #[clap(flatten)]
+    pub storage: StorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for ChainExporterConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: StratusExport
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-export` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct StratusExportConfig {
+    /// Initial block number to be exported.
+    #[arg(long = "block-start", env = "BLOCK_START", default_value = "0")]
+    pub block_start: u64,
+
+    /// Final block number to be exported. Defaults to the last mined block.
+    #[arg(long = "block-end", env = "BLOCK_END")]
+    pub block_end: Option<u64>,
+
+    /// Directory where Parquet files are written.
+    #[arg(short = 'o', long = "output", env = "OUTPUT")]
+    pub output: String,
+
+    /// Number of blocks exported to each file.
+    #[arg(long = "chunk-size", env = "CHUNK_SIZE", default_value = "10000")]
+    pub chunk_size: u64,
+
+    #[clap(flatten)]
+    pub storage: StorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for StratusExportConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: ExternalRelayer
+// -----------------------------------------------------------------------------
+
+/// Configuration for `relayer` binary.
+#[cfg(feature = "postgres")]
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct ExternalRelayerConfig {
+    #[clap(flatten)]
+    pub relayer: ExternalRelayerServerConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+#[cfg(feature = "postgres")]
+impl WithCommonConfig for ExternalRelayerConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: StratusAdmin
+// -----------------------------------------------------------------------------
+
+/// Configuration for `stratus-admin` binary.
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct StratusAdminConfig {
+    #[command(subcommand)]
+    pub command: AdminCommand,
+
+    /// RocksDB storage path prefix of the administered instance.
+    #[arg(long = "rocks-path-prefix", env = "ROCKS_PATH_PREFIX", global = true)]
     pub rocks_path_prefix: Option<String>,
 
+    #[deref]
     #[clap(flatten)]
     pub common: CommonConfig,
 }
 
-impl WithCommonConfig for RocksRevertToBlockConfig {
+impl WithCommonConfig for StratusAdminConfig {
     fn common(&self) -> &CommonConfig {
         &self.common
     }
 }
 
+/// Operation executed by `stratus-admin`.
+#[derive(Debug, Clone, clap::Subcommand, serde::Serialize)]
+pub enum AdminCommand {
+    /// Shows the mined block number and the header of the latest block.
+    LatestBlock,
+
+    /// Removes all blocks after a block, restoring accounts and slots to the values they had at it.
+    ResetToBlock {
+        /// Block number to reset to.
+        #[arg(long = "block")]
+        block_number: u64,
+    },
+
+    /// Sets the mined block number to the last saved block and counts current accounts and slots.
+    RecomputeCounters,
+
+    /// Shows an account with all its current slots.
+    DumpAccount {
+        /// Address of the account.
+        #[arg(long = "address")]
+        address: Address,
+    },
+
+    /// Checks that all saved blocks are chained by their parent hashes.
+    VerifyChain,
+
+    /// Exports a block with the accounts and slots it reads, so it can be re-executed in isolation by `stratus-replay-one`.
+    ExportBlockBundle {
+        /// Block number to export.
+        #[arg(long = "block")]
+        block_number: u64,
+
+        /// File where the bundle is written.
+        #[arg(long = "output")]
+        output: String,
+    },
+
+    /// Compacts all column families, reclaiming space of deleted and overwritten values.
+    Compact,
+}
+
 // -----------------------------------------------------------------------------
 // Config: Test
 // -----------------------------------------------------------------------------
@@ -416,3 +899,41 @@ impl FromStr for ValidatorMethodConfig {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_args() {
+        let options: toml::Table = toml::from_str(
+            r#"
+            chain-id = 2008
+            address = "0.0.0.0:3001"
+            perm-storage-token-transfers = true
+            rpc-allowed-methods = ["eth_call", "eth_chainId"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            chain_args("app", options).unwrap(),
+            [
+                "stratus-app",
+                "--leader",
+                "--address=0.0.0.0:3001",
+                "--chain-id=2008",
+                "--perm-storage-token-transfers",
+                "--rpc-allowed-methods=eth_call,eth_chainId",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chain_args_rejects_other_modes() {
+        let options: toml::Table = toml::from_str("follower = true").unwrap();
+        assert!(chain_args("app", options).is_err());
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use arrow_array::ArrayRef;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_array::UInt64Array;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::storage::StorageReader;
+use crate::ext::to_json_string_pretty;
+use crate::infra::parquet::write_parquet;
+use crate::log_and_err;
+use crate::GlobalState;
+
+/// Version of the exported files schema. Must be incremented when columns change.
+pub const EXPORT_SCHEMA_VERSION: u32 = 2;
+
+/// Exports transactions, logs and account diffs of mined blocks as Parquet files that can be loaded into data warehouses.
+///
+/// Output is split in chunks of blocks, each chunk written to its own files, so an interrupted export can be resumed by running it again
+/// with the same parameters. Files are placed in a directory named after the schema version, so consumers are never surprised by columns
+/// changing.
+pub struct AnalyticsExporter {
+    storage: Arc<dyn StorageReader>,
+    output: PathBuf,
+    chunk_size: u64,
+}
+
+impl AnalyticsExporter {
+    /// Creates a new [`AnalyticsExporter`] writing to a subdirectory of the specified directory named after the schema version.
+    pub fn new(storage: Arc<dyn StorageReader>, output: impl AsRef<Path>, chunk_size: u64) -> anyhow::Result<Self> {
+        if chunk_size == 0 {
+            return log_and_err!("chunk size must be greater than zero");
+        }
+
+        let output = output.as_ref().join(format!("v{}", EXPORT_SCHEMA_VERSION));
+        for table in Table::ALL {
+            fs::create_dir_all(output.join(table.name()))?;
+        }
+        write_schema(&output)?;
+
+        Ok(Self { storage, output, chunk_size })
+    }
+
+    /// Directory where files are written.
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// Exports the blocks in the range, skipping chunks exported by a previous run.
+    pub fn export(&self, task_name: &str, block_start: u64, block_end: u64) -> anyhow::Result<()> {
+        if block_start > block_end {
+            return log_and_err!(payload = (block_start, block_end), "block start must not be greater than block end");
+        }
+
+        // export chunks aligned to chunk size, so resumed exports produce the same files
+        let mut chunk_start = block_start;
+        while chunk_start <= block_end {
+            if GlobalState::is_shutdown_warn(task_name) {
+                break;
+            }
+
+            let chunk_end = (chunk_start - chunk_start % self.chunk_size).saturating_add(self.chunk_size - 1).min(block_end);
+            let chunk = Chunk::new(&self.output, chunk_start, chunk_end);
+            if chunk.is_exported() {
+                tracing::info!(%chunk_start, %chunk_end, "chunk already exported, skipping");
+            } else {
+                tracing::info!(%chunk_start, %chunk_end, "exporting chunk");
+                let mut blocks = Vec::new();
+                for number in chunk_start..=chunk_end {
+                    let Some(block) = self.storage.read_block(BlockFilter::Number(BlockNumber::from(number)))? else {
+                        return log_and_err!(payload = number, "block not found in storage");
+                    };
+                    blocks.push(block);
+                }
+                chunk.export(&blocks)?;
+            }
+
+            chunk_start = match chunk_end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the description of all exported tables.
+fn write_schema(output: &Path) -> anyhow::Result<()> {
+    let tables = Table::ALL
+        .iter()
+        .map(|table| {
+            let columns = table
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| serde_json::json!({"name": field.name(), "type": field.data_type().to_string(), "nullable": field.is_nullable()}))
+                .collect::<Vec<_>>();
+            (table.name().to_owned(), serde_json::Value::Array(columns))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let schema = serde_json::json!({ "version": EXPORT_SCHEMA_VERSION, "format": "parquet", "tables": tables });
+    fs::write(output.join("schema.json"), to_json_string_pretty(&schema))?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Tables
+// -----------------------------------------------------------------------------
+
+/// Exported table.
+#[derive(Debug, Clone, Copy)]
+pub enum Table {
+    Transactions,
+    Logs,
+    AccountDiffs,
+}
+
+impl Table {
+    pub const ALL: [Table; 3] = [Table::Transactions, Table::Logs, Table::AccountDiffs];
+
+    /// Name of the directory where the table files are written.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Table::Transactions => "transactions",
+            Table::Logs => "logs",
+            Table::AccountDiffs => "account_diffs",
+        }
+    }
+
+    /// Columns of the table files.
+    pub fn schema(&self) -> Arc<Schema> {
+        let number = |name: &str| Field::new(name, DataType::UInt64, false);
+        let text = |name: &str| Field::new(name, DataType::Utf8, false);
+        let optional_text = |name: &str| Field::new(name, DataType::Utf8, true);
+
+        let fields = match self {
+            Table::Transactions => vec![
+                number("block_number"),
+                text("block_hash"),
+                number("block_timestamp"),
+                number("transaction_index"),
+                text("transaction_hash"),
+                text("from"),
+                optional_text("to"),
+                number("nonce"),
+                text("value"),
+                number("gas_limit"),
+                text("gas_price"),
+                number("gas_used"),
+                text("input"),
+                text("output"),
+                text("result"),
+                optional_text("deployed_contract_address"),
+            ],
+            Table::Logs => vec![
+                number("block_number"),
+                text("block_hash"),
+                number("transaction_index"),
+                text("transaction_hash"),
+                number("log_index"),
+                text("address"),
+                optional_text("topic0"),
+                optional_text("topic1"),
+                optional_text("topic2"),
+                optional_text("topic3"),
+                text("data"),
+            ],
+            Table::AccountDiffs => vec![
+                number("block_number"),
+                number("transaction_index"),
+                text("transaction_hash"),
+                text("address"),
+                text("field"),
+                optional_text("slot_index"),
+                optional_text("original_value"),
+                text("modified_value"),
+            ],
+        };
+        Arc::new(Schema::new(fields))
+    }
+
+    /// Path of the file with the table rows of a chunk.
+    pub fn chunk_path(&self, output: &Path, start: u64, end: u64) -> PathBuf {
+        output.join(self.name()).join(format!("{:012}-{:012}.parquet", start, end))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Chunk
+// -----------------------------------------------------------------------------
+
+/// Range of blocks exported to the same set of files.
+struct Chunk {
+    transactions: PathBuf,
+    logs: PathBuf,
+    account_diffs: PathBuf,
+}
+
+impl Chunk {
+    fn new(output: &Path, start: u64, end: u64) -> Self {
+        Self {
+            transactions: Table::Transactions.chunk_path(output, start, end),
+            logs: Table::Logs.chunk_path(output, start, end),
+            account_diffs: Table::AccountDiffs.chunk_path(output, start, end),
+        }
+    }
+
+    fn files(&self) -> [&PathBuf; 3] {
+        [&self.transactions, &self.logs, &self.account_diffs]
+    }
+
+    fn is_exported(&self) -> bool {
+        self.files().iter().all(|path| path.exists())
+    }
+
+    /// Writes all files to temporary paths and only moves them to their final paths when all of them are complete.
+    fn export(&self, blocks: &[Block]) -> anyhow::Result<()> {
+        let mut transactions = TableWriter::new(&self.transactions, Table::Transactions);
+        let mut logs = TableWriter::new(&self.logs, Table::Logs);
+        let mut account_diffs = TableWriter::new(&self.account_diffs, Table::AccountDiffs);
+
+        for block in blocks {
+            for tx in &block.transactions {
+                let input = &tx.input;
+                let execution = &tx.execution;
+                transactions.write_row(vec![
+                    block.number().as_u64().into(),
+                    text(block.hash()),
+                    (*block.header.timestamp).into(),
+                    tx.transaction_index.0.into(),
+                    text(input.hash),
+                    text(input.signer),
+                    optional(input.to),
+                    input.nonce.as_u64().into(),
+                    text(input.value),
+                    input.gas_limit.as_u64().into(),
+                    text(input.gas_price),
+                    execution.gas.as_u64().into(),
+                    text(const_hex::encode_prefixed(&input.input.0)),
+                    text(const_hex::encode_prefixed(&execution.output.0)),
+                    text(&execution.result),
+                    optional(execution.contract_address()),
+                ]);
+
+                for log in &tx.logs {
+                    let [topic0, topic1, topic2, topic3] = log.log.topics();
+                    logs.write_row(vec![
+                        log.block_number.as_u64().into(),
+                        text(log.block_hash),
+                        log.transaction_index.0.into(),
+                        text(log.transaction_hash),
+                        log.log_index.0.into(),
+                        text(log.log.address),
+                        optional(topic0),
+                        optional(topic1),
+                        optional(topic2),
+                        optional(topic3),
+                        text(const_hex::encode_prefixed(&log.log.data.0)),
+                    ]);
+                }
+
+                for changes in execution.changes.values() {
+                    let mut write_diff = |field: &str, slot_index: Value, original: Value, modified: Value| {
+                        account_diffs.write_row(vec![
+                            block.number().as_u64().into(),
+                            tx.transaction_index.0.into(),
+                            text(input.hash),
+                            text(changes.address),
+                            text(field),
+                            slot_index,
+                            original,
+                            modified,
+                        ]);
+                    };
+
+                    if let Some(modified) = changes.nonce.take_modified_ref() {
+                        write_diff("nonce", Value::Null, optional(changes.nonce.take_original_ref()), text(modified));
+                    }
+                    if let Some(modified) = changes.balance.take_modified_ref() {
+                        write_diff("balance", Value::Null, optional(changes.balance.take_original_ref()), text(modified));
+                    }
+                    if let Some(Some(modified)) = changes.bytecode.take_modified_ref() {
+                        write_diff("bytecode", Value::Null, Value::Null, text(const_hex::encode_prefixed(&modified.0)));
+                    }
+                    for slot in changes.slots.values() {
+                        if let Some(modified) = slot.take_modified_ref() {
+                            let original = optional(slot.take_original_ref().map(|slot| slot.value));
+                            write_diff("slot", text(modified.index), original, text(modified.value));
+                        }
+                    }
+                }
+            }
+        }
+
+        let tmp_paths = [transactions.finish()?, logs.finish()?, account_diffs.finish()?];
+        for (tmp_path, path) in tmp_paths.iter().zip(self.files()) {
+            fs::rename(tmp_path, path)?;
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Parquet
+// -----------------------------------------------------------------------------
+
+/// Value of a column of an exported row.
+enum Value {
+    Number(u64),
+    Text(String),
+    Null,
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::Number(value)
+    }
+}
+
+/// Formats a value as text.
+fn text<T: Display>(value: T) -> Value {
+    Value::Text(value.to_string())
+}
+
+/// Formats an optional value as text or null when not present.
+fn optional<T: Display>(value: Option<T>) -> Value {
+    value.map(text).unwrap_or(Value::Null)
+}
+
+/// Buffers the rows of a table and writes them as a Parquet file to a temporary path.
+struct TableWriter {
+    tmp_path: PathBuf,
+    schema: Arc<Schema>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl TableWriter {
+    fn new(path: &Path, table: Table) -> Self {
+        Self {
+            tmp_path: path.with_extension("parquet.tmp"),
+            schema: table.schema(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn write_row(&mut self, values: Vec<Value>) {
+        debug_assert_eq!(values.len(), self.schema.fields().len());
+        self.rows.push(values);
+    }
+
+    /// Writes the file and returns its temporary path.
+    fn finish(self) -> anyhow::Result<PathBuf> {
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(self.schema.fields().len());
+        for (index, field) in self.schema.fields().iter().enumerate() {
+            let values = self.rows.iter().map(|row| &row[index]);
+            let column: ArrayRef = match field.data_type() {
+                DataType::UInt64 => Arc::new(
+                    values
+                        .map(|value| match value {
+                            Value::Number(number) => Ok(Some(*number)),
+                            Value::Null => Ok(None),
+                            Value::Text(_) => Err(anyhow!("column {} expects a number", field.name())),
+                        })
+                        .collect::<anyhow::Result<UInt64Array>>()?,
+                ),
+                DataType::Utf8 => Arc::new(
+                    values
+                        .map(|value| match value {
+                            Value::Text(text) => Ok(Some(text.as_str())),
+                            Value::Null => Ok(None),
+                            Value::Number(_) => Err(anyhow!("column {} expects a text", field.name())),
+                        })
+                        .collect::<anyhow::Result<StringArray>>()?,
+                ),
+                data_type => return Err(anyhow!("column {} has unsupported type {}", field.name(), data_type)),
+            };
+            columns.push(column);
+        }
+        let batch = RecordBatch::try_new(Arc::clone(&self.schema), columns)?;
+
+        if let Err(e) = fs::write(&self.tmp_path, write_parquet(&batch)?) {
+            return log_and_err!(reason = e, payload = self.tmp_path, "failed to write export file");
+        }
+        Ok(self.tmp_path)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt64Type;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::ExecutionAccountChanges;
+    use crate::eth::primitives::LogMined;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+    use crate::infra::parquet::read_parquet;
+
+    /// Creates a storage with blocks mined up to the specified number, each one with a transaction that emits a log and changes a balance.
+    fn storage_with_blocks(mined: u64) -> Arc<dyn StorageReader> {
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::new(BlockNumber::ZERO, UnixTime::ZERO)).unwrap();
+        for number in 1..=mined {
+            let mut block = Block::new(BlockNumber::from(number), UnixTime::from(number));
+
+            let mut tx: TransactionMined = Faker.fake();
+            tx.block_number = block.number();
+            tx.block_hash = block.hash();
+            tx.transaction_index = 0.into();
+
+            let mut log: LogMined = Faker.fake();
+            log.block_number = block.number();
+            log.block_hash = block.hash();
+            log.transaction_hash = tx.input.hash;
+            log.transaction_index = 0.into();
+            log.log_index = 0.into();
+            tx.logs = vec![log];
+
+            let address = Address::new([number as u8; 20]);
+            let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, Wei::from(10u64)));
+            changes.balance.set_modified(Wei::from(number));
+            tx.execution.changes = [(address, changes)].into_iter().collect();
+
+            block.transactions.push(tx);
+            perm.save_block(block).unwrap();
+        }
+        perm.set_mined_block_number(BlockNumber::from(mined)).unwrap();
+
+        let temp = InMemoryTemporaryStorage::new(BlockNumber::from(mined + 1));
+        Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap())
+    }
+
+    fn read_table(exporter: &AnalyticsExporter, table: Table, start: u64, end: u64) -> RecordBatch {
+        let file = File::open(table.chunk_path(exporter.output(), start, end)).unwrap();
+        let batches = read_parquet(file).unwrap();
+        assert_eq!(batches.len(), 1);
+        batches.into_iter().next().unwrap()
+    }
+
+    fn numbers(batch: &RecordBatch, column: &str) -> Vec<u64> {
+        batch.column_by_name(column).unwrap().as_primitive::<UInt64Type>().values().to_vec()
+    }
+
+    fn texts(batch: &RecordBatch, column: &str) -> Vec<Option<String>> {
+        batch
+            .column_by_name(column)
+            .unwrap()
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(str::to_owned))
+            .collect()
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let storage = storage_with_blocks(2);
+        let output = tempfile::tempdir().unwrap();
+        let exporter = AnalyticsExporter::new(Arc::clone(&storage), output.path(), 10).unwrap();
+        exporter.export("test", 1, 2).unwrap();
+
+        let blocks = [1, 2].map(|number| storage.read_block(BlockFilter::Number(BlockNumber::from(number))).unwrap().unwrap());
+        let txs = blocks.iter().map(|block| &block.transactions[0]).collect::<Vec<_>>();
+
+        let transactions = read_table(&exporter, Table::Transactions, 1, 2);
+        assert_eq!(transactions.schema(), Table::Transactions.schema());
+        assert_eq!(numbers(&transactions, "block_number"), vec![1, 2]);
+        assert_eq!(numbers(&transactions, "block_timestamp"), vec![1, 2]);
+        assert_eq!(
+            texts(&transactions, "transaction_hash"),
+            txs.iter().map(|tx| Some(tx.input.hash.to_string())).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            numbers(&transactions, "gas_used"),
+            txs.iter().map(|tx| tx.execution.gas.as_u64()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            texts(&transactions, "to"),
+            txs.iter().map(|tx| tx.input.to.map(|to| to.to_string())).collect::<Vec<_>>()
+        );
+
+        let logs = read_table(&exporter, Table::Logs, 1, 2);
+        assert_eq!(numbers(&logs, "block_number"), vec![1, 2]);
+        assert_eq!(
+            texts(&logs, "address"),
+            txs.iter().map(|tx| Some(tx.logs[0].log.address.to_string())).collect::<Vec<_>>()
+        );
+
+        let account_diffs = read_table(&exporter, Table::AccountDiffs, 1, 2);
+        assert_eq!(texts(&account_diffs, "field"), vec![Some("balance".to_owned()); 2]);
+        assert_eq!(texts(&account_diffs, "slot_index"), vec![None; 2]);
+        assert_eq!(texts(&account_diffs, "original_value"), vec![Some("10".to_owned()); 2]);
+        assert_eq!(texts(&account_diffs, "modified_value"), vec![Some("1".to_owned()), Some("2".to_owned())]);
+
+        let schema: serde_json::Value = serde_json::from_slice(&fs::read(exporter.output().join("schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["version"], EXPORT_SCHEMA_VERSION);
+        assert_eq!(schema["tables"]["transactions"][0]["name"], "block_number");
+    }
+
+    #[test]
+    fn test_export_resumes_from_the_first_chunk_not_exported() {
+        let storage = storage_with_blocks(4);
+        let output = tempfile::tempdir().unwrap();
+        let exporter = AnalyticsExporter::new(storage, output.path(), 2).unwrap();
+        exporter.export("test", 1, 4).unwrap();
+
+        // simulate an export interrupted while writing the chunk of blocks 2 and 3
+        let exported = Table::Transactions.chunk_path(exporter.output(), 1, 1);
+        fs::write(&exported, b"not rewritten").unwrap();
+        let interrupted = Table::Logs.chunk_path(exporter.output(), 2, 3);
+        fs::remove_file(&interrupted).unwrap();
+        fs::write(interrupted.with_extension("parquet.tmp"), b"partial").unwrap();
+
+        exporter.export("test", 1, 4).unwrap();
+        assert_eq!(fs::read(&exported).unwrap(), b"not rewritten");
+        assert_eq!(numbers(&read_table(&exporter, Table::Logs, 2, 3), "block_number"), vec![2, 3]);
+        assert!(!interrupted.with_extension("parquet.tmp").exists());
+        assert_eq!(numbers(&read_table(&exporter, Table::Transactions, 4, 4), "block_number"), vec![4]);
+    }
+}
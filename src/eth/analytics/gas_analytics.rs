@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+
+use display_json::DebugAsJson;
+use itertools::Itertools;
+use parking_lot::RwLock;
+
+use crate::eth::codegen;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::storage::StorageReader;
+
+/// Selector used when the transaction input has less than 4 bytes (plain transfers and fallback calls).
+const SELECTOR_MISSING: [u8; 4] = [0; 4];
+
+/// Aggregates gas used per contract address and per function selector across mined blocks.
+///
+/// Intended for chain operators doing capacity planning, so it is disabled by default. The aggregated usage is persisted in the permanent storage
+/// after each committed block (see [`GasUsageSnapshot`]), so it survives restarts.
+#[derive(Default)]
+pub struct GasAnalytics {
+    state: RwLock<GasAnalyticsState>,
+}
+
+#[derive(Default)]
+struct GasAnalyticsState {
+    first_block: Option<BlockNumber>,
+    last_block: Option<BlockNumber>,
+    blocks: u64,
+    usage: GasUsage,
+    contracts: HashMap<Address, ContractGasUsage>,
+}
+
+#[derive(Default)]
+struct ContractGasUsage {
+    usage: GasUsage,
+    functions: HashMap<[u8; 4], GasUsage>,
+}
+
+/// Number of transactions and gas used by them.
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct GasUsage {
+    pub transactions: u64,
+    pub gas_used: u64,
+}
+
+impl GasUsage {
+    fn add(&mut self, gas_used: u64) {
+        self.transactions += 1;
+        self.gas_used = self.gas_used.saturating_add(gas_used);
+    }
+}
+
+impl GasAnalytics {
+    /// Loads the usage persisted in the storage, then aggregates blocks mined after it was persisted.
+    ///
+    /// If no usage was persisted yet, aggregation starts at the next committed block.
+    pub fn load(&self, storage: &dyn StorageReader) -> Result<(), StratusError> {
+        let Some(snapshot) = storage.read_gas_usage()? else {
+            return Ok(());
+        };
+        let last_block = snapshot.last_block;
+        *self.state.write() = GasAnalyticsState::from(snapshot);
+
+        // blocks committed after the usage was persisted (the node stopped between saving both)
+        let Some(last_block) = last_block else { return Ok(()) };
+        let mined_block = storage.read_mined_block_number()?;
+        let mut number = last_block.next_block_number();
+        while number <= mined_block {
+            if let Some(block) = storage.read_block(BlockFilter::Number(number))? {
+                self.record_block(&block);
+            }
+            number = number.next_block_number();
+        }
+        tracing::info!(%last_block, %mined_block, "loaded persisted gas analytics");
+
+        Ok(())
+    }
+
+    /// Aggregates gas used by all transactions of a block.
+    pub fn record_block(&self, block: &Block) {
+        let mut state = self.state.write();
+
+        state.first_block = Some(state.first_block.map_or(block.number(), |first| first.min(block.number())));
+        state.last_block = Some(state.last_block.map_or(block.number(), |last| last.max(block.number())));
+        state.blocks += 1;
+
+        for tx in &block.transactions {
+            state.record_transaction(tx);
+        }
+    }
+
+    /// Returns the aggregated usage in the format persisted in the storage.
+    pub fn snapshot(&self) -> GasUsageSnapshot {
+        let state = self.state.read();
+        GasUsageSnapshot {
+            first_block: state.first_block,
+            last_block: state.last_block,
+            blocks: state.blocks,
+            usage: state.usage,
+            functions: state
+                .contracts
+                .iter()
+                .flat_map(|(contract, contract_usage)| {
+                    contract_usage.functions.iter().map(|(selector, usage)| FunctionGasUsage {
+                        contract: *contract,
+                        selector: *selector,
+                        usage: *usage,
+                    })
+                })
+                .sorted_by_key(|function| (function.contract, function.selector))
+                .collect(),
+        }
+    }
+
+    /// Returns the aggregated stats keeping only the `limit` contracts that used more gas.
+    pub fn stats(&self, limit: usize) -> GasStats {
+        let state = self.state.read();
+
+        let contracts = state
+            .contracts
+            .iter()
+            .sorted_by(|(_, a), (_, b)| b.usage.gas_used.cmp(&a.usage.gas_used))
+            .take(limit)
+            .map(|(address, contract)| ContractGasStats {
+                address: *address,
+                name: codegen::contract_name_for_o11y(&Some(*address)),
+                transactions: contract.usage.transactions,
+                gas_used: contract.usage.gas_used,
+                functions: contract
+                    .functions
+                    .iter()
+                    .sorted_by(|(_, a), (_, b)| b.gas_used.cmp(&a.gas_used))
+                    .map(|(selector, usage)| FunctionGasStats {
+                        selector: const_hex::encode_prefixed(selector),
+                        signature: codegen::function_sig_for_o11y(selector),
+                        transactions: usage.transactions,
+                        gas_used: usage.gas_used,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        GasStats {
+            first_block: state.first_block,
+            last_block: state.last_block,
+            blocks: state.blocks,
+            transactions: state.usage.transactions,
+            gas_used: state.usage.gas_used,
+            contracts,
+        }
+    }
+}
+
+impl GasAnalyticsState {
+    fn record_transaction(&mut self, tx: &TransactionMined) {
+        let gas_used = tx.execution.gas.as_u64();
+        self.usage.add(gas_used);
+
+        // contract deployments are accounted to the deployed contract
+        let Some(contract) = tx.input.to.or(tx.execution.deployed_contract_address) else {
+            return;
+        };
+        let selector = match tx.input.input.get(..4) {
+            Some(selector) if tx.input.to.is_some() => selector.try_into().unwrap_or(SELECTOR_MISSING),
+            _ => SELECTOR_MISSING,
+        };
+
+        let contract_usage = self.contracts.entry(contract).or_default();
+        contract_usage.usage.add(gas_used);
+        contract_usage.functions.entry(selector).or_default().add(gas_used);
+    }
+}
+
+impl From<GasUsageSnapshot> for GasAnalyticsState {
+    fn from(snapshot: GasUsageSnapshot) -> Self {
+        let mut contracts: HashMap<Address, ContractGasUsage> = HashMap::new();
+        for function in snapshot.functions {
+            // every transaction is accounted to exactly one function, so the contract usage is the sum of its functions
+            let contract_usage = contracts.entry(function.contract).or_default();
+            contract_usage.usage.transactions += function.usage.transactions;
+            contract_usage.usage.gas_used = contract_usage.usage.gas_used.saturating_add(function.usage.gas_used);
+            contract_usage.functions.insert(function.selector, function.usage);
+        }
+        Self {
+            first_block: snapshot.first_block,
+            last_block: snapshot.last_block,
+            blocks: snapshot.blocks,
+            usage: snapshot.usage,
+            contracts,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Snapshot
+// -----------------------------------------------------------------------------
+
+/// Aggregated gas usage persisted in the permanent storage.
+#[derive(DebugAsJson, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct GasUsageSnapshot {
+    pub first_block: Option<BlockNumber>,
+    pub last_block: Option<BlockNumber>,
+    pub blocks: u64,
+    pub usage: GasUsage,
+    pub functions: Vec<FunctionGasUsage>,
+}
+
+/// Gas usage of a single function of a contract.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct FunctionGasUsage {
+    pub contract: Address,
+    pub selector: [u8; 4],
+    pub usage: GasUsage,
+}
+
+// -----------------------------------------------------------------------------
+// Stats
+// -----------------------------------------------------------------------------
+
+/// Gas usage aggregated over the committed blocks, including the ones committed before the last restart.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasStats {
+    pub first_block: Option<BlockNumber>,
+    pub last_block: Option<BlockNumber>,
+    pub blocks: u64,
+    pub transactions: u64,
+    pub gas_used: u64,
+    pub contracts: Vec<ContractGasStats>,
+}
+
+/// Gas usage of a single contract.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractGasStats {
+    pub address: Address,
+    pub name: codegen::ContractName,
+    pub transactions: u64,
+    pub gas_used: u64,
+    pub functions: Vec<FunctionGasStats>,
+}
+
+/// Gas usage of a single function of a contract.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionGasStats {
+    pub selector: String,
+    pub signature: codegen::SoliditySignature,
+    pub transactions: u64,
+    pub gas_used: u64,
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+    use crate::ext::to_json_value;
+
+    fn fake_tx(to: Address, input: &[u8], gas: u64) -> TransactionMined {
+        let mut tx: TransactionMined = Faker.fake();
+        tx.input.to = Some(to);
+        tx.input.input = Bytes::from(input.to_vec());
+        tx.execution.gas = Gas::from(gas);
+        tx
+    }
+
+    #[test]
+    fn test_gas_analytics_aggregates_by_contract_and_selector() {
+        let contract_a = Address::new([1; 20]);
+        let contract_b = Address::new([2; 20]);
+
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1));
+        block.transactions.push(fake_tx(contract_a, &[0xaa, 0xbb, 0xcc, 0xdd, 0x00], 100));
+        block.transactions.push(fake_tx(contract_a, &[0xaa, 0xbb, 0xcc, 0xdd], 50));
+        block.transactions.push(fake_tx(contract_a, &[], 21));
+        block.transactions.push(fake_tx(contract_b, &[0x11, 0x22, 0x33, 0x44], 500));
+
+        let analytics = GasAnalytics::default();
+        analytics.record_block(&block);
+
+        let stats = analytics.stats(usize::MAX);
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.transactions, 4);
+        assert_eq!(stats.gas_used, 671);
+
+        // contracts sorted by gas used
+        assert_eq!(stats.contracts.len(), 2);
+        assert_eq!(stats.contracts[0].address, contract_b);
+        assert_eq!(stats.contracts[1].address, contract_a);
+        assert_eq!(stats.contracts[1].transactions, 3);
+        assert_eq!(stats.contracts[1].gas_used, 171);
+
+        // functions sorted by gas used
+        let functions = &stats.contracts[1].functions;
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].selector, "0xaabbccdd");
+        assert_eq!(functions[0].gas_used, 150);
+        assert_eq!(functions[1].selector, "0x00000000");
+
+        // limit
+        assert_eq!(analytics.stats(1).contracts.len(), 1);
+    }
+
+    #[test]
+    fn test_gas_analytics_snapshot_restores_stats() {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1));
+        block.transactions.push(fake_tx(Address::new([1; 20]), &[0xaa, 0xbb, 0xcc, 0xdd], 100));
+        block.transactions.push(fake_tx(Address::new([1; 20]), &[], 21));
+        block.transactions.push(fake_tx(Address::new([2; 20]), &[0x11, 0x22, 0x33, 0x44], 500));
+        let analytics = GasAnalytics::default();
+        analytics.record_block(&block);
+
+        let snapshot = analytics.snapshot();
+        let restored = GasAnalytics {
+            state: RwLock::new(GasAnalyticsState::from(snapshot.clone())),
+        };
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(to_json_value(restored.stats(usize::MAX)), to_json_value(analytics.stats(usize::MAX)));
+    }
+
+    #[test]
+    fn test_gas_analytics_load_aggregates_blocks_mined_after_persisted_usage() {
+        let blocks = (1..=3u64)
+            .map(|number| {
+                let mut block = Block::new(BlockNumber::from(number), UnixTime::from(number));
+                block.transactions.push(fake_tx(Address::new([1; 20]), &[0xaa, 0xbb, 0xcc, 0xdd], 100));
+                block
+            })
+            .collect_vec();
+
+        // usage persisted after the first block, then the node stopped before persisting the others
+        let persisted = GasAnalytics::default();
+        persisted.record_block(&blocks[0]);
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::genesis()).unwrap();
+        for block in &blocks {
+            perm.save_block(block.clone()).unwrap();
+        }
+        perm.set_mined_block_number(BlockNumber::from(3u64)).unwrap();
+        perm.save_gas_usage(persisted.snapshot()).unwrap();
+        let storage = StratusStorage::new(Box::new(InMemoryTemporaryStorage::new(4.into())), Box::new(perm), None).unwrap();
+
+        let loaded = GasAnalytics::default();
+        loaded.load(&storage).unwrap();
+
+        let expected = GasAnalytics::default();
+        for block in &blocks {
+            expected.record_block(block);
+        }
+        assert_eq!(loaded.snapshot(), expected.snapshot());
+        assert_eq!(loaded.stats(usize::MAX).gas_used, 300);
+    }
+
+    #[test]
+    fn test_gas_analytics_load_without_persisted_usage() {
+        let storage = StratusStorage::new_test().unwrap();
+        let analytics = GasAnalytics::default();
+        analytics.load(&storage).unwrap();
+        assert_eq!(analytics.snapshot(), GasUsageSnapshot::default());
+    }
+}
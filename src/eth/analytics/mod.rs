@@ -0,0 +1,22 @@
+//! Optional analytics aggregated from mined blocks and the current state, and exports of mined blocks to data warehouses.
+
+#[cfg(feature = "parquet")]
+mod export;
+mod gas_analytics;
+mod state_stats;
+
+#[cfg(feature = "parquet")]
+pub use export::AnalyticsExporter;
+pub use gas_analytics::ContractGasStats;
+pub use gas_analytics::FunctionGasStats;
+pub use gas_analytics::FunctionGasUsage;
+pub use gas_analytics::GasAnalytics;
+pub use gas_analytics::GasStats;
+pub use gas_analytics::GasUsage;
+pub use gas_analytics::GasUsageSnapshot;
+pub use state_stats::ContractStateCount;
+pub use state_stats::ContractStateStats;
+pub use state_stats::StateAnalytics;
+pub use state_stats::StateCount;
+pub use state_stats::StateStats;
+pub use state_stats::StateStatsConfig;
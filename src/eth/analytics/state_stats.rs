@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use itertools::Itertools;
+use parking_lot::RwLock;
+
+use crate::eth::codegen;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::UnixTime;
+use crate::eth::storage::StorageReader;
+use crate::ext::parse_duration;
+use crate::ext::spawn_blocking_named;
+use crate::ext::spawn_named;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::infra::task_registry;
+use crate::GlobalState;
+
+/// Approximate size of an account without bytecode: address, nonce, balance and code hash.
+const ACCOUNT_SIZE: u64 = 20 + 8 + 32 + 32;
+
+/// Approximate size of a slot: index and value.
+const SLOT_SIZE: u64 = 32 + 32;
+
+/// Max wait since last checked shutdown while waiting for the next calculation.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct StateStatsConfig {
+    /// Interval between calculations of state statistics queried with `stratus_stateStats`. Disabled when not set.
+    ///
+    /// Each calculation iterates over all accounts and slots, so it should not be too frequent for large states.
+    #[arg(long = "state-stats-interval", env = "STATE_STATS_INTERVAL", value_parser = parse_duration)]
+    pub state_stats_interval: Option<Duration>,
+}
+
+impl StateStatsConfig {
+    /// Initializes the state statistics job, if enabled.
+    pub fn init(&self, storage: Arc<dyn StorageReader>) -> Option<Arc<StateAnalytics>> {
+        let interval = self.state_stats_interval?;
+        tracing::info!(config = ?self, "creating state analytics");
+
+        let analytics = Arc::new(StateAnalytics {
+            storage,
+            last: RwLock::new(None),
+        });
+        StateAnalytics::spawn_calculator(Arc::clone(&analytics), interval);
+        Some(analytics)
+    }
+}
+
+/// Current state counted by the permanent storage.
+#[derive(Debug, Clone, Default)]
+pub struct StateCount {
+    pub accounts: u64,
+    pub contracts: HashMap<Address, ContractStateCount>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContractStateCount {
+    pub slots: u64,
+    pub bytecode_size: u64,
+}
+
+impl StateCount {
+    /// Counts an account and its bytecode, if it is a contract.
+    pub fn count_account(&mut self, address: Address, bytecode_size: usize) {
+        self.accounts += 1;
+        if bytecode_size > 0 {
+            self.contracts.entry(address).or_default().bytecode_size = bytecode_size as u64;
+        }
+    }
+
+    /// Counts a slot of a contract.
+    pub fn count_slot(&mut self, address: Address) {
+        self.contracts.entry(address).or_default().slots += 1;
+    }
+}
+
+/// Periodically calculates the size of the current state, so operators can track its growth.
+pub struct StateAnalytics {
+    storage: Arc<dyn StorageReader>,
+
+    /// Last calculated count and when it was calculated.
+    last: RwLock<Option<StateCountSnapshot>>,
+}
+
+struct StateCountSnapshot {
+    block_number: BlockNumber,
+    calculated_at: UnixTime,
+    count: StateCount,
+}
+
+impl StateAnalytics {
+    /// Returns the last calculated stats keeping only the `limit` contracts with more slots.
+    ///
+    /// Returns `None` if the first calculation did not finish yet.
+    pub fn stats(&self, limit: usize) -> Option<StateStats> {
+        let last = self.last.read();
+        let last = last.as_ref()?;
+
+        let slots = last.count.contracts.values().map(|contract| contract.slots).sum::<u64>();
+        let bytecode_size = last.count.contracts.values().map(|contract| contract.bytecode_size).sum::<u64>();
+        let contracts = last
+            .count
+            .contracts
+            .iter()
+            .sorted_by(|(_, a), (_, b)| b.slots.cmp(&a.slots))
+            .take(limit)
+            .map(|(address, contract)| ContractStateStats {
+                address: *address,
+                name: codegen::contract_name_for_o11y(&Some(*address)),
+                slots: contract.slots,
+                bytecode_size: contract.bytecode_size,
+            })
+            .collect();
+
+        Some(StateStats {
+            block_number: last.block_number,
+            calculated_at: last.calculated_at,
+            accounts: last.count.accounts,
+            slots,
+            size: last.count.accounts * ACCOUNT_SIZE + slots * SLOT_SIZE + bytecode_size,
+            contracts,
+        })
+    }
+
+    /// Counts the current state and replaces the last calculated stats.
+    fn calculate(&self) -> anyhow::Result<()> {
+        let start = Instant::now();
+
+        // the count is not atomic, so the block number is an approximation of the counted state
+        let block_number = self.storage.read_mined_block_number()?;
+        let count = self.storage.read_state_count()?;
+
+        tracing::info!(%block_number, accounts = %count.accounts, contracts = %count.contracts.len(), elapsed = ?start.elapsed(), "calculated state stats");
+        *self.last.write() = Some(StateCountSnapshot {
+            block_number,
+            calculated_at: UnixTime::now(),
+            count,
+        });
+
+        #[cfg(feature = "metrics")]
+        if let Some(stats) = self.stats(0) {
+            metrics::set_state_accounts(stats.accounts);
+            metrics::set_state_slots(stats.slots);
+            metrics::set_state_size(stats.size);
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a new task that calculates the state stats at the configured interval.
+    fn spawn_calculator(analytics: Arc<Self>, interval: Duration) {
+        const TASK_NAME: &str = "analytics::state-stats";
+
+        spawn_named(TASK_NAME, async move {
+            let mut last_calculation: Option<Instant> = None;
+            loop {
+                task_registry::heartbeat();
+
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return;
+                }
+
+                if last_calculation.map_or(true, |last| last.elapsed() >= interval) {
+                    last_calculation = Some(Instant::now());
+
+                    // iterates over the whole state, so it must not block the async runtime
+                    let analytics = Arc::clone(&analytics);
+                    match spawn_blocking_named(TASK_NAME, move || analytics.calculate()).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => tracing::error!(reason = ?e, "failed to calculate state stats"),
+                        Err(e) => tracing::error!(reason = ?e, "state stats calculation panicked"),
+                    }
+                }
+
+                traced_sleep(SHUTDOWN_CHECK_INTERVAL.min(interval), SleepReason::Interval).await;
+            }
+        });
+    }
+}
+
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+pub struct StateStats {
+    /// Mined block when the calculation started.
+    pub block_number: BlockNumber,
+    pub calculated_at: UnixTime,
+    pub accounts: u64,
+    pub slots: u64,
+
+    /// Approximate size in bytes of accounts, slots and bytecodes, not including storage overhead and history.
+    pub size: u64,
+    pub contracts: Vec<ContractStateStats>,
+}
+
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+pub struct ContractStateStats {
+    pub address: Address,
+    pub name: codegen::ContractName,
+    pub slots: u64,
+    pub bytecode_size: u64,
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_count() {
+        let eoa = Address::new([1; 20]);
+        let contract = Address::new([2; 20]);
+
+        let mut count = StateCount::default();
+        count.count_account(eoa, 0);
+        count.count_account(contract, 100);
+        count.count_slot(contract);
+        count.count_slot(contract);
+
+        assert_eq!(count.accounts, 2);
+        assert_eq!(count.contracts.len(), 1);
+        assert_eq!(count.contracts[&contract].slots, 2);
+        assert_eq!(count.contracts[&contract].bytecode_size, 100);
+    }
+}
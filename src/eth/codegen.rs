@@ -1,6 +1,7 @@
 //! Auto-generated code.
 
 use crate::eth::primitives::Address;
+use crate::eth::selector_registry;
 use crate::infra::metrics;
 
 include!(concat!(env!("OUT_DIR"), "/contracts.rs"));
@@ -15,7 +16,7 @@ pub fn contract_name_for_o11y(address: &Option<Address>) -> ContractName {
     let Some(address) = address else { return metrics::LABEL_MISSING };
     match CONTRACTS.get(address.as_bytes()) {
         Some(contract_name) => contract_name,
-        None => metrics::LABEL_UNKNOWN,
+        None => selector_registry::contract_name(address).unwrap_or(metrics::LABEL_UNKNOWN),
     }
 }
 
@@ -24,6 +25,6 @@ pub fn function_sig_for_o11y(bytes: impl AsRef<[u8]>) -> SoliditySignature {
     let Some(id) = bytes.as_ref().get(..4) else { return metrics::LABEL_MISSING };
     match SIGNATURES_4_BYTES.get(id) {
         Some(signature) => signature,
-        None => metrics::LABEL_UNKNOWN,
+        None => selector_registry::function_sig(id).unwrap_or(metrics::LABEL_UNKNOWN),
     }
 }
@@ -0,0 +1,254 @@
+//! Re-execution of mined blocks.
+//!
+//! Transactions of a block are executed again, each one over the changes of the previous ones, and their results are compared with the ones
+//! persisted when the block was mined. It is used to validate EVM and storage refactors against real history and to debug a single block
+//! exported with the state it reads.
+
+use std::collections::hash_map::Entry;
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::eth::executor::Evm;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::EvmInput;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionChanges;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::StorageReader;
+use crate::eth::storage::StratusStorage;
+use crate::ext::not;
+
+/// Result of re-executing a block.
+pub struct BlockReplay {
+    /// Changes of all transactions of the block, merged over the prestate.
+    pub changes: ExecutionChanges,
+
+    /// Values that differ between the persisted and the replayed executions.
+    pub divergences: Vec<Divergence>,
+}
+
+/// Value that differs between the persisted and the replayed execution.
+pub struct Divergence {
+    pub tx_hash: Option<Hash>,
+    pub field: String,
+    pub persisted: String,
+    pub replayed: String,
+}
+
+impl Divergence {
+    pub fn new(tx_hash: Option<Hash>, field: impl Display, persisted: impl Display, replayed: impl Display) -> Self {
+        Self {
+            tx_hash,
+            field: field.to_string(),
+            persisted: persisted.to_string(),
+            replayed: replayed.to_string(),
+        }
+    }
+}
+
+/// Re-executes all transactions of a block over the state of the previous block and compares their results with the persisted ones.
+///
+/// Accounts and slots present in `prestate` are read from it instead of from the storage.
+pub fn replay_block(storage: &StratusStorage, evm: &mut Evm, block: &Block, prestate: ExecutionChanges) -> anyhow::Result<BlockReplay> {
+    let parent = PointInTime::MinedPast(block.number().prev().unwrap_or(BlockNumber::ZERO));
+
+    // execute transactions, each one over the changes of the previous ones
+    let mut divergences = Vec::new();
+    let mut block_changes = prestate;
+    for tx in &block.transactions {
+        let persisted = &tx.execution;
+        let tx_hash = Some(tx.input.hash);
+
+        // failed external transactions are recreated from their receipts instead of executed
+        if persisted.receipt_applied && not(persisted.is_success()) {
+            let changes = failed_external_changes(storage, tx, &block_changes, parent)?;
+            merge_changes(&mut block_changes, changes);
+            continue;
+        }
+
+        let input = EvmInput::from_mined(tx, block.header.timestamp, block_changes.clone());
+        let mut replayed = match evm.execute(input) {
+            Ok(result) => result.execution,
+            Err(e) => {
+                divergences.push(Divergence::new(tx_hash, "execution", "executed", format!("{:?}", e)));
+                continue;
+            }
+        };
+
+        // external transactions have gas and execution costs fixed from their receipts
+        if persisted.receipt_applied {
+            replayed.gas = persisted.gas;
+            if let Some(sender_changes) = replayed.changes.get_mut(&tx.input.signer) {
+                let sender_balance = sender_changes.balance.take_ref().copied().unwrap_or_default();
+                sender_changes.balance.set_modified(charge_execution_cost(sender_balance, tx));
+            }
+        }
+
+        if persisted.result != replayed.result {
+            divergences.push(Divergence::new(tx_hash, "result", &persisted.result, &replayed.result));
+        }
+        if persisted.gas != replayed.gas {
+            divergences.push(Divergence::new(tx_hash, "gas", persisted.gas, replayed.gas));
+        }
+        merge_changes(&mut block_changes, replayed.changes);
+    }
+
+    Ok(BlockReplay {
+        changes: block_changes,
+        divergences,
+    })
+}
+
+/// Compares the latest values of the accounts changed when the block was mined with the latest values after replaying it.
+pub fn compare_state_diff(state_diff: &[ExecutionAccountChanges], replayed: &ExecutionChanges) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    let mut compare = |field: String, persisted: String, replayed: String| {
+        if persisted != replayed {
+            divergences.push(Divergence::new(None, field, persisted, replayed));
+        }
+    };
+    let bytecode_to_string = |bytecode: Option<&Option<Bytes>>| match bytecode.cloned().flatten() {
+        Some(bytecode) => bytecode.to_string(),
+        None => "null".to_owned(),
+    };
+
+    for persisted in state_diff.iter().sorted_by_key(|changes| changes.address) {
+        let address = persisted.address;
+        let Some(replayed) = replayed.get(&address) else {
+            compare(format!("{}", address), "changed".to_owned(), "not changed".to_owned());
+            continue;
+        };
+
+        let (persisted_account, replayed_account) = (persisted.to_account(), replayed.to_account());
+        compare(
+            format!("{}.nonce", address),
+            persisted_account.nonce.to_string(),
+            replayed_account.nonce.to_string(),
+        );
+        compare(
+            format!("{}.balance", address),
+            persisted_account.balance.to_string(),
+            replayed_account.balance.to_string(),
+        );
+        compare(
+            format!("{}.bytecode", address),
+            bytecode_to_string(persisted.bytecode.take_ref()),
+            bytecode_to_string(replayed.bytecode.take_ref()),
+        );
+        for (index, slot) in persisted.slots.iter().sorted_by_key(|(index, _)| **index) {
+            let persisted_value = slot.take_ref().map(|slot| slot.value).unwrap_or_default();
+            let replayed_value = replayed
+                .slots
+                .get(index)
+                .and_then(|slot| slot.take_ref())
+                .map(|slot| slot.value)
+                .unwrap_or_default();
+            compare(format!("{}.slot.{}", address, index), persisted_value.to_string(), replayed_value.to_string());
+        }
+    }
+
+    // accounts changed only by the replay
+    for (address, replayed) in replayed.iter().sorted_by_key(|(address, _)| **address) {
+        let modified = replayed.is_account_modified() || replayed.slots.values().any(|slot| slot.is_modified());
+        if modified && not(state_diff.iter().any(|changes| changes.address == *address)) {
+            compare(format!("{}", address), "not changed".to_owned(), "changed".to_owned());
+        }
+    }
+
+    divergences
+}
+
+/// Recreates the changes of a failed external transaction, that only increments the sender nonce and charges its execution costs.
+fn failed_external_changes(
+    storage: &StratusStorage,
+    tx: &TransactionMined,
+    block_changes: &ExecutionChanges,
+    parent: PointInTime,
+) -> anyhow::Result<ExecutionChanges> {
+    let sender = match block_changes.get(&tx.input.signer) {
+        Some(changes) => changes.to_account(),
+        None => storage.read_account(tx.input.signer, parent)?,
+    };
+
+    let mut changes = ExecutionAccountChanges::from_original_values(sender.clone());
+    changes.nonce.set_modified(sender.nonce.next_nonce());
+    changes.balance.set_modified(charge_execution_cost(sender.balance, tx));
+    Ok(ExecutionChanges::from([(tx.input.signer, changes)]))
+}
+
+/// Subtracts the execution cost of an external transaction from the sender balance, like it was done when the transaction was imported.
+fn charge_execution_cost(balance: Wei, tx: &TransactionMined) -> Wei {
+    let execution_cost = Wei::from(tx.input.gas_price.0 * tx.execution.gas.as_u64());
+    if balance > execution_cost {
+        balance - execution_cost
+    } else {
+        Wei::ZERO
+    }
+}
+
+/// Merges the changes of an execution into the changes of the previous executions.
+fn merge_changes(block_changes: &mut ExecutionChanges, changes: ExecutionChanges) {
+    for (address, changes) in changes {
+        match block_changes.entry(address) {
+            Entry::Occupied(mut entry) => entry.get_mut().merge(changes),
+            Entry::Vacant(entry) => {
+                entry.insert(changes);
+            }
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::ExecutionValueChange;
+    use crate::eth::primitives::Slot;
+    use crate::eth::primitives::SlotIndex;
+
+    #[test]
+    fn test_compare_state_diff() {
+        let (a, b) = (Address::new([1; 20]), Address::new([2; 20]));
+        let index = SlotIndex::from(1u64);
+        let changes = |address: Address, balance: u64, slot_value: u64| {
+            let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, 100u64.into()));
+            changes.balance.set_modified(balance.into());
+            changes
+                .slots
+                .insert(index, ExecutionValueChange::from_modified(Slot::new(index, slot_value.into())));
+            changes
+        };
+
+        // same values do not diverge, even if replayed changes include accounts that were only read
+        let state_diff = vec![changes(a, 50, 1)];
+        let read_only = ExecutionAccountChanges::from_original_values(Account::new_with_balance(b, 100u64.into()));
+        let replayed = ExecutionChanges::from([(a, changes(a, 50, 1)), (b, read_only)]);
+        assert!(compare_state_diff(&state_diff, &replayed).is_empty());
+
+        // different balance and slot diverge
+        let replayed = ExecutionChanges::from([(a, changes(a, 60, 2))]);
+        let fields = compare_state_diff(&state_diff, &replayed)
+            .into_iter()
+            .map(|divergence| divergence.field)
+            .collect_vec();
+        assert_eq!(fields, vec![format!("{}.balance", a), format!("{}.slot.{}", a, index)]);
+
+        // accounts changed only by one side diverge
+        let replayed = ExecutionChanges::from([(b, changes(b, 50, 1))]);
+        let fields = compare_state_diff(&state_diff, &replayed)
+            .into_iter()
+            .map(|divergence| divergence.field)
+            .collect_vec();
+        assert_eq!(fields, vec![a.to_string(), b.to_string()]);
+    }
+}
@@ -4,8 +4,10 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use itertools::Itertools;
+use revm::inspector_handle_register;
 use revm::primitives::AccountInfo;
 use revm::primitives::AnalysisKind;
+use revm::primitives::BlobExcessGasAndPrice;
 use revm::primitives::EVMError;
 use revm::primitives::ExecutionResult as RevmExecutionResult;
 use revm::primitives::InvalidTransaction;
@@ -21,24 +23,29 @@ use revm::Handler;
 
 use crate::alias::RevmAddress;
 use crate::alias::RevmBytecode;
-use crate::eth::executor::EvmExecutionResult;
-use crate::eth::executor::EvmInput;
+use crate::eth::executor::EvmInspector;
 use crate::eth::executor::ExecutorConfig;
+use crate::eth::executor::OpcodeTracer;
+use crate::eth::executor::TransferTracer;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainSpec;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
+use crate::eth::primitives::EvmExecutionResult;
+use crate::eth::primitives::EvmInput;
 use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::ExecutionChanges;
 use crate::eth::primitives::ExecutionResult;
 use crate::eth::primitives::ExecutionValueChange;
 use crate::eth::primitives::Gas;
+use crate::eth::primitives::InternalTransfer;
 use crate::eth::primitives::Log;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
-use crate::eth::storage::Storage;
+use crate::eth::storage::StorageReader;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
 use crate::ext::OptionExt;
@@ -50,17 +57,28 @@ const GAS_MAX_LIMIT: u64 = 1_000_000_000;
 
 /// Implementation of EVM using [`revm`](https://crates.io/crates/revm).
 pub struct Evm {
-    evm: RevmEvm<'static, (), RevmSession>,
+    evm: RevmEvm<'static, EvmInspector, RevmSession>,
 }
 
 impl Evm {
     /// Creates a new instance of the Evm.
-    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new(storage: Arc<StratusStorage>, config: ExecutorConfig) -> Self {
-        tracing::info!(?config, "creating revm");
+        Self::build(storage, config, false)
+    }
+
+    /// Creates a new instance of the Evm that tracks banned opcodes executed during account abstraction validation.
+    ///
+    /// Tracking adds overhead to every executed opcode, so it should be used only for validation simulations.
+    pub fn new_with_opcode_tracer(storage: Arc<StratusStorage>, config: ExecutorConfig) -> Self {
+        Self::build(storage, config, true)
+    }
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn build(storage: Arc<StratusStorage>, config: ExecutorConfig, trace_opcodes: bool) -> Self {
+        tracing::info!(?config, %trace_opcodes, "creating revm");
 
         // configure handler
-        let mut handler = Handler::mainnet_with_spec(SpecId::LONDON);
+        let mut handler = Handler::mainnet_with_spec(ChainSpec::EVM_SPEC);
 
         // handler custom validators
         let validate_tx_against_state = handler.validation.tx_against_state;
@@ -72,14 +90,42 @@ impl Evm {
             result
         });
 
+        // handler fees: credit collected fees to the treasury instead of the coinbase, or burn them if there is no treasury
+        if config.executor_gas_mode.is_charged() {
+            match config.executor_fee_treasury {
+                Some(treasury) => {
+                    let reward_beneficiary = Arc::clone(&handler.post_execution.reward_beneficiary);
+                    handler.post_execution.reward_beneficiary = Arc::new(move |ctx, gas| {
+                        let coinbase = std::mem::replace(&mut ctx.evm.inner.env.block.coinbase, treasury.into());
+                        let result = reward_beneficiary(ctx, gas);
+                        ctx.evm.inner.env.block.coinbase = coinbase;
+                        result
+                    });
+                }
+                None => {
+                    handler.post_execution.reward_beneficiary = Arc::new(|_, _| Ok(()));
+                }
+            }
+        }
+
         // handler custom instructions
         let instructions = handler.take_instruction_table();
         handler.set_instruction_table(instructions);
 
+        // handler inspector
+        let inspector = EvmInspector {
+            opcodes: trace_opcodes.then(OpcodeTracer::default),
+            transfers: config.executor_internal_transfers.then(TransferTracer::default),
+        };
+        if inspector.is_enabled() {
+            inspector_handle_register(&mut handler);
+        }
+
         // configure revm
         let chain_id = config.executor_chain_id;
+        let max_code_size = config.executor_max_code_size;
         let mut evm = RevmEvm::builder()
-            .with_external_context(())
+            .with_external_context(inspector)
             .with_db(RevmSession::new(storage, config))
             .with_handler(handler)
             .build();
@@ -87,12 +133,17 @@ impl Evm {
         // global general config
         let cfg_env = evm.cfg_mut();
         cfg_env.chain_id = chain_id;
-        cfg_env.limit_contract_code_size = Some(usize::MAX);
+        cfg_env.limit_contract_code_size = Some(max_code_size);
         cfg_env.perf_analyse_created_bytecodes = AnalysisKind::Raw;
 
         // global block config
         let block_env = evm.block_mut();
         block_env.coinbase = Address::COINBASE.into();
+        // blob gas is not charged, like the gas of reexecuted external transactions
+        block_env.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+            excess_blob_gas: 0,
+            blob_gasprice: 0,
+        });
 
         // global tx config
         let tx_env = evm.tx_mut();
@@ -112,7 +163,8 @@ impl Evm {
 
         // configure block params
         let block_env = evm.block_mut();
-        block_env.basefee = U256::ZERO;
+        block_env.basefee = ChainSpec::BASE_FEE.into();
+        block_env.prevrandao = Some(ChainSpec::MIX_HASH.0 .0.into());
         block_env.timestamp = input.block_timestamp.into();
         block_env.number = input.block_number.into();
         let block_env_log = block_env.clone();
@@ -130,6 +182,11 @@ impl Evm {
         tx_env.nonce = input.nonce.map_into();
         tx_env.data = input.data.into();
         tx_env.value = input.value.into();
+        // blob hashes are exposed to BLOBHASH only after Cancun; before it revm rejects transactions with them
+        tx_env.blob_hashes = match SpecId::enabled(ChainSpec::EVM_SPEC, SpecId::CANCUN) {
+            true => input.blob_hashes.into_iter().map(|hash| B256::from(hash.0 .0)).collect(),
+            false => Vec::new(),
+        };
         let tx_env_log = tx_env.clone();
 
         // execute transaction
@@ -143,11 +200,13 @@ impl Evm {
         let session_metrics = std::mem::take(&mut session.metrics);
         #[cfg(feature = "metrics")]
         let session_point_in_time = std::mem::take(&mut session.input.point_in_time);
+        let banned_opcodes = evm.context.external.opcodes.as_mut().map(OpcodeTracer::take).unwrap_or_default();
+        let internal_transfers = evm.context.external.transfers.as_mut().map(TransferTracer::take).unwrap_or_default();
 
         // parse result
         let execution = match evm_result {
             // executed
-            Ok(result) => Ok(parse_revm_execution(result, session_input, session_storage_changes)),
+            Ok(result) => Ok(parse_revm_execution(result, session_input, session_storage_changes, internal_transfers)),
 
             // nonce errors
             Err(EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx, state })) => Err(StratusError::TransactionNonce {
@@ -182,6 +241,7 @@ impl Evm {
         execution.map(|execution| EvmExecutionResult {
             execution,
             metrics: session_metrics,
+            banned_opcodes,
         })
     }
 }
@@ -234,9 +294,12 @@ impl Database for RevmSession {
     fn basic(&mut self, revm_address: RevmAddress) -> Result<Option<AccountInfo>, StratusError> {
         self.metrics.account_reads += 1;
 
-        // retrieve account
+        // retrieve account, giving preference to changes of previous executions
         let address: Address = revm_address.into();
-        let account = self.storage.read_account(address, self.input.point_in_time)?;
+        let account = match self.input.overlay.get(&address) {
+            Some(changes) => changes.to_account(),
+            None => self.storage.read_account(address, self.input.point_in_time)?,
+        };
 
         // warn if the loaded account is the `to` account and it does not have a bytecode
         if let Some(ref to_address) = self.input.to {
@@ -272,8 +335,16 @@ impl Database for RevmSession {
         let address: Address = revm_address.into();
         let index: SlotIndex = revm_index.into();
 
-        // load slot from storage
-        let slot = self.storage.read_slot(address, index, self.input.point_in_time)?;
+        // load slot from previous executions or from storage
+        let overlay_slot = self
+            .input
+            .overlay
+            .get(&address)
+            .and_then(|changes| changes.slots.get(&index)?.take_ref().copied());
+        let slot = match overlay_slot {
+            Some(slot) => slot,
+            None => self.storage.read_slot(address, index, self.input.point_in_time)?,
+        };
 
         // track original value, except if ignored address
         if not(address.is_ignored()) {
@@ -303,7 +374,12 @@ impl Database for RevmSession {
 // Conversion
 // -----------------------------------------------------------------------------
 
-fn parse_revm_execution(revm_result: RevmResultAndState, input: EvmInput, execution_changes: ExecutionChanges) -> EvmExecution {
+fn parse_revm_execution(
+    revm_result: RevmResultAndState,
+    input: EvmInput,
+    execution_changes: ExecutionChanges,
+    internal_transfers: Vec<InternalTransfer>,
+) -> EvmExecution {
     let (result, tx_output, logs, gas) = parse_revm_result(revm_result.result);
     let changes = parse_revm_state(revm_result.state, execution_changes);
 
@@ -324,6 +400,7 @@ fn parse_revm_execution(revm_result: RevmResultAndState, input: EvmInput, execut
         gas,
         changes,
         deployed_contract_address,
+        internal_transfers,
     }
 }
 
@@ -394,3 +471,319 @@ fn parse_revm_state(revm_state: RevmState, mut execution_changes: ExecutionChang
     }
     execution_changes
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "rocks")]
+    use std::time::Duration;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::eth::primitives::BannedOpcode;
+    use crate::eth::primitives::ChainId;
+    use crate::eth::primitives::CodeHash;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::InternalTransferKind;
+    use crate::eth::primitives::PointInTime;
+    use crate::eth::primitives::TransactionInput;
+    use crate::eth::primitives::Wei;
+    #[cfg(feature = "rocks")]
+    use crate::eth::storage::permanent::RocksPermanentStorage;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::PermanentStorage;
+    use crate::eth::storage::Storage;
+
+    const SENDER: Address = Address::new([1; 20]);
+    const RECIPIENT: Address = Address::new([2; 20]);
+    const TREASURY: Address = Address::new([3; 20]);
+    const SENDER_BALANCE: u64 = 1_000_000_000;
+    const VALUE: u64 = 1_000;
+    const GAS_PRICE: u64 = 10;
+    const TRANSFER_GAS: u64 = 21_000;
+
+    /// Executes a transfer with the given executor options and returns the sender balance after it and the execution.
+    fn execute_transfer(perm: Box<dyn PermanentStorage>, args: &[&str]) -> (Wei, EvmExecution) {
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = Arc::new(StratusStorage::new(temp, perm, None).unwrap());
+        storage
+            .save_accounts(vec![Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE))])
+            .unwrap();
+
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"].iter().chain(args));
+        let gas_mode = config.executor_gas_mode;
+        let mut evm = Evm::new(Arc::clone(&storage), config);
+
+        let tx = TransactionInput {
+            chain_id: Some(ChainId::from(2008u64)),
+            signer: SENDER,
+            from: SENDER,
+            to: Some(RECIPIENT),
+            value: Wei::from(VALUE),
+            gas_limit: Gas::from(100_000u64),
+            gas_price: Wei::from(GAS_PRICE),
+            ..Default::default()
+        };
+        let input = EvmInput::from_eth_transaction(&tx, &storage.read_pending_block_header(), gas_mode);
+        let execution = evm.execute(input).unwrap().execution;
+
+        let sender_balance = *execution.changes[&SENDER].balance.take_modified_ref().unwrap();
+        (sender_balance, execution)
+    }
+
+    /// Executes a contract deployment whose constructor returns `code_size` bytes of code.
+    fn execute_deploy(args: &[&str], code_size: u16, padding: usize) -> EvmExecution {
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = Arc::new(StratusStorage::new(temp, Box::new(InMemoryPermanentStorage::default()), None).unwrap());
+        storage
+            .save_accounts(vec![Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE))])
+            .unwrap();
+
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"].iter().chain(args));
+        let mut evm = Evm::new(Arc::clone(&storage), config);
+
+        // PUSH2 code_size, PUSH1 0, RETURN, followed by unreachable padding to increase the initcode size
+        let [size_hi, size_lo] = code_size.to_be_bytes();
+        let mut initcode = vec![0x61, size_hi, size_lo, 0x60, 0x00, 0xf3];
+        initcode.extend(vec![0; padding]);
+
+        let input = EvmInput {
+            from: SENDER,
+            to: None,
+            data: initcode.into(),
+            gas_limit: Gas::from(10_000_000u64),
+            block_number: 1.into(),
+            point_in_time: PointInTime::Pending,
+            ..Default::default()
+        };
+        evm.execute(input).unwrap().execution
+    }
+
+    fn perm_storages() -> Vec<(Box<dyn PermanentStorage>, Option<tempfile::TempDir>)> {
+        #[allow(unused_mut)]
+        let mut storages: Vec<(Box<dyn PermanentStorage>, Option<tempfile::TempDir>)> = vec![(Box::new(InMemoryPermanentStorage::default()), None)];
+
+        #[cfg(feature = "rocks")]
+        {
+            let dir = tempfile::tempdir().unwrap();
+            let prefix = dir.path().join("test").to_string_lossy().into_owned();
+            let rocks = RocksPermanentStorage::new(Some(prefix), Duration::from_secs(240), None, false, false, false).unwrap();
+            storages.push((Box::new(rocks), Some(dir)));
+        }
+
+        storages
+    }
+
+    #[test]
+    fn test_gas_free_meters_gas_without_charging_sender() {
+        for (perm, _dir) in perm_storages() {
+            let (sender_balance, execution) = execute_transfer(perm, &["--executor-gas-mode", "free"]);
+            assert_eq!(execution.gas, Gas::from(TRANSFER_GAS));
+            assert_eq!(sender_balance, Wei::from(SENDER_BALANCE - VALUE));
+        }
+    }
+
+    #[test]
+    fn test_gas_charged_charges_sender_and_burns_fees() {
+        for (perm, _dir) in perm_storages() {
+            let (sender_balance, execution) = execute_transfer(perm, &["--executor-gas-mode", "charged"]);
+            assert_eq!(execution.gas, Gas::from(TRANSFER_GAS));
+            assert_eq!(sender_balance, Wei::from(SENDER_BALANCE - VALUE - TRANSFER_GAS * GAS_PRICE));
+            assert!(execution
+                .changes
+                .get(&Address::COINBASE)
+                .map_or(true, |changes| not(changes.balance.is_modified())));
+        }
+    }
+    #[test]
+    fn test_gas_charged_credits_fees_to_treasury() {
+        let treasury = TREASURY.to_string();
+        for (perm, _dir) in perm_storages() {
+            let (sender_balance, execution) = execute_transfer(perm, &["--executor-gas-mode", "charged", "--executor-fee-treasury", &treasury]);
+            assert_eq!(sender_balance, Wei::from(SENDER_BALANCE - VALUE - TRANSFER_GAS * GAS_PRICE));
+
+            let treasury_balance = execution.changes[&TREASURY].balance.take_modified_ref().copied();
+            assert_eq!(treasury_balance, Some(Wei::from(TRANSFER_GAS * GAS_PRICE)));
+        }
+    }
+
+    #[test]
+    fn test_code_size_limit_defaults_to_eip170() {
+        let execution = execute_deploy(&[], 24_576, 0);
+        assert!(execution.is_success());
+
+        let execution = execute_deploy(&[], 24_577, 0);
+        assert!(execution.is_failure());
+    }
+
+    #[test]
+    fn test_code_size_limit_can_be_increased() {
+        let execution = execute_deploy(&["--executor-max-code-size", "30000"], 30_000, 0);
+        assert!(execution.is_success());
+    }
+
+    #[test]
+    fn test_code_size_limit_fails_execution() {
+        // within the limit
+        let execution = execute_deploy(&["--executor-max-code-size", "100"], 100, 0);
+        assert!(execution.is_success());
+
+        // exceeding the limit fails the execution instead of returning an error, so the transaction is still mined
+        let execution = execute_deploy(&["--executor-max-code-size", "100"], 101, 0);
+        assert!(execution.is_failure());
+        assert!(execution.deployed_contract_address.is_none());
+    }
+
+    #[test]
+    fn test_initcode_size_limit_is_not_enforced_by_evm() {
+        // imported transactions execute through the evm directly and must not be rejected by the limit of local transactions
+        let execution = execute_deploy(&["--executor-max-initcode-size", "10"], 10, 100);
+        assert!(execution.is_success());
+    }
+
+    #[test]
+    fn test_blob_hashes_do_not_fail_execution() {
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = Arc::new(StratusStorage::new(temp, Box::new(InMemoryPermanentStorage::default()), None).unwrap());
+        storage
+            .save_accounts(vec![Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE))])
+            .unwrap();
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"]);
+        let mut evm = Evm::new(Arc::clone(&storage), config);
+
+        // imported blob transactions carry their blob hashes and are not charged for blob gas
+        let input = EvmInput {
+            from: SENDER,
+            to: Some(RECIPIENT),
+            value: Wei::from(VALUE),
+            gas_limit: Gas::from(100_000u64),
+            block_number: 1.into(),
+            point_in_time: PointInTime::Pending,
+            blob_hashes: vec![Hash::new([1; 32])],
+            ..Default::default()
+        };
+        let execution = evm.execute(input).unwrap().execution;
+        assert!(execution.is_success());
+
+        let sender_balance = *execution.changes[&SENDER].balance.take_modified_ref().unwrap();
+        assert_eq!(sender_balance, Wei::from(SENDER_BALANCE - VALUE));
+    }
+
+    #[test]
+    fn test_call_with_value_records_internal_transfer() {
+        const FORWARDER: Address = Address::new([4; 20]);
+
+        // forwards the received value to the recipient: CALL(gas, RECIPIENT, CALLVALUE, 0, 0, 0, 0)
+        let mut code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x34, 0x73];
+        code.extend(RECIPIENT.as_bytes());
+        code.extend([0x5a, 0xf1, 0x00]);
+        let code = Bytes::from(code);
+
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = Arc::new(StratusStorage::new(temp, Box::new(InMemoryPermanentStorage::default()), None).unwrap());
+        let forwarder = Account {
+            bytecode: Some(code.clone()),
+            code_hash: CodeHash::from_bytecode(Some(code)),
+            ..Account::new_empty(FORWARDER)
+        };
+        storage
+            .save_accounts(vec![Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE)), forwarder])
+            .unwrap();
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008", "--executor-internal-transfers"]);
+        let mut evm = Evm::new(Arc::clone(&storage), config);
+
+        let input = EvmInput {
+            from: SENDER,
+            to: Some(FORWARDER),
+            value: Wei::from(VALUE),
+            gas_limit: Gas::from(100_000u64),
+            block_number: 1.into(),
+            point_in_time: PointInTime::Pending,
+            ..Default::default()
+        };
+        let execution = evm.execute(input).unwrap().execution;
+        assert!(execution.is_success());
+
+        // the transaction value itself is not an internal transfer, only the value forwarded by the contract
+        let expected = InternalTransfer {
+            kind: InternalTransferKind::Call,
+            from: FORWARDER,
+            to: RECIPIENT,
+            value: Wei::from(VALUE),
+            depth: 1,
+        };
+        assert_eq!(execution.internal_transfers, vec![expected]);
+        assert_eq!(execution.changes[&RECIPIENT].balance.take_modified_ref(), Some(&Wei::from(VALUE)));
+    }
+
+    #[test]
+    fn test_opcode_tracer_tracks_banned_opcodes_of_called_contracts() {
+        const ENTRY_POINT: Address = Address::new([5; 20]);
+        const ACCOUNT: Address = Address::new([6; 20]);
+
+        // CALL(GAS, to, 0, 0, 0, 0, 0)
+        let call = |to: Address| {
+            let mut code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+            code.extend(to.as_bytes());
+            code.extend([0x5a, 0xf1, 0x50]);
+            code
+        };
+        let contract = |address: Address, code: Vec<u8>| {
+            let code = Bytes::from(code);
+            Account {
+                bytecode: Some(code.clone()),
+                code_hash: CodeHash::from_bytecode(Some(code)),
+                ..Account::new_empty(address)
+            }
+        };
+
+        // entry point: TIMESTAMP, then calls the account
+        let entry_point_code = [vec![0x42, 0x50], call(ACCOUNT), vec![0x00]].concat();
+
+        // account: TIMESTAMP, GAS followed by a call, then GAS not followed by a call
+        let account_code = [vec![0x42, 0x50], call(RECIPIENT), vec![0x5a, 0x50, 0x00]].concat();
+
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = Arc::new(StratusStorage::new(temp, Box::new(InMemoryPermanentStorage::default()), None).unwrap());
+        storage
+            .save_accounts(vec![
+                Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE)),
+                contract(ENTRY_POINT, entry_point_code),
+                contract(ACCOUNT, account_code),
+            ])
+            .unwrap();
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"]);
+
+        let input = EvmInput {
+            from: SENDER,
+            to: Some(ENTRY_POINT),
+            gas_limit: Gas::from(100_000u64),
+            block_number: 1.into(),
+            point_in_time: PointInTime::Pending,
+            ..Default::default()
+        };
+
+        // opcodes of the entry point itself and GAS followed by a call are allowed
+        let mut evm = Evm::new_with_opcode_tracer(Arc::clone(&storage), config.clone());
+        let result = evm.execute(input.clone()).unwrap();
+        assert!(result.execution.is_success());
+        let banned = |opcode: &str| BannedOpcode {
+            address: ACCOUNT,
+            depth: 2,
+            opcode: opcode.to_owned(),
+        };
+        assert_eq!(result.banned_opcodes, vec![banned("TIMESTAMP"), banned("GAS")]);
+
+        // the tracer is reset between executions
+        let result = evm.execute(input.clone()).unwrap();
+        assert_eq!(result.banned_opcodes.len(), 2);
+
+        // opcodes are not tracked without the tracer
+        let mut evm = Evm::new(Arc::clone(&storage), config);
+        assert!(evm.execute(input).unwrap().banned_opcodes.is_empty());
+    }
+}
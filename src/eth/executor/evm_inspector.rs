@@ -0,0 +1,69 @@
+use revm::interpreter::CallInputs;
+use revm::interpreter::CallOutcome;
+use revm::interpreter::CreateInputs;
+use revm::interpreter::CreateOutcome;
+use revm::interpreter::Interpreter;
+use revm::primitives::U256;
+use revm::Database;
+use revm::EvmContext;
+use revm::Inspector;
+
+use crate::alias::RevmAddress;
+use crate::eth::executor::OpcodeTracer;
+use crate::eth::executor::TransferTracer;
+
+/// Tracers registered as the EVM inspector. Each tracer is enabled independently.
+#[derive(Default)]
+pub struct EvmInspector {
+    pub opcodes: Option<OpcodeTracer>,
+    pub transfers: Option<TransferTracer>,
+}
+
+impl EvmInspector {
+    /// Checks if at least one tracer is enabled, so the inspector must be registered.
+    pub fn is_enabled(&self) -> bool {
+        self.opcodes.is_some() || self.transfers.is_some()
+    }
+}
+
+impl<DB: Database> Inspector<DB> for EvmInspector {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if let Some(ref mut opcodes) = self.opcodes {
+            opcodes.step(interp, context);
+        }
+    }
+
+    fn call(&mut self, context: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        match self.transfers {
+            Some(ref mut transfers) => transfers.call(context, inputs),
+            None => None,
+        }
+    }
+
+    fn call_end(&mut self, context: &mut EvmContext<DB>, inputs: &CallInputs, outcome: CallOutcome) -> CallOutcome {
+        match self.transfers {
+            Some(ref mut transfers) => transfers.call_end(context, inputs, outcome),
+            None => outcome,
+        }
+    }
+
+    fn create(&mut self, context: &mut EvmContext<DB>, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        match self.transfers {
+            Some(ref mut transfers) => transfers.create(context, inputs),
+            None => None,
+        }
+    }
+
+    fn create_end(&mut self, context: &mut EvmContext<DB>, inputs: &CreateInputs, outcome: CreateOutcome) -> CreateOutcome {
+        match self.transfers {
+            Some(ref mut transfers) => transfers.create_end(context, inputs, outcome),
+            None => outcome,
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: RevmAddress, target: RevmAddress, value: U256) {
+        if let Some(ref mut transfers) = self.transfers {
+            Inspector::<DB>::selfdestruct(transfers, contract, target, value);
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use std::cmp::max;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::mem;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::thread;
+#[cfg(feature = "metrics")]
+use std::time::Duration;
 
 use anyhow::anyhow;
 use cfg_if::cfg_if;
+use itertools::Itertools;
 use parking_lot::Mutex;
 use tracing::info_span;
 use tracing::Span;
@@ -12,26 +18,33 @@ use tracing::Span;
 #[cfg(feature = "metrics")]
 use crate::eth::codegen;
 use crate::eth::executor::Evm;
-use crate::eth::executor::EvmExecutionResult;
-use crate::eth::executor::EvmInput;
 use crate::eth::executor::ExecutorConfig;
 use crate::eth::miner::Miner;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::CallInput;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
+use crate::eth::primitives::EvmExecutionResult;
+use crate::eth::primitives::EvmInput;
+use crate::eth::primitives::ExecutionChanges;
+use crate::eth::primitives::ExecutionConflict;
+use crate::eth::primitives::ExecutionValueChange;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalReceipts;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::ExternalTransactionExecution;
+use crate::eth::primitives::GasMode;
+use crate::eth::primitives::Nonce;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionInput;
 use crate::eth::primitives::UnixTime;
-use crate::eth::storage::Storage;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::StorageReader;
 use crate::eth::storage::StratusStorage;
 use crate::ext::spawn_thread;
 use crate::ext::to_json_string;
@@ -41,6 +54,7 @@ use crate::ext::OptionExt;
 use crate::infra::metrics;
 use crate::infra::tracing::warn_task_tx_closed;
 use crate::infra::tracing::SpanExt;
+use crate::log_and_err;
 use crate::GlobalState;
 
 // -----------------------------------------------------------------------------
@@ -84,14 +98,20 @@ struct Evms {
 
     /// Pool for parallel execution of calls (eth_call and eth_estimateGas) reading from past state. Usually contains multiple EVMs.
     pub call_past: crossbeam_channel::Sender<EvmTask>,
+
+    /// Pool for execution of account abstraction validation simulations tracking banned opcodes. Usually contains a single EVM.
+    pub call_validation: crossbeam_channel::Sender<EvmTask>,
 }
 
 impl Evms {
     /// Spawns EVM tasks in background.
     fn spawn(storage: Arc<StratusStorage>, config: &ExecutorConfig) -> Self {
         // function executed by evm threads
-        fn evm_loop(task_name: &str, storage: Arc<StratusStorage>, config: ExecutorConfig, task_rx: crossbeam_channel::Receiver<EvmTask>) {
-            let mut evm = Evm::new(storage, config);
+        fn evm_loop(task_name: &str, storage: Arc<StratusStorage>, config: ExecutorConfig, trace_opcodes: bool, task_rx: crossbeam_channel::Receiver<EvmTask>) {
+            let mut evm = match trace_opcodes {
+                true => Evm::new_with_opcode_tracer(storage, config),
+                false => Evm::new(storage, config),
+            };
 
             // keep executing transactions until the channel is closed
             while let Ok(task) = task_rx.recv() {
@@ -111,7 +131,7 @@ impl Evms {
         }
 
         // function that spawn evm threads
-        let spawn_evms = |task_name: &str, num_evms: usize| {
+        let spawn_evms = |task_name: &str, num_evms: usize, trace_opcodes: bool| {
             let (evm_tx, evm_rx) = crossbeam_channel::unbounded::<EvmTask>();
 
             for evm_index in 1..=num_evms {
@@ -121,20 +141,21 @@ impl Evms {
                 let evm_rx = evm_rx.clone();
                 let thread_name = evm_task_name.clone();
                 spawn_thread(&thread_name, move || {
-                    evm_loop(&evm_task_name, evm_storage, evm_config, evm_rx);
+                    evm_loop(&evm_task_name, evm_storage, evm_config, trace_opcodes, evm_rx);
                 });
             }
             evm_tx
         };
 
         let tx_parallel = match config.executor_strategy {
-            ExecutorStrategy::Serial => spawn_evms("evm-tx-unused", 1), // should not really be used if strategy is serial, but keep 1 for fallback
-            ExecutorStrategy::Paralell => spawn_evms("evm-tx-parallel", config.executor_evms),
+            ExecutorStrategy::Serial => spawn_evms("evm-tx-unused", 1, false), // should not really be used if strategy is serial, but keep 1 for fallback
+            ExecutorStrategy::Paralell => spawn_evms("evm-tx-parallel", config.executor_evms, false),
         };
-        let tx_serial = spawn_evms("evm-tx-serial", 1);
-        let tx_external = spawn_evms("evm-tx-external", 1);
-        let call_present = spawn_evms("evm-call-present", max(config.executor_evms / 2, 1));
-        let call_past = spawn_evms("evm-call-past", max(config.executor_evms / 4, 1));
+        let tx_serial = spawn_evms("evm-tx-serial", 1, false);
+        let tx_external = spawn_evms("evm-tx-external", 1, false);
+        let call_present = spawn_evms("evm-call-present", max(config.executor_evms / 2, 1), false);
+        let call_past = spawn_evms("evm-call-past", max(config.executor_evms / 4, 1), false);
+        let call_validation = spawn_evms("evm-call-validation", 1, true);
 
         Evms {
             tx_parallel,
@@ -142,6 +163,7 @@ impl Evms {
             tx_external,
             call_present,
             call_past,
+            call_validation,
         }
     }
 
@@ -156,6 +178,7 @@ impl Evms {
             EvmRoute::External => self.tx_external.send(task),
             EvmRoute::CallPresent => self.call_present.send(task),
             EvmRoute::CallPast => self.call_past.send(task),
+            EvmRoute::CallValidation => self.call_validation.send(task),
         };
 
         match execution_rx.recv() {
@@ -181,6 +204,9 @@ pub enum EvmRoute {
 
     #[strum(to_string = "call_past")]
     CallPast,
+
+    #[strum(to_string = "call_validation")]
+    CallValidation,
 }
 
 // -----------------------------------------------------------------------------
@@ -223,6 +249,21 @@ impl Executor {
         }
     }
 
+    /// How transactions pay for the gas they use.
+    pub fn gas_mode(&self) -> GasMode {
+        self.config.executor_gas_mode
+    }
+
+    /// Max size of deployed contracts code.
+    pub fn max_code_size(&self) -> usize {
+        self.config.executor_max_code_size
+    }
+
+    /// Max size of contract deployment transactions data received through `eth_sendRawTransaction`.
+    pub fn max_initcode_size(&self) -> usize {
+        self.config.executor_max_initcode_size
+    }
+
     // -------------------------------------------------------------------------
     // External transactions
     // -------------------------------------------------------------------------
@@ -230,10 +271,10 @@ impl Executor {
     /// Reexecutes an external block locally and imports it to the temporary storage.
     ///
     /// Returns the remaining receipts that were not consumed by the execution.
-    pub fn execute_external_block(&self, mut block: ExternalBlock, mut receipts: ExternalReceipts) -> anyhow::Result<()> {
+    pub fn execute_external_block(&self, mut block: ExternalBlock, mut receipts: ExternalReceipts) -> Result<(), StratusError> {
         // track
         #[cfg(feature = "metrics")]
-        let (start, mut block_metrics) = (metrics::now(), EvmExecutionMetrics::default());
+        let (start, mut block_metrics, mut block_evm_time) = (metrics::now(), EvmExecutionMetrics::default(), Duration::ZERO);
 
         #[cfg(feature = "tracing")]
         let _span = info_span!("executor::external_block", block_number = %block.number()).entered();
@@ -244,6 +285,11 @@ impl Executor {
         let block_timestamp = block.timestamp();
         let block_transactions = mem::take(&mut block.transactions);
 
+        // verify senders of all transactions in parallel before executing any of them
+        if self.config.executor_sender_recovery_threads > 0 {
+            verify_external_senders(&block_transactions, self.config.executor_sender_recovery_threads)?;
+        }
+
         // determine how to execute each transaction
         for tx in block_transactions {
             let receipt = receipts.try_remove(tx.hash())?;
@@ -254,6 +300,8 @@ impl Executor {
                 block_timestamp,
                 #[cfg(feature = "metrics")]
                 &mut block_metrics,
+                #[cfg(feature = "metrics")]
+                &mut block_evm_time,
             )?;
         }
 
@@ -263,11 +311,103 @@ impl Executor {
             metrics::inc_executor_external_block(start.elapsed());
             metrics::inc_executor_external_block_account_reads(block_metrics.account_reads);
             metrics::inc_executor_external_block_slot_reads(block_metrics.slot_reads);
+            metrics::inc_block_evm_execution(block_evm_time, self.storage.perm_kind().to_string());
         }
 
         Ok(())
     }
 
+    /// Applies the executions of an external block sent by a Stratus leader without reexecuting its transactions.
+    ///
+    /// When `spot_check` is set, transactions are also executed locally and the block is rejected if their state changes diverge from the
+    /// leader's, verifying that the follower state did not drift from the leader state.
+    pub fn apply_external_block(
+        &self,
+        mut block: ExternalBlock,
+        mut receipts: ExternalReceipts,
+        executions: Vec<EvmExecution>,
+        spot_check: bool,
+    ) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = info_span!("executor::apply_external_block", block_number = %block.number()).entered();
+        tracing::info!(block_number = %block.number(), %spot_check, "applying external block executions");
+
+        let block_number = block.number();
+        let block_timestamp = block.timestamp();
+        let block_transactions = mem::take(&mut block.transactions);
+        if block_transactions.len() != executions.len() {
+            return log_and_err!(format!(
+                "executions length mismatch | block={} transactions={} executions={}",
+                block_number,
+                block_transactions.len(),
+                executions.len()
+            ))
+            .map_err(Into::into);
+        }
+
+        for (tx, mut execution) in block_transactions.into_iter().zip(executions) {
+            let receipt = receipts.try_remove(tx.hash())?;
+
+            // verify changes against a local reexecution
+            if spot_check {
+                self.spot_check_external_transaction(&tx, &receipt, &execution, block_number, block_timestamp)?;
+            }
+
+            // apply receipt the same way as a reexecuted transaction and ensure they match before saving
+            execution.apply_receipt(&receipt)?;
+            execution.compare_with_receipt(&receipt)?;
+
+            let evm_result = EvmExecutionResult {
+                execution,
+                metrics: EvmExecutionMetrics::default(),
+                banned_opcodes: Vec::new(),
+            };
+            let tx_execution = TransactionExecution::External(ExternalTransactionExecution::new(tx, receipt, evm_result));
+            self.miner.save_execution(tx_execution, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compares the changes of a transaction sent by a Stratus leader with the changes produced locally.
+    ///
+    /// Successful transactions are reexecuted and all changes are compared. Failed transactions are recreated from the receipt like in
+    /// reexecution, so only the nonces and balances they modify are compared, and the leader must not have modified any slot or bytecode.
+    fn spot_check_external_transaction(
+        &self,
+        tx: &ExternalTransaction,
+        receipt: &ExternalReceipt,
+        leader_execution: &EvmExecution,
+        block_number: BlockNumber,
+        block_timestamp: UnixTime,
+    ) -> Result<(), StratusError> {
+        let (diverged, local_changes) = match receipt.is_success() {
+            true => {
+                let evm_input = EvmInput::from_external(tx, receipt, block_number, block_timestamp)?;
+                let local_changes = self.evms.execute(evm_input, EvmRoute::External)?.execution.changes;
+                (local_changes != leader_execution.changes, local_changes)
+            }
+            false => {
+                let sender = self.storage.read_account(receipt.from.into(), PointInTime::Pending)?;
+                let local_changes = EvmExecution::from_failed_external_transaction(sender, receipt, block_timestamp)?.changes;
+                let diverged = failed_tx_modifications(&local_changes) != failed_tx_modifications(&leader_execution.changes)
+                    || leader_execution
+                        .changes
+                        .values()
+                        .any(|changes| changes.bytecode.is_modified() || changes.slots.values().any(ExecutionValueChange::is_modified));
+                (diverged, local_changes)
+            }
+        };
+
+        if diverged {
+            let json_leader_changes = to_json_string(&leader_execution.changes);
+            let json_local_changes = to_json_string(&local_changes);
+            tracing::error!(%block_number, tx_hash = %tx.hash(), %json_leader_changes, %json_local_changes, "spot check diverged from leader changes");
+            return log_and_err!(format!("state changes mismatch | hash={}", tx.hash())).map_err(Into::into);
+        }
+        Ok(())
+    }
+
     /// Reexecutes an external transaction locally ensuring it produces the same output.
     ///
     /// This function wraps `reexecute_external_tx_inner` and returns back the payload
@@ -279,6 +419,7 @@ impl Executor {
         block_number: BlockNumber,
         block_timestamp: UnixTime,
         #[cfg(feature = "metrics")] block_metrics: &mut EvmExecutionMetrics,
+        #[cfg(feature = "metrics")] block_evm_time: &mut Duration,
     ) -> anyhow::Result<()> {
         // track
         #[cfg(feature = "metrics")]
@@ -291,6 +432,9 @@ impl Executor {
         #[cfg(feature = "tracing")]
         let _span = info_span!("executor::external_transaction", tx_hash = %tx.hash).entered();
         tracing::info!(%block_number, tx_hash = %tx.hash(), "reexecuting external transaction");
+        if tx.is_blob() {
+            tracing::debug!(tx_hash = %tx.hash(), blobs = %tx.blob_versioned_hashes().len(), "reexecuting blob transaction without blob verification");
+        }
 
         // when transaction externally failed, create fake transaction instead of reexecuting
         let tx_execution = match receipt.is_success() {
@@ -298,7 +442,18 @@ impl Executor {
             true => {
                 // re-execute transaction
                 let evm_input = EvmInput::from_external(&tx, &receipt, block_number, block_timestamp)?;
+                #[cfg(feature = "metrics")]
+                let evm_start = metrics::now();
                 let evm_execution = self.evms.execute(evm_input, EvmRoute::External);
+                #[cfg(feature = "metrics")]
+                {
+                    let evm_time = evm_start.elapsed();
+                    *block_evm_time += evm_time;
+                    if let Ok(ref evm_execution) = evm_execution {
+                        let gas_per_second = evm_execution.execution.gas.as_u64() as f64 / evm_time.as_secs_f64().max(f64::EPSILON);
+                        metrics::inc_block_transaction_gas_per_second(gas_per_second as usize, self.storage.perm_kind().to_string());
+                    }
+                }
 
                 // handle re-execution result
                 let mut evm_execution = match evm_execution {
@@ -333,6 +488,7 @@ impl Executor {
                 let evm_result = EvmExecutionResult {
                     execution,
                     metrics: EvmExecutionMetrics::default(),
+                    banned_opcodes: Vec::new(),
                 };
                 ExternalTransactionExecution::new(tx, receipt, evm_result)
             }
@@ -410,12 +566,11 @@ impl Executor {
                 let parallel_attempt = self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Parallel, 1);
                 match parallel_attempt {
                     Ok(tx_execution) => Ok(tx_execution),
-                    Err(e) =>
-                        if let StratusError::TransactionConflict(_) = e {
-                            self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Serial, INFINITE_ATTEMPTS)
-                        } else {
-                            Err(e)
-                        },
+                    Err(e) => match e {
+                        StratusError::TransactionConflict(_) => self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Serial, INFINITE_ATTEMPTS),
+                        e if e.is_block_limit_reached() => self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Serial, INFINITE_ATTEMPTS),
+                        e => Err(e),
+                    },
                 }
             }
         };
@@ -427,6 +582,8 @@ impl Executor {
     }
 
     /// Executes a transaction until it reaches the max number of attempts.
+    ///
+    /// Conflicts do not count as attempts: they are re-executed until `executor_conflict_retries` is reached.
     fn execute_local_transaction_attempts(&self, tx_input: TransactionInput, evm_route: EvmRoute, max_attempts: usize) -> Result<(), StratusError> {
         // validate
         if tx_input.signer.is_zero() {
@@ -435,6 +592,8 @@ impl Executor {
 
         // executes transaction until no more conflicts
         let mut attempt = 0;
+        let mut conflict_retries = 0;
+        let mut pending_pool_ticket = None;
         loop {
             attempt += 1;
 
@@ -454,7 +613,7 @@ impl Executor {
 
             // prepare evm input
             let pending_header = self.storage.read_pending_block_header();
-            let evm_input = EvmInput::from_eth_transaction(&tx_input, &pending_header);
+            let evm_input = EvmInput::from_eth_transaction(&tx_input, &pending_header, self.config.executor_gas_mode);
 
             // execute transaction in evm (retry only in case of conflict, but do not retry on other failures)
             tracing::info!(
@@ -494,19 +653,39 @@ impl Executor {
                         metrics::inc_executor_local_transaction_account_reads(tx_metrics.account_reads, contract, function);
                         metrics::inc_executor_local_transaction_slot_reads(tx_metrics.slot_reads, contract, function);
                         metrics::inc_executor_local_transaction_gas(gas_used.as_u64() as usize, true, contract, function);
+                        metrics::inc_executor_local_transaction_conflict_retries(conflict_retries, evm_route.to_string(), true);
                     }
                     return Ok(());
                 }
                 Err(e) => match e {
+                    // re-execute over the state that includes the conflicting transactions
                     StratusError::TransactionConflict(ref conflicts) => {
-                        tracing::warn!(%attempt, ?conflicts, "temporary storage conflict detected when saving execution");
+                        tracing::warn!(%attempt, %conflict_retries, ?conflicts, "temporary storage conflict detected when saving execution");
+                        for conflict in conflicts.iter() {
+                            trace_conflict(&tx_input, conflict);
+                        }
+                        if conflict_retries >= self.config.executor_conflict_retries {
+                            #[cfg(feature = "metrics")]
+                            metrics::inc_executor_local_transaction_conflict_retries(conflict_retries, evm_route.to_string(), false);
+                            return Err(e);
+                        }
+                        conflict_retries += 1;
+                        continue;
+                    }
+                    _ if e.is_block_limit_reached() => {
+                        tracing::warn!(%attempt, block_number = %pending_header.number, reason = ?e, "block limit reached when saving execution");
                         if attempt >= max_attempts {
                             return Err(e);
                         }
+
+                        // wait for its turn to be included in a new block
+                        let ticket = pending_pool_ticket.get_or_insert_with(|| self.miner.pending_pool.enqueue(&tx_input));
+                        self.miner.wait_block_space(ticket, pending_header.number)?;
                         continue;
                     }
-                    StratusError::TransactionEvmInputMismatch { ref expected, ref actual } => {
-                        tracing::warn!(?expected, ?actual, "evm input and block header mismatch");
+                    // re-execute when a component or the pending block changed while executing
+                    _ if e.is_transient() => {
+                        tracing::warn!(%attempt, reason = ?e, "transient failure when executing transaction");
                         if attempt >= max_attempts {
                             return Err(e);
                         }
@@ -519,8 +698,97 @@ impl Executor {
     }
 
     /// Executes a transaction without persisting state changes.
-    #[tracing::instrument(name = "executor::local_call", skip_all, fields(from, to))]
     pub fn execute_local_call(&self, call_input: CallInput, point_in_time: PointInTime) -> Result<EvmExecution, StratusError> {
+        self.execute_local_call_with_overlay(call_input, None, point_in_time, ExecutionChanges::default())
+    }
+
+    /// Executes multiple transactions sequentially without persisting state changes.
+    ///
+    /// If `apply_changes` is set, the state changes of each successful call are visible to the next calls.
+    pub fn execute_local_calls(
+        &self,
+        calls: Vec<CallInput>,
+        point_in_time: PointInTime,
+        apply_changes: bool,
+    ) -> Result<Vec<Result<EvmExecution, StratusError>>, StratusError> {
+        let calls = calls.into_iter().map(|call_input| (call_input, None)).collect_vec();
+        self.execute_local_sequence(calls, point_in_time, apply_changes)
+    }
+
+    /// Executes signed transactions sequentially without persisting state changes.
+    ///
+    /// Nonces, chain ids, gas limits and gas prices are applied like when the transactions are sent with `eth_sendRawTransaction`. The state changes of
+    /// each successful transaction are visible to the next transactions.
+    pub fn simulate_local_transactions(
+        &self,
+        txs: Vec<TransactionInput>,
+        point_in_time: PointInTime,
+    ) -> Result<Vec<Result<EvmExecution, StratusError>>, StratusError> {
+        let calls = txs
+            .into_iter()
+            .map(|tx| {
+                let call_input = CallInput {
+                    from: Some(tx.signer),
+                    to: tx.to,
+                    value: tx.value,
+                    data: tx.input.clone(),
+                };
+                (call_input, Some(tx))
+            })
+            .collect_vec();
+        self.execute_local_sequence(calls, point_in_time, true)
+    }
+
+    /// Executes calls sequentially reading from the same mined block, so new blocks mined during the execution are not visible.
+    ///
+    /// Failed calls are returned with their errors and the next calls are still executed, except when the error is internal.
+    #[tracing::instrument(name = "executor::local_sequence", skip_all, fields(calls))]
+    fn execute_local_sequence(
+        &self,
+        calls: Vec<(CallInput, Option<TransactionInput>)>,
+        point_in_time: PointInTime,
+        apply_changes: bool,
+    ) -> Result<Vec<Result<EvmExecution, StratusError>>, StratusError> {
+        Span::with(|s| s.rec_str("calls", &calls.len()));
+
+        // pin the snapshot to the current mined block
+        let point_in_time = match point_in_time {
+            PointInTime::Mined => PointInTime::MinedPast(self.storage.read_mined_block_number()?),
+            point_in_time => point_in_time,
+        };
+
+        let mut overlay = ExecutionChanges::default();
+        let mut executions = Vec::with_capacity(calls.len());
+        for (call_input, tx) in calls {
+            let execution = match self.execute_local_call_with_overlay(call_input, tx.as_ref(), point_in_time, overlay.clone()) {
+                Err(e) if e.is_fatal() => return Err(e),
+                execution => execution,
+            };
+            if let Ok(ref execution) = execution {
+                if apply_changes && execution.is_success() {
+                    for (address, changes) in execution.changes.clone() {
+                        match overlay.entry(address) {
+                            Entry::Occupied(mut entry) => entry.get_mut().merge(changes),
+                            Entry::Vacant(entry) => {
+                                entry.insert(changes);
+                            }
+                        }
+                    }
+                }
+            }
+            executions.push(execution);
+        }
+        Ok(executions)
+    }
+
+    #[tracing::instrument(name = "executor::local_call", skip_all, fields(from, to))]
+    fn execute_local_call_with_overlay(
+        &self,
+        call_input: CallInput,
+        tx: Option<&TransactionInput>,
+        point_in_time: PointInTime,
+        overlay: ExecutionChanges,
+    ) -> Result<EvmExecution, StratusError> {
         #[cfg(feature = "metrics")]
         let start = metrics::now();
 
@@ -551,7 +819,11 @@ impl Executor {
         };
 
         // execute
-        let evm_input = EvmInput::from_eth_call(call_input.clone(), point_in_time, pending_header, mined_block)?;
+        let mut evm_input = EvmInput::from_eth_call(call_input.clone(), point_in_time, pending_header, mined_block)?;
+        if let Some(tx) = tx {
+            evm_input.apply_transaction(tx, self.config.executor_gas_mode);
+        }
+        evm_input.overlay = overlay;
         let evm_route = match point_in_time {
             PointInTime::Mined | PointInTime::Pending => EvmRoute::CallPresent,
             PointInTime::MinedPast(_) => EvmRoute::CallPast,
@@ -580,6 +852,84 @@ impl Executor {
         let execution = evm_result?.execution;
         Ok(execution)
     }
+
+    /// Executes a read-only call to an account abstraction entry point, tracking banned opcodes executed by the validated entities.
+    #[tracing::instrument(name = "executor::local_validation_call", skip_all, fields(to))]
+    pub fn execute_local_validation_call(&self, call_input: CallInput) -> Result<EvmExecutionResult, StratusError> {
+        Span::with(|s| s.rec_opt("to", &call_input.to));
+        tracing::info!(from = ?call_input.from, to = ?call_input.to, data_len = call_input.data.len(), "executing validation call");
+
+        let pending_header = self.storage.read_pending_block_header();
+        let evm_input = EvmInput::from_eth_call(call_input, PointInTime::Pending, pending_header, None)?;
+        self.evms.execute(evm_input, EvmRoute::CallValidation)
+    }
+}
+
+/// Recovers the senders of external transactions in parallel and checks they match the senders informed by the external RPC.
+fn verify_external_senders(txs: &[ExternalTransaction], threads: usize) -> anyhow::Result<()> {
+    if txs.is_empty() {
+        return Ok(());
+    }
+
+    let chunk_size = txs.len().div_ceil(threads);
+    thread::scope(|s| {
+        let tasks = txs
+            .chunks(chunk_size)
+            .map(|chunk| s.spawn(move || chunk.iter().try_for_each(verify_external_sender)))
+            .collect_vec();
+        tasks
+            .into_iter()
+            .try_for_each(|task| task.join().unwrap_or_else(|_| log_and_err!("sender recovery thread panicked")))
+    })
+}
+
+fn verify_external_sender(tx: &ExternalTransaction) -> anyhow::Result<()> {
+    let informed: Address = tx.0.from.into();
+    let recovered = match tx.recover_signer() {
+        Ok(signer) => signer,
+        Err(e) => return log_and_err!(reason = e, payload = tx.hash(), "failed to recover external transaction sender"),
+    };
+    if recovered != informed {
+        return log_and_err!(
+            payload = (tx.hash(), informed, recovered),
+            "external transaction sender does not match its signature"
+        );
+    }
+    Ok(())
+}
+
+/// Nonces and balances modified by a failed transaction, by account.
+fn failed_tx_modifications(changes: &ExecutionChanges) -> HashMap<Address, (Option<Nonce>, Option<Wei>)> {
+    changes
+        .values()
+        .filter(|changes| changes.nonce.is_modified() || changes.balance.is_modified())
+        .map(|changes| {
+            let nonce = changes.nonce.is_modified().then(|| changes.nonce.take_modified_ref().copied()).flatten();
+            let balance = changes.balance.is_modified().then(|| changes.balance.take_modified_ref().copied()).flatten();
+            (changes.address, (nonce, balance))
+        })
+        .collect()
+}
+
+/// Emits a structured event with the values of a conflict, so conflicts can be filtered and aggregated by address and slot.
+fn trace_conflict(tx_input: &TransactionInput, conflict: &ExecutionConflict) {
+    let tx_hash = &tx_input.hash;
+    match conflict {
+        ExecutionConflict::Nonce { address, expected, actual } => {
+            tracing::warn!(%tx_hash, kind = "nonce", %address, %expected, %actual, "execution conflict");
+        }
+        ExecutionConflict::Balance { address, expected, actual } => {
+            tracing::warn!(%tx_hash, kind = "balance", %address, %expected, %actual, "execution conflict");
+        }
+        ExecutionConflict::Slot {
+            address,
+            slot,
+            expected,
+            actual,
+        } => {
+            tracing::warn!(%tx_hash, kind = "slot", %address, %slot, %expected, %actual, "execution conflict");
+        }
+    }
 }
 
 #[derive(Clone, Copy, serde::Serialize)]
@@ -602,3 +952,256 @@ impl FromStr for ExecutorStrategy {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use ethers_core::k256::ecdsa::SigningKey;
+    use ethers_core::types::transaction::eip2718::TypedTransaction;
+    use ethers_core::types::Eip1559TransactionRequest;
+    use ethers_core::types::Signature as EthersSignature;
+    use ethers_core::types::H256;
+    use ethers_core::types::U256;
+    use ethers_core::utils::secret_key_to_address;
+
+    use super::*;
+    use crate::alias::EthersBlockEthersTransaction;
+    use crate::alias::EthersReceipt;
+    use crate::alias::EthersTransaction;
+    use crate::eth::miner::MinerConfig;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::Hash;
+    use crate::eth::storage::Storage;
+
+    /// Signs an EIP-1559 transfer and decodes it like a transaction returned by the external RPC.
+    fn signed_external_tx(key: &SigningKey, nonce: u64) -> ExternalTransaction {
+        let tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .chain_id(2008)
+                .nonce(nonce)
+                .to(Address::COINBASE.0)
+                .value(1)
+                .gas(21_000),
+        );
+        let (signature, recovery_id) = key.sign_prehash_recoverable(tx.sighash().as_bytes()).unwrap();
+        let signature = EthersSignature {
+            r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+            s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+            v: recovery_id.to_byte() as u64 + 35 + 2008 * 2,
+        };
+
+        let mut tx: EthersTransaction = rlp::decode(&tx.rlp_signed(&signature)).unwrap();
+        tx.from = secret_key_to_address(key);
+        tx.into()
+    }
+
+    #[test]
+    fn test_verify_external_senders_accepts_signed_transactions() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let txs = (0..10).map(|nonce| signed_external_tx(&key, nonce)).collect_vec();
+        verify_external_senders(&txs, 4).unwrap();
+
+        // verifying again reuses the cached signers
+        verify_external_senders(&txs, 4).unwrap();
+    }
+
+    #[test]
+    fn test_verify_external_senders_rejects_wrong_sender() {
+        let key = SigningKey::from_slice(&[2; 32]).unwrap();
+        let other = SigningKey::from_slice(&[3; 32]).unwrap();
+
+        let mut txs = (0..4).map(|nonce| signed_external_tx(&key, nonce)).collect_vec();
+        txs[2].0.from = secret_key_to_address(&other);
+        assert!(verify_external_senders(&txs, 2).is_err());
+    }
+
+    fn new_executor(args: &[&str]) -> (Arc<StratusStorage>, Arc<Executor>) {
+        let storage = Arc::new(StratusStorage::new_test().unwrap());
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, &MinerConfig::parse_from(["test"])));
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"].iter().chain(args));
+        let executor = config.init(Arc::clone(&storage), miner);
+        (storage, executor)
+    }
+
+    /// Creates an external block with a single failed transaction and the execution sent by the leader for it.
+    fn failed_external_block(sender: Address) -> (ExternalBlock, ExternalReceipt, EvmExecution) {
+        let tx_hash = H256::repeat_byte(1);
+        let block = ExternalBlock::from(EthersBlockEthersTransaction {
+            number: Some(1u64.into()),
+            transactions: vec![EthersTransaction {
+                hash: tx_hash,
+                from: sender.into(),
+                block_number: Some(1u64.into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let receipt = ExternalReceipt::from(EthersReceipt {
+            transaction_hash: tx_hash,
+            from: sender.into(),
+            block_number: Some(1u64.into()),
+            status: Some(0u64.into()),
+            gas_used: Some(21_000u64.into()),
+            ..Default::default()
+        });
+        let execution = EvmExecution::from_failed_external_transaction(Account::new_empty(sender), &receipt, block.timestamp()).unwrap();
+        (block, receipt, execution)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_external_block_saves_leader_executions() {
+        let (storage, executor) = new_executor(&[]);
+        let (block, receipt, execution) = failed_external_block(Address::new([1; 20]));
+
+        executor
+            .apply_external_block(block, ExternalReceipts::from(vec![receipt]), vec![execution], true)
+            .unwrap();
+        assert_eq!(storage.pending_transactions().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_external_block_spot_check_detects_mismatch() {
+        let sender = Address::new([1; 20]);
+
+        // the leader incremented the nonce of the failed transaction twice
+        let (block, receipt, mut execution) = failed_external_block(sender);
+        execution.changes.get_mut(&sender).unwrap().nonce.set_modified(Nonce::from(2u64));
+
+        // without spot check the changes are applied as sent
+        let (_, executor) = new_executor(&[]);
+        let result = executor.apply_external_block(block.clone(), ExternalReceipts::from(vec![receipt.clone()]), vec![execution.clone()], false);
+        assert!(result.is_ok());
+
+        // with spot check the divergence is detected
+        let (storage, executor) = new_executor(&[]);
+        let result = executor.apply_external_block(block, ExternalReceipts::from(vec![receipt]), vec![execution], true);
+        assert!(result.is_err());
+        assert!(storage.pending_transactions().is_empty());
+    }
+
+    const SENDER: Address = Address::new([4; 20]);
+    const SENDER_BALANCE: u64 = 1_000_000_000;
+    const TRANSFER_VALUE: u64 = 1_000;
+    const GAS_PRICE: u64 = 10;
+    const TRANSFER_GAS: u64 = 21_000;
+
+    /// Simulates a transfer signed with the given gas limit and returns the result of its execution.
+    fn simulate_transfer(args: &[&str], gas_limit: u64) -> Result<EvmExecution, StratusError> {
+        let (storage, executor) = new_executor(args);
+        storage
+            .save_accounts(vec![Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE))])
+            .unwrap();
+
+        let tx = TransactionInput {
+            chain_id: Some(2008u64.into()),
+            signer: SENDER,
+            from: SENDER,
+            to: Some(Address::COINBASE),
+            value: Wei::from(TRANSFER_VALUE),
+            gas_limit: Gas::from(gas_limit),
+            gas_price: Wei::from(GAS_PRICE),
+            ..Default::default()
+        };
+        let mut executions = executor.simulate_local_transactions(vec![tx], PointInTime::Pending).unwrap();
+        executions.remove(0)
+    }
+
+    fn sender_balance(execution: &EvmExecution) -> Wei {
+        *execution.changes[&SENDER].balance.take_modified_ref().unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_simulate_transactions_charges_gas_price() {
+        let execution = simulate_transfer(&["--executor-gas-mode", "charged"], 100_000).unwrap();
+        assert_eq!(execution.gas, Gas::from(TRANSFER_GAS));
+        assert_eq!(
+            sender_balance(&execution),
+            Wei::from(SENDER_BALANCE - TRANSFER_VALUE - TRANSFER_GAS * GAS_PRICE)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_simulate_transactions_enforces_gas_limit() {
+        let result = simulate_transfer(&["--executor-gas-mode", "charged"], TRANSFER_GAS - 1);
+        assert!(matches!(result, Err(StratusError::TransactionEvmFailed(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_simulate_transactions_ignores_gas_when_free() {
+        let execution = simulate_transfer(&["--executor-gas-mode", "free"], TRANSFER_GAS - 1).unwrap();
+        assert_eq!(sender_balance(&execution), Wei::from(SENDER_BALANCE - TRANSFER_VALUE));
+    }
+
+    const OTHER_SENDER: Address = Address::new([5; 20]);
+    const RECIPIENT: Address = Address::new([6; 20]);
+
+    fn transfer_to_recipient(sender: Address) -> TransactionInput {
+        TransactionInput {
+            chain_id: Some(2008u64.into()),
+            hash: Hash::new_random(),
+            signer: sender,
+            from: sender,
+            to: Some(RECIPIENT),
+            value: Wei::from(TRANSFER_VALUE),
+            gas_limit: Gas::from(TRANSFER_GAS),
+            ..Default::default()
+        }
+    }
+
+    /// Executes a local transfer in the parallel route while another transfer to the same recipient is saved before it, so its first execution
+    /// conflicts.
+    fn execute_conflicting_transfer(args: &[&str]) -> (Arc<StratusStorage>, Result<(), StratusError>) {
+        let storage = Arc::new(StratusStorage::new_test().unwrap());
+        let miner_config = MinerConfig::parse_from(["test", "--block-max-transactions", "100"]);
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, &miner_config));
+        let config = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"].iter().chain(args));
+        let executor = config.init(Arc::clone(&storage), Arc::clone(&miner));
+        storage
+            .save_accounts(vec![
+                Account::new_with_balance(SENDER, Wei::from(SENDER_BALANCE)),
+                Account::new_with_balance(OTHER_SENDER, Wei::from(SENDER_BALANCE)),
+            ])
+            .unwrap();
+
+        // with block limits the miner saves under a lock, so holding it keeps the executed transfer waiting to be saved
+        let save_lock = miner.locks.save_execution.lock();
+        let transfer = thread::spawn({
+            let executor = Arc::clone(&executor);
+            move || executor.execute_local_transaction_attempts(transfer_to_recipient(SENDER), EvmRoute::Parallel, 1)
+        });
+        thread::sleep(Duration::from_millis(500));
+
+        let other = transfer_to_recipient(OTHER_SENDER);
+        let evm_input = EvmInput::from_eth_transaction(&other, &storage.read_pending_block_header(), GasMode::Free);
+        let evm_result = executor.evms.execute(evm_input.clone(), EvmRoute::External).unwrap();
+        storage
+            .save_execution(TransactionExecution::new_local(other, evm_input, evm_result), false)
+            .unwrap();
+        drop(save_lock);
+
+        (storage, transfer.join().unwrap())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_local_transaction_is_reexecuted_after_conflict() {
+        let (storage, result) = execute_conflicting_transfer(&[]);
+        result.unwrap();
+        assert_eq!(storage.pending_transactions().len(), 2);
+        assert_eq!(
+            storage.read_account(RECIPIENT, PointInTime::Pending).unwrap().balance,
+            Wei::from(2 * TRANSFER_VALUE)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_local_transaction_fails_after_conflict_retries() {
+        let (storage, result) = execute_conflicting_transfer(&["--executor-conflict-retries", "0"]);
+        assert!(matches!(result, Err(StratusError::TransactionConflict(_))));
+        assert_eq!(storage.pending_transactions().len(), 1);
+    }
+}
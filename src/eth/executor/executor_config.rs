@@ -7,7 +7,10 @@ use display_json::DebugAsJson;
 use crate::eth::executor::Executor;
 use crate::eth::executor::ExecutorStrategy;
 use crate::eth::miner::Miner;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::GasMode;
 use crate::eth::storage::StratusStorage;
+use crate::ext::not;
 
 #[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
 pub struct ExecutorConfig {
@@ -25,6 +28,18 @@ pub struct ExecutorConfig {
     #[arg(long = "executor-strategy", alias = "strategy", env = "EXECUTOR_STRATEGY", default_value = "serial")]
     pub executor_strategy: ExecutorStrategy,
 
+    /// How transactions pay for the gas they use. It is a property of the chain, so all nodes of the same chain must use the same mode.
+    #[arg(long = "executor-gas-mode", env = "EXECUTOR_GAS_MODE", default_value = "free")]
+    pub executor_gas_mode: GasMode,
+
+    /// Account credited with the fees collected from each transaction when gas is charged. When not set, fees are burned.
+    #[arg(long = "executor-fee-treasury", env = "EXECUTOR_FEE_TREASURY")]
+    pub executor_fee_treasury: Option<Address>,
+
+    /// Max number of times a local transaction is re-executed over fresh state after conflicting with transactions saved before it in the pending block.
+    #[arg(long = "executor-conflict-retries", env = "EXECUTOR_CONFLICT_RETRIES", default_value = "10")]
+    pub executor_conflict_retries: usize,
+
     /// Should reject contract transactions and calls to accounts that are not contracts?
     #[arg(
         long = "executor-reject-not-contract",
@@ -33,6 +48,32 @@ pub struct ExecutorConfig {
         default_value = "true"
     )]
     pub executor_reject_not_contract: bool,
+
+    /// Max size in bytes of deployed contracts code (EIP-170). Deployments exceeding it fail like any other failed execution.
+    ///
+    /// Chains that intentionally allow larger contracts can increase it. Leader and followers must use the same value, because followers
+    /// reexecute the blocks of the leader and a different limit changes the result of deployments. Followers check it against the leader when
+    /// they start.
+    #[arg(long = "executor-max-code-size", env = "EXECUTOR_MAX_CODE_SIZE", default_value = "24576")]
+    pub executor_max_code_size: usize,
+
+    /// Max size in bytes of contract deployment transactions data (EIP-3860), twice the max code size by default.
+    ///
+    /// Only enforced for transactions received through `eth_sendRawTransaction`. Imported transactions were already accepted by the external
+    /// chain and are always executed. Leader and followers must use the same value, like the max code size.
+    #[arg(long = "executor-max-initcode-size", env = "EXECUTOR_MAX_INITCODE_SIZE", default_value = "49152")]
+    pub executor_max_initcode_size: usize,
+
+    /// Should track value transferred by internal calls, so they can be queried with `stratus_getInternalTransactions`?
+    ///
+    /// Adds overhead to every call executed by the EVM.
+    #[arg(long = "executor-internal-transfers", env = "EXECUTOR_INTERNAL_TRANSFERS", default_value = "false")]
+    pub executor_internal_transfers: bool,
+
+    /// Number of threads that recover the senders of an imported external block before it is executed, checking they match the senders
+    /// informed by the external RPC. Disabled if zero, trusting the informed senders.
+    #[arg(long = "executor-sender-recovery-threads", env = "EXECUTOR_SENDER_RECOVERY_THREADS", default_value = "0")]
+    pub executor_sender_recovery_threads: usize,
 }
 
 impl ExecutorConfig {
@@ -44,6 +85,15 @@ impl ExecutorConfig {
         config.executor_evms = max(config.executor_evms, 1);
         tracing::info!(?config, "creating executor");
 
+        if let Some(treasury) = config.executor_fee_treasury {
+            if not(config.executor_gas_mode.is_charged()) {
+                tracing::warn!(%treasury, "fee treasury is configured, but gas is not charged, so no fees will be collected");
+            }
+            if treasury.is_ignored() {
+                tracing::warn!(%treasury, "fee treasury is an address ignored by the executor, so collected fees will be lost");
+            }
+        }
+
         let executor = Executor::new(storage, miner, config);
         Arc::new(executor)
     }
@@ -1,13 +1,20 @@
+mod block_replay;
 mod evm;
-mod evm_input;
-mod evm_result;
+mod evm_inspector;
 #[allow(clippy::module_inception)]
 mod executor;
 mod executor_config;
+mod opcode_tracer;
+mod transfer_tracer;
 
+pub use block_replay::compare_state_diff;
+pub use block_replay::replay_block;
+pub use block_replay::BlockReplay;
+pub use block_replay::Divergence;
 pub use evm::Evm;
-pub use evm_input::EvmInput;
-pub use evm_result::EvmExecutionResult;
+pub use evm_inspector::EvmInspector;
 pub use executor::Executor;
 pub use executor::ExecutorStrategy;
 pub use executor_config::ExecutorConfig;
+pub use opcode_tracer::OpcodeTracer;
+pub use transfer_tracer::TransferTracer;
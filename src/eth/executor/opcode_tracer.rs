@@ -0,0 +1,85 @@
+use revm::interpreter::Interpreter;
+use revm::Database;
+use revm::EvmContext;
+use revm::Inspector;
+
+use crate::eth::primitives::BannedOpcode;
+use crate::ext::not;
+
+/// Opcodes that account abstraction entities cannot use during validation, according to ERC-4337 validation rules.
+///
+/// https://eips.ethereum.org/EIPS/eip-7562#opcode-rules
+const BANNED_OPCODES: &[(u8, &str)] = &[
+    (0x31, "BALANCE"),
+    (0x32, "ORIGIN"),
+    (0x3a, "GASPRICE"),
+    (0x40, "BLOCKHASH"),
+    (0x41, "COINBASE"),
+    (0x42, "TIMESTAMP"),
+    (0x43, "NUMBER"),
+    (0x44, "PREVRANDAO"),
+    (0x45, "GASLIMIT"),
+    (0x47, "SELFBALANCE"),
+    (0x48, "BASEFEE"),
+    (0x49, "BLOBHASH"),
+    (0x4a, "BLOBBASEFEE"),
+    (0xf0, "CREATE"),
+    (0xfe, "INVALID"),
+    (0xff, "SELFDESTRUCT"),
+];
+
+const OPCODE_GAS: u8 = 0x5a;
+
+/// Opcodes that can immediately follow GAS without violating validation rules.
+const CALL_OPCODES: &[u8] = &[0xf1, 0xf2, 0xf4, 0xfa];
+
+/// Call depth of the contract called by the transaction. Entities called by it are in deeper frames.
+const ENTRY_POINT_DEPTH: usize = 1;
+
+/// Tracks banned opcodes executed by contracts called by the transaction target.
+///
+/// Only collects data when registered as the EVM inspector, otherwise it does not affect execution.
+#[derive(Default)]
+pub struct OpcodeTracer {
+    banned_opcodes: Vec<BannedOpcode>,
+
+    /// GAS opcode executed in the previous step, which is allowed only if followed by a call.
+    pending_gas: Option<BannedOpcode>,
+}
+
+impl OpcodeTracer {
+    /// Returns the banned opcodes tracked during the last execution and resets the tracer.
+    pub fn take(&mut self) -> Vec<BannedOpcode> {
+        self.pending_gas = None;
+        std::mem::take(&mut self.banned_opcodes)
+    }
+}
+
+impl<DB: Database> Inspector<DB> for OpcodeTracer {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let opcode = interp.current_opcode();
+
+        // GAS followed by anything other than a call is banned
+        if let Some(gas) = self.pending_gas.take() {
+            if not(CALL_OPCODES.contains(&opcode)) {
+                self.banned_opcodes.push(gas);
+            }
+        }
+
+        let depth = context.journaled_state.depth;
+        if depth <= ENTRY_POINT_DEPTH {
+            return;
+        }
+
+        let banned = |name: &str| BannedOpcode {
+            address: interp.contract.target_address.into(),
+            depth,
+            opcode: name.to_owned(),
+        };
+        if opcode == OPCODE_GAS {
+            self.pending_gas = Some(banned("GAS"));
+        } else if let Some((_, name)) = BANNED_OPCODES.iter().find(|(banned_opcode, _)| *banned_opcode == opcode) {
+            self.banned_opcodes.push(banned(name));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use revm::interpreter::CallInputs;
+use revm::interpreter::CallOutcome;
+use revm::interpreter::CreateInputs;
+use revm::interpreter::CreateOutcome;
+use revm::primitives::U256;
+use revm::Database;
+use revm::EvmContext;
+use revm::Inspector;
+
+use crate::alias::RevmAddress;
+use crate::eth::primitives::InternalTransfer;
+use crate::eth::primitives::InternalTransferKind;
+use crate::ext::not;
+
+/// Call depth of the contract called by the transaction when its frame is being created.
+const TRANSACTION_DEPTH: usize = 0;
+
+/// Tracks value transferred by calls, contract creations and self-destructs executed inside a transaction.
+///
+/// The value transferred by the transaction itself is not tracked because it is already part of the transaction.
+///
+/// Only collects data when registered as the EVM inspector, otherwise it does not affect execution.
+#[derive(Default)]
+pub struct TransferTracer {
+    /// Transfers of the frames being executed. Transfers of a frame are discarded if the frame fails.
+    frames: Vec<TransferFrame>,
+
+    /// Transfers of all frames that completed successfully.
+    transfers: Vec<InternalTransfer>,
+}
+
+#[derive(Default)]
+struct TransferFrame {
+    /// Creation that started the frame. The created address is only known when the frame ends.
+    pending_create: Option<(RevmAddress, U256, usize)>,
+
+    transfers: Vec<InternalTransfer>,
+}
+
+impl TransferTracer {
+    /// Returns the transfers tracked during the last execution and resets the tracer.
+    pub fn take(&mut self) -> Vec<InternalTransfer> {
+        self.frames.clear();
+        std::mem::take(&mut self.transfers)
+    }
+
+    fn enter_frame(&mut self, frame: TransferFrame) {
+        self.frames.push(frame);
+    }
+
+    fn exit_frame(&mut self, success: bool, created_address: Option<RevmAddress>) {
+        let Some(mut frame) = self.frames.pop() else { return };
+        if not(success) {
+            return;
+        }
+
+        // creation transfer happens before anything executed by the created contract
+        if let (Some((from, value, depth)), Some(to)) = (frame.pending_create, created_address) {
+            frame.transfers.insert(0, new_transfer(InternalTransferKind::Create, from, to, value, depth));
+        }
+
+        match self.frames.last_mut() {
+            Some(parent) => parent.transfers.extend(frame.transfers),
+            None => self.transfers.extend(frame.transfers),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for TransferTracer {
+    fn call(&mut self, context: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let depth = context.journaled_state.depth;
+
+        let mut frame = TransferFrame::default();
+        if let Some(value) = inputs.transfer_value() {
+            // CALLCODE transfers to the caller itself
+            if depth > TRANSACTION_DEPTH && value > U256::ZERO && inputs.caller != inputs.target_address {
+                let transfer = new_transfer(InternalTransferKind::Call, inputs.caller, inputs.target_address, value, depth);
+                frame.transfers.push(transfer);
+            }
+        }
+        self.enter_frame(frame);
+        None
+    }
+
+    fn call_end(&mut self, _: &mut EvmContext<DB>, _: &CallInputs, outcome: CallOutcome) -> CallOutcome {
+        self.exit_frame(outcome.result.result.is_ok(), None);
+        outcome
+    }
+
+    fn create(&mut self, context: &mut EvmContext<DB>, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let depth = context.journaled_state.depth;
+
+        let mut frame = TransferFrame::default();
+        if depth > TRANSACTION_DEPTH && inputs.value > U256::ZERO {
+            frame.pending_create = Some((inputs.caller, inputs.value, depth));
+        }
+        self.enter_frame(frame);
+        None
+    }
+
+    fn create_end(&mut self, _: &mut EvmContext<DB>, _: &CreateInputs, outcome: CreateOutcome) -> CreateOutcome {
+        self.exit_frame(outcome.result.result.is_ok(), outcome.address);
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: RevmAddress, target: RevmAddress, value: U256) {
+        if value == U256::ZERO {
+            return;
+        }
+        let depth = self.frames.len().saturating_sub(1);
+        if let Some(frame) = self.frames.last_mut() {
+            frame
+                .transfers
+                .push(new_transfer(InternalTransferKind::Selfdestruct, contract, target, value, depth));
+        }
+    }
+}
+
+fn new_transfer(kind: InternalTransferKind, from: RevmAddress, to: RevmAddress, value: U256, depth: usize) -> InternalTransfer {
+    InternalTransfer {
+        kind,
+        from: from.into(),
+        to: to.into(),
+        value: value.into(),
+        depth,
+    }
+}
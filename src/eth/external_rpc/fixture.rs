@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use fake::Fake;
+use fake::Faker;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionValueChange;
+use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::ExternalReceipt;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::TransactionMined;
+use crate::ext::not;
+use crate::ext::to_json_string_pretty;
+use crate::log_and_err;
+
+/// External block with its receipts and the minimal state needed to re-execute it, used as a test fixture.
+///
+/// Generated by `importer-offline --export-fixtures`, so fixtures can be regenerated from any imported block number.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExternalBlockFixture {
+    pub block: ExternalBlock,
+
+    pub receipts: Vec<ExternalReceipt>,
+
+    /// Accounts touched by the block, with their values before it was executed.
+    pub accounts: Vec<Account>,
+
+    /// Slots touched by the block, with their values before it was executed.
+    pub slots: Vec<(Address, Slot)>,
+}
+
+impl ExternalBlockFixture {
+    /// Creates a fixture from an external block and the block mined from its execution.
+    pub fn new(block: ExternalBlock, receipts: Vec<ExternalReceipt>, mined_block: &Block) -> Self {
+        let (accounts, slots) = minimal_state(mined_block);
+        Self {
+            block,
+            receipts,
+            accounts,
+            slots,
+        }
+    }
+
+    /// Path of the fixture of a block inside a fixtures directory.
+    pub fn path(dir: impl AsRef<Path>, number: BlockNumber) -> PathBuf {
+        dir.as_ref().join(format!("{:012}.json", number.as_u64()))
+    }
+
+    /// Writes the fixture to the fixtures directory, replacing a previous fixture of the same block.
+    pub fn save(&self, dir: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+        let path = Self::path(&dir, self.block.number());
+        if let Err(e) = fs::create_dir_all(&dir) {
+            return log_and_err!(reason = e, payload = dir.as_ref(), "failed to create fixtures directory");
+        }
+        if let Err(e) = fs::write(&path, to_json_string_pretty(self)) {
+            return log_and_err!(reason = e, payload = path, "failed to write fixture");
+        }
+        Ok(path)
+    }
+
+    /// Reads a fixture from a file.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return log_and_err!(reason = e, payload = path, "failed to read fixture"),
+        };
+        match serde_json::from_str(&content) {
+            Ok(fixture) => Ok(fixture),
+            Err(e) => log_and_err!(reason = e, payload = path, "failed to parse fixture"),
+        }
+    }
+
+    /// Creates a block before the fixture block containing its minimal state, so a storage can be seeded by saving it.
+    ///
+    /// The state is carried by the changes of a single transaction whose other fields are random.
+    pub fn state_block(&self) -> Block {
+        let number = self.block.number().prev().unwrap_or_default();
+
+        let mut changes = self
+            .accounts
+            .iter()
+            .map(|account| (account.address, ExecutionAccountChanges::from_modified_values(account.clone(), vec![])))
+            .collect::<HashMap<_, _>>();
+        for (address, slot) in &self.slots {
+            let account_changes = changes
+                .entry(*address)
+                .or_insert_with(|| ExecutionAccountChanges::from_modified_values(Account::new_empty(*address), vec![]));
+            account_changes.slots.insert(slot.index, ExecutionValueChange::from_modified(*slot));
+        }
+
+        let mut tx: TransactionMined = Faker.fake();
+        tx.execution.changes = changes;
+        tx.logs.clear();
+        tx.block_number = number;
+
+        let mut block = Block::new(number, self.block.timestamp());
+        tx.block_hash = block.hash();
+        block.transactions.push(tx);
+        block
+    }
+}
+
+/// Collects the original values of accounts and slots touched by the block transactions.
+///
+/// Only the first time an account or slot is touched is considered, because later transactions see values modified by the block itself.
+fn minimal_state(block: &Block) -> (Vec<Account>, Vec<(Address, Slot)>) {
+    let mut accounts = Vec::new();
+    let mut slots = Vec::new();
+    let mut seen_accounts = HashSet::<Address>::new();
+    let mut seen_slots = HashSet::<(Address, SlotIndex)>::new();
+
+    for tx in &block.transactions {
+        for changes in tx.execution.changes.values() {
+            if seen_accounts.insert(changes.address) && not(changes.new_account) {
+                accounts.push(Account {
+                    address: changes.address,
+                    nonce: changes.nonce.take_original_ref().copied().unwrap_or_default(),
+                    balance: changes.balance.take_original_ref().copied().unwrap_or_default(),
+                    bytecode: changes.bytecode.take_original_ref().cloned().flatten(),
+                    code_hash: changes.code_hash,
+                });
+            }
+            for (index, slot) in &changes.slots {
+                if let Some(original) = slot.take_original_ref() {
+                    if seen_slots.insert((changes.address, *index)) {
+                        slots.push((changes.address, *original));
+                    }
+                }
+            }
+        }
+    }
+
+    (accounts, slots)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::eth::executor::ExecutorConfig;
+    use crate::eth::miner::Miner;
+    use crate::eth::miner::MinerConfig;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::primitives::ExternalReceipts;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StorageReader;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+
+    const FIXTURES_DIR: &str = "tests/fixtures/snapshots";
+
+    #[test]
+    fn test_minimal_state_keeps_first_original_values() {
+        let address: Address = Faker.fake();
+        let index = SlotIndex::from(1u64);
+        let tx_changes = |balance: u64, value: u64| {
+            let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, Wei::from(balance)));
+            changes
+                .slots
+                .insert(index, ExecutionValueChange::from_original(Slot::new(index, SlotValue::from(value))));
+            changes
+        };
+
+        let mut block: Block = Faker.fake();
+        let mut tx1: TransactionMined = Faker.fake();
+        let mut tx2: TransactionMined = Faker.fake();
+        tx1.execution.changes = [(address, tx_changes(10, 1))].into_iter().collect();
+        tx2.execution.changes = [(address, tx_changes(20, 2))].into_iter().collect();
+        block.transactions = vec![tx1, tx2];
+
+        let (accounts, slots) = minimal_state(&block);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].balance, Wei::from(10u64));
+        assert_eq!(slots, vec![(address, Slot::new(index, SlotValue::from(1u64)))]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fixtures_reexecute_over_their_minimal_state() {
+        let paths = glob::glob(&format!("{}/*.json", FIXTURES_DIR)).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(not(paths.is_empty()), "no fixtures found in {}", FIXTURES_DIR);
+
+        for path in paths {
+            let fixture = ExternalBlockFixture::load(&path).unwrap();
+            assert_eq!(path, ExternalBlockFixture::path(FIXTURES_DIR, fixture.block.number()));
+
+            // seed a storage only with the fixture state
+            let state_block = fixture.state_block();
+            let perm = InMemoryPermanentStorage::default();
+            perm.set_mined_block_number(state_block.number()).unwrap();
+            perm.save_block(state_block).unwrap();
+            let temp = InMemoryTemporaryStorage::new(fixture.block.number());
+            let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+
+            // reexecution fails if the state is not enough to reproduce the receipts
+            let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, &MinerConfig::parse_from(["test"])));
+            let executor = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"]).init(Arc::clone(&storage), miner);
+            executor
+                .execute_external_block(fixture.block.clone(), ExternalReceipts::from(fixture.receipts.clone()))
+                .unwrap();
+            assert_eq!(storage.pending_transactions().len(), fixture.block.transactions.len());
+        }
+    }
+}
@@ -1,6 +1,15 @@
+pub use fixture::ExternalBlockFixture;
+#[cfg(feature = "parquet")]
+pub use parquet::ParquetExternalRpc;
+#[cfg(feature = "postgres")]
 pub use postgres::PostgresExternalRpc;
+#[cfg(feature = "postgres")]
 pub use postgres::PostgresExternalRpcConfig;
 
+mod fixture;
+#[cfg(feature = "parquet")]
+mod parquet;
+#[cfg(feature = "postgres")]
 mod postgres;
 
 use std::str::FromStr;
@@ -40,6 +49,11 @@ pub trait ExternalRpc: Send + Sync {
 
     /// Save an external block and its receipts to the storage.
     async fn save_block_and_receipts(&self, number: BlockNumber, block: JsonValue, receipts: Vec<(Hash, ExternalReceipt)>) -> anyhow::Result<()>;
+
+    /// Writes blocks buffered by the storage, if any.
+    async fn flush(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -49,7 +63,7 @@ pub trait ExternalRpc: Send + Sync {
 /// External RPC storage configuration.
 #[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
 pub struct ExternalRpcConfig {
-    /// External RPC storage implementation.
+    /// External RPC storage implementation: `postgres://<url>`, `s3://<bucket>/<prefix>` (Parquet) or `file://<directory>` (Parquet).
     #[arg(long = "external-rpc-storage", env = "EXTERNAL_RPC_STORAGE")]
     pub external_rpc_storage_kind: ExternalRpcKind,
 
@@ -64,11 +78,36 @@ pub struct ExternalRpcConfig {
     /// External RPC threshold in seconds for warning slow queries.
     #[arg(long = "external-rpc-slow-query-warn-threshold", value_parser=parse_duration, env = "EXTERNAL_RPC_SLOW_QUERY_WARN_THRESHOLD", default_value = "1s")]
     pub external_rpc_slow_query_warn_threshold: Duration,
+
+    /// External RPC storage number of prepared statements cached by each connection.
+    #[arg(
+        long = "external-rpc-storage-statement-cache-capacity",
+        env = "EXTERNAL_RPC_STORAGE_STATEMENT_CACHE_CAPACITY",
+        default_value = "100"
+    )]
+    pub external_rpc_storage_statement_cache_capacity: usize,
+
+    /// External RPC storage replica URL used by reads. If not set, reads use the primary storage.
+    #[arg(long = "external-rpc-storage-read-replica", env = "EXTERNAL_RPC_STORAGE_READ_REPLICA")]
+    pub external_rpc_storage_read_replica: Option<String>,
+
+    /// External RPC storage number of parallel open connections used by reads.
+    ///
+    /// If set or if a read replica is set, reads use a separate pool. Otherwise, reads and writes share the same pool.
+    #[arg(long = "external-rpc-storage-read-connections", env = "EXTERNAL_RPC_STORAGE_READ_CONNECTIONS")]
+    pub external_rpc_storage_read_connections: Option<u32>,
 }
 
 #[derive(DebugAsJson, Clone, serde::Serialize)]
 pub enum ExternalRpcKind {
-    Postgres { url: String },
+    #[cfg(feature = "postgres")]
+    Postgres {
+        url: String,
+    },
+    #[cfg(feature = "parquet")]
+    Parquet {
+        url: String,
+    },
 }
 
 impl ExternalRpcConfig {
@@ -76,16 +115,23 @@ impl ExternalRpcConfig {
     pub async fn init(&self) -> anyhow::Result<Arc<dyn ExternalRpc>> {
         tracing::info!(config = ?self, "creating external rpc storage");
 
-        let ExternalRpcKind::Postgres { url } = &self.external_rpc_storage_kind;
-
-        let config = PostgresExternalRpcConfig {
-            url: url.to_owned(),
-            connections: self.external_rpc_storage_connections,
-            acquire_timeout: self.external_rpc_storage_timeout,
-            slow_query_warn_threshold: self.external_rpc_slow_query_warn_threshold,
-        };
-
-        Ok(Arc::new(PostgresExternalRpc::new(config).await?))
+        match self.external_rpc_storage_kind.clone() {
+            #[cfg(feature = "postgres")]
+            ExternalRpcKind::Postgres { url } => {
+                let config = PostgresExternalRpcConfig {
+                    url,
+                    connections: self.external_rpc_storage_connections,
+                    acquire_timeout: self.external_rpc_storage_timeout,
+                    slow_query_warn_threshold: self.external_rpc_slow_query_warn_threshold,
+                    statement_cache_capacity: self.external_rpc_storage_statement_cache_capacity,
+                    read_url: self.external_rpc_storage_read_replica.clone(),
+                    read_connections: self.external_rpc_storage_read_connections,
+                };
+                Ok(Arc::new(PostgresExternalRpc::new(config).await?))
+            }
+            #[cfg(feature = "parquet")]
+            ExternalRpcKind::Parquet { url } => Ok(Arc::new(ParquetExternalRpc::from_url(&url)?)),
+        }
     }
 }
 
@@ -94,7 +140,10 @@ impl FromStr for ExternalRpcKind {
 
     fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
         match s {
+            #[cfg(feature = "postgres")]
             s if s.starts_with("postgres://") => Ok(Self::Postgres { url: s.to_string() }),
+            #[cfg(feature = "parquet")]
+            s if s.starts_with("s3://") || s.starts_with("file://") => Ok(Self::Parquet { url: s.to_string() }),
             s => Err(anyhow!("unknown external rpc storage: {}", s)),
         }
     }
@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::ArrayRef;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_array::UInt64Array;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use object_store::PutPayload;
+use parking_lot::Mutex;
+
+use crate::alias::JsonValue;
+use crate::eth::external_rpc::ExternalBlockWithReceipts;
+use crate::eth::external_rpc::ExternalRpc;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::ExternalReceipt;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Wei;
+use crate::ext::to_json_string;
+use crate::infra::parquet::read_parquet;
+use crate::infra::parquet::write_parquet;
+use crate::log_and_err;
+
+/// Number of blocks covered by each partition prefix.
+const BLOCKS_PER_PARTITION: u64 = 100_000;
+
+/// Number of buffered blocks that triggers writing a new file.
+const BLOCKS_PER_FILE: usize = 1_000;
+
+/// External RPC storage that keeps blocks and receipts as Parquet files in an object storage (S3 or a local directory).
+///
+/// Layout:
+/// * `<prefix>/blocks/<partition>/<first_block>-<last_block>.parquet`: columns `number`, `block` and `receipts`, with blocks and receipts as JSON.
+/// * `<prefix>/accounts/<address>.parquet`: columns `address` and `balance`.
+///
+/// Blocks are buffered in memory and written in files of [`BLOCKS_PER_FILE`] blocks, so huge historical downloads produce a manageable number
+/// of objects. Buffered blocks are only visible to readers after they are flushed.
+pub struct ParquetExternalRpc {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+
+    /// Blocks and receipts (as JSON) waiting to be written.
+    pending: Mutex<BTreeMap<u64, (String, String)>>,
+}
+
+impl ParquetExternalRpc {
+    /// Creates a new [`ParquetExternalRpc`] writing to the specified object storage.
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: impl Into<Path>) -> Self {
+        Self {
+            store,
+            prefix: prefix.into(),
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Creates a new [`ParquetExternalRpc`] from a `s3://<bucket>/<prefix>` or `file://<directory>` URL.
+    ///
+    /// S3 credentials and region are read from the standard `AWS_*` environment variables.
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        tracing::info!(%url, "creating parquet external rpc storage");
+
+        if let Some(location) = url.strip_prefix("s3://") {
+            let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+            let store = match AmazonS3Builder::from_env().with_bucket_name(bucket).build() {
+                Ok(store) => store,
+                Err(e) => return log_and_err!(reason = e, "failed to create s3 client for parquet external rpc storage"),
+            };
+            return Ok(Self::new(Arc::new(store), prefix));
+        }
+
+        if let Some(dir) = url.strip_prefix("file://") {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                return log_and_err!(reason = e, "failed to create parquet external rpc storage directory");
+            }
+            let store = match LocalFileSystem::new_with_prefix(dir) {
+                Ok(store) => store,
+                Err(e) => return log_and_err!(reason = e, "failed to open parquet external rpc storage directory"),
+            };
+            return Ok(Self::new(Arc::new(store), ""));
+        }
+
+        Err(anyhow!("unknown parquet external rpc storage location: {}", url))
+    }
+
+    // -------------------------------------------------------------------------
+    // Blocks
+    // -------------------------------------------------------------------------
+
+    /// Lists the files with blocks that overlap the block range.
+    async fn list_block_files(&self, start: u64, end: u64) -> anyhow::Result<Vec<(u64, u64, Path)>> {
+        let partitions = self.store.list_with_delimiter(Some(&self.prefix.child("blocks"))).await?;
+
+        let mut files = Vec::new();
+        for partition_prefix in partitions.common_prefixes {
+            let Some(partition) = partition_prefix.filename().and_then(|name| name.parse::<u64>().ok()) else {
+                continue;
+            };
+            if partition < start / BLOCKS_PER_PARTITION || partition > end / BLOCKS_PER_PARTITION {
+                continue;
+            }
+            let objects: Vec<_> = self.store.list(Some(&partition_prefix)).try_collect().await?;
+            for object in objects {
+                let Some((first, last)) = parse_block_file_name(object.location.filename().unwrap_or_default()) else {
+                    continue;
+                };
+                if first <= end && last >= start {
+                    files.push((first, last, object.location));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Reads all blocks of a file.
+    async fn read_block_file(&self, path: &Path) -> anyhow::Result<Vec<(u64, String, String)>> {
+        let data = self.store.get(path).await?.bytes().await?;
+
+        let mut rows = Vec::new();
+        for batch in read_parquet(data)? {
+            let numbers = batch.column(0).as_primitive::<UInt64Type>();
+            let blocks = batch.column(1).as_string::<i32>();
+            let receipts = batch.column(2).as_string::<i32>();
+            for i in 0..batch.num_rows() {
+                rows.push((numbers.value(i), blocks.value(i).to_owned(), receipts.value(i).to_owned()));
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Writes the buffered blocks, one file for each partition.
+    async fn flush_pending(&self) -> anyhow::Result<()> {
+        let pending = mem::take(&mut *self.pending.lock());
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        // group by partition
+        let mut partitions: BTreeMap<u64, Vec<(u64, String, String)>> = BTreeMap::new();
+        for (number, (block, receipts)) in &pending {
+            partitions
+                .entry(number / BLOCKS_PER_PARTITION)
+                .or_default()
+                .push((*number, block.clone(), receipts.clone()));
+        }
+
+        for (partition, rows) in partitions {
+            let (first, last) = (rows[0].0, rows[rows.len() - 1].0);
+            let path = self
+                .prefix
+                .child("blocks")
+                .child(format!("{:010}", partition))
+                .child(format!("{:012}-{:012}.parquet", first, last));
+
+            let batch = RecordBatch::try_new(
+                block_schema(),
+                vec![
+                    Arc::new(UInt64Array::from_iter_values(rows.iter().map(|row| row.0))) as ArrayRef,
+                    Arc::new(StringArray::from_iter_values(rows.iter().map(|row| &row.1))),
+                    Arc::new(StringArray::from_iter_values(rows.iter().map(|row| &row.2))),
+                ],
+            )?;
+
+            if let Err(e) = self.write_file(&path, batch).await {
+                // keep blocks not written yet buffered, so the next flush retries them
+                let mut buffered = self.pending.lock();
+                for (number, row) in pending.into_iter().filter(|(number, _)| *number >= first) {
+                    buffered.entry(number).or_insert(row);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a record batch as a Parquet file.
+    async fn write_file(&self, path: &Path, batch: RecordBatch) -> anyhow::Result<()> {
+        let buffer = write_parquet(&batch)?;
+        if let Err(e) = self.store.put(path, PutPayload::from(buffer)).await {
+            return log_and_err!(reason = e, payload = path.to_string(), "failed to write parquet external rpc file");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExternalRpc for ParquetExternalRpc {
+    async fn read_max_block_number_in_range(&self, start: BlockNumber, end: BlockNumber) -> anyhow::Result<Option<BlockNumber>> {
+        tracing::debug!(%start, %end, "retrieving max external block");
+
+        let (start, end) = (start.as_u64(), end.as_u64());
+        let mut max: Option<u64> = None;
+        for (first, last, path) in self.list_block_files(start, end).await? {
+            // file entirely inside the range: its last block is the max without reading it
+            if first >= start && last <= end {
+                max = max.max(Some(last));
+                continue;
+            }
+            let numbers = self.read_block_file(&path).await?.into_iter().map(|row| row.0);
+            max = max.max(numbers.filter(|number| *number >= start && *number <= end).max());
+        }
+        Ok(max.map(BlockNumber::from))
+    }
+
+    async fn read_block_and_receipts_in_range(&self, start: BlockNumber, end: BlockNumber) -> anyhow::Result<Vec<ExternalBlockWithReceipts>> {
+        tracing::debug!(%start, %end, "retrieving external receipts in range");
+
+        // a block may be written more than once if the download was restarted, so deduplicate by number
+        let (start, end) = (start.as_u64(), end.as_u64());
+        let mut rows: BTreeMap<u64, (String, String)> = BTreeMap::new();
+        for (_, _, path) in self.list_block_files(start, end).await? {
+            for (number, block, receipts) in self.read_block_file(&path).await? {
+                if number >= start && number <= end {
+                    rows.insert(number, (block, receipts));
+                }
+            }
+        }
+
+        let mut blocks_with_receipts: Vec<ExternalBlockWithReceipts> = Vec::with_capacity(rows.len());
+        for (block, receipts) in rows.into_values() {
+            let block: ExternalBlock = serde_json::from_str::<JsonValue>(&block)?.try_into()?;
+            let receipts: Vec<ExternalReceipt> = match serde_json::from_str::<JsonValue>(&receipts)? {
+                JsonValue::Array(receipts) => receipts.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
+                receipts => return log_and_err!(payload = receipts, "external block file has invalid receipts"),
+            };
+            blocks_with_receipts.push((block, receipts));
+        }
+        Ok(blocks_with_receipts)
+    }
+
+    async fn read_initial_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        tracing::debug!("retrieving external balances");
+
+        let objects: Vec<_> = self.store.list(Some(&self.prefix.child("accounts"))).try_collect().await?;
+
+        let mut accounts = Vec::with_capacity(objects.len());
+        for object in objects {
+            let data = self.store.get(&object.location).await?.bytes().await?;
+            for batch in read_parquet(data)? {
+                let addresses = batch.column(0).as_string::<i32>();
+                let balances = batch.column(1).as_string::<i32>();
+                for i in 0..batch.num_rows() {
+                    let address: Address = addresses.value(i).parse()?;
+                    let balance: Wei = serde_json::from_str(balances.value(i))?;
+                    accounts.push(Account::new_with_balance(address, balance));
+                }
+            }
+        }
+        Ok(accounts)
+    }
+
+    async fn save_initial_account(&self, address: Address, balance: Wei) -> anyhow::Result<()> {
+        tracing::debug!(%address, %balance, "saving external balance");
+
+        let batch = RecordBatch::try_new(
+            account_schema(),
+            vec![
+                Arc::new(StringArray::from_iter_values([address.to_string()])) as ArrayRef,
+                Arc::new(StringArray::from_iter_values([to_json_string(&balance)])),
+            ],
+        )?;
+        let path = self.prefix.child("accounts").child(format!("{}.parquet", address));
+        self.write_file(&path, batch).await
+    }
+
+    async fn save_block_and_receipts(&self, number: BlockNumber, block: JsonValue, receipts: Vec<(Hash, ExternalReceipt)>) -> anyhow::Result<()> {
+        tracing::debug!(?block, ?receipts, "saving external block and receipts");
+
+        let receipts = receipts.iter().map(|(_, receipt)| receipt).collect::<Vec<_>>();
+        let buffered = {
+            let mut pending = self.pending.lock();
+            pending.insert(number.as_u64(), (to_json_string(&block), to_json_string(&receipts)));
+            pending.len()
+        };
+
+        if buffered >= BLOCKS_PER_FILE {
+            self.flush_pending().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> anyhow::Result<()> {
+        self.flush_pending().await
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+fn block_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("number", DataType::UInt64, false),
+        Field::new("block", DataType::Utf8, false),
+        Field::new("receipts", DataType::Utf8, false),
+    ]))
+}
+
+fn account_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("address", DataType::Utf8, false),
+        Field::new("balance", DataType::Utf8, false),
+    ]))
+}
+
+/// Parses a `<first_block>-<last_block>.parquet` file name.
+fn parse_block_file_name(name: &str) -> Option<(u64, u64)> {
+    let (first, last) = name.strip_suffix(".parquet")?.split_once('-')?;
+    Some((first.parse().ok()?, last.parse().ok()?))
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    const PAYLOAD: &str = include_str!("../../../tests/fixtures/external_clients/geth.json");
+
+    fn storage() -> ParquetExternalRpc {
+        ParquetExternalRpc::new(Arc::new(InMemory::new()), "downloads")
+    }
+
+    async fn save_block(storage: &ParquetExternalRpc, number: u64) {
+        let mut payload: JsonValue = serde_json::from_str(PAYLOAD).unwrap();
+        payload["block"]["number"] = JsonValue::String(format!("{:#x}", number));
+
+        let receipt: ExternalReceipt = payload["receipts"][0].take().try_into().unwrap();
+        storage
+            .save_block_and_receipts(number.into(), payload["block"].take(), vec![(receipt.hash(), receipt)])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blocks_are_visible_after_flush() {
+        let storage = storage();
+        for number in 1..=3 {
+            save_block(&storage, number).await;
+        }
+        assert_eq!(storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(), None);
+
+        storage.flush().await.unwrap();
+        assert_eq!(
+            storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(),
+            Some(3.into())
+        );
+        assert_eq!(storage.read_max_block_number_in_range(1.into(), 2.into()).await.unwrap(), Some(2.into()));
+        assert_eq!(storage.read_max_block_number_in_range(4.into(), 10.into()).await.unwrap(), None);
+
+        let blocks = storage.read_block_and_receipts_in_range(2.into(), 3.into()).await.unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].0.number(), BlockNumber::from(2));
+        assert_eq!(blocks[1].0.number(), BlockNumber::from(3));
+        assert_eq!(blocks[0].1.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_files_are_written_by_size_and_partition() {
+        let storage = storage();
+        let first = BLOCKS_PER_PARTITION - 10;
+        for number in first..(first + BLOCKS_PER_FILE as u64) {
+            save_block(&storage, number).await;
+        }
+
+        // reaching the file size flushes without an explicit flush, splitting the blocks at the partition boundary
+        let files = storage.list_block_files(0, u64::MAX - 1).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(storage.pending.lock().is_empty());
+        assert_eq!(
+            storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(),
+            Some((first + BLOCKS_PER_FILE as u64 - 1).into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicated_blocks_are_read_once() {
+        let storage = storage();
+        save_block(&storage, 1).await;
+        storage.flush().await.unwrap();
+        save_block(&storage, 1).await;
+        save_block(&storage, 2).await;
+        storage.flush().await.unwrap();
+
+        let blocks = storage.read_block_and_receipts_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap();
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_initial_accounts() {
+        let storage = storage();
+        let address = Address::new([1; 20]);
+        storage.save_initial_account(address, Wei::from(10u64)).await.unwrap();
+
+        let accounts = storage.read_initial_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].address, address);
+        assert_eq!(accounts[0].balance, Wei::from(10u64));
+    }
+
+    #[test]
+    fn test_parse_block_file_name() {
+        assert_eq!(parse_block_file_name("000000000001-000000001000.parquet"), Some((1, 1000)));
+        assert_eq!(parse_block_file_name("000000000001.parquet"), None);
+        assert_eq!(parse_block_file_name("000000000001-000000001000.json"), None);
+    }
+}
@@ -1,12 +1,23 @@
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use log::LevelFilter;
+use rand::Rng;
+use sqlx::migrate::Migrator;
 use sqlx::postgres::PgConnectOptions;
 use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::PgQueryResult;
 use sqlx::types::BigDecimal;
 use sqlx::ConnectOptions;
+use sqlx::Connection;
+use sqlx::Executor;
+use sqlx::PgConnection;
 use sqlx::PgPool;
+use tracing::field;
+use tracing::Instrument;
 
 use crate::alias::JsonValue;
 use crate::eth::external_rpc::ExternalBlockWithReceipts;
@@ -18,15 +29,42 @@ use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Wei;
+use crate::ext::not;
 use crate::ext::to_json_value;
 use crate::ext::traced_sleep;
 use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
 use crate::log_and_err;
 
 const MAX_RETRIES: u64 = 50;
 
+/// Backoff before the first retry of an operation that failed with a transient error.
+const RETRY_BACKOFF_BASE: Duration = Duration::from_millis(10);
+
+/// Max backoff between retries of an operation that failed with a transient error.
+const RETRY_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// Schema migrations applied when the storage is created. Applied versions are tracked by sqlx in the `_sqlx_migrations` table.
+static MIGRATOR: Migrator = sqlx::migrate!("static/schema");
+
+/// Statements of the hot paths prepared when a connection is opened, so the first queries of a connection do not pay for preparing them.
+///
+/// They must match the files used by the queries because the prepared statements cache is keyed by the SQL text.
+const READ_WARM_UP_STATEMENTS: &[&str] = &[
+    include_str!("sql/select_max_external_block_in_range.sql"),
+    include_str!("sql/select_external_blocks_and_receipts_in_range.sql"),
+];
+
+/// Same as [`READ_WARM_UP_STATEMENTS`], but for statements that write.
+const WRITE_WARM_UP_STATEMENTS: &[&str] = &[include_str!("sql/insert_external_block_and_receipts.sql")];
+
 pub struct PostgresExternalRpc {
+    /// Pool used by writes.
     pool: PgPool,
+
+    /// Pool used by reads. Same as `pool` if a separate read pool is not configured.
+    read_pool: PgPool,
 }
 
 #[derive(Debug)]
@@ -35,6 +73,20 @@ pub struct PostgresExternalRpcConfig {
     pub connections: u32,
     pub acquire_timeout: Duration,
     pub slow_query_warn_threshold: Duration,
+    pub statement_cache_capacity: usize,
+
+    /// URL of a replica used by the read pool. If not set, the read pool connects to `url`.
+    pub read_url: Option<String>,
+
+    /// Number of connections of the read pool. If not set, the read pool has the same number of connections of the primary pool.
+    pub read_connections: Option<u32>,
+}
+
+impl PostgresExternalRpcConfig {
+    /// Checks if reads use a pool separated from the writes.
+    fn has_read_pool(&self) -> bool {
+        self.read_url.is_some() || self.read_connections.is_some()
+    }
 }
 
 impl PostgresExternalRpc {
@@ -42,25 +94,100 @@ impl PostgresExternalRpc {
     pub async fn new(config: PostgresExternalRpcConfig) -> anyhow::Result<Self> {
         tracing::info!(?config, "creating postgres external rpc storage");
 
-        let options = config
-            .url
-            .as_str()
+        let options = Self::connect_options(&config, &config.url)?;
+
+        // migrate before creating the pools because their connections prepare statements that depend on the schema
+        Self::migrate(&options).await?;
+
+        if not(config.has_read_pool()) {
+            let statements = [READ_WARM_UP_STATEMENTS, WRITE_WARM_UP_STATEMENTS].concat();
+            let pool = Self::connect_pool(&config, options, config.connections, statements).await?;
+            return Ok(Self { read_pool: pool.clone(), pool });
+        }
+
+        // read pool sessions are read-only, so a misrouted write fails instead of being applied to a replica or bypassing the primary pool
+        let read_url = config.read_url.as_deref().unwrap_or(&config.url);
+        let read_options = Self::connect_options(&config, read_url)?.options([("default_transaction_read_only", "on")]);
+        let read_connections = config.read_connections.unwrap_or(config.connections);
+
+        let pool = Self::connect_pool(&config, options, config.connections, WRITE_WARM_UP_STATEMENTS.to_vec()).await?;
+        let read_pool = Self::connect_pool(&config, read_options, read_connections, READ_WARM_UP_STATEMENTS.to_vec()).await?;
+        Ok(Self { pool, read_pool })
+    }
+
+    fn connect_options(config: &PostgresExternalRpcConfig, url: &str) -> anyhow::Result<PgConnectOptions> {
+        let options = url
             .parse::<PgConnectOptions>()?
-            .log_slow_statements(LevelFilter::Warn, config.slow_query_warn_threshold);
+            .log_slow_statements(LevelFilter::Warn, config.slow_query_warn_threshold)
+            .statement_cache_capacity(config.statement_cache_capacity);
+        Ok(options)
+    }
+
+    /// Creates a pool whose new connections prepare the `statements`.
+    async fn connect_pool(
+        config: &PostgresExternalRpcConfig,
+        options: PgConnectOptions,
+        connections: u32,
+        statements: Vec<&'static str>,
+    ) -> anyhow::Result<PgPool> {
+        let warm_up = config.statement_cache_capacity >= statements.len();
+        if not(warm_up) {
+            tracing::warn!(capacity = %config.statement_cache_capacity, "statement cache capacity is too small, skipping connections warm-up");
+        }
+        let statements = Arc::new(statements);
 
         let result = PgPoolOptions::new()
-            .min_connections(config.connections)
-            .max_connections(config.connections)
+            .min_connections(connections)
+            .max_connections(connections)
             .acquire_timeout(config.acquire_timeout)
+            .after_connect(move |conn, _| {
+                let statements = Arc::clone(&statements);
+                Box::pin(async move {
+                    if warm_up {
+                        Self::warm_up(conn, &statements).await?;
+                    }
+                    Ok(())
+                })
+            })
             .connect_with(options)
             .await;
 
-        let pool = match result {
-            Ok(pool) => pool,
-            Err(e) => return log_and_err!(reason = e, "failed to create postgres external rpc storage"),
+        match result {
+            Ok(pool) => Ok(pool),
+            Err(e) => log_and_err!(reason = e, "failed to create postgres external rpc storage"),
+        }
+    }
+
+    /// Applies pending schema migrations using a dedicated connection.
+    async fn migrate(options: &PgConnectOptions) -> anyhow::Result<()> {
+        let mut conn = match PgConnection::connect_with(options).await {
+            Ok(conn) => conn,
+            Err(e) => return log_and_err!(reason = e, "failed to connect to postgres external rpc storage"),
         };
 
-        Ok(Self { pool })
+        // sqlx holds an advisory lock while migrating, so concurrent instances wait instead of applying migrations twice
+        // it also fails if the database has migrations unknown to this binary, as they were applied by a newer one
+        if let Err(e) = MIGRATOR.run(&mut conn).await {
+            return log_and_err!(reason = e, "failed to migrate postgres external rpc schema");
+        }
+        tracing::info!(version = ?MIGRATOR.iter().map(|migration| migration.version).max(), "postgres external rpc schema is up to date");
+
+        if let Err(e) = conn.close().await {
+            tracing::warn!(reason = ?e, "failed to close postgres migration connection");
+        }
+        Ok(())
+    }
+
+    /// Prepares the hot paths statements in a new connection, storing them in the connection statement cache.
+    async fn warm_up(conn: &mut PgConnection, statements: &[&'static str]) -> Result<(), sqlx::Error> {
+        for statement in statements {
+            if let Err(e) = conn.prepare(statement).await {
+                tracing::warn!(reason = ?e, "failed to prepare statement while warming up postgres connection");
+                return Err(e);
+            }
+        }
+        tracing::debug!(statements = %statements.len(), "warmed up postgres connection");
+        Ok(())
     }
 }
 
@@ -69,9 +196,13 @@ impl ExternalRpc for PostgresExternalRpc {
     async fn read_max_block_number_in_range(&self, start: BlockNumber, end: BlockNumber) -> anyhow::Result<Option<BlockNumber>> {
         tracing::debug!(%start, %end, "retrieving max external block");
 
-        let result = sqlx::query_file_scalar!("src/eth/external_rpc/sql/select_max_external_block_in_range.sql", start.as_i64(), end.as_i64())
-            .fetch_one(&self.pool)
-            .await;
+        let (start, end) = (start.as_i64()?, end.as_i64()?);
+        let pool = &self.read_pool;
+        let result = with_retry("read_max_block_number_in_range", || async move {
+            let query = sqlx::query_file_scalar!("src/eth/external_rpc/sql/select_max_external_block_in_range.sql", start, end).fetch_one(pool);
+            traced_query("select_max_external_block_in_range", query).await
+        })
+        .await;
 
         match result {
             Ok(Some(max)) => Ok(Some(max.into())),
@@ -82,47 +213,38 @@ impl ExternalRpc for PostgresExternalRpc {
 
     async fn read_block_and_receipts_in_range(&self, start: BlockNumber, end: BlockNumber) -> anyhow::Result<Vec<ExternalBlockWithReceipts>> {
         tracing::debug!(%start, %end, "retrieving external receipts in range");
-        let mut attempt: u64 = 1;
-
-        loop {
-            let result = sqlx::query_file!(
-                "src/eth/external_rpc/sql/select_external_blocks_and_receipts_in_range.sql",
-                start.as_i64(),
-                end.as_i64()
-            )
-            .fetch_all(&self.pool)
-            .await;
 
-            match result {
-                Ok(rows) => {
-                    let mut blocks_with_receipts: Vec<ExternalBlockWithReceipts> = Vec::with_capacity(rows.len());
-                    for row in rows {
-                        let block: ExternalBlock = row.block.try_into()?;
-                        let receipts: Vec<ExternalReceipt> = row.receipts.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?;
-                        blocks_with_receipts.push((block, receipts));
-                    }
-                    return Ok(blocks_with_receipts);
+        let (start, end) = (start.as_i64()?, end.as_i64()?);
+        let pool = &self.read_pool;
+        let result = with_retry("read_block_and_receipts_in_range", || async move {
+            let query = sqlx::query_file!("src/eth/external_rpc/sql/select_external_blocks_and_receipts_in_range.sql", start, end).fetch_all(pool);
+            traced_query("select_external_blocks_and_receipts_in_range", query).await
+        })
+        .await;
+
+        match result {
+            Ok(rows) => {
+                let mut blocks_with_receipts: Vec<ExternalBlockWithReceipts> = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let block: ExternalBlock = row.block.try_into()?;
+                    let receipts: Vec<ExternalReceipt> = row.receipts.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?;
+                    blocks_with_receipts.push((block, receipts));
                 }
-                Err(e) =>
-                    if attempt <= MAX_RETRIES {
-                        tracing::warn!(reason = ?e, %attempt, "attempt failed. retrying now.");
-                        attempt += 1;
-
-                        let backoff = Duration::from_millis(attempt.pow(2));
-                        traced_sleep(backoff, SleepReason::RetryBackoff).await;
-                    } else {
-                        return log_and_err!(reason = e, "failed to retrieve receipts");
-                    },
+                Ok(blocks_with_receipts)
             }
+            Err(e) => log_and_err!(reason = e, "failed to retrieve receipts"),
         }
     }
 
     async fn read_initial_accounts(&self) -> anyhow::Result<Vec<Account>> {
         tracing::debug!("retrieving external balances");
 
-        let result = sqlx::query_file!("src/eth/external_rpc/sql/select_external_balances.sql")
-            .fetch_all(&self.pool)
-            .await;
+        let pool = &self.read_pool;
+        let result = with_retry("read_initial_accounts", || async move {
+            let query = sqlx::query_file!("src/eth/external_rpc/sql/select_external_balances.sql").fetch_all(pool);
+            traced_query("select_external_balances", query).await
+        })
+        .await;
 
         match result {
             Ok(rows) => {
@@ -140,12 +262,12 @@ impl ExternalRpc for PostgresExternalRpc {
     async fn save_initial_account(&self, address: Address, balance: Wei) -> anyhow::Result<()> {
         tracing::debug!(%address, %balance, "saving external balance");
 
-        let result = sqlx::query_file!(
-            "src/eth/external_rpc/sql/insert_external_balance.sql",
-            address.as_ref(),
-            TryInto::<BigDecimal>::try_into(balance)?
-        )
-        .execute(&self.pool)
+        let balance = &TryInto::<BigDecimal>::try_into(balance)?;
+        let pool = &self.pool;
+        let result = with_retry("save_initial_account", || async move {
+            let query = sqlx::query_file!("src/eth/external_rpc/sql/insert_external_balance.sql", address.as_ref(), *balance).execute(pool);
+            traced_query("insert_external_balance", query).await
+        })
         .await;
 
         match result {
@@ -157,34 +279,299 @@ impl ExternalRpc for PostgresExternalRpc {
     async fn save_block_and_receipts(&self, number: BlockNumber, block: JsonValue, receipts: Vec<(Hash, ExternalReceipt)>) -> anyhow::Result<()> {
         tracing::debug!(?block, ?receipts, "saving external block and receipts");
 
-        let mut tx = match self.pool.begin().await {
-            Ok(tx) => tx,
-            Err(e) => return log_and_err!(reason = e, "failed to init postgres transaction"),
-        };
+        let number = number.as_i64()?;
+        let block = &block;
+        let receipts = &receipts.iter().map(|(_, receipt)| to_json_value(receipt)).collect::<Vec<JsonValue>>();
+        let pool = &self.pool;
 
-        let receipts = receipts.iter().map(|(_, receipt)| to_json_value(receipt)).collect::<Vec<JsonValue>>();
+        // the whole transaction is retried because a transient error aborts it
+        let result = with_retry("save_block_and_receipts", || async move {
+            let mut tx = pool.begin().await?;
 
-        // insert block
-        let result = sqlx::query_file!(
-            "src/eth/external_rpc/sql/insert_external_block_and_receipts.sql",
-            number.as_i64(),
-            block,
-            &receipts,
-        )
-        .execute(&mut *tx)
+            // insert block
+            let query = sqlx::query_file!("src/eth/external_rpc/sql/insert_external_block_and_receipts.sql", number, *block, receipts,).execute(&mut *tx);
+            let result = traced_query("insert_external_block_and_receipts", query).await;
+
+            match result {
+                Ok(_) => {}
+                Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                    tracing::warn!(reason = ?e, "block unique violation, skipping");
+                }
+                Err(e) => return Err(e),
+            }
+
+            tx.commit().await
+        })
         .await;
 
         match result {
-            Ok(_) => {}
-            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
-                tracing::warn!(reason = ?e, "block unique violation, skipping");
-            }
-            Err(e) => return log_and_err!(reason = e, "failed to insert block"),
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to save block and receipts"),
         }
+    }
+}
 
-        match tx.commit().await {
-            Ok(_) => Ok(()),
-            Err(e) => log_and_err!(reason = e, "failed to commit postgres transaction"),
+// -----------------------------------------------------------------------------
+// Tracing
+// -----------------------------------------------------------------------------
+
+/// Runs a query inside a span identified by the name of its SQL file, recording its latency and the number of rows it returned or affected.
+async fn traced_query<T, Fut>(query: &'static str, fut: Fut) -> Result<T, sqlx::Error>
+where
+    T: QueryRows,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let span = tracing::info_span!("postgres::query", %query, rows = field::Empty, latency_ms = field::Empty);
+    let start = Instant::now();
+    let result = fut.instrument(span.clone()).await;
+
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    if let Ok(value) = &result {
+        span.record("rows", value.rows());
+    }
+    result
+}
+
+/// Number of rows returned or affected by a query.
+trait QueryRows {
+    fn rows(&self) -> u64;
+}
+
+impl<T> QueryRows for Vec<T> {
+    fn rows(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+/// Result of a scalar query, which always returns a single row, even if its value is `NULL`.
+impl<T> QueryRows for Option<T> {
+    fn rows(&self) -> u64 {
+        1
+    }
+}
+
+impl QueryRows for PgQueryResult {
+    fn rows(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Retry
+// -----------------------------------------------------------------------------
+
+/// Runs a storage operation, retrying it with jittered exponential backoff while it fails with transient errors.
+///
+/// Permanent errors and the last transient error after all retries are returned to the caller.
+async fn with_retry<T, F, Fut>(operation: &'static str, mut f: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt: u64 = 1;
+    loop {
+        let e = match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        let Some(kind) = transient_error_kind(&e) else {
+            return Err(e);
+        };
+        if attempt > MAX_RETRIES {
+            tracing::warn!(%operation, %kind, %attempt, "transient postgres error persisted after all retries");
+            return Err(e);
+        }
+
+        tracing::warn!(reason = ?e, %operation, %kind, %attempt, "transient postgres error, retrying");
+        #[cfg(feature = "metrics")]
+        metrics::inc_external_rpc_storage_retries(operation, kind);
+
+        traced_sleep(retry_backoff(attempt), SleepReason::RetryBackoff).await;
+        attempt += 1;
+    }
+}
+
+/// Classifies errors that may succeed if the operation is retried. Returns `None` for permanent errors.
+fn transient_error_kind(e: &sqlx::Error) -> Option<&'static str> {
+    match e {
+        sqlx::Error::Io(_) => Some("io"),
+        sqlx::Error::PoolTimedOut => Some("pool_timeout"),
+        sqlx::Error::Database(e) => match e.code().as_deref() {
+            Some("40001") => Some("serialization_failure"),
+            Some("40P01") => Some("deadlock"),
+            Some("53300") => Some("too_many_connections"),
+            Some("57P01" | "57P02" | "57P03") => Some("server_shutdown"),
+            // the server was demoted to a replica during a failover
+            Some("25006") => Some("read_only"),
+            Some(code) if code.starts_with("08") => Some("connection"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Backoff before retrying an attempt: exponential up to a limit, with a random jitter so clients do not retry in lockstep.
+fn retry_backoff(attempt: u64) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16) as u32;
+    let max = RETRY_BACKOFF_BASE.saturating_mul(2u32.pow(exponent)).min(RETRY_BACKOFF_MAX);
+    let min = max / 2;
+    rand::thread_rng().gen_range(min..=max)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::docker::Docker;
+    use crate::infra::docker::Service;
+    use crate::infra::tracing::CapturedLogs;
+
+    const PAYLOAD: &str = include_str!("../../../tests/fixtures/external_clients/geth.json");
+
+    /// Storage using its own empty schema in the test Postgres.
+    async fn storage(schema: &str, read_connections: Option<u32>) -> PostgresExternalRpc {
+        let docker = Docker::start(&[Service::Postgres]).await.unwrap();
+        let url = docker.reset_postgres_schema(schema).await.unwrap();
+        PostgresExternalRpc::new(PostgresExternalRpcConfig {
+            url,
+            connections: 2,
+            acquire_timeout: Duration::from_secs(10),
+            slow_query_warn_threshold: Duration::from_secs(1),
+            statement_cache_capacity: 100,
+            read_url: None,
+            read_connections,
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn save_block(storage: &PostgresExternalRpc, number: u64) {
+        let mut payload: JsonValue = serde_json::from_str(PAYLOAD).unwrap();
+        payload["block"]["number"] = JsonValue::String(format!("{:#x}", number));
+
+        let receipt: ExternalReceipt = payload["receipts"][0].take().try_into().unwrap();
+        storage
+            .save_block_and_receipts(number.into(), payload["block"].take(), vec![(receipt.hash(), receipt)])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs docker or TEST_POSTGRES_URL, run with `just test-services`"]
+    async fn test_blocks_and_receipts() {
+        let storage = storage("test_external_rpc_blocks", None).await;
+        assert_eq!(storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(), None);
+
+        for number in 1..=3 {
+            save_block(&storage, number).await;
+        }
+        assert_eq!(
+            storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(),
+            Some(3.into())
+        );
+        assert_eq!(storage.read_max_block_number_in_range(1.into(), 2.into()).await.unwrap(), Some(2.into()));
+        assert_eq!(storage.read_max_block_number_in_range(4.into(), 10.into()).await.unwrap(), None);
+
+        let blocks = storage.read_block_and_receipts_in_range(2.into(), 3.into()).await.unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].0.number(), BlockNumber::from(2));
+        assert_eq!(blocks[1].0.number(), BlockNumber::from(3));
+        assert_eq!(blocks[0].1.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs docker or TEST_POSTGRES_URL, run with `just test-services`"]
+    async fn test_duplicated_blocks_are_skipped() {
+        let storage = storage("test_external_rpc_duplicated_blocks", None).await;
+        save_block(&storage, 1).await;
+        save_block(&storage, 1).await;
+
+        let blocks = storage.read_block_and_receipts_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap();
+        assert_eq!(blocks.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs docker or TEST_POSTGRES_URL, run with `just test-services`"]
+    async fn test_initial_accounts() {
+        let storage = storage("test_external_rpc_initial_accounts", None).await;
+        let address = Address::new([1; 20]);
+        storage.save_initial_account(address, Wei::from(10u64)).await.unwrap();
+
+        let accounts = storage.read_initial_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].address, address);
+        assert_eq!(accounts[0].balance, Wei::from(10u64));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs docker or TEST_POSTGRES_URL, run with `just test-services`"]
+    async fn test_read_pool_is_read_only() {
+        let storage = storage("test_external_rpc_read_pool", Some(1)).await;
+        save_block(&storage, 1).await;
+        assert_eq!(
+            storage.read_max_block_number_in_range(BlockNumber::ZERO, BlockNumber::MAX).await.unwrap(),
+            Some(1.into())
+        );
+
+        let e = sqlx::query("delete from external_blocks").execute(&storage.read_pool).await.unwrap_err();
+        assert!(e.to_string().contains("read-only transaction"), "{}", e);
+    }
+
+    #[test]
+    fn test_transient_error_kind() {
+        assert_eq!(transient_error_kind(&sqlx::Error::PoolTimedOut), Some("pool_timeout"));
+        assert_eq!(transient_error_kind(&sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into())), Some("io"));
+        assert_eq!(transient_error_kind(&sqlx::Error::RowNotFound), None);
+        assert_eq!(transient_error_kind(&sqlx::Error::PoolClosed), None);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        for _ in 0..100 {
+            let first = retry_backoff(1);
+            assert!(first >= RETRY_BACKOFF_BASE / 2 && first <= RETRY_BACKOFF_BASE);
+
+            let last = retry_backoff(MAX_RETRIES);
+            assert!(last >= RETRY_BACKOFF_MAX / 2 && last <= RETRY_BACKOFF_MAX);
         }
     }
+
+    #[tokio::test]
+    async fn test_traced_query_records_fingerprint_and_rows() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let rows = traced_query("select_external_balances", async { Ok(vec![1, 2, 3]) }).await.unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let logs = logs.output();
+        assert!(logs.contains("postgres::query"), "{}", logs);
+        assert!(logs.contains("query=select_external_balances"), "{}", logs);
+        assert!(logs.contains("rows=3"), "{}", logs);
+        assert!(logs.contains("latency_ms="), "{}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_traced_query_does_not_record_rows_of_failed_queries() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let result = traced_query::<Vec<()>, _>("insert_external_balance", async { Err(sqlx::Error::PoolTimedOut) }).await;
+        assert!(matches!(result, Err(sqlx::Error::PoolTimedOut)));
+
+        let logs = logs.output();
+        assert!(logs.contains("query=insert_external_balance"), "{}", logs);
+        assert!(not(logs.contains("rows=")), "{}", logs);
+    }
+
+    #[test]
+    fn test_query_rows() {
+        assert_eq!(vec![(); 5].rows(), 5);
+        assert_eq!(Some(10).rows(), 1);
+        assert_eq!(None::<i64>.rows(), 1);
+        assert_eq!(PgQueryResult::default().rows(), 0);
+    }
 }
@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::cmp::min;
+use std::future::Future;
 use std::mem;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
@@ -9,31 +10,46 @@ use std::time::Duration;
 use async_trait::async_trait;
 use futures::try_join;
 use futures::StreamExt;
+use futures::TryStreamExt;
 use serde::Deserialize;
 use tokio::sync::mpsc;
 use tokio::task::yield_now;
+use tokio::task::AbortHandle;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tracing::Span;
 
+use crate::alias::JsonValue;
 use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
+use crate::eth::follower::importer::InvariantsPolicy;
+use crate::eth::follower::importer::ReceiptsVerification;
+use crate::eth::follower::importer::ReplicationMode;
 use crate::eth::miner::Miner;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ChainSpec;
+use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalReceipts;
 use crate::eth::primitives::Hash;
-use crate::eth::storage::Storage;
-use crate::eth::storage::StratusStorage;
+use crate::eth::primitives::StratusError;
+use crate::eth::storage::CacheInvalidation;
+use crate::eth::storage::StorageReader;
+use crate::ext::not;
 use crate::ext::spawn_named;
-use crate::ext::traced_sleep;
 use crate::ext::DisplayExt;
 use crate::ext::SleepReason;
 use crate::globals::IMPORTER_ONLINE_TASKS_SEMAPHORE;
 use crate::if_else;
+use crate::infra::clock::TaskClock;
+use crate::infra::clock::TokioClock;
 use crate::infra::kafka::KafkaConnector;
 #[cfg(feature = "metrics")]
 use crate::infra::metrics;
+use crate::infra::task_registry;
 use crate::infra::tracing::warn_task_rx_closed;
 use crate::infra::tracing::warn_task_tx_closed;
 use crate::infra::tracing::SpanExt;
@@ -83,30 +99,64 @@ const TIMEOUT_NEW_HEADS: Duration = Duration::from_millis(2000);
 /// Interval before we starting retrieving receipts because they are not immediately available after the block is retrieved.
 const INTERVAL_FETCH_RECEIPTS: Duration = Duration::from_millis(50);
 
+/// Delay before fetching a block again when it was not mined yet.
+const FETCH_BLOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Block and receipts fetched from the external RPC.
+struct FetchedBlock {
+    block: ExternalBlock,
+    receipts: Vec<ExternalReceipt>,
+
+    /// Cache entries touched by the block, sent only by Stratus leaders.
+    invalidation: Option<CacheInvalidation>,
+
+    /// Executions of the block transactions, sent only by Stratus leaders when requested for delta replication.
+    executions: Option<Vec<EvmExecution>>,
+}
+
 pub struct Importer {
     executor: Arc<Executor>,
 
     miner: Arc<Miner>,
 
-    storage: Arc<StratusStorage>,
+    storage: Arc<dyn StorageReader>,
 
     chain: Arc<BlockchainClient>,
 
     sync_interval: Duration,
 
+    /// What to do with external blocks that violate the chain invariants.
+    block_invariants: InvariantsPolicy,
+
+    /// What to do when the receipts of a reexecuted external block diverge from its header.
+    receipts_verification: ReceiptsVerification,
+
+    /// How the state of external blocks is replicated.
+    replication_mode: ReplicationMode,
+
+    /// Blocks replicated with delta replication between reexecutions.
+    spot_check_interval: u64,
+
     kafka_connector: Option<Arc<KafkaConnector>>,
 
     importer_mode: ImporterMode,
+
+    /// Clock used to wait for the sync interval and between retries.
+    clock: Arc<dyn TaskClock>,
 }
 
 impl Importer {
     pub fn new(
         executor: Arc<Executor>,
         miner: Arc<Miner>,
-        storage: Arc<StratusStorage>,
+        storage: Arc<dyn StorageReader>,
         chain: Arc<BlockchainClient>,
         kafka_connector: Option<Arc<KafkaConnector>>,
         sync_interval: Duration,
+        block_invariants: InvariantsPolicy,
+        receipts_verification: ReceiptsVerification,
+        replication_mode: ReplicationMode,
+        spot_check_interval: u64,
         importer_mode: ImporterMode,
     ) -> Self {
         tracing::info!("creating importer");
@@ -117,11 +167,22 @@ impl Importer {
             storage,
             chain,
             sync_interval,
+            block_invariants,
+            receipts_verification,
+            replication_mode,
+            spot_check_interval,
             kafka_connector,
             importer_mode,
+            clock: Arc::new(TokioClock),
         }
     }
 
+    /// Replaces the clock used to wait for the sync interval and between retries, so tests can drive the importer with virtual time.
+    pub fn with_clock(mut self, clock: Arc<dyn TaskClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     // -----------------------------------------------------------------------------
     // Shutdown
     // -----------------------------------------------------------------------------
@@ -139,6 +200,13 @@ impl Importer {
         let _timer = DropTimer::start("importer-online::run_importer_online");
 
         let storage = &self.storage;
+
+        // a previous run may have been aborted in the middle of a block, so its partial executions are discarded before the block is imported again
+        if not(storage.pending_transactions().is_empty()) {
+            let discarded = self.miner.discard_pending_block()?;
+            tracing::warn!(number = %discarded.header.number, transactions = %discarded.transactions.len(), "discarded partially imported block");
+        }
+
         let number = storage.read_block_number_to_resume_import()?;
 
         let (backlog_tx, backlog_rx) = mpsc::unbounded_channel();
@@ -152,6 +220,10 @@ impl Importer {
                 Arc::clone(&self.miner),
                 backlog_rx,
                 self.kafka_connector.clone(),
+                self.block_invariants,
+                self.receipts_verification,
+                self.replication_mode,
+                self.spot_check_interval,
                 self.importer_mode,
             ),
         );
@@ -161,7 +233,7 @@ impl Importer {
         let number_fetcher_chain = Arc::clone(&self.chain);
         let task_number_fetcher = spawn_named(
             "importer::number-fetcher",
-            Importer::start_number_fetcher(number_fetcher_chain, self.sync_interval),
+            Importer::start_number_fetcher(number_fetcher_chain, self.sync_interval, Arc::clone(&self.clock)),
         );
 
         // spawn block fetcher:
@@ -170,12 +242,26 @@ impl Importer {
         let block_fetcher_chain = Arc::clone(&self.chain);
         let task_block_fetcher = spawn_named(
             "importer::block-fetcher",
-            Importer::start_block_fetcher(block_fetcher_chain, backlog_tx, number),
+            Importer::start_block_fetcher(
+                block_fetcher_chain,
+                backlog_tx,
+                number,
+                self.replication_mode.is_delta(),
+                Arc::clone(&self.clock),
+            ),
         );
 
         // await all tasks
-        if let Err(e) = try_join!(task_executor, task_block_fetcher, task_number_fetcher) {
-            tracing::error!(reason = ?e, "importer-online failed");
+        // if one of them fails, the others are aborted, so the importer can be restarted from the last imported block
+        let abort_handles = [
+            task_executor.abort_handle(),
+            task_block_fetcher.abort_handle(),
+            task_number_fetcher.abort_handle(),
+        ];
+        let flatten = |task: JoinHandle<anyhow::Result<()>>| async move { task.await? };
+        if let Err(e) = try_join!(flatten(task_executor), flatten(task_block_fetcher), flatten(task_number_fetcher)) {
+            abort_handles.iter().for_each(AbortHandle::abort);
+            return log_and_err!(reason = e, "importer-online failed");
         }
         Ok(())
     }
@@ -190,19 +276,30 @@ impl Importer {
     async fn start_block_executor(
         executor: Arc<Executor>,
         miner: Arc<Miner>,
-        mut backlog_rx: mpsc::UnboundedReceiver<(ExternalBlock, Vec<ExternalReceipt>)>,
+        mut backlog_rx: mpsc::UnboundedReceiver<FetchedBlock>,
         kafka_connector: Option<Arc<KafkaConnector>>,
+        block_invariants: InvariantsPolicy,
+        receipts_verification: ReceiptsVerification,
+        replication_mode: ReplicationMode,
+        spot_check_interval: u64,
         importer_mode: ImporterMode,
     ) -> anyhow::Result<()> {
         const TASK_NAME: &str = "block-executor";
         let _permit = IMPORTER_ONLINE_TASKS_SEMAPHORE.acquire().await;
 
         loop {
+            task_registry::heartbeat();
+
             if Self::should_shutdown(TASK_NAME) {
                 return Ok(());
             }
 
-            let (block, receipts) = match timeout(Duration::from_secs(2), backlog_rx.recv()).await {
+            let FetchedBlock {
+                block,
+                receipts,
+                invalidation,
+                executions,
+            } = match timeout(Duration::from_secs(2), backlog_rx.recv()).await {
                 Ok(Some(inner)) => inner,
                 Ok(None) => break, // channel closed
                 Err(_timed_out) => {
@@ -225,7 +322,7 @@ impl Importer {
                     };
 
                     if let Err(e) = executor.execute_local_transaction(tx_input) {
-                        if e.is_internal() {
+                        if e.is_fatal() {
                             tracing::error!(reason = ?e, "internal error while executing imported transaction");
                         } else {
                             tracing::error!(reason = ?e, "transaction failed");
@@ -236,9 +333,26 @@ impl Importer {
                 return Ok(());
             }
 
-            if let Err(e) = executor.execute_external_block(block.clone(), ExternalReceipts::from(receipts)) {
-                let message = GlobalState::shutdown_from(TASK_NAME, "failed to reexecute external block");
-                return log_and_err!(reason = e, message);
+            let number = block.number();
+
+            if let Err(e) = check_block_invariants(&block, block_invariants) {
+                return Err(import_failed(number, "external block violates chain invariants", e));
+            }
+
+            let result = match (replication_mode, executions) {
+                (ReplicationMode::Delta, Some(executions)) => {
+                    let spot_check = is_spot_checked(block.number(), spot_check_interval);
+                    executor.apply_external_block(block.clone(), ExternalReceipts::from(receipts), executions, spot_check)
+                }
+                (replication_mode, _) => {
+                    if replication_mode.is_delta() {
+                        tracing::warn!(number = %block.number(), "external block fetched without executions, reexecuting it");
+                    }
+                    executor.execute_external_block(block.clone(), ExternalReceipts::from(receipts))
+                }
+            };
+            if let Err(e) = result {
+                return Err(import_failed(number, "failed to reexecute external block", e));
             };
 
             // statistics
@@ -262,11 +376,15 @@ impl Importer {
                     mined_block
                 }
                 Err(e) => {
-                    let message = GlobalState::shutdown_from(TASK_NAME, "failed to mine external block");
-                    return log_and_err!(reason = e, message);
+                    return Err(import_failed(number, "failed to mine external block", e));
                 }
             };
 
+            let receipts_verified = match verify_block_receipts(&mined_block, receipts_verification) {
+                Ok(verified) => verified,
+                Err(e) => return Err(import_failed(number, "external block receipts diverge from its reexecution", e)),
+            };
+
             if let Some(ref kafka_conn) = kafka_connector {
                 let events = mined_block
                     .transactions
@@ -281,11 +399,22 @@ impl Importer {
                     tracing::info!("committed external block");
                 }
                 Err(e) => {
-                    let message = GlobalState::shutdown_from(TASK_NAME, "failed to commit external block");
-                    return log_and_err!(reason = e, message);
+                    return Err(import_failed(number, "failed to commit external block", e));
                 }
             }
 
+            // the block was committed by the leader, but it is finalized only if this node produced the same receipts
+            if receipts_verified {
+                if let Err(e) = miner.finalize_external_block(number) {
+                    return Err(import_failed(number, "failed to finalize external block", e));
+                }
+            }
+
+            // evict entries touched by the block, so they are read again from the committed state
+            if let Some(invalidation) = invalidation {
+                miner.invalidate_cache(&invalidation);
+            }
+
             #[cfg(feature = "metrics")]
             {
                 metrics::inc_n_importer_online_transactions_total(receipts_len as u64);
@@ -302,7 +431,7 @@ impl Importer {
     // -----------------------------------------------------------------------------
 
     /// Retrieves the blockchain current block number.
-    async fn start_number_fetcher(chain: Arc<BlockchainClient>, sync_interval: Duration) -> anyhow::Result<()> {
+    async fn start_number_fetcher(chain: Arc<BlockchainClient>, sync_interval: Duration, clock: Arc<dyn TaskClock>) -> anyhow::Result<()> {
         const TASK_NAME: &str = "external-number-fetcher";
         let _permit = IMPORTER_ONLINE_TASKS_SEMAPHORE.acquire().await;
 
@@ -317,8 +446,7 @@ impl Importer {
                     Some(sub)
                 }
                 Err(e) => {
-                    let message = GlobalState::shutdown_from(TASK_NAME, "cannot subscribe to newHeads event");
-                    return log_and_err!(reason = e, message);
+                    return log_and_err!(reason = e, "cannot subscribe to newHeads event");
                 }
             }
         } else {
@@ -328,6 +456,8 @@ impl Importer {
 
         // keep reading websocket subscription or polling via http.
         loop {
+            task_registry::heartbeat();
+
             if Self::should_shutdown(TASK_NAME) {
                 return Ok(());
             }
@@ -391,7 +521,7 @@ impl Importer {
                         "fetched current block number via http. awaiting sync interval to retrieve again."
                     );
                     set_external_rpc_current_block(block_number);
-                    traced_sleep(sync_interval, SleepReason::SyncData).await;
+                    clock.sleep(sync_interval, SleepReason::SyncData).await;
                 }
                 Err(e) =>
                     if !Self::should_shutdown(TASK_NAME) {
@@ -408,13 +538,17 @@ impl Importer {
     /// Retrieves blocks and receipts.
     async fn start_block_fetcher(
         chain: Arc<BlockchainClient>,
-        backlog_tx: mpsc::UnboundedSender<(ExternalBlock, Vec<ExternalReceipt>)>,
+        backlog_tx: mpsc::UnboundedSender<FetchedBlock>,
         mut importer_block_number: BlockNumber,
+        with_executions: bool,
+        clock: Arc<dyn TaskClock>,
     ) -> anyhow::Result<()> {
         const TASK_NAME: &str = "external-block-fetcher";
         let _permit = IMPORTER_ONLINE_TASKS_SEMAPHORE.acquire().await;
 
         loop {
+            task_registry::heartbeat();
+
             if Self::should_shutdown(TASK_NAME) {
                 return Ok(());
             }
@@ -434,13 +568,20 @@ impl Importer {
             let mut tasks = Vec::with_capacity(blocks_to_fetch as usize);
             while blocks_to_fetch > 0 {
                 blocks_to_fetch -= 1;
-                tasks.push(fetch_block_and_receipts(Arc::clone(&chain), importer_block_number));
+                tasks.push(fetch_block_and_receipts(
+                    Arc::clone(&chain),
+                    importer_block_number,
+                    with_executions,
+                    Arc::clone(&clock),
+                ));
                 importer_block_number = importer_block_number.next_block_number();
             }
 
             // keep fetching in order
             let mut tasks = futures::stream::iter(tasks).buffered(PARALLEL_BLOCKS);
-            while let Some((mut block, mut receipts)) = tasks.next().await {
+            while let Some(fetched) = tasks.next().await {
+                let mut fetched = fetched?;
+                let FetchedBlock { block, receipts, .. } = &mut fetched;
                 // Stably sort transactions and receipts by transaction_index
                 block.transactions.sort_by(|a, b| a.transaction_index.cmp(&b.transaction_index));
                 receipts.sort_by(|a, b| a.transaction_index.cmp(&b.transaction_index));
@@ -461,7 +602,7 @@ impl Importer {
                     }
                 }
 
-                if backlog_tx.send((block, receipts)).is_err() {
+                if backlog_tx.send(fetched).is_err() {
                     warn_task_rx_closed(TASK_NAME);
                     return Ok(());
                 }
@@ -474,8 +615,93 @@ impl Importer {
 // Helpers
 // -----------------------------------------------------------------------------
 
+/// Checks if a block replicated with delta replication is verified by reexecution, once every `interval` blocks.
+fn is_spot_checked(number: BlockNumber, interval: u64) -> bool {
+    interval > 0 && number.as_u64() % interval == 0
+}
+
+/// Logs a failure to import an external block and wraps it in a fatal error.
+///
+/// The block cannot be skipped and importing it again would fail the same way or over a partially applied state, so the supervisor must
+/// shut down the application instead of restarting the importer.
+fn import_failed(number: BlockNumber, message: &'static str, reason: impl std::fmt::Display) -> anyhow::Error {
+    let reason = format!("{}: {:#}", message, reason);
+    tracing::error!(%number, %reason, "failed to import external block");
+    StratusError::ImporterBlockFailed { number, reason }.into()
+}
+
+/// Checks the fields of an external block that must be the same in all blocks, failing only if violations are rejected.
+fn check_block_invariants(block: &ExternalBlock, policy: InvariantsPolicy) -> anyhow::Result<()> {
+    let header = BlockHeader::try_from(block)?;
+    let violations = ChainSpec::check_header_invariants(&header);
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(feature = "metrics")]
+    for violation in &violations {
+        metrics::inc_importer_invariant_violations(violation.field);
+    }
+
+    match policy {
+        InvariantsPolicy::Reject => log_and_err!(
+            payload = (header.number, &violations),
+            "external block has unexpected values in fields that must be the same in all blocks"
+        ),
+        InvariantsPolicy::Warn => {
+            tracing::warn!(number = %header.number, ?violations, "external block has unexpected values in fields that must be the same in all blocks");
+            Ok(())
+        }
+    }
+}
+
+/// Compares the logs bloom and receipts root of an external block with the ones calculated from its reexecution, failing only in strict mode.
+///
+/// Returns whether the receipts match.
+fn verify_block_receipts(block: &Block, mode: ReceiptsVerification) -> anyhow::Result<bool> {
+    let receipts_root = block.calculate_receipts_root();
+
+    let mut divergences = Vec::new();
+    if block.calculate_logs_bloom() != block.header.bloom {
+        divergences.push("logs_bloom");
+    }
+    if receipts_root != block.header.receipts_root {
+        divergences.push("receipts_root");
+    }
+    if divergences.is_empty() {
+        return Ok(true);
+    }
+
+    #[cfg(feature = "metrics")]
+    for field in &divergences {
+        metrics::inc_importer_receipts_divergences(*field);
+    }
+
+    match mode {
+        ReceiptsVerification::Strict => log_and_err!(
+            payload = (block.number(), &divergences, block.header.receipts_root, receipts_root),
+            "external block receipts diverge from the ones calculated from its reexecution"
+        ),
+        ReceiptsVerification::Permissive => {
+            tracing::warn!(
+                number = %block.number(),
+                ?divergences,
+                expected_receipts_root = %block.header.receipts_root,
+                calculated_receipts_root = %receipts_root,
+                "external block receipts diverge from the ones calculated from its reexecution"
+            );
+            Ok(false)
+        }
+    }
+}
+
 #[tracing::instrument(name = "importer::fetch_block_and_receipts", skip_all, fields(block_number))]
-async fn fetch_block_and_receipts(chain: Arc<BlockchainClient>, block_number: BlockNumber) -> (ExternalBlock, Vec<ExternalReceipt>) {
+async fn fetch_block_and_receipts(
+    chain: Arc<BlockchainClient>,
+    block_number: BlockNumber,
+    with_executions: bool,
+    clock: Arc<dyn TaskClock>,
+) -> anyhow::Result<FetchedBlock> {
     Span::with(|s| {
         s.rec_str("block_number", &block_number);
     });
@@ -483,8 +709,12 @@ async fn fetch_block_and_receipts(chain: Arc<BlockchainClient>, block_number: Bl
     async fn try_reading_block_and_receipts_with_temporary_endpoint(
         chain: Arc<BlockchainClient>,
         block_number: BlockNumber,
-    ) -> Option<(ExternalBlock, Vec<ExternalReceipt>)> {
-        let mut json = chain.fetch_block_and_receipts_with_temporary_endpoint(block_number).await.ok()?;
+        with_executions: bool,
+    ) -> Option<FetchedBlock> {
+        let mut json = chain
+            .fetch_block_and_receipts_with_temporary_endpoint(block_number, with_executions)
+            .await
+            .ok()?;
 
         let block = mem::take(json.get_mut("block")?);
         let block: ExternalBlock = serde_json::from_value(block).ok()?;
@@ -492,62 +722,87 @@ async fn fetch_block_and_receipts(chain: Arc<BlockchainClient>, block_number: Bl
         let receipts = mem::take(json.get_mut("receipts")?);
         let receipts: Vec<ExternalReceipt> = serde_json::from_value(receipts).ok()?;
 
-        Some((block, receipts))
+        // leaders running older versions do not send invalidations
+        let invalidation = json
+            .get_mut("invalidations")
+            .map(mem::take)
+            .and_then(|invalidation| serde_json::from_value(invalidation).ok());
+        let executions = json
+            .get_mut("executions")
+            .map(mem::take)
+            .and_then(|executions| serde_json::from_value(executions).ok());
+
+        Some(FetchedBlock {
+            block,
+            receipts,
+            invalidation,
+            executions,
+        })
     }
 
-    if let Some(res) = try_reading_block_and_receipts_with_temporary_endpoint(Arc::clone(&chain), block_number).await {
+    if let Some(res) = try_reading_block_and_receipts_with_temporary_endpoint(Arc::clone(&chain), block_number, with_executions).await {
         tracing::info!("successfully imported block and receipts using endpoint stratus_getBlockAndReceipts");
-        return res;
+        return Ok(res);
     } else {
         tracing::warn!("failed to import block and receipts with endpoint stratus_getBlockAndReceipts, falling back to get block + get each receipt");
     }
 
     // fetch block
-    let block = fetch_block(Arc::clone(&chain), block_number).await;
+    let block = fetch_block(Arc::clone(&chain), block_number, clock.as_ref()).await?;
 
     // wait some time until receipts are available
-    let _ = traced_sleep(INTERVAL_FETCH_RECEIPTS, SleepReason::SyncData).await;
+    clock.sleep(INTERVAL_FETCH_RECEIPTS, SleepReason::SyncData).await;
 
     // fetch receipts in parallel
     let mut receipts_tasks = Vec::with_capacity(block.transactions.len());
     for hash in block.transactions.iter().map(|tx| tx.hash()) {
         receipts_tasks.push(fetch_receipt(Arc::clone(&chain), block_number, hash));
     }
-    let receipts = futures::stream::iter(receipts_tasks).buffer_unordered(PARALLEL_RECEIPTS).collect().await;
-
-    (block, receipts)
+    let receipts: Vec<ExternalReceipt> = futures::stream::iter(receipts_tasks).buffer_unordered(PARALLEL_RECEIPTS).try_collect().await?;
+
+    Ok(FetchedBlock {
+        block,
+        receipts,
+        invalidation: None,
+        executions: None,
+    })
 }
 
 #[tracing::instrument(name = "importer::fetch_block", skip_all, fields(block_number))]
-async fn fetch_block(chain: Arc<BlockchainClient>, block_number: BlockNumber) -> ExternalBlock {
-    const RETRY_DELAY: Duration = Duration::from_millis(10);
+async fn fetch_block(chain: Arc<BlockchainClient>, block_number: BlockNumber, clock: &dyn TaskClock) -> anyhow::Result<ExternalBlock> {
     Span::with(|s| {
         s.rec_str("block_number", &block_number);
     });
+    fetch_block_until_mined(block_number, clock, || chain.fetch_block(block_number)).await
+}
 
+/// Fetches a block until it is mined, waiting a delay after each attempt that finds it not mined.
+///
+/// Failures to reach the external RPC are retried by the client middlewares, so they are returned as they are.
+async fn fetch_block_until_mined<F, Fut>(block_number: BlockNumber, clock: &dyn TaskClock, mut fetch: F) -> anyhow::Result<ExternalBlock>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<JsonValue>>,
+{
     loop {
         tracing::info!(%block_number, "fetching block");
-        let block = match chain.fetch_block(block_number).await {
+        let block = match fetch().await {
             Ok(json) => json,
-            Err(e) => {
-                tracing::warn!(reason = ?e, %block_number, delay_ms=%RETRY_DELAY.as_millis(), "failed to retrieve block. retrying with delay.");
-                traced_sleep(RETRY_DELAY, SleepReason::RetryBackoff).await;
-                continue;
-            }
+            Err(e) => return log_and_err!(reason = e, "failed to retrieve block"),
         };
 
         if block.is_null() {
-            tracing::warn!(%block_number, delay_ms=%RETRY_DELAY.as_millis(), "block not mined yet. retrying with delay.");
-            traced_sleep(RETRY_DELAY, SleepReason::SyncData).await;
+            tracing::warn!(%block_number, delay_ms=%FETCH_BLOCK_RETRY_DELAY.as_millis(), "block not mined yet. retrying with delay.");
+            clock.sleep(FETCH_BLOCK_RETRY_DELAY, SleepReason::SyncData).await;
             continue;
         }
 
-        return ExternalBlock::deserialize(&block).expect("cannot fail to deserialize external block");
+        return Ok(ExternalBlock::deserialize(&block).expect("cannot fail to deserialize external block"));
     }
 }
 
 #[tracing::instrument(name = "importer::fetch_receipt", skip_all, fields(block_number, tx_hash))]
-async fn fetch_receipt(chain: Arc<BlockchainClient>, block_number: BlockNumber, tx_hash: Hash) -> ExternalReceipt {
+async fn fetch_receipt(chain: Arc<BlockchainClient>, block_number: BlockNumber, tx_hash: Hash) -> anyhow::Result<ExternalReceipt> {
     Span::with(|s| {
         s.rec_str("block_number", &block_number);
         s.rec_str("tx_hash", &tx_hash);
@@ -557,14 +812,12 @@ async fn fetch_receipt(chain: Arc<BlockchainClient>, block_number: BlockNumber,
         tracing::info!(%block_number, %tx_hash, "fetching receipt");
 
         match chain.fetch_receipt(tx_hash).await {
-            Ok(Some(receipt)) => return receipt,
+            Ok(Some(receipt)) => return Ok(receipt),
             Ok(None) => {
                 tracing::warn!(%block_number, %tx_hash, "receipt not available yet because block is not mined. retrying now.");
                 continue;
             }
-            Err(e) => {
-                tracing::error!(reason = ?e, %block_number, %tx_hash, "failed to fetch receipt. retrying now.");
-            }
+            Err(e) => return log_and_err!(reason = e, "failed to fetch receipt"),
         }
     }
 }
@@ -579,3 +832,136 @@ impl Consensus for Importer {
         Ok(&self.chain)
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use clap::Parser;
+    use ethereum_types::H256;
+
+    use super::*;
+    use crate::alias::EthersBlockEthersTransaction;
+    use crate::eth::executor::ExecutorConfig;
+    use crate::eth::miner::MinerConfig;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::storage::StratusStorage;
+    use crate::ext::to_json_value;
+    use crate::infra::clock::VirtualClock;
+
+    #[test]
+    fn test_is_spot_checked() {
+        assert!(not(is_spot_checked(BlockNumber::from(100), 0)));
+        assert!(is_spot_checked(BlockNumber::from(100), 100));
+        assert!(not(is_spot_checked(BlockNumber::from(101), 100)));
+        assert!(is_spot_checked(BlockNumber::from(101), 1));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_block_executor_fails_with_fatal_error() {
+        // the importer is considered shutdown until it is initialized
+        GlobalState::set_importer_shutdown(false);
+
+        let storage = Arc::new(StratusStorage::new_test().unwrap());
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, &MinerConfig::parse_from(["test"])));
+        let executor = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"]).init(Arc::clone(&storage), Arc::clone(&miner));
+
+        // block with a non-zero difficulty violates the chain invariants
+        let block = ExternalBlock::from(EthersBlockEthersTransaction {
+            number: Some(1u64.into()),
+            hash: Some(H256::repeat_byte(1)),
+            difficulty: 1u64.into(),
+            ..Default::default()
+        });
+        let (backlog_tx, backlog_rx) = mpsc::unbounded_channel();
+        backlog_tx
+            .send(FetchedBlock {
+                block,
+                receipts: vec![],
+                invalidation: None,
+                executions: None,
+            })
+            .unwrap();
+
+        // the error is returned to the supervisor as fatal, so it shuts down the application instead of restarting the importer
+        let result = Importer::start_block_executor(
+            executor,
+            miner,
+            backlog_rx,
+            None,
+            InvariantsPolicy::Reject,
+            ReceiptsVerification::Permissive,
+            ReplicationMode::Reexecute,
+            0,
+            ImporterMode::NormalFollower,
+        )
+        .await;
+        let error = result.unwrap_err();
+        let error = error.downcast_ref::<StratusError>().unwrap();
+        assert!(matches!(error, StratusError::ImporterBlockFailed { number, .. } if *number == BlockNumber::from(1)));
+        assert!(error.is_fatal());
+        assert!(not(GlobalState::is_shutdown()));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_block_until_mined_waits_between_attempts() {
+        let clock = Arc::new(VirtualClock::new());
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let mined_block = to_json_value(ExternalBlock::from(EthersBlockEthersTransaction {
+            number: Some(1u64.into()),
+            ..Default::default()
+        }));
+
+        let task = tokio::spawn({
+            let clock = Arc::clone(&clock);
+            let attempts = Arc::clone(&attempts);
+            async move {
+                fetch_block_until_mined(BlockNumber::from(1), clock.as_ref(), || {
+                    let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                    let mined_block = mined_block.clone();
+                    async move {
+                        match attempt {
+                            0 | 1 => Ok(JsonValue::Null),
+                            _ => Ok(mined_block),
+                        }
+                    }
+                })
+                .await
+            }
+        });
+
+        // each attempt that finds the block not mined waits for the retry delay before the next one
+        for attempt in 1..=2 {
+            while attempts.load(Ordering::SeqCst) < attempt || clock.sleeping() == 0 {
+                yield_now().await;
+            }
+            assert_eq!(attempts.load(Ordering::SeqCst), attempt);
+            clock.advance(FETCH_BLOCK_RETRY_DELAY);
+        }
+
+        let block = task.await.unwrap().unwrap();
+        assert_eq!(block.number(), BlockNumber::from(1));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(clock.elapsed(), FETCH_BLOCK_RETRY_DELAY * 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_block_until_mined_returns_client_errors() {
+        let clock = VirtualClock::new();
+        let attempts = AtomicUsize::new(0);
+
+        // the client already retried, so the error is not retried again
+        let result = fetch_block_until_mined(BlockNumber::from(1), &clock, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(anyhow::anyhow!("connection refused")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+    }
+}
@@ -1,6 +1,8 @@
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 use serde_json::json;
@@ -10,14 +12,19 @@ use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
 use crate::eth::follower::importer::Importer;
 use crate::eth::miner::Miner;
+use crate::eth::primitives::ChainSpec;
 use crate::eth::primitives::StratusError;
 use crate::eth::rpc::RpcContext;
-use crate::eth::storage::StratusStorage;
+use crate::eth::storage::StorageReader;
 use crate::ext::not;
 use crate::ext::parse_duration;
-use crate::ext::spawn_named;
+use crate::infra::blockchain_client::MetricsMiddleware;
+use crate::infra::blockchain_client::RetryMiddleware;
+use crate::infra::blockchain_client::TimeoutMiddleware;
 use crate::infra::kafka::KafkaConnector;
+use crate::infra::supervisor::spawn_supervised;
 use crate::infra::BlockchainClient;
+use crate::log_and_err;
 use crate::GlobalState;
 use crate::NodeMode;
 
@@ -36,8 +43,50 @@ pub struct ImporterConfig {
     #[arg(long = "external-rpc-timeout", value_parser=parse_duration, env = "EXTERNAL_RPC_TIMEOUT", default_value = "2s", required = false)]
     pub external_rpc_timeout: Duration,
 
+    /// Consecutive failures to reach the external RPC that open its circuit breaker, rejecting requests without waiting for timeouts. Zero disables it.
+    #[arg(
+        long = "external-rpc-circuit-breaker-threshold",
+        env = "EXTERNAL_RPC_CIRCUIT_BREAKER_THRESHOLD",
+        default_value = "5",
+        required = false
+    )]
+    pub external_rpc_circuit_breaker_threshold: u32,
+
+    /// How long the external RPC circuit breaker stays open before probing it again.
+    #[arg(long = "external-rpc-circuit-breaker-cooldown", value_parser=parse_duration, env = "EXTERNAL_RPC_CIRCUIT_BREAKER_COOLDOWN", default_value = "5s", required = false)]
+    pub external_rpc_circuit_breaker_cooldown: Duration,
+
+    /// Retries of requests to the external RPC that failed because it could not be reached.
+    #[arg(long = "external-rpc-max-retries", env = "EXTERNAL_RPC_MAX_RETRIES", default_value = "3", required = false)]
+    pub external_rpc_max_retries: u32,
+
+    /// Backoff before the first retry of a request to the external RPC. It doubles on each retry.
+    #[arg(long = "external-rpc-retry-backoff", value_parser=parse_duration, env = "EXTERNAL_RPC_RETRY_BACKOFF", default_value = "10ms", required = false)]
+    pub external_rpc_retry_backoff: Duration,
+
     #[arg(long = "sync-interval", value_parser=parse_duration, env = "SYNC_INTERVAL", default_value = "100ms", required = false)]
     pub sync_interval: Duration,
+
+    /// What to do when an external block has unexpected values in fields that must be the same in all blocks (uncle hash, difficulty and nonce).
+    #[arg(long = "block-invariants", env = "BLOCK_INVARIANTS", default_value = "warn", required = false)]
+    pub block_invariants: InvariantsPolicy,
+
+    /// What to do when the logs bloom or receipts root calculated from the reexecution of an external block differ from the ones in its header.
+    #[arg(long = "receipts-verification", env = "RECEIPTS_VERIFICATION", default_value = "permissive", required = false)]
+    pub receipts_verification: ReceiptsVerification,
+
+    /// How the state of external blocks is replicated: by reexecuting their transactions or by applying the changes sent by a Stratus leader.
+    #[arg(long = "importer-replication", env = "IMPORTER_REPLICATION", default_value = "reexecute", required = false)]
+    pub replication_mode: ReplicationMode,
+
+    /// Blocks replicated with delta replication between reexecutions to verify the changes sent by the leader. Zero disables verification.
+    #[arg(
+        long = "importer-spot-check-interval",
+        env = "IMPORTER_SPOT_CHECK_INTERVAL",
+        default_value = "100",
+        required = false
+    )]
+    pub spot_check_interval: u64,
 }
 
 impl ImporterConfig {
@@ -45,11 +94,11 @@ impl ImporterConfig {
         &self,
         executor: Arc<Executor>,
         miner: Arc<Miner>,
-        storage: Arc<StratusStorage>,
+        storage: Arc<dyn StorageReader>,
         kafka_connector: Option<KafkaConnector>,
     ) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
         match GlobalState::get_node_mode() {
-            NodeMode::Leader => Ok(None),
+            NodeMode::Leader | NodeMode::Archive => Ok(None),
             NodeMode::Follower =>
                 self.init_follower(executor, miner, storage, kafka_connector, ImporterMode::NormalFollower)
                     .await,
@@ -61,14 +110,30 @@ impl ImporterConfig {
         &self,
         executor: Arc<Executor>,
         miner: Arc<Miner>,
-        storage: Arc<StratusStorage>,
+        storage: Arc<dyn StorageReader>,
         kafka_connector: Option<KafkaConnector>,
         importer_mode: ImporterMode,
     ) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
-        const TASK_NAME: &str = "importer::init";
+        const TASK_NAME: &str = "importer::online";
         tracing::info!("creating importer for follower node");
 
-        let chain = Arc::new(BlockchainClient::new_http_ws(&self.external_rpc, self.external_rpc_ws.as_deref(), self.external_rpc_timeout).await?);
+        let chain = BlockchainClient::new_http_ws(&self.external_rpc, self.external_rpc_ws.as_deref(), self.external_rpc_timeout).await?;
+        let chain = chain
+            .with_circuit_breaker(
+                "external_rpc",
+                self.external_rpc_circuit_breaker_threshold,
+                self.external_rpc_circuit_breaker_cooldown,
+            )
+            .with_middleware(MetricsMiddleware)
+            .with_middleware(RetryMiddleware {
+                max_retries: self.external_rpc_max_retries,
+                backoff: self.external_rpc_retry_backoff,
+            })
+            .with_middleware(TimeoutMiddleware {
+                timeout: self.external_rpc_timeout,
+            });
+        let chain = Arc::new(chain);
+        check_leader_code_size_limits(&chain, &executor).await?;
 
         let importer = Importer::new(
             executor,
@@ -77,15 +142,24 @@ impl ImporterConfig {
             Arc::clone(&chain),
             kafka_connector.map(Arc::new),
             self.sync_interval,
+            self.block_invariants,
+            self.receipts_verification,
+            self.replication_mode,
+            self.spot_check_interval,
             importer_mode,
         );
         let importer = Arc::new(importer);
 
-        spawn_named(TASK_NAME, {
+        spawn_supervised(TASK_NAME, {
             let importer = Arc::clone(&importer);
-            async move {
-                if let Err(e) = importer.run_importer_online().await {
-                    tracing::error!(reason = ?e, "importer-online failed");
+            move || {
+                let importer = Arc::clone(&importer);
+                async move {
+                    // the importer was shut down by an operator while the supervisor was waiting to restart it
+                    if GlobalState::is_importer_shutdown_warn(TASK_NAME) {
+                        return Ok(());
+                    }
+                    importer.run_importer_online().await
                 }
             }
         });
@@ -132,3 +206,120 @@ impl ImporterConfig {
         Ok(json!(true))
     }
 }
+
+// -----------------------------------------------------------------------------
+// Leader
+// -----------------------------------------------------------------------------
+
+/// Fails if the leader applies different contract size limits, because followers reexecute its blocks and deployments would have different
+/// results.
+///
+/// Only warns if the limits of the leader cannot be read, like when the external RPC is not a Stratus node or is not reachable yet.
+async fn check_leader_code_size_limits(chain: &BlockchainClient, executor: &Executor) -> anyhow::Result<()> {
+    let leader = match chain.call::<ChainSpec>("stratus_chainInfo", [(); 0]).await {
+        Ok(leader) => leader,
+        Err(e) => {
+            tracing::warn!(reason = ?e, "failed to read leader code size limits, assuming they are the same as the follower ones");
+            return Ok(());
+        }
+    };
+
+    let follower_limits = (executor.max_code_size(), executor.max_initcode_size());
+    let leader_limits = (leader.max_code_size, leader.max_initcode_size);
+    if follower_limits != leader_limits {
+        return log_and_err!(
+            payload = (follower_limits, leader_limits),
+            "follower code size limits (max code size, max initcode size) differ from the leader ones"
+        );
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Invariants
+// -----------------------------------------------------------------------------
+
+/// Indicates what the importer does with external blocks that violate the chain invariants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, serde::Deserialize)]
+pub enum InvariantsPolicy {
+    /// Stops the importer before the block is persisted.
+    #[serde(rename = "reject")]
+    Reject,
+
+    /// Logs the violations and imports the block.
+    #[default]
+    #[serde(rename = "warn")]
+    Warn,
+}
+
+impl FromStr for InvariantsPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(Self::Reject),
+            "warn" => Ok(Self::Warn),
+            s => Err(anyhow!("unknown block invariants policy: \"{}\" - valid values are reject, warn", s)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Receipts verification
+// -----------------------------------------------------------------------------
+
+/// Indicates what the importer does when the receipts of a reexecuted external block diverge from its header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, serde::Deserialize)]
+pub enum ReceiptsVerification {
+    /// Halts the importer before the block is persisted.
+    #[serde(rename = "strict")]
+    Strict,
+
+    /// Records the divergence and imports the block.
+    #[default]
+    #[serde(rename = "permissive")]
+    Permissive,
+}
+
+impl FromStr for ReceiptsVerification {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(Self::Strict),
+            "permissive" => Ok(Self::Permissive),
+            s => Err(anyhow!("unknown receipts verification: \"{}\" - valid values are strict, permissive", s)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Replication
+// -----------------------------------------------------------------------------
+
+/// Indicates how the importer replicates the state of external blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, serde::Deserialize)]
+pub enum ReplicationMode {
+    /// Reexecutes all transactions in the local EVM.
+    #[default]
+    #[serde(rename = "reexecute")]
+    Reexecute,
+
+    /// Applies the state changes sent by a Stratus leader, reexecuting only spot-checked blocks.
+    ///
+    /// Blocks fetched without changes, like blocks from other clients, are reexecuted.
+    #[serde(rename = "delta")]
+    Delta,
+}
+
+impl FromStr for ReplicationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "reexecute" => Ok(Self::Reexecute),
+            "delta" => Ok(Self::Delta),
+            s => Err(anyhow!("unknown replication mode: \"{}\" - valid values are reexecute, delta", s)),
+        }
+    }
+}
@@ -4,3 +4,6 @@ pub(crate) mod importer_config;
 
 pub use importer::Importer;
 pub use importer_config::ImporterConfig;
+pub use importer_config::InvariantsPolicy;
+pub use importer_config::ReceiptsVerification;
+pub use importer_config::ReplicationMode;
@@ -0,0 +1,127 @@
+use parking_lot::Mutex;
+
+use crate::eth::primitives::UnixTime;
+
+/// Source of the current time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> UnixTime;
+}
+
+/// Clock backed by the system time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> UnixTime {
+        UnixTime::now()
+    }
+}
+
+/// Generates strictly increasing block timestamps, even if the clock source goes backwards because of NTP adjustments.
+pub struct BlockClock {
+    source: Box<dyn Clock>,
+    state: Mutex<BlockClockState>,
+}
+
+#[derive(Default)]
+struct BlockClockState {
+    /// Fixed increment applied to the previous block timestamp instead of reading the clock source.
+    fixed_delta: Option<u64>,
+
+    /// Timestamp of the last generated block.
+    last: Option<UnixTime>,
+}
+
+impl std::fmt::Debug for BlockClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockClock").finish_non_exhaustive()
+    }
+}
+
+impl BlockClock {
+    pub fn new(source: Box<dyn Clock>) -> Self {
+        Self {
+            source,
+            state: Mutex::new(BlockClockState::default()),
+        }
+    }
+
+    /// Configures the clock increments and the timestamp of the last generated block.
+    pub fn configure(&self, fixed_delta: Option<u64>, last: UnixTime) {
+        tracing::info!(?fixed_delta, ?last, "configuring block clock");
+        let mut state = self.state.lock();
+        state.fixed_delta = fixed_delta;
+        state.last = Some(last);
+    }
+
+    /// Returns the timestamp of the next block.
+    pub fn next_timestamp(&self) -> UnixTime {
+        let mut state = self.state.lock();
+        let next = match (state.last, state.fixed_delta) {
+            (None, _) => self.source.now(),
+            (Some(last), Some(fixed_delta)) => UnixTime::from(last.saturating_add(fixed_delta)),
+            (Some(last), None) => {
+                let now = self.source.now();
+                if *now > *last {
+                    now
+                } else {
+                    tracing::warn!(?now, ?last, "clock source did not advance since last block, incrementing last block timestamp");
+                    UnixTime::from(last.saturating_add(1))
+                }
+            }
+        };
+        state.last = Some(next);
+        next
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::*;
+
+    struct FakeClock(Arc<AtomicU64>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> UnixTime {
+            UnixTime::from(self.0.load(Ordering::Relaxed))
+        }
+    }
+
+    #[test]
+    fn test_block_clock_is_strictly_increasing() {
+        let time = Arc::new(AtomicU64::new(100));
+        let clock = BlockClock::new(Box::new(FakeClock(Arc::clone(&time))));
+
+        assert_eq!(*clock.next_timestamp(), 100);
+
+        // same second
+        assert_eq!(*clock.next_timestamp(), 101);
+
+        // clock goes backwards
+        time.store(90, Ordering::Relaxed);
+        assert_eq!(*clock.next_timestamp(), 102);
+
+        // clock catches up
+        time.store(200, Ordering::Relaxed);
+        assert_eq!(*clock.next_timestamp(), 200);
+    }
+
+    #[test]
+    fn test_block_clock_fixed_delta() {
+        let time = Arc::new(AtomicU64::new(100));
+        let clock = BlockClock::new(Box::new(FakeClock(Arc::clone(&time))));
+        clock.configure(Some(5), UnixTime::from(50));
+
+        assert_eq!(*clock.next_timestamp(), 55);
+        time.store(1000, Ordering::Relaxed);
+        assert_eq!(*clock.next_timestamp(), 60);
+    }
+}
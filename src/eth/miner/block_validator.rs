@@ -0,0 +1,129 @@
+//! Validation of mined blocks before they are persisted.
+//!
+//! Indexers rely on transaction indexes, log indexes and gas used being consistent inside a block, so a block where they drifted is rejected
+//! instead of corrupting the data derived from it.
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::StratusError;
+
+/// Recomputes transaction indexes, block log indexes and gas used of a mined block, failing with the first one that does not match.
+pub fn validate_block_assembly(block: &Block) -> Result<(), StratusError> {
+    let number = block.number();
+    let invalid = |field: &'static str, expected: u64, found: u64| StratusError::MinerBlockAssemblyInvalid {
+        number,
+        field,
+        expected,
+        found,
+    };
+
+    let mut log_index = 0u64;
+    let mut cumulative_gas_used = 0u64;
+    for (transaction_index, tx) in block.transactions.iter().enumerate() {
+        let transaction_index = transaction_index as u64;
+        if tx.transaction_index.0 != transaction_index {
+            return Err(invalid("transaction_index", transaction_index, tx.transaction_index.0));
+        }
+        if tx.block_number != number {
+            return Err(invalid("transaction_block_number", number.as_u64(), tx.block_number.as_u64()));
+        }
+
+        for log in &tx.logs {
+            if log.log_index.0 != log_index {
+                return Err(invalid("log_index", log_index, log.log_index.0));
+            }
+            if log.transaction_index.0 != transaction_index {
+                return Err(invalid("log_transaction_index", transaction_index, log.transaction_index.0));
+            }
+            log_index += 1;
+        }
+
+        cumulative_gas_used = cumulative_gas_used.saturating_add(tx.execution.gas.as_u64());
+        if tx.cumulative_gas_used.as_u64() != cumulative_gas_used {
+            return Err(invalid("cumulative_gas_used", cumulative_gas_used, tx.cumulative_gas_used.as_u64()));
+        }
+    }
+
+    if block.header.gas_used.as_u64() != cumulative_gas_used {
+        return Err(invalid("gas_used", cumulative_gas_used, block.header.gas_used.as_u64()));
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::LogMined;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+
+    /// Creates a block with two transactions that emitted two logs each.
+    fn block() -> Block {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        for transaction_index in 0..2u64 {
+            let mut tx: TransactionMined = Faker.fake();
+            tx.transaction_index = transaction_index.into();
+            tx.block_number = block.number();
+            tx.execution.gas = 21_000u64.into();
+            tx.cumulative_gas_used = (21_000 * (transaction_index + 1)).into();
+            tx.logs = (0..2u64)
+                .map(|i| LogMined {
+                    log: Faker.fake(),
+                    transaction_hash: tx.input.hash,
+                    transaction_index: transaction_index.into(),
+                    log_index: (transaction_index * 2 + i).into(),
+                    block_number: block.number(),
+                    block_hash: block.hash(),
+                })
+                .collect();
+            block.transactions.push(tx);
+        }
+        block.header.gas_used = 42_000u64.into();
+        block
+    }
+
+    fn invalid_field(block: &Block) -> Option<&'static str> {
+        match validate_block_assembly(block) {
+            Ok(()) => None,
+            Err(StratusError::MinerBlockAssemblyInvalid { field, .. }) => Some(field),
+            Err(e) => panic!("unexpected error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_block() {
+        assert_eq!(invalid_field(&block()), None);
+        assert_eq!(invalid_field(&Block::new(BlockNumber::ONE, UnixTime::from(1234567891))), None);
+    }
+
+    #[test]
+    fn test_drifted_block() {
+        let mut drifted = block();
+        drifted.transactions[1].transaction_index = 2u64.into();
+        assert_eq!(invalid_field(&drifted), Some("transaction_index"));
+
+        // log indexes restarting in each transaction
+        let mut drifted = block();
+        drifted.transactions[1].logs[0].log_index = 0u64.into();
+        assert_eq!(invalid_field(&drifted), Some("log_index"));
+
+        let mut drifted = block();
+        drifted.transactions[1].logs[1].transaction_index = 0u64.into();
+        assert_eq!(invalid_field(&drifted), Some("log_transaction_index"));
+
+        let mut drifted = block();
+        drifted.transactions[1].cumulative_gas_used = 21_000u64.into();
+        assert_eq!(invalid_field(&drifted), Some("cumulative_gas_used"));
+
+        let mut drifted = block();
+        drifted.header.gas_used = 21_000u64.into();
+        assert_eq!(invalid_field(&drifted), Some("gas_used"));
+    }
+}
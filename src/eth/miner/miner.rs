@@ -4,7 +4,6 @@ use std::sync::mpsc;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::anyhow;
 use itertools::Itertools;
 use keccak_hasher::KeccakHasher;
 use parking_lot::Mutex;
@@ -15,28 +14,46 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::Span;
 
+use crate::eth::analytics::GasAnalytics;
+use crate::eth::miner::validate_block_assembly;
+use crate::eth::miner::BlockHashing;
+use crate::eth::miner::MinerConfig;
 use crate::eth::miner::MinerMode;
+use crate::eth::miner::PendingPool;
+use crate::eth::miner::PendingPoolTicket;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalTransactionExecution;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Index;
 use crate::eth::primitives::LocalTransactionExecution;
 use crate::eth::primitives::LogMined;
+use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::PendingBlockHeader;
 use crate::eth::primitives::Size;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionMined;
+use crate::eth::storage::CacheInvalidation;
 use crate::eth::storage::Storage;
+use crate::eth::storage::StorageReader;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
 use crate::ext::DisplayExt;
-use crate::globals::STRATUS_SHUTDOWN_SIGNAL;
+use crate::if_else;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::infra::task_registry;
+use crate::infra::task_registry::TaskKind;
 use crate::infra::tracing::SpanExt;
 use crate::log_and_err;
+use crate::ChainState;
+use crate::GlobalState;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "tracing")] {
@@ -65,6 +82,36 @@ pub struct Miner {
     /// Broadcasts transaction logs events.
     pub notifier_logs: broadcast::Sender<LogMined>,
 
+    /// Broadcasts committed blocks with all their transactions.
+    pub notifier_committed_blocks: broadcast::Sender<Block>,
+
+    /// Aggregates gas usage of committed blocks, if enabled.
+    pub gas_analytics: Option<GasAnalytics>,
+
+    /// How the hash of locally mined blocks is calculated.
+    block_hashing: BlockHashing,
+
+    /// Address recorded as author of locally mined blocks.
+    block_author: Address,
+
+    // -------------------------------------------------------------------------
+    // Block limits
+    // -------------------------------------------------------------------------
+    /// Maximum gas used by all transactions of a locally mined block.
+    block_gas_limit: Option<u64>,
+
+    /// Maximum number of transactions of a locally mined block.
+    block_max_transactions: Option<usize>,
+
+    /// Maximum bytes of data of all transactions of a locally mined block.
+    block_max_bytes: Option<usize>,
+
+    /// Resources used by local transactions saved in the pending block.
+    pending_block_usage: Mutex<PendingBlockUsage>,
+
+    /// Transactions waiting for space in a new block because a block limit was reached.
+    pub pending_pool: PendingPool,
+
     // -------------------------------------------------------------------------
     // Shutdown
     // -------------------------------------------------------------------------
@@ -78,14 +125,23 @@ pub struct Miner {
 /// Locks used in operations that mutate state.
 #[derive(Default)]
 pub struct MinerLocks {
-    save_execution: Mutex<()>,
+    pub save_execution: Mutex<()>,
     pub mine_and_commit: Mutex<()>,
     mine: Mutex<()>,
     commit: Mutex<()>,
 }
 
+/// Resources used by local transactions saved in the pending block with the specified number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PendingBlockUsage {
+    block_number: BlockNumber,
+    gas: u64,
+    transactions: usize,
+    bytes: usize,
+}
+
 impl Miner {
-    pub fn new(storage: Arc<StratusStorage>, mode: MinerMode) -> Self {
+    pub fn new(storage: Arc<StratusStorage>, mode: MinerMode, config: &MinerConfig) -> Self {
         tracing::info!(?mode, "creating block miner");
         Self {
             locks: MinerLocks::default(),
@@ -95,7 +151,16 @@ impl Miner {
             notifier_pending_txs: broadcast::channel(u16::MAX as usize).0,
             notifier_blocks: broadcast::channel(u16::MAX as usize).0,
             notifier_logs: broadcast::channel(u16::MAX as usize).0,
-            shutdown_signal: Mutex::new(STRATUS_SHUTDOWN_SIGNAL.child_token()),
+            notifier_committed_blocks: broadcast::channel(1024).0,
+            gas_analytics: config.gas_analytics.then(GasAnalytics::default),
+            block_hashing: config.block_hashing,
+            block_author: config.block_author,
+            block_gas_limit: config.block_gas_limit,
+            block_max_transactions: config.block_max_transactions,
+            block_max_bytes: config.block_max_bytes,
+            pending_block_usage: Mutex::new(PendingBlockUsage::default()),
+            pending_pool: PendingPool::new(config.block_ordering),
+            shutdown_signal: Mutex::new(GlobalState::shutdown_signal().child_token()),
             interval_joinset: AsyncMutex::new(None),
         }
     }
@@ -115,16 +180,24 @@ impl Miner {
 
         // spawn miner and ticker
         let (ticks_tx, ticks_rx) = mpsc::channel();
-        let new_shutdown_signal = STRATUS_SHUTDOWN_SIGNAL.child_token();
+        let new_shutdown_signal = GlobalState::shutdown_signal().child_token();
+        let chain = ChainState::current();
         let mut joinset = JoinSet::new();
 
         joinset.spawn_blocking({
             let shutdown = new_shutdown_signal.clone();
             let miner_clone = Arc::clone(self);
-            || interval_miner::run(miner_clone, ticks_rx, shutdown)
+            let chain = Arc::clone(&chain);
+            task_registry::track_blocking("miner::interval", TaskKind::Blocking, move || {
+                let _chain = chain.enter();
+                interval_miner::run(miner_clone, ticks_rx, shutdown);
+            })
         });
 
-        joinset.spawn(interval_miner_ticker::run(block_time, ticks_tx, new_shutdown_signal.clone()));
+        joinset.spawn(chain.scope(task_registry::track_async(
+            "miner::interval-ticker",
+            interval_miner_ticker::run(block_time, ticks_tx, new_shutdown_signal.clone()),
+        )));
 
         *self.shutdown_signal.lock() = new_shutdown_signal;
         *self.interval_joinset.lock().await = Some(joinset);
@@ -160,6 +233,11 @@ impl Miner {
         *self.mode.read()
     }
 
+    /// Address recorded as author of locally mined blocks.
+    pub fn block_author(&self) -> Address {
+        self.block_author
+    }
+
     fn set_mode(&self, new_mode: MinerMode) {
         *self.mode.write() = new_mode;
     }
@@ -207,11 +285,27 @@ impl Miner {
         let is_automine = self.mode().is_automine();
 
         // if automine is enabled, only one transaction can enter the block at a time.
-        let _save_execution_lock = if is_automine { Some(self.locks.save_execution.lock()) } else { None };
+        // if block limits are enabled, the pending block usage must be checked and updated atomically.
+        let _save_execution_lock = if is_automine || self.has_block_limits() {
+            Some(self.locks.save_execution.lock())
+        } else {
+            None
+        };
+
+        // check block limits
+        let pending_block_usage = match &tx_execution {
+            TransactionExecution::Local(tx) if self.has_block_limits() => Some(self.check_block_limits(tx)?),
+            _ => None,
+        };
 
         // save execution to temporary storage
         self.storage.save_execution(tx_execution, check_conflicts)?;
 
+        // track pending block usage only after the execution is saved
+        if let Some(pending_block_usage) = pending_block_usage {
+            *self.pending_block_usage.lock() = pending_block_usage;
+        }
+
         // notify
         let _ = self.notifier_pending_txs.send(tx_hash);
 
@@ -223,6 +317,71 @@ impl Miner {
         Ok(())
     }
 
+    fn has_block_limits(&self) -> bool {
+        self.block_gas_limit.is_some() || self.block_max_transactions.is_some() || self.block_max_bytes.is_some()
+    }
+
+    /// Checks if a local transaction fits in the pending block without exceeding the block limits.
+    ///
+    /// Returns the pending block usage after the transaction is included.
+    fn check_block_limits(&self, tx: &LocalTransactionExecution) -> Result<PendingBlockUsage, StratusError> {
+        let tx_gas = tx.result.execution.gas.as_u64();
+        let tx_bytes = tx.input.input.len();
+
+        // transactions that never fit in a block
+        if let Some(limit) = self.block_gas_limit {
+            if tx_gas > limit {
+                return Err(StratusError::TransactionGasAboveBlockLimit { gas: tx_gas, limit });
+            }
+        }
+        if let Some(limit) = self.block_max_bytes {
+            if tx_bytes > limit {
+                return Err(StratusError::TransactionSizeAboveBlockLimit { size: tx_bytes, limit });
+            }
+        }
+
+        // usage tracked for a previous block does not count for the current one
+        let block_number = tx.evm_input.block_number;
+        let tracked = *self.pending_block_usage.lock();
+        let pending = if_else!(
+            tracked.block_number == block_number,
+            tracked,
+            PendingBlockUsage {
+                block_number,
+                ..PendingBlockUsage::default()
+            }
+        );
+
+        // transactions that do not fit in the pending block
+        let new_pending = PendingBlockUsage {
+            block_number,
+            gas: pending.gas.saturating_add(tx_gas),
+            transactions: pending.transactions + 1,
+            bytes: pending.bytes.saturating_add(tx_bytes),
+        };
+        if let Some(limit) = self.block_gas_limit {
+            if new_pending.gas > limit {
+                return Err(StratusError::TransactionBlockGasLimitReached { pending: pending.gas, limit });
+            }
+        }
+        if let Some(limit) = self.block_max_transactions {
+            if new_pending.transactions > limit {
+                return Err(StratusError::TransactionBlockTransactionsLimitReached { limit });
+            }
+        }
+        if let Some(limit) = self.block_max_bytes {
+            if new_pending.bytes > limit {
+                return Err(StratusError::TransactionBlockSizeLimitReached { pending: pending.bytes, limit });
+            }
+        }
+        Ok(new_pending)
+    }
+
+    /// Blocks until a block after the full block is pending and the transaction is the next one in the pending pool.
+    pub fn wait_block_space(&self, ticket: &PendingPoolTicket, full_block_number: BlockNumber) -> Result<(), StratusError> {
+        ticket.wait(|| self.storage.read_pending_block_header().number > full_block_number)
+    }
+
     /// Mines external block and external transactions.
     ///
     /// Local transactions are not allowed to be part of the block.
@@ -275,6 +434,13 @@ impl Miner {
         let block = self.storage.finish_pending_block()?;
         Span::with(|s| s.rec_str("block_number", &block.header.number));
 
+        // wake up transactions waiting for space in the new pending block
+        self.pending_pool.notify();
+
+        // track conflict checks of the transactions
+        #[cfg(feature = "metrics")]
+        metrics::inc_block_conflict_check(block.conflict_check_time, self.storage.perm_kind().to_string());
+
         // mine transactions
         let mut local_txs = Vec::with_capacity(block.transactions.len());
         for tx in block.transactions.into_values() {
@@ -285,7 +451,22 @@ impl Miner {
             }
         }
 
-        block_from_local(block.header, local_txs)
+        let parent_hash = self.parent_hash(block.header.number)?;
+        block_from_local(block.header, local_txs, parent_hash, self.block_author, self.block_hashing)
+    }
+
+    /// Hash of the block mined before the specified block.
+    ///
+    /// With canonical hashing it is read from storage because it cannot be derived from the block number.
+    fn parent_hash(&self, number: BlockNumber) -> anyhow::Result<Hash> {
+        let Some(parent_number) = number.prev() else { return Ok(Hash::ZERO) };
+        match self.block_hashing {
+            BlockHashing::Legacy => Ok(parent_number.hash()),
+            BlockHashing::Canonical => match self.storage.read_block_header(BlockFilter::Number(parent_number))? {
+                Some(parent) => Ok(parent.header.hash),
+                None => log_and_err!(payload = parent_number, "failed to mine local block because its parent block is not saved"),
+            },
+        }
     }
 
     /// Persists a mined block to permanent storage and prepares new block.
@@ -302,6 +483,12 @@ impl Miner {
 
         tracing::info!(%block_number, "miner acquired commit lock");
 
+        // validate indexes and gas before they reach storage and indexers
+        if let Err(e) = validate_block_assembly(&block) {
+            tracing::error!(reason = ?e, %block_number, "refusing to commit block with invalid assembly");
+            return Err(e.into());
+        }
+
         // extract fields to use in notifications if have subscribers
         let block_header = if self.notifier_blocks.receiver_count() > 0 {
             Some(block.header.clone())
@@ -313,11 +500,36 @@ impl Miner {
         } else {
             None
         };
+        let committed_block = if self.notifier_committed_blocks.receiver_count() > 0 {
+            Some(block.clone())
+        } else {
+            None
+        };
+        let analytics_block = if self.gas_analytics.is_some() { Some(block.clone()) } else { None };
 
         // save storage
+        // the mined block number is updated only after the block is persisted (see `StratusStorage` for the commit protocol)
+        #[cfg(feature = "metrics")]
+        let start = metrics::now();
         self.storage.save_block(block)?;
         self.storage.set_mined_block_number(block_number)?;
 
+        // a committed block is safe because it is never reorganized
+        // local blocks are also final because this node is the leader, external blocks are finalized by the importer after verifying them
+        if not(self.mode().is_external()) {
+            self.storage.set_finalized_block_number(block_number)?;
+        }
+        #[cfg(feature = "metrics")]
+        metrics::inc_block_storage_write(start.elapsed(), self.storage.perm_kind().to_string());
+
+        // track gas usage only after the block is committed, so failed commits are not aggregated
+        // usage is persisted after the block, so a restart only aggregates blocks committed after it was persisted
+        // failures are logged by the storage and the usage is persisted again with the next block
+        if let (Some(ref gas_analytics), Some(analytics_block)) = (&self.gas_analytics, analytics_block) {
+            gas_analytics.record_block(&analytics_block);
+            let _ = self.storage.save_gas_usage(gas_analytics.snapshot());
+        }
+
         // notify
         if let Some(block_logs) = block_logs {
             for log in block_logs {
@@ -327,9 +539,35 @@ impl Miner {
         if let Some(block_header) = block_header {
             let _ = self.notifier_blocks.send(block_header);
         }
+        if let Some(committed_block) = committed_block {
+            let _ = self.notifier_committed_blocks.send(committed_block);
+        }
 
         Ok(())
     }
+
+    /// Marks a committed external block as finalized, after the importer verified it against the leader.
+    pub fn finalize_external_block(&self, block_number: BlockNumber) -> Result<(), StratusError> {
+        self.storage.set_finalized_block_number(block_number)
+    }
+
+    /// Discards the transactions of the pending block, restarting it at the same number.
+    pub fn discard_pending_block(&self) -> Result<PendingBlock, StratusError> {
+        let _mine_lock = self.locks.mine.lock();
+        self.storage.discard_pending_block()
+    }
+
+    /// Evicts the cached state touched by a committed block whose changes were not computed by this node.
+    pub fn invalidate_cache(&self, invalidation: &CacheInvalidation) {
+        self.storage.invalidate_cache(invalidation);
+    }
+
+    #[cfg(feature = "dev")]
+    /// Resets the storage to the genesis state used in dev-mode.
+    pub fn reset_to_genesis(&self) -> Result<(), StratusError> {
+        let _mine_and_commit_lock = self.locks.mine_and_commit.lock();
+        self.storage.reset_to_genesis()
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -338,11 +576,18 @@ impl Miner {
 
 fn mine_external_transactions(block_number: BlockNumber, txs: Vec<ExternalTransactionExecution>) -> anyhow::Result<Vec<TransactionMined>> {
     let mut mined_txs = Vec::with_capacity(txs.len());
+    let mut cumulative_gas_used = Gas::ZERO;
     for tx in txs {
         if tx.tx.block_number()? != block_number {
             return log_and_err!("failed to mine external block because one of the transactions does not belong to the external block");
         }
-        mined_txs.push(TransactionMined::from_external(tx.tx, tx.receipt, tx.evm_execution.execution)?);
+        cumulative_gas_used = Gas::from(cumulative_gas_used.as_u64().saturating_add(tx.evm_execution.execution.gas.as_u64()));
+        mined_txs.push(TransactionMined::from_external(
+            tx.tx,
+            tx.receipt,
+            tx.evm_execution.execution,
+            cumulative_gas_used,
+        )?);
     }
     Ok(mined_txs)
 }
@@ -354,8 +599,17 @@ fn block_from_external(external_block: ExternalBlock, mined_txs: Vec<Transaction
     })
 }
 
-pub fn block_from_local(pending_header: PendingBlockHeader, txs: Vec<LocalTransactionExecution>) -> anyhow::Result<Block> {
+pub fn block_from_local(
+    pending_header: PendingBlockHeader,
+    txs: Vec<LocalTransactionExecution>,
+    parent_hash: Hash,
+    author: Address,
+    hashing: BlockHashing,
+) -> anyhow::Result<Block> {
     let mut block = Block::new(pending_header.number, *pending_header.timestamp);
+    block.header.parent_hash = parent_hash;
+    block.header.author = author;
+    block.header.miner = author;
     block.transactions.reserve(txs.len());
     block.header.size = Size::from(txs.len() as u64);
 
@@ -384,27 +638,33 @@ pub fn block_from_local(pending_header: PendingBlockHeader, txs: Vec<LocalTransa
             log_index = log_index + Index::ONE;
         }
 
+        // add transaction gas to block
+        block.header.gas_used = Gas::from(block.header.gas_used.as_u64().saturating_add(tx.result.execution.gas.as_u64()));
+
         // mine transaction
         let mined_transaction = TransactionMined {
             input: tx.input,
             execution: tx.result.execution,
             transaction_index,
+            cumulative_gas_used: block.header.gas_used,
             block_number: block.header.number,
             block_hash: block.header.hash,
             logs: mined_logs,
         };
-
-        // add transaction to block
         block.transactions.push(mined_transaction);
     }
 
-    // calculate transactions hash
+    // calculate transactions and receipts hashes
     if not(block.transactions.is_empty()) {
         let transactions_hashes: Vec<Hash> = block.transactions.iter().map(|x| x.input.hash).collect();
         block.header.transactions_root = triehash::ordered_trie_root::<KeccakHasher, _>(transactions_hashes).into();
+        block.header.receipts_root = block.calculate_receipts_root();
     }
 
     // calculate final block hash
+    if hashing.is_canonical() {
+        block.header.hash = block.header.canonical_hash();
+    }
 
     // replicate calculated block hash from header to transactions and logs
     for transaction in block.transactions.iter_mut() {
@@ -414,7 +674,7 @@ pub fn block_from_local(pending_header: PendingBlockHeader, txs: Vec<LocalTransa
         }
     }
 
-    // TODO: calculate state_root, receipts_root and parent_hash
+    // TODO: calculate state_root
     Ok(block)
 }
 
@@ -431,6 +691,7 @@ mod interval_miner {
     use tokio_util::sync::CancellationToken;
 
     use crate::eth::miner::Miner;
+    use crate::infra::task_registry;
     use crate::infra::tracing::warn_task_cancellation;
     use crate::infra::tracing::warn_task_rx_closed;
 
@@ -438,6 +699,8 @@ mod interval_miner {
         const TASK_NAME: &str = "interval-miner-ticker";
 
         loop {
+            task_registry::heartbeat();
+
             if cancellation.is_cancelled() {
                 warn_task_cancellation(TASK_NAME);
                 break;
@@ -533,3 +796,195 @@ mod interval_miner_ticker {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use clap::Parser;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::TransactionInput;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::storage::permanent::ReadOnlyPermanentStorage;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::WriteStorage;
+
+    fn miner(args: &[&str]) -> Miner {
+        let config = MinerConfig::parse_from(["test"].iter().chain(args));
+        Miner::new(Arc::new(StratusStorage::new_test().unwrap()), MinerMode::External, &config)
+    }
+
+    fn local_tx(gas: u64, bytes: usize) -> LocalTransactionExecution {
+        let mut tx: LocalTransactionExecution = Faker.fake();
+        tx.evm_input.block_number = BlockNumber::ONE;
+        tx.input.input = Bytes::from(vec![0; bytes]);
+        tx.result.execution.gas = Gas::from(gas);
+        tx
+    }
+
+    /// Checks a transaction against the block limits and tracks it as saved when it fits.
+    fn include(miner: &Miner, tx: &LocalTransactionExecution) -> Result<(), StratusError> {
+        let usage = miner.check_block_limits(tx)?;
+        *miner.pending_block_usage.lock() = usage;
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_max_transactions() {
+        let miner = miner(&["--block-max-transactions", "2"]);
+        include(&miner, &local_tx(1, 1)).unwrap();
+        include(&miner, &local_tx(1, 1)).unwrap();
+        assert!(matches!(
+            include(&miner, &local_tx(1, 1)),
+            Err(StratusError::TransactionBlockTransactionsLimitReached { limit: 2 })
+        ));
+
+        // a new pending block has space again
+        let mut tx = local_tx(1, 1);
+        tx.evm_input.block_number = BlockNumber::from(2u64);
+        include(&miner, &tx).unwrap();
+    }
+
+    #[test]
+    fn test_wait_block_space_wakes_up_when_block_is_mined() {
+        let miner = miner(&["--block-max-transactions", "1"]);
+        let full_block_number = miner.storage.read_pending_block_header().number;
+        let ticket = miner.pending_pool.enqueue(&TransactionInput::default());
+
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(|| done_tx.send(miner.wait_block_space(&ticket, full_block_number)).unwrap());
+
+            // waits while the full block is pending
+            assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+            miner.mine_local().unwrap();
+            assert!(done_rx.recv_timeout(Duration::from_millis(50)).unwrap().is_ok());
+        });
+    }
+
+    #[test]
+    fn test_block_max_bytes() {
+        let miner = miner(&["--block-max-bytes", "100"]);
+        assert!(matches!(
+            include(&miner, &local_tx(1, 1024 * 1024)),
+            Err(StratusError::TransactionSizeAboveBlockLimit { limit: 100, .. })
+        ));
+        include(&miner, &local_tx(1, 60)).unwrap();
+        assert!(matches!(
+            include(&miner, &local_tx(1, 60)),
+            Err(StratusError::TransactionBlockSizeLimitReached { pending: 60, limit: 100 })
+        ));
+        include(&miner, &local_tx(1, 40)).unwrap();
+    }
+
+    #[test]
+    fn test_block_limits_disabled() {
+        let miner = miner(&[]);
+        assert!(not(miner.has_block_limits()));
+    }
+
+    #[test]
+    fn test_block_from_local_assembly() {
+        let mut txs = vec![local_tx(21_000, 1), local_tx(50_000, 1)];
+        for tx in &mut txs {
+            tx.result.execution.logs = vec![Faker.fake(), Faker.fake()];
+        }
+
+        let author: Address = Faker.fake();
+        let block = block_from_local(
+            PendingBlockHeader::new(BlockNumber::ONE, UnixTime::now()),
+            txs,
+            Hash::ZERO,
+            author,
+            BlockHashing::Canonical,
+        )
+        .unwrap();
+        validate_block_assembly(&block).unwrap();
+        assert_eq!(block.header.gas_used, Gas::from(71_000u64));
+        assert_eq!(block.header.author, author);
+        assert_eq!(block.transactions[1].logs[0].log_index, Index::new(2));
+    }
+
+    #[test]
+    fn test_block_author() {
+        assert_eq!(miner(&[]).block_author(), Address::COINBASE);
+
+        let author = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+        assert_eq!(miner(&["--block-author", author]).block_author(), author.parse().unwrap());
+    }
+
+    /// Commits the first block after genesis with a miner in the given mode.
+    fn commit_first_block(mode: MinerMode) -> (Miner, Arc<StratusStorage>) {
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::genesis()).unwrap();
+        let temp = InMemoryTemporaryStorage::new(2.into());
+        let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+
+        let miner = Miner::new(Arc::clone(&storage), mode, &MinerConfig::parse_from(["test"]));
+        miner.commit(Block::new(BlockNumber::ONE, UnixTime::ZERO)).unwrap();
+        (miner, storage)
+    }
+
+    #[test]
+    fn test_commit_local_block_is_finalized() {
+        let (_, storage) = commit_first_block(MinerMode::Automine);
+        assert_eq!(storage.read_block(BlockFilter::Finalized).unwrap().unwrap().number(), BlockNumber::ONE);
+        assert_eq!(storage.read_block(BlockFilter::Safe).unwrap().unwrap().number(), BlockNumber::ONE);
+    }
+
+    #[test]
+    fn test_commit_external_block_is_safe_until_finalized() {
+        let (miner, storage) = commit_first_block(MinerMode::External);
+        assert_eq!(storage.read_block(BlockFilter::Safe).unwrap().unwrap().number(), BlockNumber::ONE);
+        assert!(matches!(
+            storage.read_block(BlockFilter::Finalized),
+            Err(StratusError::RpcBlockFinalityUnavailable { .. })
+        ));
+
+        miner.finalize_external_block(BlockNumber::ONE).unwrap();
+        assert_eq!(storage.read_block(BlockFilter::Finalized).unwrap().unwrap().number(), BlockNumber::ONE);
+    }
+
+    #[tokio::test]
+    async fn test_commit_persists_gas_usage() {
+        let config = MinerConfig::parse_from(["test", "--gas-analytics"]);
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::genesis()).unwrap();
+        let temp = InMemoryTemporaryStorage::new(2.into());
+        let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+
+        let miner = Miner::new(Arc::clone(&storage), MinerMode::External, &config);
+        miner.commit(Block::new(BlockNumber::ONE, UnixTime::ZERO)).unwrap();
+        let usage = storage.read_gas_usage().unwrap().unwrap();
+        assert_eq!(usage.last_block, Some(BlockNumber::ONE));
+        assert_eq!(usage.blocks, 1);
+
+        // a restarted miner continues from the persisted usage
+        let restarted = config.init_with_mode(MinerMode::External, Arc::clone(&storage)).await.unwrap();
+        assert_eq!(restarted.gas_analytics.as_ref().unwrap().snapshot(), usage);
+    }
+
+    #[test]
+    fn test_commit_failure_does_not_record_gas_usage() {
+        let config = MinerConfig::parse_from(["test", "--gas-analytics"]);
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::genesis()).unwrap();
+        let perm = ReadOnlyPermanentStorage::new(Box::new(perm));
+        let temp = InMemoryTemporaryStorage::new(2.into());
+        let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+
+        let miner = Miner::new(Arc::clone(&storage), MinerMode::External, &config);
+        assert!(miner.commit(Block::new(BlockNumber::ONE, UnixTime::ZERO)).is_err());
+        assert_eq!(miner.gas_analytics.as_ref().unwrap().stats(usize::MAX).blocks, 0);
+    }
+}
@@ -2,10 +2,12 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 
 use crate::eth::miner::Miner;
+use crate::eth::primitives::Address;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
 use crate::ext::parse_duration;
@@ -21,6 +23,43 @@ pub struct MinerConfig {
     /// Target block time.
     #[arg(long = "block-mode", env = "BLOCK_MODE", default_value = "automine")]
     pub block_mode: MinerMode,
+
+    /// Maximum gas that can be used by all transactions of a locally mined block. Unlimited if not set.
+    #[arg(long = "block-gas-limit", env = "BLOCK_GAS_LIMIT")]
+    pub block_gas_limit: Option<u64>,
+
+    /// Maximum number of transactions of a locally mined block. Unlimited if not set.
+    #[arg(long = "block-max-transactions", env = "BLOCK_MAX_TRANSACTIONS")]
+    pub block_max_transactions: Option<usize>,
+
+    /// Maximum bytes of data of all transactions of a locally mined block. Unlimited if not set.
+    #[arg(long = "block-max-bytes", env = "BLOCK_MAX_BYTES")]
+    pub block_max_bytes: Option<usize>,
+
+    /// Ordering policy of transactions waiting for space in a new block after a block limit is reached.
+    #[arg(long = "block-ordering", env = "BLOCK_ORDERING", default_value = "fifo")]
+    pub block_ordering: BlockOrdering,
+
+    /// How the hash of locally mined blocks is calculated.
+    ///
+    /// Defaults to legacy so upgrading nodes keep producing the same hashes; canonical hashing must be enabled explicitly.
+    ///
+    /// Blocks already saved keep the hash they were mined with. New blocks always reference the saved hash of their parent, so a chain mined with
+    /// legacy hashing can switch to canonical hashing without rewriting existing data.
+    #[arg(long = "block-hashing", env = "BLOCK_HASHING", default_value = "legacy")]
+    pub block_hashing: BlockHashing,
+
+    /// Increments block timestamps by a fixed amount of seconds instead of using the system clock. Intended for deterministic test environments.
+    #[arg(long = "block-timestamp-delta", env = "BLOCK_TIMESTAMP_DELTA", value_parser = clap::value_parser!(u64).range(1..))]
+    pub block_timestamp_delta: Option<u64>,
+
+    /// Address recorded as author of locally mined blocks and returned by `eth_coinbase`.
+    #[arg(long = "block-author", env = "BLOCK_AUTHOR", default_value = "0x00000000000000000000000000000000000000ff")]
+    pub block_author: Address,
+
+    /// Aggregates gas used per contract and function to be queried with `stratus_gasStats`.
+    #[arg(long = "gas-analytics", env = "GAS_ANALYTICS", default_value = "false")]
+    pub gas_analytics: bool,
 }
 
 impl MinerConfig {
@@ -36,6 +75,8 @@ impl MinerConfig {
                 MinerMode::External
             }
             NodeMode::Leader | NodeMode::FakeLeader => self.block_mode,
+            // archive nodes never mine, but the miner still serves the pending block to RPC reads
+            NodeMode::Archive => MinerMode::External,
         };
 
         self.init_with_mode(mode, storage).await
@@ -45,8 +86,14 @@ impl MinerConfig {
     pub async fn init_with_mode(&self, mode: MinerMode, storage: Arc<StratusStorage>) -> anyhow::Result<Arc<Miner>> {
         tracing::info!(config = ?self, mode = ?mode, "creating block miner with specific mode");
 
+        // configure block timestamps starting from the block being mined
+        storage.configure_block_clock(self.block_timestamp_delta);
+
         // create miner
-        let miner = Miner::new(Arc::clone(&storage), mode);
+        let miner = Miner::new(Arc::clone(&storage), mode, self);
+        if let Some(ref gas_analytics) = miner.gas_analytics {
+            gas_analytics.load(storage.as_ref())?;
+        }
         let miner = Arc::new(miner);
 
         if let MinerMode::Interval(block_time) = mode {
@@ -102,3 +149,61 @@ impl FromStr for MinerMode {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Ordering
+// -----------------------------------------------------------------------------
+
+/// Indicates the order transactions waiting for block space are included in new blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, serde::Deserialize)]
+pub enum BlockOrdering {
+    /// Transactions are included in the order they were received.
+    #[default]
+    #[serde(rename = "fifo")]
+    Fifo,
+
+    /// Transactions with higher gas price are included first. Ties are included in the order they were received.
+    #[serde(rename = "gas-price")]
+    GasPrice,
+}
+
+impl FromStr for BlockOrdering {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "fifo" => Ok(Self::Fifo),
+            "gas-price" => Ok(Self::GasPrice),
+            s => Err(anyhow!("unknown block ordering: \"{}\" - valid values are fifo, gas-price", s)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Hashing
+// -----------------------------------------------------------------------------
+
+/// Indicates how the hash of locally mined blocks is calculated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, serde::Deserialize)]
+pub enum BlockHashing {
+    /// Keccak256 of the RLP-encoded header, like Ethereum clients, so the hash can be verified from the header fields.
+    #[serde(rename = "canonical")]
+    Canonical,
+
+    /// Keccak256 of the block number, used by previous versions.
+    #[default]
+    #[serde(rename = "legacy")]
+    Legacy,
+}
+
+impl FromStr for BlockHashing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "canonical" => Ok(Self::Canonical),
+            "legacy" => Ok(Self::Legacy),
+            s => Err(anyhow!("unknown block hashing: \"{}\" - valid values are canonical, legacy", s)),
+        }
+    }
+}
@@ -1,7 +1,18 @@
+mod block_clock;
+mod block_validator;
 #[allow(clippy::module_inception)]
 mod miner;
 mod miner_config;
+mod pending_pool;
 
+pub use block_clock::BlockClock;
+pub use block_clock::Clock;
+pub use block_clock::SystemClock;
+pub use block_validator::validate_block_assembly;
 pub use miner::Miner;
+pub use miner_config::BlockHashing;
+pub use miner_config::BlockOrdering;
 pub use miner_config::MinerConfig;
 pub use miner_config::MinerMode;
+pub use pending_pool::PendingPool;
+pub use pending_pool::PendingPoolTicket;
@@ -0,0 +1,212 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use parking_lot::Condvar;
+use parking_lot::Mutex;
+
+use crate::eth::miner::BlockOrdering;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::Wei;
+use crate::GlobalState;
+
+/// Maximum time a waiting transaction sleeps before checking if the application is being shutdown.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Transactions waiting for space in a new block because the pending block reached the block gas limit.
+///
+/// The pool does not hold the transactions, only their position in the queue. Each waiting transaction is re-executed
+/// against the new pending block when it is its turn, according to the configured [`BlockOrdering`].
+pub struct PendingPool {
+    ordering: BlockOrdering,
+    next_seq: AtomicU64,
+    entries: Mutex<BTreeSet<PendingPoolKey>>,
+
+    /// Notified when a transaction leaves the pool or a new pending block is started.
+    changed: Condvar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct PendingPoolKey {
+    priority: Reverse<Wei>,
+    seq: u64,
+}
+
+impl PendingPool {
+    pub fn new(ordering: BlockOrdering) -> Self {
+        Self {
+            ordering,
+            next_seq: AtomicU64::new(0),
+            entries: Mutex::new(BTreeSet::new()),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Adds a transaction to the pool. The transaction leaves the pool when the returned ticket is dropped.
+    pub fn enqueue(&self, tx: &TransactionInput) -> PendingPoolTicket<'_> {
+        let priority = match self.ordering {
+            BlockOrdering::Fifo => Wei::ZERO,
+            BlockOrdering::GasPrice => tx.gas_price,
+        };
+        let key = PendingPoolKey {
+            priority: Reverse(priority),
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+        };
+        self.entries.lock().insert(key);
+        PendingPoolTicket { pool: self, key }
+    }
+
+    /// Number of transactions waiting for space in a new block.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Checks if there are no transactions waiting.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Wakes up the waiting transactions so they check again if they can be included.
+    ///
+    /// Must be called after a new pending block is started.
+    pub fn notify(&self) {
+        // holding the lock ensures a transaction cannot miss the notification between checking its condition and waiting
+        let _entries = self.entries.lock();
+        self.changed.notify_all();
+    }
+}
+
+/// Position of a transaction in the [`PendingPool`].
+pub struct PendingPoolTicket<'a> {
+    pool: &'a PendingPool,
+    key: PendingPoolKey,
+}
+
+impl PendingPoolTicket<'_> {
+    /// Checks if the transaction is the next one to be included according to the ordering policy.
+    pub fn is_next(&self) -> bool {
+        self.pool.entries.lock().first() == Some(&self.key)
+    }
+
+    /// Blocks until the transaction is the next one to be included and `has_space` is satisfied.
+    ///
+    /// The condition is checked again only when the pool changes or is notified, instead of polling it.
+    pub fn wait(&self, has_space: impl Fn() -> bool) -> Result<(), StratusError> {
+        let mut entries = self.pool.entries.lock();
+        loop {
+            if GlobalState::is_shutdown() {
+                return Err(StratusError::StratusShutdown);
+            }
+            if entries.first() == Some(&self.key) && has_space() {
+                return Ok(());
+            }
+            self.pool.changed.wait_for(&mut entries, SHUTDOWN_CHECK_INTERVAL);
+        }
+    }
+}
+
+impl Drop for PendingPoolTicket<'_> {
+    fn drop(&mut self) {
+        self.pool.entries.lock().remove(&self.key);
+        self.pool.changed.notify_all();
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+
+    /// Time to wait before assuming a blocked transaction will not be woken up.
+    const NOT_WOKEN_TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn tx_with_gas_price(gas_price: u64) -> TransactionInput {
+        TransactionInput {
+            gas_price: Wei::from(gas_price),
+            ..TransactionInput::default()
+        }
+    }
+
+    #[test]
+    fn test_pending_pool_fifo() {
+        let pool = PendingPool::new(BlockOrdering::Fifo);
+        let first = pool.enqueue(&tx_with_gas_price(1));
+        let second = pool.enqueue(&tx_with_gas_price(10));
+
+        assert!(first.is_next());
+        assert!(!second.is_next());
+
+        drop(first);
+        assert!(second.is_next());
+        drop(second);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_pending_pool_gas_price() {
+        let pool = PendingPool::new(BlockOrdering::GasPrice);
+        let cheap = pool.enqueue(&tx_with_gas_price(1));
+        let expensive = pool.enqueue(&tx_with_gas_price(10));
+        let expensive_later = pool.enqueue(&tx_with_gas_price(10));
+
+        assert!(expensive.is_next());
+        drop(expensive);
+        assert!(expensive_later.is_next());
+        drop(expensive_later);
+        assert!(cheap.is_next());
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_pending_pool_wait_wakes_up_when_previous_transaction_leaves() {
+        let pool = PendingPool::new(BlockOrdering::Fifo);
+        let first = pool.enqueue(&tx_with_gas_price(1));
+        let second = pool.enqueue(&tx_with_gas_price(1));
+
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(move || {
+                let result = second.wait(|| true);
+                done_tx.send(result).unwrap();
+            });
+
+            // waits while it is not the next one
+            assert!(done_rx.recv_timeout(NOT_WOKEN_TIMEOUT).is_err());
+
+            drop(first);
+            assert!(done_rx.recv_timeout(SHUTDOWN_CHECK_INTERVAL / 2).unwrap().is_ok());
+        });
+    }
+
+    #[test]
+    fn test_pending_pool_wait_wakes_up_when_notified() {
+        let pool = PendingPool::new(BlockOrdering::Fifo);
+        let ticket = pool.enqueue(&tx_with_gas_price(1));
+        let has_space = AtomicBool::new(false);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(|| {
+                let result = ticket.wait(|| has_space.load(Ordering::SeqCst));
+                done_tx.send(result).unwrap();
+            });
+
+            // waits while there is no space, even if it is the next one
+            assert!(done_rx.recv_timeout(NOT_WOKEN_TIMEOUT).is_err());
+
+            has_space.store(true, Ordering::SeqCst);
+            pool.notify();
+            assert!(done_rx.recv_timeout(SHUTDOWN_CHECK_INTERVAL / 2).unwrap().is_ok());
+        });
+    }
+}
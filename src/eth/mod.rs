@@ -1,8 +1,21 @@
+#[cfg(feature = "node")]
+pub mod analytics;
+#[cfg(feature = "node")]
 pub mod codegen;
+#[cfg(feature = "node")]
 pub mod executor;
+#[cfg(feature = "node")]
 pub mod external_rpc;
+#[cfg(feature = "node")]
 pub mod follower;
+#[cfg(feature = "node")]
 pub mod miner;
 pub mod primitives;
+#[cfg(feature = "postgres")]
+pub mod relayer;
+#[cfg(feature = "node")]
 pub mod rpc;
+#[cfg(feature = "node")]
+pub mod selector_registry;
+#[cfg(feature = "node")]
 pub mod storage;
@@ -11,9 +11,13 @@ use ethers_core::types::NameOrAddress;
 use fake::Dummy;
 use fake::Faker;
 use hex_literal::hex;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::error::BoxDynError;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
+#[cfg(feature = "postgres")]
 use sqlx::Decode;
 
 use crate::alias::RevmAddress;
@@ -119,6 +123,7 @@ impl TryFrom<Vec<u8>> for Address {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Address {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
         let value = <[u8; 20] as Decode<sqlx::Postgres>>::decode(value)?;
@@ -126,12 +131,14 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Address {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for Address {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("BYTEA")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for Address {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <[u8; 20] as PgHasArrayType>::array_type_info()
@@ -144,6 +151,7 @@ impl AsRef<[u8]> for Address {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Address {
     fn encode_by_ref(&self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError> {
         self.0 .0.encode(buf)
@@ -169,7 +177,7 @@ impl From<Address> for H160 {
 
 impl From<Address> for RevmAddress {
     fn from(value: Address) -> Self {
-        revm::primitives::Address(value.0 .0.into())
+        revm_primitives::Address(value.0 .0.into())
     }
 }
 
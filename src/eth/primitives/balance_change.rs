@@ -0,0 +1,31 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::Wei;
+
+/// Balance of an account changed by a mined transaction.
+///
+/// Kept as a journal so balances can be audited without external indexers.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BalanceChange {
+    pub address: Address,
+    pub block_number: BlockNumber,
+    pub transaction_hash: Hash,
+    pub transaction_index: Index,
+    pub previous_balance: Wei,
+    pub balance: Wei,
+}
+
+impl BalanceChange {
+    /// Signed difference between the new and previous balances formatted as decimal.
+    pub fn delta(&self) -> String {
+        if self.balance >= self.previous_balance {
+            (self.balance - self.previous_balance).to_string()
+        } else {
+            format!("-{}", self.previous_balance - self.balance)
+        }
+    }
+}
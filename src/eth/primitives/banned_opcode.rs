@@ -0,0 +1,17 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+
+/// Banned opcode executed by a contract.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize, fake::Dummy))]
+pub struct BannedOpcode {
+    /// Contract that executed the opcode.
+    pub address: Address,
+
+    /// Call depth where the opcode was executed.
+    pub depth: usize,
+
+    /// Opcode name.
+    pub opcode: String,
+}
@@ -3,20 +3,31 @@ use std::collections::HashMap;
 use display_json::DebugAsJson;
 use ethereum_types::H256;
 use itertools::Itertools;
+use keccak_hasher::KeccakHasher;
+use rlp::Encodable;
+use rlp::RlpStream;
 use serde::Deserialize;
 
+use super::logs_bloom::LogsBloom;
 use super::LogMined;
 use super::TransactionInput;
 use crate::alias::EthersBlockEthersTransaction;
 use crate::alias::EthersBlockH256;
+use crate::alias::EthersReceipt;
 use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
-use crate::eth::executor::EvmExecutionResult;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::EvmExecutionResult;
 use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::GasMode;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
 use crate::eth::primitives::UnixTime;
 use crate::ext::to_json_value;
@@ -45,6 +56,9 @@ impl Block {
     /// Pushes a single transaction execution to the blocks transactions.
     pub fn push_execution(&mut self, input: TransactionInput, evm_result: EvmExecutionResult) {
         let transaction_index = (self.transactions.len() as u64).into();
+        let first_log_index = self.transactions.iter().map(|tx| tx.logs.len() as u64).sum::<u64>();
+        self.header.gas_used = Gas::from(self.header.gas_used.as_u64().saturating_add(evm_result.execution.gas.as_u64()));
+        let cumulative_gas_used = self.header.gas_used;
         self.transactions.push(TransactionMined {
             logs: evm_result
                 .execution
@@ -53,7 +67,7 @@ impl Block {
                 .cloned()
                 .enumerate()
                 .map(|(i, log)| LogMined {
-                    log_index: (i as u64).into(),
+                    log_index: (first_log_index + i as u64).into(),
                     log,
                     transaction_hash: input.hash,
                     transaction_index,
@@ -64,6 +78,7 @@ impl Block {
             input,
             execution: evm_result.execution,
             transaction_index,
+            cumulative_gas_used,
             block_number: self.header.number,
             block_hash: self.header.hash,
         }); // TODO: update logs bloom
@@ -111,6 +126,37 @@ impl Block {
         to_json_value(ethers_block)
     }
 
+    /// Converts all transactions to JSON-RPC receipts.
+    pub fn to_ethers_receipts(&self, gas_mode: GasMode) -> Vec<EthersReceipt> {
+        self.transactions.iter().map(|tx| tx.clone().to_ethers_receipt(gas_mode)).collect()
+    }
+
+    /// Encodes all transactions receipts in the order they are stored in the block receipts trie.
+    pub fn encode_receipts(&self) -> Vec<Vec<u8>> {
+        let mut cumulative_gas_used = 0;
+        self.transactions
+            .iter()
+            .map(|tx| {
+                cumulative_gas_used += tx.execution.gas.as_u64();
+                tx.encode_receipt(cumulative_gas_used)
+            })
+            .collect()
+    }
+
+    /// Calculates the root of the receipts trie from the transactions receipts.
+    pub fn calculate_receipts_root(&self) -> Hash {
+        triehash::ordered_trie_root::<KeccakHasher, _>(self.encode_receipts()).into()
+    }
+
+    /// Calculates the logs bloom from the logs of all transactions.
+    pub fn calculate_logs_bloom(&self) -> LogsBloom {
+        let mut bloom = LogsBloom::default();
+        for log_mined in self.transactions.iter().flat_map(|tx| &tx.logs) {
+            bloom.accrue_log(&log_mined.log);
+        }
+        bloom
+    }
+
     /// Returns the block number.
     pub fn number(&self) -> BlockNumber {
         self.header.number
@@ -121,6 +167,99 @@ impl Block {
         self.header.hash
     }
 
+    /// Lists balances changed by each transaction, in execution order.
+    pub fn balance_changes(&self) -> Vec<BalanceChange> {
+        let mut balance_changes = Vec::new();
+        for transaction in &self.transactions {
+            for changes in transaction.execution.changes.values() {
+                let Some(&balance) = changes.balance.take_modified_ref() else {
+                    continue;
+                };
+                let previous_balance = changes.balance.take_original_ref().copied().unwrap_or_default();
+                if balance == previous_balance {
+                    continue;
+                }
+                balance_changes.push(BalanceChange {
+                    address: changes.address,
+                    block_number: transaction.block_number,
+                    transaction_hash: transaction.input.hash,
+                    transaction_index: transaction.transaction_index,
+                    previous_balance,
+                    balance,
+                });
+            }
+        }
+        balance_changes
+    }
+
+    /// Lists contracts deployed by the transactions of the block.
+    pub fn contract_creations(&self) -> Vec<ContractCreation> {
+        self.transactions
+            .iter()
+            .filter_map(|transaction| {
+                Some(ContractCreation {
+                    address: transaction.execution.deployed_contract_address?,
+                    creator: transaction.input.signer,
+                    transaction_hash: transaction.input.hash,
+                    transaction_index: transaction.transaction_index,
+                    block_number: transaction.block_number,
+                })
+            })
+            .collect()
+    }
+
+    /// Lists value transferred by internal calls of each transaction, in execution order.
+    pub fn internal_transfers(&self) -> Vec<InternalTransferMined> {
+        self.transactions
+            .iter()
+            .flat_map(|transaction| {
+                transaction.execution.internal_transfers.iter().map(|transfer| InternalTransferMined {
+                    transfer: transfer.clone(),
+                    transaction_hash: transaction.input.hash,
+                    transaction_index: transaction.transaction_index,
+                    block_number: transaction.block_number,
+                })
+            })
+            .collect()
+    }
+
+    /// Groups internal transfers by the accounts that sent or received them, keeping the execution order.
+    pub fn internal_transfers_by_account(&self) -> HashMap<Address, Vec<InternalTransferMined>> {
+        let mut by_account: HashMap<Address, Vec<InternalTransferMined>> = HashMap::new();
+        for transfer in self.internal_transfers() {
+            for address in [transfer.transfer.from, transfer.transfer.to].into_iter().dedup() {
+                by_account.entry(address).or_default().push(transfer.clone());
+            }
+        }
+        by_account
+    }
+
+    /// Lists token transfers decoded from standard events emitted by each transaction, in execution order.
+    pub fn token_transfers(&self) -> Vec<TokenTransferMined> {
+        self.transactions
+            .iter()
+            .flat_map(|transaction| transaction.logs.iter().filter_map(TokenTransferMined::decode))
+            .collect()
+    }
+
+    /// Groups token transfers by the accounts that sent or received them, keeping the execution order.
+    pub fn token_transfers_by_account(&self) -> HashMap<Address, Vec<TokenTransferMined>> {
+        let mut by_account: HashMap<Address, Vec<TokenTransferMined>> = HashMap::new();
+        for transfer in self.token_transfers() {
+            for address in [transfer.transfer.from, transfer.transfer.to].into_iter().dedup() {
+                by_account.entry(address).or_default().push(transfer.clone());
+            }
+        }
+        by_account
+    }
+
+    /// Lists the compacted account changes of the block sorted by address, so the state diff of a block is always reported in the same order.
+    pub fn state_diff(&self) -> Vec<ExecutionAccountChanges> {
+        let mut state_diff = self.compact_account_changes();
+        state_diff.sort_by_key(|changes| changes.address);
+        state_diff
+    }
+
     /// Compact accounts changes removing intermediate values, keeping only the last modified nonce, balance, bytecode and slots.
     pub fn compact_account_changes(&self) -> Vec<ExecutionAccountChanges> {
         let mut block_compacted_changes: HashMap<Address, ExecutionAccountChanges> = HashMap::new();
@@ -155,6 +294,27 @@ impl Block {
     }
 }
 
+/// Encodes the block in the format used by chain export files: header, transactions and uncles.
+impl Encodable for Block {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.header);
+
+        s.begin_list(self.transactions.len());
+        for tx in &self.transactions {
+            let encoded_tx = EthersTransaction::from(tx.clone()).rlp();
+            match encoded_tx.first() {
+                // legacy transactions are embedded as lists
+                Some(&prefix) if prefix >= 0xc0 => s.append_raw(&encoded_tx, 1),
+                // typed transactions are embedded as byte strings
+                _ => s.append(&encoded_tx.to_vec()),
+            };
+        }
+
+        s.begin_list(0);
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Conversions: Self -> Other
 // -----------------------------------------------------------------------------
@@ -190,3 +350,127 @@ impl TryFrom<JsonValue> for Block {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethereum_types::U256;
+    use ethereum_types::U64;
+    use ethers_core::utils::keccak256;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::ExecutionResult;
+    use crate::eth::primitives::Wei;
+
+    /// Creates a successful mined transaction that used the specified gas.
+    fn tx_mined(block: &Block, gas: u64, tx_type: Option<u64>, deployed: Option<Address>) -> TransactionMined {
+        let mut tx = TransactionMined {
+            input: Faker.fake(),
+            execution: Faker.fake(),
+            logs: vec![],
+            transaction_index: (block.transactions.len() as u64).into(),
+            cumulative_gas_used: (block.transactions.last().map_or(0, |tx| tx.cumulative_gas_used.as_u64()) + gas).into(),
+            block_number: block.number(),
+            block_hash: block.hash(),
+        };
+        tx.input.tx_type = tx_type.map(U64::from);
+        tx.input.gas_price = Wei::from(7u64);
+        tx.execution.result = ExecutionResult::Success;
+        tx.execution.gas = gas.into();
+        tx.execution.deployed_contract_address = deployed;
+        tx
+    }
+
+    #[test]
+    fn test_empty_block_receipts_match_header() {
+        let block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        assert_eq!(block.calculate_receipts_root(), block.header.receipts_root);
+        assert_eq!(block.calculate_logs_bloom(), block.header.bloom);
+    }
+
+    #[test]
+    fn test_receipts_decoded_by_ethers() {
+        let contract: Address = Faker.fake();
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        block.transactions.push(tx_mined(&block, 21_000, None, None));
+        block.transactions.push(tx_mined(&block, 100_000, Some(2), Some(contract)));
+
+        let receipts = block
+            .to_ethers_receipts(GasMode::Charged)
+            .into_iter()
+            .map(|receipt| serde_json::from_value::<EthersReceipt>(to_json_value(receipt)).unwrap())
+            .collect_vec();
+
+        // legacy transaction
+        assert_eq!(receipts[0].transaction_type, Some(U64::zero()));
+        assert_eq!(receipts[0].gas_used, Some(U256::from(21_000)));
+        assert_eq!(receipts[0].cumulative_gas_used, U256::from(21_000));
+        assert_eq!(receipts[0].effective_gas_price, Some(U256::from(7)));
+        assert_eq!(receipts[0].contract_address, None);
+
+        // contract deployment with dynamic fee
+        assert_eq!(receipts[1].transaction_type, Some(U64::from(2)));
+        assert_eq!(receipts[1].gas_used, Some(U256::from(100_000)));
+        assert_eq!(receipts[1].cumulative_gas_used, U256::from(121_000));
+        assert_eq!(receipts[1].contract_address, Some(contract.into()));
+        assert_eq!(receipts[1].transaction_index, U64::one());
+        assert_eq!(receipts[1].status, Some(U64::one()));
+    }
+
+    #[test]
+    fn test_receipts_effective_gas_price_follows_gas_mode() {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        block.transactions.push(tx_mined(&block, 21_000, None, None));
+
+        let charged = block.to_ethers_receipts(GasMode::Charged);
+        assert_eq!(charged[0].effective_gas_price, Some(U256::from(7)));
+
+        // the sender pays nothing when gas is free, even if the transaction was signed with a gas price
+        let free = block.to_ethers_receipts(GasMode::Free);
+        assert_eq!(free[0].effective_gas_price, Some(U256::zero()));
+        assert_eq!(block.transactions[0].effective_gas_price(GasMode::Free), Wei::ZERO);
+    }
+
+    #[test]
+    fn test_chain_export_file_is_decoded() {
+        // blocks linked by canonical hashes, as mined by the leader
+        let mut parent = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        parent.transactions.push(tx_mined(&parent, 21_000, None, None));
+        parent.header.hash = parent.header.canonical_hash();
+        let mut child = Block::new(BlockNumber::from(2u64), UnixTime::from(1234567892));
+        child.header.parent_hash = parent.hash();
+        child.transactions.push(tx_mined(&child, 21_000, Some(2), None));
+        child.transactions[0].input.v = U64::one(); // typed transactions are signed with the parity of y
+        child.header.hash = child.header.canonical_hash();
+
+        let file = [rlp::encode(&parent), rlp::encode(&child)].concat();
+
+        let mut offset = 0;
+        for block in [&parent, &child] {
+            let len = rlp::Rlp::new(&file[offset..]).payload_info().unwrap().total();
+            let entry = rlp::Rlp::new(&file[offset..offset + len]);
+            offset += len;
+            assert_eq!(entry.item_count().unwrap(), 3);
+
+            // clients recompute the block hash from the encoded header
+            let header = entry.at(0).unwrap();
+            assert_eq!(Hash::new(keccak256(header.as_raw())), block.hash());
+            assert_eq!(header.val_at::<H256>(0).unwrap(), block.header.parent_hash.0);
+            assert_eq!(header.val_at::<H256>(3).unwrap(), block.header.state_root.0);
+
+            // legacy transactions are embedded as lists and typed transactions as byte strings
+            let transactions = entry.at(1).unwrap();
+            assert_eq!(transactions.item_count().unwrap(), 1);
+            let tx = transactions.at(0).unwrap();
+            let encoded = if tx.is_list() { tx.as_raw().to_vec() } else { tx.data().unwrap().to_vec() };
+            assert_eq!(encoded, EthersTransaction::from(block.transactions[0].clone()).rlp().to_vec());
+
+            assert_eq!(entry.at(2).unwrap().item_count().unwrap(), 0);
+        }
+        assert_eq!(offset, file.len());
+    }
+}
@@ -0,0 +1,44 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionChanges;
+use crate::eth::primitives::ExecutionValueChange;
+use crate::eth::primitives::Slot;
+
+/// Mined block exported with the state it reads, so it can be re-executed without the storage it was mined on.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct BlockBundle {
+    /// Block to be re-executed.
+    pub block: Block,
+
+    /// Values of all accounts and slots read by the block, as they were at the end of the previous block.
+    pub prestate: Vec<BundleAccount>,
+
+    /// Account changes persisted when the block was mined, used to check the re-execution.
+    pub state_diff: Vec<ExecutionAccountChanges>,
+}
+
+/// Account read by a bundled block, with the slots it reads.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct BundleAccount {
+    pub account: Account,
+    pub slots: Vec<Slot>,
+}
+
+impl BlockBundle {
+    /// Converts the prestate into execution changes without modified values, so it can be used as the overlay of EVM executions.
+    pub fn prestate_changes(&self) -> ExecutionChanges {
+        self.prestate
+            .iter()
+            .map(|prestate| {
+                let mut changes = ExecutionAccountChanges::from_original_values(prestate.account.clone());
+                for slot in &prestate.slots {
+                    changes.slots.insert(slot.index, ExecutionValueChange::from_original(*slot));
+                }
+                (prestate.account.address, changes)
+            })
+            .collect()
+    }
+}
@@ -6,7 +6,7 @@ use crate::alias::JsonValue;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
 
-#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, Hash)]
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, strum::EnumIs, serde::Serialize, Hash)]
 #[cfg_attr(test, derive(fake::Dummy))]
 pub enum BlockFilter {
     /// Information from the last mined block.
@@ -19,6 +19,12 @@ pub enum BlockFilter {
     /// Information from the first block.
     Earliest,
 
+    /// Information from the last block that is not expected to be reverted.
+    Safe,
+
+    /// Information from the last block that cannot be reverted.
+    Finalized,
+
     /// Retrieve a block by its hash.
     Hash(Hash),
 
@@ -32,6 +38,8 @@ impl Display for BlockFilter {
             BlockFilter::Latest => write!(f, "latest"),
             BlockFilter::Pending => write!(f, "pending"),
             BlockFilter::Earliest => write!(f, "earliest"),
+            BlockFilter::Safe => write!(f, "safe"),
+            BlockFilter::Finalized => write!(f, "finalized"),
             BlockFilter::Hash(block_hash) => write!(f, "{}", block_hash),
             BlockFilter::Number(block_number) => write!(f, "{}", block_number),
         }
@@ -65,6 +73,8 @@ impl<'de> serde::Deserialize<'de> for BlockFilter {
                     "latest" | "Latest" => Ok(Self::Latest),
                     "pending" | "Pending" => Ok(Self::Pending),
                     "earliest" | "Earliest" => Ok(Self::Earliest),
+                    "safe" | "Safe" => Ok(Self::Safe),
+                    "finalized" | "Finalized" => Ok(Self::Finalized),
 
                     // parse hash (64: H256 without 0x prefix; 66: H256 with 0x prefix)
                     s if s.len() == 64 || s.len() == 66 => {
@@ -126,6 +136,12 @@ mod tests {
         assert_eq!(serde_json::from_value::<BlockFilter>(json).unwrap(), BlockFilter::Latest);
     }
 
+    #[test]
+    fn serde_block_number_with_finality_tags() {
+        assert_eq!(serde_json::from_value::<BlockFilter>(json!("safe")).unwrap(), BlockFilter::Safe);
+        assert_eq!(serde_json::from_value::<BlockFilter>(json!("finalized")).unwrap(), BlockFilter::Finalized);
+    }
+
     #[test]
     fn serde_block_number_with_number() {
         let json = json!("0x2");
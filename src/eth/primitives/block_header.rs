@@ -1,21 +1,26 @@
 use display_json::DebugAsJson;
-use ethereum_types::H256;
 use ethereum_types::H64;
 use ethereum_types::U256;
 use ethers_core::types::Block as EthersBlock;
 use ethers_core::types::OtherFields;
+use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Fake;
 use fake::Faker;
 use hex_literal::hex;
+#[cfg(feature = "node")]
 use jsonrpsee::SubscriptionMessage;
+use rlp::Encodable;
+use rlp::RlpStream;
 
+#[cfg(feature = "node")]
 use crate::alias::EthersBlockVoid;
 use crate::alias::EthersBytes;
 use crate::eth::primitives::logs_bloom::LogsBloom;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainSpec;
 use crate::eth::primitives::Difficulty;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::Gas;
@@ -23,13 +28,17 @@ use crate::eth::primitives::Hash;
 use crate::eth::primitives::MinerNonce;
 use crate::eth::primitives::Size;
 use crate::eth::primitives::UnixTime;
+#[cfg(feature = "node")]
 use crate::ext::InfallibleExt;
 
 /// Special hash used in block mining to indicate no uncle blocks.
-const HASH_EMPTY_UNCLES: Hash = Hash::new(hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"));
+pub(super) const HASH_EMPTY_UNCLES: Hash = Hash::new(hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"));
+
+/// Gas limit presented in JSON-RPC for all blocks.
+const DISPLAY_GAS_LIMIT: u64 = 100_000_000;
 
 /// Special hash used in block mining to indicate no transaction root and no receipts root.
-const HASH_EMPTY_TRIE: Hash = Hash::new(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
+pub(super) const HASH_EMPTY_TRIE: Hash = Hash::new(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
 
 #[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct BlockHeader {
@@ -77,6 +86,32 @@ impl BlockHeader {
             nonce: MinerNonce::default(),
         }
     }
+
+    /// Author presented in JSON-RPC.
+    ///
+    /// Blocks mined locally before the author was recorded have a zero author, so they keep being presented with the coinbase address.
+    pub fn display_author(&self) -> Address {
+        if self.author.is_zero() {
+            Address::COINBASE
+        } else {
+            self.author
+        }
+    }
+
+    /// Indicates if the header has the state root of the block.
+    ///
+    /// Stratus does not keep a state trie, so blocks mined locally present the empty trie root instead. Imported blocks keep the state
+    /// root of the external block.
+    pub fn has_state_root(&self) -> bool {
+        self.state_root != HASH_EMPTY_TRIE
+    }
+
+    /// Calculates the canonical hash of the header: the keccak256 of its RLP encoding.
+    ///
+    /// The encoding uses the same values presented in JSON-RPC, so clients can recompute the hash from `eth_getBlockByNumber`.
+    pub fn canonical_hash(&self) -> Hash {
+        Hash::new(keccak256(rlp::encode(self)))
+    }
 }
 
 impl Dummy<Faker> for BlockHeader {
@@ -104,6 +139,31 @@ impl Dummy<Faker> for BlockHeader {
     }
 }
 
+/// Encodes the header as a London header with the same values presented in JSON-RPC.
+///
+/// Fields that are not presented (difficulty, nonce and extra data) are encoded with the constant values served instead.
+impl Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(16);
+        s.append(&self.parent_hash.0);
+        s.append(&HASH_EMPTY_UNCLES.0);
+        s.append(&self.display_author().0);
+        s.append(&self.state_root.0);
+        s.append(&self.transactions_root.0);
+        s.append(&self.receipts_root.0);
+        s.append(&self.bloom.0);
+        s.append(&U256::zero()); // difficulty
+        s.append(&self.number.0);
+        s.append(&DISPLAY_GAS_LIMIT);
+        s.append(&self.gas_used.as_u64());
+        s.append(&*self.timestamp);
+        s.append(&Vec::<u8>::new()); // extra data
+        s.append(&ChainSpec::MIX_HASH.0);
+        s.append(&H64::zero()); // nonce
+        s.append(&U256::from(ChainSpec::BASE_FEE));
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Conversions: Self -> Other
 // -----------------------------------------------------------------------------
@@ -116,7 +176,7 @@ where
             // block: identifiers
             hash: Some(header.hash.into()),
             number: Some(header.number.into()),
-            mix_hash: Some(H256::default()),
+            mix_hash: Some(ChainSpec::MIX_HASH.into()),
 
             // block: relation with other blocks
             uncles_hash: HASH_EMPTY_UNCLES.into(),
@@ -126,7 +186,7 @@ where
 
             // mining: identifiers
             timestamp: (*header.timestamp).into(),
-            author: Some(Address::COINBASE.into()),
+            author: Some(header.display_author().into()),
 
             // minining: difficulty
             difficulty: U256::zero(),
@@ -134,16 +194,16 @@ where
             nonce: Some(H64::zero()),
 
             // mining: gas
-            gas_limit: Gas::from(100_000_000u64).into(),
+            gas_limit: DISPLAY_GAS_LIMIT.into(),
             gas_used: header.gas_used.into(),
-            base_fee_per_gas: Some(U256::zero()),
+            base_fee_per_gas: Some(ChainSpec::BASE_FEE.into()),
             blob_gas_used: None,
             excess_blob_gas: None,
 
             // transactions
             transactions: vec![], // can't fill transactions from header, must be modified afterward
             transactions_root: header.transactions_root.into(),
-            receipts_root: HASH_EMPTY_TRIE.into(),
+            receipts_root: header.receipts_root.into(),
             withdrawals_root: None,
             withdrawals: None,
 
@@ -188,6 +248,7 @@ impl TryFrom<&ExternalBlock> for BlockHeader {
     }
 }
 
+#[cfg(feature = "node")]
 impl From<BlockHeader> for SubscriptionMessage {
     fn from(value: BlockHeader) -> Self {
         let ethers_block = EthersBlockVoid::from(value);
@@ -200,8 +261,16 @@ impl From<BlockHeader> for SubscriptionMessage {
 // -----------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use ethereum_types::H256;
+    use ethers_core::types::Block as EthersBlock;
+    use ethers_core::utils::keccak256;
+    use rlp::RlpStream;
+
+    use crate::alias::EthersBlockVoid;
+    use crate::eth::primitives::Address;
     use crate::eth::primitives::BlockHeader;
     use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::Gas;
     use crate::eth::primitives::Hash;
     use crate::eth::primitives::UnixTime;
 
@@ -225,4 +294,96 @@ mod tests {
         let header = BlockHeader::new(BlockNumber::ZERO, UnixTime::from(1234567890));
         assert_eq!(header.parent_hash, Hash::ZERO);
     }
+
+    #[test]
+    fn block_header_rlp_is_london_header() {
+        let header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        let encoded = rlp::encode(&header);
+        let decoded = rlp::Rlp::new(&encoded);
+        assert_eq!(decoded.item_count().unwrap(), 16);
+        assert_eq!(decoded.val_at::<H256>(0).unwrap(), header.parent_hash.0);
+        assert_eq!(decoded.val_at::<u64>(8).unwrap(), 1);
+        assert_eq!(decoded.val_at::<u64>(11).unwrap(), 1234567891);
+    }
+
+    #[test]
+    fn block_header_rlp_vector() {
+        let mut header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        header.author = Address::new([0x11; 20]);
+        header.gas_used = Gas::from(21_000u64);
+
+        let expected = [
+            "f901f8",                                                             // list header
+            "a0011b4d03dd8c01f1049143cf9c4c817e4b167f1d1b83e5c6f0f10d89ba1e7bce", // parent hash
+            "a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347", // uncles hash
+            "941111111111111111111111111111111111111111",                         // author
+            "a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421", // state root
+            "a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421", // transactions root
+            "a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421", // receipts root
+            &format!("b90100{}", "00".repeat(256)),                               // logs bloom
+            "80",                                                                 // difficulty
+            "01",                                                                 // number
+            "8405f5e100",                                                         // gas limit
+            "825208",                                                             // gas used
+            "84499602d3",                                                         // timestamp
+            "80",                                                                 // extra data
+            "a00000000000000000000000000000000000000000000000000000000000000000", // mix hash
+            "880000000000000000",                                                 // nonce
+            "80",                                                                 // base fee
+        ]
+        .concat();
+        assert_eq!(const_hex::encode(rlp::encode(&header)), expected);
+    }
+
+    #[test]
+    fn block_header_canonical_hash_covers_fields() {
+        let header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        assert_eq!(header.canonical_hash(), header.clone().canonical_hash());
+        assert_ne!(header.canonical_hash(), header.hash);
+
+        let mut changed = header.clone();
+        changed.parent_hash = Hash::ZERO;
+        assert_ne!(changed.canonical_hash(), header.canonical_hash());
+    }
+
+    #[test]
+    fn block_header_canonical_hash_matches_json() {
+        let mut header = BlockHeader::new(BlockNumber::from(7u64), UnixTime::from(1234567891));
+        header.gas_used = Gas::from(21_000u64);
+        header.hash = header.canonical_hash();
+
+        // recompute the hash only from the fields served by eth_getBlockByNumber
+        let json = serde_json::to_value(EthersBlockVoid::from(header.clone())).unwrap();
+        let block: EthersBlock<H256> = serde_json::from_value(json).unwrap();
+
+        let mut s = RlpStream::new_list(16);
+        s.append(&block.parent_hash);
+        s.append(&block.uncles_hash);
+        s.append(&block.author.unwrap());
+        s.append(&block.state_root);
+        s.append(&block.transactions_root);
+        s.append(&block.receipts_root);
+        s.append(&block.logs_bloom.unwrap());
+        s.append(&block.difficulty);
+        s.append(&block.number.unwrap().as_u64());
+        s.append(&block.gas_limit);
+        s.append(&block.gas_used);
+        s.append(&block.timestamp);
+        s.append(&block.extra_data.to_vec());
+        s.append(&block.mix_hash.unwrap());
+        s.append(&block.nonce.unwrap());
+        s.append(&block.base_fee_per_gas.unwrap());
+
+        assert_eq!(H256(keccak256(s.out())), block.hash.unwrap());
+        assert_eq!(block.hash.unwrap(), header.hash.0);
+    }
+
+    #[test]
+    fn block_header_state_root_is_missing_for_mined_blocks() {
+        let mut header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        assert!(!header.has_state_root());
+
+        header.state_root = Hash::new([1; 32]);
+        assert!(header.has_state_root());
+    }
 }
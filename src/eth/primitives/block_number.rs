@@ -9,9 +9,13 @@ use ethereum_types::U64;
 use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Faker;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::error::BoxDynError;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
+#[cfg(feature = "postgres")]
 use sqlx::types::BigDecimal;
 
 use crate::alias::RevmU256;
@@ -77,9 +81,12 @@ impl BlockNumber {
         }
     }
 
-    /// Converts itself to i64.
-    pub fn as_i64(&self) -> i64 {
-        self.0.as_u64() as i64
+    /// Converts itself to i64, failing instead of wrapping when it is greater than the max i64 value.
+    pub fn as_i64(&self) -> anyhow::Result<i64> {
+        match i64::try_from(*self) {
+            Ok(number) => Ok(number),
+            Err(_) => Err(anyhow!("block number {} does not fit in i64", self)),
+        }
     }
 
     /// Converts itself to u64.
@@ -132,6 +139,7 @@ impl FromStr for BlockNumber {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl TryFrom<BigDecimal> for BlockNumber {
     type Error = anyhow::Error;
 
@@ -179,6 +187,7 @@ impl From<BlockNumber> for [u8; 8] {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for BlockNumber {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
         let value = <BigDecimal as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
@@ -186,18 +195,21 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for BlockNumber {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for BlockNumber {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("NUMERIC")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for BlockNumber {
     fn encode_by_ref(&self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError> {
         BigDecimal::from(u64::from(*self)).encode(buf)
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for BlockNumber {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <BigDecimal as PgHasArrayType>::array_type_info()
@@ -1,8 +1,9 @@
 use std::fmt::Display;
 use std::ops::Deref;
-use std::ops::DerefMut;
 
 use display_json::DebugAsJson;
+use fake::Dummy;
+use fake::Faker;
 
 use crate::alias::EthersBytes;
 use crate::alias::RevmBytecode;
@@ -10,8 +11,11 @@ use crate::alias::RevmBytes;
 use crate::alias::RevmOutput;
 use crate::gen_newtype_from;
 
-#[derive(DebugAsJson, Clone, Default, Eq, PartialEq, fake::Dummy)]
-pub struct Bytes(pub Vec<u8>);
+/// Arbitrary sized bytes, like transaction input, contract bytecode and log data.
+///
+/// Backed by a reference-counted buffer, so cloning and converting to or from EVM and RPC types do not copy the underlying data.
+#[derive(DebugAsJson, Clone, Default, Eq, PartialEq)]
+pub struct Bytes(pub RevmBytes);
 
 impl Display for Bytes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -23,6 +27,12 @@ impl Display for Bytes {
     }
 }
 
+impl Dummy<Faker> for Bytes {
+    fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(faker: &Faker, rng: &mut R) -> Self {
+        Vec::<u8>::dummy_with_rng(faker, rng).into()
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Serialization / Deserialization
 // -----------------------------------------------------------------------------
@@ -42,7 +52,7 @@ impl<'de> serde::Deserialize<'de> for Bytes {
     {
         let value = String::deserialize(deserializer)?;
         match const_hex::decode(value) {
-            Ok(value) => Ok(Self(value)),
+            Ok(value) => Ok(value.into()),
             Err(e) => {
                 tracing::warn!(reason = ?e, "failed to parse hex bytes");
                 Err(serde::de::Error::custom(e))
@@ -54,37 +64,43 @@ impl<'de> serde::Deserialize<'de> for Bytes {
 // -----------------------------------------------------------------------------
 // Conversions: Other -> Self
 // -----------------------------------------------------------------------------
-gen_newtype_from!(self = Bytes, other = Vec<u8>, &[u8], [u8; 32]);
+gen_newtype_from!(self = Bytes, other = Vec<u8>, RevmBytes);
+
+impl From<&[u8]> for Bytes {
+    fn from(value: &[u8]) -> Self {
+        Self(RevmBytes::copy_from_slice(value))
+    }
+}
+
+impl From<[u8; 32]> for Bytes {
+    fn from(value: [u8; 32]) -> Self {
+        Self::from(value.as_slice())
+    }
+}
 
 impl From<EthersBytes> for Bytes {
     fn from(value: EthersBytes) -> Self {
-        Self(value.0.into())
+        Self(RevmBytes::from(value.0))
     }
 }
 
 impl From<RevmBytecode> for Bytes {
     fn from(value: RevmBytecode) -> Self {
-        Self(value.bytecode().clone().into())
-    }
-}
-
-impl From<RevmBytes> for Bytes {
-    fn from(value: RevmBytes) -> Self {
-        Self(value.0.into())
+        Self(value.bytecode().clone())
     }
 }
 
 impl From<&RevmBytes> for Bytes {
     fn from(value: &RevmBytes) -> Self {
-        Self(value.0.clone().into())
+        Self(value.clone())
     }
 }
 
 impl From<RevmOutput> for Bytes {
     fn from(value: RevmOutput) -> Self {
         match value {
-            RevmOutput::Call(bytes) => Self(bytes.0.into()),
-            RevmOutput::Create(bytes, _) => Self(bytes.0.into()),
+            RevmOutput::Call(bytes) => Self(bytes),
+            RevmOutput::Create(bytes, _) => Self(bytes),
         }
     }
 }
@@ -99,33 +115,52 @@ impl AsRef<[u8]> for Bytes {
 }
 
 impl Deref for Bytes {
-    type Target = Vec<u8>;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl DerefMut for Bytes {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl From<Bytes> for Vec<u8> {
+    fn from(value: Bytes) -> Self {
+        value.0.to_vec()
     }
 }
 
 impl From<Bytes> for EthersBytes {
     fn from(value: Bytes) -> Self {
-        value.0.into()
+        EthersBytes::from(value.0 .0)
     }
 }
 
 impl From<Bytes> for RevmBytes {
     fn from(value: Bytes) -> Self {
-        value.0.into()
+        value.0
     }
 }
 
 impl From<Bytes> for RevmBytecode {
     fn from(value: Bytes) -> Self {
-        RevmBytecode::new_raw(value.0.into())
+        RevmBytecode::new_raw(value.0)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_share_buffer() {
+        let bytes = Bytes::from(vec![1, 2, 3]);
+        let ptr = bytes.as_ptr();
+
+        assert_eq!(bytes.clone().as_ptr(), ptr);
+        assert_eq!(RevmBytes::from(bytes.clone()).as_ptr(), ptr);
+        assert_eq!(Bytes::from(EthersBytes::from(bytes.clone())).as_ptr(), ptr);
+        assert_eq!(Bytes::from(RevmBytecode::new_raw(bytes.clone().into())).as_ptr(), ptr);
     }
 }
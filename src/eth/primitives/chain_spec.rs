@@ -0,0 +1,135 @@
+use display_json::DebugAsJson;
+use ethereum_types::H64;
+use ethereum_types::U256;
+use revm_primitives::SpecId;
+
+use super::block_header::HASH_EMPTY_UNCLES;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::Difficulty;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::MinerNonce;
+use crate::eth::primitives::Wei;
+
+/// Identity of the chain served by the node and the rules applied to its blocks.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSpec {
+    pub chain_id: ChainId,
+
+    /// Hash of the genesis block. Unknown while the genesis block is not saved, like in followers that did not import it yet.
+    pub genesis_hash: Option<Hash>,
+
+    /// EVM hardfork whose rules are applied to all blocks.
+    pub hardfork: String,
+
+    /// Max size in bytes of deployed contracts code.
+    pub max_code_size: usize,
+
+    /// Max size in bytes of contract deployment transactions data.
+    pub max_initcode_size: usize,
+}
+
+impl ChainSpec {
+    /// EVM hardfork used by the executor.
+    pub const EVM_SPEC: SpecId = SpecId::LONDON;
+
+    /// Uncle hash of all blocks, as blocks never have uncles.
+    pub const UNCLE_HASH: Hash = HASH_EMPTY_UNCLES;
+
+    /// Difficulty of all blocks, as blocks are not mined with proof of work.
+    pub const DIFFICULTY: Difficulty = Difficulty(U256::zero());
+
+    /// Nonce of all blocks, as blocks are not mined with proof of work.
+    pub const NONCE: MinerNonce = MinerNonce::new([0; 8]);
+
+    /// Base fee of all blocks, as transactions pay only their gas price. Also the `BASEFEE` seen by the executor.
+    pub const BASE_FEE: Wei = Wei::ZERO;
+
+    /// Mix hash of all blocks, as blocks have no beacon randomness. Also the `PREVRANDAO` seen by the executor.
+    pub const MIX_HASH: Hash = Hash::ZERO;
+
+    pub fn new(chain_id: ChainId, genesis_hash: Option<Hash>, max_code_size: usize, max_initcode_size: usize) -> Self {
+        Self {
+            chain_id,
+            genesis_hash,
+            hardfork: format!("{:?}", Self::EVM_SPEC).to_lowercase(),
+            max_code_size,
+            max_initcode_size,
+        }
+    }
+
+    /// Checks the header fields that must have the same value in all blocks of the chain, returning the ones with unexpected values.
+    pub fn check_header_invariants(header: &BlockHeader) -> Vec<HeaderInvariantViolation> {
+        let mut violations = Vec::new();
+        if header.uncle_hash != Self::UNCLE_HASH {
+            violations.push(HeaderInvariantViolation::new("uncle_hash", Self::UNCLE_HASH, header.uncle_hash));
+        }
+        if header.difficulty != Self::DIFFICULTY {
+            violations.push(HeaderInvariantViolation::new("difficulty", Self::DIFFICULTY, header.difficulty));
+        }
+        if header.nonce != Self::NONCE {
+            let (expected, found) = (H64::from(Self::NONCE), H64::from(header.nonce));
+            violations.push(HeaderInvariantViolation::new("nonce", format!("{:?}", expected), format!("{:?}", found)));
+        }
+        violations
+    }
+}
+
+/// Header field with a value different from the one expected for all blocks of the chain.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HeaderInvariantViolation {
+    pub field: &'static str,
+    pub expected: String,
+    pub found: String,
+}
+
+impl HeaderInvariantViolation {
+    fn new(field: &'static str, expected: impl ToString, found: impl ToString) -> Self {
+        Self {
+            field,
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::UnixTime;
+    use crate::ext::to_json_value;
+
+    #[test]
+    fn test_chain_spec_serialization() {
+        let spec = ChainSpec::new(ChainId::from(2008u64), Some(Hash::ZERO), 24576, 49152);
+        assert_eq!(
+            to_json_value(&spec),
+            serde_json::json!({
+                "chainId": "0x7d8",
+                "genesisHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "hardfork": "london",
+                "maxCodeSize": 24576,
+                "maxInitcodeSize": 49152,
+            })
+        );
+        assert_eq!(serde_json::from_value::<ChainSpec>(to_json_value(&spec)).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_header_invariants() {
+        let mut header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        assert!(ChainSpec::check_header_invariants(&header).is_empty());
+
+        header.difficulty = Difficulty::from(1u64);
+        header.nonce = MinerNonce::new([0, 0, 0, 0, 0, 0, 0, 1]);
+        let violations = ChainSpec::check_header_invariants(&header);
+        assert_eq!(violations.iter().map(|v| v.field).collect::<Vec<_>>(), ["difficulty", "nonce"]);
+        assert_eq!(violations[0].found, "1");
+        assert_eq!(violations[1].found, "0x0000000000000001");
+    }
+}
@@ -3,8 +3,8 @@ use ethereum_types::H256;
 use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Faker;
-use revm::primitives::FixedBytes;
-use revm::primitives::KECCAK_EMPTY;
+use revm_primitives::FixedBytes;
+use revm_primitives::KECCAK_EMPTY;
 
 use crate::eth::primitives::Bytes;
 use crate::gen_newtype_from;
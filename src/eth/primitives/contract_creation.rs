@@ -0,0 +1,25 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+
+/// Contract deployed by a transaction that was added to a block.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct ContractCreation {
+    /// Address of the deployed contract.
+    pub address: Address,
+
+    /// Account that signed the deployment transaction.
+    pub creator: Address,
+
+    /// Hash of the deployment transaction.
+    pub transaction_hash: Hash,
+
+    /// Position of the deployment transaction inside the block.
+    pub transaction_index: Index,
+
+    /// Block number where the contract was deployed.
+    pub block_number: BlockNumber,
+}
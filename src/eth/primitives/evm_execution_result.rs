@@ -1,5 +1,6 @@
 use display_json::DebugAsJson;
 
+use crate::eth::primitives::BannedOpcode;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
 
@@ -9,6 +10,9 @@ use crate::eth::primitives::EvmExecutionMetrics;
 pub struct EvmExecutionResult {
     pub execution: EvmExecution,
     pub metrics: EvmExecutionMetrics,
+
+    /// Banned opcodes executed during account abstraction validation. Only tracked by EVMs with opcode tracer.
+    pub banned_opcodes: Vec<BannedOpcode>,
 }
 
 impl EvmExecutionResult {
@@ -6,13 +6,17 @@ use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CallInput;
 use crate::eth::primitives::ChainId;
+use crate::eth::primitives::ExecutionChanges;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::Gas;
+use crate::eth::primitives::GasMode;
+use crate::eth::primitives::Hash;
 use crate::eth::primitives::Nonce;
 use crate::eth::primitives::PendingBlockHeader;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::TransactionMined;
 use crate::eth::primitives::UnixTime;
 use crate::eth::primitives::Wei;
 use crate::ext::not;
@@ -78,24 +82,44 @@ pub struct EvmInput {
     ///
     /// If not specified, it will not be validated.
     pub chain_id: Option<ChainId>,
+
+    /// Versioned hashes of the blobs of an EIP-4844 blob transaction.
+    #[serde(default)]
+    pub blob_hashes: Vec<Hash>,
+
+    /// Changes of previous executions applied on top of the state read at `point_in_time`.
+    ///
+    /// Used to chain executions whose changes are not persisted, like the calls of a `stratus_callMany`.
+    #[serde(default)]
+    #[cfg_attr(test, serde(serialize_with = "crate::ext::ordered_map"))]
+    pub overlay: ExecutionChanges,
 }
 
 impl EvmInput {
     /// Creates from a transaction that was sent directly to Stratus with `eth_sendRawTransaction`.
-    pub fn from_eth_transaction(input: &TransactionInput, pending_header: &PendingBlockHeader) -> Self {
-        Self {
+    pub fn from_eth_transaction(input: &TransactionInput, pending_header: &PendingBlockHeader, gas_mode: GasMode) -> Self {
+        let mut evm_input = Self {
             from: input.signer,
             to: input.to,
             value: input.value,
             data: input.input.clone(),
-            gas_limit: Gas::MAX,
-            gas_price: Wei::ZERO,
-            nonce: Some(input.nonce),
             block_number: pending_header.number,
             block_timestamp: *pending_header.timestamp,
             point_in_time: PointInTime::Pending,
-            chain_id: input.chain_id,
-        }
+            ..Self::default()
+        };
+        evm_input.apply_transaction(input, gas_mode);
+        evm_input
+    }
+
+    /// Applies the nonce, chain id, gas limit and gas price of a signed transaction, so it executes like when sent with `eth_sendRawTransaction`.
+    ///
+    /// The transaction gas limit and gas price are only used when gas is charged, otherwise it executes with max gas and zero gas price.
+    pub fn apply_transaction(&mut self, input: &TransactionInput, gas_mode: GasMode) {
+        self.nonce = Some(input.nonce);
+        self.chain_id = input.chain_id;
+        self.gas_limit = if_else!(gas_mode.is_charged(), input.gas_limit, Gas::MAX);
+        self.gas_price = if_else!(gas_mode.is_charged(), input.gas_price, Wei::ZERO);
     }
 
     /// Creates from a call that was sent directly to Stratus with `eth_call` or `eth_estimateGas`.
@@ -125,6 +149,8 @@ impl EvmInput {
             },
             point_in_time,
             chain_id: None,
+            blob_hashes: Vec::new(),
+            overlay: ExecutionChanges::default(),
         })
     }
 
@@ -147,9 +173,32 @@ impl EvmInput {
                 Some(chain_id) => Some(chain_id.try_into()?),
                 None => None,
             },
+            blob_hashes: tx.blob_versioned_hashes(),
+            overlay: ExecutionChanges::default(),
         })
     }
 
+    /// Creates from a transaction that was mined in Stratus, so it can be replayed over the state of the previous block.
+    ///
+    /// Changes of the transactions executed before it in the same block must be provided in `overlay`.
+    pub fn from_mined(tx: &TransactionMined, block_timestamp: UnixTime, overlay: ExecutionChanges) -> Self {
+        Self {
+            from: tx.input.signer,
+            to: tx.input.to,
+            value: tx.input.value,
+            data: tx.input.input.clone(),
+            nonce: Some(tx.input.nonce),
+            gas_limit: Gas::MAX,
+            gas_price: Wei::ZERO,
+            block_number: tx.block_number,
+            block_timestamp,
+            point_in_time: PointInTime::MinedPast(tx.block_number.prev().unwrap_or(BlockNumber::ZERO)),
+            chain_id: tx.input.chain_id,
+            blob_hashes: Vec::new(),
+            overlay,
+        }
+    }
+
     /// Checks if the input is a contract call.
     ///
     /// It is when there is a `to` address and the `data` field is also populated.
@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 
-use anyhow::anyhow;
 use anyhow::Ok;
 use display_json::DebugAsJson;
 use hex_literal::hex;
@@ -12,6 +11,7 @@ use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::ExecutionResult;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::Gas;
+use crate::eth::primitives::InternalTransfer;
 use crate::eth::primitives::Log;
 use crate::eth::primitives::UnixTime;
 use crate::eth::primitives::Wei;
@@ -49,6 +49,10 @@ pub struct EvmExecution {
 
     /// The contract address if the executed transaction deploys a contract.
     pub deployed_contract_address: Option<Address>,
+
+    /// Value transferred by internal calls. Only tracked when internal transfers indexing is enabled.
+    #[serde(default)]
+    pub internal_transfers: Vec<InternalTransfer>,
 }
 
 impl EvmExecution {
@@ -80,6 +84,7 @@ impl EvmExecution {
             gas: receipt.gas_used.unwrap_or_default().try_into()?,
             changes: HashMap::from([(sender_changes.address, sender_changes)]),
             deployed_contract_address: None,
+            internal_transfers: Vec::new(),
         };
         execution.apply_receipt(receipt)?;
         Ok(execution)
@@ -237,10 +242,17 @@ impl EvmExecution {
                 continue;
             }
 
-            let (Some(destination), Some(source)) = (execution_log.data.get_mut(0..32), receipt_log.data.get(0..32)) else {
+            let Some(source) = receipt_log.data.get(0..32) else {
                 continue;
             };
-            destination.copy_from_slice(source);
+            if execution_log.data.len() < 32 {
+                continue;
+            }
+
+            // data is a shared buffer, so it is copied before being overwritten
+            let mut data = execution_log.data.to_vec();
+            data[0..32].copy_from_slice(source);
+            execution_log.data = Bytes::from(data);
         }
     }
 }
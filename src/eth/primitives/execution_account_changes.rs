@@ -11,6 +11,7 @@ use crate::eth::primitives::Nonce;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::Wei;
+use crate::ext::not;
 #[cfg(test)]
 use crate::ext::ordered_map;
 
@@ -100,8 +101,92 @@ impl ExecutionAccountChanges {
         }
     }
 
+    /// Applies the changes of a later execution, so the modified values are the latest ones.
+    pub fn merge(&mut self, later: ExecutionAccountChanges) {
+        if later.nonce.is_modified() {
+            if let Some(nonce) = later.nonce.take_modified() {
+                self.nonce.set_modified(nonce);
+            }
+        }
+        if later.balance.is_modified() {
+            if let Some(balance) = later.balance.take_modified() {
+                self.balance.set_modified(balance);
+            }
+        }
+        if later.bytecode.is_modified() {
+            if let Some(bytecode) = later.bytecode.take_modified() {
+                self.bytecode.set_modified(bytecode);
+                self.code_hash = later.code_hash;
+            }
+        }
+        for (index, slot) in later.slots {
+            if not(slot.is_modified()) {
+                continue;
+            }
+            let Some(slot) = slot.take_modified() else { continue };
+            match self.slots.get_mut(&index) {
+                Some(entry) => entry.set_modified(slot),
+                None => {
+                    self.slots.insert(index, ExecutionValueChange::from_modified(slot));
+                }
+            }
+        }
+    }
+
+    /// Returns the account with the latest values, giving preference to the modified values.
+    pub fn to_account(&self) -> Account {
+        Account {
+            address: self.address,
+            nonce: self.nonce.take_ref().copied().unwrap_or_default(),
+            balance: self.balance.take_ref().copied().unwrap_or_default(),
+            bytecode: self.bytecode.take_ref().cloned().flatten(),
+            code_hash: self.code_hash,
+        }
+    }
+
     /// Checks if account nonce, balance or bytecode were modified.
     pub fn is_account_modified(&self) -> bool {
         self.nonce.is_modified() || self.balance.is_modified() || self.bytecode.is_modified()
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_latest_values() {
+        let address = Address::new([1; 20]);
+        let (index_a, index_b) = (SlotIndex::from(1u64), SlotIndex::from(2u64));
+
+        // first execution changes balance and slot a
+        let mut first = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, 10u64.into()));
+        first.balance.set_modified(5u64.into());
+        first
+            .slots
+            .insert(index_a, ExecutionValueChange::from_modified(Slot::new(index_a, 1u64.into())));
+
+        // second execution starts from the first one, changes nonce and slot b
+        let mut second = ExecutionAccountChanges::from_original_values(first.to_account());
+        second.nonce.set_modified(1u64.into());
+        second
+            .slots
+            .insert(index_b, ExecutionValueChange::from_modified(Slot::new(index_b, 2u64.into())));
+
+        first.merge(second);
+        let account = first.to_account();
+        assert_eq!(account.balance, 5u64.into());
+        assert_eq!(account.nonce, 1u64.into());
+        assert_eq!(
+            first.slots.get(&index_a).and_then(|slot| slot.take_ref()).map(|slot| slot.value),
+            Some(1u64.into())
+        );
+        assert_eq!(
+            first.slots.get(&index_b).and_then(|slot| slot.take_ref()).map(|slot| slot.value),
+            Some(2u64.into())
+        );
+    }
+}
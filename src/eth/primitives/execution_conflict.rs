@@ -10,6 +10,13 @@ use crate::eth::primitives::Wei;
 #[derive(DebugAsJson, serde::Serialize)]
 pub struct ExecutionConflicts(pub NonEmpty<ExecutionConflict>);
 
+impl ExecutionConflicts {
+    /// Iterates over the conflicts.
+    pub fn iter(&self) -> impl Iterator<Item = &ExecutionConflict> {
+        self.0.iter()
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ExecutionConflictsBuilder(Vec<ExecutionConflict>);
 
@@ -40,8 +47,12 @@ impl ExecutionConflictsBuilder {
     }
 }
 
+/// Value read by a transaction execution that changed in the pending block before the execution was saved.
+///
+/// `expected` is the value read by the execution and `actual` is the value in the pending block.
 #[derive(DebugAsJson, serde::Serialize)]
 #[cfg_attr(test, derive(serde::Deserialize, fake::Dummy, PartialEq))]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ExecutionConflict {
     /// Account nonce mismatch.
     Nonce { address: Address, expected: Nonce, actual: Nonce },
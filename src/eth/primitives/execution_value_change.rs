@@ -47,6 +47,14 @@ where
         }
     }
 
+    /// Creates a new [`ExecutionValueChange`] from original and modified values that may not be set.
+    pub fn from_parts(original: Option<T>, modified: Option<T>) -> Self {
+        Self {
+            original: original.map_or(ValueState::NotSet, ValueState::Set),
+            modified: modified.map_or(ValueState::NotSet, ValueState::Set),
+        }
+    }
+
     /// Sets the modified value of an original value.
     pub fn set_modified(&mut self, value: T) {
         self.modified = ValueState::Set(value);
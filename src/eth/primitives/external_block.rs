@@ -3,6 +3,7 @@ use serde::Deserialize;
 use crate::alias::EthersBlockEthersTransaction;
 use crate::alias::EthersBlockExternalTransaction;
 use crate::alias::JsonValue;
+use crate::eth::primitives::external_compat::normalize_block;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockNumber;
@@ -12,7 +13,7 @@ use crate::eth::primitives::Hash;
 use crate::eth::primitives::UnixTime;
 use crate::log_and_err;
 
-#[derive(Debug, Clone, derive_more::Deref, derive_more::DerefMut, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, derive_more::Deref, derive_more::DerefMut, serde::Serialize)]
 #[serde(transparent)]
 pub struct ExternalBlock(#[deref] pub EthersBlockExternalTransaction);
 
@@ -66,6 +67,15 @@ impl TryFrom<&ExternalBlock> for Block {
 // -----------------------------------------------------------------------------
 // Conversions: Other -> Self
 // -----------------------------------------------------------------------------
+/// Normalizes differences between clients before deserializing (see [`normalize_block`]).
+impl<'de> Deserialize<'de> for ExternalBlock {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = JsonValue::deserialize(deserializer)?;
+        normalize_block(&mut value);
+        EthersBlockExternalTransaction::deserialize(value).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
 impl TryFrom<JsonValue> for ExternalBlock {
     type Error = anyhow::Error;
 
@@ -0,0 +1,188 @@
+//! Normalization of blocks and receipts returned by different Ethereum clients.
+//!
+//! Geth, Erigon, Nethermind and Besu agree on the JSON-RPC schema, but not on every detail: some fields are omitted instead of returned as `null`
+//! (or the opposite), the block author may be returned only as `author`, transactions may carry their input as `data`, and typed transactions may
+//! carry their signature parity only as `yParity`. Payloads are normalized
+//! to the shape expected by the deserializers before being converted, so differences between clients do not stop the importer.
+
+use serde_json::Map;
+
+use crate::alias::JsonValue;
+use crate::ext::not;
+
+/// Normalizes an external block and all its transactions.
+pub fn normalize_block(block: &mut JsonValue) {
+    let Some(block) = block.as_object_mut() else { return };
+    remove_nulls(block);
+
+    // block author returned only as `author` (OpenEthereum and older Nethermind versions)
+    if not(block.contains_key("miner")) {
+        if let Some(author) = block.get("author").cloned() {
+            block.insert("miner".to_owned(), author);
+        }
+    }
+
+    if let Some(JsonValue::Array(transactions)) = block.get_mut("transactions") {
+        for tx in transactions {
+            normalize_transaction(tx);
+        }
+    }
+}
+
+/// Normalizes an external transaction.
+fn normalize_transaction(tx: &mut JsonValue) {
+    let Some(tx) = tx.as_object_mut() else { return };
+    remove_nulls(tx);
+
+    // transaction input returned only as `data`
+    if not(tx.contains_key("input")) {
+        if let Some(data) = tx.get("data").cloned() {
+            tx.insert("input".to_owned(), data);
+        }
+    }
+
+    // signature parity of typed transactions (like EIP-4844 blob transactions) returned only as `yParity`
+    if not(tx.contains_key("v")) {
+        if let Some(y_parity) = tx.get("yParity").cloned() {
+            tx.insert("v".to_owned(), y_parity);
+        }
+    }
+}
+
+/// Normalizes an external receipt and all its logs.
+pub fn normalize_receipt(receipt: &mut JsonValue) {
+    let Some(receipt) = receipt.as_object_mut() else { return };
+    remove_nulls(receipt);
+
+    if let Some(JsonValue::Array(logs)) = receipt.get_mut("logs") {
+        for log in logs.iter_mut().filter_map(JsonValue::as_object_mut) {
+            remove_nulls(log);
+        }
+    }
+}
+
+/// Removes fields with `null` values, so they are handled the same way as missing fields and fall back to their defaults.
+fn remove_nulls(object: &mut Map<String, JsonValue>) {
+    object.retain(|_, value| not(value.is_null()));
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::BlockHeader;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::ExternalBlock;
+    use crate::eth::primitives::ExternalReceipt;
+    use crate::eth::primitives::ExternalTransaction;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::TransactionInput;
+    use crate::eth::primitives::Wei;
+
+    /// Block and receipts payloads in the shape returned by each client.
+    ///
+    /// Expected values are read from the raw payloads, so fixtures captured with `utils/capture-external-client-fixture.sh` can replace them.
+    const CLIENT_PAYLOADS: [(&str, &str); 4] = [
+        ("geth", include_str!("../../../tests/fixtures/external_clients/geth.json")),
+        ("erigon", include_str!("../../../tests/fixtures/external_clients/erigon.json")),
+        ("nethermind", include_str!("../../../tests/fixtures/external_clients/nethermind.json")),
+        ("besu", include_str!("../../../tests/fixtures/external_clients/besu.json")),
+    ];
+
+    #[test]
+    fn test_client_payloads() {
+        for (client, payload) in CLIENT_PAYLOADS {
+            let mut payload: JsonValue = serde_json::from_str(payload).unwrap();
+
+            // expected values from the raw payload
+            let raw_block = &payload["block"];
+            let number: BlockNumber = serde_json::from_value(raw_block["number"].clone()).unwrap();
+            let author: Address = serde_json::from_value(raw_block.get("miner").unwrap_or(&raw_block["author"]).clone()).unwrap();
+            let tx_hashes: Vec<Hash> = raw_block["transactions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tx| serde_json::from_value(tx["hash"].clone()).unwrap())
+                .collect();
+            let statuses: Vec<bool> = payload["receipts"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|receipt| receipt["status"] == json!("0x1"))
+                .collect();
+
+            // block
+            let block = ExternalBlock::try_from(payload["block"].take()).unwrap_or_else(|e| panic!("{client}: {e:?}"));
+            let header = BlockHeader::try_from(&block).unwrap_or_else(|e| panic!("{client}: {e:?}"));
+            assert_eq!(header.number, number, "{client}");
+            assert_eq!(header.author, author, "{client}");
+            assert_eq!(block.transactions.iter().map(|tx| tx.hash()).collect::<Vec<_>>(), tx_hashes, "{client}");
+
+            // receipts
+            let JsonValue::Array(receipts) = payload["receipts"].take() else {
+                panic!("{client}: receipts are not an array")
+            };
+            assert_eq!(receipts.len(), tx_hashes.len(), "{client}");
+            for ((receipt, tx_hash), success) in receipts.into_iter().zip(tx_hashes).zip(statuses) {
+                let receipt = ExternalReceipt::try_from(receipt).unwrap_or_else(|e| panic!("{client}: {e:?}"));
+                assert_eq!(receipt.hash(), tx_hash, "{client}");
+                assert_eq!(receipt.block_number(), number, "{client}");
+                assert_eq!(receipt.is_success(), success, "{client}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_block_nulls_and_author() {
+        let mut block = json!({
+            "author": "0x8ba1f109551bd432803012645ac136ddd64dba72",
+            "extraData": null,
+            "uncles": null,
+            "transactions": [{ "data": "0x01", "to": null }],
+        });
+        normalize_block(&mut block);
+        assert_eq!(
+            block,
+            json!({
+                "author": "0x8ba1f109551bd432803012645ac136ddd64dba72",
+                "miner": "0x8ba1f109551bd432803012645ac136ddd64dba72",
+                "transactions": [{ "data": "0x01", "input": "0x01" }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_blob_transaction() {
+        let mut payload: JsonValue = serde_json::from_str(CLIENT_PAYLOADS[0].1).unwrap();
+        let tx = &mut payload["block"]["transactions"][0];
+        tx["type"] = json!("0x3");
+        tx["maxFeePerGas"] = json!("0x3b9aca00");
+        tx["maxPriorityFeePerGas"] = json!("0x0");
+        tx["maxFeePerBlobGas"] = json!("0x1");
+        tx["blobVersionedHashes"] = json!(["0x01b0a4cdd5f55589f5c5b4d46c76704bb6ce95c0a8c09f77f197a57808dded28"]);
+        tx["yParity"] = json!("0x1");
+        tx.as_object_mut().unwrap().remove("v");
+
+        let block = ExternalBlock::try_from(payload["block"].take()).unwrap();
+        let tx = &block.transactions[0];
+        assert!(tx.is_blob());
+        assert_eq!(tx.v.as_u64(), 1);
+        assert_eq!(tx.max_fee_per_blob_gas(), Some(Wei::from(1u64)));
+        assert_eq!(tx.blob_versioned_hashes().len(), 1);
+
+        let input = TransactionInput::try_from(tx.clone()).unwrap();
+        assert_eq!(input.tx_type.unwrap().as_u64(), ExternalTransaction::BLOB_TX_TYPE);
+    }
+
+    #[test]
+    fn test_normalize_receipt_nulls() {
+        let mut receipt = json!({ "contractAddress": null, "logs": [{ "removed": null, "logIndex": "0x0" }] });
+        normalize_receipt(&mut receipt);
+        assert_eq!(receipt, json!({ "logs": [{ "logIndex": "0x0" }] }));
+    }
+}
@@ -3,6 +3,7 @@ use serde::Deserialize;
 
 use crate::alias::EthersReceipt;
 use crate::alias::JsonValue;
+use crate::eth::primitives::external_compat::normalize_receipt;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Wei;
@@ -10,7 +11,7 @@ use crate::ext::not;
 use crate::ext::OptionExt;
 use crate::log_and_err;
 
-#[derive(Debug, Clone, derive_more::Deref, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, derive_more::Deref, serde::Serialize)]
 #[serde(transparent)]
 pub struct ExternalReceipt(#[deref] pub EthersReceipt);
 
@@ -65,6 +66,15 @@ impl From<EthersReceipt> for ExternalReceipt {
 // Conversions: Other -> Self
 // -----------------------------------------------------------------------------
 
+/// Normalizes differences between clients before deserializing (see [`normalize_receipt`]).
+impl<'de> Deserialize<'de> for ExternalReceipt {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = JsonValue::deserialize(deserializer)?;
+        normalize_receipt(&mut value);
+        EthersReceipt::deserialize(value).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
 impl TryFrom<JsonValue> for ExternalReceipt {
     type Error = anyhow::Error;
 
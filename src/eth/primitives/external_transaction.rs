@@ -1,15 +1,28 @@
 use anyhow::Context;
 use anyhow::Result;
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethers_core::types::Signature;
+use ethers_core::utils::keccak256;
+use itertools::Itertools;
+use rlp::RlpStream;
 
 use crate::alias::EthersTransaction;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::Wei;
+use crate::log_and_err;
 
 #[derive(Debug, Clone, Default, derive_more::Deref, serde::Deserialize, serde::Serialize)]
 #[serde(transparent)]
 pub struct ExternalTransaction(#[deref] pub EthersTransaction);
 
 impl ExternalTransaction {
+    /// Type of EIP-4844 blob transactions.
+    pub const BLOB_TX_TYPE: u64 = 3;
+
     /// Returns the block number where the transaction was mined.
     pub fn block_number(&self) -> Result<BlockNumber> {
         Ok(self.0.block_number.context("ExternalTransaction has no block_number")?.into())
@@ -20,6 +33,82 @@ impl ExternalTransaction {
         self.0.hash.into()
     }
 
+    /// Checks if it is an EIP-4844 blob transaction.
+    pub fn is_blob(&self) -> bool {
+        self.0.transaction_type.is_some_and(|tx_type| tx_type.as_u64() == Self::BLOB_TX_TYPE)
+    }
+
+    /// Returns the versioned hashes of the blobs of a blob transaction.
+    ///
+    /// Blobs are not available to the EVM, so they are kept only as transaction metadata and never verified.
+    pub fn blob_versioned_hashes(&self) -> Vec<Hash> {
+        let hashes = self.0.other.get_deserialized::<Vec<H256>>("blobVersionedHashes").and_then(Result::ok);
+        hashes.unwrap_or_default().into_iter().map_into().collect()
+    }
+
+    /// Returns the max fee per blob gas of a blob transaction.
+    pub fn max_fee_per_blob_gas(&self) -> Option<Wei> {
+        let fee = self.0.other.get_deserialized::<U256>("maxFeePerBlobGas").and_then(Result::ok);
+        fee.map(Wei::from)
+    }
+
+    /// Recovers the sender from the transaction signature, reusing the signer recovered for the same signed transaction.
+    ///
+    /// ethers-core hashes blob transactions as legacy transactions, so they are recovered from their EIP-4844 signing hash instead.
+    pub fn recover_signer(&self) -> Result<Address> {
+        let signature = Signature {
+            r: self.0.r,
+            s: self.0.s,
+            v: self.0.v.as_u64(),
+        };
+
+        if self.is_blob() {
+            let signed_hash = Hash::new(keccak256(self.blob_rlp(Some(&signature))?));
+            let signing_hash = H256(keccak256(self.blob_rlp(None)?));
+            return TransactionInput::recover_signer_cached(signed_hash, || Ok(signature.recover(signing_hash)?.into()));
+        }
+
+        // the hash is calculated from the signed fields instead of trusting the hash informed by the external RPC
+        TransactionInput::recover_signer_cached(self.0.hash().into(), || Ok(self.0.recover_from()?.into()))
+    }
+
+    /// Encodes a blob transaction according to EIP-4844, with the signature when it is the signed encoding or without it when it is the
+    /// encoding signed by the sender.
+    fn blob_rlp(&self, signature: Option<&Signature>) -> Result<Vec<u8>> {
+        let (Some(chain_id), Some(to)) = (self.0.chain_id, self.0.to) else {
+            return log_and_err!(payload = self.hash(), "blob transaction is missing the chain id or the recipient");
+        };
+        let max_fee_per_blob_gas = self
+            .0
+            .other
+            .get_deserialized::<U256>("maxFeePerBlobGas")
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        let blob_versioned_hashes = self.blob_versioned_hashes().into_iter().map(H256::from).collect_vec();
+
+        let mut s = RlpStream::new_list(if signature.is_some() { 14 } else { 11 });
+        s.append(&chain_id);
+        s.append(&self.0.nonce);
+        s.append(&self.0.max_priority_fee_per_gas.unwrap_or_default());
+        s.append(&self.0.max_fee_per_gas.unwrap_or_default());
+        s.append(&self.0.gas);
+        s.append(&to);
+        s.append(&self.0.value);
+        s.append(&self.0.input.to_vec());
+        s.append(&self.0.access_list.clone().unwrap_or_default());
+        s.append(&max_fee_per_blob_gas);
+        s.append_list(&blob_versioned_hashes);
+        if let Some(signature) = signature {
+            s.append(&signature.v);
+            s.append(&signature.r);
+            s.append(&signature.s);
+        }
+
+        let mut encoded = vec![Self::BLOB_TX_TYPE as u8];
+        encoded.extend_from_slice(&s.out());
+        Ok(encoded)
+    }
+
     /// Fills the field transaction_type based on `v`
     pub fn fill_missing_transaction_type(&mut self) {
         // Don't try overriding if it's already set
@@ -42,3 +131,61 @@ impl From<EthersTransaction> for ExternalTransaction {
         ExternalTransaction(value)
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethereum_types::H160;
+    use ethers_core::k256::ecdsa::SigningKey;
+    use ethers_core::types::transaction::eip2930::AccessList;
+    use ethers_core::utils::secret_key_to_address;
+    use serde_json::json;
+
+    use super::*;
+    use crate::ext::not;
+
+    #[test]
+    fn test_recover_blob_transaction_signer() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let signer = secret_key_to_address(&key);
+
+        let mut tx = ExternalTransaction(EthersTransaction {
+            transaction_type: Some(3.into()),
+            chain_id: Some(2008.into()),
+            nonce: 7.into(),
+            to: Some(H160::repeat_byte(0xff)),
+            value: 1.into(),
+            gas: 21_000.into(),
+            max_fee_per_gas: Some(2.into()),
+            max_priority_fee_per_gas: Some(1.into()),
+            access_list: Some(AccessList::default()),
+            from: signer,
+            ..Default::default()
+        });
+        tx.0.other.insert("maxFeePerBlobGas".into(), json!("0x1"));
+        tx.0.other.insert(
+            "blobVersionedHashes".into(),
+            json!(["0x01b0a4cdd5f55589f5c5b4d46c76704bb6ce95c0a8c09f77f197a57808dded28"]),
+        );
+
+        // sign the EIP-4844 signing hash
+        let signing_hash = keccak256(tx.blob_rlp(None).unwrap());
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&signing_hash).unwrap();
+        tx.0.r = U256::from_big_endian(signature.r().to_bytes().as_slice());
+        tx.0.s = U256::from_big_endian(signature.s().to_bytes().as_slice());
+        tx.0.v = recovery_id.to_byte().into();
+        tx.0.hash = H256(keccak256(
+            tx.blob_rlp(Some(&Signature {
+                r: tx.0.r,
+                s: tx.0.s,
+                v: tx.0.v.as_u64(),
+            }))
+            .unwrap(),
+        ));
+
+        assert_eq!(tx.recover_signer().unwrap(), signer.into());
+        assert!(not(matches!(tx.0.recover_from(), Ok(recovered) if recovered == signer)));
+    }
+}
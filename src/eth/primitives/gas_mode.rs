@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+/// How transactions pay for the gas they use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum GasMode {
+    /// Gas is metered and recorded in receipts, but execution is sponsored by the chain and never charged to balances.
+    #[serde(rename = "free")]
+    Free,
+
+    /// Gas limit and gas price of transactions are enforced and fees are charged to the sender balance.
+    ///
+    /// Collected fees are credited to the fee treasury when configured, otherwise they are burned.
+    #[serde(rename = "charged")]
+    Charged,
+}
+
+impl GasMode {
+    pub fn is_charged(&self) -> bool {
+        matches!(self, Self::Charged)
+    }
+}
+
+impl FromStr for GasMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "free" => Ok(Self::Free),
+            "charged" => Ok(Self::Charged),
+            s => Err(anyhow!("unknown gas mode: {}", s)),
+        }
+    }
+}
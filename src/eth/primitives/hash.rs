@@ -6,7 +6,9 @@ use display_json::DebugAsJson;
 use ethereum_types::H256;
 use fake::Dummy;
 use fake::Faker;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
 
 use crate::gen_newtype_from;
@@ -82,6 +84,7 @@ impl FromStr for Hash {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Hash {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
         let value = <[u8; 32] as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
@@ -89,6 +92,7 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Hash {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Hash {
     fn encode(self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError>
     where
@@ -102,12 +106,14 @@ impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Hash {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for Hash {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("BYTEA")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for Hash {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <&[u8; 32] as PgHasArrayType>::array_type_info()
@@ -0,0 +1,79 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::Wei;
+
+/// Value transferred by a call, contract creation or self-destruct executed inside a transaction.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct InternalTransfer {
+    pub kind: InternalTransferKind,
+    pub from: Address,
+    pub to: Address,
+    pub value: Wei,
+
+    /// Call depth of the frame that received the value. The contract called by the transaction is at depth 0.
+    pub depth: usize,
+}
+
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub enum InternalTransferKind {
+    #[serde(rename = "call")]
+    Call,
+
+    #[serde(rename = "create")]
+    Create,
+
+    #[serde(rename = "selfdestruct")]
+    Selfdestruct,
+}
+
+/// Selects internal transfers of a block or sent or received by an account.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum InternalTransferFilter {
+    Address(Address),
+    Block(BlockFilter),
+}
+
+/// Internal transfer of a transaction that was added to a block.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct InternalTransferMined {
+    /// Original transfer executed by the EVM.
+    pub transfer: InternalTransfer,
+
+    /// Hash of the transaction that executed this transfer.
+    pub transaction_hash: Hash,
+
+    /// Position of the transaction that executed this transfer inside the block.
+    pub transaction_index: Index,
+
+    /// Block number where the transfer was mined.
+    pub block_number: BlockNumber,
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn test_internal_transfer_filter_deserialize() {
+        let parse = |json: &str| serde_json::from_str::<InternalTransferFilter>(json).unwrap();
+
+        assert_eq!(
+            parse(r#""0x0000000000000000000000000000000000000001""#),
+            InternalTransferFilter::Address(Address::from(hex!("0000000000000000000000000000000000000001")))
+        );
+        assert_eq!(parse(r#""0x1""#), InternalTransferFilter::Block(BlockFilter::Number(1u64.into())));
+        assert_eq!(parse("1"), InternalTransferFilter::Block(BlockFilter::Number(1u64.into())));
+        assert_eq!(parse(r#""latest""#), InternalTransferFilter::Block(BlockFilter::Latest));
+    }
+}
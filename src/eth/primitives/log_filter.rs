@@ -96,6 +96,7 @@ mod tests {
     use crate::eth::primitives::Log;
     use crate::eth::primitives::LogFilterInputTopic;
     use crate::eth::primitives::LogTopic;
+    use crate::eth::storage::StorageReader;
     use crate::eth::storage::StratusStorage;
     use crate::utils::test_utils::fake_first;
     use crate::utils::test_utils::fake_list;
@@ -103,14 +104,14 @@ mod tests {
     fn build_filter(addresses: Vec<Address>, topics_nested: Vec<Vec<Option<LogTopic>>>) -> LogFilter {
         let topics_map = |topics: Vec<Option<LogTopic>>| LogFilterInputTopic(topics.into_iter().collect());
 
-        let storage = StratusStorage::new_test().unwrap();
+        let storage: Arc<dyn StorageReader> = Arc::new(StratusStorage::new_test().unwrap());
 
         LogFilterInput {
             address: addresses,
             topics: topics_nested.into_iter().map(topics_map).collect(),
             ..LogFilterInput::default()
         }
-        .parse(&Arc::new(storage))
+        .parse(&storage)
         .unwrap()
     }
 
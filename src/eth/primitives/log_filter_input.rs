@@ -1,4 +1,5 @@
 use std::ops::Deref;
+#[cfg(feature = "node")]
 use std::sync::Arc;
 
 use display_json::DebugAsJson;
@@ -11,11 +12,13 @@ use serde_with::PickFirst;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::Hash;
+#[cfg(feature = "node")]
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogTopic;
+#[cfg(feature = "node")]
 use crate::eth::primitives::PointInTime;
-use crate::eth::storage::Storage;
-use crate::eth::storage::StratusStorage;
+#[cfg(feature = "node")]
+use crate::eth::storage::StorageReader;
 
 /// JSON-RPC input used in methods like `eth_getLogs` and `eth_subscribe`.
 #[serde_as]
@@ -41,9 +44,10 @@ pub struct LogFilterInput {
     pub topics: Vec<LogFilterInputTopic>,
 }
 
+#[cfg(feature = "node")]
 impl LogFilterInput {
     /// Parses itself into a filter that can be applied in produced log events or to query the storage.
-    pub fn parse(self, storage: &Arc<StratusStorage>) -> anyhow::Result<LogFilter> {
+    pub fn parse(self, storage: &Arc<dyn StorageReader>) -> anyhow::Result<LogFilter> {
         let original_input = self.clone();
 
         // parse point-in-time
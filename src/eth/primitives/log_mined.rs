@@ -1,5 +1,6 @@
 use display_json::DebugAsJson;
 use itertools::Itertools;
+#[cfg(feature = "node")]
 use jsonrpsee::SubscriptionMessage;
 
 use crate::alias::EthersLog;
@@ -93,6 +94,7 @@ impl From<LogMined> for EthersLog {
     }
 }
 
+#[cfg(feature = "node")]
 impl TryFrom<LogMined> for SubscriptionMessage {
     type Error = serde_json::Error;
 
@@ -1,16 +1,23 @@
 mod account;
 mod address;
+mod balance_change;
+mod banned_opcode;
 mod block;
+mod block_bundle;
 mod block_filter;
 mod block_header;
 mod block_number;
 pub mod bytes;
 mod call_input;
 mod chain_id;
+mod chain_spec;
 mod code_hash;
+mod contract_creation;
 mod difficulty;
 mod ecdsa_rs;
 mod ecdsa_v;
+mod evm_execution_result;
+mod evm_input;
 mod execution;
 mod execution_account_changes;
 mod execution_conflict;
@@ -18,12 +25,15 @@ mod execution_metrics;
 mod execution_result;
 mod execution_value_change;
 mod external_block;
+mod external_compat;
 mod external_receipt;
 mod external_receipts;
 mod external_transaction;
 mod gas;
+mod gas_mode;
 mod hash;
 mod index;
+mod internal_transfer;
 mod log;
 mod log_filter;
 mod log_filter_input;
@@ -35,33 +45,45 @@ mod nonce;
 mod pending_block;
 mod pending_block_header;
 mod point_in_time;
+mod receipt_proof;
 mod size;
 mod slot;
 mod slot_index;
 mod slot_value;
 mod stratus_error;
+mod token_transfer;
 mod transaction_execution;
 mod transaction_input;
 mod transaction_mined;
 mod transaction_stage;
 mod unix_time;
 mod unix_time_now;
+mod user_operation;
 mod wei;
 
 pub use account::test_accounts;
 pub use account::Account;
 pub use address::Address;
+pub use balance_change::BalanceChange;
+pub use banned_opcode::BannedOpcode;
 pub use block::Block;
+pub use block_bundle::BlockBundle;
+pub use block_bundle::BundleAccount;
 pub use block_filter::BlockFilter;
 pub use block_header::BlockHeader;
 pub use block_number::BlockNumber;
 pub use bytes::Bytes;
 pub use call_input::CallInput;
 pub use chain_id::ChainId;
+pub use chain_spec::ChainSpec;
+pub use chain_spec::HeaderInvariantViolation;
 pub use code_hash::CodeHash;
+pub use contract_creation::ContractCreation;
 pub use difficulty::Difficulty;
 pub use ecdsa_rs::EcdsaRs;
 pub use ecdsa_v::EcdsaV;
+pub use evm_execution_result::EvmExecutionResult;
+pub use evm_input::EvmInput;
 pub use execution::EvmExecution;
 pub use execution::ExecutionChanges;
 pub use execution_account_changes::ExecutionAccountChanges;
@@ -76,8 +98,13 @@ pub use external_receipt::ExternalReceipt;
 pub use external_receipts::ExternalReceipts;
 pub use external_transaction::ExternalTransaction;
 pub use gas::Gas;
+pub use gas_mode::GasMode;
 pub use hash::Hash;
 pub use index::Index;
+pub use internal_transfer::InternalTransfer;
+pub use internal_transfer::InternalTransferFilter;
+pub use internal_transfer::InternalTransferKind;
+pub use internal_transfer::InternalTransferMined;
 pub use log::Log;
 pub use log_filter::LogFilter;
 pub use log_filter_input::LogFilterInput;
@@ -89,11 +116,18 @@ pub use nonce::Nonce;
 pub use pending_block::PendingBlock;
 pub use pending_block_header::PendingBlockHeader;
 pub use point_in_time::PointInTime;
+pub use receipt_proof::ReceiptProof;
 pub use size::Size;
 pub use slot::Slot;
 pub use slot_index::SlotIndex;
 pub use slot_value::SlotValue;
+pub use stratus_error::ErrorClass;
 pub use stratus_error::StratusError;
+pub use token_transfer::TokenAmount;
+pub use token_transfer::TokenStandard;
+pub use token_transfer::TokenTransfer;
+pub use token_transfer::TokenTransferFilter;
+pub use token_transfer::TokenTransferMined;
 pub use transaction_execution::ExternalTransactionExecution;
 pub use transaction_execution::LocalTransactionExecution;
 pub use transaction_execution::TransactionExecution;
@@ -102,6 +136,8 @@ pub use transaction_mined::TransactionMined;
 pub use transaction_stage::TransactionStage;
 pub use unix_time::UnixTime;
 pub use unix_time_now::UnixTimeNow;
+pub use user_operation::is_simulate_validation_success;
+pub use user_operation::UserOperation;
 pub use wei::Wei;
 
 // -----------------------------------------------------------------------------
@@ -109,7 +145,25 @@ pub use wei::Wei;
 // -----------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use ethereum_types::H160;
+    use ethereum_types::H256;
+    use ethereum_types::U256;
+    use ethers_core::k256::ecdsa::SigningKey;
+    use ethers_core::types::transaction::eip2718::TypedTransaction;
+    use ethers_core::types::Signature as EthersSignature;
+    use ethers_core::types::TransactionRequest;
+    use ethers_core::utils::secret_key_to_address;
+    use fake::Fake;
+    use fake::Faker;
+    #[cfg(feature = "postgres")]
+    use sqlx::types::BigDecimal;
+
     use super::*;
+    use crate::alias::EthersTransaction;
+    use crate::alias::RevmU256;
+    use crate::gen_test_roundtrip;
     use crate::gen_test_serde;
 
     type TransactionExecutionValueChangeBytes = ExecutionValueChange<Bytes>;
@@ -133,6 +187,7 @@ mod tests {
     gen_test_serde!(Account);
     gen_test_serde!(Address);
     gen_test_serde!(Block);
+    gen_test_serde!(BlockBundle);
     gen_test_serde!(BlockFilter);
     gen_test_serde!(BlockHeader);
     gen_test_serde!(BlockNumber);
@@ -140,6 +195,7 @@ mod tests {
     gen_test_serde!(CallInput);
     gen_test_serde!(ChainId);
     gen_test_serde!(CodeHash);
+    gen_test_serde!(ContractCreation);
     gen_test_serde!(Difficulty);
     gen_test_serde!(EcdsaRs);
     gen_test_serde!(EcdsaV);
@@ -151,6 +207,8 @@ mod tests {
     gen_test_serde!(Gas);
     gen_test_serde!(Hash);
     gen_test_serde!(Index);
+    gen_test_serde!(InternalTransfer);
+    gen_test_serde!(InternalTransferMined);
     gen_test_serde!(LocalTransactionExecution);
     gen_test_serde!(Log);
     gen_test_serde!(LogFilter);
@@ -169,9 +227,106 @@ mod tests {
     gen_test_serde!(TransactionExecutionValueChangeOptionString);
     gen_test_serde!(TransactionExecutionValueChangeSlot);
     gen_test_serde!(TransactionExecutionValueChangeWei);
+    gen_test_serde!(TokenAmount);
+    gen_test_serde!(TokenTransfer);
+    gen_test_serde!(TokenTransferMined);
     gen_test_serde!(TransactionInput);
     gen_test_serde!(TransactionMined);
     gen_test_serde!(UnixTime);
     gen_test_serde!(UnixTimeNow);
     gen_test_serde!(Wei);
+
+    // conversions to other representations, mostly used by storages and external libraries
+    gen_test_roundtrip!(Address, bytes, |value| Ok(Address::from(<[u8; 20]>::from(value))));
+    gen_test_roundtrip!(Address, h160, |value| Ok(Address::from(H160::from(value))));
+    gen_test_roundtrip!(Address, str, |value| Address::from_str(&value.to_string()));
+    #[cfg(feature = "postgres")]
+    gen_test_roundtrip!(BlockNumber, big_decimal, |value| BlockNumber::try_from(BigDecimal::from(u64::from(value))));
+    gen_test_roundtrip!(
+        BlockNumber,
+        i64,
+        |value| Ok(BlockNumber::from(value.as_i64()?)),
+        domain = |value| value.as_u64() <= i64::MAX as u64
+    );
+    gen_test_roundtrip!(BlockNumber, u64, |value| Ok(BlockNumber::from(u64::from(value))));
+    gen_test_roundtrip!(Gas, u256, |value| Gas::try_from(U256::from(value)));
+    gen_test_roundtrip!(Hash, h256, |value| Ok(Hash::from(H256::from(value))));
+    gen_test_roundtrip!(Hash, str, |value| Hash::from_str(&value.to_string()));
+    #[cfg(feature = "postgres")]
+    gen_test_roundtrip!(Wei, big_decimal, |value| Wei::try_from(BigDecimal::try_from(value)?));
+    gen_test_roundtrip!(Wei, revm_u256, |value| Ok(Wei::from(<RevmU256 as From<Wei>>::from(value))));
+    gen_test_roundtrip!(TransactionInput, ethers, |value| {
+        // ethers transactions have a single sender field, so the converted `from` is always the signer
+        let from = value.from;
+        let converted = TransactionInput::try_from(ExternalTransaction(EthersTransaction::from(value)))?;
+        Ok(TransactionInput { from, ..converted })
+    });
+
+    // postgres columns, through the representations decoded and encoded by the sqlx implementations
+    gen_test_roundtrip!(Hash, bytes, |value| Ok(Hash::from(*value.0.as_fixed_bytes())));
+    gen_test_roundtrip!(SlotIndex, bytes, |value| Ok(SlotIndex::from(<[u8; 32]>::from(value))));
+    gen_test_roundtrip!(SlotValue, bytes, |value| Ok(SlotValue::from(<[u8; 32]>::from(value))));
+
+    // fields of RLP encoded block headers and transactions
+    gen_test_roundtrip!(Address, rlp, |value| Ok(Address::from(rlp::decode::<H160>(&rlp::encode(&H160::from(value)))?)));
+    gen_test_roundtrip!(BlockNumber, rlp, |value| {
+        let encoded = rlp::encode(&u64::from(value));
+        Ok(BlockNumber::from(rlp::decode::<u64>(&encoded)?))
+    });
+    gen_test_roundtrip!(Bytes, rlp, |value| {
+        let encoded = rlp::encode(&Vec::<u8>::from(value));
+        Ok(Bytes::from(rlp::decode::<Vec<u8>>(&encoded)?))
+    });
+    gen_test_roundtrip!(Hash, rlp, |value| Ok(Hash::from(rlp::decode::<H256>(&rlp::encode(&H256::from(value)))?)));
+    gen_test_roundtrip!(Nonce, rlp, |value| Ok(Nonce::from(rlp::decode::<u64>(&rlp::encode(&u64::from(value)))?)));
+    gen_test_roundtrip!(Wei, rlp, |value| Ok(Wei::from(rlp::decode::<U256>(&rlp::encode(&U256::from(value)))?)));
+
+    #[test]
+    fn roundtrip_rlp_signed_transaction_input() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        for _ in 0..100 {
+            let tx = TypedTransaction::Legacy(
+                TransactionRequest::new()
+                    .chain_id(2008)
+                    .nonce(Faker.fake::<Nonce>())
+                    .to(H160::from(Faker.fake::<Address>()))
+                    .value(Faker.fake::<Wei>())
+                    .data(Faker.fake::<Bytes>())
+                    .gas(Faker.fake::<Gas>())
+                    .gas_price(Faker.fake::<Wei>()),
+            );
+            let (signature, recovery_id) = key.sign_prehash_recoverable(tx.sighash().as_bytes()).unwrap();
+            let signature = EthersSignature {
+                r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+                s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+                v: recovery_id.to_byte() as u64 + 35 + 2008 * 2,
+            };
+            let encoded = tx.rlp_signed(&signature);
+
+            let decoded: TransactionInput = rlp::decode(&encoded).unwrap();
+            assert_eq!(decoded.signer, Address::from(secret_key_to_address(&key)));
+            assert_eq!(EthersTransaction::from(decoded).rlp(), encoded);
+        }
+    }
+
+    #[test]
+    fn roundtrip_edge_values() {
+        // block numbers are stored as i64 by postgres
+        assert_eq!(BlockNumber::from(i64::MAX).as_i64().unwrap(), i64::MAX);
+        assert!(BlockNumber::from(u64::MAX).as_i64().is_err());
+        #[cfg(feature = "postgres")]
+        assert_eq!(BlockNumber::try_from(BigDecimal::from(u64::MAX)).unwrap(), BlockNumber::from(u64::MAX));
+
+        // gas is limited to u64
+        assert_eq!(Gas::try_from(U256::from(u64::MAX)).unwrap(), Gas::from(u64::MAX));
+        assert!(Gas::try_from(U256::from(u64::MAX) + 1).is_err());
+
+        // wei uses the full u256 range
+        #[cfg(feature = "postgres")]
+        assert_eq!(
+            Wei::try_from(BigDecimal::try_from(Wei::from(U256::MAX)).unwrap()).unwrap(),
+            Wei::from(U256::MAX)
+        );
+        assert_eq!(Wei::from(<RevmU256 as From<Wei>>::from(Wei::from(U256::MAX))), Wei::from(U256::MAX));
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use display_json::DebugAsJson;
 use indexmap::IndexMap;
 
@@ -6,6 +8,7 @@ use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlockHeader;
 use crate::eth::primitives::TransactionExecution;
+use crate::eth::primitives::UnixTime;
 
 /// Block that is being mined and receiving updates.
 #[derive(DebugAsJson, Clone, Default, serde::Serialize)]
@@ -13,15 +16,20 @@ pub struct PendingBlock {
     pub header: PendingBlockHeader,
     pub transactions: IndexMap<Hash, TransactionExecution>,
     pub external_block: Option<ExternalBlock>,
+
+    /// Time spent checking conflicts of the transactions saved in the block.
+    #[serde(skip)]
+    pub conflict_check_time: Duration,
 }
 
 impl PendingBlock {
-    /// Creates a new [`PendingBlock`] with the specified number.
-    pub fn new_at_now(number: BlockNumber) -> Self {
+    /// Creates a new [`PendingBlock`] with the specified number and timestamp.
+    pub fn new(number: BlockNumber, timestamp: UnixTime) -> Self {
         Self {
-            header: PendingBlockHeader::new_at_now(number),
+            header: PendingBlockHeader::new(number, timestamp),
             transactions: IndexMap::new(),
             external_block: None,
+            conflict_check_time: Duration::ZERO,
         }
     }
 
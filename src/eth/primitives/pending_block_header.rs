@@ -1,6 +1,7 @@
 use display_json::DebugAsJson;
 
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::UnixTime;
 use crate::eth::primitives::UnixTimeNow;
 
 /// Header of the pending block being mined.
@@ -11,8 +12,11 @@ pub struct PendingBlockHeader {
 }
 
 impl PendingBlockHeader {
-    /// Creates a new [`PendingBlockHeader`] with the specified number and the current timestamp.
-    pub fn new_at_now(number: BlockNumber) -> Self {
-        Self { number, ..Self::default() }
+    /// Creates a new [`PendingBlockHeader`] with the specified number and timestamp.
+    pub fn new(number: BlockNumber, timestamp: UnixTime) -> Self {
+        Self {
+            number,
+            timestamp: timestamp.into(),
+        }
     }
 }
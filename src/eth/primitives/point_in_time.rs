@@ -1,4 +1,5 @@
 use crate::eth::primitives::BlockNumber;
+#[cfg(feature = "node")]
 use crate::infra::metrics::MetricLabelValue;
 
 /// EVM storage point-in-time indicator.
@@ -23,6 +24,7 @@ pub enum PointInTime {
 // -----------------------------------------------------------------------------
 // Conversions: Self -> Other
 // -----------------------------------------------------------------------------
+#[cfg(feature = "node")]
 impl From<PointInTime> for MetricLabelValue {
     fn from(value: PointInTime) -> Self {
         Self::Some(value.to_string())
@@ -0,0 +1,307 @@
+use display_json::DebugAsJson;
+use ethereum_types::H256;
+use ethers_core::utils::keccak256;
+use rlp::RlpStream;
+
+use super::block_header::HASH_EMPTY_TRIE;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::StratusError;
+
+/// Merkle proof that a transaction receipt is included in the receipts trie of a block.
+///
+/// Proof nodes are ordered from the trie root to the leaf containing the receipt, as in `eth_getProof`.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptProof {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub receipts_root: Hash,
+    pub transaction_hash: Hash,
+    pub transaction_index: Index,
+
+    /// RLP encoded receipt, the value stored in the trie.
+    pub receipt: Bytes,
+
+    /// RLP encoded trie nodes.
+    pub proof: Vec<Bytes>,
+}
+
+impl ReceiptProof {
+    /// Builds the proof for the receipt of the transaction with the specified hash.
+    ///
+    /// Returns `None` if the transaction is not part of the block, and fails if the receipts do not produce the receipts root of the block header,
+    /// because the proof would not be verifiable against the block.
+    ///
+    /// Legacy blocks mined before receipts roots were calculated have the empty trie root in the header, so no proof can be verified against them.
+    pub fn new(block: &Block, transaction_hash: Hash) -> Result<Option<Self>, StratusError> {
+        let Some(position) = block.transactions.iter().position(|tx| tx.input.hash == transaction_hash) else {
+            return Ok(None);
+        };
+        if block.header.receipts_root == HASH_EMPTY_TRIE {
+            tracing::warn!(number = %block.number(), "receipt proof requested for legacy block without receipts root");
+            return Err(StratusError::RpcReceiptProofUnavailable { number: block.number() });
+        }
+        let receipts = block.encode_receipts();
+        let (receipts_root, proof) = ordered_trie_proof(&receipts, position);
+
+        if receipts_root != block.header.receipts_root {
+            tracing::error!(number = %block.number(), expected = %block.header.receipts_root, computed = %receipts_root, "receipts root mismatch");
+            return Err(StratusError::StorageReceiptsRootMismatch {
+                number: block.number(),
+                expected: block.header.receipts_root,
+                computed: receipts_root,
+            });
+        }
+
+        Ok(Some(Self {
+            block_number: block.number(),
+            block_hash: block.hash(),
+            receipts_root,
+            transaction_hash,
+            transaction_index: block.transactions[position].transaction_index,
+            receipt: receipts[position].clone().into(),
+            proof: proof.into_iter().map(Bytes::from).collect(),
+        }))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Trie
+// -----------------------------------------------------------------------------
+
+/// Builds the Merkle Patricia Trie where keys are the RLP encoded indexes of the values, the same used for transactions and receipts roots.
+///
+/// Returns the trie root and the proof nodes of the value at the specified position.
+fn ordered_trie_proof(values: &[Vec<u8>], position: usize) -> (Hash, Vec<Vec<u8>>) {
+    let items = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (to_nibbles(&rlp::encode(&index)), value.as_slice()))
+        .collect::<Vec<_>>();
+    let target = to_nibbles(&rlp::encode(&position));
+
+    let mut proof = Vec::new();
+    let root = encode_node(&items, 0, Some(&target), &mut proof);
+
+    // root is always referenced by hash, even when smaller than a hash
+    if proof.last() != Some(&root) {
+        proof.push(root.clone());
+    }
+    proof.reverse();
+
+    (Hash::from(keccak256(&root)), proof)
+}
+
+/// Encodes the node containing all items, whose keys share the first `depth` nibbles.
+///
+/// Nodes in the path of the target key referenced by hash are added to the proof, from the deepest to the shallowest.
+fn encode_node(items: &[(Vec<u8>, &[u8])], depth: usize, target: Option<&[u8]>, proof: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    let encoded = match items {
+        // leaf
+        [(key, value)] => {
+            let mut s = RlpStream::new_list(2);
+            s.append(&hex_prefix(&key[depth..], true));
+            s.append(&value.to_vec());
+            s.out().to_vec()
+        }
+
+        // extension or branch
+        _ => {
+            let prefix_len = common_prefix_len(items, depth);
+            if prefix_len > 0 {
+                let (key, _) = &items[0];
+                let target = target.filter(|target| target.len() >= depth + prefix_len && target[depth..depth + prefix_len] == key[depth..depth + prefix_len]);
+                let child = encode_node(items, depth + prefix_len, target, proof);
+
+                let mut s = RlpStream::new_list(2);
+                s.append(&hex_prefix(&key[depth..depth + prefix_len], false));
+                append_node_reference(&mut s, &child);
+                s.out().to_vec()
+            } else {
+                let mut s = RlpStream::new_list(17);
+                for nibble in 0..16 {
+                    let children = items
+                        .iter()
+                        .filter(|(key, _)| key.len() > depth && key[depth] == nibble)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if children.is_empty() {
+                        s.append_empty_data();
+                        continue;
+                    }
+                    let target = target.filter(|target| target.len() > depth && target[depth] == nibble);
+                    let child = encode_node(&children, depth + 1, target, proof);
+                    append_node_reference(&mut s, &child);
+                }
+                match items.iter().find(|(key, _)| key.len() == depth) {
+                    Some((_, value)) => s.append(&value.to_vec()),
+                    None => s.append_empty_data(),
+                };
+                s.out().to_vec()
+            }
+        }
+    };
+
+    // nodes smaller than a hash are embedded in their parents
+    if target.is_some() && encoded.len() >= 32 {
+        proof.push(encoded.clone());
+    }
+    encoded
+}
+
+/// Embeds the child node if it is smaller than a hash, otherwise references it by its hash.
+fn append_node_reference(s: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        s.append_raw(node, 1);
+    } else {
+        s.append(&H256::from(keccak256(node)));
+    }
+}
+
+/// Length of the key prefix shared by all items after the first `depth` nibbles.
+fn common_prefix_len(items: &[(Vec<u8>, &[u8])], depth: usize) -> usize {
+    let (first, _) = &items[0];
+    let mut len = 0;
+    while depth + len < first.len() && items.iter().all(|(key, _)| key.len() > depth + len && key[depth + len] == first[depth + len]) {
+        len += 1;
+    }
+    len
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Compact encoding of a nibbles path, flagging if it belongs to a leaf node.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    for pair in rest.chunks(2) {
+        encoded.push((pair[0] << 4) | pair[1]);
+    }
+    encoded
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethers_core::utils::keccak256;
+    use fake::Fake;
+    use fake::Faker;
+    use keccak_hasher::KeccakHasher;
+
+    use super::ordered_trie_proof;
+    use super::ReceiptProof;
+    use super::HASH_EMPTY_TRIE;
+    use crate::eth::primitives::Block;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::StratusError;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+
+    fn values(len: usize) -> Vec<Vec<u8>> {
+        (0..len)
+            .map(|i| format!("receipt-{}-with-enough-bytes-to-not-be-embedded", i).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn ordered_trie_proof_root_matches_triehash() {
+        for len in [1, 2, 16, 17, 130, 300] {
+            let values = values(len);
+            let expected: Hash = triehash::ordered_trie_root::<KeccakHasher, _>(values.clone()).into();
+            for position in [0, len / 2, len - 1] {
+                let (root, _) = ordered_trie_proof(&values, position);
+                assert_eq!(root, expected, "len={} position={}", len, position);
+            }
+        }
+    }
+
+    #[test]
+    fn ordered_trie_proof_nodes_are_linked() {
+        let values = values(300);
+        let (root, proof) = ordered_trie_proof(&values, 129);
+
+        assert_eq!(Hash::from(keccak256(&proof[0])), root);
+        for window in proof.windows(2) {
+            let child_hash = keccak256(&window[1]);
+            assert!(window[0].windows(32).any(|bytes| bytes == child_hash), "parent node must reference child");
+        }
+        assert!(proof.last().unwrap().ends_with(&values[129]));
+    }
+
+    /// Creates a block with transactions and the receipts root of its header calculated from them.
+    fn block_with_transactions(len: usize) -> Block {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        for index in 0..len {
+            let mut tx: TransactionMined = Faker.fake();
+            tx.transaction_index = (index as u64).into();
+            tx.execution.gas = 21_000u64.into();
+            tx.block_number = block.number();
+            block.transactions.push(tx);
+        }
+        block.header.receipts_root = block.calculate_receipts_root();
+        block
+    }
+
+    #[test]
+    fn receipt_proof_root_matches_header() {
+        let block = block_with_transactions(20);
+        let tx_hash = block.transactions[7].input.hash;
+
+        let proof = ReceiptProof::new(&block, tx_hash).unwrap().unwrap();
+        assert_eq!(proof.receipts_root, block.header.receipts_root);
+        assert_eq!(Hash::from(keccak256(&proof.proof[0])), block.header.receipts_root);
+        assert_eq!(proof.transaction_index, block.transactions[7].transaction_index);
+    }
+
+    #[test]
+    fn receipt_proof_of_unknown_transaction_is_none() {
+        let block = block_with_transactions(3);
+        assert!(ReceiptProof::new(&block, Faker.fake()).unwrap().is_none());
+    }
+
+    #[test]
+    fn receipt_proof_fails_when_header_root_differs() {
+        let mut block = block_with_transactions(3);
+        let computed = block.header.receipts_root;
+        block.header.receipts_root = Faker.fake();
+        let tx_hash = block.transactions[0].input.hash;
+
+        match ReceiptProof::new(&block, tx_hash) {
+            Err(StratusError::StorageReceiptsRootMismatch {
+                expected, computed: actual, ..
+            }) => {
+                assert_eq!(expected, block.header.receipts_root);
+                assert_eq!(actual, computed);
+            }
+            other => panic!("expected receipts root mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn receipt_proof_of_legacy_block_is_unavailable() {
+        let mut block = block_with_transactions(3);
+        block.header.receipts_root = HASH_EMPTY_TRIE;
+        let tx_hash = block.transactions[1].input.hash;
+
+        match ReceiptProof::new(&block, tx_hash) {
+            Err(StratusError::RpcReceiptProofUnavailable { number }) => assert_eq!(number, block.number()),
+            other => panic!("expected receipt proof unavailable, got {:?}", other),
+        }
+    }
+}
@@ -2,26 +2,46 @@ use std::fmt::Display;
 use std::io::Read;
 use std::str::FromStr;
 
+use anyhow::anyhow;
 use display_json::DebugAsJson;
 use ethereum_types::U256;
 use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Faker;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::error::BoxDynError;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
+#[cfg(feature = "postgres")]
 use sqlx::Decode;
 
 use crate::alias::RevmU256;
+use crate::ext::not;
 use crate::gen_newtype_from;
 
-#[derive(DebugAsJson, Clone, Copy, Default, Hash, Eq, PartialEq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[derive(DebugAsJson, Clone, Copy, Default, Hash, Eq, PartialEq, PartialOrd, Ord, serde::Serialize)]
 pub struct SlotIndex(pub U256);
 
 impl SlotIndex {
     pub const ZERO: SlotIndex = SlotIndex(U256::zero());
     pub const ONE: SlotIndex = SlotIndex(U256::one());
 
+    /// Parses a hexadecimal slot index with or without `0x` prefix.
+    ///
+    /// Accepts any number of leading zeros, as clients send keys both compact and zero-padded to 32 bytes or more.
+    pub fn from_hex_str(s: &str) -> anyhow::Result<Self> {
+        let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > 64 {
+            return Err(anyhow!("slot index is larger than 32 bytes: {}", s));
+        }
+        let digits = if digits.len() % 2 == 1 { format!("0{}", digits) } else { digits.to_owned() };
+        let bytes = const_hex::decode(digits)?;
+        Ok(Self(U256::from_big_endian(&bytes)))
+    }
+
     /// Converts itself to [`U256`].
     pub fn as_u256(&self) -> U256 {
         self.0
@@ -53,6 +73,19 @@ impl Dummy<Faker> for SlotIndex {
     }
 }
 
+impl<'de> serde::Deserialize<'de> for SlotIndex {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if not(deserializer.is_human_readable()) {
+            return U256::deserialize(deserializer).map(Self);
+        }
+        let value = String::deserialize(deserializer)?;
+        Self::from_hex_str(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for SlotIndex {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:#x}", self.0)
@@ -132,6 +165,7 @@ impl From<SlotIndex> for ethereum_types::U256 {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for SlotIndex {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
         let value = <[u8; 32] as Decode<sqlx::Postgres>>::decode(value)?;
@@ -139,12 +173,14 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for SlotIndex {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for SlotIndex {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("BYTEA")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for SlotIndex {
     fn encode_by_ref(&self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError> {
         <[u8; 32] as sqlx::Encode<sqlx::Postgres>>::encode((*self).into(), buf)
@@ -158,6 +194,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Postgres> for SlotIndex {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for SlotIndex {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <[u8; 32] as PgHasArrayType>::array_type_info()
@@ -180,4 +217,33 @@ mod tests {
         let hashed = SlotIndex::ZERO.to_mapping_index(address);
         assert_eq!(hashed.to_string(), "0x215be5d23550ceb1beff54fb579a765903ba2ccc85b6f79bcf9bda4e8cb86034");
     }
+
+    #[test]
+    fn slot_index_from_hex_str_with_any_length() {
+        let one = SlotIndex::from(1u64);
+        for s in [
+            "0x1",
+            "0x01",
+            "1",
+            "0x0000000000000000000000000000000000000000000000000000000000000001",
+            &format!("0x{}1", "0".repeat(100)),
+        ] {
+            assert_eq!(SlotIndex::from_hex_str(s).unwrap(), one, "{}", s);
+        }
+        assert_eq!(SlotIndex::from_hex_str("0x").unwrap(), SlotIndex::ZERO);
+        assert_eq!(SlotIndex::from_hex_str("0x0").unwrap(), SlotIndex::ZERO);
+        assert!(SlotIndex::from_hex_str(&format!("0x1{}", "0".repeat(64))).is_err());
+        assert!(SlotIndex::from_hex_str("0xzz").is_err());
+    }
+
+    #[test]
+    fn slot_index_deserialize_matches_serialize() {
+        let index = SlotIndex::from(0x1234u64);
+        let json = serde_json::to_string(&index).unwrap();
+        assert_eq!(serde_json::from_str::<SlotIndex>(&json).unwrap(), index);
+        assert_eq!(
+            serde_json::from_str::<SlotIndex>(r#""0x0000000000000000000000000000000000000000000000000000000000001234""#).unwrap(),
+            index
+        );
+    }
 }
@@ -5,8 +5,11 @@ use display_json::DebugAsJson;
 use ethereum_types::U256;
 use fake::Dummy;
 use fake::Faker;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
+#[cfg(feature = "postgres")]
 use sqlx::Decode;
 
 use crate::alias::RevmU256;
@@ -112,6 +115,7 @@ impl FromStr for SlotValue {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for SlotValue {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
         let value = <[u8; 32] as Decode<sqlx::Postgres>>::decode(value)?;
@@ -119,12 +123,14 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for SlotValue {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for SlotValue {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("BYTEA")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for SlotValue {
     fn encode_by_ref(&self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError> {
         <[u8; 32] as sqlx::Encode<sqlx::Postgres>>::encode((*self).into(), buf)
@@ -138,6 +144,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Postgres> for SlotValue {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for SlotValue {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <[u8; 32] as PgHasArrayType>::array_type_info()
@@ -1,21 +1,45 @@
-use jsonrpsee::types::error::CALL_EXECUTION_FAILED_CODE;
-use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
-use jsonrpsee::types::error::INVALID_PARAMS_CODE;
-use jsonrpsee::types::error::INVALID_REQUEST_CODE;
-use jsonrpsee::types::error::SERVER_IS_BUSY_CODE;
-use jsonrpsee::types::ErrorObjectOwned;
+use std::time::Duration;
+
+use itertools::Itertools;
+use jsonrpsee_types::error::CALL_EXECUTION_FAILED_CODE;
+use jsonrpsee_types::error::INTERNAL_ERROR_CODE;
+use jsonrpsee_types::error::INVALID_PARAMS_CODE;
+use jsonrpsee_types::error::INVALID_REQUEST_CODE;
+use jsonrpsee_types::error::SERVER_IS_BUSY_CODE;
+use jsonrpsee_types::ErrorObjectOwned;
 use strum::EnumProperty;
 
 use crate::alias::JsonValue;
-use crate::eth::executor::EvmInput;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BannedOpcode;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::EvmInput;
 use crate::eth::primitives::ExecutionConflicts;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Wei;
 use crate::ext::to_json_value;
 
+/// How callers should handle an error, independently of the component that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum ErrorClass {
+    /// Request is invalid or rejected by the current state. Retrying the same request fails again.
+    #[strum(to_string = "user")]
+    User,
+
+    /// A component or dependency is temporarily unavailable. Retrying later may succeed.
+    #[strum(to_string = "transient")]
+    Transient,
+
+    /// An invariant was violated or an unexpected failure happened. Retrying is unsafe and the node may need to stop.
+    #[strum(to_string = "fatal")]
+    Fatal,
+}
+
 /// Valid error catogories are:
 /// * client_request: request is invalid.
 /// * client_state:   request is valid, specific client rules rejects it.
@@ -31,10 +55,30 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcBlockFilterInvalid { filter: BlockFilter },
 
+    #[error("No block is {filter} yet.")]
+    #[strum(props(kind = "server_state"))]
+    RpcBlockFinalityUnavailable { filter: BlockFilter },
+
     #[error("Denied because will fetch data from {actual} blocks, but the max allowed is {max}.")]
     #[strum(props(kind = "client_request"))]
     RpcBlockRangeInvalid { actual: u64, max: u64 },
 
+    #[error("Receipt proof is unavailable for block {number} because it was mined before receipts roots were calculated.")]
+    #[strum(props(kind = "server_state"))]
+    RpcReceiptProofUnavailable { number: BlockNumber },
+
+    #[error("Denied because requested {actual} slots, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcStorageRangeInvalid { actual: usize, max: usize },
+
+    #[error("Denied because requested {actual} results, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcResultLimitExceeded { actual: usize, max: usize },
+
+    #[error("Denied because requested {actual} calls, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcCallsLimitExceeded { actual: usize, max: usize },
+
     #[error("Denied because client did not identify itself.")]
     #[strum(props(kind = "client_request"))]
     RpcClientMissing,
@@ -47,6 +91,10 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcParameterMissing { rust_type: &'static str },
 
+    #[error("Request timed out after {timeout:?}.")]
+    #[strum(props(kind = "server_state"))]
+    RpcRequestTimeout { timeout: Duration },
+
     #[error("Invalid subscription event: {event}")]
     #[strum(props(kind = "client_request"))]
     RpcSubscriptionInvalid { event: String },
@@ -55,6 +103,26 @@ pub enum StratusError {
     #[strum(props(kind = "client_state"))]
     RpcSubscriptionLimit { max: u32 },
 
+    #[error("Audit log is disabled.")]
+    #[strum(props(kind = "server_state"))]
+    RpcAuditLogDisabled,
+
+    #[error("Gas analytics is disabled.")]
+    #[strum(props(kind = "server_state"))]
+    RpcGasAnalyticsDisabled,
+
+    #[error("State stats are disabled.")]
+    #[strum(props(kind = "server_state"))]
+    RpcStateStatsDisabled,
+
+    #[error("State stats are not calculated yet.")]
+    #[strum(props(kind = "server_state"))]
+    RpcStateStatsNotReady,
+
+    #[error("Token transfers are not indexed.")]
+    #[strum(props(kind = "server_state"))]
+    RpcTokenTransfersDisabled,
+
     #[error("Transaction processing is temporarily disabled.")]
     #[strum(props(kind = "server_state"))]
     RpcTransactionDisabled,
@@ -67,6 +135,34 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcTransactionInvalid { decode_error: String },
 
+    #[error("Transaction chain id {actual} does not match the node chain id {expected}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcTransactionChainIdMismatch { expected: ChainId, actual: ChainId },
+
+    #[error("Transaction has {size} bytes, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcTransactionTooLarge { size: usize, max: usize },
+
+    #[error("Transaction signature is invalid.")]
+    #[strum(props(kind = "client_request"))]
+    RpcTransactionSignatureInvalid { reason: String },
+
+    #[error("Transaction gas limit {gas_limit} is below the intrinsic gas of {intrinsic}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcTransactionIntrinsicGasTooLow { gas_limit: Gas, intrinsic: Gas },
+
+    #[error("Sender balance {balance} is not enough to pay for the transaction that requires {required}.")]
+    #[strum(props(kind = "client_state"))]
+    RpcTransactionInsufficientBalance { balance: Wei, required: Wei },
+
+    #[error("Only replay-protected (EIP-155) transactions are allowed.")]
+    #[strum(props(kind = "client_request"))]
+    RpcTransactionUnprotected,
+
+    #[error("Transaction from {signer} denied by {policy} policy.")]
+    #[strum(props(kind = "client_state"))]
+    RpcTransactionDeniedByPolicy { policy: String, signer: Address },
+
     // -------------------------------------------------------------------------
     // Transaction
     // -------------------------------------------------------------------------
@@ -78,6 +174,26 @@ pub enum StratusError {
     #[strum(props(kind = "execution"))]
     TransactionConflict(Box<ExecutionConflicts>),
 
+    #[error("Transaction used {gas} gas, but the block gas limit is {limit}.")]
+    #[strum(props(kind = "execution"))]
+    TransactionGasAboveBlockLimit { gas: u64, limit: u64 },
+
+    #[error("Pending block already used {pending} gas and transaction does not fit in the block gas limit of {limit}.")]
+    #[strum(props(kind = "server_state"))]
+    TransactionBlockGasLimitReached { pending: u64, limit: u64 },
+
+    #[error("Transaction data has {size} bytes, but the block size limit is {limit}.")]
+    #[strum(props(kind = "execution"))]
+    TransactionSizeAboveBlockLimit { size: usize, limit: usize },
+
+    #[error("Pending block already has the max of {limit} transactions.")]
+    #[strum(props(kind = "server_state"))]
+    TransactionBlockTransactionsLimitReached { limit: usize },
+
+    #[error("Pending block already has {pending} bytes of transactions data and transaction does not fit in the block size limit of {limit}.")]
+    #[strum(props(kind = "server_state"))]
+    TransactionBlockSizeLimitReached { pending: usize, limit: usize },
+
     #[error("Transaction nonce {transaction} does not match account nonce {account}.")]
     #[strum(props(kind = "execution"))]
     TransactionNonce { transaction: Nonce, account: Nonce },
@@ -98,6 +214,10 @@ pub enum StratusError {
     #[strum(props(kind = "execution"))]
     TransactionReverted { output: Bytes },
 
+    #[error("Contract deployment data has {size} bytes, but the max allowed is {max}.")]
+    #[strum(props(kind = "execution"))]
+    TransactionInitcodeSizeExceeded { size: usize, max: usize },
+
     #[error("Transaction from zero address is not allowed.")]
     #[strum(props(kind = "execution"))]
     TransactionFromZeroAddress,
@@ -106,6 +226,25 @@ pub enum StratusError {
     #[strum(props(kind = "execution"))]
     TransactionEvmInputMismatch { expected: Box<EvmInput>, actual: Box<EvmInput> },
 
+    // -------------------------------------------------------------------------
+    // User operation
+    // -------------------------------------------------------------------------
+    #[error("Entry point {entry_point} is not supported.")]
+    #[strum(props(kind = "client_request"))]
+    UserOperationEntryPointNotSupported { entry_point: Address },
+
+    #[error("User operation failed validation.")]
+    #[strum(props(kind = "execution"))]
+    UserOperationValidationFailed { output: Bytes },
+
+    #[error("User operation validation executed banned opcodes.")]
+    #[strum(props(kind = "execution"))]
+    UserOperationBannedOpcodes { opcodes: Vec<BannedOpcode> },
+
+    #[error("Denied because user operation pool reached the maximum of {max} operations.")]
+    #[strum(props(kind = "server_state"))]
+    UserOperationPoolFull { max: usize },
+
     // -------------------------------------------------------------------------
     // Storage
     // -------------------------------------------------------------------------
@@ -121,6 +260,22 @@ pub enum StratusError {
     #[strum(props(kind = "internal"))]
     StoragePendingNumberConflict { new: BlockNumber, pending: BlockNumber },
 
+    #[error("Storage is temporarily unavailable: {reason}.")]
+    #[strum(props(kind = "internal"))]
+    StorageUnavailable { reason: String },
+
+    #[error("Storage invariant violated: {reason}.")]
+    #[strum(props(kind = "internal"))]
+    StorageInvariantViolated { reason: String },
+
+    #[error("Storage is read-only and does not accept {operation}.")]
+    #[strum(props(kind = "internal"))]
+    StorageReadOnly { operation: &'static str },
+
+    #[error("Receipts root mismatch in block {number}: header has {expected}, but receipts produce {computed}.")]
+    #[strum(props(kind = "internal"))]
+    StorageReceiptsRootMismatch { number: BlockNumber, expected: Hash, computed: Hash },
+
     #[error("There are ({pending_txs}) pending transactions.")]
     #[strum(props(kind = "internal"))]
     PendingTransactionsExist { pending_txs: usize },
@@ -132,6 +287,15 @@ pub enum StratusError {
     #[strum(props(kind = "internal"))]
     MinerModeParamInvalid,
 
+    #[error("Block {number} was assembled with invalid {field}: expected {expected}, found {found}.")]
+    #[strum(props(kind = "internal"))]
+    MinerBlockAssemblyInvalid {
+        number: BlockNumber,
+        field: &'static str,
+        expected: u64,
+        found: u64,
+    },
+
     // -------------------------------------------------------------------------
     // Importer
     // -------------------------------------------------------------------------
@@ -151,6 +315,10 @@ pub enum StratusError {
     #[strum(props(kind = "internal"))]
     ImporterInitError,
 
+    #[error("Failed to import external block {number}: {reason}.")]
+    #[strum(props(kind = "internal"))]
+    ImporterBlockFailed { number: BlockNumber, reason: String },
+
     // -------------------------------------------------------------------------
     // Consensus
     // -------------------------------------------------------------------------
@@ -192,19 +360,71 @@ pub enum StratusError {
     #[strum(props(kind = "server_state"))]
     StratusNotFollower,
 
+    #[error("Stratus node is an archive and cannot change its node or miner mode.")]
+    #[strum(props(kind = "server_state"))]
+    StratusArchive,
+
     #[error("Stratus node is already in the process of changing mode.")]
     #[strum(props(kind = "server_state"))]
     ModeChangeInProgress,
 }
 
 impl StratusError {
-    /// Checks if the error is an unexpected/internal error.
-    pub fn is_internal(&self) -> bool {
-        self.rpc_code() == INTERNAL_ERROR_CODE
+    /// Classifies the error to decide if the operation should be retried, reported to the user or stop the node.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            // transient failures inside other kinds
+            Self::TransactionConflict(_)
+            | Self::TransactionForwardToLeaderFailed
+            | Self::TransactionEvmInputMismatch { .. }
+            | Self::StorageUnavailable { .. }
+            | Self::ConsensusUnavailable
+            | Self::UnexpectedChannelClosed { .. } => ErrorClass::Transient,
+
+            // server state that does not change without reconfiguring the node
+            Self::RpcAuditLogDisabled
+            | Self::RpcGasAnalyticsDisabled
+            | Self::RpcStateStatsDisabled
+            | Self::RpcTokenTransfersDisabled
+            | Self::StratusNotFollower
+            | Self::StratusArchive => ErrorClass::User,
+
+            _ => match self.get_str("kind") {
+                Some("client_request" | "client_state" | "execution") => ErrorClass::User,
+                Some("server_state") => ErrorClass::Transient,
+                _ => ErrorClass::Fatal,
+            },
+        }
+    }
+
+    /// Checks if the operation that failed may succeed if retried.
+    pub fn is_transient(&self) -> bool {
+        self.class() == ErrorClass::Transient
+    }
+
+    /// Checks if the error is an invariant violation or an unexpected failure that must not be retried.
+    pub fn is_fatal(&self) -> bool {
+        self.class() == ErrorClass::Fatal
+    }
+
+    /// Checks if the error is caused by the pending block being full, so the transaction may fit in the next block.
+    pub fn is_block_limit_reached(&self) -> bool {
+        matches!(
+            self,
+            Self::TransactionBlockGasLimitReached { .. }
+                | Self::TransactionBlockTransactionsLimitReached { .. }
+                | Self::TransactionBlockSizeLimitReached { .. }
+        )
     }
 
     /// Error code to be used in JSON-RPC response.
+    ///
+    /// The code is derived from the error kind, so a server state rejection like a disabled RPC keeps its own code instead of being
+    /// reported like a retriable failure. Fatal errors are always reported as internal.
     pub fn rpc_code(&self) -> i32 {
+        if self.is_fatal() {
+            return INTERNAL_ERROR_CODE;
+        }
         match self.get_str("kind") {
             Some("client_request") => INVALID_PARAMS_CODE,
             Some("client_state") => INVALID_REQUEST_CODE,
@@ -232,13 +452,20 @@ impl StratusError {
         match self {
             // RPC
             Self::RpcBlockFilterInvalid { filter } => to_json_value(filter),
+            Self::RpcBlockFinalityUnavailable { filter } => to_json_value(filter),
             Self::RpcParameterInvalid { decode_error, .. } => to_json_value(decode_error),
 
             // Transaction
             Self::RpcTransactionInvalid { decode_error } => to_json_value(decode_error),
+            Self::RpcTransactionSignatureInvalid { reason } => to_json_value(reason),
+            Self::TransactionConflict(conflicts) => to_json_value(conflicts.iter().collect_vec()),
             Self::TransactionEvmFailed(e) => JsonValue::String(e.to_string()),
             Self::TransactionReverted { output } => to_json_value(output),
 
+            // User operation
+            Self::UserOperationValidationFailed { output } => to_json_value(output),
+            Self::UserOperationBannedOpcodes { opcodes } => to_json_value(opcodes),
+
             // Unexpected
             Self::Unexpected(e) => JsonValue::String(e.to_string()),
 
@@ -279,3 +506,73 @@ impl From<StratusError> for ErrorObjectOwned {
         Self::owned(value.rpc_code(), value.rpc_message(), Some(data))
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::eth::primitives::ExecutionConflictsBuilder;
+
+    #[test]
+    fn test_error_class() {
+        // user errors are not retried
+        assert_eq!(StratusError::RpcClientMissing.class(), ErrorClass::User);
+        assert_eq!(StratusError::RpcTokenTransfersDisabled.class(), ErrorClass::User);
+        assert_eq!(StratusError::TransactionFromZeroAddress.class(), ErrorClass::User);
+        assert_eq!(StratusError::StratusArchive.class(), ErrorClass::User);
+
+        // transient errors can be retried
+        assert!(StratusError::StratusNotReady.is_transient());
+        assert!(StratusError::TransactionForwardToLeaderFailed.is_transient());
+        assert!(StratusError::StorageUnavailable { reason: "timeout".into() }.is_transient());
+
+        // fatal errors stop the operation
+        assert!(StratusError::StorageInvariantViolated { reason: "gap".into() }.is_fatal());
+        assert!(StratusError::StorageBlockConflict { number: BlockNumber::ZERO }.is_fatal());
+        assert!(StratusError::StorageReadOnly { operation: "save_block" }.is_fatal());
+        assert!(StratusError::ImporterBlockFailed {
+            number: BlockNumber::ZERO,
+            reason: "diverged".into()
+        }
+        .is_fatal());
+        assert!(StratusError::Unexpected(anyhow!("unexpected")).is_fatal());
+    }
+
+    #[test]
+    fn test_rpc_code_follows_kind() {
+        // user errors keep the code of their kind
+        assert_eq!(StratusError::RpcClientMissing.rpc_code(), INVALID_PARAMS_CODE);
+        assert_eq!(StratusError::TransactionFromZeroAddress.rpc_code(), CALL_EXECUTION_FAILED_CODE);
+        assert_eq!(StratusError::StratusNotFollower.rpc_code(), SERVER_IS_BUSY_CODE);
+        assert_eq!(StratusError::RpcTransactionDisabled.rpc_code(), SERVER_IS_BUSY_CODE);
+
+        // transient errors keep the code of their kind too, the class only decides retries
+        assert_eq!(StratusError::ConsensusUnavailable.rpc_code(), INTERNAL_ERROR_CODE);
+        assert_eq!(StratusError::TransactionForwardToLeaderFailed.rpc_code(), CALL_EXECUTION_FAILED_CODE);
+        assert_eq!(StratusError::StratusNotReady.rpc_code(), SERVER_IS_BUSY_CODE);
+
+        // fatal errors are reported as internal
+        assert_eq!(StratusError::StorageInvariantViolated { reason: "gap".into() }.rpc_code(), INTERNAL_ERROR_CODE);
+        assert_eq!(StratusError::ImporterAlreadyShutdown.rpc_code(), INTERNAL_ERROR_CODE);
+    }
+
+    #[test]
+    fn test_rpc_data_of_conflicts() {
+        let address = Address::new([1; 20]);
+        let mut conflicts = ExecutionConflictsBuilder::default();
+        conflicts.add_balance(address, Wei::from(10u64), Wei::from(20u64));
+        let error = StratusError::TransactionConflict(Box::new(conflicts.build().unwrap()));
+
+        // each conflict is tagged with its kind and has the value read by the execution and the value in the pending block
+        let data = error.rpc_data();
+        assert_eq!(data.as_array().unwrap().len(), 1);
+        assert_eq!(data[0]["kind"], "balance");
+        assert_eq!(data[0]["address"], to_json_value(address));
+        assert_eq!(data[0]["expected"], to_json_value(Wei::from(10u64)));
+        assert_eq!(data[0]["actual"], to_json_value(Wei::from(20u64)));
+    }
+}
@@ -0,0 +1,243 @@
+use display_json::DebugAsJson;
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethers_core::utils::keccak256;
+use fake::Dummy;
+use fake::Faker;
+use once_cell::sync::Lazy;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::Log;
+use crate::eth::primitives::LogMined;
+use crate::gen_newtype_from;
+
+/// Topic of the ERC-20 and ERC-721 `Transfer` event. ERC-721 indexes the token ID, so it is emitted with one more topic.
+static TRANSFER_TOPIC: Lazy<H256> = Lazy::new(|| H256::from(keccak256("Transfer(address,address,uint256)")));
+
+/// Topic of the ERC-1155 `TransferSingle` event.
+static TRANSFER_SINGLE_TOPIC: Lazy<H256> = Lazy::new(|| H256::from(keccak256("TransferSingle(address,address,address,uint256,uint256)")));
+
+/// Token standard that defines the decoded transfer event.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub enum TokenStandard {
+    #[serde(rename = "erc20")]
+    Erc20,
+
+    #[serde(rename = "erc721")]
+    Erc721,
+
+    #[serde(rename = "erc1155")]
+    Erc1155,
+}
+
+/// Token amount or token ID, without decimals.
+#[derive(DebugAsJson, derive_more::Display, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TokenAmount(pub U256);
+
+impl Dummy<Faker> for TokenAmount {
+    fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(_: &Faker, rng: &mut R) -> Self {
+        rng.next_u64().into()
+    }
+}
+
+gen_newtype_from!(self = TokenAmount, other = u64, U256);
+
+impl From<[u64; 4]> for TokenAmount {
+    fn from(value: [u64; 4]) -> Self {
+        Self(U256(value))
+    }
+}
+
+impl From<TokenAmount> for [u64; 4] {
+    fn from(value: TokenAmount) -> Self {
+        value.0 .0
+    }
+}
+
+/// Transfer decoded from a standard token event.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct TokenTransfer {
+    pub standard: TokenStandard,
+
+    /// Contract that emitted the event.
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+
+    /// Transferred token of ERC-721 and ERC-1155 transfers.
+    pub token_id: Option<TokenAmount>,
+
+    /// Transferred amount. Always one for ERC-721 transfers.
+    pub value: TokenAmount,
+}
+
+impl TokenTransfer {
+    /// Decodes a standard transfer event. Returns `None` if the log is not one.
+    pub fn decode(log: &Log) -> Option<Self> {
+        let topic0 = log.topic0?.0;
+        let data = &log.data.0;
+
+        if topic0 == *TRANSFER_TOPIC {
+            let (from, to) = (log.topic1?.into(), log.topic2?.into());
+            return match log.topic3 {
+                // ERC-20: Transfer(address indexed from, address indexed to, uint256 value)
+                None if data.len() == 32 => Some(Self {
+                    standard: TokenStandard::Erc20,
+                    token: log.address,
+                    from,
+                    to,
+                    token_id: None,
+                    value: U256::from_big_endian(data).into(),
+                }),
+                // ERC-721: Transfer(address indexed from, address indexed to, uint256 indexed tokenId)
+                Some(token_id) if data.is_empty() => Some(Self {
+                    standard: TokenStandard::Erc721,
+                    token: log.address,
+                    from,
+                    to,
+                    token_id: Some(U256::from_big_endian(token_id.0.as_bytes()).into()),
+                    value: 1u64.into(),
+                }),
+                _ => None,
+            };
+        }
+
+        // ERC-1155: TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+        if topic0 == *TRANSFER_SINGLE_TOPIC && data.len() == 64 {
+            return Some(Self {
+                standard: TokenStandard::Erc1155,
+                token: log.address,
+                from: log.topic2?.into(),
+                to: log.topic3?.into(),
+                token_id: Some(U256::from_big_endian(&data[..32]).into()),
+                value: U256::from_big_endian(&data[32..]).into(),
+            });
+        }
+
+        None
+    }
+}
+
+/// Selects token transfers sent or received by an account, of a token, or both.
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenTransferFilter {
+    #[serde(default)]
+    pub account: Option<Address>,
+
+    #[serde(default)]
+    pub token: Option<Address>,
+}
+
+/// Token transfer decoded from a log that was added to a block.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct TokenTransferMined {
+    /// Transfer decoded from the log.
+    pub transfer: TokenTransfer,
+
+    /// Hash of the transaction that emitted the log.
+    pub transaction_hash: Hash,
+
+    /// Position of the transaction that emitted the log inside the block.
+    pub transaction_index: Index,
+
+    /// Position of the log inside the block.
+    pub log_index: Index,
+
+    /// Block number where the log was mined.
+    pub block_number: BlockNumber,
+}
+
+impl TokenTransferMined {
+    /// Decodes a standard transfer event. Returns `None` if the log is not one.
+    pub fn decode(log: &LogMined) -> Option<Self> {
+        Some(Self {
+            transfer: TokenTransfer::decode(&log.log)?,
+            transaction_hash: log.transaction_hash,
+            transaction_index: log.transaction_index,
+            log_index: log.log_index,
+            block_number: log.block_number,
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::LogTopic;
+
+    fn topic_address(address: Address) -> Option<LogTopic> {
+        Some(address.into())
+    }
+
+    fn topic_u64(value: u64) -> Option<LogTopic> {
+        Some(LogTopic::new(H256::from_low_u64_be(value)))
+    }
+
+    fn word(value: u64) -> Vec<u8> {
+        H256::from_low_u64_be(value).as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_token_transfer_decode() {
+        let (token, from, to) = (Address::new([1; 20]), Address::new([2; 20]), Address::new([3; 20]));
+        let transfer_topic = Some(LogTopic::new(*TRANSFER_TOPIC));
+
+        // erc20
+        let log = Log {
+            address: token,
+            topic0: transfer_topic,
+            topic1: topic_address(from),
+            topic2: topic_address(to),
+            topic3: None,
+            data: Bytes::from(word(100)),
+        };
+        let transfer = TokenTransfer::decode(&log).unwrap();
+        assert_eq!(transfer.standard, TokenStandard::Erc20);
+        assert_eq!((transfer.token, transfer.from, transfer.to), (token, from, to));
+        assert_eq!((transfer.token_id, transfer.value), (None, 100u64.into()));
+
+        // erc721
+        let log = Log {
+            topic3: topic_u64(7),
+            data: Bytes::default(),
+            ..log
+        };
+        let transfer = TokenTransfer::decode(&log).unwrap();
+        assert_eq!(transfer.standard, TokenStandard::Erc721);
+        assert_eq!((transfer.token_id, transfer.value), (Some(7u64.into()), 1u64.into()));
+
+        // erc1155
+        let log = Log {
+            address: token,
+            topic0: Some(LogTopic::new(*TRANSFER_SINGLE_TOPIC)),
+            topic1: topic_address(Address::new([4; 20])),
+            topic2: topic_address(from),
+            topic3: topic_address(to),
+            data: Bytes::from([word(7), word(100)].concat()),
+        };
+        let transfer = TokenTransfer::decode(&log).unwrap();
+        assert_eq!(transfer.standard, TokenStandard::Erc1155);
+        assert_eq!((transfer.from, transfer.to), (from, to));
+        assert_eq!((transfer.token_id, transfer.value), (Some(7u64.into()), 100u64.into()));
+
+        // other events and malformed data
+        assert!(TokenTransfer::decode(&Log {
+            topic0: topic_u64(1),
+            ..log.clone()
+        })
+        .is_none());
+        assert!(TokenTransfer::decode(&Log {
+            data: Bytes::from(word(7)),
+            ..log
+        })
+        .is_none());
+    }
+}
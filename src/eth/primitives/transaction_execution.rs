@@ -1,9 +1,9 @@
 use display_json::DebugAsJson;
 
-use crate::eth::executor::EvmExecutionResult;
-use crate::eth::executor::EvmInput;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
+use crate::eth::primitives::EvmExecutionResult;
+use crate::eth::primitives::EvmInput;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::Hash;
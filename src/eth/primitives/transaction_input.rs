@@ -7,7 +7,12 @@ use ethers_core::types::TransactionRequest;
 use fake::Dummy;
 use fake::Fake;
 use fake::Faker;
+use once_cell::sync::Lazy;
+use quick_cache::sync::Cache;
+use quick_cache::sync::DefaultLifecycle;
+use quick_cache::UnitWeighter;
 use rlp::Decodable;
+use rustc_hash::FxBuildHasher;
 use serde::Deserialize;
 
 use crate::alias::EthersTransaction;
@@ -23,6 +28,24 @@ use crate::eth::primitives::Wei;
 use crate::ext::OptionExt;
 use crate::log_and_err;
 
+/// Max number of recovered signers kept in [`SIGNER_CACHE`].
+const SIGNER_CACHE_CAPACITY: usize = 100_000;
+
+/// Signers recovered from transactions decoded from RLP, keyed by transaction hash.
+///
+/// A transaction is decoded and its signer recovered when it is received, when it is traced and when it is imported, so caching avoids
+/// paying the signature recovery cost more than once. The hash of a decoded transaction is computed from its signed data, so the same hash
+/// always recovers the same signer.
+static SIGNER_CACHE: Lazy<Cache<Hash, Address, UnitWeighter, FxBuildHasher>> = Lazy::new(|| {
+    Cache::with(
+        SIGNER_CACHE_CAPACITY,
+        SIGNER_CACHE_CAPACITY as u64,
+        UnitWeighter,
+        FxBuildHasher,
+        DefaultLifecycle::default(),
+    )
+});
+
 #[derive(DebugAsJson, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct TransactionInput {
     /// This is needed for relaying transactions correctly, a transaction sent as Legacy should
@@ -75,7 +98,10 @@ impl Dummy<Faker> for TransactionInput {
 impl Decodable for TransactionInput {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
         let ethers_transaction = EthersTransaction::decode(rlp)?;
-        match Self::try_from(ethers_transaction) {
+        let Ok(signer) = Self::recover_decoded_signer(&ethers_transaction) else {
+            return Err(rlp::DecoderError::Custom("decoding error"));
+        };
+        match Self::from_ethers_with_signer(ethers_transaction, signer) {
             Ok(transaction) => Ok(transaction),
             Err(_) => Err(rlp::DecoderError::Custom("decoding error")),
         }
@@ -114,25 +140,57 @@ fn try_from_ethers_transaction(value: EthersTransaction, compute_signer: bool) -
         false => value.from.into(),
     };
 
-    Ok(TransactionInput {
-        tx_type: value.transaction_type,
-        chain_id: match value.chain_id {
-            Some(chain_id) => Some(chain_id.try_into()?),
-            None => None,
-        },
-        hash: value.hash.into(),
-        nonce: value.nonce.try_into()?,
-        signer,
-        from: Address::new(value.from.into()),
-        to: value.to.map_into(),
-        value: value.value.into(),
-        input: value.input.clone().into(),
-        gas_limit: value.gas.try_into()?,
-        gas_price: value.gas_price.unwrap_or_default().into(),
-        v: value.v,
-        r: value.r,
-        s: value.s,
-    })
+    TransactionInput::from_ethers_with_signer(value, signer)
+}
+
+impl TransactionInput {
+    /// Recovers the signer of a transaction decoded from RLP, reusing the signer recovered from a previous decoding of the same transaction.
+    ///
+    /// Must only be used with transactions decoded from RLP, because the cache trusts the transaction hash.
+    pub fn recover_decoded_signer(value: &EthersTransaction) -> anyhow::Result<Address> {
+        Self::recover_signer_cached(value.hash.into(), || match value.recover_from() {
+            Ok(signer) => Ok(signer.into()),
+            Err(e) => {
+                tracing::warn!(reason = ?e, "failed to recover transaction signer");
+                Err(anyhow!("Transaction signer cannot be recovered. Check the transaction signature is valid."))
+            }
+        })
+    }
+
+    /// Returns the signer cached for the transaction hash, or recovers it with `recover` and caches it.
+    ///
+    /// The hash must be computed from the signed transaction data, so the same hash always recovers the same signer.
+    pub fn recover_signer_cached(tx_hash: Hash, recover: impl FnOnce() -> anyhow::Result<Address>) -> anyhow::Result<Address> {
+        if let Some(signer) = SIGNER_CACHE.get(&tx_hash) {
+            return Ok(signer);
+        }
+        let signer = recover()?;
+        SIGNER_CACHE.insert(tx_hash, signer);
+        Ok(signer)
+    }
+
+    /// Creates from a decoded transaction whose signer was already recovered.
+    pub fn from_ethers_with_signer(value: EthersTransaction, signer: Address) -> anyhow::Result<Self> {
+        Ok(TransactionInput {
+            tx_type: value.transaction_type,
+            chain_id: match value.chain_id {
+                Some(chain_id) => Some(chain_id.try_into()?),
+                None => None,
+            },
+            hash: value.hash.into(),
+            nonce: value.nonce.try_into()?,
+            signer,
+            from: Address::new(value.from.into()),
+            to: value.to.map_into(),
+            value: value.value.into(),
+            input: value.input.clone().into(),
+            gas_limit: value.gas.try_into()?,
+            gas_price: value.gas_price.unwrap_or_default().into(),
+            v: value.v,
+            r: value.r,
+            s: value.s,
+        })
+    }
 }
 
 // -----------------------------------------------------------------------------
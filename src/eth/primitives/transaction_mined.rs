@@ -2,6 +2,7 @@ use std::hash::Hash as HashTrait;
 
 use display_json::DebugAsJson;
 use itertools::Itertools;
+use rlp::RlpStream;
 
 use crate::alias::EthersReceipt;
 use crate::alias::EthersTransaction;
@@ -10,10 +11,14 @@ use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalTransaction;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::GasMode;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Index;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::Wei;
+use crate::ext::not;
 use crate::ext::OptionExt;
 use crate::if_else;
 
@@ -32,6 +37,9 @@ pub struct TransactionMined {
     /// Position of the transaction inside the block.
     pub transaction_index: Index,
 
+    /// Gas used by the transactions of the block up to and including this one.
+    pub cumulative_gas_used: Gas,
+
     /// Block number where the transaction was mined.
     pub block_number: BlockNumber,
 
@@ -61,13 +69,14 @@ impl TransactionMined {
     /// Creates a new mined transaction from an external mined transaction that was re-executed locally.
     ///
     /// TODO: this kind of conversion should be infallibe.
-    pub fn from_external(tx: ExternalTransaction, receipt: ExternalReceipt, execution: EvmExecution) -> anyhow::Result<Self> {
+    pub fn from_external(tx: ExternalTransaction, receipt: ExternalReceipt, execution: EvmExecution, cumulative_gas_used: Gas) -> anyhow::Result<Self> {
         Ok(Self {
             input: tx.clone().try_into()?,
             execution,
             block_number: receipt.block_number(),
             block_hash: receipt.block_hash(),
             transaction_index: receipt.transaction_index.into(),
+            cumulative_gas_used,
             logs: receipt.0.logs.into_iter().map(LogMined::try_from).collect::<Result<Vec<LogMined>, _>>()?,
         })
     }
@@ -77,6 +86,68 @@ impl TransactionMined {
         self.execution.is_success()
     }
 
+    /// Encodes the transaction receipt as it is stored in the block receipts trie.
+    pub fn encode_receipt(&self, cumulative_gas_used: u64) -> Vec<u8> {
+        let mut s = RlpStream::new_list(4);
+        s.append(&u8::from(self.is_success()));
+        s.append(&cumulative_gas_used);
+        s.append(&self.compute_bloom().0);
+        s.begin_list(self.logs.len());
+        for log_mined in &self.logs {
+            let topics = log_mined.log.topics_non_empty();
+            s.begin_list(3);
+            s.append(&log_mined.log.address.0);
+            s.begin_list(topics.len());
+            for topic in topics {
+                s.append(&topic.0);
+            }
+            s.append(&log_mined.log.data.to_vec());
+        }
+        let encoded = s.out().to_vec();
+
+        // typed transactions receipts are prefixed with the transaction type
+        match self.input.tx_type {
+            Some(tx_type) if not(tx_type.is_zero()) => [vec![tx_type.as_u64() as u8], encoded].concat(),
+            _ => encoded,
+        }
+    }
+
+    /// Gas price paid by the sender for each unit of gas used, which is zero when gas is not charged.
+    pub fn effective_gas_price(&self, gas_mode: GasMode) -> Wei {
+        if_else!(gas_mode.is_charged(), self.input.gas_price, Wei::ZERO)
+    }
+
+    /// Converts itself to a JSON-RPC receipt.
+    pub fn to_ethers_receipt(self, gas_mode: GasMode) -> EthersReceipt {
+        let logs_bloom = self.compute_bloom().into();
+        let effective_gas_price = self.effective_gas_price(gas_mode);
+        EthersReceipt {
+            // receipt specific
+            status: Some(if_else!(self.is_success(), 1, 0).into()),
+            contract_address: self.execution.contract_address().map_into(),
+            gas_used: Some(self.execution.gas.into()),
+            cumulative_gas_used: self.cumulative_gas_used.into(),
+            effective_gas_price: Some(effective_gas_price.into()),
+
+            // transaction
+            transaction_hash: self.input.hash.into(),
+            transaction_type: Some(self.input.tx_type.unwrap_or_default()),
+            from: self.input.signer.into(),
+            to: self.input.to.map_into(),
+
+            // block
+            block_hash: Some(self.block_hash.into()),
+            block_number: Some(self.block_number.into()),
+            transaction_index: self.transaction_index.into(),
+
+            // logs
+            logs: self.logs.into_iter().map_into().collect(),
+            logs_bloom, // TODO: save this to the database instead of computing it every time (could also be useful for eth_getLogs)
+
+            ..Default::default()
+        }
+    }
+
     fn compute_bloom(&self) -> LogsBloom {
         let mut bloom = LogsBloom::default();
         for log_mined in self.logs.iter() {
@@ -114,35 +185,6 @@ impl From<TransactionMined> for EthersTransaction {
     }
 }
 
-impl From<TransactionMined> for EthersReceipt {
-    fn from(value: TransactionMined) -> Self {
-        let logs_bloom = value.compute_bloom().into();
-        Self {
-            // receipt specific
-            status: Some(if_else!(value.is_success(), 1, 0).into()),
-            contract_address: value.execution.contract_address().map_into(),
-            gas_used: Some(value.execution.gas.into()),
-
-            // transaction
-            transaction_hash: value.input.hash.into(),
-            from: value.input.signer.into(),
-            to: value.input.to.map_into(),
-
-            // block
-            block_hash: Some(value.block_hash.into()),
-            block_number: Some(value.block_number.into()),
-            transaction_index: value.transaction_index.into(),
-
-            // logs
-            logs: value.logs.into_iter().map_into().collect(),
-            logs_bloom, // TODO: save this to the database instead of computing it every time (could also be useful for eth_getLogs)
-
-            // TODO: there are more fields to populate here
-            ..Default::default()
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use fake::Fake;
@@ -157,6 +199,7 @@ mod tests {
             execution: Faker.fake(),
             logs: vec![],
             transaction_index: transaction_index.into(),
+            cumulative_gas_used: Faker.fake(),
             block_number: block_number.into(),
             block_hash: Hash::default(),
         }
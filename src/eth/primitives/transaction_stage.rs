@@ -1,6 +1,6 @@
-use crate::alias::EthersReceipt;
 use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
+use crate::eth::primitives::GasMode;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionMined;
 use crate::ext::to_json_value;
@@ -39,13 +39,10 @@ impl TransactionStage {
     }
 
     /// Serializes itself to JSON-RPC receipt format.
-    pub fn to_json_rpc_receipt(self) -> JsonValue {
+    pub fn to_json_rpc_receipt(self, gas_mode: GasMode) -> JsonValue {
         match self {
             TransactionStage::Executed(_) => JsonValue::Null,
-            TransactionStage::Mined(tx) => {
-                let json_rpc_format: EthersReceipt = tx.into();
-                to_json_value(json_rpc_format)
-            }
+            TransactionStage::Mined(tx) => to_json_value(tx.to_ethers_receipt(gas_mode)),
         }
     }
 }
@@ -0,0 +1,158 @@
+use display_json::DebugAsJson;
+use ethereum_types::U256;
+use ethers_core::abi::encode;
+use ethers_core::abi::Token;
+use ethers_core::utils::id;
+use ethers_core::utils::keccak256;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::Hash;
+
+/// Tuple type of the user operation in EntryPoint ABI.
+const USER_OPERATION_ABI: &str = "(address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)";
+
+/// ERC-4337 user operation, as accepted by EntryPoint v0.6.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Calculates the hash that identifies the user operation in the specified entry point and chain.
+    ///
+    /// Same as `EntryPoint.getUserOpHash`.
+    pub fn hash(&self, entry_point: Address, chain_id: ChainId) -> Hash {
+        let packed = encode(&[
+            Token::Address(self.sender.into()),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(keccak256(&self.init_code.0).to_vec()),
+            Token::FixedBytes(keccak256(&self.call_data.0).to_vec()),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            Token::FixedBytes(keccak256(&self.paymaster_and_data.0).to_vec()),
+        ]);
+        let encoded = encode(&[
+            Token::FixedBytes(keccak256(packed).to_vec()),
+            Token::Address(entry_point.into()),
+            Token::Uint(chain_id.into()),
+        ]);
+        keccak256(encoded).into()
+    }
+
+    /// Encodes the call to `EntryPoint.simulateValidation`.
+    pub fn encode_simulate_validation(&self) -> Bytes {
+        let selector = id(format!("simulateValidation({})", USER_OPERATION_ABI));
+        let args = encode(&[self.to_token()]);
+        [selector.as_slice(), &args].concat().into()
+    }
+
+    fn to_token(&self) -> Token {
+        Token::Tuple(vec![
+            Token::Address(self.sender.into()),
+            Token::Uint(self.nonce),
+            Token::Bytes(self.init_code.to_vec()),
+            Token::Bytes(self.call_data.to_vec()),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            Token::Bytes(self.paymaster_and_data.to_vec()),
+            Token::Bytes(self.signature.to_vec()),
+        ])
+    }
+}
+
+/// Checks if the output of `EntryPoint.simulateValidation` indicates the user operation is valid.
+///
+/// The simulation always reverts, returning the validation result or the reason it failed.
+pub fn is_simulate_validation_success(output: &Bytes) -> bool {
+    let success_selectors = [
+        id("ValidationResult((uint256,uint256,bool,uint48,uint48,bytes),(uint256,uint256),(uint256,uint256),(uint256,uint256))"),
+        id("ValidationResultWithAggregation((uint256,uint256,bool,uint48,uint48,bytes),(uint256,uint256),(uint256,uint256),(uint256,uint256),(address,(uint256,uint256)))"),
+    ];
+    success_selectors.iter().any(|selector| output.0.starts_with(selector))
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+    use crate::ext::not;
+
+    fn user_operation() -> UserOperation {
+        UserOperation {
+            sender: Address::ZERO,
+            nonce: U256::zero(),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: U256::zero(),
+            verification_gas_limit: U256::zero(),
+            pre_verification_gas: U256::zero(),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        }
+    }
+
+    #[test]
+    fn user_operation_simulate_validation_selector() {
+        assert!(user_operation().encode_simulate_validation().0.starts_with(&hex!("ee219423")));
+    }
+
+    #[test]
+    fn user_operation_hash_ignores_signature() {
+        let user_op = user_operation();
+        let entry_point = Address::new([1; 20]);
+        let chain_id = ChainId::from(2008u64);
+        let hash = user_op.hash(entry_point, chain_id);
+
+        // signature signs the hash, so it cannot be part of it
+        let signed = UserOperation {
+            signature: Bytes::from(vec![1; 65]),
+            ..user_op.clone()
+        };
+        assert_eq!(signed.hash(entry_point, chain_id), hash);
+
+        // same operation in other entry points, chains or with other fields is a different operation
+        assert_ne!(user_op.hash(Address::new([2; 20]), chain_id), hash);
+        assert_ne!(user_op.hash(entry_point, ChainId::from(1u64)), hash);
+        let other_nonce = UserOperation {
+            nonce: U256::one(),
+            ..user_op.clone()
+        };
+        assert_ne!(other_nonce.hash(entry_point, chain_id), hash);
+    }
+
+    #[test]
+    fn user_operation_simulate_validation_success() {
+        // ValidationResult and ValidationResultWithAggregation
+        assert!(is_simulate_validation_success(&Bytes::from(hex!("e0cff05f00").to_vec())));
+        assert!(is_simulate_validation_success(&Bytes::from(hex!("faecb4e400").to_vec())));
+
+        // FailedOp(uint256,string) and empty outputs
+        assert!(not(is_simulate_validation_success(&Bytes::from(hex!("220266b600").to_vec()))));
+        assert!(not(is_simulate_validation_success(&Bytes::default())));
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "postgres")]
 use std::str::FromStr;
 
 use display_json::DebugAsJson;
@@ -5,10 +6,15 @@ use ethabi::Token;
 use ethereum_types::U256;
 use fake::Dummy;
 use fake::Faker;
+#[cfg(feature = "postgres")]
 use sqlx::encode::IsNull;
+#[cfg(feature = "postgres")]
 use sqlx::error::BoxDynError;
+#[cfg(feature = "postgres")]
 use sqlx::postgres::PgHasArrayType;
+#[cfg(feature = "postgres")]
 use sqlx::types::BigDecimal;
+#[cfg(feature = "postgres")]
 use sqlx::Decode;
 
 use crate::alias::RevmU256;
@@ -70,6 +76,7 @@ impl From<RevmU256> for Wei {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl TryFrom<BigDecimal> for Wei {
     type Error = anyhow::Error;
 
@@ -82,6 +89,7 @@ impl TryFrom<BigDecimal> for Wei {
 // -----------------------------------------------------------------------------
 // sqlx traits
 // -----------------------------------------------------------------------------
+#[cfg(feature = "postgres")]
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Wei {
     fn decode(value: <sqlx::Postgres as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
         let value = <BigDecimal as Decode<sqlx::Postgres>>::decode(value)?;
@@ -89,12 +97,14 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Wei {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl sqlx::Type<sqlx::Postgres> for Wei {
     fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("NUMERIC")
     }
 }
 
+#[cfg(feature = "postgres")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Wei {
     fn encode_by_ref(&self, buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>) -> Result<IsNull, sqlx::error::BoxDynError> {
         match BigDecimal::try_from(*self) {
@@ -120,6 +130,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Wei {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl PgHasArrayType for Wei {
     fn array_type_info() -> sqlx::postgres::PgTypeInfo {
         <BigDecimal as PgHasArrayType>::array_type_info()
@@ -147,6 +158,7 @@ impl From<Wei> for U256 {
     }
 }
 
+#[cfg(feature = "postgres")]
 impl TryFrom<Wei> for BigDecimal {
     type Error = anyhow::Error;
     fn try_from(value: Wei) -> Result<Self, Self::Error> {
@@ -160,6 +172,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "postgres")]
     fn big_decimal_to_nonce_conversion() {
         // Test with a simple value
         let big_decimal = BigDecimal::new(1.into(), -4);
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+/// Number of blocks relayed by iteration, adapted to how the target chain is handling the relayed transactions.
+///
+/// The window grows by one block after an iteration that filled it and whose transactions were confirmed within the target latency. It
+/// shrinks by a quarter after a slow iteration and by half after a failed one, so the relayer backs off quickly when the target chain
+/// is congested and recovers gradually (additive increase, multiplicative decrease).
+#[derive(Debug, Clone)]
+pub struct BlockWindow {
+    size: u64,
+    min: u64,
+    max: u64,
+    target_latency: Duration,
+}
+
+impl BlockWindow {
+    /// Creates a window starting at `initial` blocks, kept between `min` and `max` blocks.
+    pub fn new(initial: u64, min: u64, max: u64, target_latency: Duration) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+        Self {
+            size: initial.clamp(min, max),
+            min,
+            max,
+            target_latency,
+        }
+    }
+
+    /// Current number of blocks relayed by iteration.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Adapts the window after an iteration that relayed `blocks`, whose slowest confirmation took `latency`.
+    ///
+    /// Unconfirmed transactions count as a failure, because they mean the target chain is not keeping up.
+    pub fn on_relayed(&mut self, blocks: u64, latency: Duration, unconfirmed: u64) {
+        if unconfirmed > 0 {
+            self.on_error();
+        } else if latency > self.target_latency {
+            self.resize(self.size - self.size / 4);
+        } else if blocks >= self.size {
+            self.resize(self.size + 1);
+        }
+    }
+
+    /// Adapts the window after an iteration that failed.
+    pub fn on_error(&mut self) {
+        self.resize(self.size / 2);
+    }
+
+    fn resize(&mut self, size: u64) {
+        let size = size.clamp(self.min, self.max);
+        if size != self.size {
+            tracing::info!(from = %self.size, to = %size, "resizing relayer block window");
+            self.size = size;
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET_LATENCY: Duration = Duration::from_secs(5);
+    const FAST: Duration = Duration::from_secs(1);
+    const SLOW: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_window_grows_when_full_and_fast() {
+        let mut window = BlockWindow::new(10, 1, 12, TARGET_LATENCY);
+        window.on_relayed(10, FAST, 0);
+        assert_eq!(window.size(), 11);
+
+        // not full: the window was not what limited the iteration
+        window.on_relayed(3, FAST, 0);
+        assert_eq!(window.size(), 11);
+
+        // limited by max
+        window.on_relayed(11, FAST, 0);
+        window.on_relayed(12, FAST, 0);
+        assert_eq!(window.size(), 12);
+    }
+
+    #[test]
+    fn test_window_shrinks_when_slow_or_failing() {
+        let mut window = BlockWindow::new(40, 2, 100, TARGET_LATENCY);
+        window.on_relayed(40, SLOW, 0);
+        assert_eq!(window.size(), 30);
+
+        window.on_relayed(30, FAST, 1);
+        assert_eq!(window.size(), 15);
+
+        window.on_error();
+        assert_eq!(window.size(), 7);
+
+        // limited by min
+        window.on_error();
+        window.on_error();
+        assert_eq!(window.size(), 2);
+    }
+
+    #[test]
+    fn test_window_bounds_are_normalized() {
+        let window = BlockWindow::new(0, 0, 0, TARGET_LATENCY);
+        assert_eq!(window.size(), 1);
+
+        let window = BlockWindow::new(50, 5, 20, TARGET_LATENCY);
+        assert_eq!(window.size(), 20);
+    }
+}
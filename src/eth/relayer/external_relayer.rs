@@ -0,0 +1,707 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::bail;
+use ethers_core::types::Bytes as EthersBytes;
+use ethers_core::types::U256;
+use parking_lot::Mutex;
+use serde_json::json;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::ExternalReceipt;
+use crate::eth::primitives::ExternalTransaction;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::eth::relayer::reconciliation::ReconciliationAction;
+use crate::eth::relayer::reconciliation::ReconciliationReport;
+use crate::eth::relayer::reconciliation::RelayedBlock;
+use crate::eth::relayer::relay_status::BlockRelayStatus;
+use crate::eth::relayer::relay_status::RelayStatusCounts;
+use crate::eth::relayer::relay_status::RelayedBlockStatus;
+use crate::eth::relayer::relay_status::RelayerStatus;
+use crate::eth::relayer::BlockWindow;
+use crate::eth::relayer::ExternalRelayerServerConfig;
+use crate::eth::relayer::GasFees;
+use crate::eth::relayer::GasStrategy;
+use crate::eth::relayer::Multicall;
+use crate::eth::relayer::RelayedCall;
+use crate::eth::relayer::RelayerSigner;
+use crate::ext::not;
+use crate::ext::to_json_value;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+use crate::GlobalState;
+
+/// Schema migrations applied when the relayer is created. They are the same of the external RPC storage, so both can share a database.
+static MIGRATOR: Migrator = sqlx::migrate!("static/schema");
+
+/// Interval between checks of the receipt of a relayed transaction in the target chain.
+const CONFIRMATION_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Errors returned by the target chain when it already received a relayed transaction, as when the relayer restarts in the middle of a block.
+const ALREADY_RECEIVED_ERRORS: [&str; 3] = ["already known", "known transaction", "nonce too low"];
+
+/// Number of most recent blocks returned in the relayer status.
+const STATUS_RECENT_BLOCKS: i64 = 20;
+
+/// Relays transactions mined by Stratus to a target chain with the same chain id.
+///
+/// Blocks are relayed in order. The transactions of a block are sent to the target chain and their receipts in the target chain are
+/// compared with the Stratus ones. The relay status of each block and the mismatched receipts are saved in Postgres, so the relayer
+/// resumes from the first unfinished block after a restart (see [`crate::eth::relayer::relay_status`]).
+///
+/// Transactions are relayed as the raw transactions signed by their senders, or signed by the relayer when it has a signer (see
+/// [`RelayedCall`]), so their fees follow the relayer [`GasStrategy`] and they can be batched by a [`Multicall`] contract.
+pub struct ExternalRelayer {
+    pool: PgPool,
+    stratus: BlockchainClient,
+    target: BlockchainClient,
+    signer: Option<RelayerSigner>,
+    gas: GasStrategy,
+    multicall: Option<Multicall>,
+    window: Mutex<BlockWindow>,
+    config: ExternalRelayerServerConfig,
+}
+
+/// Result of relaying one or more blocks.
+#[derive(Default)]
+struct RelayedBlockStats {
+    /// Time from sending the transactions until the last one was confirmed. The slowest block when aggregating blocks.
+    confirmation_latency: Duration,
+
+    /// Number of transactions not confirmed before the timeout.
+    unconfirmed: u64,
+}
+
+/// Transaction sent to the target chain, relaying one or more transactions mined by Stratus.
+struct Submission {
+    target_hash: Hash,
+    tx_hashes: Vec<Hash>,
+}
+
+impl Submission {
+    fn new(target_hash: Hash, txs: &[ExternalTransaction]) -> Self {
+        Self {
+            target_hash,
+            tx_hashes: txs.iter().map(|tx| tx.hash()).collect(),
+        }
+    }
+}
+
+/// Signs the transactions of a block with sequential nonces of the relayer account and the fees estimated for the block.
+struct BlockSigning<'a> {
+    signer: &'a RelayerSigner,
+    nonce: U256,
+    fees: GasFees,
+}
+
+impl BlockSigning<'_> {
+    fn sign(&mut self, call: RelayedCall) -> anyhow::Result<(EthersBytes, Hash)> {
+        let signed = self.signer.sign(call, self.nonce, self.fees)?;
+        self.nonce += U256::one();
+        Ok(signed)
+    }
+}
+
+impl ExternalRelayer {
+    /// Creates a new [`ExternalRelayer`].
+    pub async fn new(config: ExternalRelayerServerConfig) -> anyhow::Result<Self> {
+        tracing::info!(?config, "creating external relayer");
+
+        let result = PgPoolOptions::new()
+            .max_connections(config.db_connections)
+            .acquire_timeout(config.db_timeout)
+            .connect(&config.db_url)
+            .await;
+        let pool = match result {
+            Ok(pool) => pool,
+            Err(e) => return log_and_err!(reason = e, "failed to connect to relayer database"),
+        };
+
+        // sqlx holds an advisory lock while migrating, so concurrent instances wait instead of applying migrations twice
+        if let Err(e) = MIGRATOR.run(&pool).await {
+            return log_and_err!(reason = e, "failed to migrate relayer database schema");
+        }
+
+        let stratus = BlockchainClient::new_http(&config.stratus_rpc, config.rpc_timeout).await?;
+        let target = BlockchainClient::new_http(&config.forward_to, config.rpc_timeout).await?;
+        let gas = GasStrategy::new(&config)?;
+        let signer = match &config.signer_key {
+            Some(key) => Some(RelayerSigner::new(key, &target).await?),
+            None => None,
+        };
+        if config.multicall_address.is_some() && signer.is_none() {
+            return log_and_err!("relayer multicall requires the relayer signer key");
+        }
+        let multicall = config.multicall_address.map(|address| Multicall::new(address.into(), config.max_batch_size));
+        let window = BlockWindow::new(
+            config.blocks_to_fetch,
+            config.blocks_to_fetch_min,
+            config.blocks_to_fetch_max,
+            config.target_latency,
+        );
+        Ok(Self {
+            pool,
+            stratus,
+            target,
+            signer,
+            gas,
+            multicall,
+            window: Mutex::new(window),
+            config,
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Relay
+    // -------------------------------------------------------------------------
+
+    /// Relays blocks until the application is shut down.
+    pub async fn run(&self) -> anyhow::Result<()> {
+        const TASK_NAME: &str = "relayer";
+
+        loop {
+            if GlobalState::is_shutdown_warn(TASK_NAME) {
+                return Ok(());
+            }
+
+            match self.relay_next_blocks().await {
+                Ok(0) => traced_sleep(self.config.backoff, SleepReason::SyncData).await,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!(reason = ?e, "failed to relay blocks, retrying");
+                    traced_sleep(self.config.backoff, SleepReason::RetryBackoff).await;
+                }
+            }
+        }
+    }
+
+    /// Relays the next blocks mined by Stratus, up to the block window size, returning how many were relayed.
+    ///
+    /// The window is adapted to the confirmation latency and errors of the relayed blocks (see [`BlockWindow`]).
+    pub async fn relay_next_blocks(&self) -> anyhow::Result<u64> {
+        let size = self.window.lock().size();
+        #[cfg(feature = "metrics")]
+        metrics::set_relayer_blocks_to_fetch(size);
+
+        let result = self.relay_window(size).await;
+        let mut window = self.window.lock();
+        match result {
+            Ok((relayed, stats)) => {
+                window.on_relayed(relayed, stats.confirmation_latency, stats.unconfirmed);
+                Ok(relayed)
+            }
+            Err(e) => {
+                window.on_error();
+                Err(e)
+            }
+        }
+    }
+
+    /// Relays up to `size` blocks, returning how many were relayed and their aggregated stats.
+    async fn relay_window(&self, size: u64) -> anyhow::Result<(u64, RelayedBlockStats)> {
+        let mut number = self.read_next_block().await?.unwrap_or(BlockNumber::ZERO);
+        let mined = self.stratus.fetch_block_number().await?;
+
+        let (mut relayed, mut window_stats) = (0, RelayedBlockStats::default());
+        while number <= mined && relayed < size {
+            if GlobalState::is_shutdown() {
+                break;
+            }
+            let stats = self.relay_block(number, mined).await?;
+            relayed += 1;
+            window_stats.confirmation_latency = window_stats.confirmation_latency.max(stats.confirmation_latency);
+            window_stats.unconfirmed += stats.unconfirmed;
+            number = number.next_block_number();
+        }
+        Ok((relayed, window_stats))
+    }
+
+    /// Relays the transactions of a block and compares their receipts, saving the block status after each step.
+    ///
+    /// `mined` is the last block mined by Stratus, used to report the relay lag.
+    async fn relay_block(&self, number: BlockNumber, mined: BlockNumber) -> anyhow::Result<RelayedBlockStats> {
+        let start = Instant::now();
+
+        // read block from stratus
+        let block = self.stratus.fetch_block(number).await?;
+        if block.is_null() {
+            return log_and_err!(payload = number, "block mined by stratus not found");
+        }
+        let block = ExternalBlock::try_from(block)?;
+        let receipts = self.stratus.fetch_block_receipts(number).await?.unwrap_or_default();
+        let mut receipts: HashMap<Hash, ExternalReceipt> = receipts.into_iter().map(|receipt| (receipt.hash(), receipt)).collect();
+        tracing::info!(%number, transactions = %block.transactions.len(), "relaying block");
+        self.save_block_pending(number, block.hash(), block.timestamp()).await?;
+
+        // send all transactions before waiting for confirmations, so the target chain can include them in the same block
+        let submissions = self.send_transactions(&block.transactions).await?;
+        self.save_block_relayed(number).await?;
+
+        // compare receipts
+        let sent_at = Instant::now();
+        let deadline = sent_at + self.config.confirmation_timeout;
+        let (mut unconfirmed, mut mismatched) = (0, false);
+        for submission in submissions {
+            let mut stratus_receipts = Vec::with_capacity(submission.tx_hashes.len());
+            for tx_hash in &submission.tx_hashes {
+                let Some(stratus_receipt) = receipts.remove(tx_hash) else {
+                    return log_and_err!(payload = tx_hash, "receipt of transaction mined by stratus not found");
+                };
+                stratus_receipts.push(stratus_receipt);
+            }
+            let target_receipt = self.wait_confirmation(submission.target_hash, deadline).await?;
+            if target_receipt.is_none() {
+                unconfirmed += submission.tx_hashes.len() as u64;
+            }
+
+            let matches = target_receipt
+                .as_ref()
+                .is_some_and(|target_receipt| receipts_match(&stratus_receipts, target_receipt));
+            if matches {
+                continue;
+            }
+            mismatched = true;
+            for (tx_hash, stratus_receipt) in submission.tx_hashes.iter().zip(&stratus_receipts) {
+                tracing::warn!(%number, %tx_hash, target_hash = %submission.target_hash, confirmed = %target_receipt.is_some(), "relayed transaction receipt differs from stratus");
+                self.save_mismatch(number, *tx_hash, stratus_receipt, target_receipt.as_ref()).await?;
+                #[cfg(feature = "metrics")]
+                metrics::inc_relayer_mismatches();
+            }
+        }
+
+        let confirmation_latency = sent_at.elapsed();
+        let status = match mismatched {
+            true => BlockRelayStatus::Mismatched,
+            false => BlockRelayStatus::Confirmed,
+        };
+        self.save_block_checked(number, status).await?;
+
+        #[cfg(feature = "metrics")]
+        {
+            metrics::inc_n_relayer_transactions_relayed(block.transactions.len() as u64);
+            metrics::inc_relayer_relay_block(start.elapsed());
+            metrics::set_relayer_lag_blocks(mined.as_u64().saturating_sub(number.as_u64()));
+            metrics::set_relayer_lag_seconds(UnixTime::now().saturating_sub(*block.timestamp()));
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = mined;
+        #[cfg(not(feature = "metrics"))]
+        let _ = start;
+
+        Ok(RelayedBlockStats {
+            confirmation_latency,
+            unconfirmed,
+        })
+    }
+
+    /// Sends the transactions of a block to the target chain, in the order they were mined by Stratus.
+    async fn send_transactions(&self, txs: &[ExternalTransaction]) -> anyhow::Result<Vec<Submission>> {
+        let mut submissions = Vec::with_capacity(txs.len());
+
+        // raw transactions signed by their senders
+        let Some(mut signing) = self.block_signing().await? else {
+            for tx in txs {
+                self.send_transaction(tx.hash(), tx.rlp()).await?;
+                submissions.push(Submission::new(tx.hash(), std::slice::from_ref(tx)));
+            }
+            return Ok(submissions);
+        };
+
+        // transactions signed by the relayer, batched when a multicall contract is configured
+        let batches = match &self.multicall {
+            Some(multicall) => multicall.batches(txs),
+            None => txs.chunks(1).collect(),
+        };
+        for batch in batches {
+            let call = match (&self.multicall, batch) {
+                (_, [tx]) => RelayedCall::new(tx),
+                (Some(multicall), batch) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::inc_relayer_batches_relayed();
+                    multicall.aggregate(batch)
+                }
+                (None, _) => unreachable!("transactions are only batched by the multicall contract"),
+            };
+            let (rlp, target_hash) = signing.sign(call)?;
+            self.send_transaction(target_hash, rlp).await?;
+            submissions.push(Submission::new(target_hash, batch));
+        }
+        Ok(submissions)
+    }
+
+    /// Prepares the signing of the transactions of a block by the relayer, or returns `None` if the relayer has no signer.
+    async fn block_signing(&self) -> anyhow::Result<Option<BlockSigning<'_>>> {
+        let Some(signer) = &self.signer else { return Ok(None) };
+        Ok(Some(BlockSigning {
+            signer,
+            nonce: signer.fetch_nonce(&self.target).await?,
+            fees: self.gas.estimate(&self.target).await?,
+        }))
+    }
+
+    /// Sends a raw signed transaction to the target chain.
+    async fn send_transaction(&self, tx_hash: Hash, rlp: EthersBytes) -> anyhow::Result<()> {
+        tracing::debug!(%tx_hash, "sending transaction to target chain");
+
+        match self.target.send_request("eth_sendRawTransaction", Some(json!([rlp]))).await? {
+            Ok(_) => Ok(()),
+            Err(e) if ALREADY_RECEIVED_ERRORS.iter().any(|error| e.message().contains(error)) => {
+                tracing::warn!(%tx_hash, reason = %e.message(), "relayed transaction already received by target chain");
+                Ok(())
+            }
+            Err(e) => log_and_err!(reason = e, payload = tx_hash, "target chain rejected relayed transaction"),
+        }
+    }
+
+    /// Waits until the target chain confirms a relayed transaction, returning its receipt, or `None` if the deadline is reached.
+    async fn wait_confirmation(&self, tx_hash: Hash, deadline: Instant) -> anyhow::Result<Option<ExternalReceipt>> {
+        loop {
+            if let Some(receipt) = self.target.fetch_receipt(tx_hash).await? {
+                return Ok(Some(receipt));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            if GlobalState::is_shutdown() {
+                bail!("shutdown while waiting for relayed transaction confirmation");
+            }
+            traced_sleep(CONFIRMATION_INTERVAL, SleepReason::SyncData).await;
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Status
+    // -------------------------------------------------------------------------
+
+    /// Reads the relay status of the most recent blocks and the end-to-end relay lag.
+    pub async fn status(&self) -> anyhow::Result<RelayerStatus> {
+        let stratus_block = self.stratus.fetch_block_number().await?;
+        let counts = self.read_status_counts().await?;
+        let recent = self.read_recent_blocks().await?;
+        Ok(RelayerStatus::new(stratus_block, counts, recent))
+    }
+
+    // -------------------------------------------------------------------------
+    // Reconciliation
+    // -------------------------------------------------------------------------
+
+    /// Reconciles the most recent relayed blocks with the blocks in Stratus (see [`crate::eth::relayer::reconciliation`]).
+    ///
+    /// In dry-run mode, the changes are reported but not applied.
+    pub async fn reconcile(&self) -> anyhow::Result<ReconciliationReport> {
+        let dry_run = self.config.reconcile_dry_run;
+        tracing::info!(%dry_run, window = %self.config.reconcile_window, "reconciling relayed blocks with stratus");
+
+        let Some(max) = self.read_max_relayed_block().await? else {
+            return Ok(ReconciliationReport { dry_run, ..Default::default() });
+        };
+        let start = max.as_u64().saturating_sub(self.config.reconcile_window.saturating_sub(1));
+        let blocks = self.read_blocks_to_reconcile(BlockNumber::from(start)).await?;
+
+        let mut report = ReconciliationReport { dry_run, ..Default::default() };
+        for block in blocks {
+            report.checked += 1;
+
+            let stratus_block = self.stratus.fetch_block(block.number).await?;
+            let stratus_hash = match stratus_block.is_null() {
+                true => None,
+                false => Some(ExternalBlock::try_from(stratus_block)?.hash()),
+            };
+            let Some(action) = ReconciliationAction::decide(&block, stratus_hash) else {
+                continue;
+            };
+
+            tracing::warn!(number = %block.number, relayed_hash = %block.hash, ?stratus_hash, %action, %dry_run, "reconciling relayed block");
+            if not(dry_run) {
+                self.apply_reconciliation(block.number, action).await?;
+            }
+            report.push(block.number, action);
+
+            #[cfg(feature = "metrics")]
+            metrics::inc_relayer_reconciliation_blocks(action.to_string(), dry_run);
+        }
+
+        tracing::info!(
+            %dry_run,
+            checked = %report.checked,
+            marked = ?report.marked,
+            restored = ?report.restored,
+            deleted = ?report.deleted,
+            "reconciled relayed blocks with stratus"
+        );
+        Ok(report)
+    }
+
+    // -------------------------------------------------------------------------
+    // Database
+    // -------------------------------------------------------------------------
+
+    async fn read_max_relayed_block(&self) -> anyhow::Result<Option<BlockNumber>> {
+        let result = sqlx::query_file_scalar!("src/eth/relayer/sql/select_max_relayer_block.sql")
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(max) => Ok(max.map(BlockNumber::from)),
+            Err(e) => log_and_err!(reason = e, "failed to read max relayed block"),
+        }
+    }
+
+    async fn read_next_block(&self) -> anyhow::Result<Option<BlockNumber>> {
+        let result = sqlx::query_file_scalar!("src/eth/relayer/sql/select_next_relayer_block.sql")
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(next) => Ok(next.map(BlockNumber::from)),
+            Err(e) => log_and_err!(reason = e, "failed to read next block to relay"),
+        }
+    }
+
+    async fn save_block_pending(&self, number: BlockNumber, hash: Hash, mined_at: UnixTime) -> anyhow::Result<()> {
+        let mined_at = i64::try_from(*mined_at)?;
+        let result = sqlx::query_file!("src/eth/relayer/sql/insert_relayer_block.sql", number.as_i64()?, hash.as_ref(), mined_at)
+            .execute(&self.pool)
+            .await;
+
+        match result {
+            Ok(_) => {
+                #[cfg(feature = "metrics")]
+                metrics::set_relayer_block_number(number.as_u64(), BlockRelayStatus::Pending.to_string());
+                Ok(())
+            }
+            Err(e) => log_and_err!(reason = e, payload = number, "failed to save pending block"),
+        }
+    }
+
+    async fn save_block_relayed(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let result = sqlx::query_file!("src/eth/relayer/sql/update_relayer_block_relayed.sql", number.as_i64()?)
+            .execute(&self.pool)
+            .await;
+
+        match result {
+            Ok(_) => {
+                #[cfg(feature = "metrics")]
+                metrics::set_relayer_block_number(number.as_u64(), BlockRelayStatus::Relayed.to_string());
+                Ok(())
+            }
+            Err(e) => log_and_err!(reason = e, payload = number, "failed to save relayed block"),
+        }
+    }
+
+    async fn save_block_checked(&self, number: BlockNumber, status: BlockRelayStatus) -> anyhow::Result<()> {
+        let result = sqlx::query_file!("src/eth/relayer/sql/update_relayer_block_checked.sql", number.as_i64()?, status.to_string())
+            .execute(&self.pool)
+            .await;
+
+        match result {
+            Ok(_) => {
+                #[cfg(feature = "metrics")]
+                metrics::set_relayer_block_number(number.as_u64(), status.to_string());
+                Ok(())
+            }
+            Err(e) => log_and_err!(reason = e, payload = number, "failed to save checked block"),
+        }
+    }
+
+    async fn read_status_counts(&self) -> anyhow::Result<RelayStatusCounts> {
+        let result = sqlx::query_file!("src/eth/relayer/sql/select_relayer_status_counts.sql")
+            .fetch_all(&self.pool)
+            .await;
+
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(e) => return log_and_err!(reason = e, "failed to read relayed block status counts"),
+        };
+        let mut counts = RelayStatusCounts::default();
+        for row in rows {
+            counts.add(BlockRelayStatus::from_str(&row.status)?, row.count as u64);
+        }
+        Ok(counts)
+    }
+
+    async fn read_recent_blocks(&self) -> anyhow::Result<Vec<RelayedBlockStatus>> {
+        let result = sqlx::query_file!("src/eth/relayer/sql/select_relayer_recent_blocks.sql", STATUS_RECENT_BLOCKS)
+            .fetch_all(&self.pool)
+            .await;
+
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(e) => return log_and_err!(reason = e, "failed to read recent relayed blocks"),
+        };
+        rows.into_iter()
+            .map(|row| {
+                Ok(RelayedBlockStatus {
+                    number: row.number.into(),
+                    hash: row.hash,
+                    status: BlockRelayStatus::from_str(&row.status)?,
+                    mined_at: row.mined_at.map(|at| at as u64),
+                    pending_at: row.pending_at as u64,
+                    relayed_at: row.relayed_at.map(|at| at as u64),
+                    checked_at: row.checked_at.map(|at| at as u64),
+                })
+            })
+            .collect()
+    }
+
+    async fn save_mismatch(
+        &self,
+        number: BlockNumber,
+        tx_hash: Hash,
+        stratus_receipt: &ExternalReceipt,
+        target_receipt: Option<&ExternalReceipt>,
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query_file!(
+            "src/eth/relayer/sql/insert_relayer_mismatch.sql",
+            tx_hash.as_ref(),
+            number.as_i64()?,
+            to_json_value(stratus_receipt),
+            target_receipt.map(to_json_value)
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, payload = tx_hash, "failed to save relayed transaction mismatch"),
+        }
+    }
+
+    async fn read_blocks_to_reconcile(&self, start: BlockNumber) -> anyhow::Result<Vec<RelayedBlock>> {
+        let grace_period = self.config.reconcile_grace_period.as_secs_f64();
+        let result = sqlx::query_file!("src/eth/relayer/sql/select_relayer_blocks_to_reconcile.sql", start.as_i64()?, grace_period)
+            .fetch_all(&self.pool)
+            .await;
+
+        match result {
+            Ok(rows) => Ok(rows
+                .into_iter()
+                .map(|row| RelayedBlock {
+                    number: row.number.into(),
+                    hash: row.hash,
+                    orphaned: row.orphaned,
+                    grace_period_expired: row.grace_period_expired,
+                })
+                .collect()),
+            Err(e) => log_and_err!(reason = e, "failed to read relayed blocks to reconcile"),
+        }
+    }
+
+    async fn apply_reconciliation(&self, number: BlockNumber, action: ReconciliationAction) -> anyhow::Result<()> {
+        let number_i64 = number.as_i64()?;
+        let result = match action {
+            ReconciliationAction::Mark =>
+                sqlx::query_file!("src/eth/relayer/sql/update_relayer_block_orphaned.sql", number_i64)
+                    .execute(&self.pool)
+                    .await,
+            ReconciliationAction::Restore =>
+                sqlx::query_file!("src/eth/relayer/sql/update_relayer_block_restored.sql", number_i64)
+                    .execute(&self.pool)
+                    .await,
+            ReconciliationAction::Delete =>
+                sqlx::query_file!("src/eth/relayer/sql/delete_relayer_block.sql", number_i64)
+                    .execute(&self.pool)
+                    .await,
+        };
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, payload = number, "failed to reconcile relayed block"),
+        }
+    }
+}
+
+/// Checks if the target chain receipt has the same result and logs of the Stratus receipts relayed by it.
+///
+/// A multicall batch succeeds even when some of its calls fail, so only its logs are compared with the logs of all relayed receipts,
+/// which failed calls do not emit. Gas is not compared because it depends on the chain fee rules.
+fn receipts_match(stratus: &[ExternalReceipt], target: &ExternalReceipt) -> bool {
+    let success = match stratus {
+        [stratus] => stratus.is_success(),
+        _ => true,
+    };
+    let stratus_logs: Vec<_> = stratus.iter().flat_map(|receipt| &receipt.logs).collect();
+
+    success == target.is_success()
+        && stratus_logs.len() == target.logs.len()
+        && stratus_logs
+            .iter()
+            .zip(&target.logs)
+            .all(|(stratus, target)| stratus.address == target.address && stratus.topics == target.topics && stratus.data == target.data)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::Log as EthersLog;
+    use ethers_core::types::U64;
+
+    use super::*;
+    use crate::alias::EthersReceipt;
+
+    fn receipt(status: u64, data: &[u8]) -> ExternalReceipt {
+        ExternalReceipt(EthersReceipt {
+            status: Some(U64::from(status)),
+            logs: vec![EthersLog {
+                data: data.to_vec().into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+    }
+
+    fn batch_receipt(data: &[&[u8]]) -> ExternalReceipt {
+        let mut receipt = receipt(1, b"");
+        receipt.0.logs = data
+            .iter()
+            .map(|data| EthersLog {
+                data: data.to_vec().into(),
+                ..Default::default()
+            })
+            .collect();
+        receipt
+    }
+
+    #[test]
+    fn test_receipts_match() {
+        assert!(receipts_match(&[receipt(1, b"a")], &receipt(1, b"a")));
+
+        // gas depends on the chain
+        let mut target = receipt(1, b"a");
+        target.0.gas_used = Some(21_000.into());
+        assert!(receipts_match(&[receipt(1, b"a")], &target));
+    }
+
+    #[test]
+    fn test_receipts_mismatch() {
+        assert!(not(receipts_match(&[receipt(1, b"a")], &receipt(0, b"a"))));
+        assert!(not(receipts_match(&[receipt(1, b"a")], &receipt(1, b"b"))));
+        assert!(not(receipts_match(&[receipt(1, b"a")], &ExternalReceipt(EthersReceipt::default()))));
+    }
+
+    #[test]
+    fn test_batch_receipts_match() {
+        let failed = ExternalReceipt(EthersReceipt {
+            status: Some(U64::zero()),
+            ..Default::default()
+        });
+        let stratus = [receipt(1, b"a"), failed, receipt(1, b"b")];
+
+        assert!(receipts_match(&stratus, &batch_receipt(&[b"a", b"b"])));
+        assert!(not(receipts_match(&stratus, &batch_receipt(&[b"b", b"a"]))));
+        assert!(not(receipts_match(&stratus, &batch_receipt(&[b"a"]))));
+    }
+}
@@ -0,0 +1,131 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::relayer::ExternalRelayer;
+use crate::eth::relayer::GasStrategyKind;
+use crate::ext::parse_duration;
+
+/// External relayer configuration.
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct ExternalRelayerServerConfig {
+    /// Address where the relayer serves `stratus_relayerStatus`.
+    #[arg(long = "relayer-address", env = "RELAYER_ADDRESS", default_value = "0.0.0.0:3004")]
+    pub address: SocketAddr,
+
+    /// Postgres URL where relayed blocks and mismatches are stored.
+    #[arg(long = "relayer-db-url", env = "RELAYER_DB_URL")]
+    pub db_url: String,
+
+    /// Relayer database number of parallel open connections.
+    #[arg(long = "relayer-db-connections", env = "RELAYER_DB_CONNECTIONS", default_value = "5")]
+    pub db_connections: u32,
+
+    /// Relayer database timeout when opening a connection.
+    #[arg(long = "relayer-db-timeout", value_parser=parse_duration, env = "RELAYER_DB_TIMEOUT", default_value = "1s")]
+    pub db_timeout: Duration,
+
+    /// Stratus RPC endpoint where mined blocks are read from.
+    #[arg(long = "stratus-rpc", env = "STRATUS_RPC")]
+    pub stratus_rpc: String,
+
+    /// Target chain RPC endpoint where transactions are relayed to.
+    #[arg(long = "forward-to", env = "RELAYER_FORWARD_TO")]
+    pub forward_to: String,
+
+    /// Timeout for requests to Stratus and to the target chain.
+    #[arg(long = "relayer-rpc-timeout", value_parser=parse_duration, env = "RELAYER_RPC_TIMEOUT", default_value = "2s")]
+    pub rpc_timeout: Duration,
+
+    /// Initial number of blocks relayed by iteration. It adapts to the target chain confirmation latency and errors while relaying.
+    #[arg(long = "blocks-to-fetch", env = "BLOCKS_TO_FETCH", default_value = "10")]
+    pub blocks_to_fetch: u64,
+
+    /// Min number of blocks relayed by iteration.
+    #[arg(long = "blocks-to-fetch-min", env = "BLOCKS_TO_FETCH_MIN", default_value = "1")]
+    pub blocks_to_fetch_min: u64,
+
+    /// Max number of blocks relayed by iteration. Set it and the min to the initial number to disable the adaptation.
+    #[arg(long = "blocks-to-fetch-max", env = "BLOCKS_TO_FETCH_MAX", default_value = "100")]
+    pub blocks_to_fetch_max: u64,
+
+    /// Confirmation latency of relayed transactions above which fewer blocks are relayed by iteration.
+    #[arg(long = "relayer-target-latency", value_parser=parse_duration, env = "RELAYER_TARGET_LATENCY", default_value = "5s")]
+    pub target_latency: Duration,
+
+    /// Max time waiting for the target chain to confirm a relayed transaction. Unconfirmed transactions are saved as mismatches.
+    #[arg(long = "relayer-confirmation-timeout", value_parser=parse_duration, env = "RELAYER_CONFIRMATION_TIMEOUT", default_value = "30s")]
+    pub confirmation_timeout: Duration,
+
+    /// Time waiting for new blocks when all blocks mined by Stratus were relayed, and before retrying after an error.
+    #[arg(long = "relayer-backoff", value_parser=parse_duration, env = "RELAYER_BACKOFF", default_value = "1s")]
+    pub backoff: Duration,
+
+    /// Private key of the relayer account. When set, relayed transactions are signed by the relayer and their fees are priced by the gas
+    /// strategy, otherwise the raw transactions signed by their senders are relayed with their original fees.
+    #[arg(long = "relayer-signer-key", env = "RELAYER_SIGNER_KEY")]
+    #[serde(skip)]
+    pub signer_key: Option<String>,
+
+    /// How the fees of transactions signed by the relayer are priced.
+    #[arg(long = "relayer-gas-strategy", env = "RELAYER_GAS_STRATEGY", default_value = "legacy")]
+    pub gas_strategy: GasStrategyKind,
+
+    /// Multiplier applied to the target chain gas price when using the legacy gas strategy.
+    #[arg(long = "relayer-gas-price-multiplier", env = "RELAYER_GAS_PRICE_MULTIPLIER", default_value = "1.0")]
+    pub gas_price_multiplier: f64,
+
+    /// Number of recent target chain blocks sampled when using the eip1559 gas strategy.
+    #[arg(long = "relayer-fee-history-blocks", env = "RELAYER_FEE_HISTORY_BLOCKS", default_value = "10")]
+    pub fee_history_blocks: u64,
+
+    /// Percentile of the priority fees paid in each sampled block when using the eip1559 gas strategy.
+    #[arg(long = "relayer-fee-history-percentile", env = "RELAYER_FEE_HISTORY_PERCENTILE", default_value = "50")]
+    pub fee_history_percentile: f64,
+
+    /// Max fee per gas (or gas price) paid by transactions signed by the relayer. Paid as gas price when using the fixed gas strategy.
+    #[arg(long = "relayer-max-fee-per-gas", env = "RELAYER_MAX_FEE_PER_GAS")]
+    pub max_fee_per_gas: Option<u64>,
+
+    /// Max priority fee per gas paid by transactions signed by the relayer.
+    #[arg(long = "relayer-max-priority-fee-per-gas", env = "RELAYER_MAX_PRIORITY_FEE_PER_GAS")]
+    pub max_priority_fee_per_gas: Option<u64>,
+
+    /// Multicall3 contract in the target chain. When set, consecutive contract calls of a block are relayed in batches executed by the
+    /// contract. Requires the relayer signer key.
+    #[arg(long = "relayer-multicall-address", env = "RELAYER_MULTICALL_ADDRESS")]
+    pub multicall_address: Option<Address>,
+
+    /// Max number of transactions relayed in a single multicall batch.
+    #[arg(long = "relayer-max-batch-size", env = "RELAYER_MAX_BATCH_SIZE", default_value = "50")]
+    pub max_batch_size: usize,
+
+    /// Reconciles relayed blocks with Stratus at startup.
+    ///
+    /// Relayed blocks not found in Stratus are marked. Marked blocks found again are unmarked, and blocks still missing after the grace
+    /// period are deleted, so they are relayed again when Stratus mines them.
+    #[arg(long = "reconcile", env = "RELAYER_RECONCILE", default_value = "false")]
+    pub reconcile: bool,
+
+    /// Reports what the reconciliation would do without changing the relayer database.
+    #[arg(long = "reconcile-dry-run", env = "RELAYER_RECONCILE_DRY_RUN", default_value = "false")]
+    pub reconcile_dry_run: bool,
+
+    /// Time a block must stay marked as not found in Stratus before the reconciliation deletes it.
+    #[arg(long = "reconcile-grace-period", value_parser=parse_duration, env = "RELAYER_RECONCILE_GRACE_PERIOD", default_value = "1h")]
+    pub reconcile_grace_period: Duration,
+
+    /// Number of most recent relayed blocks checked by the reconciliation.
+    #[arg(long = "reconcile-window", env = "RELAYER_RECONCILE_WINDOW", default_value = "1000")]
+    pub reconcile_window: u64,
+}
+
+impl ExternalRelayerServerConfig {
+    /// Initializes the external relayer.
+    pub async fn init(&self) -> anyhow::Result<ExternalRelayer> {
+        ExternalRelayer::new(self.clone()).await
+    }
+}
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use display_json::DebugAsJson;
+use ethers_core::types::FeeHistory;
+use ethers_core::types::U256;
+
+use crate::eth::primitives::Wei;
+use crate::eth::relayer::ExternalRelayerServerConfig;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+
+/// Precision of the legacy gas price multiplier.
+const MULTIPLIER_SCALE: u64 = 1_000;
+
+/// How the fees of transactions signed by the relayer are priced.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum GasStrategyKind {
+    /// Pays the target chain `eth_gasPrice` times the configured multiplier as legacy gas price.
+    #[serde(rename = "legacy")]
+    Legacy,
+
+    /// Estimates EIP-1559 fees from the base fee and priority fees of recent target chain blocks (`eth_feeHistory`).
+    #[serde(rename = "eip1559")]
+    Eip1559,
+
+    /// Always pays the configured max fee per gas as legacy gas price. Used by zero-fee target chains.
+    #[serde(rename = "fixed")]
+    Fixed,
+}
+
+impl FromStr for GasStrategyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "legacy" => Ok(Self::Legacy),
+            "eip1559" => Ok(Self::Eip1559),
+            "fixed" => Ok(Self::Fixed),
+            s => Err(anyhow!("unknown relayer gas strategy: {}", s)),
+        }
+    }
+}
+
+/// Fees of a transaction signed by the relayer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasFees {
+    Legacy { gas_price: Wei },
+    Eip1559 { max_fee_per_gas: Wei, max_priority_fee_per_gas: Wei },
+}
+
+/// Prices the fees of transactions signed by the relayer according to the configured [`GasStrategyKind`].
+///
+/// The configured max fees cap the estimated fees of all strategies, so a fee spike in the target chain delays relayed transactions
+/// instead of draining the relayer account.
+#[derive(Debug, Clone)]
+pub struct GasStrategy {
+    kind: GasStrategyKind,
+    multiplier: U256,
+    fee_history_blocks: u64,
+    fee_history_percentile: f64,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+}
+
+impl GasStrategy {
+    /// Creates the gas strategy from the relayer configuration.
+    pub fn new(config: &ExternalRelayerServerConfig) -> anyhow::Result<Self> {
+        if not_positive(config.gas_price_multiplier) {
+            return Err(anyhow!("relayer gas price multiplier must be greater than zero"));
+        }
+        if not_percentile(config.fee_history_percentile) {
+            return Err(anyhow!("relayer fee history percentile must be between 0 and 100"));
+        }
+        if config.fee_history_blocks == 0 {
+            return Err(anyhow!("relayer fee history blocks must be greater than zero"));
+        }
+
+        Ok(Self {
+            kind: config.gas_strategy,
+            multiplier: U256::from((config.gas_price_multiplier * MULTIPLIER_SCALE as f64).round() as u64),
+            fee_history_blocks: config.fee_history_blocks,
+            fee_history_percentile: config.fee_history_percentile,
+            max_fee_per_gas: config.max_fee_per_gas.map(U256::from),
+            max_priority_fee_per_gas: config.max_priority_fee_per_gas.map(U256::from),
+        })
+    }
+
+    /// Estimates the fees of the next transactions signed by the relayer.
+    pub async fn estimate(&self, target: &BlockchainClient) -> anyhow::Result<GasFees> {
+        let fees = match self.kind {
+            GasStrategyKind::Legacy => {
+                let gas_price = match target.call::<U256>("eth_gasPrice", [(); 0]).await {
+                    Ok(gas_price) => gas_price,
+                    Err(e) => return log_and_err!(reason = e, "failed to fetch target chain gas price"),
+                };
+                self.legacy_fees(gas_price)
+            }
+            GasStrategyKind::Eip1559 => {
+                let params = (U256::from(self.fee_history_blocks), "latest", [self.fee_history_percentile]);
+                let history = match target.call::<FeeHistory>("eth_feeHistory", params).await {
+                    Ok(history) => history,
+                    Err(e) => return log_and_err!(reason = e, "failed to fetch target chain fee history"),
+                };
+                self.eip1559_fees(&history)?
+            }
+            GasStrategyKind::Fixed => GasFees::Legacy {
+                gas_price: self.max_fee_per_gas.unwrap_or_default().into(),
+            },
+        };
+        tracing::debug!(?fees, "estimated relayer gas fees");
+        Ok(fees)
+    }
+
+    fn legacy_fees(&self, gas_price: U256) -> GasFees {
+        let gas_price = gas_price.saturating_mul(self.multiplier) / MULTIPLIER_SCALE;
+        GasFees::Legacy {
+            gas_price: cap(gas_price, self.max_fee_per_gas).into(),
+        }
+    }
+
+    /// Pays the average of the sampled priority fees and up to twice the next block base fee, so the transaction stays valid even if the
+    /// base fee keeps rising for a few blocks.
+    fn eip1559_fees(&self, history: &FeeHistory) -> anyhow::Result<GasFees> {
+        // the last base fee is the one of the next block
+        let Some(base_fee) = history.base_fee_per_gas.last().copied() else {
+            return log_and_err!("target chain fee history has no base fee, it may not support eip-1559");
+        };
+
+        let rewards: Vec<U256> = history.reward.iter().filter_map(|block| block.first().copied()).collect();
+        let priority_fee = match rewards.len() {
+            0 => U256::zero(),
+            len => rewards.iter().fold(U256::zero(), |sum, reward| sum.saturating_add(*reward)) / len,
+        };
+        let priority_fee = cap(priority_fee, self.max_priority_fee_per_gas);
+
+        let max_fee = cap(base_fee.saturating_mul(2.into()).saturating_add(priority_fee), self.max_fee_per_gas);
+        if max_fee < base_fee {
+            tracing::warn!(%base_fee, %max_fee, "relayer max fee per gas is below the target chain base fee");
+        }
+
+        Ok(GasFees::Eip1559 {
+            max_fee_per_gas: max_fee.into(),
+            max_priority_fee_per_gas: priority_fee.min(max_fee).into(),
+        })
+    }
+}
+
+fn cap(fee: U256, max: Option<U256>) -> U256 {
+    match max {
+        Some(max) => fee.min(max),
+        None => fee,
+    }
+}
+
+fn not_positive(value: f64) -> bool {
+    value.is_nan() || value <= 0.0
+}
+
+fn not_percentile(value: f64) -> bool {
+    value.is_nan() || !(0.0..=100.0).contains(&value)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(kind: GasStrategyKind, max_fee_per_gas: Option<u64>, max_priority_fee_per_gas: Option<u64>) -> GasStrategy {
+        GasStrategy {
+            kind,
+            multiplier: U256::from(1_500),
+            fee_history_blocks: 3,
+            fee_history_percentile: 50.0,
+            max_fee_per_gas: max_fee_per_gas.map(U256::from),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.map(U256::from),
+        }
+    }
+
+    fn history(base_fees: &[u64], rewards: &[u64]) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees.iter().map(|fee| U256::from(*fee)).collect(),
+            gas_used_ratio: vec![0.5; rewards.len()],
+            oldest_block: U256::zero(),
+            reward: rewards.iter().map(|reward| vec![U256::from(*reward)]).collect(),
+        }
+    }
+
+    fn eip1559(max_fee_per_gas: u64, max_priority_fee_per_gas: u64) -> GasFees {
+        GasFees::Eip1559 {
+            max_fee_per_gas: max_fee_per_gas.into(),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.into(),
+        }
+    }
+
+    #[test]
+    fn test_legacy_fees_apply_multiplier_and_cap() {
+        let fees = strategy(GasStrategyKind::Legacy, None, None).legacy_fees(100.into());
+        assert_eq!(fees, GasFees::Legacy { gas_price: 150u64.into() });
+
+        let fees = strategy(GasStrategyKind::Legacy, Some(120), None).legacy_fees(100.into());
+        assert_eq!(fees, GasFees::Legacy { gas_price: 120u64.into() });
+    }
+
+    #[test]
+    fn test_eip1559_fees_from_fee_history() {
+        let history = history(&[90, 95, 100, 110], &[1, 2, 6]);
+
+        let fees = strategy(GasStrategyKind::Eip1559, None, None).eip1559_fees(&history).unwrap();
+        assert_eq!(fees, eip1559(223, 3));
+
+        // caps
+        let fees = strategy(GasStrategyKind::Eip1559, Some(200), Some(2)).eip1559_fees(&history).unwrap();
+        assert_eq!(fees, eip1559(200, 2));
+
+        // priority fee is never above the max fee
+        let fees = strategy(GasStrategyKind::Eip1559, Some(1), None).eip1559_fees(&history).unwrap();
+        assert_eq!(fees, eip1559(1, 1));
+    }
+
+    #[test]
+    fn test_eip1559_fees_without_rewards_or_base_fee() {
+        let fees = strategy(GasStrategyKind::Eip1559, None, None).eip1559_fees(&history(&[10], &[])).unwrap();
+        assert_eq!(fees, eip1559(20, 0));
+
+        assert!(strategy(GasStrategyKind::Eip1559, None, None).eip1559_fees(&history(&[], &[])).is_err());
+    }
+
+    #[test]
+    fn test_gas_strategy_kind_from_str() {
+        assert_eq!(GasStrategyKind::from_str(" EIP1559 ").unwrap(), GasStrategyKind::Eip1559);
+        assert!(GasStrategyKind::from_str("dynamic").is_err());
+    }
+}
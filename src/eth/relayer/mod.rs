@@ -0,0 +1,20 @@
+mod block_window;
+mod external_relayer;
+mod external_relayer_config;
+mod gas_strategy;
+mod multicall;
+pub mod reconciliation;
+pub mod relay_status;
+mod relayer_server;
+mod relayer_signer;
+
+pub use block_window::BlockWindow;
+pub use external_relayer::ExternalRelayer;
+pub use external_relayer_config::ExternalRelayerServerConfig;
+pub use gas_strategy::GasFees;
+pub use gas_strategy::GasStrategy;
+pub use gas_strategy::GasStrategyKind;
+pub use multicall::Multicall;
+pub use relayer_server::serve_relayer_rpc;
+pub use relayer_signer::RelayedCall;
+pub use relayer_signer::RelayerSigner;
@@ -0,0 +1,152 @@
+use ethers_core::abi::encode;
+use ethers_core::abi::Token;
+use ethers_core::types::H160;
+use ethers_core::types::U256;
+use ethers_core::utils::keccak256;
+
+use crate::eth::primitives::ExternalTransaction;
+use crate::eth::relayer::RelayedCall;
+
+/// Signature of the Multicall3 function that executes calls with value, allowing each call to fail independently.
+const AGGREGATE3_VALUE: &str = "aggregate3Value((address,bool,uint256,bytes)[])";
+
+/// Batches transactions signed by the relayer into a single call to a Multicall3 contract in the target chain.
+///
+/// Calls are executed in the same order they were mined by Stratus, and each one is allowed to fail without reverting the others, so a
+/// batch has the same effects of sending its transactions one by one. Target contracts see the multicall contract as the sender, so they
+/// must trust it as ERC-2771 forwarder to read the original sender appended to the calldata (see [`RelayedCall`]).
+#[derive(Debug, Clone)]
+pub struct Multicall {
+    address: H160,
+    max_batch_size: usize,
+}
+
+impl Multicall {
+    pub fn new(address: H160, max_batch_size: usize) -> Self {
+        Self {
+            address,
+            max_batch_size: max_batch_size.max(1),
+        }
+    }
+
+    /// Splits the transactions of a block into consecutive batches of up to the max batch size.
+    ///
+    /// Contract creations cannot be executed by the multicall contract, so each one is a batch of its own.
+    pub fn batches<'a>(&self, txs: &'a [ExternalTransaction]) -> Vec<&'a [ExternalTransaction]> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        for (index, tx) in txs.iter().enumerate() {
+            let is_creation = tx.to.is_none();
+            if is_creation || index - start == self.max_batch_size {
+                if start < index {
+                    batches.push(&txs[start..index]);
+                }
+                start = index;
+            }
+            if is_creation {
+                batches.push(&txs[index..=index]);
+                start = index + 1;
+            }
+        }
+        if start < txs.len() {
+            batches.push(&txs[start..]);
+        }
+        batches
+    }
+
+    /// Creates the call to the multicall contract that executes all transactions of a batch.
+    pub fn aggregate(&self, txs: &[ExternalTransaction]) -> RelayedCall {
+        let calls: Vec<RelayedCall> = txs.iter().map(RelayedCall::new).collect();
+
+        let mut data = keccak256(AGGREGATE3_VALUE)[..4].to_vec();
+        let tokens = calls
+            .iter()
+            .map(|call| {
+                Token::Tuple(vec![
+                    Token::Address(call.to.unwrap_or_default()),
+                    Token::Bool(true),
+                    Token::Uint(call.value),
+                    Token::Bytes(call.data.clone()),
+                ])
+            })
+            .collect();
+        data.extend(encode(&[Token::Array(tokens)]));
+
+        // the gas of each transaction already covers the intrinsic cost of the batch
+        RelayedCall {
+            to: Some(self.address),
+            value: calls.iter().fold(U256::zero(), |sum, call| sum.saturating_add(call.value)),
+            data,
+            gas: calls.iter().fold(U256::zero(), |sum, call| sum.saturating_add(call.gas)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethers_core::abi::decode;
+    use ethers_core::abi::ParamType;
+
+    use super::*;
+    use crate::alias::EthersTransaction;
+
+    fn transaction(to: Option<u8>, value: u64) -> ExternalTransaction {
+        ExternalTransaction(EthersTransaction {
+            from: H160::repeat_byte(1),
+            to: to.map(H160::repeat_byte),
+            value: value.into(),
+            input: vec![0xaa].into(),
+            gas: 30_000.into(),
+            ..Default::default()
+        })
+    }
+
+    fn batch_sizes(multicall: &Multicall, txs: &[ExternalTransaction]) -> Vec<usize> {
+        multicall.batches(txs).iter().map(|batch| batch.len()).collect()
+    }
+
+    #[test]
+    fn test_batches_are_limited_by_size_and_creations() {
+        let multicall = Multicall::new(H160::repeat_byte(9), 2);
+        let call = transaction(Some(2), 0);
+        let creation = transaction(None, 0);
+
+        assert!(multicall.batches(&[]).is_empty());
+        assert_eq!(batch_sizes(&multicall, &[call.clone(), call.clone(), call.clone()]), vec![2, 1]);
+        assert_eq!(
+            batch_sizes(&multicall, &[call.clone(), creation.clone(), call.clone(), call.clone()]),
+            vec![1, 1, 2]
+        );
+        assert_eq!(batch_sizes(&multicall, &[creation.clone(), creation.clone()]), vec![1, 1]);
+        assert_eq!(batch_sizes(&multicall, &[call.clone(), call.clone(), creation]), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_aggregate_encodes_calls() {
+        let multicall = Multicall::new(H160::repeat_byte(9), 10);
+        let txs = [transaction(Some(2), 5), transaction(Some(3), 7)];
+        let call = multicall.aggregate(&txs);
+
+        assert_eq!(call.to, Some(H160::repeat_byte(9)));
+        assert_eq!(call.value, U256::from(12));
+        assert_eq!(call.gas, U256::from(2 * RelayedCall::new(&txs[0]).gas.as_u64()));
+        assert_eq!(call.data[..4], [0x17, 0x4d, 0xea, 0x71]);
+
+        let call_type = ParamType::Tuple(vec![ParamType::Address, ParamType::Bool, ParamType::Uint(256), ParamType::Bytes]);
+        let tokens = decode(&[ParamType::Array(Box::new(call_type))], &call.data[4..]).unwrap();
+        let Token::Array(calls) = &tokens[0] else { panic!("calls are not an array") };
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[1],
+            Token::Tuple(vec![
+                Token::Address(H160::repeat_byte(3)),
+                Token::Bool(true),
+                Token::Uint(7.into()),
+                Token::Bytes(RelayedCall::new(&txs[1]).data),
+            ])
+        );
+    }
+}
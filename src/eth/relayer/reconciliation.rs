@@ -0,0 +1,116 @@
+//! Reconciliation of relayed blocks with the blocks in Stratus.
+//!
+//! Stratus may lose blocks the relayer already relayed, for example when it is restored from an older backup. The reconciliation
+//! runs in two phases, so a block that is temporarily unavailable is not forgotten by the relayer:
+//!
+//! 1. A relayed block not found in Stratus is marked as orphaned.
+//! 2. In a later run, an orphaned block still not found after the grace period is deleted. If it was found again, it is unmarked.
+//!
+//! Deleted blocks are relayed again when Stratus mines them.
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+
+/// Relayed block checked by the reconciliation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayedBlock {
+    pub number: BlockNumber,
+    pub hash: Hash,
+
+    /// The block was not found in Stratus in a previous run.
+    pub orphaned: bool,
+
+    /// The block is orphaned for longer than the grace period.
+    pub grace_period_expired: bool,
+}
+
+/// Change applied to a relayed block by the reconciliation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum ReconciliationAction {
+    /// Block not found in Stratus is marked as orphaned.
+    #[strum(to_string = "mark")]
+    Mark,
+
+    /// Orphaned block found again in Stratus is unmarked.
+    #[strum(to_string = "restore")]
+    Restore,
+
+    /// Orphaned block still not found in Stratus after the grace period is deleted.
+    #[strum(to_string = "delete")]
+    Delete,
+}
+
+impl ReconciliationAction {
+    /// Decides the change to a relayed block given the hash of the block with the same number in Stratus, if any.
+    ///
+    /// Returns `None` when the block must be kept as it is.
+    pub fn decide(block: &RelayedBlock, stratus_hash: Option<Hash>) -> Option<Self> {
+        let found = stratus_hash == Some(block.hash);
+        match (found, block.orphaned) {
+            (true, false) => None,
+            (true, true) => Some(Self::Restore),
+            (false, false) => Some(Self::Mark),
+            (false, true) if block.grace_period_expired => Some(Self::Delete),
+            (false, true) => None,
+        }
+    }
+}
+
+/// Blocks changed by a reconciliation run.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ReconciliationReport {
+    /// The changes were only reported, not applied.
+    pub dry_run: bool,
+
+    /// Number of relayed blocks checked.
+    pub checked: u64,
+
+    pub marked: Vec<BlockNumber>,
+    pub restored: Vec<BlockNumber>,
+    pub deleted: Vec<BlockNumber>,
+}
+
+impl ReconciliationReport {
+    /// Records the change applied to a block.
+    pub fn push(&mut self, number: BlockNumber, action: ReconciliationAction) {
+        match action {
+            ReconciliationAction::Mark => self.marked.push(number),
+            ReconciliationAction::Restore => self.restored.push(number),
+            ReconciliationAction::Delete => self.deleted.push(number),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(orphaned: bool, grace_period_expired: bool) -> RelayedBlock {
+        RelayedBlock {
+            number: BlockNumber::from(1u64),
+            hash: Hash::new([1; 32]),
+            orphaned,
+            grace_period_expired,
+        }
+    }
+
+    #[test]
+    fn test_block_found_in_stratus_is_kept_or_restored() {
+        let hash = Some(Hash::new([1; 32]));
+        assert_eq!(ReconciliationAction::decide(&block(false, false), hash), None);
+        assert_eq!(ReconciliationAction::decide(&block(true, false), hash), Some(ReconciliationAction::Restore));
+        assert_eq!(ReconciliationAction::decide(&block(true, true), hash), Some(ReconciliationAction::Restore));
+    }
+
+    #[test]
+    fn test_block_not_found_in_stratus_is_marked_before_deleted() {
+        for hash in [None, Some(Hash::new([2; 32]))] {
+            assert_eq!(ReconciliationAction::decide(&block(false, false), hash), Some(ReconciliationAction::Mark));
+            assert_eq!(ReconciliationAction::decide(&block(true, false), hash), None);
+            assert_eq!(ReconciliationAction::decide(&block(true, true), hash), Some(ReconciliationAction::Delete));
+        }
+    }
+}
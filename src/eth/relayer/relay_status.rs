@@ -0,0 +1,163 @@
+//! Relay status of blocks, exposed by `stratus_relayerStatus`.
+//!
+//! A block is `pending` when it is read from Stratus, `relayed` when its transactions were sent to the target chain, and `confirmed`
+//! or `mismatched` when their receipts in the target chain were compared with the Stratus ones.
+
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+
+/// Relay status of a block.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockRelayStatus {
+    /// Read from Stratus, transactions not sent yet.
+    #[strum(to_string = "pending")]
+    Pending,
+
+    /// Transactions sent to the target chain, receipts not compared yet.
+    #[strum(to_string = "relayed")]
+    Relayed,
+
+    /// All receipts in the target chain match the Stratus ones.
+    #[strum(to_string = "confirmed")]
+    Confirmed,
+
+    /// Some receipt differs from the Stratus one or was not confirmed in time.
+    #[strum(to_string = "mismatched")]
+    Mismatched,
+}
+
+/// Relay status of a block and when it changed, as unix timestamps in seconds.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayedBlockStatus {
+    pub number: BlockNumber,
+    pub hash: Hash,
+    pub status: BlockRelayStatus,
+
+    /// Block timestamp in Stratus. Not known for blocks relayed before statuses were tracked.
+    pub mined_at: Option<u64>,
+
+    pub pending_at: u64,
+    pub relayed_at: Option<u64>,
+    pub checked_at: Option<u64>,
+}
+
+/// Number of relayed blocks by status.
+#[derive(DebugAsJson, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RelayStatusCounts {
+    pub pending: u64,
+    pub relayed: u64,
+    pub confirmed: u64,
+    pub mismatched: u64,
+}
+
+impl RelayStatusCounts {
+    pub fn add(&mut self, status: BlockRelayStatus, count: u64) {
+        match status {
+            BlockRelayStatus::Pending => self.pending += count,
+            BlockRelayStatus::Relayed => self.relayed += count,
+            BlockRelayStatus::Confirmed => self.confirmed += count,
+            BlockRelayStatus::Mismatched => self.mismatched += count,
+        }
+    }
+}
+
+/// End-to-end relay progress.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerStatus {
+    /// Last block mined by Stratus.
+    pub stratus_block: BlockNumber,
+
+    /// Last block whose receipts were compared.
+    pub checked_block: Option<BlockNumber>,
+
+    /// Number of blocks mined by Stratus whose receipts were not compared yet.
+    pub lag_blocks: u64,
+
+    /// Seconds from Stratus mining the last checked block until its receipts were compared.
+    pub lag_seconds: Option<u64>,
+
+    pub blocks: RelayStatusCounts,
+
+    /// Most recent relayed blocks, newest first.
+    pub recent: Vec<RelayedBlockStatus>,
+}
+
+impl RelayerStatus {
+    pub fn new(stratus_block: BlockNumber, blocks: RelayStatusCounts, recent: Vec<RelayedBlockStatus>) -> Self {
+        let checked = recent.iter().find(|block| block.checked_at.is_some());
+        let checked_block = checked.map(|block| block.number);
+        let lag_blocks = match checked_block {
+            Some(checked_block) => stratus_block.as_u64().saturating_sub(checked_block.as_u64()),
+            None => stratus_block.as_u64() + 1,
+        };
+        let lag_seconds = checked.and_then(|block| Some(block.checked_at?.saturating_sub(block.mined_at?)));
+
+        Self {
+            stratus_block,
+            checked_block,
+            lag_blocks,
+            lag_seconds,
+            blocks,
+            recent,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn block(number: u64, status: BlockRelayStatus, checked_at: Option<u64>) -> RelayedBlockStatus {
+        RelayedBlockStatus {
+            number: number.into(),
+            hash: Hash::new([1; 32]),
+            status,
+            mined_at: Some(100),
+            pending_at: 101,
+            relayed_at: Some(102),
+            checked_at,
+        }
+    }
+
+    #[test]
+    fn test_relayer_status_lag() {
+        let recent = vec![
+            block(9, BlockRelayStatus::Relayed, None),
+            block(8, BlockRelayStatus::Mismatched, Some(105)),
+            block(7, BlockRelayStatus::Confirmed, Some(104)),
+        ];
+        let status = RelayerStatus::new(10u64.into(), RelayStatusCounts::default(), recent);
+        assert_eq!(status.checked_block, Some(8u64.into()));
+        assert_eq!(status.lag_blocks, 2);
+        assert_eq!(status.lag_seconds, Some(5));
+
+        // nothing checked yet
+        let status = RelayerStatus::new(10u64.into(), RelayStatusCounts::default(), vec![]);
+        assert_eq!(status.checked_block, None);
+        assert_eq!(status.lag_blocks, 11);
+        assert_eq!(status.lag_seconds, None);
+    }
+
+    #[test]
+    fn test_block_relay_status_roundtrip() {
+        for status in [
+            BlockRelayStatus::Pending,
+            BlockRelayStatus::Relayed,
+            BlockRelayStatus::Confirmed,
+            BlockRelayStatus::Mismatched,
+        ] {
+            assert_eq!(BlockRelayStatus::from_str(&status.to_string()).unwrap(), status);
+            assert_eq!(serde_json::to_value(status).unwrap(), status.to_string());
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use jsonrpsee::server::middleware::http::ProxyGetRequestLayer;
+use jsonrpsee::server::RpcModule;
+use jsonrpsee::server::Server;
+use jsonrpsee::types::Params;
+use jsonrpsee::Extensions;
+use tokio::select;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::StratusError;
+use crate::eth::relayer::ExternalRelayer;
+use crate::ext::to_json_value;
+use crate::GlobalState;
+
+/// Context of the relayer JSON-RPC methods.
+struct RelayerRpcContext {
+    relayer: Arc<ExternalRelayer>,
+}
+
+/// Serves the relayer JSON-RPC methods until the application is shut down.
+///
+/// The relay status is also available as `GET /status` for dashboards and health checks.
+pub async fn serve_relayer_rpc(relayer: Arc<ExternalRelayer>, address: SocketAddr) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "relayer::rpc-server";
+    tracing::info!(%address, "creating relayer rpc server");
+
+    let mut module = RpcModule::new(RelayerRpcContext { relayer });
+    module.register_async_method("stratus_relayerStatus", stratus_relayer_status)?;
+
+    let http_middleware = tower::ServiceBuilder::new().layer(ProxyGetRequestLayer::new("/status", "stratus_relayerStatus")?);
+    let server = Server::builder().set_http_middleware(http_middleware).build(address).await?;
+    let handle = server.start(module);
+    let handle_watch = handle.clone();
+
+    select! {
+        _ = handle_watch.stopped() => {
+            GlobalState::shutdown_from(TASK_NAME, "finished unexpectedly");
+        },
+        _ = GlobalState::wait_shutdown_warn(TASK_NAME) => {
+            let _ = handle.stop();
+        }
+    }
+    handle.stopped().await;
+
+    Ok(())
+}
+
+async fn stratus_relayer_status(_: Params<'_>, ctx: Arc<RelayerRpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    Ok(to_json_value(ctx.relayer.status().await?))
+}
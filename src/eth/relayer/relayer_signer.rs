@@ -0,0 +1,199 @@
+use anyhow::Context;
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::Bytes as EthersBytes;
+use ethers_core::types::Eip1559TransactionRequest;
+use ethers_core::types::NameOrAddress;
+use ethers_core::types::Signature as EthersSignature;
+use ethers_core::types::TransactionRequest;
+use ethers_core::types::H160;
+use ethers_core::types::U256;
+use ethers_core::types::U64;
+use ethers_core::utils::keccak256;
+use ethers_core::utils::secret_key_to_address;
+
+use crate::eth::primitives::ExternalTransaction;
+use crate::eth::primitives::Hash;
+use crate::eth::relayer::GasFees;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+
+/// Gas paid for the calldata of the original sender appended to relayed calls.
+const SENDER_SUFFIX_GAS: u64 = 20 * 16;
+
+/// Call to be signed by the relayer on behalf of the sender of a transaction mined by Stratus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayedCall {
+    pub to: Option<H160>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub gas: U256,
+}
+
+impl RelayedCall {
+    /// Creates the call that executes the same of a transaction mined by Stratus.
+    ///
+    /// The original sender is appended to the calldata of contract calls as in ERC-2771, so target contracts that trust the relayer as
+    /// forwarder still see the original sender. Contract creations are relayed unchanged, because the suffix would be read as a
+    /// constructor argument.
+    pub fn new(tx: &ExternalTransaction) -> Self {
+        let mut data = tx.input.to_vec();
+        let mut gas = tx.gas;
+        if tx.to.is_some() {
+            data.extend_from_slice(tx.from.as_bytes());
+            gas = gas.saturating_add(SENDER_SUFFIX_GAS.into());
+        }
+        Self {
+            to: tx.to,
+            value: tx.value,
+            data,
+            gas,
+        }
+    }
+}
+
+/// Account of the relayer that signs relayed transactions, so their fees are priced by the relayer gas strategy instead of being fixed
+/// by the original signatures.
+pub struct RelayerSigner {
+    key: SigningKey,
+    address: H160,
+    chain_id: U64,
+}
+
+impl RelayerSigner {
+    /// Creates the signer from a hex encoded private key, reading the chain id from the target chain.
+    pub async fn new(private_key: &str, target: &BlockchainClient) -> anyhow::Result<Self> {
+        let key_bytes = const_hex::decode(private_key.trim()).context("failed to decode relayer private key")?;
+        let key = SigningKey::from_slice(&key_bytes).context("failed to parse relayer private key")?;
+        let address = secret_key_to_address(&key);
+
+        let chain_id = match target.call::<U64>("eth_chainId", [(); 0]).await {
+            Ok(chain_id) => chain_id,
+            Err(e) => return log_and_err!(reason = e, "failed to fetch target chain id"),
+        };
+        tracing::info!(?address, %chain_id, "relayed transactions will be signed by the relayer");
+
+        Ok(Self { key, address, chain_id })
+    }
+
+    /// Reads the next nonce of the relayer account, including transactions still pending in the target chain.
+    pub async fn fetch_nonce(&self, target: &BlockchainClient) -> anyhow::Result<U256> {
+        match target.call::<U256>("eth_getTransactionCount", (self.address, "pending")).await {
+            Ok(nonce) => Ok(nonce),
+            Err(e) => log_and_err!(reason = e, "failed to fetch relayer nonce"),
+        }
+    }
+
+    /// Signs a call with the specified nonce and fees, returning the encoded transaction and its hash.
+    pub fn sign(&self, call: RelayedCall, nonce: U256, fees: GasFees) -> anyhow::Result<(EthersBytes, Hash)> {
+        let to = call.to.map(NameOrAddress::Address);
+        let tx: TypedTransaction = match fees {
+            GasFees::Legacy { gas_price } => {
+                let mut request = TransactionRequest::new()
+                    .from(self.address)
+                    .nonce(nonce)
+                    .value(call.value)
+                    .data(call.data)
+                    .gas(call.gas)
+                    .gas_price(gas_price.0)
+                    .chain_id(self.chain_id);
+                request.to = to;
+                request.into()
+            }
+            GasFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let mut request = Eip1559TransactionRequest::new()
+                    .from(self.address)
+                    .nonce(nonce)
+                    .value(call.value)
+                    .data(call.data)
+                    .gas(call.gas)
+                    .max_fee_per_gas(max_fee_per_gas.0)
+                    .max_priority_fee_per_gas(max_priority_fee_per_gas.0)
+                    .chain_id(self.chain_id);
+                request.to = to;
+                request.into()
+            }
+        };
+
+        let sighash = tx.sighash();
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(sighash.as_bytes())
+            .context("failed to sign relayed transaction")?;
+        let signature = EthersSignature {
+            r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+            s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+            v: recovery_id.to_byte() as u64 + 35 + self.chain_id.as_u64() * 2,
+        };
+        let rlp = tx.rlp_signed(&signature);
+        let hash = Hash::new(keccak256(&rlp));
+        Ok((rlp, hash))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alias::EthersTransaction;
+
+    fn transaction(to: Option<H160>) -> ExternalTransaction {
+        ExternalTransaction(EthersTransaction {
+            from: H160::repeat_byte(1),
+            to,
+            value: 7.into(),
+            input: vec![0xaa, 0xbb].into(),
+            gas: 50_000.into(),
+            ..Default::default()
+        })
+    }
+
+    fn signer() -> RelayerSigner {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        RelayerSigner {
+            address: secret_key_to_address(&key),
+            key,
+            chain_id: 2008.into(),
+        }
+    }
+
+    #[test]
+    fn test_relayed_call_appends_sender() {
+        let call = RelayedCall::new(&transaction(Some(H160::repeat_byte(2))));
+        assert_eq!(call.data, [&[0xaa, 0xbb][..], &[1; 20][..]].concat());
+        assert_eq!(call.gas, U256::from(50_000 + SENDER_SUFFIX_GAS));
+        assert_eq!(call.value, U256::from(7));
+
+        // contract creation
+        let call = RelayedCall::new(&transaction(None));
+        assert_eq!(call.data, vec![0xaa, 0xbb]);
+        assert_eq!(call.gas, U256::from(50_000));
+    }
+
+    #[test]
+    fn test_signed_transaction_recovers_relayer() {
+        let signer = signer();
+        let call = RelayedCall::new(&transaction(Some(H160::repeat_byte(2))));
+        let fees = [
+            GasFees::Legacy { gas_price: 10u64.into() },
+            GasFees::Eip1559 {
+                max_fee_per_gas: 10u64.into(),
+                max_priority_fee_per_gas: 1u64.into(),
+            },
+        ];
+
+        for fees in fees {
+            let (rlp, hash) = signer.sign(call.clone(), 3.into(), fees).unwrap();
+            let decoded: EthersTransaction = rlp::decode(&rlp).unwrap();
+            assert_eq!(Hash::from(decoded.hash), hash);
+            assert_eq!(decoded.recover_from().unwrap(), signer.address);
+            assert_eq!(decoded.nonce, U256::from(3));
+            assert_eq!(decoded.chain_id, Some(U256::from(2008)));
+        }
+    }
+}
@@ -0,0 +1,7 @@
+with deleted as (
+    delete from relayer_blocks
+    where number = $1 and orphaned_at is not null
+    returning number
+)
+delete from relayer_mismatches
+where block_number in (select number from deleted);
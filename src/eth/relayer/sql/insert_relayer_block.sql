@@ -0,0 +1,11 @@
+insert into relayer_blocks(number, hash, mined_at)
+values ($1, $2, to_timestamp($3::bigint))
+on conflict (number) do update
+set
+    hash = excluded.hash,
+    mined_at = excluded.mined_at,
+    status = 'pending',
+    pending_at = now(),
+    relayed_at = null,
+    checked_at = null
+where relayer_blocks.status in ('pending', 'relayed');
@@ -0,0 +1,3 @@
+insert into relayer_mismatches(hash, block_number, stratus_receipt, target_receipt)
+values ($1, $2, $3, $4)
+on conflict do nothing;
@@ -0,0 +1,2 @@
+select max(number)
+from relayer_blocks;
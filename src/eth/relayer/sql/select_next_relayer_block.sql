@@ -0,0 +1,4 @@
+select coalesce(
+    (select min(number) from relayer_blocks where status in ('pending', 'relayed')),
+    (select max(number) + 1 from relayer_blocks)
+);
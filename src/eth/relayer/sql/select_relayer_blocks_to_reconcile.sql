@@ -0,0 +1,9 @@
+select
+    number,
+    hash as "hash: Hash",
+    orphaned_at is not null as "orphaned!",
+    coalesce(orphaned_at <= now() - make_interval(secs => $2), false) as "grace_period_expired!"
+from
+    relayer_blocks
+where number >= $1
+order by number;
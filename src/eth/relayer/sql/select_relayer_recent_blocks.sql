@@ -0,0 +1,11 @@
+select
+    number,
+    hash as "hash: Hash",
+    status,
+    extract(epoch from mined_at)::bigint as "mined_at",
+    extract(epoch from pending_at)::bigint as "pending_at!",
+    extract(epoch from relayed_at)::bigint as "relayed_at",
+    extract(epoch from checked_at)::bigint as "checked_at"
+from relayer_blocks
+order by number desc
+limit $1;
@@ -0,0 +1,5 @@
+select
+    status as "status!",
+    count(*) as "count!"
+from relayer_blocks
+group by status;
@@ -0,0 +1,3 @@
+update relayer_blocks
+set status = $2, checked_at = now()
+where number = $1;
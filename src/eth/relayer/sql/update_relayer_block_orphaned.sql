@@ -0,0 +1,3 @@
+update relayer_blocks
+set orphaned_at = now()
+where number = $1 and orphaned_at is null;
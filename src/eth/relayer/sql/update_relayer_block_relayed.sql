@@ -0,0 +1,3 @@
+update relayer_blocks
+set status = 'relayed', relayed_at = now()
+where number = $1;
@@ -0,0 +1,3 @@
+update relayer_blocks
+set orphaned_at = null
+where number = $1;
@@ -1,19 +1,52 @@
 //! Ethereum JSON-RPC server.
 
+mod rpc_audit;
+mod rpc_capture;
 mod rpc_client_app;
 mod rpc_config;
 mod rpc_context;
+mod rpc_fee_oracle;
+#[cfg(feature = "graphql")]
+mod rpc_graphql;
+#[cfg(feature = "grpc")]
+mod rpc_grpc;
 mod rpc_http_middleware;
+mod rpc_lanes;
 mod rpc_method_wrapper;
 mod rpc_middleware;
 mod rpc_parser;
 mod rpc_server;
 mod rpc_subscriptions;
+mod rpc_tx_policy;
+mod rpc_tx_validation;
+mod rpc_user_operations;
 
+pub use rpc_audit::AuditEntry;
+pub use rpc_audit::RpcAuditLog;
+pub use rpc_capture::CapturedRequest;
+pub use rpc_capture::RpcCapture;
 pub use rpc_client_app::RpcClientApp;
 pub use rpc_config::RpcServerConfig;
 pub use rpc_context::RpcContext;
+pub use rpc_fee_oracle::FeeOracle;
+pub use rpc_fee_oracle::FeeOracleConfig;
+pub use rpc_fee_oracle::FeeOracleKind;
+#[cfg(feature = "graphql")]
+use rpc_graphql::execute_graphql;
+#[cfg(feature = "graphql")]
+use rpc_graphql::graphql_schema;
+#[cfg(feature = "graphql")]
+use rpc_graphql::GraphqlSchema;
+#[cfg(feature = "graphql")]
+use rpc_graphql::RpcGraphqlMiddleware;
+#[cfg(feature = "grpc")]
+pub use rpc_grpc::proto as grpc;
+#[cfg(feature = "grpc")]
+use rpc_grpc::serve_grpc;
 use rpc_http_middleware::RpcHttpMiddleware;
+use rpc_lanes::RpcLane;
+use rpc_lanes::RpcLaneWorker;
+use rpc_lanes::RpcLanes;
 use rpc_middleware::RpcMiddleware;
 use rpc_parser::next_rpc_param;
 use rpc_parser::next_rpc_param_or_default;
@@ -21,3 +54,6 @@ use rpc_parser::parse_rpc_rlp;
 use rpc_server::reject_unknown_client;
 pub use rpc_server::serve_rpc;
 pub use rpc_subscriptions::RpcSubscriptions;
+pub use rpc_tx_policy::TransactionPolicyConfig;
+use rpc_tx_validation::validate_raw_transaction;
+pub use rpc_user_operations::RpcUserOperationPool;
@@ -0,0 +1,152 @@
+//! Append-only audit log of admin operations received through RPC.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+
+use anyhow::Context;
+use chrono::DateTime;
+use chrono::Utc;
+use parking_lot::Mutex;
+
+use crate::alias::JsonValue;
+use crate::eth::rpc::RpcClientApp;
+use crate::ext::to_json_string;
+
+/// Methods that change the node state or configuration and are recorded in the audit log.
+const AUDITED_METHODS: [&str; 16] = [
+    // dev
+    "evm_setNextBlockTimestamp",
+    "evm_mine",
+    "hardhat_reset",
+    "stratus_reset",
+    // admin
+    "stratus_enableTransactions",
+    "stratus_disableTransactions",
+    "stratus_enableMiner",
+    "stratus_disableMiner",
+    "stratus_enableUnknownClients",
+    "stratus_disableUnknownClients",
+    "stratus_changeToLeader",
+    "stratus_changeToFollower",
+    "stratus_initImporter",
+    "stratus_shutdownImporter",
+    "stratus_changeMinerMode",
+    "stratus_setStorageFaults",
+];
+
+/// Admin operation with its caller and result, written as a JSON line.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuditEntry {
+    /// When the operation was received.
+    pub timestamp: DateTime<Utc>,
+
+    /// Client that called the operation.
+    pub client: String,
+
+    pub method: String,
+
+    pub params: Option<JsonValue>,
+
+    /// Result of the operation, if it succeeded.
+    pub result: Option<JsonValue>,
+
+    /// Error of the operation, if it failed.
+    pub error: Option<JsonValue>,
+}
+
+impl AuditEntry {
+    pub fn new(client: &RpcClientApp, method: String, params: Option<JsonValue>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            client: client.to_string(),
+            method,
+            params,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+/// Appends admin operations to a file that is never rewritten, and reads them back in pages.
+#[derive(Debug)]
+pub struct RpcAuditLog {
+    path: String,
+    file: Mutex<File>,
+}
+
+impl RpcAuditLog {
+    /// Opens the audit file, creating it if necessary.
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        tracing::info!(%path, "recording admin operations in audit log");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open audit log file {}", path))?;
+        Ok(Self {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Checks if calls to a method are recorded.
+    pub fn is_audited(method: &str) -> bool {
+        AUDITED_METHODS.contains(&method)
+    }
+
+    /// Writes an entry and flushes it before returning, so admin operations are not lost.
+    pub fn record(&self, entry: &AuditEntry) {
+        let mut file = self.file.lock();
+        if let Err(e) = writeln!(file, "{}", to_json_string(entry)).and_then(|_| file.flush()) {
+            tracing::error!(reason = ?e, method = %entry.method, client = %entry.client, "failed to write audit log entry");
+        }
+    }
+
+    /// Reads up to `limit` entries starting at `offset`, in the order they were recorded.
+    pub fn read(&self, offset: usize, limit: usize) -> anyhow::Result<Vec<AuditEntry>> {
+        // hold the lock so a partially written entry is not read
+        let _file = self.file.lock();
+        let file = File::open(&self.path).with_context(|| format!("failed to open audit log file {}", self.path))?;
+
+        let mut entries = Vec::with_capacity(limit);
+        for line in BufReader::new(file).lines().skip(offset).take(limit) {
+            let line = line.context("failed to read audit log entry")?;
+            let entry = serde_json::from_str::<AuditEntry>(&line).context("failed to parse audit log entry")?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_read_pages() {
+        let path = std::env::temp_dir().join(format!("stratus-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let audit = RpcAuditLog::open(path.to_str().unwrap()).unwrap();
+
+        for method in ["stratus_enableMiner", "stratus_disableMiner", "stratus_reset"] {
+            let mut entry = AuditEntry::new(&RpcClientApp::Unknown, method.to_owned(), None);
+            entry.result = Some(JsonValue::Bool(true));
+            audit.record(&entry);
+        }
+
+        let page = audit.read(1, 10).unwrap();
+        assert_eq!(
+            page.iter().map(|entry| entry.method.as_str()).collect::<Vec<_>>(),
+            ["stratus_disableMiner", "stratus_reset"]
+        );
+        assert!(audit.read(3, 10).unwrap().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
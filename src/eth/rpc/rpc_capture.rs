@@ -0,0 +1,88 @@
+//! Capture of RPC requests and responses, so they can be replayed against another node with `rpc-replayer`.
+
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::mpsc::TrySendError;
+
+use anyhow::Context;
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::alias::JsonValue;
+use crate::ext::spawn_thread;
+use crate::ext::to_json_string;
+use crate::infra::tracing::warn_task_rx_closed;
+
+/// Max number of captured requests waiting to be written. Requests captured when it is full are dropped instead of slowing down the server.
+const CAPTURE_BUFFER_SIZE: usize = 10_000;
+
+/// RPC request and its response, written as a JSON line.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapturedRequest {
+    /// When the request was received.
+    pub timestamp: DateTime<Utc>,
+
+    pub method: String,
+
+    pub params: Option<JsonValue>,
+
+    /// Result of the response, if it succeeded.
+    pub result: Option<JsonValue>,
+
+    /// Error of the response, if it failed.
+    pub error: Option<JsonValue>,
+}
+
+/// Appends captured requests to a file in background.
+#[derive(Debug)]
+pub struct RpcCapture {
+    tx: mpsc::SyncSender<CapturedRequest>,
+}
+
+impl RpcCapture {
+    /// Opens the capture file and spawns the thread that writes to it.
+    pub fn spawn(path: &str) -> anyhow::Result<Self> {
+        const TASK_NAME: &str = "rpc::capture";
+        tracing::info!(%path, "capturing rpc requests");
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open rpc capture file {}", path))?;
+        let mut writer = BufWriter::new(file);
+
+        let (tx, rx) = mpsc::sync_channel::<CapturedRequest>(CAPTURE_BUFFER_SIZE);
+        spawn_thread(TASK_NAME, move || {
+            while let Ok(request) = rx.recv() {
+                // write all buffered requests before flushing
+                for request in std::iter::once(request).chain(rx.try_iter()) {
+                    if let Err(e) = writeln!(writer, "{}", to_json_string(&request)) {
+                        tracing::error!(reason = ?e, "failed to write captured rpc request");
+                    }
+                }
+                if let Err(e) = writer.flush() {
+                    tracing::error!(reason = ?e, "failed to flush captured rpc requests");
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+        });
+
+        Ok(Self { tx })
+    }
+
+    /// Enqueues a request to be written without blocking.
+    pub fn capture(&self, request: CapturedRequest) {
+        match self.tx.try_send(request) {
+            Ok(_) => {}
+            Err(TrySendError::Full(request)) => {
+                tracing::warn!(method = %request.method, "rpc capture buffer is full, dropping captured request");
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                tracing::error!("rpc capture writer stopped, dropping captured request");
+            }
+        }
+    }
+}
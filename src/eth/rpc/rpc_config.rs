@@ -1,14 +1,26 @@
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::eth::analytics::StateStatsConfig;
+use crate::eth::primitives::Address;
+use crate::eth::rpc::FeeOracleConfig;
+use crate::eth::rpc::TransactionPolicyConfig;
+use crate::ext::parse_duration;
+
 #[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
 pub struct RpcServerConfig {
     /// JSON-RPC server binding address.
     #[arg(short = 'a', long = "address", env = "ADDRESS", default_value = "0.0.0.0:3000")]
     pub rpc_address: SocketAddr,
 
+    /// gRPC query server binding address. When not set, the gRPC server is not started.
+    #[cfg(feature = "grpc")]
+    #[arg(long = "grpc-address", env = "GRPC_ADDRESS")]
+    pub grpc_address: Option<SocketAddr>,
+
     /// JSON-RPC server max active connections
     #[arg(long = "max-connections", env = "MAX_CONNECTIONS", default_value = "400")]
     pub rpc_max_connections: u32,
@@ -16,4 +28,70 @@ pub struct RpcServerConfig {
     /// JSON-RPC server max active subscriptions per client.
     #[arg(long = "max-subscriptions", env = "MAX_SUBSCRIPTIONS", default_value = "30")]
     pub rpc_max_subscriptions: u32,
+
+    /// Max notifications queued for each subscriber. When the queue is full, new notifications are dropped and the subscriber is marked as lagging.
+    #[arg(long = "rpc-subscription-queue-size", env = "RPC_SUBSCRIPTION_QUEUE_SIZE", default_value = "256")]
+    pub rpc_subscription_queue_size: usize,
+
+    /// Max requests executing concurrently in the high-priority lane, used by transactions and consensus-critical calls.
+    ///
+    /// Together with the normal lane workers, it should not exceed the blocking threads, so these requests always find a free thread.
+    #[arg(long = "rpc-high-priority-workers", env = "RPC_HIGH_PRIORITY_WORKERS", default_value = "64")]
+    pub rpc_high_priority_workers: usize,
+
+    /// Max requests executing concurrently in the normal lane, used by all other requests.
+    #[arg(long = "rpc-normal-priority-workers", env = "RPC_NORMAL_PRIORITY_WORKERS", default_value = "384")]
+    pub rpc_normal_priority_workers: usize,
+
+    /// Max time executing a single request. Requests that take longer fail with a timeout error and release the connection, but their execution is
+    /// not interrupted.
+    #[arg(long = "rpc-request-timeout", value_parser=parse_duration, env = "RPC_REQUEST_TIMEOUT", default_value = "60s")]
+    pub rpc_request_timeout: Duration,
+
+    /// Keep HTTP connections open between requests. When disabled, HTTP connections are closed after each response.
+    #[arg(long = "rpc-http-keep-alive", env = "RPC_HTTP_KEEP_ALIVE", default_value = "true")]
+    pub rpc_http_keep_alive: bool,
+
+    /// Max time an HTTP connection is reused. After it, the connection is closed after the next response and the client must reconnect.
+    #[arg(long = "rpc-max-connection-lifetime", value_parser=parse_duration, env = "RPC_MAX_CONNECTION_LIFETIME")]
+    pub rpc_max_connection_lifetime: Option<Duration>,
+
+    /// Interval between pings sent to websocket connections to keep them alive.
+    #[arg(long = "rpc-ws-ping-interval", value_parser=parse_duration, env = "RPC_WS_PING_INTERVAL", default_value = "30s")]
+    pub rpc_ws_ping_interval: Duration,
+
+    /// Websocket connections that do not answer pings for this long are closed.
+    #[arg(long = "rpc-ws-inactive-limit", value_parser=parse_duration, env = "RPC_WS_INACTIVE_LIMIT", default_value = "90s")]
+    pub rpc_ws_inactive_limit: Duration,
+
+    /// Reject legacy transactions without chain id (pre EIP-155), which can be replayed in other chains.
+    ///
+    /// Disabled by default because existing clients may still send them.
+    #[arg(long = "rpc-reject-unprotected-txs", env = "RPC_REJECT_UNPROTECTED_TXS", default_value = "false")]
+    pub rpc_reject_unprotected_txs: bool,
+
+    /// Max size in bytes of raw transactions sent with `eth_sendRawTransaction`. Checked before the transaction is decoded.
+    #[arg(long = "rpc-max-transaction-size", env = "RPC_MAX_TRANSACTION_SIZE", default_value = "131072")]
+    pub rpc_max_transaction_size: usize,
+
+    /// Account abstraction entry points accepted by user operation methods.
+    #[arg(long = "rpc-entry-points", env = "RPC_ENTRY_POINTS", value_delimiter = ',')]
+    pub rpc_entry_points: Vec<Address>,
+
+    /// File where RPC requests and their responses are appended as JSON lines, so they can be replayed against another node with `rpc-replayer`.
+    #[arg(long = "rpc-capture-file", env = "RPC_CAPTURE_FILE")]
+    pub rpc_capture_file: Option<String>,
+
+    /// Append-only file where admin operations are recorded with their caller, parameters and result. Queried with `stratus_getAuditLog`.
+    #[arg(long = "rpc-audit-file", env = "RPC_AUDIT_FILE")]
+    pub rpc_audit_file: Option<String>,
+
+    #[clap(flatten)]
+    pub fee_oracle: FeeOracleConfig,
+
+    #[clap(flatten)]
+    pub tx_policy: TransactionPolicyConfig,
+
+    #[clap(flatten)]
+    pub state_stats: StateStatsConfig,
 }
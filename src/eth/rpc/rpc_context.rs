@@ -4,32 +4,45 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 
 use crate::alias::JsonValue;
+use crate::eth::analytics::StateAnalytics;
 use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
 use crate::eth::miner::Miner;
-use crate::eth::primitives::ChainId;
+use crate::eth::primitives::ChainSpec;
 use crate::eth::rpc::rpc_subscriptions::RpcSubscriptionsConnected;
+use crate::eth::rpc::FeeOracle;
+#[cfg(feature = "graphql")]
+use crate::eth::rpc::GraphqlSchema;
+use crate::eth::rpc::RpcAuditLog;
 use crate::eth::rpc::RpcServerConfig;
-use crate::eth::storage::StratusStorage;
+use crate::eth::rpc::RpcUserOperationPool;
+use crate::eth::storage::StorageReader;
+use crate::ChainState;
 
 pub struct RpcContext {
     // app config
     pub app_config: JsonValue,
 
     // blockchain config
-    pub chain_id: ChainId,
+    pub chain: ChainSpec,
+    pub chain_state: Arc<ChainState>,
     pub client_version: &'static str,
 
     // gas config
-    pub gas_price: usize,
+    pub fee_oracle: FeeOracle,
 
     // services
     pub executor: Arc<Executor>,
     pub miner: Arc<Miner>,
-    pub storage: Arc<StratusStorage>,
+    pub storage: Arc<dyn StorageReader>,
     pub consensus: RwLock<Option<Arc<dyn Consensus>>>,
     pub rpc_server: RpcServerConfig,
     pub subs: Arc<RpcSubscriptionsConnected>,
+    pub user_operations: Arc<RpcUserOperationPool>,
+    pub state_stats: Option<Arc<StateAnalytics>>,
+    pub audit: Option<Arc<RpcAuditLog>>,
+    #[cfg(feature = "graphql")]
+    pub graphql: GraphqlSchema,
 }
 
 impl RpcContext {
@@ -45,9 +58,8 @@ impl RpcContext {
 impl Debug for RpcContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RpcContext")
-            .field("chain_id", &self.chain_id)
+            .field("chain", &self.chain)
             .field("client_version", &self.client_version)
-            .field("gas_price", &self.gas_price)
             .finish_non_exhaustive()
     }
 }
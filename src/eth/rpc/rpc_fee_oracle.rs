@@ -0,0 +1,164 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use clap::Parser;
+use display_json::DebugAsJson;
+use parking_lot::Mutex;
+
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::StorageReader;
+
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct FeeOracleConfig {
+    /// How gas prices suggested to clients are calculated.
+    #[arg(long = "fee-oracle", env = "FEE_ORACLE", default_value = "fixed")]
+    pub fee_oracle: FeeOracleKind,
+
+    /// Gas price suggested when using the fixed oracle.
+    #[arg(long = "fee-oracle-gas-price", env = "FEE_ORACLE_GAS_PRICE", default_value = "0")]
+    pub fee_oracle_gas_price: u64,
+
+    /// Priority fee suggested when using the fixed oracle.
+    #[arg(long = "fee-oracle-priority-fee", env = "FEE_ORACLE_PRIORITY_FEE", default_value = "0")]
+    pub fee_oracle_priority_fee: u64,
+
+    /// Number of recent blocks sampled when using the blocks oracle.
+    #[arg(long = "fee-oracle-blocks", env = "FEE_ORACLE_BLOCKS", default_value = "20")]
+    pub fee_oracle_blocks: u64,
+
+    /// Percentile of the sampled gas prices suggested when using the blocks oracle.
+    #[arg(long = "fee-oracle-percentile", env = "FEE_ORACLE_PERCENTILE", default_value = "60")]
+    pub fee_oracle_percentile: u8,
+}
+
+impl FeeOracleConfig {
+    /// Initializes the fee oracle.
+    pub fn init(&self, storage: Arc<dyn StorageReader>) -> anyhow::Result<FeeOracle> {
+        tracing::info!(config = ?self, "creating fee oracle");
+        if self.fee_oracle_percentile > 100 {
+            return Err(anyhow!("fee oracle percentile must be between 0 and 100"));
+        }
+        Ok(FeeOracle {
+            config: self.clone(),
+            storage,
+            cache: Mutex::new(None),
+        })
+    }
+}
+
+#[derive(DebugAsJson, Clone, Copy, serde::Serialize)]
+pub enum FeeOracleKind {
+    /// Always suggests the configured values. Used by zero-fee chains.
+    #[serde(rename = "fixed")]
+    Fixed,
+
+    /// Suggests a percentile of the gas prices paid in recent blocks.
+    #[serde(rename = "blocks")]
+    Blocks,
+}
+
+impl FromStr for FeeOracleKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fixed" => Ok(Self::Fixed),
+            "blocks" => Ok(Self::Blocks),
+            s => Err(anyhow!("unknown fee oracle: {}", s)),
+        }
+    }
+}
+
+/// Fees suggested to clients building transactions.
+#[derive(DebugAsJson, Clone, Copy, serde::Serialize)]
+pub struct FeeSuggestion {
+    pub gas_price: Wei,
+    pub max_priority_fee_per_gas: Wei,
+}
+
+/// Suggests fees for `eth_gasPrice` and `eth_maxPriorityFeePerGas`.
+pub struct FeeOracle {
+    config: FeeOracleConfig,
+    storage: Arc<dyn StorageReader>,
+
+    /// Last suggestion and the mined block it was calculated from.
+    cache: Mutex<Option<(BlockNumber, FeeSuggestion)>>,
+}
+
+impl FeeOracle {
+    /// Returns the current fee suggestion.
+    pub fn suggest(&self) -> Result<FeeSuggestion, StratusError> {
+        match self.config.fee_oracle {
+            FeeOracleKind::Fixed => Ok(FeeSuggestion {
+                gas_price: self.config.fee_oracle_gas_price.into(),
+                max_priority_fee_per_gas: self.config.fee_oracle_priority_fee.into(),
+            }),
+            FeeOracleKind::Blocks => self.suggest_from_blocks(),
+        }
+    }
+
+    fn suggest_from_blocks(&self) -> Result<FeeSuggestion, StratusError> {
+        let mined_number = self.storage.read_mined_block_number()?;
+        if let Some((cached_number, suggestion)) = *self.cache.lock() {
+            if cached_number == mined_number {
+                return Ok(suggestion);
+            }
+        }
+
+        // collect gas prices from the most recent blocks
+        let mut gas_prices = Vec::new();
+        let mut number = mined_number;
+        for _ in 0..self.config.fee_oracle_blocks {
+            if let Some(block) = self.storage.read_block(BlockFilter::Number(number))? {
+                gas_prices.extend(block.transactions.iter().map(|tx| tx.input.gas_price));
+            }
+            match number.prev() {
+                Some(prev) => number = prev,
+                None => break,
+            }
+        }
+
+        // without fees, base fee is always zero, so the whole gas price is the priority fee
+        let gas_price = percentile(gas_prices, self.config.fee_oracle_percentile).unwrap_or(self.config.fee_oracle_gas_price.into());
+        let suggestion = FeeSuggestion {
+            gas_price,
+            max_priority_fee_per_gas: gas_price,
+        };
+        tracing::debug!(%mined_number, ?suggestion, "calculated fee suggestion from blocks");
+
+        *self.cache.lock() = Some((mined_number, suggestion));
+        Ok(suggestion)
+    }
+}
+
+/// Returns the value at the specified percentile, or `None` if there are no values.
+fn percentile(mut values: Vec<Wei>, percentile: u8) -> Option<Wei> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let index = (values.len() - 1) * percentile as usize / 100;
+    Some(values[index])
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::percentile;
+    use crate::eth::primitives::Wei;
+
+    #[test]
+    fn test_fee_oracle_percentile() {
+        let values = (1..=10u64).rev().map(Wei::from).collect::<Vec<_>>();
+        assert_eq!(percentile(values.clone(), 0), Some(Wei::from(1u64)));
+        assert_eq!(percentile(values.clone(), 50), Some(Wei::from(5u64)));
+        assert_eq!(percentile(values.clone(), 100), Some(Wei::from(10u64)));
+        assert_eq!(percentile(vec![], 50), None);
+    }
+}
@@ -0,0 +1,721 @@
+//! GraphQL endpoint compatible with a subset of EIP-1767.
+//!
+//! It is served by the JSON-RPC HTTP server in the `/graphql` path and exposes blocks, transactions, logs and accounts, so explorers can read
+//! nested data in a single request instead of many JSON-RPC round-trips. Blocks are read once with all their transactions and logs, so nested
+//! fields are resolved from memory and only accounts and links to other blocks or transactions read the storage again.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_graphql::parser::parse_query;
+use async_graphql::parser::types::ExecutableDocument;
+use async_graphql::parser::types::Selection;
+use async_graphql::parser::types::SelectionSet;
+use async_graphql::Context;
+use async_graphql::EmptyMutation;
+use async_graphql::EmptySubscription;
+use async_graphql::InputObject;
+use async_graphql::InputValueError;
+use async_graphql::InputValueResult;
+use async_graphql::Object;
+use async_graphql::Scalar;
+use async_graphql::ScalarType;
+use async_graphql::Schema;
+use async_graphql::ServerError;
+use async_graphql::Value;
+use ethereum_types::H256;
+use futures::TryFutureExt;
+use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_TYPE;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use http::Uri;
+use http_body_util::BodyExt;
+use http_body_util::Limited;
+use jsonrpsee::core::BoxError;
+use jsonrpsee::server::HttpBody;
+use jsonrpsee::server::HttpRequest;
+use jsonrpsee::server::HttpResponse;
+use serde_json::json;
+use tokio::runtime::Handle;
+use tower::Service;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogFilterInput;
+use crate::eth::primitives::LogFilterInputTopic;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::LogTopic;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::TransactionStage;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::StorageReader;
+use crate::ext::to_json_string;
+use crate::ext::to_json_value;
+
+/// Path where GraphQL requests are served.
+const GRAPHQL_PATH: &str = "/graphql";
+
+/// JSON-RPC method that executes GraphQL requests, so they go through the same middleware of all other requests.
+const GRAPHQL_RPC_METHOD: &str = "stratus_graphql";
+
+/// Max size of a GraphQL request body.
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Max nesting of fields in a single query.
+const MAX_QUERY_DEPTH: usize = 10;
+
+/// Max number of fields in a single query, counting each field once per occurrence.
+const MAX_QUERY_COMPLEXITY: usize = 200;
+
+/// Max number of aliased fields in a single query, so the same expensive field cannot be requested many times.
+const MAX_QUERY_ALIASES: usize = 30;
+
+/// Max number of blocks returned by the `blocks` query.
+const MAX_BLOCKS: u64 = 100;
+
+/// Max number of blocks scanned by the `logs` query, the same limit of `eth_getLogs`.
+const MAX_LOGS_BLOCK_RANGE: u64 = 5_000;
+
+pub type GraphqlSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// Creates the GraphQL schema that reads data from the storage.
+pub fn graphql_schema(storage: Arc<dyn StorageReader>) -> GraphqlSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(storage)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Executes a GraphQL request after checking the limits not enforced by the schema itself.
+///
+/// Resolvers read the storage synchronously, so it must be called from a blocking thread.
+pub fn execute_graphql(schema: &GraphqlSchema, request: async_graphql::Request) -> async_graphql::Response {
+    if let Ok(document) = parse_query(&request.query) {
+        let aliases = count_aliases(&document);
+        if aliases > MAX_QUERY_ALIASES {
+            let message = format!("query has {} aliases, but the limit is {}", aliases, MAX_QUERY_ALIASES);
+            return async_graphql::Response::from_errors(vec![ServerError::new(message, None)]);
+        }
+    }
+    Handle::current().block_on(schema.execute(request))
+}
+
+/// Counts aliased fields in all operations and fragments of a query.
+fn count_aliases(document: &ExecutableDocument) -> usize {
+    fn count(selection_set: &SelectionSet) -> usize {
+        selection_set
+            .items
+            .iter()
+            .map(|selection| match &selection.node {
+                Selection::Field(field) => usize::from(field.node.alias.is_some()) + count(&field.node.selection_set.node),
+                Selection::InlineFragment(fragment) => count(&fragment.node.selection_set.node),
+                Selection::FragmentSpread(_) => 0,
+            })
+            .sum()
+    }
+
+    let operations: usize = document.operations.iter().map(|(_, operation)| count(&operation.node.selection_set.node)).sum();
+    let fragments: usize = document.fragments.values().map(|fragment| count(&fragment.node.selection_set.node)).sum();
+    operations + fragments
+}
+
+// -----------------------------------------------------------------------------
+// Middleware
+// -----------------------------------------------------------------------------
+
+/// Middleware of HTTP requests that converts GraphQL requests into JSON-RPC requests and forwards all requests to the JSON-RPC server.
+///
+/// GraphQL requests are executed by the `stratus_graphql` method, so they are subject to the same client identification, lanes,
+/// timeout and metrics of the JSON-RPC middleware.
+#[derive(Clone)]
+pub struct RpcGraphqlMiddleware<S> {
+    service: S,
+}
+
+impl<S> RpcGraphqlMiddleware<S> {
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+impl<S> Service<HttpRequest<HttpBody>> for RpcGraphqlMiddleware<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + 'static,
+    S::Error: Into<BoxError> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: HttpRequest<HttpBody>) -> Self::Future {
+        if request.uri().path() != GRAPHQL_PATH || request.method() != Method::POST {
+            return Box::pin(self.service.call(request).map_err(Into::into));
+        }
+
+        // the ready service is taken and a clone is left in its place, as the request is forwarded only after its body is read
+        let clone = self.service.clone();
+        let mut service = std::mem::replace(&mut self.service, clone);
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let body = Limited::new(body, MAX_REQUEST_SIZE).collect().await?.to_bytes();
+            let query = match serde_json::from_slice::<JsonValue>(&body) {
+                Ok(query) => query,
+                Err(e) => return graphql_response(&graphql_error(format!("invalid graphql request: {}", e))),
+            };
+
+            // forward as a JSON-RPC request keeping the extensions that identify the client
+            let rpc_request = json!({ "jsonrpc": "2.0", "id": 0, "method": GRAPHQL_RPC_METHOD, "params": [query] });
+            parts.uri = Uri::from_static("/");
+            parts.headers.remove(CONTENT_LENGTH);
+            parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            let rpc_request = HttpRequest::from_parts(parts, HttpBody::from(to_json_string(&rpc_request)));
+            let rpc_response = service.call(rpc_request).await.map_err(Into::into)?;
+
+            // unwrap the GraphQL response from the JSON-RPC response
+            let rpc_response = rpc_response.into_body().collect().await?.to_bytes();
+            let response = match serde_json::from_slice::<JsonValue>(&rpc_response) {
+                Ok(mut rpc_response) => match rpc_response.get_mut("result") {
+                    Some(result) => result.take(),
+                    None => {
+                        let message = rpc_response.pointer("/error/message").and_then(JsonValue::as_str).unwrap_or("unknown error");
+                        graphql_error(message)
+                    }
+                },
+                Err(e) => graphql_error(format!("invalid json-rpc response: {}", e)),
+            };
+            graphql_response(&response)
+        })
+    }
+}
+
+/// Creates a GraphQL response with a single error and no data.
+fn graphql_error(message: impl Into<String>) -> JsonValue {
+    to_json_value(async_graphql::Response::from_errors(vec![ServerError::new(message, None)]))
+}
+
+fn graphql_response(response: &JsonValue) -> Result<HttpResponse, BoxError> {
+    let response = HttpResponse::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(HttpBody::from(to_json_string(response)))?;
+    Ok(response)
+}
+
+// -----------------------------------------------------------------------------
+// Scalars
+// -----------------------------------------------------------------------------
+
+async_graphql::scalar!(Address, "Address", "20 byte Ethereum address, encoded as 0x-prefixed hexadecimal.");
+async_graphql::scalar!(Hash, "Bytes32", "32 byte binary string, encoded as 0x-prefixed hexadecimal.");
+async_graphql::scalar!(Bytes, "Bytes", "Arbitrary length binary string, encoded as 0x-prefixed hexadecimal.");
+async_graphql::scalar!(Wei, "BigInt", "Large integer, encoded as 0x-prefixed hexadecimal.");
+
+/// 64 bit unsigned integer, encoded as 0x-prefixed hexadecimal. Also accepts JSON numbers as input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Long(pub u64);
+
+#[Scalar(name = "Long")]
+impl ScalarType for Long {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let parsed = match &value {
+            Value::Number(number) => number.as_u64(),
+            Value::String(hex) => hex.strip_prefix("0x").and_then(|hex| u64::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        parsed.map(Long).ok_or_else(|| InputValueError::expected_type(value))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(format!("{:#x}", self.0))
+    }
+}
+
+impl From<Long> for BlockNumber {
+    fn from(value: Long) -> Self {
+        BlockNumber::from(value.0)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Inputs
+// -----------------------------------------------------------------------------
+
+/// Filter of logs across a range of blocks.
+#[derive(InputObject)]
+pub struct FilterCriteria {
+    /// First block of the range. Defaults to the latest mined block.
+    from_block: Option<Long>,
+
+    /// Last block of the range. Defaults to the latest mined block.
+    to_block: Option<Long>,
+
+    /// Addresses that emitted the logs. Empty matches any address.
+    addresses: Option<Vec<Address>>,
+
+    /// Topics of the logs by position, with the same rules of `eth_getLogs`. Empty positions match any topic.
+    topics: Option<Vec<Vec<Hash>>>,
+}
+
+/// Filter of logs inside a single block.
+#[derive(InputObject)]
+pub struct BlockFilterCriteria {
+    /// Addresses that emitted the logs. Empty matches any address.
+    addresses: Option<Vec<Address>>,
+
+    /// Topics of the logs by position, with the same rules of `eth_getLogs`. Empty positions match any topic.
+    topics: Option<Vec<Vec<Hash>>>,
+}
+
+fn filter_topics(topics: Option<Vec<Vec<Hash>>>) -> Vec<LogFilterInputTopic> {
+    topics
+        .unwrap_or_default()
+        .into_iter()
+        .map(|position| LogFilterInputTopic(position.into_iter().map(|topic| Some(LogTopic::from(H256::from(topic)))).collect()))
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Block by number or hash. Defaults to the latest mined block.
+    async fn block(&self, ctx: &Context<'_>, number: Option<Long>, hash: Option<Hash>) -> async_graphql::Result<Option<BlockNode>> {
+        let filter = match (number, hash) {
+            (_, Some(hash)) => BlockFilter::Hash(hash),
+            (Some(number), None) => BlockFilter::Number(number.into()),
+            (None, None) => BlockFilter::Latest,
+        };
+        read_block(ctx, filter)
+    }
+
+    /// Blocks in a range. The end defaults to the latest mined block.
+    async fn blocks(&self, ctx: &Context<'_>, from: Long, to: Option<Long>) -> async_graphql::Result<Vec<BlockNode>> {
+        let to = match to {
+            Some(to) => to.0,
+            None => storage(ctx).read_mined_block_number()?.as_u64(),
+        };
+        if to >= from.0 && to - from.0 >= MAX_BLOCKS {
+            return Err(format!("block range must not exceed {} blocks", MAX_BLOCKS).into());
+        }
+
+        let mut blocks = Vec::new();
+        for number in from.0..=to {
+            match read_block(ctx, BlockFilter::Number(number.into()))? {
+                Some(block) => blocks.push(block),
+                None => break,
+            }
+        }
+        Ok(blocks)
+    }
+
+    /// Mined transaction by hash.
+    async fn transaction(&self, ctx: &Context<'_>, hash: Hash) -> async_graphql::Result<Option<TransactionNode>> {
+        read_transaction(ctx, hash)
+    }
+
+    /// Logs matching a filter.
+    async fn logs(&self, ctx: &Context<'_>, filter: FilterCriteria) -> async_graphql::Result<Vec<LogNode>> {
+        let storage = storage(ctx);
+        let input = LogFilterInput {
+            from_block: filter.from_block.map(|number| BlockFilter::Number(number.into())),
+            to_block: filter.to_block.map(|number| BlockFilter::Number(number.into())),
+            block_hash: None,
+            address: filter.addresses.unwrap_or_default(),
+            topics: filter_topics(filter.topics),
+        };
+        let mut filter = input.parse(storage)?;
+
+        let to_block = match filter.to_block {
+            Some(block) => block,
+            None => storage.read_mined_block_number()?,
+        };
+        filter.to_block = Some(to_block);
+        if filter.from_block.count_to(to_block) > MAX_LOGS_BLOCK_RANGE {
+            return Err(format!("block range must not exceed {} blocks", MAX_LOGS_BLOCK_RANGE).into());
+        }
+
+        Ok(storage.read_logs(&filter)?.into_iter().map(LogNode).collect())
+    }
+
+    /// Account at a block. Defaults to the latest mined block.
+    async fn account(&self, ctx: &Context<'_>, address: Address, block: Option<Long>) -> async_graphql::Result<AccountNode> {
+        let point_in_time = match block {
+            Some(number) => PointInTime::MinedPast(number.into()),
+            None => PointInTime::Mined,
+        };
+        AccountNode::read(ctx, address, point_in_time)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Objects
+// -----------------------------------------------------------------------------
+
+/// Mined block with all its transactions.
+pub struct BlockNode(Block);
+
+#[Object(name = "Block")]
+impl BlockNode {
+    async fn number(&self) -> Long {
+        Long(self.0.number().as_u64())
+    }
+
+    async fn hash(&self) -> Hash {
+        self.0.hash()
+    }
+
+    async fn parent(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<BlockNode>> {
+        if self.0.number() == BlockNumber::ZERO {
+            return Ok(None);
+        }
+        read_block(ctx, BlockFilter::Hash(self.0.header.parent_hash))
+    }
+
+    async fn timestamp(&self) -> Long {
+        Long(*self.0.header.timestamp)
+    }
+
+    async fn gas_limit(&self) -> Long {
+        Long(self.0.header.gas_limit.as_u64())
+    }
+
+    async fn gas_used(&self) -> Long {
+        Long(self.0.header.gas_used.as_u64())
+    }
+
+    async fn miner(&self, ctx: &Context<'_>) -> async_graphql::Result<AccountNode> {
+        AccountNode::read(ctx, self.0.header.miner, PointInTime::MinedPast(self.0.number()))
+    }
+
+    async fn extra_data(&self) -> Bytes {
+        self.0.header.extra_data.clone()
+    }
+
+    async fn transactions_root(&self) -> Hash {
+        self.0.header.transactions_root
+    }
+
+    async fn state_root(&self) -> Hash {
+        self.0.header.state_root
+    }
+
+    async fn receipts_root(&self) -> Hash {
+        self.0.header.receipts_root
+    }
+
+    async fn transaction_count(&self) -> Long {
+        Long(self.0.transactions.len() as u64)
+    }
+
+    async fn transactions(&self) -> Vec<TransactionNode> {
+        self.0.transactions.iter().cloned().map(TransactionNode).collect()
+    }
+
+    async fn transaction_at(&self, index: Long) -> Option<TransactionNode> {
+        self.0.transactions.get(index.0 as usize).cloned().map(TransactionNode)
+    }
+
+    /// Logs of the block matching a filter, without reading the storage again.
+    async fn logs(&self, filter: BlockFilterCriteria) -> Vec<LogNode> {
+        let filter = LogFilter {
+            from_block: self.0.number(),
+            to_block: Some(self.0.number()),
+            addresses: filter.addresses.unwrap_or_default(),
+            original_input: LogFilterInput {
+                topics: filter_topics(filter.topics),
+                ..LogFilterInput::default()
+            },
+        };
+        self.0
+            .transactions
+            .iter()
+            .flat_map(|tx| tx.logs.iter())
+            .filter(|log| filter.matches(log))
+            .cloned()
+            .map(LogNode)
+            .collect()
+    }
+
+    async fn account(&self, ctx: &Context<'_>, address: Address) -> async_graphql::Result<AccountNode> {
+        AccountNode::read(ctx, address, PointInTime::MinedPast(self.0.number()))
+    }
+}
+
+/// Transaction included in a mined block.
+pub struct TransactionNode(TransactionMined);
+
+#[Object(name = "Transaction")]
+impl TransactionNode {
+    async fn hash(&self) -> Hash {
+        self.0.input.hash
+    }
+
+    async fn nonce(&self) -> Long {
+        Long(self.0.input.nonce.as_u64())
+    }
+
+    async fn index(&self) -> Long {
+        Long(self.0.transaction_index.0)
+    }
+
+    /// Sender at the block of the transaction.
+    async fn from(&self, ctx: &Context<'_>) -> async_graphql::Result<AccountNode> {
+        AccountNode::read(ctx, self.0.input.signer, PointInTime::MinedPast(self.0.block_number))
+    }
+
+    /// Receiver at the block of the transaction. Null for contract deployments.
+    async fn to(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<AccountNode>> {
+        match self.0.input.to {
+            Some(to) => AccountNode::read(ctx, to, PointInTime::MinedPast(self.0.block_number)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn value(&self) -> Wei {
+        self.0.input.value
+    }
+
+    async fn gas_price(&self) -> Wei {
+        self.0.input.gas_price
+    }
+
+    async fn gas(&self) -> Long {
+        Long(self.0.input.gas_limit.as_u64())
+    }
+
+    async fn input_data(&self) -> Bytes {
+        self.0.input.input.clone()
+    }
+
+    async fn block(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<BlockNode>> {
+        read_block(ctx, BlockFilter::Number(self.0.block_number))
+    }
+
+    /// 1 if the transaction succeeded, 0 otherwise.
+    async fn status(&self) -> Long {
+        Long(self.0.is_success() as u64)
+    }
+
+    async fn gas_used(&self) -> Long {
+        Long(self.0.execution.gas.as_u64())
+    }
+
+    async fn created_contract(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<AccountNode>> {
+        match self.0.execution.contract_address() {
+            Some(address) => AccountNode::read(ctx, address, PointInTime::MinedPast(self.0.block_number)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn logs(&self) -> Vec<LogNode> {
+        self.0.logs.iter().cloned().map(LogNode).collect()
+    }
+}
+
+/// Log emitted by a mined transaction.
+pub struct LogNode(LogMined);
+
+#[Object(name = "Log")]
+impl LogNode {
+    async fn index(&self) -> Long {
+        Long(self.0.log_index.0)
+    }
+
+    /// Emitter at the block of the log.
+    async fn account(&self, ctx: &Context<'_>) -> async_graphql::Result<AccountNode> {
+        AccountNode::read(ctx, self.0.address(), PointInTime::MinedPast(self.0.block_number))
+    }
+
+    async fn topics(&self) -> Vec<Hash> {
+        self.0.topics_non_empty().into_iter().map(|topic| Hash::from(H256::from(topic))).collect()
+    }
+
+    async fn data(&self) -> Bytes {
+        self.0.log.data.clone()
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TransactionNode>> {
+        read_transaction(ctx, self.0.transaction_hash)
+    }
+}
+
+/// Account at a point in time.
+pub struct AccountNode {
+    account: Account,
+    point_in_time: PointInTime,
+}
+
+impl AccountNode {
+    fn read(ctx: &Context<'_>, address: Address, point_in_time: PointInTime) -> async_graphql::Result<Self> {
+        let account = storage(ctx).read_account(address, point_in_time)?;
+        Ok(Self { account, point_in_time })
+    }
+}
+
+#[Object(name = "Account")]
+impl AccountNode {
+    async fn address(&self) -> Address {
+        self.account.address
+    }
+
+    async fn balance(&self) -> Wei {
+        self.account.balance
+    }
+
+    async fn transaction_count(&self) -> Long {
+        Long(self.account.nonce.as_u64())
+    }
+
+    async fn code(&self) -> Bytes {
+        self.account.bytecode.clone().unwrap_or_default()
+    }
+
+    async fn storage(&self, ctx: &Context<'_>, slot: Hash) -> async_graphql::Result<Hash> {
+        let index = SlotIndex::from(H256::from(slot).0);
+        let slot = storage(ctx).read_slot(self.account.address, index, self.point_in_time)?;
+        Ok(Hash::from(<[u8; 32]>::from(slot.value)))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Storage
+// -----------------------------------------------------------------------------
+
+fn storage<'a>(ctx: &Context<'a>) -> &'a Arc<dyn StorageReader> {
+    ctx.data_unchecked::<Arc<dyn StorageReader>>()
+}
+
+fn read_block(ctx: &Context<'_>, filter: BlockFilter) -> async_graphql::Result<Option<BlockNode>> {
+    Ok(storage(ctx).read_block(filter)?.map(BlockNode))
+}
+
+fn read_transaction(ctx: &Context<'_>, hash: Hash) -> async_graphql::Result<Option<TransactionNode>> {
+    match storage(ctx).read_transaction(hash)? {
+        Some(TransactionStage::Mined(tx)) => Ok(Some(TransactionNode(tx))),
+        Some(TransactionStage::Executed(_)) | None => Ok(None),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::Storage;
+    use crate::eth::storage::StratusStorage;
+
+    #[test]
+    fn test_parse_long() {
+        assert_eq!(Long::parse(Value::from(10)).unwrap(), Long(10));
+        assert_eq!(Long::parse(Value::from("0xa")).unwrap(), Long(10));
+        assert!(Long::parse(Value::from("10")).is_err());
+        assert_eq!(Long(10).to_value(), Value::from("0xa"));
+    }
+
+    fn genesis_schema() -> GraphqlSchema {
+        let storage = StratusStorage::new(
+            Box::new(InMemoryTemporaryStorage::new(1.into())),
+            Box::new(InMemoryPermanentStorage::default()),
+            None,
+        )
+        .unwrap();
+        storage.save_block(Block::genesis()).unwrap();
+        graphql_schema(Arc::new(storage))
+    }
+
+    async fn execute(query: String) -> async_graphql::Response {
+        let schema = genesis_schema();
+        tokio::task::spawn_blocking(move || execute_graphql(&schema, async_graphql::Request::new(query)))
+            .await
+            .unwrap()
+    }
+
+    fn assert_rejected(response: &async_graphql::Response, expected: &str) {
+        assert!(response.data == Value::Null, "{:?}", response.data);
+        assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+        assert!(response.errors[0].message.contains(expected), "{}", response.errors[0].message);
+    }
+
+    #[tokio::test]
+    async fn test_query_genesis_block() {
+        let schema = genesis_schema();
+
+        let response = schema.execute("{ block(number: 0) { number transactionCount parent { number } } }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "block": { "number": "0x0", "transactionCount": "0x0", "parent": null } })
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_query_within_limits_is_executed() {
+        let aliases = (0..MAX_QUERY_ALIASES)
+            .map(|i| format!("b{}: block(number: 0) {{ number }}", i))
+            .collect::<Vec<_>>();
+        let response = execute(format!("{{ {} }}", aliases.join(" "))).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reject_deep_query() {
+        let mut query = "number".to_owned();
+        for _ in 0..MAX_QUERY_DEPTH {
+            query = format!("parent {{ {} }}", query);
+        }
+        let response = execute(format!("{{ block(number: 0) {{ {} }} }}", query)).await;
+        assert_rejected(&response, "nested too deep");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reject_complex_query() {
+        // each alias is within the alias limit, but together they exceed the complexity limit
+        let fields = "number hash timestamp gasLimit gasUsed transactionCount transactions { hash nonce value from { address balance } }";
+        let aliases = (0..MAX_QUERY_ALIASES)
+            .map(|i| format!("b{}: block(number: 0) {{ {} }}", i, fields))
+            .collect::<Vec<_>>();
+        let response = execute(format!("{{ {} }}", aliases.join(" "))).await;
+        assert_rejected(&response, "too complex");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reject_query_with_too_many_aliases() {
+        let aliases = (0..=MAX_QUERY_ALIASES)
+            .map(|i| format!("b{}: block(number: 0) {{ number }}", i))
+            .collect::<Vec<_>>();
+        let response = execute(format!("{{ {} }}", aliases.join(" "))).await;
+        assert_rejected(&response, "aliases");
+    }
+
+    #[test]
+    fn test_count_aliases_in_fragments() {
+        let document = parse_query("query { a: block { ...F } b: block { number } } fragment F on Block { c: number d: hash }").unwrap();
+        assert_eq!(count_aliases(&document), 4);
+    }
+}
@@ -0,0 +1,820 @@
+//! gRPC query API mirroring a subset of the JSON-RPC API.
+//!
+//! It is served in its own address and gives internal services a strongly-typed interface to blocks, transactions, accounts and calls without
+//! the cost of encoding and parsing JSON. The API is described by `static/proto/stratus.proto` and its messages are derived by hand in
+//! [`proto`], so the build does not depend on `protoc`.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use ethereum_types::H160;
+use ethereum_types::H256;
+use ethereum_types::U256;
+use jsonrpsee_types::error::CALL_EXECUTION_FAILED_CODE;
+use jsonrpsee_types::error::INVALID_PARAMS_CODE;
+use jsonrpsee_types::error::INVALID_REQUEST_CODE;
+use jsonrpsee_types::error::SERVER_IS_BUSY_CODE;
+use tokio::net::TcpListener;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::empty_body;
+use tonic::codegen::http;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::server::Grpc;
+use tonic::server::NamedService;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Body;
+use tonic::transport::Server;
+use tonic::Code;
+use tonic::Status;
+use tracing::info_span;
+
+use crate::eth::executor::Executor;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::CallInput;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionExecution;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::TransactionStage;
+use crate::eth::primitives::Wei;
+use crate::eth::rpc::rpc_server::translate_to_state_point_in_time;
+use crate::eth::storage::StorageReader;
+use crate::ext::spawn_blocking_named;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::log_and_err;
+use crate::ChainState;
+use crate::GlobalState;
+
+// -----------------------------------------------------------------------------
+// Server
+// -----------------------------------------------------------------------------
+
+/// Serves the gRPC query API until the application is shut down.
+pub async fn serve_grpc(address: SocketAddr, storage: Arc<dyn StorageReader>, executor: Arc<Executor>) -> anyhow::Result<()> {
+    const TASK_NAME: &str = "rpc::grpc";
+    tracing::info!(%address, "starting {}", TASK_NAME);
+
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            GlobalState::shutdown_from(TASK_NAME, "failed to bind grpc address");
+            return log_and_err!(reason = e, "failed to bind grpc address");
+        }
+    };
+    if let Err(e) = serve_grpc_on(listener, storage, executor, GlobalState::wait_shutdown_warn(TASK_NAME)).await {
+        GlobalState::shutdown_from(TASK_NAME, "failed to serve grpc");
+        return log_and_err!(reason = e, "failed to serve grpc");
+    }
+    Ok(())
+}
+
+/// Serves the gRPC query API in an already bound listener until the shutdown future completes.
+async fn serve_grpc_on(
+    listener: TcpListener,
+    storage: Arc<dyn StorageReader>,
+    executor: Arc<Executor>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let service = GrpcQueryService {
+        ctx: Arc::new(GrpcContext {
+            storage,
+            executor,
+            chain: ChainState::current(),
+        }),
+    };
+    let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|e| anyhow!(e))?;
+    Server::builder().add_service(service).serve_with_incoming_shutdown(incoming, shutdown).await?;
+    Ok(())
+}
+
+/// Services used by the gRPC methods.
+struct GrpcContext {
+    storage: Arc<dyn StorageReader>,
+    executor: Arc<Executor>,
+
+    /// Chain served by the server, entered by the methods because tonic handles connections in its own tasks.
+    chain: Arc<ChainState>,
+}
+
+/// Routes gRPC requests to the query methods.
+#[derive(Clone)]
+struct GrpcQueryService {
+    ctx: Arc<GrpcContext>,
+}
+
+impl NamedService for GrpcQueryService {
+    const NAME: &'static str = "stratus.v1.Query";
+}
+
+impl Service<http::Request<Body>> for GrpcQueryService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let ctx = Arc::clone(&self.ctx);
+        match request.uri().path() {
+            "/stratus.v1.Query/GetBlock" => unary(ctx, "GetBlock", get_block, request),
+            "/stratus.v1.Query/GetTransaction" => unary(ctx, "GetTransaction", get_transaction, request),
+            "/stratus.v1.Query/GetAccount" => unary(ctx, "GetAccount", get_account, request),
+            "/stratus.v1.Query/Call" => unary(ctx, "Call", call, request),
+            _ => Box::pin(async {
+                let response = http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header("grpc-status", (Code::Unimplemented as i32).to_string())
+                    .header(http::header::CONTENT_TYPE, "application/grpc")
+                    .body(empty_body())
+                    .expect("static grpc response should be valid");
+                Ok(response)
+            }),
+        }
+    }
+}
+
+/// Decodes a unary request, executes its method in a blocking thread and encodes its response.
+fn unary<Req, Res>(
+    ctx: Arc<GrpcContext>,
+    method: &'static str,
+    handler: fn(&GrpcContext, Req) -> Result<Res, StratusError>,
+    request: http::Request<Body>,
+) -> BoxFuture<http::Response<BoxBody>, Infallible>
+where
+    Req: prost::Message + Default + Send + 'static,
+    Res: prost::Message + Send + 'static,
+{
+    let chain = Arc::clone(&ctx.chain);
+    let method = BlockingMethod { ctx, method, handler };
+    Box::pin(chain.scope(async move {
+        let mut grpc = Grpc::new(ProstCodec::<Res, Req>::default());
+        Ok(grpc.unary(method, request).await)
+    }))
+}
+
+/// Method executed in a blocking thread, because storage reads and EVM calls are synchronous.
+struct BlockingMethod<Req, Res> {
+    ctx: Arc<GrpcContext>,
+    method: &'static str,
+    handler: fn(&GrpcContext, Req) -> Result<Res, StratusError>,
+}
+
+impl<Req, Res> Service<tonic::Request<Req>> for BlockingMethod<Req, Res>
+where
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    type Response = tonic::Response<Res>;
+    type Error = Status;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+        let (ctx, method, handler) = (Arc::clone(&self.ctx), self.method, self.handler);
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = spawn_blocking_named("rpc::grpc::method", move || {
+                let _span = info_span!("rpc::grpc", %method).entered();
+                handler(&ctx, request.into_inner())
+            })
+            .await;
+
+            let result = match result {
+                Ok(result) => result.map_err(status),
+                Err(e) => {
+                    tracing::error!(reason = ?e, %method, "grpc method panicked");
+                    Err(Status::internal("method panicked"))
+                }
+            };
+
+            #[cfg(feature = "metrics")]
+            {
+                let code = result.as_ref().map_or_else(Status::code, |_| Code::Ok);
+                metrics::inc_grpc_requests_finished(start.elapsed(), method, format!("{:?}", code));
+            }
+            #[cfg(not(feature = "metrics"))]
+            let _ = start;
+
+            result.map(tonic::Response::new)
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Methods
+// -----------------------------------------------------------------------------
+
+fn get_block(ctx: &GrpcContext, request: proto::GetBlockRequest) -> Result<proto::GetBlockResponse, StratusError> {
+    let filter = block_filter(request.block)?;
+    tracing::info!(%filter, full_transactions = %request.full_transactions, "reading block");
+
+    if request.full_transactions {
+        let block = ctx.storage.read_block(filter)?;
+        Ok(proto::GetBlockResponse {
+            block: block.map(proto::Block::from),
+        })
+    } else {
+        let header = ctx.storage.read_block_header(filter)?;
+        let block = header.map(|header| {
+            let hashes = header.transactions.into_iter().map(hash_bytes).collect();
+            block_message(&header.header, hashes, Vec::new())
+        });
+        Ok(proto::GetBlockResponse { block })
+    }
+}
+
+fn get_transaction(ctx: &GrpcContext, request: proto::GetTransactionRequest) -> Result<proto::GetTransactionResponse, StratusError> {
+    let tx_hash = parse_hash(&request.hash, "hash")?;
+    tracing::info!(%tx_hash, "reading transaction");
+
+    let transaction = match ctx.storage.read_transaction(tx_hash)? {
+        Some(TransactionStage::Mined(tx)) => Some(mined_transaction(tx)),
+        Some(TransactionStage::Executed(TransactionExecution::Local(tx))) => Some(transaction_message(&tx.input, None)),
+        Some(TransactionStage::Executed(TransactionExecution::External(tx))) => {
+            let input = TransactionInput::try_from(tx.tx).map_err(StratusError::Unexpected)?;
+            Some(transaction_message(&input, None))
+        }
+        None => None,
+    };
+    Ok(proto::GetTransactionResponse { transaction })
+}
+
+fn get_account(ctx: &GrpcContext, request: proto::GetAccountRequest) -> Result<proto::GetAccountResponse, StratusError> {
+    let address = parse_address(&request.address, "address")?;
+    let filter = block_filter(request.block)?;
+    tracing::info!(%address, %filter, "reading account");
+
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, filter)?;
+    let account = ctx.storage.read_account(address, point_in_time)?;
+    Ok(proto::GetAccountResponse {
+        address: address_bytes(account.address),
+        nonce: account.nonce.as_u64(),
+        balance: u256_bytes(account.balance.into()),
+        bytecode: account.bytecode.map(|bytecode| bytecode.to_vec()),
+    })
+}
+
+fn call(ctx: &GrpcContext, request: proto::CallRequest) -> Result<proto::CallResponse, StratusError> {
+    let call = CallInput {
+        from: request.from.map(|from| parse_address(&from, "from")).transpose()?,
+        to: request.to.map(|to| parse_address(&to, "to")).transpose()?,
+        value: parse_wei(&request.value, "value")?,
+        data: request.data.as_slice().into(),
+    };
+    let filter = block_filter(request.block)?;
+    tracing::info!(%filter, "executing call");
+
+    let point_in_time = ctx.storage.translate_to_point_in_time(filter)?;
+    match ctx.executor.execute_local_call(call, point_in_time) {
+        Ok(result) => Ok(proto::CallResponse {
+            success: result.is_success(),
+            output: result.output.to_vec(),
+        }),
+        Err(e) => {
+            if e.is_fatal() {
+                tracing::error!(reason = ?e, "failed to execute call");
+            }
+            Err(e)
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Conversions
+// -----------------------------------------------------------------------------
+
+/// Converts an error to the gRPC status with the closest meaning of its JSON-RPC error code.
+fn status(e: StratusError) -> Status {
+    let code = match e.rpc_code() {
+        INVALID_PARAMS_CODE => Code::InvalidArgument,
+        INVALID_REQUEST_CODE | CALL_EXECUTION_FAILED_CODE => Code::FailedPrecondition,
+        SERVER_IS_BUSY_CODE => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    match e {
+        StratusError::RpcParameterInvalid { decode_error, .. } => Status::new(code, decode_error),
+        e => Status::new(code, e.rpc_message()),
+    }
+}
+
+fn block_filter(selector: Option<proto::BlockSelector>) -> Result<BlockFilter, StratusError> {
+    match selector.and_then(|selector| selector.selector) {
+        None => Ok(BlockFilter::Latest),
+        Some(proto::block_selector::Selector::Number(number)) => Ok(BlockFilter::Number(number.into())),
+        Some(proto::block_selector::Selector::Hash(hash)) => Ok(BlockFilter::Hash(parse_hash(&hash, "block.hash")?)),
+    }
+}
+
+fn parse_hash(bytes: &[u8], field: &str) -> Result<Hash, StratusError> {
+    if bytes.len() != 32 {
+        return Err(StratusError::RpcParameterInvalid {
+            rust_type: "Hash",
+            decode_error: format!("{} must have 32 bytes", field),
+        });
+    }
+    Ok(Hash(H256::from_slice(bytes)))
+}
+
+fn parse_address(bytes: &[u8], field: &str) -> Result<Address, StratusError> {
+    if bytes.len() != 20 {
+        return Err(StratusError::RpcParameterInvalid {
+            rust_type: "Address",
+            decode_error: format!("{} must have 20 bytes", field),
+        });
+    }
+    Ok(Address(H160::from_slice(bytes)))
+}
+
+fn parse_wei(bytes: &[u8], field: &str) -> Result<Wei, StratusError> {
+    if bytes.len() > 32 {
+        return Err(StratusError::RpcParameterInvalid {
+            rust_type: "Wei",
+            decode_error: format!("{} must have at most 32 bytes", field),
+        });
+    }
+    Ok(Wei::from(U256::from_big_endian(bytes)))
+}
+
+fn hash_bytes(hash: Hash) -> Vec<u8> {
+    hash.0.as_bytes().to_vec()
+}
+
+fn address_bytes(address: Address) -> Vec<u8> {
+    address.0.as_bytes().to_vec()
+}
+
+fn u256_bytes(value: U256) -> Vec<u8> {
+    let mut bytes = vec![0; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+impl From<Block> for proto::Block {
+    fn from(block: Block) -> Self {
+        let hashes = block.transactions.iter().map(|tx| hash_bytes(tx.input.hash)).collect();
+        let transactions = block.transactions.into_iter().map(mined_transaction).collect();
+        block_message(&block.header, hashes, transactions)
+    }
+}
+
+fn block_message(header: &BlockHeader, transaction_hashes: Vec<Vec<u8>>, transactions: Vec<proto::Transaction>) -> proto::Block {
+    proto::Block {
+        number: header.number.as_u64(),
+        hash: hash_bytes(header.hash),
+        parent_hash: hash_bytes(header.parent_hash),
+        timestamp: *header.timestamp,
+        miner: address_bytes(header.miner),
+        gas_used: header.gas_used.as_u64(),
+        gas_limit: header.gas_limit.as_u64(),
+        state_root: hash_bytes(header.state_root),
+        transactions_root: hash_bytes(header.transactions_root),
+        receipts_root: hash_bytes(header.receipts_root),
+        transaction_hashes,
+        transactions,
+    }
+}
+
+fn mined_transaction(tx: TransactionMined) -> proto::Transaction {
+    let receipt = proto::Receipt {
+        block_number: tx.block_number.as_u64(),
+        block_hash: hash_bytes(tx.block_hash),
+        transaction_index: tx.transaction_index.0,
+        success: tx.is_success(),
+        gas_used: tx.execution.gas.as_u64(),
+        contract_address: tx.execution.deployed_contract_address.map(address_bytes),
+        logs: tx.logs.iter().map(log_message).collect(),
+    };
+    transaction_message(&tx.input, Some(receipt))
+}
+
+fn transaction_message(input: &TransactionInput, receipt: Option<proto::Receipt>) -> proto::Transaction {
+    proto::Transaction {
+        hash: hash_bytes(input.hash),
+        from: address_bytes(input.signer),
+        to: input.to.map(address_bytes),
+        nonce: input.nonce.as_u64(),
+        value: u256_bytes(input.value.into()),
+        input: input.input.to_vec(),
+        gas_limit: input.gas_limit.as_u64(),
+        gas_price: u256_bytes(input.gas_price.into()),
+        receipt,
+    }
+}
+
+fn log_message(log: &LogMined) -> proto::Log {
+    proto::Log {
+        address: address_bytes(log.address()),
+        topics: log.topics_non_empty().into_iter().map(|topic| topic.0.as_bytes().to_vec()).collect(),
+        data: log.log.data.to_vec(),
+        log_index: log.log_index.0,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Messages
+// -----------------------------------------------------------------------------
+
+/// Messages of `static/proto/stratus.proto`.
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BlockSelector {
+        #[prost(oneof = "block_selector::Selector", tags = "1, 2")]
+        pub selector: Option<block_selector::Selector>,
+    }
+
+    pub mod block_selector {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Selector {
+            #[prost(uint64, tag = "1")]
+            Number(u64),
+
+            #[prost(bytes = "vec", tag = "2")]
+            Hash(Vec<u8>),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetBlockRequest {
+        #[prost(message, optional, tag = "1")]
+        pub block: Option<BlockSelector>,
+
+        #[prost(bool, tag = "2")]
+        pub full_transactions: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetBlockResponse {
+        #[prost(message, optional, tag = "1")]
+        pub block: Option<Block>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Block {
+        #[prost(uint64, tag = "1")]
+        pub number: u64,
+
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "3")]
+        pub parent_hash: Vec<u8>,
+
+        #[prost(uint64, tag = "4")]
+        pub timestamp: u64,
+
+        #[prost(bytes = "vec", tag = "5")]
+        pub miner: Vec<u8>,
+
+        #[prost(uint64, tag = "6")]
+        pub gas_used: u64,
+
+        #[prost(uint64, tag = "7")]
+        pub gas_limit: u64,
+
+        #[prost(bytes = "vec", tag = "8")]
+        pub state_root: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "9")]
+        pub transactions_root: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "10")]
+        pub receipts_root: Vec<u8>,
+
+        #[prost(bytes = "vec", repeated, tag = "11")]
+        pub transaction_hashes: Vec<Vec<u8>>,
+
+        #[prost(message, repeated, tag = "12")]
+        pub transactions: Vec<Transaction>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetTransactionRequest {
+        #[prost(bytes = "vec", tag = "1")]
+        pub hash: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetTransactionResponse {
+        #[prost(message, optional, tag = "1")]
+        pub transaction: Option<Transaction>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Transaction {
+        #[prost(bytes = "vec", tag = "1")]
+        pub hash: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "2")]
+        pub from: Vec<u8>,
+
+        #[prost(bytes = "vec", optional, tag = "3")]
+        pub to: Option<Vec<u8>>,
+
+        #[prost(uint64, tag = "4")]
+        pub nonce: u64,
+
+        #[prost(bytes = "vec", tag = "5")]
+        pub value: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "6")]
+        pub input: Vec<u8>,
+
+        #[prost(uint64, tag = "7")]
+        pub gas_limit: u64,
+
+        #[prost(bytes = "vec", tag = "8")]
+        pub gas_price: Vec<u8>,
+
+        #[prost(message, optional, tag = "9")]
+        pub receipt: Option<Receipt>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Receipt {
+        #[prost(uint64, tag = "1")]
+        pub block_number: u64,
+
+        #[prost(bytes = "vec", tag = "2")]
+        pub block_hash: Vec<u8>,
+
+        #[prost(uint64, tag = "3")]
+        pub transaction_index: u64,
+
+        #[prost(bool, tag = "4")]
+        pub success: bool,
+
+        #[prost(uint64, tag = "5")]
+        pub gas_used: u64,
+
+        #[prost(bytes = "vec", optional, tag = "6")]
+        pub contract_address: Option<Vec<u8>>,
+
+        #[prost(message, repeated, tag = "7")]
+        pub logs: Vec<Log>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Log {
+        #[prost(bytes = "vec", tag = "1")]
+        pub address: Vec<u8>,
+
+        #[prost(bytes = "vec", repeated, tag = "2")]
+        pub topics: Vec<Vec<u8>>,
+
+        #[prost(bytes = "vec", tag = "3")]
+        pub data: Vec<u8>,
+
+        #[prost(uint64, tag = "4")]
+        pub log_index: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetAccountRequest {
+        #[prost(bytes = "vec", tag = "1")]
+        pub address: Vec<u8>,
+
+        #[prost(message, optional, tag = "2")]
+        pub block: Option<BlockSelector>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetAccountResponse {
+        #[prost(bytes = "vec", tag = "1")]
+        pub address: Vec<u8>,
+
+        #[prost(uint64, tag = "2")]
+        pub nonce: u64,
+
+        #[prost(bytes = "vec", tag = "3")]
+        pub balance: Vec<u8>,
+
+        #[prost(bytes = "vec", optional, tag = "4")]
+        pub bytecode: Option<Vec<u8>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CallRequest {
+        #[prost(bytes = "vec", optional, tag = "1")]
+        pub from: Option<Vec<u8>>,
+
+        #[prost(bytes = "vec", optional, tag = "2")]
+        pub to: Option<Vec<u8>>,
+
+        #[prost(bytes = "vec", tag = "3")]
+        pub data: Vec<u8>,
+
+        #[prost(bytes = "vec", tag = "4")]
+        pub value: Vec<u8>,
+
+        #[prost(message, optional, tag = "5")]
+        pub block: Option<BlockSelector>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CallResponse {
+        #[prost(bool, tag = "1")]
+        pub success: bool,
+
+        #[prost(bytes = "vec", tag = "2")]
+        pub output: Vec<u8>,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use fake::Fake;
+    use fake::Faker;
+    use tonic::transport::Channel;
+    use tonic::transport::Endpoint;
+
+    use super::*;
+    use crate::eth::executor::ExecutorConfig;
+    use crate::eth::miner::Miner;
+    use crate::eth::miner::MinerConfig;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+
+    const ALICE: Address = Address::new([1; 20]);
+
+    /// Transaction mined in block 1.
+    fn mined_tx() -> TransactionMined {
+        let mut tx: TransactionMined = Faker.fake();
+        tx.block_number = BlockNumber::ONE;
+        tx
+    }
+
+    /// Starts a server over a storage with the genesis block, a block with the transaction and a funded account, returning a channel connected to it.
+    async fn start_server(tx: &TransactionMined) -> Channel {
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::genesis()).unwrap();
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1));
+        block.transactions.push(tx.clone());
+        perm.save_block(block).unwrap();
+        perm.save_accounts(vec![Account::new_with_balance(ALICE, Wei::from(10u64))]).unwrap();
+        perm.set_mined_block_number(BlockNumber::ONE).unwrap();
+
+        let temp = InMemoryTemporaryStorage::new(BlockNumber::from(2));
+        let storage = Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap());
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, &MinerConfig::parse_from(["test"])));
+        let executor = ExecutorConfig::parse_from(["test", "--executor-chain-id", "2008"]).init(Arc::clone(&storage), miner);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(serve_grpc_on(listener, storage, executor, std::future::pending()));
+        Endpoint::from_shared(format!("http://{}", address)).unwrap().connect().await.unwrap()
+    }
+
+    async fn request<Req, Res>(channel: &Channel, path: &'static str, request: Req) -> Result<Res, Status>
+    where
+        Req: prost::Message + 'static,
+        Res: prost::Message + Default + 'static,
+    {
+        let mut client = tonic::client::Grpc::new(channel.clone());
+        client.ready().await.unwrap();
+        let path = http::uri::PathAndQuery::from_static(path);
+        client
+            .unary(tonic::Request::new(request), path, ProstCodec::default())
+            .await
+            .map(tonic::Response::into_inner)
+    }
+
+    fn block_number(number: u64) -> Option<proto::BlockSelector> {
+        Some(proto::BlockSelector {
+            selector: Some(proto::block_selector::Selector::Number(number)),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_block() {
+        let tx = mined_tx();
+        let channel = start_server(&tx).await;
+
+        // latest block with transactions hashes
+        let request_latest = proto::GetBlockRequest::default();
+        let response: proto::GetBlockResponse = request(&channel, "/stratus.v1.Query/GetBlock", request_latest).await.unwrap();
+        let block = response.block.unwrap();
+        assert_eq!(block.number, 1);
+        assert_eq!(block.transaction_hashes, vec![hash_bytes(tx.input.hash)]);
+        assert!(block.transactions.is_empty());
+
+        // block with full transactions
+        let request_full = proto::GetBlockRequest {
+            block: block_number(1),
+            full_transactions: true,
+        };
+        let response: proto::GetBlockResponse = request(&channel, "/stratus.v1.Query/GetBlock", request_full).await.unwrap();
+        let block = response.block.unwrap();
+        assert_eq!(block.transactions, vec![mined_transaction(tx)]);
+
+        // block not mined yet
+        let request_missing = proto::GetBlockRequest {
+            block: block_number(5),
+            full_transactions: false,
+        };
+        let response: proto::GetBlockResponse = request(&channel, "/stratus.v1.Query/GetBlock", request_missing).await.unwrap();
+        assert_eq!(response.block, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction() {
+        let tx = mined_tx();
+        let channel = start_server(&tx).await;
+
+        let request_found = proto::GetTransactionRequest {
+            hash: hash_bytes(tx.input.hash),
+        };
+        let response: proto::GetTransactionResponse = request(&channel, "/stratus.v1.Query/GetTransaction", request_found).await.unwrap();
+        let found = response.transaction.unwrap();
+        assert_eq!(found.from, address_bytes(tx.input.signer));
+        assert_eq!(found.receipt.unwrap().block_number, 1);
+
+        let request_missing = proto::GetTransactionRequest { hash: vec![0; 32] };
+        let response: proto::GetTransactionResponse = request(&channel, "/stratus.v1.Query/GetTransaction", request_missing).await.unwrap();
+        assert_eq!(response.transaction, None);
+
+        let request_invalid = proto::GetTransactionRequest { hash: vec![0; 20] };
+        let e = request::<_, proto::GetTransactionResponse>(&channel, "/stratus.v1.Query/GetTransaction", request_invalid)
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_account() {
+        let channel = start_server(&mined_tx()).await;
+
+        let request_account = proto::GetAccountRequest {
+            address: address_bytes(ALICE),
+            block: None,
+        };
+        let response: proto::GetAccountResponse = request(&channel, "/stratus.v1.Query/GetAccount", request_account).await.unwrap();
+        assert_eq!(response.balance, u256_bytes(U256::from(10)));
+        assert_eq!(response.nonce, 0);
+        assert_eq!(response.bytecode, None);
+
+        // state of blocks not mined yet is rejected like in JSON-RPC
+        let request_future = proto::GetAccountRequest {
+            address: address_bytes(ALICE),
+            block: block_number(5),
+        };
+        let e = request::<_, proto::GetAccountResponse>(&channel, "/stratus.v1.Query/GetAccount", request_future)
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let channel = start_server(&mined_tx()).await;
+
+        let request_call = proto::CallRequest {
+            from: None,
+            to: Some(address_bytes(ALICE)),
+            data: vec![],
+            value: vec![],
+            block: None,
+        };
+        let response: proto::CallResponse = request(&channel, "/stratus.v1.Query/Call", request_call).await.unwrap();
+        assert!(response.success);
+        assert!(response.output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_is_unimplemented() {
+        let channel = start_server(&mined_tx()).await;
+
+        let e = request::<_, proto::CallResponse>(&channel, "/stratus.v1.Query/Unknown", proto::CallRequest::default())
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), Code::Unimplemented);
+    }
+}
@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::time::Duration;
+use std::time::Instant;
 
 use futures::TryFutureExt;
+use http::header::CONNECTION;
+use http::StatusCode;
 use jsonrpsee::client_transport::ws::Uri;
 use jsonrpsee::core::BoxError;
 use jsonrpsee::server::HttpBody;
@@ -15,9 +19,41 @@ use tower::Service;
 use crate::eth::rpc::RpcClientApp;
 use crate::ext::not;
 
-#[derive(Debug, Clone, derive_new::new)]
+/// Middleware of HTTP requests. It is created for each connection, so it also closes connections that must not be reused.
+#[derive(Debug, Clone)]
 pub struct RpcHttpMiddleware<S> {
     service: S,
+
+    /// Keep the connection open between requests.
+    keep_alive: bool,
+
+    /// Max time the connection is reused.
+    max_lifetime: Option<Duration>,
+
+    /// When the connection was accepted.
+    created_at: Instant,
+}
+
+impl<S> RpcHttpMiddleware<S> {
+    pub fn new(service: S, keep_alive: bool, max_lifetime: Option<Duration>) -> Self {
+        Self {
+            service,
+            keep_alive,
+            max_lifetime,
+            created_at: Instant::now(),
+        }
+    }
+
+    /// Checks if the connection must be closed after the current response.
+    fn should_close_connection(&self) -> bool {
+        if not(self.keep_alive) {
+            return true;
+        }
+        match self.max_lifetime {
+            Some(max_lifetime) => self.created_at.elapsed() >= max_lifetime,
+            None => false,
+        }
+    }
 }
 
 impl<S> Service<HttpRequest<HttpBody>> for RpcHttpMiddleware<S>
@@ -38,7 +74,14 @@ where
         let client_app = parse_client_app(request.headers(), request.uri());
         request.extensions_mut().insert(client_app);
 
-        Box::pin(self.service.call(request).map_err(Into::into))
+        let close_connection = self.should_close_connection();
+        Box::pin(self.service.call(request).map_err(Into::into).map_ok(move |mut response| {
+            // websocket upgrades keep the connection open and are closed by pings instead
+            if close_connection && response.status() != StatusCode::SWITCHING_PROTOCOLS {
+                response.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+            }
+            response
+        }))
     }
 }
 
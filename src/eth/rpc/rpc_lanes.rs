@@ -0,0 +1,125 @@
+//! Scheduling of requests in separate lanes with their own workers.
+//!
+//! Transactions and consensus-critical calls run in the high-priority lane, while all other requests share the normal lane. Each lane limits how many
+//! requests execute concurrently, so heavy read traffic fills only the normal lane and cannot delay transaction inclusion.
+
+use std::sync::Arc;
+
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Methods executed in the high-priority lane.
+const HIGH_PRIORITY_METHODS: [&str; 6] = [
+    "eth_sendRawTransaction",
+    "eth_sendUserOperation",
+    "stratus_getBlockAndReceipts",
+    "stratus_health",
+    "stratus_changeToLeader",
+    "stratus_changeToFollower",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
+pub enum RpcLane {
+    #[strum(to_string = "high")]
+    High,
+
+    #[strum(to_string = "normal")]
+    Normal,
+}
+
+impl RpcLane {
+    /// Lane where a method is executed.
+    pub fn of(method: &str) -> Self {
+        if HIGH_PRIORITY_METHODS.contains(&method) {
+            Self::High
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Workers available in each lane.
+#[derive(Debug)]
+pub struct RpcLanes {
+    high: Arc<Semaphore>,
+    normal: Arc<Semaphore>,
+}
+
+impl RpcLanes {
+    pub fn new(high_workers: usize, normal_workers: usize) -> Self {
+        Self {
+            high: Arc::new(Semaphore::new(high_workers.max(1))),
+            normal: Arc::new(Semaphore::new(normal_workers.max(1))),
+        }
+    }
+
+    /// Waits for a worker of the lane to be available. The worker is released when all clones of it are dropped.
+    pub async fn acquire(&self, lane: RpcLane) -> RpcLaneWorker {
+        let semaphore = match lane {
+            RpcLane::High => &self.high,
+            RpcLane::Normal => &self.normal,
+        };
+
+        #[cfg(feature = "metrics")]
+        let start = metrics::now();
+
+        let permit = Arc::clone(semaphore).acquire_owned().await.expect("rpc lane semaphore is never closed");
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_rpc_lane_wait(start.elapsed(), <&'static str>::from(lane));
+
+        RpcLaneWorker(Arc::new(permit))
+    }
+}
+
+/// Worker of a lane assigned to a request.
+///
+/// It is stored in the request extensions, so a blocking method keeps it until its execution finishes, even if the request timed out before that.
+#[derive(Debug, Clone)]
+pub struct RpcLaneWorker(#[allow(dead_code)] Arc<OwnedSemaphorePermit>);
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_lane_of_method() {
+        assert_eq!(RpcLane::of("eth_sendRawTransaction"), RpcLane::High);
+        assert_eq!(RpcLane::of("stratus_getBlockAndReceipts"), RpcLane::High);
+        assert_eq!(RpcLane::of("eth_call"), RpcLane::Normal);
+        assert_eq!(RpcLane::of("eth_getLogs"), RpcLane::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_high_lane_not_blocked_by_normal_lane() {
+        let lanes = RpcLanes::new(1, 1);
+        let _normal = lanes.acquire(RpcLane::Normal).await;
+
+        // normal lane is full, but high lane still has a worker
+        assert!(tokio::time::timeout(Duration::from_millis(10), lanes.acquire(RpcLane::Normal)).await.is_err());
+        assert!(tokio::time::timeout(Duration::from_millis(10), lanes.acquire(RpcLane::High)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_worker_released_when_all_clones_are_dropped() {
+        let lanes = RpcLanes::new(1, 1);
+        let worker = lanes.acquire(RpcLane::Normal).await;
+        let blocking_task_worker = worker.clone();
+
+        // request finished, but its blocking task is still running
+        drop(worker);
+        assert!(tokio::time::timeout(Duration::from_millis(10), lanes.acquire(RpcLane::Normal)).await.is_err());
+
+        // blocking task finished
+        drop(blocking_task_worker);
+        assert!(tokio::time::timeout(Duration::from_millis(10), lanes.acquire(RpcLane::Normal)).await.is_ok());
+    }
+}
@@ -24,6 +24,8 @@ cfg_if! {
             F: Fn(Params<'_>, Arc<RpcContext>, &Extensions) -> Result<T, StratusError> + Clone,
         {
             move |params, ctx, extensions| {
+                // blocking methods run outside the middleware, so the chain must be entered again
+                let _chain = ctx.chain_state.enter();
                 reject_unknown_client(extensions.rpc_client())?;
                 function(params, ctx, &extensions).inspect_err(|e| metrify_stratus_error(e, &extensions, method_name))
             }
@@ -34,6 +36,8 @@ cfg_if! {
             F: Fn(Params<'_>, Arc<RpcContext>, &Extensions) -> Result<T, StratusError> + Clone,
         {
             move |params, ctx, extensions| {
+                // blocking methods run outside the middleware, so the chain must be entered again
+                let _chain = ctx.chain_state.enter();
                 reject_unknown_client(extensions.rpc_client())?;
                 function(params, ctx, &extensions)
             }
@@ -1,16 +1,20 @@
 //! Track RPC requests and responses using metrics and traces.
 
 use std::future::Future;
+use std::sync::Arc;
 use std::task::Poll;
+use std::time::Duration;
 use std::time::Instant;
 
+use chrono::Utc;
 use futures::future::BoxFuture;
-use jsonrpsee::server::middleware::rpc::layer::ResponseFuture;
+use futures::FutureExt;
 use jsonrpsee::server::middleware::rpc::RpcService;
 use jsonrpsee::server::middleware::rpc::RpcServiceT;
 #[cfg(feature = "metrics")]
 use jsonrpsee::server::ConnectionGuard;
 use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
+use jsonrpsee::types::ErrorObjectOwned;
 use jsonrpsee::types::Params;
 use jsonrpsee::MethodResponse;
 use pin_project::pin_project;
@@ -28,11 +32,19 @@ use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CallInput;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionInput;
 use crate::eth::rpc::next_rpc_param;
 use crate::eth::rpc::parse_rpc_rlp;
+use crate::eth::rpc::rpc_audit::AuditEntry;
+use crate::eth::rpc::rpc_audit::RpcAuditLog;
+use crate::eth::rpc::rpc_capture::CapturedRequest;
+use crate::eth::rpc::rpc_capture::RpcCapture;
 use crate::eth::rpc::rpc_parser::RpcExtensionsExt;
 use crate::eth::rpc::RpcClientApp;
+use crate::eth::rpc::RpcLane;
+use crate::eth::rpc::RpcLaneWorker;
+use crate::eth::rpc::RpcLanes;
 use crate::event_with;
 use crate::ext::from_json_str;
 use crate::ext::to_json_string;
@@ -42,6 +54,7 @@ use crate::infra::metrics;
 use crate::infra::tracing::new_cid;
 use crate::infra::tracing::SpanExt;
 use crate::infra::tracing::TracingExt;
+use crate::ChainState;
 
 // -----------------------------------------------------------------------------
 // Request handling
@@ -50,11 +63,34 @@ use crate::infra::tracing::TracingExt;
 #[derive(Debug)]
 pub struct RpcMiddleware {
     service: RpcService,
+    capture: Option<Arc<RpcCapture>>,
+    audit: Option<Arc<RpcAuditLog>>,
+    lanes: Arc<RpcLanes>,
+
+    /// Max time executing a request before responding with a timeout error.
+    request_timeout: Duration,
+
+    /// Chain served by the server, entered while the request is handled.
+    chain_state: Arc<ChainState>,
 }
 
 impl RpcMiddleware {
-    pub fn new(service: RpcService) -> Self {
-        Self { service }
+    pub fn new(
+        service: RpcService,
+        capture: Option<Arc<RpcCapture>>,
+        audit: Option<Arc<RpcAuditLog>>,
+        lanes: Arc<RpcLanes>,
+        request_timeout: Duration,
+        chain_state: Arc<ChainState>,
+    ) -> Self {
+        Self {
+            service,
+            capture,
+            audit,
+            lanes,
+            request_timeout,
+            chain_state,
+        }
     }
 }
 
@@ -62,6 +98,7 @@ impl<'a> RpcServiceT<'a> for RpcMiddleware {
     type Future = RpcResponse<'a>;
 
     fn call(&self, mut request: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let _chain = self.chain_state.enter();
         let span = info_span!(
             parent: None,
             "rpc::request",
@@ -132,17 +169,71 @@ impl<'a> RpcServiceT<'a> for RpcMiddleware {
             }
         }
 
+        // capture request to be replayed
+        let capture = self.capture.as_ref().map(|capture| {
+            let request = CapturedRequest {
+                timestamp: Utc::now(),
+                method: method.clone(),
+                params: request.params.as_ref().map(|params| from_json_str(params.get())),
+                result: None,
+                error: None,
+            };
+            (Arc::clone(capture), request)
+        });
+
+        // audit admin operations
+        let audit = match self.audit {
+            Some(ref audit) if RpcAuditLog::is_audited(&method) => {
+                let params = request.params.as_ref().map(|params| from_json_str(params.get()));
+                Some((Arc::clone(audit), AuditEntry::new(&client, method.clone(), params)))
+            }
+            _ => None,
+        };
+
         // make span available to rpc-server
         drop(middleware_enter);
-        request.extensions_mut().insert(span);
+        request.extensions_mut().insert(span.clone());
+
+        // execute when a worker of the request lane is available
+        // the service is called only after, because blocking methods are spawned as soon as they are called
+        // the worker goes with the request, so a blocking method holds it until it finishes even if the request times out
+        // respond with error if waiting and execution take too long, so the connection is not held by the request
+        let id = request.id.to_string();
+        let request_id = request.id.clone().into_owned();
+        let lane = RpcLane::of(&method);
+        let lanes = Arc::clone(&self.lanes);
+        let service = self.service.clone();
+        let timeout = self.request_timeout;
+        let future_response = async move {
+            let scheduled_response = async move {
+                let worker = lanes.acquire(lane).await;
+                request.extensions_mut().insert(worker);
+                let mut response = service.call(request).await;
+                response.extensions_mut().remove::<RpcLaneWorker>();
+                response
+            };
+            match tokio::time::timeout(timeout, scheduled_response).await {
+                Ok(response) => response,
+                Err(_) => {
+                    tracing::warn!(parent: &span, ?timeout, "rpc request timed out");
+                    let mut response = MethodResponse::error(request_id, ErrorObjectOwned::from(StratusError::RpcRequestTimeout { timeout }));
+                    response.extensions_mut().insert(span);
+                    response
+                }
+            }
+        }
+        .boxed();
 
         RpcResponse {
+            chain_state: Arc::clone(&self.chain_state),
             client,
-            id: request.id.to_string(),
+            id,
             method: method.to_string(),
             tx,
+            capture,
+            audit,
             start: Instant::now(),
-            future_response: self.service.call(request),
+            future_response,
         }
     }
 }
@@ -154,16 +245,25 @@ impl<'a> RpcServiceT<'a> for RpcMiddleware {
 /// https://blog.adamchalmers.com/pin-unpin/
 #[pin_project]
 pub struct RpcResponse<'a> {
+    // chain entered while polling
+    chain_state: Arc<ChainState>,
+
     // identifiers
     client: RpcClientApp,
     id: String,
     method: String,
     tx: Option<TransactionTracingIdentifiers>,
 
+    // request captured to be written with its response
+    capture: Option<(Arc<RpcCapture>, CapturedRequest)>,
+
+    // admin operation recorded with its response
+    audit: Option<(Arc<RpcAuditLog>, AuditEntry)>,
+
     // data
     start: Instant,
     #[pin]
-    future_response: ResponseFuture<BoxFuture<'a, MethodResponse>>,
+    future_response: BoxFuture<'a, MethodResponse>,
 }
 
 impl<'a> Future for RpcResponse<'a> {
@@ -172,6 +272,7 @@ impl<'a> Future for RpcResponse<'a> {
     fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
         // poll future
         let resp = self.project();
+        let _chain = resp.chain_state.enter();
         let mut response = resp.future_response.poll(cx);
 
         // when ready, track response before returning
@@ -245,6 +346,20 @@ impl<'a> Future for RpcResponse<'a> {
                 );
             }
 
+            // capture response
+            if let Some((capture, mut request)) = resp.capture.take() {
+                request.result = response_result.get("result").cloned();
+                request.error = response_result.get("error").cloned();
+                capture.capture(request);
+            }
+
+            // audit response
+            if let Some((audit, mut entry)) = resp.audit.take() {
+                entry.result = response_result.get("result").cloned();
+                entry.error = response_result.get("error").cloned();
+                audit.record(&entry);
+            }
+
             // drop span because maybe jsonrpsee is keeping it alive
             drop(middleware_enter);
             response.extensions_mut().remove::<Span>();
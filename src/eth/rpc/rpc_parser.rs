@@ -67,3 +67,58 @@ pub fn parse_rpc_rlp<T: Decodable>(value: &[u8]) -> Result<T, StratusError> {
         Err(e) => Err(StratusError::RpcTransactionInvalid { decode_error: e.to_string() }),
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::TransactionInput;
+
+    /// Wraps an RLP item in a list.
+    fn rlp_list(item: Vec<u8>) -> Vec<u8> {
+        let mut encoded = match item.len() {
+            len @ 0..=55 => vec![0xc0 + len as u8],
+            len => {
+                let len_bytes = len.to_be_bytes().into_iter().skip_while(|byte| *byte == 0).collect::<Vec<_>>();
+                let mut header = vec![0xf7 + len_bytes.len() as u8];
+                header.extend(len_bytes);
+                header
+            }
+        };
+        encoded.extend(item);
+        encoded
+    }
+
+    #[test]
+    fn test_parse_rpc_rlp_rejects_deeply_nested_lists() {
+        let mut data = vec![0xc0];
+        for _ in 0..10_000 {
+            data = rlp_list(data);
+        }
+        assert!(matches!(
+            parse_rpc_rlp::<TransactionInput>(&data),
+            Err(StratusError::RpcTransactionInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_rpc_rlp_rejects_huge_declared_length() {
+        // list declaring 4 GiB of payload, but with only a few bytes present
+        let mut data = vec![0xfb, 0xff, 0xff, 0xff, 0xff];
+        data.extend([0x01, 0x02, 0x03]);
+        assert!(matches!(
+            parse_rpc_rlp::<TransactionInput>(&data),
+            Err(StratusError::RpcTransactionInvalid { .. })
+        ));
+
+        // typed transaction declaring 4 GiB of payload
+        let mut data = vec![0x02, 0xfb, 0xff, 0xff, 0xff, 0xff];
+        data.extend([0x01, 0x02, 0x03]);
+        assert!(matches!(
+            parse_rpc_rlp::<TransactionInput>(&data),
+            Err(StratusError::RpcTransactionInvalid { .. })
+        ));
+    }
+}
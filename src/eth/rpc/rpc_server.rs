@@ -12,6 +12,7 @@ use futures::join;
 use http::Method;
 use itertools::Itertools;
 use jsonrpsee::server::middleware::http::ProxyGetRequestLayer;
+use jsonrpsee::server::PingConfig;
 use jsonrpsee::server::RandomStringIdProvider;
 use jsonrpsee::server::RpcModule;
 use jsonrpsee::server::RpcServiceBuilder;
@@ -36,45 +37,84 @@ use tracing::Instrument;
 use tracing::Span;
 
 use super::rpc_method_wrapper::metrics_wrapper;
-use crate::alias::EthersReceipt;
 use crate::alias::JsonValue;
 use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
 use crate::eth::follower::importer::ImporterConfig;
+use crate::eth::follower::importer::InvariantsPolicy;
+use crate::eth::follower::importer::ReceiptsVerification;
+use crate::eth::follower::importer::ReplicationMode;
 use crate::eth::miner::Miner;
 use crate::eth::miner::MinerMode;
+use crate::eth::primitives::is_simulate_validation_success;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CallInput;
 use crate::eth::primitives::ChainId;
+use crate::eth::primitives::ChainSpec;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferFilter;
 use crate::eth::primitives::LogFilterInput;
 use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::ReceiptProof;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferFilter;
 use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::TransactionStage;
+use crate::eth::primitives::UserOperation;
+use crate::eth::primitives::Wei;
+#[cfg(feature = "graphql")]
+use crate::eth::rpc::execute_graphql;
+#[cfg(feature = "graphql")]
+use crate::eth::rpc::graphql_schema;
 use crate::eth::rpc::next_rpc_param;
 use crate::eth::rpc::next_rpc_param_or_default;
 use crate::eth::rpc::parse_rpc_rlp;
 use crate::eth::rpc::rpc_parser::RpcExtensionsExt;
+#[cfg(feature = "grpc")]
+use crate::eth::rpc::serve_grpc;
+use crate::eth::rpc::validate_raw_transaction;
+use crate::eth::rpc::RpcAuditLog;
+use crate::eth::rpc::RpcCapture;
 use crate::eth::rpc::RpcClientApp;
 use crate::eth::rpc::RpcContext;
+#[cfg(feature = "graphql")]
+use crate::eth::rpc::RpcGraphqlMiddleware;
 use crate::eth::rpc::RpcHttpMiddleware;
+use crate::eth::rpc::RpcLanes;
 use crate::eth::rpc::RpcMiddleware;
 use crate::eth::rpc::RpcServerConfig;
 use crate::eth::rpc::RpcSubscriptions;
-use crate::eth::storage::Storage;
-use crate::eth::storage::StratusStorage;
+use crate::eth::rpc::RpcUserOperationPool;
+#[cfg(feature = "chaos")]
+use crate::eth::storage::set_storage_faults;
+#[cfg(feature = "chaos")]
+use crate::eth::storage::storage_faults;
+use crate::eth::storage::CacheInvalidation;
+#[cfg(feature = "chaos")]
+use crate::eth::storage::StorageFault;
+use crate::eth::storage::StorageReader;
 use crate::ext::not;
 use crate::ext::parse_duration;
+#[cfg(feature = "grpc")]
+use crate::ext::spawn_named;
 use crate::ext::to_json_string;
 use crate::ext::to_json_value;
 use crate::ext::InfallibleExt;
 use crate::infra::build_info;
 use crate::infra::metrics;
+use crate::infra::task_registry;
 use crate::infra::tracing::SpanExt;
+use crate::infra::watchdog;
 use crate::log_and_err;
+use crate::ChainState;
 use crate::GlobalState;
 use crate::NodeMode;
 // -----------------------------------------------------------------------------
@@ -85,7 +125,7 @@ use crate::NodeMode;
 #[allow(clippy::too_many_arguments)]
 pub async fn serve_rpc(
     // services
-    storage: Arc<StratusStorage>,
+    storage: Arc<dyn StorageReader>,
     executor: Arc<Executor>,
     miner: Arc<Miner>,
     consensus: Option<Arc<dyn Consensus>>,
@@ -100,17 +140,52 @@ pub async fn serve_rpc(
 
     // configure subscriptions
     let subs = RpcSubscriptions::spawn(
+        rpc_config.rpc_subscription_queue_size,
         miner.notifier_pending_txs.subscribe(),
         miner.notifier_blocks.subscribe(),
         miner.notifier_logs.subscribe(),
     );
 
+    // configure user operations pool
+    let miner_logs_rx = miner.notifier_logs.subscribe();
+
+    // configure fee oracle
+    let fee_oracle = rpc_config.fee_oracle.init(Arc::clone(&storage))?;
+
+    // configure state analytics
+    let state_stats = rpc_config.state_stats.init(Arc::clone(&storage));
+
+    // configure chain identity
+    let genesis_hash = storage
+        .read_block_header(BlockFilter::Number(BlockNumber::ZERO))?
+        .map(|genesis| genesis.header.hash);
+    let chain = ChainSpec::new(chain_id, genesis_hash, executor.max_code_size(), executor.max_initcode_size());
+    tracing::info!(?chain, "serving chain");
+    let chain_state = ChainState::current();
+
+    // configure audit log
+    let audit = match rpc_config.rpc_audit_file {
+        Some(ref path) => Some(Arc::new(RpcAuditLog::open(path)?)),
+        None => None,
+    };
+
+    // configure graphql
+    #[cfg(feature = "graphql")]
+    let graphql = graphql_schema(Arc::clone(&storage));
+
+    // configure grpc
+    #[cfg(feature = "grpc")]
+    let grpc = rpc_config
+        .grpc_address
+        .map(|address| spawn_named("rpc::grpc", serve_grpc(address, Arc::clone(&storage), Arc::clone(&executor))));
+
     // configure context
     let ctx = RpcContext {
         app_config: to_json_value(app_config),
-        chain_id,
+        chain,
+        chain_state: Arc::clone(&chain_state),
         client_version: "stratus",
-        gas_price: 0,
+        fee_oracle,
 
         // services
         executor,
@@ -121,6 +196,19 @@ pub async fn serve_rpc(
 
         // subscriptions
         subs: Arc::clone(&subs.connected),
+
+        // account abstraction
+        user_operations: RpcUserOperationPool::spawn(miner_logs_rx),
+
+        // analytics
+        state_stats,
+
+        // admin
+        audit: audit.clone(),
+
+        // graphql
+        #[cfg(feature = "graphql")]
+        graphql,
     };
 
     // configure module
@@ -129,14 +217,32 @@ pub async fn serve_rpc(
 
     // configure middleware
     let cors = CorsLayer::new().allow_methods([Method::POST]).allow_origin(Any).allow_headers(Any);
-    let rpc_middleware = RpcServiceBuilder::new().layer_fn(RpcMiddleware::new);
+    let rpc_capture = match rpc_config.rpc_capture_file {
+        Some(ref path) => Some(Arc::new(RpcCapture::spawn(path)?)),
+        None => None,
+    };
+    let lanes = Arc::new(RpcLanes::new(rpc_config.rpc_high_priority_workers, rpc_config.rpc_normal_priority_workers));
+    let request_timeout = rpc_config.rpc_request_timeout;
+    let rpc_middleware = RpcServiceBuilder::new().layer_fn(move |service| {
+        RpcMiddleware::new(
+            service,
+            rpc_capture.clone(),
+            audit.clone(),
+            Arc::clone(&lanes),
+            request_timeout,
+            Arc::clone(&chain_state),
+        )
+    });
+    let (http_keep_alive, max_connection_lifetime) = (rpc_config.rpc_http_keep_alive, rpc_config.rpc_max_connection_lifetime);
     let http_middleware = tower::ServiceBuilder::new()
         .layer(cors)
-        .layer_fn(RpcHttpMiddleware::new)
+        .layer_fn(move |service| RpcHttpMiddleware::new(service, http_keep_alive, max_connection_lifetime))
         .layer(ProxyGetRequestLayer::new("/health", "stratus_health").unwrap())
         .layer(ProxyGetRequestLayer::new("/version", "stratus_version").unwrap())
         .layer(ProxyGetRequestLayer::new("/config", "stratus_config").unwrap())
         .layer(ProxyGetRequestLayer::new("/state", "stratus_state").unwrap());
+    #[cfg(feature = "graphql")]
+    let http_middleware = http_middleware.layer_fn(RpcGraphqlMiddleware::new);
 
     // serve module
     let server = Server::builder()
@@ -144,6 +250,11 @@ pub async fn serve_rpc(
         .set_http_middleware(http_middleware)
         .set_id_provider(RandomStringIdProvider::new(8))
         .max_connections(rpc_config.rpc_max_connections)
+        .enable_ws_ping(
+            PingConfig::new()
+                .ping_interval(rpc_config.rpc_ws_ping_interval)
+                .inactive_limit(rpc_config.rpc_ws_inactive_limit),
+        )
         .build(rpc_config.rpc_address)
         .await?;
 
@@ -163,6 +274,12 @@ pub async fn serve_rpc(
     // await rpc server and subscriptions to finish
     join!(handle_rpc_server.stopped(), subs.handles.stopped());
 
+    // await grpc server to finish, its errors are already logged
+    #[cfg(feature = "grpc")]
+    if let Some(grpc) = grpc {
+        let _ = grpc.await;
+    }
+
     Ok(())
 }
 
@@ -191,26 +308,52 @@ fn register_methods(mut module: RpcModule<RpcContext>) -> anyhow::Result<RpcModu
     module.register_async_method("stratus_initImporter", stratus_init_importer)?;
     module.register_method("stratus_shutdownImporter", stratus_shutdown_importer)?;
     module.register_async_method("stratus_changeMinerMode", stratus_change_miner_mode)?;
+    register_blocking_method(&mut module, "stratus_getAuditLog", stratus_get_audit_log)?;
+    #[cfg(feature = "chaos")]
+    module.register_method("stratus_setStorageFaults", stratus_set_storage_faults)?;
 
     // stratus state
     module.register_method("stratus_version", stratus_version)?;
     module.register_method("stratus_config", stratus_config)?;
     module.register_method("stratus_state", stratus_state)?;
+    module.register_method("stratus_tasks", stratus_tasks)?;
 
     module.register_async_method("stratus_getSubscriptions", stratus_get_subscriptions)?;
     module.register_method("stratus_pendingTransactionsCount", stratus_pending_transactions_count)?;
+    register_blocking_method(&mut module, "stratus_gasStats", stratus_gas_stats)?;
+    register_blocking_method(&mut module, "stratus_getStorageRange", stratus_get_storage_range)?;
+    module.register_method("stratus_stateStats", stratus_state_stats)?;
+    register_blocking_method(&mut module, "stratus_getBalanceHistory", stratus_get_balance_history)?;
+    register_blocking_method(&mut module, "stratus_getInternalTransactions", stratus_get_internal_transactions)?;
+    register_blocking_method(&mut module, "stratus_getTokenTransfers", stratus_get_token_transfers)?;
+    register_blocking_method(&mut module, "stratus_getContractCreation", stratus_get_contract_creation)?;
+    register_blocking_method(&mut module, "stratus_getStateDiff", stratus_get_state_diff)?;
+    register_blocking_method(&mut module, "stratus_callMany", stratus_call_many)?;
+    register_blocking_method(&mut module, "stratus_simulateBundle", stratus_simulate_bundle)?;
+    #[cfg(feature = "graphql")]
+    register_blocking_method(&mut module, "stratus_graphql", stratus_graphql)?;
 
     // blockchain
     module.register_method("net_version", net_version)?;
     module.register_async_method("net_listening", net_listening)?;
     module.register_method("eth_chainId", eth_chain_id)?;
+    module.register_method("eth_coinbase", eth_coinbase)?;
+    register_blocking_method(&mut module, "stratus_chainInfo", stratus_chain_info)?;
     module.register_method("web3_clientVersion", web3_client_version)?;
 
     // gas
-    module.register_method("eth_gasPrice", eth_gas_price)?;
+    register_blocking_method(&mut module, "eth_gasPrice", eth_gas_price)?;
+    register_blocking_method(&mut module, "eth_maxPriorityFeePerGas", eth_max_priority_fee_per_gas)?;
 
     // stratus importing helpers
     register_blocking_method(&mut module, "stratus_getBlockAndReceipts", stratus_get_block_and_receipts)?;
+    register_blocking_method(&mut module, "stratus_getReceiptProof", stratus_get_receipt_proof)?;
+
+    // account abstraction
+    module.register_method("eth_supportedEntryPoints", eth_supported_entry_points)?;
+    register_blocking_method(&mut module, "eth_sendUserOperation", eth_send_user_operation)?;
+    register_blocking_method(&mut module, "debug_simulateUserOperationValidation", debug_simulate_user_operation_validation)?;
+    register_blocking_method(&mut module, "stratus_pendingUserOperations", stratus_pending_user_operations)?;
 
     // block
     register_blocking_method(&mut module, "eth_blockNumber", eth_block_number)?;
@@ -291,7 +434,7 @@ async fn stratus_health(_: Params<'_>, context: Arc<RpcContext>, _: Extensions)
     }
 
     let should_serve = match GlobalState::get_node_mode() {
-        NodeMode::Leader | NodeMode::FakeLeader => true,
+        NodeMode::Leader | NodeMode::FakeLeader | NodeMode::Archive => true,
         NodeMode::Follower => match context.consensus() {
             Some(consensus) => consensus.should_serve().await,
             None => false,
@@ -305,6 +448,12 @@ async fn stratus_health(_: Params<'_>, context: Arc<RpcContext>, _: Extensions)
     }
 
     metrics::set_consensus_is_ready(1_u64);
+
+    if not(watchdog::is_healthy()) {
+        tracing::warn!("readiness check failed because a critical loop is stalled");
+        return Err(StratusError::StratusNotReady);
+    }
+
     Ok(json!(true))
 }
 
@@ -314,7 +463,7 @@ async fn stratus_health(_: Params<'_>, context: Arc<RpcContext>, _: Extensions)
 
 #[cfg(feature = "dev")]
 fn stratus_reset(_: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
-    ctx.storage.reset_to_genesis()?;
+    ctx.miner.reset_to_genesis()?;
     Ok(to_json_value(true))
 }
 
@@ -330,6 +479,11 @@ async fn stratus_change_to_leader(_: Params<'_>, ctx: Arc<RpcContext>, ext: Exte
     const LEADER_MINER_INTERVAL: Duration = Duration::from_secs(1);
     tracing::info!("starting process to change node to leader");
 
+    if GlobalState::get_node_mode() == NodeMode::Archive {
+        tracing::error!("node is an archive, cannot change node mode");
+        return Err(StratusError::StratusArchive);
+    }
+
     if GlobalState::get_node_mode() == NodeMode::Leader {
         tracing::info!("node is already in leader mode, no changes made");
         return Ok(json!(false));
@@ -378,6 +532,11 @@ async fn stratus_change_to_follower(params: Params<'_>, ctx: Arc<RpcContext>, ex
 
     tracing::info!("starting process to change node to follower");
 
+    if GlobalState::get_node_mode() == NodeMode::Archive {
+        tracing::error!("node is an archive, cannot change node mode");
+        return Err(StratusError::StratusArchive);
+    }
+
     if GlobalState::get_node_mode() == NodeMode::Follower {
         tracing::info!("node is already in follower mode, no changes made");
         return Ok(json!(false));
@@ -443,7 +602,15 @@ async fn stratus_init_importer(params: Params<'_>, ctx: Arc<RpcContext>, _: Exte
         external_rpc,
         external_rpc_ws: Some(external_rpc_ws),
         external_rpc_timeout,
+        external_rpc_circuit_breaker_threshold: 5,
+        external_rpc_circuit_breaker_cooldown: Duration::from_secs(5),
+        external_rpc_max_retries: 3,
+        external_rpc_retry_backoff: Duration::from_millis(10),
         sync_interval,
+        block_invariants: InvariantsPolicy::default(),
+        receipts_verification: ReceiptsVerification::default(),
+        replication_mode: ReplicationMode::default(),
+        spot_check_interval: 100,
     };
 
     importer_config.init_follower_importer(ctx).await
@@ -482,6 +649,11 @@ async fn stratus_change_miner_mode(params: Params<'_>, ctx: Arc<RpcContext>, _:
 ///
 /// This function also enables the miner after changing it.
 async fn change_miner_mode(new_mode: MinerMode, ctx: &RpcContext) -> Result<JsonValue, StratusError> {
+    if GlobalState::get_node_mode() == NodeMode::Archive {
+        tracing::error!("cannot change miner mode of an archive node");
+        return Err(StratusError::StratusArchive);
+    }
+
     if GlobalState::is_transactions_enabled() {
         tracing::error!("cannot change miner mode while transactions are enabled");
         return Err(StratusError::RpcTransactionEnabled);
@@ -560,6 +732,63 @@ fn stratus_disable_miner(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> boo
     false
 }
 
+/// Replaces the faults injected in the permanent storage and returns the ones now active. An empty list disables fault injection.
+#[cfg(feature = "chaos")]
+fn stratus_set_storage_faults(params: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let (_, raw_faults) = next_rpc_param::<Vec<String>>(params.sequence())?;
+    let faults = raw_faults
+        .iter()
+        .map(|raw_fault| StorageFault::from_str(raw_fault))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| StratusError::RpcParameterInvalid {
+            rust_type: "StorageFault",
+            decode_error: e.to_string(),
+        })?;
+
+    set_storage_faults(faults);
+    Ok(to_json_value(storage_faults().iter().map(ToString::to_string).collect::<Vec<_>>()))
+}
+
+/// Returns a page of the admin operations recorded in the audit log, oldest first.
+fn stratus_get_audit_log(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = 1000;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getAuditLog").entered();
+
+    // check audit log is enabled
+    let Some(ref audit) = ctx.audit else {
+        return Err(StratusError::RpcAuditLogDisabled);
+    };
+
+    // parse params
+    let (params, offset) = next_rpc_param_or_default::<Option<usize>>(params.sequence())?;
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+    let offset = offset.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    // validate
+    if limit > MAX_LIMIT {
+        return Err(StratusError::RpcResultLimitExceeded { actual: limit, max: MAX_LIMIT });
+    }
+
+    // execute: reads one extra entry to know if there is a next page
+    let mut entries = audit.read(offset, limit + 1)?;
+    let next_offset = if entries.len() > limit {
+        entries.pop();
+        Some(offset + limit)
+    } else {
+        None
+    };
+
+    Ok(json!({
+        "entries": entries,
+        "nextOffset": next_offset,
+    }))
+}
+
 /// Returns the count of executed transactions waiting to enter the next block.
 fn stratus_pending_transactions_count(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> usize {
     ctx.storage.pending_transactions().len()
@@ -581,6 +810,10 @@ fn stratus_state(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<Json
     Ok(GlobalState::get_global_state_as_json(ctx))
 }
 
+fn stratus_tasks(_: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    Ok(to_json_value(task_registry::tasks()))
+}
+
 async fn stratus_get_subscriptions(_: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
     reject_unknown_client(ext.rpc_client())?;
 
@@ -597,6 +830,322 @@ async fn stratus_get_subscriptions(_: Params<'_>, ctx: Arc<RpcContext>, ext: Ext
     Ok(response)
 }
 
+/// Returns gas used aggregated per contract and function over the committed blocks, persisted across restarts.
+fn stratus_gas_stats(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_gasStats").entered();
+
+    // parse params
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params.sequence())?;
+
+    // execute
+    let Some(ref gas_analytics) = ctx.miner.gas_analytics else {
+        return Err(StratusError::RpcGasAnalyticsDisabled);
+    };
+    Ok(to_json_value(gas_analytics.stats(limit.unwrap_or(DEFAULT_LIMIT))))
+}
+
+fn stratus_state_stats(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_stateStats").entered();
+
+    // parse params
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params.sequence())?;
+
+    // execute
+    let Some(ref state_stats) = ctx.state_stats else {
+        return Err(StratusError::RpcStateStatsDisabled);
+    };
+    match state_stats.stats(limit.unwrap_or(DEFAULT_LIMIT)) {
+        Some(stats) => Ok(to_json_value(stats)),
+        None => Err(StratusError::RpcStateStatsNotReady),
+    }
+}
+
+fn stratus_get_balance_history(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = 1000;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getBalanceHistory", address = field::Empty, from_block = field::Empty).entered();
+
+    // parse params
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (params, from_block) = next_rpc_param_or_default::<Option<BlockNumber>>(params)?;
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+    let from_block = from_block.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("address", &address);
+        s.rec_str("from_block", &from_block);
+    });
+
+    // validate
+    if limit > MAX_LIMIT {
+        return Err(StratusError::RpcResultLimitExceeded { actual: limit, max: MAX_LIMIT });
+    }
+
+    // execute
+    let (changes, next_block) = read_page_by_block(
+        limit,
+        |limit| ctx.storage.read_balance_changes(address, from_block, limit),
+        |change| change.block_number,
+    )?;
+
+    let changes = changes
+        .into_iter()
+        .map(|change| {
+            json!({
+                "blockNumber": change.block_number,
+                "transactionHash": change.transaction_hash,
+                "transactionIndex": change.transaction_index,
+                "previousBalance": change.previous_balance,
+                "balance": change.balance,
+                "delta": change.delta(),
+            })
+        })
+        .collect_vec();
+    Ok(json!({
+        "changes": changes,
+        "nextBlock": next_block,
+    }))
+}
+
+fn stratus_get_internal_transactions(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = 1000;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getInternalTransactions", filter = field::Empty).entered();
+
+    // parse params
+    let (params, filter) = next_rpc_param::<InternalTransferFilter>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &to_json_string(&filter)));
+
+    // execute
+    let (transfers, next_block) = match filter {
+        InternalTransferFilter::Block(filter) => {
+            let number = match filter {
+                BlockFilter::Number(number) => Some(number),
+                filter => ctx.storage.read_block(filter)?.map(|block| block.number()),
+            };
+            let transfers = match number {
+                Some(number) => ctx.storage.read_internal_transfers_by_block(number)?,
+                None => vec![],
+            };
+            (transfers, None)
+        }
+        InternalTransferFilter::Address(address) => {
+            let (params, from_block) = next_rpc_param_or_default::<Option<BlockNumber>>(params)?;
+            let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+            let from_block = from_block.unwrap_or_default();
+            let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+            // validate
+            if limit > MAX_LIMIT {
+                return Err(StratusError::RpcResultLimitExceeded { actual: limit, max: MAX_LIMIT });
+            }
+
+            read_page_by_block(
+                limit,
+                |limit| ctx.storage.read_internal_transfers_by_address(address, from_block, limit),
+                |transfer| transfer.block_number,
+            )?
+        }
+    };
+
+    let transfers = transfers
+        .into_iter()
+        .map(|mined| {
+            json!({
+                "blockNumber": mined.block_number,
+                "transactionHash": mined.transaction_hash,
+                "transactionIndex": mined.transaction_index,
+                "type": mined.transfer.kind,
+                "from": mined.transfer.from,
+                "to": mined.transfer.to,
+                "value": mined.transfer.value,
+                "depth": mined.transfer.depth,
+            })
+        })
+        .collect_vec();
+    Ok(json!({
+        "transfers": transfers,
+        "nextBlock": next_block,
+    }))
+}
+
+fn stratus_get_token_transfers(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = 1000;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getTokenTransfers", filter = field::Empty).entered();
+
+    // check indexing is enabled
+    if not(ctx.storage.token_transfers_indexed()) {
+        return Err(StratusError::RpcTokenTransfersDisabled);
+    }
+
+    // parse params
+    let (params, filter) = next_rpc_param::<TokenTransferFilter>(params.sequence())?;
+    let (params, from_block) = next_rpc_param_or_default::<Option<BlockNumber>>(params)?;
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+    let from_block = from_block.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    // track
+    Span::with(|s| s.rec_str("filter", &to_json_string(&filter)));
+
+    // validate
+    if limit > MAX_LIMIT {
+        return Err(StratusError::RpcResultLimitExceeded { actual: limit, max: MAX_LIMIT });
+    }
+
+    // execute
+    let (mut transfers, next_block) = read_page_by_block(
+        limit,
+        |limit| match filter {
+            TokenTransferFilter { account: Some(account), .. } => ctx.storage.read_token_transfers_by_account(account, from_block, limit),
+            TokenTransferFilter { token: Some(token), .. } => ctx.storage.read_token_transfers_by_token(token, from_block, limit),
+            TokenTransferFilter { account: None, token: None } => Err(StratusError::RpcParameterInvalid {
+                rust_type: "TokenTransferFilter",
+                decode_error: "account or token must be specified".to_string(),
+            }),
+        },
+        |transfer| transfer.block_number,
+    )?;
+
+    // account and token filter: the account index is paginated, so the token is filtered after
+    if let (Some(_), Some(token)) = (filter.account, filter.token) {
+        transfers.retain(|transfer| transfer.transfer.token == token);
+    }
+
+    let transfers = transfers
+        .into_iter()
+        .map(|mined| {
+            json!({
+                "blockNumber": mined.block_number,
+                "transactionHash": mined.transaction_hash,
+                "transactionIndex": mined.transaction_index,
+                "logIndex": mined.log_index,
+                "standard": mined.transfer.standard,
+                "token": mined.transfer.token,
+                "from": mined.transfer.from,
+                "to": mined.transfer.to,
+                "tokenId": mined.transfer.token_id,
+                "value": mined.transfer.value,
+            })
+        })
+        .collect_vec();
+    Ok(json!({
+        "transfers": transfers,
+        "nextBlock": next_block,
+    }))
+}
+
+fn stratus_get_contract_creation(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getContractCreation", address = field::Empty).entered();
+
+    // parse params
+    let (_, address) = next_rpc_param::<Address>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("address", &address));
+
+    // execute
+    match ctx.storage.read_contract_creation(address)? {
+        Some(creation) => Ok(json!({
+            "contractAddress": creation.address,
+            "creator": creation.creator,
+            "transactionHash": creation.transaction_hash,
+            "transactionIndex": creation.transaction_index,
+            "blockNumber": creation.block_number,
+        })),
+        None => Ok(JsonValue::Null),
+    }
+}
+
+fn stratus_get_state_diff(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getStateDiff", filter = field::Empty, block_number = field::Empty).entered();
+
+    // parse params
+    let (_, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &filter));
+
+    // execute
+    let number = match filter {
+        BlockFilter::Number(number) => Some(number),
+        filter => ctx.storage.read_block(filter)?.map(|block| block.number()),
+    };
+    let Some(number) = number else { return Ok(JsonValue::Null) };
+    Span::with(|s| s.rec_str("block_number", &number));
+
+    match ctx.storage.read_state_diff(number)? {
+        Some(state_diff) => Ok(json!({
+            "blockNumber": number,
+            "stateDiff": state_diff_json(&state_diff),
+        })),
+        None => Ok(JsonValue::Null),
+    }
+}
+
+fn stratus_get_storage_range(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 256;
+    const MAX_LIMIT: usize = 1024;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getStorageRange", address = field::Empty, start = field::Empty).entered();
+
+    // parse params
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (params, start) = next_rpc_param_or_default::<Option<SlotIndex>>(params)?;
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+    let start = start.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("address", &address);
+        s.rec_str("start", &start);
+    });
+
+    // validate
+    if limit > MAX_LIMIT {
+        return Err(StratusError::RpcStorageRangeInvalid { actual: limit, max: MAX_LIMIT });
+    }
+
+    // execute
+    // reads one extra slot to know where the next page starts
+    let mut slots = ctx.storage.read_slots_range(address, start, limit + 1)?;
+    let next_key = if slots.len() > limit { slots.pop().map(|slot| slot.index) } else { None };
+
+    Ok(json!({
+        "slots": slots,
+        "nextKey": next_key,
+    }))
+}
+
 // -----------------------------------------------------------------------------
 // Blockchain
 // -----------------------------------------------------------------------------
@@ -610,11 +1159,29 @@ async fn net_listening(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions
 }
 
 fn net_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
-    ctx.chain_id.to_string()
+    ctx.chain.chain_id.to_string()
 }
 
 fn eth_chain_id(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
-    hex_num(ctx.chain_id)
+    hex_num(ctx.chain.chain_id)
+}
+
+fn eth_coinbase(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> JsonValue {
+    to_json_value(ctx.miner.block_author())
+}
+
+/// Returns the chain id, genesis hash and hardfork of the served chain.
+fn stratus_chain_info(_: Params<'_>, ctx: Arc<RpcContext>, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let mut chain = ctx.chain.clone();
+
+    // genesis may be saved after the server started, like in followers
+    if chain.genesis_hash.is_none() {
+        chain.genesis_hash = ctx
+            .storage
+            .read_block_header(BlockFilter::Number(BlockNumber::ZERO))?
+            .map(|genesis| genesis.header.hash);
+    }
+    Ok(to_json_value(chain))
 }
 
 fn web3_client_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
@@ -625,8 +1192,136 @@ fn web3_client_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Strin
 // Gas
 // -----------------------------------------------------------------------------
 
-fn eth_gas_price(_: Params<'_>, _: &RpcContext, _: &Extensions) -> String {
-    hex_zero()
+fn eth_gas_price(_: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_gasPrice").entered();
+
+    let suggestion = ctx.fee_oracle.suggest()?;
+    Ok(hex_num(suggestion.gas_price))
+}
+
+fn eth_max_priority_fee_per_gas(_: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_maxPriorityFeePerGas").entered();
+
+    let suggestion = ctx.fee_oracle.suggest()?;
+    Ok(hex_num(suggestion.max_priority_fee_per_gas))
+}
+
+// -----------------------------------------------------------------------------
+// Account abstraction
+// -----------------------------------------------------------------------------
+
+fn eth_supported_entry_points(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    Ok(to_json_value(&ctx.rpc_server.rpc_entry_points))
+}
+
+fn eth_send_user_operation(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_sendUserOperation", user_op_sender = field::Empty, entry_point = field::Empty).entered();
+
+    // parse params
+    let (params, user_op) = next_rpc_param::<UserOperation>(params.sequence())?;
+    let (_, entry_point) = next_rpc_param::<Address>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("user_op_sender", &user_op.sender);
+        s.rec_str("entry_point", &entry_point);
+    });
+    tracing::info!(sender = %user_op.sender, nonce = %user_op.nonce, %entry_point, "sending user operation");
+
+    // validate before accepting in the pool
+    simulate_user_operation_validation(&ctx, &user_op, entry_point)?;
+
+    let hash = user_op.hash(entry_point, ctx.chain.chain_id);
+    ctx.user_operations.add(hash, entry_point, user_op)?;
+    Ok(hex_data(hash))
+}
+
+fn debug_simulate_user_operation_validation(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!(
+        "rpc::debug_simulateUserOperationValidation",
+        user_op_sender = field::Empty,
+        entry_point = field::Empty
+    )
+    .entered();
+
+    // parse params
+    let (params, user_op) = next_rpc_param::<UserOperation>(params.sequence())?;
+    let (_, entry_point) = next_rpc_param::<Address>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("user_op_sender", &user_op.sender);
+        s.rec_str("entry_point", &entry_point);
+    });
+    tracing::info!(sender = %user_op.sender, nonce = %user_op.nonce, %entry_point, "simulating user operation validation");
+
+    let output = simulate_user_operation_validation(&ctx, &user_op, entry_point)?;
+    Ok(json!({
+        "userOpHash": user_op.hash(entry_point, ctx.chain.chain_id),
+        "validationResult": output,
+    }))
+}
+
+fn stratus_pending_user_operations(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_pendingUserOperations", entry_point = field::Empty).entered();
+
+    // parse params
+    let (_, entry_point) = next_rpc_param::<Address>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("entry_point", &entry_point));
+
+    if not(ctx.rpc_server.rpc_entry_points.contains(&entry_point)) {
+        return Err(StratusError::UserOperationEntryPointNotSupported { entry_point });
+    }
+    Ok(to_json_value(ctx.user_operations.pending(entry_point)))
+}
+
+/// Executes `EntryPoint.simulateValidation` for the user operation, returning the validation result when the operation is valid.
+fn simulate_user_operation_validation(ctx: &RpcContext, user_op: &UserOperation, entry_point: Address) -> Result<Bytes, StratusError> {
+    if not(ctx.rpc_server.rpc_entry_points.contains(&entry_point)) {
+        return Err(StratusError::UserOperationEntryPointNotSupported { entry_point });
+    }
+
+    let call = CallInput {
+        from: None,
+        to: Some(entry_point),
+        value: Wei::default(),
+        data: user_op.encode_simulate_validation(),
+    };
+    let result = match ctx.executor.execute_local_validation_call(call) {
+        Ok(result) => result,
+        Err(e) => {
+            if e.is_fatal() {
+                tracing::error!(reason = ?e, "failed to simulate user operation validation");
+            }
+            return Err(e);
+        }
+    };
+
+    // simulation always reverts, so only the output tells if the validation succeeded
+    if not(result.banned_opcodes.is_empty()) {
+        tracing::warn!(banned_opcodes = ?result.banned_opcodes, "user operation validation executed banned opcodes");
+        return Err(StratusError::UserOperationBannedOpcodes {
+            opcodes: result.banned_opcodes,
+        });
+    }
+    let output = result.execution.output;
+    if not(is_simulate_validation_success(&output)) {
+        tracing::warn!(%output, "user operation validation failed");
+        return Err(StratusError::UserOperationValidationFailed { output });
+    }
+    Ok(output)
 }
 
 // -----------------------------------------------------------------------------
@@ -651,10 +1346,11 @@ fn stratus_get_block_and_receipts(params: Params<'_>, ctx: Arc<RpcContext>, ext:
     let _method_enter = info_span!("rpc::stratus_getBlockAndReceipts").entered();
 
     // parse params
-    let (_, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+    let (params, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+    let (_, with_executions) = next_rpc_param_or_default::<bool>(params)?;
 
     // track
-    tracing::info!(%filter, "reading block and receipts");
+    tracing::info!(%filter, %with_executions, "reading block and receipts");
 
     let Some(block) = ctx.storage.read_block(filter)? else {
         tracing::info!(%filter, "block not found");
@@ -662,12 +1358,47 @@ fn stratus_get_block_and_receipts(params: Params<'_>, ctx: Arc<RpcContext>, ext:
     };
 
     tracing::info!(%filter, "block with transactions found");
-    let receipts = block.transactions.iter().cloned().map(EthersReceipt::from).collect::<Vec<_>>();
+    let receipts = block.to_ethers_receipts(ctx.executor.gas_mode());
+    let invalidations = CacheInvalidation::from_block(&block);
+    let executions = with_executions.then(|| to_json_value(block.transactions.iter().map(|tx| &tx.execution).collect_vec()));
 
-    Ok(json!({
+    let mut json = json!({
         "block": block.to_json_rpc_with_full_transactions(),
         "receipts": receipts,
-    }))
+        "invalidations": invalidations,
+    });
+    if let Some(executions) = executions {
+        json["executions"] = executions;
+    }
+    Ok(json)
+}
+
+fn stratus_get_receipt_proof(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getReceiptProof", tx_hash = field::Empty).entered();
+
+    // parse params
+    let (_, tx_hash) = next_rpc_param::<Hash>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("tx_hash", &tx_hash));
+    tracing::info!(%tx_hash, "reading receipt proof");
+
+    // only mined transactions have receipts in a block trie
+    let Some(TransactionStage::Mined(tx)) = ctx.storage.read_transaction(tx_hash)? else {
+        tracing::info!(%tx_hash, "mined transaction not found");
+        return Ok(JsonValue::Null);
+    };
+    let Some(block) = ctx.storage.read_block(BlockFilter::Number(tx.block_number))? else {
+        tracing::info!(%tx_hash, block_number = %tx.block_number, "transaction block not found");
+        return Ok(JsonValue::Null);
+    };
+
+    match ReceiptProof::new(&block, tx_hash)? {
+        Some(proof) => Ok(to_json_value(proof)),
+        None => Ok(JsonValue::Null),
+    }
 }
 
 fn eth_get_block_by_hash(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
@@ -709,6 +1440,12 @@ fn eth_get_block_by_selector<const KIND: char>(params: Params<'_>, ctx: Arc<RpcC
     tracing::info!(%filter, %full_transactions, "reading block");
 
     // execute
+    if filter.is_pending() {
+        return eth_get_pending_block(&*ctx.storage, ctx.miner.block_author(), full_transactions);
+    }
+    if not(full_transactions) {
+        return eth_get_block_header_by_selector(&ctx, filter);
+    }
     let block = ctx.storage.read_block(filter)?;
     Span::with(|s| {
         s.record("found", block.is_some());
@@ -716,22 +1453,69 @@ fn eth_get_block_by_selector<const KIND: char>(params: Params<'_>, ctx: Arc<RpcC
             s.rec_str("block_number", &block.number());
         }
     });
-    match (block, full_transactions) {
-        (Some(block), true) => {
+    match block {
+        Some(block) => {
             tracing::info!(%filter, "block with full transactions found");
             Ok(block.to_json_rpc_with_full_transactions())
         }
-        (Some(block), false) => {
+        None => {
+            tracing::info!(%filter, "block not found");
+            Ok(JsonValue::Null)
+        }
+    }
+}
+
+/// Reads a block with only transactions hashes, which is served from the header cache for the latest blocks.
+fn eth_get_block_header_by_selector(ctx: &RpcContext, filter: BlockFilter) -> Result<JsonValue, StratusError> {
+    let header = ctx.storage.read_block_header(filter)?;
+    Span::with(|s| {
+        s.record("found", header.is_some());
+        if let Some(ref header) = header {
+            s.rec_str("block_number", &header.header.number);
+        }
+    });
+    match header {
+        Some(header) => {
             tracing::info!(%filter, "block with only hashes found");
-            Ok(block.to_json_rpc_with_transactions_hashes())
+            Ok(header.to_json_rpc_with_transactions_hashes())
         }
-        (None, _) => {
+        None => {
             tracing::info!(%filter, "block not found");
             Ok(JsonValue::Null)
         }
     }
 }
 
+/// Reads the block being mined from the temporary storage, with the transactions executed so far on top of the latest block.
+///
+/// The block is not sealed yet, so it has no hash and its roots and logs bloom are not calculated.
+fn eth_get_pending_block(storage: &dyn StorageReader, author: Address, full_transactions: bool) -> Result<JsonValue, StratusError> {
+    let pending_header = storage.read_pending_block_header();
+    let txs = storage.pending_transactions();
+    Span::with(|s| {
+        s.record("found", true);
+        s.rec_str("block_number", &pending_header.number);
+    });
+
+    let mut block = Block::new(pending_header.number, *pending_header.timestamp);
+    block.header.author = author;
+    if let Some(parent) = storage.read_block_header(BlockFilter::Latest)? {
+        block.header.parent_hash = parent.header.hash;
+    }
+    block.header.gas_used = Gas::from(txs.iter().map(|tx| tx.execution().gas.as_u64()).sum::<u64>());
+
+    let mut json_rpc_block = block.to_json_rpc_with_transactions_hashes();
+    json_rpc_block["hash"] = JsonValue::Null;
+    json_rpc_block["transactions"] = if full_transactions {
+        txs.into_iter().map(|tx| TransactionStage::Executed(tx).to_json_rpc_transaction()).collect()
+    } else {
+        txs.iter().map(|tx| to_json_value(tx.hash())).collect()
+    };
+
+    tracing::info!(number = %pending_header.number, %full_transactions, "pending block found");
+    Ok(json_rpc_block)
+}
+
 fn eth_get_uncle_by_block_hash_and_index(_: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
     Ok(JsonValue::Null)
 }
@@ -791,7 +1575,7 @@ fn eth_get_transaction_receipt(params: Params<'_>, ctx: Arc<RpcContext>, ext: &E
     match tx {
         Some(tx) => {
             tracing::info!(%tx_hash, "transaction receipt found");
-            Ok(tx.to_json_rpc_receipt())
+            Ok(tx.to_json_rpc_receipt(ctx.executor.gas_mode()))
         }
         None => {
             tracing::info!(%tx_hash, "transaction receipt not found");
@@ -832,7 +1616,7 @@ fn eth_estimate_gas(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions)
 
         // internal error
         Err(e) => {
-            if e.is_internal() {
+            if e.is_fatal() {
                 tracing::error!(reason = ?e, "failed to execute eth_estimateGas");
             }
             Err(e)
@@ -874,7 +1658,7 @@ fn eth_call(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Resul
 
         // internal error
         Err(e) => {
-            if e.is_internal() {
+            if e.is_fatal() {
                 tracing::error!(reason = ?e, "failed to execute eth_call");
             }
             Err(e)
@@ -882,6 +1666,99 @@ fn eth_call(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Resul
     }
 }
 
+fn stratus_call_many(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const MAX_CALLS: usize = 100;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_callMany", calls = field::Empty, filter = field::Empty).entered();
+
+    // parse params
+    let (params, calls) = next_rpc_param::<Vec<CallInput>>(params.sequence())?;
+    let (params, filter) = next_rpc_param_or_default::<BlockFilter>(params)?;
+    let (_, apply_changes) = next_rpc_param_or_default::<Option<bool>>(params)?;
+    let apply_changes = apply_changes.unwrap_or(true);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("calls", &calls.len());
+        s.rec_str("filter", &filter);
+    });
+
+    // validate
+    if calls.len() > MAX_CALLS {
+        return Err(StratusError::RpcCallsLimitExceeded {
+            actual: calls.len(),
+            max: MAX_CALLS,
+        });
+    }
+
+    // execute
+    let point_in_time = ctx.storage.translate_to_point_in_time(filter)?;
+    let executions = ctx.executor.execute_local_calls(calls, point_in_time, apply_changes)?;
+
+    let results = executions.iter().map(simulated_execution_json).collect_vec();
+    Ok(JsonValue::Array(results))
+}
+
+fn stratus_simulate_bundle(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    const MAX_TRANSACTIONS: usize = 100;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_simulateBundle", txs = field::Empty, filter = field::Empty).entered();
+
+    // parse params
+    let (params, txs_data) = next_rpc_param::<Vec<Bytes>>(params.sequence())?;
+    let (_, filter) = next_rpc_param_or_default::<BlockFilter>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("txs", &txs_data.len());
+        s.rec_str("filter", &filter);
+    });
+
+    // validate the bundle size before decoding its transactions
+    if txs_data.len() > MAX_TRANSACTIONS {
+        return Err(StratusError::RpcCallsLimitExceeded {
+            actual: txs_data.len(),
+            max: MAX_TRANSACTIONS,
+        });
+    }
+    let txs = txs_data
+        .iter()
+        .map(|tx_data| parse_rpc_rlp::<TransactionInput>(tx_data))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // validate
+    for tx in &txs {
+        if let Some(chain_id) = tx.chain_id {
+            if chain_id != ctx.chain.chain_id {
+                return Err(StratusError::RpcTransactionChainIdMismatch {
+                    expected: ctx.chain.chain_id,
+                    actual: chain_id,
+                });
+            }
+        }
+    }
+
+    // execute
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, filter)?;
+    let tx_hashes = txs.iter().map(|tx| tx.hash).collect_vec();
+    let executions = ctx.executor.simulate_local_transactions(txs, point_in_time)?;
+
+    let results = tx_hashes
+        .into_iter()
+        .zip(executions)
+        .map(|(tx_hash, execution)| {
+            let mut result = simulated_execution_json(&execution);
+            result["transactionHash"] = json!(tx_hash);
+            result
+        })
+        .collect_vec();
+    Ok(JsonValue::Array(results))
+}
+
 fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<String, StratusError> {
     // enter span
     let _middleware_enter = ext.enter_middleware_span();
@@ -894,9 +1771,11 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Exte
     )
     .entered();
 
-    // parse params
+    // parse and validate params
+    // state is only checked by nodes that execute the transaction, because followers may be behind the leader
     let (_, tx_data) = next_rpc_param::<Bytes>(params.sequence())?;
-    let tx = parse_rpc_rlp::<TransactionInput>(&tx_data)?;
+    let node_mode = GlobalState::get_node_mode();
+    let tx = validate_raw_transaction(&ctx, &tx_data, not(matches!(node_mode, NodeMode::Follower)))?;
     let tx_hash = tx.hash;
 
     // track
@@ -913,11 +1792,11 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Exte
     }
 
     // execute locally or forward to leader
-    match GlobalState::get_node_mode() {
+    match node_mode {
         NodeMode::Leader | NodeMode::FakeLeader => match ctx.executor.execute_local_transaction(tx) {
             Ok(_) => Ok(hex_data(tx_hash)),
             Err(e) => {
-                if e.is_internal() {
+                if e.is_fatal() {
                     tracing::error!(reason = ?e, "failed to execute eth_sendRawTransaction");
                 }
                 Err(e)
@@ -933,6 +1812,10 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Exte
                 Err(StratusError::ConsensusUnavailable)
             }
         },
+        NodeMode::Archive => {
+            tracing::warn!(%tx_hash, "failed to execute eth_sendRawTransaction because archive nodes do not accept transactions");
+            Err(StratusError::RpcTransactionDisabled)
+        }
     }
 }
 
@@ -1011,11 +1894,11 @@ fn eth_get_transaction_count(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Ext
     // track
     Span::with(|s| {
         s.rec_str("address", &address);
-        s.rec_str("address", &filter);
+        s.rec_str("filter", &filter);
     });
     tracing::info!(%address, %filter, "reading account nonce");
 
-    let point_in_time = ctx.storage.translate_to_point_in_time(filter)?;
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, filter)?;
     let account = ctx.storage.read_account(address, point_in_time)?;
     Ok(hex_num(account.nonce))
 }
@@ -1037,7 +1920,7 @@ fn eth_get_balance(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -
     tracing::info!(%address, %filter, "reading account native balance");
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(filter)?;
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, filter)?;
     let account = ctx.storage.read_account(address, point_in_time)?;
     Ok(hex_num(account.balance))
 }
@@ -1058,12 +1941,51 @@ fn eth_get_code(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> R
     });
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(filter)?;
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, filter)?;
     let account = ctx.storage.read_account(address, point_in_time)?;
 
     Ok(account.bytecode.map(hex_data).unwrap_or_else(hex_null))
 }
 
+/// Translates the block filter to the point-in-time used to read account and slot state, rejecting blocks that were not mined yet.
+///
+/// Past blocks are served from the state history, so they return the state as it was after the block was mined.
+pub(super) fn translate_to_state_point_in_time(storage: &dyn StorageReader, filter: BlockFilter) -> Result<PointInTime, StratusError> {
+    let point_in_time = storage.translate_to_point_in_time(filter)?;
+    if let PointInTime::MinedPast(number) = point_in_time {
+        let mined_number = storage.read_mined_block_number()?;
+        if number > mined_number {
+            tracing::warn!(%number, %mined_number, "account state requested for block not mined yet");
+            return Err(StratusError::RpcBlockFilterInvalid { filter });
+        }
+    }
+    Ok(point_in_time)
+}
+
+// -----------------------------------------------------------------------------
+// GraphQL
+// -----------------------------------------------------------------------------
+
+/// Executes requests received in the `/graphql` path, which are converted to this method by the GraphQL middleware.
+#[cfg(feature = "graphql")]
+fn stratus_graphql(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_graphql", operation = field::Empty).entered();
+
+    // parse params
+    let (_, request) = next_rpc_param::<async_graphql::Request>(params.sequence())?;
+
+    // track
+    if let Some(ref operation) = request.operation_name {
+        Span::with(|s| s.rec_str("operation", operation));
+    }
+
+    // execute
+    let response = execute_graphql(&ctx.graphql, request);
+    Ok(to_json_value(response))
+}
+
 // -----------------------------------------------------------------------------
 // Subscriptions
 // -----------------------------------------------------------------------------
@@ -1113,6 +2035,32 @@ async fn eth_subscribe(params: Params<'_>, pending: PendingSubscriptionSink, ctx
                 ctx.subs.add_logs_subscription(client, filter, pending.accept().await?).await;
             }
 
+            // same as `logs`, but first streams the historical logs since `fromBlock`
+            "logsWithHistory" => {
+                const MAX_HISTORY_BLOCK_RANGE: u64 = 50_000;
+
+                let (_, filter) = next_rpc_param_or_default::<LogFilterInput>(params)?;
+                let filter = filter.parse(&ctx.storage)?;
+
+                // the history is read in pages by a background task, but its range is still limited so one subscriber cannot scan the whole chain
+                let mined_number = ctx.storage.read_mined_block_number()?;
+                let history_to_block = filter.to_block.map_or(mined_number, |to_block| to_block.min(mined_number));
+                let blocks_in_history = filter.from_block.count_to(history_to_block);
+                if blocks_in_history > MAX_HISTORY_BLOCK_RANGE {
+                    pending
+                        .reject(StratusError::RpcBlockRangeInvalid {
+                            actual: blocks_in_history,
+                            max: MAX_HISTORY_BLOCK_RANGE,
+                        })
+                        .await;
+                    return Ok(());
+                }
+
+                ctx.subs
+                    .add_logs_subscription_with_history(client, filter, pending.accept().await?, &ctx.storage)
+                    .await?;
+            }
+
             // unsupported
             event => {
                 pending.reject(StratusError::RpcSubscriptionInvalid { event: event.to_string() }).await;
@@ -1132,7 +2080,7 @@ async fn eth_subscribe(params: Params<'_>, pending: PendingSubscriptionSink, ctx
 fn eth_get_storage_at(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions) -> Result<String, StratusError> {
     // enter span
     let _middleware_enter = ext.enter_middleware_span();
-    let _method_enter = info_span!("rpc::eth_getStorageAt", address = field::Empty, index = field::Empty).entered();
+    let _method_enter = info_span!("rpc::eth_getStorageAt", address = field::Empty, index = field::Empty, filter = field::Empty).entered();
 
     // parse params
     let (params, address) = next_rpc_param::<Address>(params.sequence())?;
@@ -1142,13 +2090,15 @@ fn eth_get_storage_at(params: Params<'_>, ctx: Arc<RpcContext>, ext: &Extensions
     Span::with(|s| {
         s.rec_str("address", &address);
         s.rec_str("index", &index);
+        s.rec_str("filter", &block_filter);
     });
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(block_filter)?;
+    let point_in_time = translate_to_state_point_in_time(&*ctx.storage, block_filter)?;
     let slot = ctx.storage.read_slot(address, index, point_in_time)?;
 
-    // It must be padded, even if it is zero.
+    // missing slots are returned as zero by all storages
+    // it must be padded, even if it is zero.
     Ok(hex_num_zero_padded(slot.value.as_u256()))
 }
 
@@ -1168,6 +2118,28 @@ pub(super) fn reject_unknown_client(client: &RpcClientApp) -> Result<(), Stratus
 // Response helpers
 // -----------------------------------------------------------------------------
 
+/// Reads a page of items ordered by block with up to `limit` blocks, returning the items and the block where the next page starts.
+///
+/// `read` is called with the number of blocks to read. It reads one extra block to know if there is a next page, and the items of the extra
+/// block are removed, so a page never splits the items of a block.
+fn read_page_by_block<T>(
+    limit: usize,
+    read: impl FnOnce(usize) -> Result<Vec<T>, StratusError>,
+    block_number: impl Fn(&T) -> BlockNumber,
+) -> Result<(Vec<T>, Option<BlockNumber>), StratusError> {
+    let mut items = read(limit + 1)?;
+    let blocks = items.iter().map(&block_number).dedup().count();
+    if blocks <= limit {
+        return Ok((items, None));
+    }
+
+    let next_block = items.last().map(&block_number);
+    if let Some(next_block) = next_block {
+        items.retain(|item| block_number(item) != next_block);
+    }
+    Ok((items, next_block))
+}
+
 #[inline(always)]
 fn hex_data<T: AsRef<[u8]>>(value: T) -> String {
     const_hex::encode_prefixed(value)
@@ -1184,10 +2156,145 @@ fn hex_num_zero_padded(value: impl Into<U256>) -> String {
     format!("{:#0width$x}", value.into(), width = width)
 }
 
-fn hex_zero() -> String {
-    "0x0".to_owned()
+/// Serializes the result and the state diff of an execution that was not persisted, including the error when it could not be executed.
+fn simulated_execution_json(execution: &Result<EvmExecution, StratusError>) -> JsonValue {
+    match execution {
+        Ok(execution) => json!({
+            "result": execution.result,
+            "output": execution.output,
+            "gasUsed": execution.gas,
+            "logs": execution.logs,
+            "stateDiff": state_diff_json(execution.changes.values()),
+        }),
+        Err(e) => json!({
+            "error": {
+                "code": e.rpc_code(),
+                "message": e.rpc_message(),
+                "data": e.rpc_data(),
+            }
+        }),
+    }
+}
+
+/// Serializes the accounts and slots modified by an execution or a block with their previous and new values.
+fn state_diff_json<'a>(changes: impl IntoIterator<Item = &'a ExecutionAccountChanges>) -> JsonValue {
+    let mut diff = serde_json::Map::new();
+    for account in changes.into_iter().sorted_by_key(|account| account.address) {
+        let mut account_diff = serde_json::Map::new();
+        if account.nonce.is_modified() {
+            account_diff.insert(
+                "nonce".into(),
+                json!({"from": account.nonce.take_original_ref(), "to": account.nonce.take_modified_ref()}),
+            );
+        }
+        if account.balance.is_modified() {
+            account_diff.insert(
+                "balance".into(),
+                json!({"from": account.balance.take_original_ref(), "to": account.balance.take_modified_ref()}),
+            );
+        }
+        if account.bytecode.is_modified() {
+            account_diff.insert(
+                "code".into(),
+                json!({"from": account.bytecode.take_original_ref(), "to": account.bytecode.take_modified_ref()}),
+            );
+        }
+        let storage_diff: serde_json::Map<String, JsonValue> = account
+            .slots
+            .iter()
+            .filter(|(_, slot)| slot.is_modified())
+            .sorted_by_key(|(index, _)| **index)
+            .map(|(index, slot)| {
+                let from = slot.take_original_ref().map(|slot| slot.value);
+                let to = slot.take_modified_ref().map(|slot| slot.value);
+                (index.to_string(), json!({"from": from, "to": to}))
+            })
+            .collect();
+        if not(storage_diff.is_empty()) {
+            account_diff.insert("storage".into(), JsonValue::Object(storage_diff));
+        }
+        if not(account_diff.is_empty()) {
+            diff.insert(account.address.to_string(), JsonValue::Object(account_diff));
+        }
+    }
+    JsonValue::Object(diff)
 }
 
 fn hex_null() -> String {
     "0x".to_owned()
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::EvmInput;
+    use crate::eth::primitives::GasMode;
+    use crate::eth::primitives::LocalTransactionExecution;
+    use crate::eth::primitives::TransactionExecution;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::Storage;
+    use crate::eth::storage::StratusStorage;
+
+    /// Creates a storage with only the genesis block mined and a transaction executed in the pending block.
+    fn storage_with_pending_transaction() -> (StratusStorage, LocalTransactionExecution) {
+        let storage = StratusStorage::new(
+            Box::new(InMemoryTemporaryStorage::new(1.into())),
+            Box::new(InMemoryPermanentStorage::default()),
+            None,
+        )
+        .unwrap();
+
+        let mut tx: LocalTransactionExecution = Faker.fake();
+        tx.evm_input = EvmInput::from_eth_transaction(&tx.input, &storage.read_pending_block_header(), GasMode::Free);
+        tx.result.execution.gas = Gas::from(21_000u64);
+        storage.save_execution(TransactionExecution::Local(tx.clone()), false).unwrap();
+        (storage, tx)
+    }
+
+    #[test]
+    fn test_pending_block_with_transaction_hashes() {
+        let (storage, tx) = storage_with_pending_transaction();
+        let genesis = storage.read_block(BlockFilter::Number(BlockNumber::ZERO)).unwrap().unwrap();
+
+        let author = Address::new([1; 20]);
+        let block = eth_get_pending_block(&storage, author, false).unwrap();
+        assert_eq!(block["number"], to_json_value(BlockNumber::ONE));
+        assert_eq!(block["hash"], JsonValue::Null);
+        assert_eq!(block["miner"], to_json_value(author));
+        assert_eq!(block["parentHash"], to_json_value(genesis.hash()));
+        assert_eq!(block["gasUsed"], to_json_value(Gas::from(21_000u64)));
+        assert_eq!(block["transactions"], JsonValue::Array(vec![to_json_value(tx.input.hash)]));
+    }
+
+    #[test]
+    fn test_read_page_by_block() {
+        let items = [1u64, 1, 2, 3, 3, 4].map(BlockNumber::from).to_vec();
+        let read = |limit: usize| Ok(items.iter().copied().filter(|number| *number <= BlockNumber::from(limit as u64)).collect_vec());
+
+        // next page starts at the first block not returned
+        let (page, next_block) = read_page_by_block(2, read, |number| *number).unwrap();
+        assert_eq!(page, [1u64, 1, 2].map(BlockNumber::from));
+        assert_eq!(next_block, Some(BlockNumber::from(3u64)));
+
+        // last page
+        let (page, next_block) = read_page_by_block(4, read, |number| *number).unwrap();
+        assert_eq!(page, items);
+        assert_eq!(next_block, None);
+    }
+
+    #[test]
+    fn test_pending_block_with_full_transactions() {
+        let (storage, tx) = storage_with_pending_transaction();
+
+        let block = eth_get_pending_block(&storage, Address::COINBASE, true).unwrap();
+        assert_eq!(block["transactions"].as_array().unwrap().len(), 1);
+        assert_eq!(block["transactions"][0]["hash"], to_json_value(tx.input.hash));
+    }
+}
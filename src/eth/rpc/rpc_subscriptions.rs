@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -9,14 +10,21 @@ use itertools::Itertools;
 use jsonrpsee::ConnectionId;
 use jsonrpsee::SubscriptionMessage;
 use jsonrpsee::SubscriptionSink;
+use parking_lot::Mutex;
 use serde::ser::SerializeMap;
+use serde_json::value::RawValue;
 use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tokio::time::Duration;
 
+use crate::alias::EthersBlockVoid;
+use crate::alias::EthersLog;
 use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogFilterInput;
@@ -24,7 +32,9 @@ use crate::eth::primitives::LogMined;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::UnixTimeNow;
 use crate::eth::rpc::RpcClientApp;
+use crate::eth::storage::StorageReader;
 use crate::ext::not;
+use crate::ext::spawn_blocking_named;
 use crate::ext::spawn_named;
 use crate::ext::traced_sleep;
 use crate::ext::DisplayExt;
@@ -44,7 +54,9 @@ const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
 /// Max wait since last checked shutdown in notifier.
 const NOTIFIER_SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 
-#[cfg(feature = "metrics")]
+/// Number of blocks read at once when sending historical logs to a subscriber.
+const LOGS_HISTORY_PAGE_BLOCKS: u64 = 1_000;
+
 mod label {
     pub(super) const PENDING_TXS: &str = "newPendingTransactions";
     pub(super) const NEW_HEADS: &str = "newHeads";
@@ -60,8 +72,16 @@ pub struct RpcSubscriptions {
 
 impl RpcSubscriptions {
     /// Creates a new subscription manager that automatically spawns all necessary tasks in background.
-    pub fn spawn(rx_pending_txs: broadcast::Receiver<Hash>, rx_blocks: broadcast::Receiver<BlockHeader>, rx_logs: broadcast::Receiver<LogMined>) -> Self {
-        let connected = Arc::new(RpcSubscriptionsConnected::default());
+    ///
+    /// Each subscriber gets a queue of `queue_size` notifications. When the subscriber does not consume them fast enough, new notifications are dropped
+    /// and the subscriber is marked as lagging until its queue is empty again.
+    pub fn spawn(
+        queue_size: usize,
+        rx_pending_txs: broadcast::Receiver<Hash>,
+        rx_blocks: broadcast::Receiver<BlockHeader>,
+        rx_logs: broadcast::Receiver<LogMined>,
+    ) -> Self {
+        let connected = Arc::new(RpcSubscriptionsConnected::new(queue_size));
 
         Self::spawn_subscriptions_cleaner(Arc::clone(&connected));
         let handles = RpcSubscriptionsHandles {
@@ -171,7 +191,7 @@ impl RpcSubscriptions {
 
                 let interested_subs = subs.pending_txs.read().await;
                 let interested_subs = interested_subs.values().collect_vec();
-                Self::notify(interested_subs, tx_hash.to_string());
+                Self::notify(interested_subs, label::PENDING_TXS, &tx_hash);
             }
             warn_task_rx_closed(TASK_NAME);
             Ok(())
@@ -195,7 +215,7 @@ impl RpcSubscriptions {
 
                 let interested_subs = subs.new_heads.read().await;
                 let interested_subs = interested_subs.values().collect_vec();
-                Self::notify(interested_subs, block_header);
+                Self::notify(interested_subs, label::NEW_HEADS, &EthersBlockVoid::from(block_header));
             }
             warn_task_rx_closed(TASK_NAME);
             Ok(())
@@ -224,7 +244,7 @@ impl RpcSubscriptions {
                     .filter_map(|s| if_else!(s.filter.matches(&log), Some(&s.inner), None))
                     .collect_vec();
 
-                Self::notify(interested_subs, log);
+                Self::notify(interested_subs, label::LOGS, &EthersLog::from(log));
             }
             warn_task_rx_closed(TASK_NAME);
             Ok(())
@@ -235,19 +255,19 @@ impl RpcSubscriptions {
     // Helpers
     // -------------------------------------------------------------------------
 
-    fn notify<T>(subs: Vec<&Subscription>, msg: T)
+    /// Serializes the notification once and enqueues the same payload to all subscribers.
+    fn notify<T>(subs: Vec<&Subscription>, sub_label: &'static str, msg: &T)
     where
-        T: TryInto<SubscriptionMessage>,
-        T::Error: fmt::Debug,
+        T: serde::Serialize + fmt::Debug,
     {
         if subs.is_empty() {
             return;
         }
 
-        let msg = match msg.try_into() {
-            Ok(msg) => msg,
+        let payload: Arc<RawValue> = match serde_json::value::to_raw_value(msg) {
+            Ok(payload) => payload.into(),
             Err(e) => {
-                tracing::error!(parent: None, reason = ?e, "failed to convert message into subscription message");
+                tracing::error!(parent: None, reason = ?e, ?msg, "failed to serialize subscription notification");
                 return;
             }
         };
@@ -256,18 +276,7 @@ impl RpcSubscriptions {
             if not(sub.is_active()) {
                 continue;
             }
-
-            // track metric
-            sub.inc_sent();
-
-            // send
-            let sink = Arc::clone(&sub.sink);
-            let msg_clone = msg.clone();
-            spawn_named("rpc::sub::notify", async move {
-                if let Err(e) = sink.send_timeout(msg_clone, NOTIFICATION_TIMEOUT).await {
-                    tracing::error!(reason = ?e, "failed to send subscription notification");
-                }
-            });
+            sub.enqueue(sub_label, Arc::clone(&payload));
         }
     }
 }
@@ -294,27 +303,165 @@ impl RpcSubscriptionsHandles {
 // Connected clients
 // -----------------------------------------------------------------------------
 
-#[derive(Debug, derive_new::new)]
+#[derive(Debug)]
 pub struct Subscription {
-    #[new(default)]
     created_at: UnixTimeNow,
-
     client: RpcClientApp,
     sink: Arc<SubscriptionSink>,
 
-    #[new(default)]
+    /// Queue of serialized notifications consumed by the task that sends them to the subscriber.
+    queue: mpsc::Sender<Arc<RawValue>>,
+
+    /// Subscriber did not consume its queue fast enough and notifications are being dropped.
+    lagging: Arc<AtomicBool>,
+
+    /// Notifications received while historical logs are being sent.
+    backfill: Arc<BackfillBuffer>,
+
     sent: AtomicUsize,
+    dropped: AtomicUsize,
 }
 
 impl Subscription {
+    /// Creates a new subscription and spawns the task that sends its queued notifications.
+    fn new(client: RpcClientApp, sink: SubscriptionSink, queue_size: usize) -> Self {
+        Self::new_with_history(client, sink, queue_size, None)
+    }
+
+    /// Creates a new subscription that sends historical logs before its queued notifications.
+    ///
+    /// Notifications enqueued while the history is being sent are buffered and sent after it, so they are not dropped while the sender task is
+    /// busy with the history. The buffer has the same size as the queue, and when it is full new notifications are dropped and the subscriber
+    /// is marked as lagging.
+    fn new_with_history(client: RpcClientApp, sink: SubscriptionSink, queue_size: usize, history: Option<LogsHistory>) -> Self {
+        let sink = Arc::new(sink);
+        let lagging = Arc::new(AtomicBool::new(false));
+        let backfill = Arc::new(if_else!(history.is_some(), BackfillBuffer::backfilling(queue_size), BackfillBuffer::default()));
+        let (tx, rx) = mpsc::channel(queue_size.max(1));
+        Self::spawn_sender(Arc::clone(&sink), Arc::clone(&lagging), Arc::clone(&backfill), rx, history);
+
+        Self {
+            created_at: UnixTimeNow::default(),
+            client,
+            sink,
+            queue: tx,
+            lagging,
+            backfill,
+            sent: AtomicUsize::default(),
+            dropped: AtomicUsize::default(),
+        }
+    }
+
+    /// Spawns a task that sends queued notifications to the subscriber until the subscription is closed or removed.
+    fn spawn_sender(
+        sink: Arc<SubscriptionSink>,
+        lagging: Arc<AtomicBool>,
+        backfill: Arc<BackfillBuffer>,
+        mut rx: mpsc::Receiver<Arc<RawValue>>,
+        history: Option<LogsHistory>,
+    ) -> JoinHandle<()> {
+        spawn_named("rpc::sub::sender", async move {
+            if let Some(history) = history {
+                if let Err(e) = history.send(&sink).await {
+                    tracing::error!(reason = ?e, id = sink.subscription_id().to_string_ext(), "failed to send historical logs to subscriber");
+                    backfill.discard();
+                    return;
+                }
+
+                // send notifications buffered during the history before the queued ones
+                loop {
+                    let buffered = backfill.take();
+                    if buffered.is_empty() {
+                        break;
+                    }
+                    for payload in buffered {
+                        if not(Self::send(&sink, &payload).await) {
+                            backfill.discard();
+                            return;
+                        }
+                    }
+                }
+            }
+
+            while let Some(payload) = rx.recv().await {
+                if not(Self::send(&sink, &payload).await) {
+                    break;
+                }
+
+                // subscriber caught up
+                if rx.is_empty() && lagging.swap(false, Ordering::Relaxed) {
+                    tracing::info!(id = sink.subscription_id().to_string_ext(), "subscriber is not lagging anymore");
+                }
+            }
+        })
+    }
+
+    /// Sends a notification to the subscriber, returning false if the subscription is closed.
+    async fn send(sink: &SubscriptionSink, payload: &RawValue) -> bool {
+        let msg = match SubscriptionMessage::from_json(&payload) {
+            Ok(msg) => msg,
+            Err(e) => {
+                tracing::error!(reason = ?e, "failed to convert payload into subscription message");
+                return true;
+            }
+        };
+        if let Err(e) = sink.send_timeout(msg, NOTIFICATION_TIMEOUT).await {
+            tracing::error!(reason = ?e, "failed to send subscription notification");
+            return not(sink.is_closed());
+        }
+        true
+    }
+
     /// Checks if the subscription still active.
     fn is_active(&self) -> bool {
         not(self.sink.is_closed())
     }
 
-    /// Increment the number of messages sent to this subscription.
-    fn inc_sent(&self) {
-        self.sent.fetch_add(1, Ordering::Relaxed);
+    /// Checks if the subscriber is not consuming notifications fast enough.
+    fn is_lagging(&self) -> bool {
+        self.lagging.load(Ordering::Relaxed)
+    }
+
+    /// Enqueues a notification to be sent to the subscriber, dropping it if the subscriber queue is full.
+    ///
+    /// While historical logs are being sent, the notification is buffered instead, and dropped only if the buffer is full.
+    fn enqueue(&self, sub_label: &'static str, payload: Arc<RawValue>) {
+        let payload = match self.backfill.buffer(payload) {
+            Backfill::Buffered => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            Backfill::Full => {
+                self.drop_notification(sub_label);
+                return;
+            }
+            Backfill::Finished(payload) => payload,
+        };
+
+        match self.queue.try_send(payload) {
+            Ok(()) => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Full(_)) => self.drop_notification(sub_label),
+            // sender task stopped because the sink is closed, so the subscription will be cleaned
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+
+    /// Drops a notification the subscriber could not keep up with, marking it as lagging.
+    fn drop_notification(&self, sub_label: &'static str) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        if not(self.lagging.swap(true, Ordering::Relaxed)) {
+            tracing::warn!(
+                id = self.sink.subscription_id().to_string_ext(),
+                client = %self.client,
+                subscription = %sub_label,
+                "subscriber is lagging, dropping notifications"
+            );
+        }
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_rpc_subscriptions_dropped(sub_label, self.client.to_string());
     }
 }
 
@@ -323,16 +470,125 @@ impl serde::Serialize for Subscription {
     where
         S: serde::Serializer,
     {
-        let mut s = serializer.serialize_map(Some(5))?;
+        let mut s = serializer.serialize_map(Some(7))?;
         s.serialize_entry("created_at", &self.created_at)?;
         s.serialize_entry("client", &self.client)?;
         s.serialize_entry("id", &self.sink.subscription_id())?;
         s.serialize_entry("active", &self.is_active())?;
+        s.serialize_entry("lagging", &self.is_lagging())?;
         s.serialize_entry("sent", &self.sent.load(Ordering::Relaxed))?;
+        s.serialize_entry("dropped", &self.dropped.load(Ordering::Relaxed))?;
         s.end()
     }
 }
 
+/// Notifications received by a subscription while its historical logs are being sent.
+///
+/// The subscriber queue would fill up while the sender task is busy with the history, so live notifications are buffered here until the
+/// history is sent. The buffer is bounded like the queue, so a subscriber that cannot keep up does not hold notifications without limit.
+#[derive(Debug, Default)]
+struct BackfillBuffer {
+    buffered: Mutex<Option<Vec<Arc<RawValue>>>>,
+
+    /// Max notifications buffered at once.
+    max: usize,
+}
+
+/// What happened to a notification received by a subscription during the backfill.
+enum Backfill {
+    /// Buffered to be sent after the history.
+    Buffered,
+
+    /// Dropped because the buffer is full.
+    Full,
+
+    /// The backfill is finished, so the notification must be enqueued.
+    Finished(Arc<RawValue>),
+}
+
+impl BackfillBuffer {
+    /// Creates a buffer that keeps up to `max` notifications until the history is sent.
+    fn backfilling(max: usize) -> Self {
+        Self {
+            buffered: Mutex::new(Some(Vec::new())),
+            max: max.max(1),
+        }
+    }
+
+    /// Buffers the notification if the history is still being sent and the buffer is not full, otherwise returns it to be enqueued.
+    fn buffer(&self, payload: Arc<RawValue>) -> Backfill {
+        match self.buffered.lock().as_mut() {
+            Some(buffered) if buffered.len() >= self.max => Backfill::Full,
+            Some(buffered) => {
+                buffered.push(payload);
+                Backfill::Buffered
+            }
+            None => Backfill::Finished(payload),
+        }
+    }
+
+    /// Takes the buffered notifications in the order they were received.
+    ///
+    /// When there are none left, the backfill is finished and new notifications are enqueued instead, so they are always sent after the
+    /// buffered ones.
+    fn take(&self) -> Vec<Arc<RawValue>> {
+        let mut buffer = self.buffered.lock();
+        match buffer.as_mut() {
+            Some(buffered) if not(buffered.is_empty()) => std::mem::take(buffered),
+            _ => {
+                *buffer = None;
+                Vec::new()
+            }
+        }
+    }
+
+    /// Discards the buffered notifications and finishes the backfill when the history cannot be sent.
+    fn discard(&self) {
+        *self.buffered.lock() = None;
+    }
+}
+
+/// Historical logs sent to a subscriber before its live logs.
+struct LogsHistory {
+    storage: Arc<dyn StorageReader>,
+
+    /// Filter of the historical logs, always ending in the last block mined before the subscription was added.
+    filter: LogFilter,
+}
+
+impl LogsHistory {
+    /// Reads the historical logs from the storage in pages of blocks and sends them to the subscriber.
+    async fn send(self, sink: &SubscriptionSink) -> anyhow::Result<()> {
+        let Some(to_block) = self.filter.to_block else {
+            return Ok(());
+        };
+
+        let mut from_block = self.filter.from_block;
+        while from_block <= to_block {
+            if sink.is_closed() {
+                return Ok(());
+            }
+
+            // read page
+            let page_to_block = BlockNumber::from((from_block.as_u64() + LOGS_HISTORY_PAGE_BLOCKS - 1).min(to_block.as_u64()));
+            let mut page_filter = self.filter.clone();
+            page_filter.from_block = from_block;
+            page_filter.to_block = Some(page_to_block);
+            let storage = Arc::clone(&self.storage);
+            let logs = spawn_blocking_named("rpc::sub::logs::history", move || storage.read_logs(&page_filter)).await??;
+
+            // send page
+            for log in logs {
+                let msg = SubscriptionMessage::from_json(&EthersLog::from(log))?;
+                sink.send_timeout(msg, NOTIFICATION_TIMEOUT).await?;
+            }
+            from_block = page_to_block.next_block_number();
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, derive_more::Deref, derive_new::new, serde::Serialize)]
 pub struct SubscriptionWithFilter {
     #[deref]
@@ -343,14 +599,26 @@ pub struct SubscriptionWithFilter {
 }
 
 /// Active client subscriptions.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct RpcSubscriptionsConnected {
     pub pending_txs: RwLock<HashMap<ConnectionId, Subscription>>,
     pub new_heads: RwLock<HashMap<ConnectionId, Subscription>>,
     pub logs: RwLock<HashMap<ConnectionId, HashMap<LogFilter, SubscriptionWithFilter>>>,
+
+    /// Max notifications queued for each subscriber.
+    queue_size: usize,
 }
 
 impl RpcSubscriptionsConnected {
+    pub fn new(queue_size: usize) -> Self {
+        Self {
+            pending_txs: RwLock::default(),
+            new_heads: RwLock::default(),
+            logs: RwLock::default(),
+            queue_size,
+        }
+    }
+
     /// Checks the number of subscriptions for a given client.
     pub async fn check_client_subscriptions(&self, max_subscriptions: u32, client: &RpcClientApp) -> Result<(), StratusError> {
         let pending_txs = self.pending_txs.read().await.values().filter(|s| s.client == *client).count();
@@ -380,7 +648,7 @@ impl RpcSubscriptionsConnected {
             "subscribing to newPendingTransactions event"
         );
         let mut subs = self.pending_txs.write().await;
-        subs.insert(sink.connection_id(), Subscription::new(rpc_client.clone(), sink.into()));
+        subs.insert(sink.connection_id(), Subscription::new(rpc_client.clone(), sink, self.queue_size));
 
         #[cfg(feature = "metrics")]
         sub_metrics::update_new_pending_txs_subscription_metrics(&subs);
@@ -394,7 +662,7 @@ impl RpcSubscriptionsConnected {
             "subscribing to newHeads event"
         );
         let mut subs = self.new_heads.write().await;
-        subs.insert(sink.connection_id(), Subscription::new(rpc_client.clone(), sink.into()));
+        subs.insert(sink.connection_id(), Subscription::new(rpc_client.clone(), sink, self.queue_size));
 
         #[cfg(feature = "metrics")]
         sub_metrics::update_new_heads_subscription_metrics(&subs);
@@ -415,12 +683,56 @@ impl RpcSubscriptionsConnected {
 
         // Insert the new subscription, if it already existed with the provided filter, overwrite
         // the previous sink with the newest
-        let inner = Subscription::new(rpc_client.clone(), sink.into());
+        let inner = Subscription::new(rpc_client.clone(), sink, self.queue_size);
         filter_to_subscription_map.insert(filter.clone(), SubscriptionWithFilter::new(inner, filter));
 
         #[cfg(feature = "metrics")]
         sub_metrics::update_logs_subscription_metrics(&subs);
     }
+
+    /// Adds a new subscriber to `logs` event that first receives the historical logs matching the filter and then the live ones.
+    ///
+    /// The last mined block is read while the subscriptions are locked, so blocks until it are sent as history and blocks after it are
+    /// notified only after the subscription is added, without gaps between both. Live logs of the boundary block or before it are rejected by
+    /// the live filter, so no log is sent twice.
+    pub async fn add_logs_subscription_with_history(
+        &self,
+        rpc_client: &RpcClientApp,
+        filter: LogFilter,
+        sink: SubscriptionSink,
+        storage: &Arc<dyn StorageReader>,
+    ) -> Result<(), StratusError> {
+        tracing::info!(
+            id = sink.subscription_id().to_string_ext(), ?filter,
+            %rpc_client,
+            "subscribing to logs event with history"
+        );
+        let mut subs = self.logs.write().await;
+        let mined_number = storage.read_mined_block_number()?;
+
+        let (history_filter, live_filter) = split_logs_filter(filter, mined_number);
+        let history = LogsHistory {
+            storage: Arc::clone(storage),
+            filter: history_filter,
+        };
+        let inner = Subscription::new_with_history(rpc_client.clone(), sink, self.queue_size, Some(history));
+        let filter_to_subscription_map = subs.entry(inner.sink.connection_id()).or_default();
+        filter_to_subscription_map.insert(live_filter.clone(), SubscriptionWithFilter::new(inner, live_filter));
+
+        #[cfg(feature = "metrics")]
+        sub_metrics::update_logs_subscription_metrics(&subs);
+
+        Ok(())
+    }
+}
+
+/// Splits a logs filter into the filter of the historical logs, until the last mined block, and the filter of the live logs, after it.
+fn split_logs_filter(filter: LogFilter, mined_number: BlockNumber) -> (LogFilter, LogFilter) {
+    let mut history_filter = filter.clone();
+    history_filter.to_block = Some(filter.to_block.map_or(mined_number, |to_block| to_block.min(mined_number)));
+    let mut live_filter = filter;
+    live_filter.from_block = live_filter.from_block.max(mined_number.next_block_number());
+    (history_filter, live_filter)
 }
 
 #[cfg(feature = "metrics")]
@@ -429,7 +741,6 @@ mod sub_metrics {
     use super::metrics;
     use super::ConnectionId;
     use super::HashMap;
-    use super::Itertools;
     use super::LogFilter;
     use super::RpcClientApp;
     use super::Subscription;
@@ -454,10 +765,73 @@ mod sub_metrics {
     where
         I: Iterator<Item = &'a Subscription>,
     {
-        let client_counts: HashMap<&RpcClientApp, usize> = sub_client_app_iter.map(|sub| &sub.client).counts();
+        let mut client_counts: HashMap<&RpcClientApp, (usize, usize)> = HashMap::new();
+        for sub in sub_client_app_iter {
+            let (active, lagging) = client_counts.entry(&sub.client).or_default();
+            *active += 1;
+            *lagging += sub.is_lagging() as usize;
+        }
+
+        for (client, (active, lagging)) in client_counts {
+            metrics::set_rpc_subscriptions_active(active as u64, sub_label, client.to_string());
+            metrics::set_rpc_subscriptions_lagging(lagging as u64, sub_label, client.to_string());
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
 
-        for (client, count) in client_counts {
-            metrics::set_rpc_subscriptions_active(count as u64, sub_label, client.to_string());
+    use super::*;
+
+    fn payload(n: usize) -> Arc<RawValue> {
+        Arc::from(RawValue::from_string(n.to_string()).unwrap())
+    }
+
+    #[test]
+    fn test_backfill_buffers_live_logs_up_to_max() {
+        let backfill = BackfillBuffer::backfilling(1_000);
+
+        // live logs emitted during the history are buffered until the buffer is full, then dropped
+        for n in 0..1_000 {
+            assert!(matches!(backfill.buffer(payload(n)), Backfill::Buffered));
+        }
+        assert!(matches!(backfill.buffer(payload(1_000)), Backfill::Full));
+        let buffered = backfill.take();
+        assert_eq!(buffered.len(), 1_000);
+        assert!(buffered.iter().enumerate().all(|(n, payload)| payload.get() == n.to_string()));
+
+        // live logs emitted while the buffered ones are sent are still buffered
+        assert!(matches!(backfill.buffer(payload(1_001)), Backfill::Buffered));
+        assert_eq!(backfill.take().len(), 1);
+
+        // once empty, the backfill finishes and live logs go to the queue
+        assert!(backfill.take().is_empty());
+        assert!(matches!(backfill.buffer(payload(1_002)), Backfill::Finished(_)));
+    }
+
+    #[test]
+    fn test_split_logs_filter_at_boundary_block() {
+        let filter = LogFilter {
+            from_block: BlockNumber::from(5),
+            ..Default::default()
+        };
+        let (history, live) = split_logs_filter(filter, BlockNumber::from(10));
+        assert_eq!(history.from_block, BlockNumber::from(5));
+        assert_eq!(history.to_block, Some(BlockNumber::from(10)));
+        assert_eq!(live.from_block, BlockNumber::from(11));
+
+        // each log is matched by only one of the filters
+        for number in 4..=12u64 {
+            let mut log: LogMined = Faker.fake();
+            log.block_number = BlockNumber::from(number);
+            let matched = [history.matches(&log), live.matches(&log)].into_iter().filter(|matched| *matched).count();
+            assert_eq!(matched, if_else!(number < 5, 0, 1));
         }
     }
 }
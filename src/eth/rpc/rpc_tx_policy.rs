@@ -0,0 +1,141 @@
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionInput;
+use crate::ext::not;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Address-based rules for transactions received through `eth_sendRawTransaction`, used by permissioned deployments.
+///
+/// All lists are disabled when empty.
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct TransactionPolicyConfig {
+    /// Only transactions signed by these addresses are accepted.
+    #[arg(long = "tx-policy-sender-allowlist", env = "TX_POLICY_SENDER_ALLOWLIST", value_delimiter = ',')]
+    pub tx_policy_sender_allowlist: Vec<Address>,
+
+    /// Transactions signed by these addresses are rejected.
+    #[arg(long = "tx-policy-sender-denylist", env = "TX_POLICY_SENDER_DENYLIST", value_delimiter = ',')]
+    pub tx_policy_sender_denylist: Vec<Address>,
+
+    /// Only these addresses can deploy contracts.
+    #[arg(long = "tx-policy-deployer-allowlist", env = "TX_POLICY_DEPLOYER_ALLOWLIST", value_delimiter = ',')]
+    pub tx_policy_deployer_allowlist: Vec<Address>,
+}
+
+/// Policy that rejected a transaction.
+#[derive(Debug, Clone, Copy, strum::Display, strum::IntoStaticStr)]
+pub enum TransactionPolicy {
+    #[strum(to_string = "sender_allowlist")]
+    SenderAllowlist,
+
+    #[strum(to_string = "sender_denylist")]
+    SenderDenylist,
+
+    #[strum(to_string = "deployer_allowlist")]
+    DeployerAllowlist,
+}
+
+impl TransactionPolicyConfig {
+    /// Checks if the transaction is allowed by all configured policies.
+    pub fn check(&self, tx: &TransactionInput) -> Result<(), StratusError> {
+        if let Some(policy) = self.find_violated_policy(tx) {
+            tracing::warn!(tx_hash = %tx.hash, signer = %tx.signer, %policy, "transaction rejected by policy");
+
+            #[cfg(feature = "metrics")]
+            metrics::inc_rpc_transaction_policy_rejected(<&'static str>::from(policy));
+
+            return Err(StratusError::RpcTransactionDeniedByPolicy {
+                policy: policy.to_string(),
+                signer: tx.signer,
+            });
+        }
+        Ok(())
+    }
+
+    fn find_violated_policy(&self, tx: &TransactionInput) -> Option<TransactionPolicy> {
+        if not(self.tx_policy_sender_allowlist.is_empty()) && not(self.tx_policy_sender_allowlist.contains(&tx.signer)) {
+            return Some(TransactionPolicy::SenderAllowlist);
+        }
+        if self.tx_policy_sender_denylist.contains(&tx.signer) {
+            return Some(TransactionPolicy::SenderDenylist);
+        }
+        let is_deployment = tx.to.is_none();
+        if is_deployment && not(self.tx_policy_deployer_allowlist.is_empty()) && not(self.tx_policy_deployer_allowlist.contains(&tx.signer)) {
+            return Some(TransactionPolicy::DeployerAllowlist);
+        }
+        None
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+
+    fn config() -> TransactionPolicyConfig {
+        TransactionPolicyConfig {
+            tx_policy_sender_allowlist: vec![],
+            tx_policy_sender_denylist: vec![],
+            tx_policy_deployer_allowlist: vec![],
+        }
+    }
+
+    fn tx(signer: Address, to: Option<Address>) -> TransactionInput {
+        let mut tx: TransactionInput = Faker.fake();
+        tx.signer = signer;
+        tx.to = to;
+        tx
+    }
+
+    #[test]
+    fn test_tx_policy_empty_lists_allow_everything() {
+        let config = config();
+        assert!(config.find_violated_policy(&tx(Address::new([1; 20]), None)).is_none());
+        assert!(config.find_violated_policy(&tx(Address::new([1; 20]), Some(Address::new([2; 20])))).is_none());
+    }
+
+    #[test]
+    fn test_tx_policy_senders() {
+        let allowed = Address::new([1; 20]);
+        let denied = Address::new([2; 20]);
+
+        let mut config = config();
+        config.tx_policy_sender_denylist = vec![denied];
+        assert!(config.find_violated_policy(&tx(allowed, Some(denied))).is_none());
+        assert!(matches!(
+            config.find_violated_policy(&tx(denied, Some(allowed))),
+            Some(TransactionPolicy::SenderDenylist)
+        ));
+
+        config.tx_policy_sender_allowlist = vec![allowed];
+        assert!(config.find_violated_policy(&tx(allowed, Some(denied))).is_none());
+        assert!(matches!(
+            config.find_violated_policy(&tx(Address::new([3; 20]), Some(allowed))),
+            Some(TransactionPolicy::SenderAllowlist)
+        ));
+    }
+
+    #[test]
+    fn test_tx_policy_deployers() {
+        let deployer = Address::new([1; 20]);
+        let other = Address::new([2; 20]);
+
+        let mut config = config();
+        config.tx_policy_deployer_allowlist = vec![deployer];
+        assert!(config.find_violated_policy(&tx(deployer, None)).is_none());
+        assert!(config.find_violated_policy(&tx(other, Some(deployer))).is_none());
+        assert!(matches!(
+            config.find_violated_policy(&tx(other, None)),
+            Some(TransactionPolicy::DeployerAllowlist)
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+//! Validation pipeline of raw transactions received through `eth_sendRawTransaction`.
+
+use ethereum_types::U256;
+
+use crate::alias::EthersTransaction;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::GasMode;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::Wei;
+use crate::eth::rpc::parse_rpc_rlp;
+use crate::eth::rpc::RpcContext;
+use crate::ext::not;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Gas charged for every transaction before execution.
+const TX_BASE_GAS: u64 = 21_000;
+
+/// Gas charged for every contract deployment before execution.
+const TX_CREATE_GAS: u64 = 53_000;
+
+/// Gas charged for each zero byte of transaction data.
+const TX_DATA_ZERO_GAS: u64 = 4;
+
+/// Gas charged for each non-zero byte of transaction data (EIP-2028).
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+
+/// Steps of the validation pipeline, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::IntoStaticStr)]
+pub enum ValidationStep {
+    #[strum(to_string = "size")]
+    Size,
+
+    #[strum(to_string = "decode")]
+    Decode,
+
+    #[strum(to_string = "signature")]
+    Signature,
+
+    #[strum(to_string = "chain_id")]
+    ChainId,
+
+    #[strum(to_string = "policy")]
+    Policy,
+
+    #[strum(to_string = "intrinsic_gas")]
+    IntrinsicGas,
+
+    #[strum(to_string = "initcode_size")]
+    InitcodeSize,
+
+    #[strum(to_string = "nonce")]
+    Nonce,
+
+    #[strum(to_string = "balance")]
+    Balance,
+}
+
+/// Validates a raw transaction before it is executed or forwarded to the leader.
+///
+/// Stateless steps always run. Steps that check the sender account against the latest state only run if `check_state` is set, because
+/// followers may be behind the leader.
+pub fn validate_raw_transaction(ctx: &RpcContext, tx_data: &[u8], check_state: bool) -> Result<TransactionInput, StratusError> {
+    let max_size = ctx.rpc_server.rpc_max_transaction_size;
+    let gas_mode = ctx.executor.gas_mode();
+    let max_initcode_size = ctx.executor.max_initcode_size();
+
+    run_step(ValidationStep::Size, || check_size(tx_data, max_size))?;
+    let tx = run_step(ValidationStep::Decode, || parse_rpc_rlp::<EthersTransaction>(tx_data))?;
+    let tx = run_step(ValidationStep::Signature, || recover_signer(tx))?;
+    run_step(ValidationStep::ChainId, || {
+        check_chain_id(&tx, ctx.chain.chain_id, ctx.rpc_server.rpc_reject_unprotected_txs)
+    })?;
+    run_step(ValidationStep::Policy, || ctx.rpc_server.tx_policy.check(&tx))?;
+    run_step(ValidationStep::IntrinsicGas, || check_intrinsic_gas(&tx, gas_mode))?;
+    run_step(ValidationStep::InitcodeSize, || check_initcode_size(&tx, max_initcode_size))?;
+
+    if check_state {
+        let account = ctx.storage.read_account(tx.signer, PointInTime::Pending)?;
+        run_step(ValidationStep::Nonce, || check_nonce(&tx, &account))?;
+        run_step(ValidationStep::Balance, || check_balance(&tx, &account, gas_mode))?;
+    }
+
+    Ok(tx)
+}
+
+/// Runs a validation step, tracking when it rejects the transaction.
+fn run_step<T>(step: ValidationStep, f: impl FnOnce() -> Result<T, StratusError>) -> Result<T, StratusError> {
+    let result = f();
+    if let Err(ref e) = result {
+        tracing::warn!(%step, reason = ?e, "raw transaction rejected by validation");
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_rpc_transaction_validation_rejected(<&'static str>::from(step));
+    }
+    result
+}
+
+// -----------------------------------------------------------------------------
+// Steps
+// -----------------------------------------------------------------------------
+
+/// Checks the raw transaction size before spending resources decoding it.
+fn check_size(tx_data: &[u8], max: usize) -> Result<(), StratusError> {
+    if tx_data.len() > max {
+        return Err(StratusError::RpcTransactionTooLarge { size: tx_data.len(), max });
+    }
+    Ok(())
+}
+
+fn recover_signer(tx: EthersTransaction) -> Result<TransactionInput, StratusError> {
+    let signer = match TransactionInput::recover_decoded_signer(&tx) {
+        Ok(signer) => signer,
+        Err(e) => return Err(StratusError::RpcTransactionSignatureInvalid { reason: e.to_string() }),
+    };
+    match TransactionInput::from_ethers_with_signer(tx, signer) {
+        Ok(tx) => Ok(tx),
+        Err(e) => Err(StratusError::RpcTransactionInvalid { decode_error: e.to_string() }),
+    }
+}
+
+fn check_chain_id(tx: &TransactionInput, expected: ChainId, reject_unprotected: bool) -> Result<(), StratusError> {
+    match tx.chain_id {
+        Some(chain_id) if chain_id != expected => Err(StratusError::RpcTransactionChainIdMismatch { expected, actual: chain_id }),
+        None if reject_unprotected => Err(StratusError::RpcTransactionUnprotected),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the gas limit covers the gas charged before execution. Only enforced when gas is charged, otherwise the gas limit is ignored.
+fn check_intrinsic_gas(tx: &TransactionInput, gas_mode: GasMode) -> Result<(), StratusError> {
+    if not(gas_mode.is_charged()) {
+        return Ok(());
+    }
+    let intrinsic = intrinsic_gas(tx);
+    if tx.gas_limit.as_u64() < intrinsic {
+        return Err(StratusError::RpcTransactionIntrinsicGasTooLow {
+            gas_limit: tx.gas_limit,
+            intrinsic: Gas::from(intrinsic),
+        });
+    }
+    Ok(())
+}
+
+/// Checks the data of contract deployments does not exceed the configured limit (EIP-3860).
+fn check_initcode_size(tx: &TransactionInput, max: usize) -> Result<(), StratusError> {
+    if tx.to.is_none() && tx.input.len() > max {
+        return Err(StratusError::TransactionInitcodeSizeExceeded { size: tx.input.len(), max });
+    }
+    Ok(())
+}
+
+fn intrinsic_gas(tx: &TransactionInput) -> u64 {
+    let base = if tx.to.is_some() { TX_BASE_GAS } else { TX_CREATE_GAS };
+    let zero_bytes = tx.input.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zero_bytes = tx.input.len() as u64 - zero_bytes;
+    base + zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS
+}
+
+fn check_nonce(tx: &TransactionInput, account: &Account) -> Result<(), StratusError> {
+    if tx.nonce != account.nonce {
+        return Err(StratusError::TransactionNonce {
+            transaction: tx.nonce,
+            account: account.nonce,
+        });
+    }
+    Ok(())
+}
+
+/// Checks the sender can pay for the transferred value and, when gas is charged, for the max fee.
+fn check_balance(tx: &TransactionInput, account: &Account, gas_mode: GasMode) -> Result<(), StratusError> {
+    let max_fee = if gas_mode.is_charged() {
+        U256::from(tx.gas_limit).saturating_mul(U256::from(tx.gas_price))
+    } else {
+        U256::zero()
+    };
+    let required = Wei::from(U256::from(tx.value).saturating_add(max_fee));
+    if account.balance < required {
+        return Err(StratusError::RpcTransactionInsufficientBalance {
+            balance: account.balance,
+            required,
+        });
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use ethereum_types::H160;
+    use ethers_core::k256::ecdsa::SigningKey;
+    use ethers_core::types::transaction::eip2718::TypedTransaction;
+    use ethers_core::types::Signature as EthersSignature;
+    use ethers_core::types::TransactionRequest;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::rpc::RpcServerConfig;
+
+    /// Signs a legacy transaction, protected by EIP-155 only if a chain id is given.
+    fn signed_legacy_tx(chain_id: Option<u64>) -> Vec<u8> {
+        let mut request = TransactionRequest::new().nonce(0).to(H160::repeat_byte(1)).gas(21_000).gas_price(1);
+        if let Some(chain_id) = chain_id {
+            request = request.chain_id(chain_id);
+        }
+        let tx = TypedTransaction::Legacy(request);
+
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(tx.sighash().as_bytes()).unwrap();
+        let signature = EthersSignature {
+            r: U256::from_big_endian(signature.r().to_bytes().as_slice()),
+            s: U256::from_big_endian(signature.s().to_bytes().as_slice()),
+            v: recovery_id.to_byte() as u64 + chain_id.map_or(27, |chain_id| 35 + chain_id * 2),
+        };
+        tx.rlp_signed(&signature).to_vec()
+    }
+
+    fn tx(to: Option<Address>, data: Vec<u8>, gas_limit: u64) -> TransactionInput {
+        let mut tx: TransactionInput = Faker.fake();
+        tx.to = to;
+        tx.input = Bytes::from(data);
+        tx.gas_limit = Gas::from(gas_limit);
+        tx.value = Wei::from(100u64);
+        tx.gas_price = Wei::from(2u64);
+        tx.nonce = Nonce::from(1u64);
+        tx
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert!(check_size(&[0; 10], 10).is_ok());
+        assert!(matches!(
+            check_size(&[0; 11], 10),
+            Err(StratusError::RpcTransactionTooLarge { size: 11, max: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_intrinsic_gas() {
+        let to = Some(Address::new([1; 20]));
+        assert_eq!(intrinsic_gas(&tx(to, vec![], 0)), 21_000);
+        assert_eq!(intrinsic_gas(&tx(to, vec![0, 1, 0, 2], 0)), 21_000 + 2 * 4 + 2 * 16);
+        assert_eq!(intrinsic_gas(&tx(None, vec![], 0)), 53_000);
+
+        // only enforced when gas is charged
+        assert!(check_intrinsic_gas(&tx(to, vec![], 20_999), GasMode::Free).is_ok());
+        assert!(check_intrinsic_gas(&tx(to, vec![], 21_000), GasMode::Charged).is_ok());
+        assert!(matches!(
+            check_intrinsic_gas(&tx(to, vec![], 20_999), GasMode::Charged),
+            Err(StratusError::RpcTransactionIntrinsicGasTooLow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_initcode_size() {
+        let deploy = tx(None, vec![0; 11], 0);
+        assert!(check_initcode_size(&deploy, 11).is_ok());
+        assert!(matches!(
+            check_initcode_size(&deploy, 10),
+            Err(StratusError::TransactionInitcodeSizeExceeded { size: 11, max: 10 })
+        ));
+
+        // calls are not limited
+        assert!(check_initcode_size(&tx(Some(Address::new([1; 20])), vec![0; 11], 0), 10).is_ok());
+    }
+
+    #[test]
+    fn test_check_chain_id() {
+        let mut tx = tx(None, vec![], 0);
+        tx.chain_id = Some(ChainId::from(1u64));
+        assert!(check_chain_id(&tx, ChainId::from(1u64), false).is_ok());
+        assert!(matches!(
+            check_chain_id(&tx, ChainId::from(2u64), false),
+            Err(StratusError::RpcTransactionChainIdMismatch { .. })
+        ));
+
+        tx.chain_id = None;
+        assert!(check_chain_id(&tx, ChainId::from(1u64), false).is_ok());
+        assert!(matches!(
+            check_chain_id(&tx, ChainId::from(1u64), true),
+            Err(StratusError::RpcTransactionUnprotected)
+        ));
+    }
+
+    #[test]
+    fn test_unprotected_transactions_are_accepted_by_default() {
+        let chain_id = ChainId::from(2008u64);
+        let unprotected = recover_signer(parse_rpc_rlp(&signed_legacy_tx(None)).unwrap()).unwrap();
+        let protected = recover_signer(parse_rpc_rlp(&signed_legacy_tx(Some(2008))).unwrap()).unwrap();
+        assert!(unprotected.chain_id.is_none());
+        assert_eq!(protected.chain_id, Some(chain_id));
+
+        let config = RpcServerConfig::parse_from(["test"]);
+        assert!(check_chain_id(&unprotected, chain_id, config.rpc_reject_unprotected_txs).is_ok());
+        assert!(check_chain_id(&protected, chain_id, config.rpc_reject_unprotected_txs).is_ok());
+
+        let config = RpcServerConfig::parse_from(["test", "--rpc-reject-unprotected-txs"]);
+        assert!(matches!(
+            check_chain_id(&unprotected, chain_id, config.rpc_reject_unprotected_txs),
+            Err(StratusError::RpcTransactionUnprotected)
+        ));
+        assert!(check_chain_id(&protected, chain_id, config.rpc_reject_unprotected_txs).is_ok());
+    }
+
+    #[test]
+    fn test_check_nonce_and_balance() {
+        let tx = tx(Some(Address::new([1; 20])), vec![], 50);
+        let mut account = Account::new_with_balance(tx.signer, Wei::from(100u64));
+        account.nonce = Nonce::from(1u64);
+
+        assert!(check_nonce(&tx, &account).is_ok());
+        account.nonce = Nonce::from(2u64);
+        assert!(matches!(check_nonce(&tx, &account), Err(StratusError::TransactionNonce { .. })));
+
+        // value is enough when gas is free, but max fee must also be covered when gas is charged
+        assert!(check_balance(&tx, &account, GasMode::Free).is_ok());
+        assert!(matches!(
+            check_balance(&tx, &account, GasMode::Charged),
+            Err(StratusError::RpcTransactionInsufficientBalance { .. })
+        ));
+        account.balance = Wei::from(200u64);
+        assert!(check_balance(&tx, &account, GasMode::Charged).is_ok());
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use ethereum_types::H256;
+use ethers_core::utils::keccak256;
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio::time::Duration;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::UserOperation;
+use crate::ext::not;
+use crate::ext::spawn_named;
+use crate::infra::tracing::warn_task_rx_closed;
+use crate::GlobalState;
+
+/// Max number of user operations kept in the pool.
+const POOL_MAX_SIZE: usize = 10_000;
+
+/// Max wait since last checked shutdown in the pruner.
+const PRUNER_SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Signature of the event emitted by the entry point after executing a user operation.
+const USER_OPERATION_EVENT: &str = "UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)";
+
+/// User operations accepted by `eth_sendUserOperation` waiting to be included in a bundle by external bundlers.
+#[derive(Debug, Default)]
+pub struct RpcUserOperationPool {
+    operations: Mutex<IndexMap<Hash, PooledUserOperation>>,
+}
+
+#[derive(Debug, Clone)]
+struct PooledUserOperation {
+    entry_point: Address,
+    user_op: UserOperation,
+}
+
+impl RpcUserOperationPool {
+    /// Creates a new pool that is automatically pruned when user operations are executed by the entry points.
+    pub fn spawn(rx_logs: broadcast::Receiver<LogMined>) -> Arc<Self> {
+        let pool = Arc::new(Self::default());
+        Self::spawn_pruner(Arc::clone(&pool), rx_logs);
+        pool
+    }
+
+    /// Adds a user operation to the pool, replacing any operation from the same sender with the same nonce.
+    pub fn add(&self, hash: Hash, entry_point: Address, user_op: UserOperation) -> Result<(), StratusError> {
+        let mut operations = self.operations.lock();
+
+        let replaced = operations
+            .iter()
+            .find(|(_, pooled)| pooled.entry_point == entry_point && pooled.user_op.sender == user_op.sender && pooled.user_op.nonce == user_op.nonce)
+            .map(|(hash, _)| *hash);
+        if let Some(replaced) = replaced {
+            tracing::info!(%replaced, %hash, "replacing user operation in pool");
+            operations.shift_remove(&replaced);
+        }
+
+        if not(operations.contains_key(&hash)) && operations.len() >= POOL_MAX_SIZE {
+            return Err(StratusError::UserOperationPoolFull { max: POOL_MAX_SIZE });
+        }
+        operations.insert(hash, PooledUserOperation { entry_point, user_op });
+        Ok(())
+    }
+
+    /// Lists pending user operations of an entry point in the order they were received.
+    pub fn pending(&self, entry_point: Address) -> Vec<UserOperation> {
+        self.operations
+            .lock()
+            .values()
+            .filter(|pooled| pooled.entry_point == entry_point)
+            .map(|pooled| pooled.user_op.clone())
+            .collect()
+    }
+
+    /// Removes a user operation from the pool.
+    fn remove(&self, hash: &Hash) {
+        if self.operations.lock().shift_remove(hash).is_some() {
+            tracing::info!(%hash, "removed executed user operation from pool");
+        }
+    }
+
+    /// Spawns a new task that removes user operations from the pool when they are executed.
+    fn spawn_pruner(pool: Arc<Self>, mut rx_logs: broadcast::Receiver<LogMined>) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::user-operations::pruner";
+        let event_topic = H256::from(keccak256(USER_OPERATION_EVENT));
+
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let log = match timeout(PRUNER_SHUTDOWN_CHECK_INTERVAL, rx_logs.recv()).await {
+                    Ok(Ok(log)) => log,
+                    Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                        tracing::warn!(%skipped, "user operations pruner lagged behind mined logs");
+                        continue;
+                    }
+                    Ok(Err(broadcast::error::RecvError::Closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                // topic1 of the event is the user operation hash
+                if log.log.topic0.map(|topic| topic.0) != Some(event_topic) {
+                    continue;
+                }
+                if let Some(user_op_hash) = log.log.topic1 {
+                    pool.remove(&Hash::from(user_op_hash.0));
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use ethereum_types::U256;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::LogTopic;
+
+    const ENTRY_POINT: Address = Address::new([1; 20]);
+
+    fn user_operation(sender: Address, nonce: u64) -> UserOperation {
+        UserOperation {
+            sender,
+            nonce: U256::from(nonce),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: U256::zero(),
+            verification_gas_limit: U256::zero(),
+            pre_verification_gas: U256::zero(),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        }
+    }
+
+    #[test]
+    fn test_pool_lists_pending_operations_of_entry_point_in_order() {
+        let pool = RpcUserOperationPool::default();
+        let (alice, bob) = (Address::new([2; 20]), Address::new([3; 20]));
+        let other_entry_point = Address::new([4; 20]);
+
+        pool.add(Faker.fake(), ENTRY_POINT, user_operation(bob, 0)).unwrap();
+        pool.add(Faker.fake(), other_entry_point, user_operation(alice, 0)).unwrap();
+        pool.add(Faker.fake(), ENTRY_POINT, user_operation(alice, 0)).unwrap();
+
+        assert_eq!(pool.pending(ENTRY_POINT), vec![user_operation(bob, 0), user_operation(alice, 0)]);
+        assert_eq!(pool.pending(other_entry_point), vec![user_operation(alice, 0)]);
+    }
+
+    #[test]
+    fn test_pool_replaces_operation_with_same_sender_and_nonce() {
+        let pool = RpcUserOperationPool::default();
+        let sender = Address::new([2; 20]);
+
+        let replaced = user_operation(sender, 0);
+        let replacement = UserOperation {
+            call_data: Bytes::from(vec![1]),
+            ..user_operation(sender, 0)
+        };
+        pool.add(Faker.fake(), ENTRY_POINT, replaced).unwrap();
+        pool.add(Faker.fake(), ENTRY_POINT, user_operation(sender, 1)).unwrap();
+        pool.add(Faker.fake(), ENTRY_POINT, replacement.clone()).unwrap();
+
+        assert_eq!(pool.pending(ENTRY_POINT), vec![user_operation(sender, 1), replacement]);
+    }
+
+    #[test]
+    fn test_pool_rejects_operations_when_full() {
+        let pool = RpcUserOperationPool::default();
+        let sender = Address::new([2; 20]);
+        for nonce in 0..POOL_MAX_SIZE as u64 {
+            pool.add(Faker.fake(), ENTRY_POINT, user_operation(sender, nonce)).unwrap();
+        }
+
+        let result = pool.add(Faker.fake(), ENTRY_POINT, user_operation(sender, POOL_MAX_SIZE as u64));
+        assert!(matches!(result, Err(StratusError::UserOperationPoolFull { max: POOL_MAX_SIZE })));
+
+        // replacing an operation does not increase the pool size
+        pool.add(Faker.fake(), ENTRY_POINT, user_operation(sender, 0)).unwrap();
+        assert_eq!(pool.pending(ENTRY_POINT).len(), POOL_MAX_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_pool_prunes_executed_operations() {
+        let (tx_logs, rx_logs) = broadcast::channel(16);
+        let pool = RpcUserOperationPool::spawn(rx_logs);
+        let sender = Address::new([2; 20]);
+        let (executed, pending): (Hash, Hash) = (Faker.fake(), Faker.fake());
+        pool.add(executed, ENTRY_POINT, user_operation(sender, 0)).unwrap();
+        pool.add(pending, ENTRY_POINT, user_operation(sender, 1)).unwrap();
+
+        // other events with the same topic1 do not remove operations
+        let mut other_event: LogMined = Faker.fake();
+        other_event.log.topic1 = Some(LogTopic::new(pending.0));
+        tx_logs.send(other_event).unwrap();
+
+        let mut user_operation_event: LogMined = Faker.fake();
+        user_operation_event.log.topic0 = Some(LogTopic::new(H256::from(keccak256(USER_OPERATION_EVENT))));
+        user_operation_event.log.topic1 = Some(LogTopic::new(executed.0));
+        tx_logs.send(user_operation_event).unwrap();
+
+        for _ in 0..100 {
+            if pool.pending(ENTRY_POINT).len() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(pool.pending(ENTRY_POINT), vec![user_operation(sender, 1)]);
+    }
+}
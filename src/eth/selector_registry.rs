@@ -0,0 +1,122 @@
+//! Runtime registry of contract names and Solidity signatures.
+//!
+//! Complements the signatures generated at compile-time with contracts deployed after the build, so operators can debug their traffic without
+//! a new release.
+
+use std::collections::HashMap;
+use std::fs;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use ethers_core::utils::id;
+use once_cell::sync::OnceCell;
+
+use crate::eth::codegen::ContractName;
+use crate::eth::codegen::SoliditySignature;
+use crate::eth::primitives::Address;
+use crate::log_and_err;
+
+/// Registry loaded at startup, if configured.
+static REGISTRY: OnceCell<SelectorRegistry> = OnceCell::new();
+
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct SelectorRegistryConfig {
+    /// JSON file with contract names and Solidity signatures used to decorate logs and traces.
+    ///
+    /// Format: `{"contracts": {"<address>": "<name>"}, "signatures": ["transfer(address,uint256)"]}`
+    #[arg(long = "selector-registry", env = "SELECTOR_REGISTRY")]
+    pub selector_registry: Option<String>,
+}
+
+impl SelectorRegistryConfig {
+    /// Loads the registry file, if configured.
+    pub fn init(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.selector_registry else { return Ok(()) };
+        tracing::info!(%path, "loading selector registry");
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return log_and_err!(reason = e, payload = path, "failed to read selector registry"),
+        };
+        let file: SelectorRegistryFile = match serde_json::from_str(&content) {
+            Ok(file) => file,
+            Err(e) => return log_and_err!(reason = e, payload = path, "failed to parse selector registry"),
+        };
+
+        let registry = SelectorRegistry::from(file);
+        tracing::info!(contracts = %registry.contracts.len(), signatures = %registry.signatures.len(), "loaded selector registry");
+        let _ = REGISTRY.set(registry);
+        Ok(())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SelectorRegistryFile {
+    #[serde(default)]
+    contracts: HashMap<Address, String>,
+
+    #[serde(default)]
+    signatures: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct SelectorRegistry {
+    contracts: HashMap<Address, ContractName>,
+    signatures: HashMap<[u8; 4], SoliditySignature>,
+}
+
+impl From<SelectorRegistryFile> for SelectorRegistry {
+    fn from(file: SelectorRegistryFile) -> Self {
+        // names are leaked because they live until the end of the process, same as the generated ones
+        let contracts = file
+            .contracts
+            .into_iter()
+            .map(|(address, name)| (address, &*Box::leak(name.into_boxed_str())))
+            .collect();
+        let signatures = file
+            .signatures
+            .into_iter()
+            .map(|signature| (id(&signature), &*Box::leak(signature.into_boxed_str())))
+            .collect();
+        Self { contracts, signatures }
+    }
+}
+
+/// Returns the contract name registered at runtime.
+pub fn contract_name(address: &Address) -> Option<ContractName> {
+    REGISTRY.get()?.contracts.get(address).copied()
+}
+
+/// Returns the function signature registered at runtime.
+pub fn function_sig(id: &[u8]) -> Option<SoliditySignature> {
+    let id: [u8; 4] = id.try_into().ok()?;
+    REGISTRY.get()?.signatures.get(&id).copied()
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn test_selector_registry_from_file() {
+        let file: SelectorRegistryFile = serde_json::from_str(
+            r#"{
+                "contracts": {"0x0000000000000000000000000000000000000001": "Token"},
+                "signatures": ["transfer(address,uint256)"]
+            }"#,
+        )
+        .unwrap();
+        let registry = SelectorRegistry::from(file);
+
+        assert_eq!(
+            registry.contracts.get(&Address::from(hex!("0000000000000000000000000000000000000001"))),
+            Some(&"Token")
+        );
+        assert_eq!(registry.signatures.get(&hex!("a9059cbb")), Some(&"transfer(address,uint256)"));
+    }
+}
@@ -6,6 +6,7 @@ use rustc_hash::FxBuildHasher;
 use super::AccountWithSlots;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
 use crate::eth::primitives::ExecutionChanges;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
@@ -61,6 +62,16 @@ impl StorageCache {
         }
     }
 
+    /// Evicts the accounts and slots touched by a block, so they are read again from storage.
+    pub fn invalidate(&self, invalidation: &CacheInvalidation) {
+        for address in &invalidation.accounts {
+            self.account_cache.remove(address);
+        }
+        for key in &invalidation.slots {
+            self.slot_cache.remove(key);
+        }
+    }
+
     pub fn get_slot(&self, address: Address, index: SlotIndex) -> Option<Slot> {
         self.slot_cache.get(&(address, index)).map(|value| Slot { value, index })
     }
@@ -69,3 +80,71 @@ impl StorageCache {
         self.account_cache.get(&address)
     }
 }
+
+// -----------------------------------------------------------------------------
+// Invalidation
+// -----------------------------------------------------------------------------
+
+/// Accounts and slots modified by a block.
+///
+/// Sent by the leader together with the block in the replication stream, so followers evict the touched entries from their caches after
+/// committing the block instead of flushing the whole cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CacheInvalidation {
+    pub accounts: Vec<Address>,
+    pub slots: Vec<(Address, SlotIndex)>,
+}
+
+impl CacheInvalidation {
+    /// Collects the accounts and slots modified by all transactions of the block.
+    pub fn from_block(block: &Block) -> Self {
+        let mut invalidation = Self::default();
+        for changes in block.transactions.iter().flat_map(|tx| tx.execution.changes.values()) {
+            if changes.is_account_modified() {
+                invalidation.accounts.push(changes.address);
+            }
+            for (index, slot) in &changes.slots {
+                if slot.is_modified() {
+                    invalidation.slots.push((changes.address, *index));
+                }
+            }
+        }
+
+        // the same entry may be modified by multiple transactions
+        invalidation.accounts.sort();
+        invalidation.accounts.dedup();
+        invalidation.slots.sort();
+        invalidation.slots.dedup();
+        invalidation
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalidate_evicts_only_touched_entries() {
+        let cache = StorageCache::default();
+        let (touched, untouched) = (Address::new([1; 20]), Address::new([2; 20]));
+        for address in [touched, untouched] {
+            cache.cache_account(Account::new_empty(address));
+            cache.cache_slot(address, Slot::new_empty(SlotIndex::ZERO));
+            cache.cache_slot(address, Slot::new_empty(SlotIndex::ONE));
+        }
+
+        cache.invalidate(&CacheInvalidation {
+            accounts: vec![touched],
+            slots: vec![(touched, SlotIndex::ONE)],
+        });
+
+        assert!(cache.get_account(touched).is_none());
+        assert!(cache.get_slot(touched, SlotIndex::ZERO).is_some());
+        assert!(cache.get_slot(touched, SlotIndex::ONE).is_none());
+        assert!(cache.get_account(untouched).is_some());
+        assert!(cache.get_slot(untouched, SlotIndex::ONE).is_some());
+    }
+}
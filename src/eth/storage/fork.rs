@@ -0,0 +1,394 @@
+use std::future::Future;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use quick_cache::sync::Cache;
+use quick_cache::sync::DefaultLifecycle;
+use quick_cache::UnitWeighter;
+use rustc_hash::FxBuildHasher;
+use tokio::runtime::Handle;
+use tokio::runtime::Runtime;
+use tokio::runtime::RuntimeFlavor;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::ext::parse_duration;
+use crate::infra::metrics;
+use crate::infra::metrics::timed;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+
+/// Upstream chain used as the base state of a forked Stratus.
+///
+/// Accounts and slots that were never written locally are fetched from the upstream chain at the fork block and cached. The upstream state at a fixed
+/// block never changes, so cached values never become stale.
+///
+/// Upstream requests are executed by a runtime owned by the fork, so the synchronous storage code can wait for them from any thread, including
+/// workers of a single-threaded runtime and threads outside a runtime.
+pub struct StorageFork {
+    client: Arc<BlockchainClient>,
+    block_number: BlockNumber,
+    runtime: Option<Runtime>,
+    account_cache: Cache<Address, Account, UnitWeighter, FxBuildHasher>,
+    slot_cache: Cache<(Address, SlotIndex), SlotValue, UnitWeighter, FxBuildHasher>,
+}
+
+impl StorageFork {
+    /// Creates a fork of the upstream chain at the specified block, or at its current block if not specified.
+    pub fn new(url: &str, block_number: Option<BlockNumber>, timeout: Duration) -> anyhow::Result<Self> {
+        let runtime = match tokio::runtime::Builder::new_multi_thread()
+            .thread_name("storage-fork")
+            .worker_threads(2)
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => return log_and_err!(reason = e, "failed to create storage fork runtime"),
+        };
+
+        let (client, block_number) = block_on(runtime.handle(), {
+            let url = url.to_owned();
+            async move {
+                let client = BlockchainClient::new_http(&url, timeout).await?;
+                let block_number = match block_number {
+                    Some(block_number) => block_number,
+                    None => client.fetch_block_number().await?,
+                };
+                anyhow::Ok((client, block_number))
+            }
+        })?;
+        tracing::info!(%url, %block_number, "forking upstream chain");
+
+        Ok(Self {
+            client: Arc::new(client),
+            block_number,
+            runtime: Some(runtime),
+            account_cache: Cache::with(100_000, 100_000, UnitWeighter, FxBuildHasher, DefaultLifecycle::default()),
+            slot_cache: Cache::with(1_000_000, 1_000_000, UnitWeighter, FxBuildHasher, DefaultLifecycle::default()),
+        })
+    }
+
+    /// Upstream block where the chain was forked.
+    pub fn block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
+    /// Reads an account from the upstream chain at the fork block.
+    pub fn read_account(&self, address: Address) -> anyhow::Result<Account> {
+        if let Some(account) = self.account_cache.get(&address) {
+            return Ok(account);
+        }
+
+        let client = Arc::clone(&self.client);
+        let block_number = Some(self.block_number);
+        let account = timed(|| {
+            self.block_on(async move {
+                let (nonce, balance, bytecode) = tokio::try_join!(
+                    client.fetch_transaction_count(address, block_number),
+                    client.fetch_balance(address, block_number),
+                    client.fetch_code(address, block_number),
+                )?;
+                let bytecode = if bytecode.is_empty() { None } else { Some(bytecode) };
+                anyhow::Ok(Account {
+                    address,
+                    nonce,
+                    balance,
+                    code_hash: CodeHash::from_bytecode(bytecode.clone()),
+                    bytecode,
+                })
+            })
+        })
+        .with(|m| {
+            metrics::inc_storage_fork_read_account(m.elapsed, m.result.is_ok());
+        });
+
+        match account {
+            Ok(account) => {
+                tracing::debug!(%address, ?account, "account fetched from fork");
+                self.account_cache.insert(address, account.clone());
+                Ok(account)
+            }
+            Err(e) => log_and_err!(reason = e, "failed to fetch account from fork"),
+        }
+    }
+
+    /// Reads a slot from the upstream chain at the fork block.
+    pub fn read_slot(&self, address: Address, index: SlotIndex) -> anyhow::Result<Slot> {
+        if let Some(value) = self.slot_cache.get(&(address, index)) {
+            return Ok(Slot { index, value });
+        }
+
+        let client = Arc::clone(&self.client);
+        let block_number = Some(self.block_number);
+        let value = timed(|| self.block_on(async move { client.fetch_storage_at(address, index, block_number).await })).with(|m| {
+            metrics::inc_storage_fork_read_slot(m.elapsed, m.result.is_ok());
+        });
+
+        match value {
+            Ok(value) => {
+                tracing::debug!(%address, %index, %value, "slot fetched from fork");
+                self.slot_cache.insert((address, index), value);
+                Ok(Slot { index, value })
+            }
+            Err(e) => log_and_err!(reason = e, "failed to fetch slot from fork"),
+        }
+    }
+
+    /// Executes an upstream request in the fork runtime and waits for its result.
+    fn block_on<T>(&self, future: impl Future<Output = anyhow::Result<T>> + Send + 'static) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+    {
+        let Some(ref runtime) = self.runtime else {
+            return log_and_err!("storage fork runtime is stopped");
+        };
+        block_on(runtime.handle(), future)
+    }
+}
+
+impl Drop for StorageFork {
+    fn drop(&mut self) {
+        // the storage may be dropped inside an async context, where waiting for the runtime to shutdown is not allowed
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Executes a future in the specified runtime and blocks the current thread until it completes.
+///
+/// The future runs in the workers of the specified runtime, so the current thread can be outside a runtime or a worker of any other runtime.
+fn block_on<T>(runtime: &Handle, future: impl Future<Output = anyhow::Result<T>> + Send + 'static) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(1);
+    runtime.spawn(async move {
+        let _ = tx.send(future.await);
+    });
+    let wait = move || match rx.recv() {
+        Ok(result) => result,
+        Err(e) => log_and_err!(reason = e, "storage fork runtime stopped before completing the request"),
+    };
+
+    // let the current runtime move its other tasks to another worker while this one is blocked
+    match Handle::try_current() {
+        Ok(current) if current.runtime_flavor() == RuntimeFlavor::MultiThread => tokio::task::block_in_place(wait),
+        _ => wait(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+/// Fork configuration.
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct StorageForkConfig {
+    /// Upstream RPC endpoint of the chain to fork. Accounts and slots not found locally are fetched from it.
+    #[arg(long = "fork-url", env = "FORK_URL")]
+    pub fork_url: Option<String>,
+
+    /// Upstream block where the chain is forked. Defaults to the upstream current block.
+    #[arg(long = "fork-block", env = "FORK_BLOCK", requires = "fork_url")]
+    pub fork_block: Option<BlockNumber>,
+
+    /// Timeout of requests to the upstream chain.
+    #[arg(long = "fork-timeout", value_parser=parse_duration, env = "FORK_TIMEOUT", default_value = "2s")]
+    pub fork_timeout: Duration,
+}
+
+impl StorageForkConfig {
+    /// Initializes the fork if an upstream chain is configured.
+    pub fn init(&self) -> anyhow::Result<Option<StorageFork>> {
+        let Some(ref fork_url) = self.fork_url else { return Ok(None) };
+
+        tracing::info!(config = ?self, "creating storage fork");
+        StorageFork::new(fork_url, self.fork_block, self.fork_timeout).map(Some)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use jsonrpsee::server::Server;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::RpcModule;
+
+    use super::*;
+    use crate::eth::primitives::Block;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::primitives::PointInTime;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::Storage;
+    use crate::eth::storage::StorageReader;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+
+    const FORK_BLOCK: u64 = 16;
+    const UPSTREAM_ACCOUNT: Address = Address::new([1; 20]);
+
+    /// Upstream chain where every account has nonce 3, balance 100 and code `0x6080`, and every slot has value 42.
+    struct Upstream {
+        url: String,
+        requests: Arc<AtomicUsize>,
+        _server: ServerHandle,
+        runtime: Option<Runtime>,
+    }
+
+    impl Upstream {
+        fn start() -> Self {
+            let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_all().build().unwrap();
+            let requests = Arc::new(AtomicUsize::new(0));
+
+            let mut module = RpcModule::new(Arc::clone(&requests));
+            module.register_method("net_listening", |_, _, _| true).unwrap();
+            module.register_method("eth_blockNumber", |_, _, _| format!("{:#x}", FORK_BLOCK)).unwrap();
+            for (method, result) in [
+                ("eth_getTransactionCount", "0x3"),
+                ("eth_getBalance", "0x64"),
+                ("eth_getCode", "0x6080"),
+                ("eth_getStorageAt", "0x2a"),
+            ] {
+                module
+                    .register_method(method, move |_, requests, _| {
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        result
+                    })
+                    .unwrap();
+            }
+
+            let (url, server) = block_on(runtime.handle(), async move {
+                let server = Server::builder().build("127.0.0.1:0").await?;
+                let url = format!("http://{}", server.local_addr()?);
+                anyhow::Ok((url, server.start(module)))
+            })
+            .unwrap();
+
+            Self {
+                url,
+                requests,
+                _server: server,
+                runtime: Some(runtime),
+            }
+        }
+
+        fn fork(&self) -> StorageFork {
+            StorageFork::new(&self.url, Some(FORK_BLOCK.into()), Duration::from_secs(2)).unwrap()
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Drop for Upstream {
+        fn drop(&mut self) {
+            if let Some(runtime) = self.runtime.take() {
+                runtime.shutdown_background();
+            }
+        }
+    }
+
+    fn storage_with_fork(fork: StorageFork) -> StratusStorage {
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::new(BlockNumber::ZERO, UnixTime::ZERO)).unwrap();
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        StratusStorage::new(temp, Box::new(perm), Some(fork)).unwrap()
+    }
+
+    fn assert_upstream_account(account: &Account) {
+        assert_eq!(account.address, UPSTREAM_ACCOUNT);
+        assert_eq!(account.nonce, Nonce::from(3u64));
+        assert_eq!(account.balance, Wei::from(100u64));
+        assert_eq!(account.bytecode, Some(Bytes::from(vec![0x60, 0x80])));
+    }
+
+    #[test]
+    fn test_fork_reads_and_caches_upstream_state() {
+        let upstream = Upstream::start();
+        let fork = upstream.fork();
+        assert_eq!(fork.block_number(), FORK_BLOCK.into());
+
+        // account is fetched with three requests, then cached
+        assert_upstream_account(&fork.read_account(UPSTREAM_ACCOUNT).unwrap());
+        assert_eq!(upstream.requests(), 3);
+        assert_upstream_account(&fork.read_account(UPSTREAM_ACCOUNT).unwrap());
+        assert_eq!(upstream.requests(), 3);
+
+        // slot is fetched with one request, then cached
+        let index = SlotIndex::from(1u64);
+        assert_eq!(fork.read_slot(UPSTREAM_ACCOUNT, index).unwrap().value, SlotValue::from(42u64));
+        assert_eq!(fork.read_slot(UPSTREAM_ACCOUNT, index).unwrap().value, SlotValue::from(42u64));
+        assert_eq!(upstream.requests(), 4);
+    }
+
+    #[test]
+    fn test_fork_defaults_to_upstream_current_block() {
+        let upstream = Upstream::start();
+        let fork = StorageFork::new(&upstream.url, None, Duration::from_secs(2)).unwrap();
+        assert_eq!(fork.block_number(), FORK_BLOCK.into());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_fork_reads_from_single_threaded_runtime() {
+        let upstream = Upstream::start();
+        let fork = upstream.fork();
+        assert_upstream_account(&fork.read_account(UPSTREAM_ACCOUNT).unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fork_reads_from_multi_threaded_runtime() {
+        let upstream = Upstream::start();
+        let fork = upstream.fork();
+        assert_upstream_account(&fork.read_account(UPSTREAM_ACCOUNT).unwrap());
+    }
+
+    #[test]
+    fn test_storage_falls_back_to_fork_only_for_missing_state() {
+        let upstream = Upstream::start();
+        let storage = storage_with_fork(upstream.fork());
+
+        // missing account and slot are read from the fork
+        let index = SlotIndex::from(1u64);
+        assert_upstream_account(&storage.read_account(UPSTREAM_ACCOUNT, PointInTime::Mined).unwrap());
+        assert_eq!(
+            storage.read_slot(UPSTREAM_ACCOUNT, index, PointInTime::Mined).unwrap().value,
+            SlotValue::from(42u64)
+        );
+
+        // local account shadows the fork
+        let local = Address::new([2; 20]);
+        storage.save_accounts(vec![Account::new_with_balance(local, Wei::from(7u64))]).unwrap();
+        let requests = upstream.requests();
+        assert_eq!(storage.read_account(local, PointInTime::Mined).unwrap().balance, Wei::from(7u64));
+        assert_eq!(upstream.requests(), requests);
+    }
+
+    #[test]
+    fn test_storage_fails_when_fork_is_unavailable() {
+        let upstream = Upstream::start();
+        let fork = upstream.fork();
+        drop(upstream);
+
+        let storage = storage_with_fork(fork);
+        assert!(storage.read_account(UPSTREAM_ACCOUNT, PointInTime::Mined).is_err());
+    }
+}
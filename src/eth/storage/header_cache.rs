@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+use parking_lot::RwLock;
+
+use crate::alias::EthersBlockH256;
+use crate::alias::JsonValue;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::ext::not;
+use crate::ext::to_json_value;
+
+/// Number of latest blocks kept in the cache by default.
+const DEFAULT_CAPACITY: usize = 128;
+
+/// Header of a mined block with the hashes of its transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedBlockHeader {
+    pub header: BlockHeader,
+    pub transactions: Vec<Hash>,
+}
+
+impl CachedBlockHeader {
+    /// Serializes itself to JSON-RPC block format with only transactions hashes included.
+    pub fn to_json_rpc_with_transactions_hashes(self) -> JsonValue {
+        let ethers_block = EthersBlockH256::from(self.header);
+        let ethers_block = EthersBlockH256 {
+            transactions: self.transactions.into_iter().map_into().collect(),
+            ..ethers_block
+        };
+        to_json_value(ethers_block)
+    }
+}
+
+impl From<&Block> for CachedBlockHeader {
+    fn from(block: &Block) -> Self {
+        Self {
+            header: block.header.clone(),
+            transactions: block.transactions.iter().map(|tx| tx.input.hash).collect(),
+        }
+    }
+}
+
+/// Ring buffer of the latest saved block headers and of the mined block number, so the most common block queries do not hit the permanent storage.
+///
+/// Only consecutive blocks are kept. If a block that does not follow the latest cached one is saved, the cache restarts from it.
+pub struct BlockHeaderCache {
+    capacity: usize,
+    state: RwLock<BlockHeaderCacheState>,
+}
+
+#[derive(Default)]
+struct BlockHeaderCacheState {
+    /// Latest blocks ordered by number, starting from the oldest.
+    blocks: VecDeque<CachedBlockHeader>,
+
+    /// Numbers of the cached blocks indexed by their hashes.
+    numbers_by_hash: HashMap<Hash, BlockNumber>,
+
+    /// Last mined block number, unknown until read from or written to the permanent storage.
+    mined_number: Option<BlockNumber>,
+}
+
+impl Default for BlockHeaderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl BlockHeaderCache {
+    /// Creates a cache that keeps up to `capacity` latest blocks.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: RwLock::new(BlockHeaderCacheState::default()),
+        }
+    }
+
+    pub fn clear(&self) {
+        *self.state.write() = BlockHeaderCacheState::default();
+    }
+
+    /// Caches the header of a block that was saved in the permanent storage.
+    pub fn push(&self, block: CachedBlockHeader) {
+        let mut state = self.state.write();
+
+        // restart from this block if it is not consecutive to the latest cached one
+        let is_next = match state.blocks.back() {
+            Some(latest) => latest.header.number.next_block_number() == block.header.number,
+            None => true,
+        };
+        if not(is_next) {
+            state.blocks.clear();
+            state.numbers_by_hash.clear();
+        }
+
+        state.numbers_by_hash.insert(block.header.hash, block.header.number);
+        state.blocks.push_back(block);
+        while state.blocks.len() > self.capacity {
+            if let Some(evicted) = state.blocks.pop_front() {
+                state.numbers_by_hash.remove(&evicted.header.hash);
+            }
+        }
+    }
+
+    /// Retrieves a cached block header. Returns `None` when the block is not cached, so it must be read from the permanent storage.
+    pub fn get(&self, filter: BlockFilter) -> Option<CachedBlockHeader> {
+        let state = self.state.read();
+        let number = match filter {
+            BlockFilter::Latest => return state.blocks.back().cloned(),
+            BlockFilter::Number(number) => number,
+            BlockFilter::Hash(hash) => *state.numbers_by_hash.get(&hash)?,
+            BlockFilter::Pending | BlockFilter::Earliest | BlockFilter::Safe | BlockFilter::Finalized => return None,
+        };
+
+        let oldest = state.blocks.front()?.header.number;
+        let index = number.as_u64().checked_sub(oldest.as_u64())?;
+        state.blocks.get(index as usize).cloned()
+    }
+
+    /// Retrieves the cached hash of a block, used to check parent hashes without reading the whole block.
+    pub fn get_hash(&self, number: BlockNumber) -> Option<Hash> {
+        self.get(BlockFilter::Number(number)).map(|block| block.header.hash)
+    }
+
+    /// Retrieves the cached mined block number.
+    pub fn get_mined_number(&self) -> Option<BlockNumber> {
+        self.state.read().mined_number
+    }
+
+    /// Caches the mined block number after it is written to the permanent storage.
+    pub fn set_mined_number(&self, number: BlockNumber) {
+        self.state.write().mined_number = Some(number);
+    }
+
+    /// Caches the mined block number read from the permanent storage, unless it was already written in the meantime.
+    pub fn init_mined_number(&self, number: BlockNumber) {
+        self.state.write().mined_number.get_or_insert(number);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::UnixTime;
+
+    fn block(number: u64) -> Block {
+        Block::new(number.into(), UnixTime::from(number))
+    }
+
+    fn header(number: u64) -> CachedBlockHeader {
+        CachedBlockHeader::from(&block(number))
+    }
+
+    #[test]
+    fn test_keeps_latest_blocks() {
+        let cache = BlockHeaderCache::new(3);
+        for number in 0..5 {
+            cache.push(header(number));
+        }
+
+        assert_eq!(cache.get(BlockFilter::Latest).unwrap().header.number, BlockNumber::from(4));
+        assert_eq!(cache.get(BlockFilter::Number(2.into())).unwrap().header.number, BlockNumber::from(2));
+        assert_eq!(cache.get(BlockFilter::Hash(block(3).hash())).unwrap().header.number, BlockNumber::from(3));
+        assert_eq!(cache.get_hash(4.into()), Some(block(4).hash()));
+
+        // evicted blocks are read from storage
+        assert!(cache.get(BlockFilter::Number(1.into())).is_none());
+        assert!(cache.get(BlockFilter::Hash(block(1).hash())).is_none());
+        assert!(cache.get(BlockFilter::Number(5.into())).is_none());
+    }
+
+    #[test]
+    fn test_restarts_on_gap() {
+        let cache = BlockHeaderCache::new(3);
+        cache.push(header(0));
+        cache.push(header(1));
+        cache.push(header(5));
+
+        assert!(cache.get(BlockFilter::Number(1.into())).is_none());
+        assert!(cache.get(BlockFilter::Hash(block(0).hash())).is_none());
+        assert_eq!(cache.get(BlockFilter::Latest).unwrap().header.number, BlockNumber::from(5));
+    }
+
+    #[test]
+    fn test_mined_number_written_wins_over_read() {
+        let cache = BlockHeaderCache::default();
+        assert!(cache.get_mined_number().is_none());
+
+        cache.set_mined_number(2.into());
+        cache.init_mined_number(1.into());
+        assert_eq!(cache.get_mined_number(), Some(BlockNumber::from(2)));
+    }
+}
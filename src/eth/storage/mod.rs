@@ -1,18 +1,34 @@
 //! Ethereum / EVM storage.
 
+pub use cache::CacheInvalidation;
 pub use cache::StorageCache;
+pub use fork::StorageFork;
+pub use fork::StorageForkConfig;
+pub use header_cache::BlockHeaderCache;
+pub use header_cache::CachedBlockHeader;
+#[cfg(feature = "chaos")]
+pub use permanent::set_storage_faults;
+#[cfg(feature = "chaos")]
+pub use permanent::storage_faults;
 pub use permanent::InMemoryPermanentStorage;
 pub use permanent::PermanentStorage;
 pub use permanent::PermanentStorageConfig;
 pub use permanent::PermanentStorageKind;
+pub use permanent::ReadStorage;
+#[cfg(feature = "chaos")]
+pub use permanent::StorageFault;
+pub use permanent::WriteStorage;
 pub use stratus_storage::StratusStorage;
 use strum::VariantNames;
+pub use temporary::BlockCandidateId;
 pub use temporary::InMemoryTemporaryStorage;
 pub use temporary::TemporaryStorage;
 pub use temporary::TemporaryStorageConfig;
 pub use temporary::TemporaryStorageKind;
 
 mod cache;
+mod fork;
+mod header_cache;
 pub mod permanent;
 mod stratus_storage;
 mod temporary;
@@ -25,12 +41,18 @@ use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PendingBlock;
@@ -39,10 +61,14 @@ use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionStage;
 
-pub trait Storage: Send + Sync + 'static {
+/// Read operations of the storage, including the pending block state.
+///
+/// Components that only serve or inspect data should depend on this trait instead of [`Storage`], so they cannot change the state.
+pub trait StorageReader: Send + Sync + 'static {
     // -------------------------------------------------------------------------
     // Block number
     // -------------------------------------------------------------------------
@@ -53,17 +79,96 @@ pub trait Storage: Send + Sync + 'static {
 
     fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError>;
 
+    // -------------------------------------------------------------------------
+    // Accounts and slots
+    // -------------------------------------------------------------------------
+
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Account, StratusError>;
+
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Slot, StratusError>;
+
+    /// Retrieves up to `limit` mined slots of an account, starting at the specified index.
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError>;
+
+    /// Retrieves mined balance changes of an account, including all changes of up to `limit` blocks.
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError>;
+
+    /// Retrieves value transferred by internal calls of transactions in a mined block.
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError>;
+
+    /// Retrieves mined internal transfers sent or received by an account, including all transfers of up to `limit` blocks.
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError>;
+
+    /// Checks if token transfers are indexed when saving blocks.
+    fn token_transfers_indexed(&self) -> bool;
+
+    /// Retrieves mined token transfers sent or received by an account, including all transfers of up to `limit` blocks.
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError>;
+
+    /// Retrieves mined token transfers of a token contract, including all transfers of up to `limit` blocks.
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError>;
+
+    /// Retrieves the mined transaction that deployed a contract.
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError>;
+
+    /// Retrieves the account changes of a mined block, compacted to the original and last modified values of each account.
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError>;
+
+    /// Counts all mined accounts and slots.
+    fn read_state_count(&self) -> Result<StateCount, StratusError>;
+
+    /// Retrieves the gas usage aggregated by the gas analytics.
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError>;
+
+    // -------------------------------------------------------------------------
+    // Blocks
+    // -------------------------------------------------------------------------
+
+    /// Retrieves pending transactions being mined.
+    fn pending_transactions(&self) -> Vec<TransactionExecution>;
+
+    fn read_block(&self, filter: BlockFilter) -> Result<Option<Block>, StratusError>;
+
+    /// Retrieves the header of a block with the hashes of its transactions, served from the cache for the latest blocks.
+    fn read_block_header(&self, filter: BlockFilter) -> Result<Option<CachedBlockHeader>, StratusError>;
+
+    fn read_transaction(&self, tx_hash: Hash) -> Result<Option<TransactionStage>, StratusError>;
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError>;
+
+    // -------------------------------------------------------------------------
+    // Utils
+    // -------------------------------------------------------------------------
+
+    /// Translates a block filter to a specific storage point-in-time indicator.
+    fn translate_to_point_in_time(&self, block_filter: BlockFilter) -> Result<PointInTime, StratusError>;
+}
+
+/// Read and write operations of the storage.
+pub trait Storage: StorageReader {
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
     fn set_mined_block_number(&self, block_number: BlockNumber) -> Result<(), StratusError>;
 
+    /// Sets the last finalized block number, ignoring numbers that are not greater than the current one.
+    ///
+    /// Fails if the block is not mined yet, so a block is never finalized before it is saved.
+    fn set_finalized_block_number(&self, block_number: BlockNumber) -> Result<(), StratusError>;
+
     // -------------------------------------------------------------------------
     // Accounts and slots
     // -------------------------------------------------------------------------
 
     fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError>;
 
-    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Account, StratusError>;
+    // -------------------------------------------------------------------------
+    // Analytics
+    // -------------------------------------------------------------------------
 
-    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Slot, StratusError>;
+    /// Persists the gas usage aggregated by the gas analytics.
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError>;
 
     // -------------------------------------------------------------------------
     // Blocks
@@ -71,31 +176,31 @@ pub trait Storage: Send + Sync + 'static {
 
     fn save_execution(&self, tx: TransactionExecution, check_conflicts: bool) -> Result<(), StratusError>;
 
-    /// Retrieves pending transactions being mined.
-    fn pending_transactions(&self) -> Vec<TransactionExecution>;
-
     fn finish_pending_block(&self) -> Result<PendingBlock, StratusError>;
 
+    /// Discards the executions of the pending block, restarting it at the same number.
+    fn discard_pending_block(&self) -> Result<PendingBlock, StratusError>;
+
+    /// Finishes the pending block as a candidate awaiting confirmation in the specified epoch, and starts a new block on top of it.
+    fn propose_pending_block(&self, epoch: u64) -> Result<PendingBlock, StratusError>;
+
+    /// Promotes the oldest block candidate after it is confirmed, so it can be saved like a finished block.
+    fn promote_block_candidate(&self, id: BlockCandidateId) -> Result<PendingBlock, StratusError>;
+
+    /// Discards the block candidates from the specified number onwards and the pending block built on top of them.
+    fn discard_block_candidates(&self, from: BlockNumber) -> Result<Vec<PendingBlock>, StratusError>;
+
     fn save_block(&self, block: Block) -> Result<(), StratusError>;
 
     fn save_block_batch(&self, blocks: Vec<Block>) -> Result<(), StratusError> {
         blocks.into_iter().try_for_each(|block| self.save_block(block))
     }
 
-    fn read_block(&self, filter: BlockFilter) -> Result<Option<Block>, StratusError>;
-
-    fn read_transaction(&self, tx_hash: Hash) -> Result<Option<TransactionStage>, StratusError>;
-
-    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError>;
-
     #[cfg(feature = "dev")]
     /// Resets the storage to the genesis state used in dev-mode.
     ///
     /// TODO: For now it uses the dev genesis block and test accounts, but it should be refactored to support genesis.json files.
     fn reset_to_genesis(&self) -> Result<(), StratusError>;
-
-    /// Translates a block filter to a specific storage point-in-time indicator.
-    fn translate_to_point_in_time(&self, block_filter: BlockFilter) -> Result<PointInTime, StratusError>;
 }
 
 #[derive(Debug, Clone)]
@@ -129,16 +234,20 @@ pub struct StorageConfig {
 
     #[clap(flatten)]
     pub perm_storage: PermanentStorageConfig,
+
+    #[clap(flatten)]
+    pub fork: StorageForkConfig,
 }
 
 impl StorageConfig {
     /// Initializes Stratus storage.
     pub fn init(&self) -> Result<Arc<StratusStorage>, StratusError> {
         let perm_storage = self.perm_storage.init()?;
-        let temp_storage = self.temp_storage.init(&*perm_storage)?;
+        let temp_storage = self.temp_storage.init(perm_storage.as_read())?;
 
         let StorageKind::StratusStorage = self.storage_kind;
-        let storage = StratusStorage::new(temp_storage, perm_storage)?;
+        let fork = self.fork.init()?;
+        let storage = StratusStorage::new(temp_storage, perm_storage, fork)?;
 
         Ok(Arc::new(storage))
     }
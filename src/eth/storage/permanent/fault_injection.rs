@@ -0,0 +1,331 @@
+//! Fault injection in the permanent storage, used to test how services behave when the storage is slow or failing.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::bail;
+use parking_lot::RwLock;
+
+use super::PermanentStorage;
+use super::PermanentStorageKind;
+use super::ReadStorage;
+use super::WriteStorage;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
+use crate::eth::primitives::TransactionMined;
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::ext::DisplayExt;
+
+/// Faults currently injected in the permanent storage.
+static STORAGE_FAULTS: RwLock<Vec<StorageFault>> = RwLock::new(Vec::new());
+
+/// Replaces the faults injected in the permanent storage. An empty list disables fault injection.
+pub fn set_storage_faults(faults: Vec<StorageFault>) {
+    tracing::warn!(faults = ?faults.iter().map(ToString::to_string).collect::<Vec<_>>(), "setting permanent storage faults");
+    *STORAGE_FAULTS.write() = faults;
+}
+
+/// Retrieves the faults injected in the permanent storage.
+pub fn storage_faults() -> Vec<StorageFault> {
+    STORAGE_FAULTS.read().clone()
+}
+
+// -----------------------------------------------------------------------------
+// Fault
+// -----------------------------------------------------------------------------
+
+/// Fault injected in a permanent storage method.
+///
+/// Parsed from `method:kind[@probability]`, where `method` is a [`PermanentStorage`] method name or `*` for all methods and `kind` is one of:
+/// * `latency=<duration>`: delays the operation.
+/// * `error`: fails without executing the operation.
+/// * `partial`: executes the operation, but fails as if it was not executed.
+///
+/// Examples: `save_block:error@0.1`, `read_account:latency=200ms`, `*:partial@0.01`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StorageFault {
+    pub method: String,
+    pub kind: StorageFaultKind,
+    pub probability: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum StorageFaultKind {
+    Latency(Duration),
+    Error,
+    Partial,
+}
+
+impl StorageFault {
+    fn applies_to(&self, method: &str) -> bool {
+        (self.method == "*" || self.method == method) && rand::random::<f64>() < self.probability
+    }
+}
+
+impl FromStr for StorageFault {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (fault, probability) = match s.trim().split_once('@') {
+            Some((fault, probability)) => (fault, probability.parse::<f64>()?),
+            None => (s.trim(), 1.0),
+        };
+        if not((0.0..=1.0).contains(&probability)) {
+            bail!("storage fault probability must be between 0 and 1: {}", s);
+        }
+
+        let Some((method, kind)) = fault.split_once(':') else {
+            bail!("storage fault must have the format method:kind[@probability]: {}", s);
+        };
+        let kind = match kind.split_once('=') {
+            Some(("latency", duration)) => StorageFaultKind::Latency(parse_duration(duration)?),
+            None if kind == "error" => StorageFaultKind::Error,
+            None if kind == "partial" => StorageFaultKind::Partial,
+            _ => return Err(anyhow!("unknown storage fault kind: {}", kind)),
+        };
+
+        Ok(Self {
+            method: method.to_owned(),
+            kind,
+            probability,
+        })
+    }
+}
+
+impl Display for StorageFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            StorageFaultKind::Latency(duration) => write!(f, "{}:latency={}", self.method, duration.to_string_ext())?,
+            StorageFaultKind::Error => write!(f, "{}:error", self.method)?,
+            StorageFaultKind::Partial => write!(f, "{}:partial", self.method)?,
+        }
+        write!(f, "@{}", self.probability)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Storage
+// -----------------------------------------------------------------------------
+
+/// Permanent storage wrapper that injects the configured faults before delegating operations.
+pub struct FaultInjectionPermanentStorage {
+    inner: Box<dyn PermanentStorage>,
+}
+
+impl FaultInjectionPermanentStorage {
+    pub fn new(inner: Box<dyn PermanentStorage>, faults: Vec<StorageFault>) -> Self {
+        set_storage_faults(faults);
+        Self { inner }
+    }
+
+    /// Executes a storage operation, injecting the faults that apply to the method.
+    fn inject<T>(method: &'static str, operation: impl FnOnce() -> Result<T, StratusError>) -> Result<T, StratusError> {
+        let faults = STORAGE_FAULTS
+            .read()
+            .iter()
+            .filter(|fault| fault.applies_to(method))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut partial = false;
+        for fault in faults {
+            tracing::warn!(%method, fault = %fault, "injecting permanent storage fault");
+            match fault.kind {
+                StorageFaultKind::Latency(duration) => thread::sleep(duration),
+                StorageFaultKind::Error =>
+                    return Err(StratusError::StorageUnavailable {
+                        reason: format!("injected error in permanent storage {}", method),
+                    }),
+                StorageFaultKind::Partial => partial = true,
+            }
+        }
+
+        let result = operation();
+        if partial {
+            return Err(StratusError::StorageUnavailable {
+                reason: format!("injected partial failure in permanent storage {}", method),
+            });
+        }
+        result
+    }
+}
+
+impl ReadStorage for FaultInjectionPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        self.inner.kind()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
+        Self::inject("read_mined_block_number", || self.inner.read_mined_block_number())
+    }
+
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        Self::inject("read_finalized_block_number", || self.inner.read_finalized_block_number())
+    }
+
+    // -------------------------------------------------------------------------
+    // Block
+    // -------------------------------------------------------------------------
+
+    fn read_block(&self, block_filter: BlockFilter) -> Result<Option<Block>, StratusError> {
+        Self::inject("read_block", || self.inner.read_block(block_filter))
+    }
+
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
+        Self::inject("read_transaction", || self.inner.read_transaction(hash))
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
+        Self::inject("read_logs", || self.inner.read_logs(filter))
+    }
+
+    // -------------------------------------------------------------------------
+    // Account and slots
+    // -------------------------------------------------------------------------
+
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
+        Self::inject("read_account", || self.inner.read_account(address, point_in_time))
+    }
+
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
+        Self::inject("read_slot", || self.inner.read_slot(address, index, point_in_time))
+    }
+
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        Self::inject("read_slots_range", || self.inner.read_slots_range(address, start, limit))
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        Self::inject("read_balance_changes", || self.inner.read_balance_changes(address, from_block, limit))
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        Self::inject("read_internal_transfers_by_block", || self.inner.read_internal_transfers_by_block(number))
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        Self::inject("read_internal_transfers_by_address", || {
+            self.inner.read_internal_transfers_by_address(address, from_block, limit)
+        })
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.inner.token_transfers_indexed()
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        Self::inject("read_token_transfers_by_account", || {
+            self.inner.read_token_transfers_by_account(address, from_block, limit)
+        })
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        Self::inject("read_token_transfers_by_token", || {
+            self.inner.read_token_transfers_by_token(token, from_block, limit)
+        })
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        Self::inject("read_contract_creation", || self.inner.read_contract_creation(address))
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        Self::inject("read_state_diff", || self.inner.read_state_diff(number))
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        Self::inject("read_state_count", || self.inner.read_state_count())
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        Self::inject("read_gas_usage", || self.inner.read_gas_usage())
+    }
+}
+
+impl WriteStorage for FaultInjectionPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        Self::inject("set_mined_block_number", || self.inner.set_mined_block_number(number))
+    }
+
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        Self::inject("set_finalized_block_number", || self.inner.set_finalized_block_number(number))
+    }
+
+    fn save_block(&self, block: Block) -> Result<(), StratusError> {
+        Self::inject("save_block", || self.inner.save_block(block))
+    }
+
+    fn save_block_batch(&self, blocks: Vec<Block>) -> Result<(), StratusError> {
+        Self::inject("save_block_batch", || self.inner.save_block_batch(blocks))
+    }
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
+        Self::inject("save_accounts", || self.inner.save_accounts(accounts))
+    }
+
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        Self::inject("save_gas_usage", || self.inner.save_gas_usage(usage))
+    }
+
+    #[cfg(feature = "dev")]
+    fn reset(&self) -> Result<(), StratusError> {
+        Self::inject("reset", || self.inner.reset())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_storage_fault() {
+        let fault = StorageFault::from_str("save_block:error@0.1").unwrap();
+        assert_eq!(fault.method, "save_block");
+        assert_eq!(fault.kind, StorageFaultKind::Error);
+        assert_eq!(fault.probability, 0.1);
+
+        let fault = StorageFault::from_str("read_account:latency=200ms").unwrap();
+        assert_eq!(fault.kind, StorageFaultKind::Latency(Duration::from_millis(200)));
+        assert_eq!(fault.probability, 1.0);
+
+        let fault = StorageFault::from_str("*:partial").unwrap();
+        assert_eq!(fault.method, "*");
+        assert_eq!(fault.kind, StorageFaultKind::Partial);
+
+        assert!(StorageFault::from_str("save_block").is_err());
+        assert!(StorageFault::from_str("save_block:crash").is_err());
+        assert!(StorageFault::from_str("save_block:error@2").is_err());
+    }
+}
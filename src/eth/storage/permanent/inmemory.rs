@@ -1,5 +1,6 @@
 //! In-memory storage implementations.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::atomic::AtomicU64;
@@ -13,23 +14,33 @@ use parking_lot::RwLock;
 use parking_lot::RwLockReadGuard;
 use parking_lot::RwLockWriteGuard;
 
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::Nonce;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
 use crate::eth::primitives::Wei;
-use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::PermanentStorageKind;
+use crate::eth::storage::ReadStorage;
+use crate::eth::storage::WriteStorage;
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct InMemoryPermanentStorageState {
@@ -37,15 +48,34 @@ struct InMemoryPermanentStorageState {
     pub transactions: HashMap<Hash, Arc<Block>, hash_hasher::HashBuildHasher>,
     pub blocks_by_number: IndexMap<BlockNumber, Arc<Block>>,
     pub blocks_by_hash: IndexMap<Hash, Arc<Block>>,
+    pub balance_changes: HashMap<Address, BTreeMap<BlockNumber, Vec<BalanceChange>>, hash_hasher::HashBuildHasher>,
+    pub internal_transfers: HashMap<Address, BTreeMap<BlockNumber, Vec<InternalTransferMined>>, hash_hasher::HashBuildHasher>,
+    pub token_transfers_by_account: HashMap<Address, BTreeMap<BlockNumber, Vec<TokenTransferMined>>, hash_hasher::HashBuildHasher>,
+    pub token_transfers_by_token: HashMap<Address, BTreeMap<BlockNumber, Vec<TokenTransferMined>>, hash_hasher::HashBuildHasher>,
+    pub contract_creations: HashMap<Address, ContractCreation, hash_hasher::HashBuildHasher>,
+    pub gas_usage: Option<GasUsageSnapshot>,
+    pub finalized_block_number: Option<BlockNumber>,
 }
 
 #[derive(Debug)]
 pub struct InMemoryPermanentStorage {
     state: RwLock<InMemoryPermanentStorageState>,
     block_number: AtomicU64,
+
+    /// Should decode and index token transfers when saving blocks?
+    index_token_transfers: bool,
 }
 
 impl InMemoryPermanentStorage {
+    pub fn new(index_token_transfers: bool) -> Self {
+        tracing::info!(%index_token_transfers, "creating inmemory permanent storage");
+        Self {
+            state: RwLock::new(InMemoryPermanentStorageState::default()),
+            block_number: AtomicU64::default(),
+            index_token_transfers,
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Lock methods
     // -------------------------------------------------------------------------
@@ -71,38 +101,44 @@ impl InMemoryPermanentStorage {
         state.transactions.clear();
         state.blocks_by_hash.clear();
         state.blocks_by_number.clear();
+        state.balance_changes.clear();
+        state.internal_transfers.clear();
+        state.token_transfers_by_account.clear();
+        state.token_transfers_by_token.clear();
+        state.contract_creations.clear();
+        state.gas_usage = None;
+        state.finalized_block_number = None;
     }
 }
 
 impl Default for InMemoryPermanentStorage {
     fn default() -> Self {
-        tracing::info!("creating inmemory permanent storage");
-        Self {
-            state: RwLock::new(InMemoryPermanentStorageState::default()),
-            block_number: AtomicU64::default(),
-        }
+        Self::new(false)
     }
 }
 
-impl PermanentStorage for InMemoryPermanentStorage {
+impl ReadStorage for InMemoryPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        PermanentStorageKind::InMemory
+    }
+
     // -------------------------------------------------------------------------
     // Block number operations
     // -------------------------------------------------------------------------
 
-    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber> {
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
         Ok(self.block_number.load(Ordering::SeqCst).into())
     }
 
-    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
-        self.block_number.store(number.as_u64(), Ordering::SeqCst);
-        Ok(())
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        Ok(self.lock_read().finalized_block_number)
     }
 
     // -------------------------------------------------------------------------
     // State operations
     // -------------------------------------------------------------------------
 
-    fn read_account(&self, address: Address, point_in_time: PointInTime) -> anyhow::Result<Option<Account>> {
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
         let state = self.lock_read();
 
         match state.accounts.get(&address) {
@@ -114,26 +150,124 @@ impl PermanentStorage for InMemoryPermanentStorage {
         }
     }
 
-    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> anyhow::Result<Option<Slot>> {
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
         let state = self.lock_read();
 
         let Some(account) = state.accounts.get(&address) else {
             return Ok(None);
         };
 
+        // same as rocks: slots without value at the point-in-time are missing, not zero with a default index
         match account.slots.get(&index) {
-            Some(slot_history) => {
-                let slot = slot_history.get_at_point(point_in_time).unwrap_or_default();
-                Ok(Some(slot))
-            }
+            Some(slot_history) => Ok(slot_history.get_at_point(point_in_time)),
             None => Ok(None),
         }
     }
 
-    fn read_block(&self, selection: BlockFilter) -> anyhow::Result<Option<Block>> {
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        let state = self.lock_read();
+
+        let Some(account) = state.accounts.get(&address) else {
+            return Ok(vec![]);
+        };
+
+        // slots are kept in a hashmap, so they are sorted by index to have a stable order
+        let slots = account
+            .slots
+            .iter()
+            .filter(|(index, _)| **index >= start)
+            .sorted_by_key(|(index, _)| **index)
+            .take(limit)
+            .map(|(_, slot_history)| slot_history.get_current())
+            .collect();
+        Ok(slots)
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        let state = self.lock_read();
+
+        let Some(balance_changes) = state.balance_changes.get(&address) else {
+            return Ok(vec![]);
+        };
+        let balance_changes = balance_changes
+            .range(from_block..)
+            .take(limit)
+            .flat_map(|(_, changes)| changes)
+            .cloned()
+            .collect();
+        Ok(balance_changes)
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        let state = self.lock_read();
+        Ok(state.blocks_by_number.get(&number).map(|block| block.internal_transfers()).unwrap_or_default())
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        let state = self.lock_read();
+
+        let Some(internal_transfers) = state.internal_transfers.get(&address) else {
+            return Ok(vec![]);
+        };
+        let internal_transfers = internal_transfers
+            .range(from_block..)
+            .take(limit)
+            .flat_map(|(_, transfers)| transfers)
+            .cloned()
+            .collect();
+        Ok(internal_transfers)
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.index_token_transfers
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        let state = self.lock_read();
+        Ok(read_token_transfers_range(&state.token_transfers_by_account, address, from_block, limit))
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        let state = self.lock_read();
+        Ok(read_token_transfers_range(&state.token_transfers_by_token, token, from_block, limit))
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        let state = self.lock_read();
+        Ok(state.contract_creations.get(&address).copied())
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        let state = self.lock_read();
+        Ok(state.blocks_by_number.get(&number).map(|block| block.state_diff()))
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        let state = self.lock_read();
+
+        let mut count = StateCount::default();
+        for (address, account) in &state.accounts {
+            let bytecode_size = account.bytecode.get_current().map(|bytecode| bytecode.len()).unwrap_or_default();
+            count.count_account(*address, bytecode_size);
+            for _ in account.slots.keys() {
+                count.count_slot(*address);
+            }
+        }
+        Ok(count)
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        Ok(self.lock_read().gas_usage.clone())
+    }
+
+    fn read_block(&self, selection: BlockFilter) -> Result<Option<Block>, StratusError> {
         let state_lock = self.lock_read();
         let block = match selection {
             BlockFilter::Latest | BlockFilter::Pending => state_lock.blocks_by_number.values().last().cloned(),
+            BlockFilter::Safe => state_lock.blocks_by_number.get(&self.read_mined_block_number()?).cloned(),
+            BlockFilter::Finalized => state_lock
+                .finalized_block_number
+                .and_then(|number| state_lock.blocks_by_number.get(&number).cloned()),
             BlockFilter::Earliest => state_lock.blocks_by_number.values().next().cloned(),
             BlockFilter::Number(block_number) => state_lock.blocks_by_number.get(&block_number).cloned(),
             BlockFilter::Hash(block_hash) => state_lock.blocks_by_hash.get(&block_hash).cloned(),
@@ -144,13 +278,13 @@ impl PermanentStorage for InMemoryPermanentStorage {
         }
     }
 
-    fn read_transaction(&self, hash: Hash) -> anyhow::Result<Option<TransactionMined>> {
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
         let state_lock = self.lock_read();
         let Some(block) = state_lock.transactions.get(&hash) else { return Ok(None) };
         Ok(block.transactions.iter().find(|tx| tx.input.hash == hash).cloned())
     }
 
-    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>> {
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
         let state = self.lock_read();
 
         // determine block start and end
@@ -174,8 +308,20 @@ impl PermanentStorage for InMemoryPermanentStorage {
 
         Ok(filtered_logs.into_iter().cloned().collect_vec())
     }
+}
+
+impl WriteStorage for InMemoryPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.block_number.store(number.as_u64(), Ordering::SeqCst);
+        Ok(())
+    }
 
-    fn save_block(&self, block: Block) -> anyhow::Result<()> {
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.lock_write().finalized_block_number = Some(number);
+        Ok(())
+    }
+
+    fn save_block(&self, block: Block) -> Result<(), StratusError> {
         let mut state = self.lock_write();
 
         // save block
@@ -189,12 +335,47 @@ impl PermanentStorage for InMemoryPermanentStorage {
             state.transactions.insert(tx.input.hash, Arc::clone(&block));
         }
 
+        // save balance changes journal
+        for balance_change in block.balance_changes() {
+            state
+                .balance_changes
+                .entry(balance_change.address)
+                .or_default()
+                .entry(block_number)
+                .or_default()
+                .push(balance_change);
+        }
+
+        // save internal transfers indexed by sender and receiver
+        for (address, internal_transfers) in block.internal_transfers_by_account() {
+            state.internal_transfers.entry(address).or_default().insert(block_number, internal_transfers);
+        }
+
+        // save token transfers indexed by sender, receiver and token
+        if self.index_token_transfers {
+            for (address, token_transfers) in block.token_transfers_by_account() {
+                state
+                    .token_transfers_by_account
+                    .entry(address)
+                    .or_default()
+                    .insert(block_number, token_transfers);
+            }
+            for (token, token_transfers) in block.token_transfers().into_iter().into_group_map_by(|transfer| transfer.transfer.token) {
+                state.token_transfers_by_token.entry(token).or_default().insert(block_number, token_transfers);
+            }
+        }
+
+        // save deployed contracts indexed by address
+        for contract_creation in block.contract_creations() {
+            state.contract_creations.insert(contract_creation.address, contract_creation);
+        }
+
         // save block account changes
         for changes in block.compact_account_changes() {
             let account = state
                 .accounts
                 .entry(changes.address)
-                .or_insert_with(|| InMemoryPermanentAccount::new_empty(changes.address));
+                .or_insert_with(|| InMemoryPermanentAccount::new_with_original(&changes));
 
             // account basic info
             if let Some(nonce) = changes.nonce.take_modified() {
@@ -227,18 +408,21 @@ impl PermanentStorage for InMemoryPermanentStorage {
         Ok(())
     }
 
-    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
         let mut state = self.lock_write();
         for account in accounts {
-            state
-                .accounts
-                .insert(account.address, InMemoryPermanentAccount::new_with_balance(account.address, account.balance));
+            state.accounts.insert(account.address, InMemoryPermanentAccount::new(account));
         }
         Ok(())
     }
 
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        self.lock_write().gas_usage = Some(usage);
+        Ok(())
+    }
+
     #[cfg(feature = "dev")]
-    fn reset(&self) -> anyhow::Result<()> {
+    fn reset(&self) -> Result<(), StratusError> {
         self.block_number.store(0u64, Ordering::SeqCst);
 
         let mut state = self.lock_write();
@@ -248,6 +432,24 @@ impl PermanentStorage for InMemoryPermanentStorage {
     }
 }
 
+/// Reads token transfers of an address index starting at the specified block, including all transfers of up to `limit` blocks.
+fn read_token_transfers_range(
+    index: &HashMap<Address, BTreeMap<BlockNumber, Vec<TokenTransferMined>>, hash_hasher::HashBuildHasher>,
+    address: Address,
+    from_block: BlockNumber,
+    limit: usize,
+) -> Vec<TokenTransferMined> {
+    let Some(token_transfers) = index.get(&address) else {
+        return vec![];
+    };
+    token_transfers
+        .range(from_block..)
+        .take(limit)
+        .flat_map(|(_, transfers)| transfers)
+        .cloned()
+        .collect()
+}
+
 /// TODO: group bytecode, code_hash, static_slot_indexes and mapping_slot_indexes into a single bytecode struct.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct InMemoryPermanentAccount {
@@ -261,19 +463,27 @@ struct InMemoryPermanentAccount {
 }
 
 impl InMemoryPermanentAccount {
-    /// Creates a new empty permanent account.
-    fn new_empty(address: Address) -> Self {
-        Self::new_with_balance(address, Wei::ZERO)
+    /// Creates a new permanent account with the initial values of the account.
+    pub fn new(account: Account) -> Self {
+        Self {
+            address: account.address,
+            balance: InMemoryHistory::new_at_zero(account.balance),
+            nonce: InMemoryHistory::new_at_zero(account.nonce),
+            bytecode: InMemoryHistory::new_at_zero(account.bytecode),
+            code_hash: InMemoryHistory::new_at_zero(account.code_hash),
+            slots: HashMap::default(),
+        }
     }
 
-    /// Creates a new permanent account with initial balance.
-    pub fn new_with_balance(address: Address, balance: Wei) -> Self {
+    /// Creates a new permanent account with the values the account had before an execution, that may have been read from a fork.
+    fn new_with_original(changes: &ExecutionAccountChanges) -> Self {
+        let bytecode = changes.bytecode.take_original_ref().cloned().flatten();
         Self {
-            address,
-            balance: InMemoryHistory::new_at_zero(balance),
-            nonce: InMemoryHistory::new_at_zero(Nonce::ZERO),
-            bytecode: InMemoryHistory::new_at_zero(None),
-            code_hash: InMemoryHistory::new_at_zero(CodeHash::default()),
+            address: changes.address,
+            balance: InMemoryHistory::new_at_zero(changes.balance.take_original_ref().copied().unwrap_or_default()),
+            nonce: InMemoryHistory::new_at_zero(changes.nonce.take_original_ref().copied().unwrap_or_default()),
+            code_hash: InMemoryHistory::new_at_zero(CodeHash::from_bytecode(bytecode.clone())),
+            bytecode: InMemoryHistory::new_at_zero(bytecode),
             slots: HashMap::default(),
         }
     }
@@ -346,3 +556,24 @@ impl<T: Clone + Debug + serde::Serialize + for<'a> serde::Deserialize<'a>> From<
         value.0.into()
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inmemory_history_at_past_blocks_boundaries() {
+        let mut history = InMemoryHistory::new(BlockNumber::from(2u64), 100u64);
+        history.push(BlockNumber::from(5u64), 50);
+
+        assert_eq!(history.get_at_point(PointInTime::MinedPast(1u64.into())), None);
+        assert_eq!(history.get_at_point(PointInTime::MinedPast(2u64.into())), Some(100));
+        assert_eq!(history.get_at_point(PointInTime::MinedPast(4u64.into())), Some(100));
+        assert_eq!(history.get_at_point(PointInTime::MinedPast(5u64.into())), Some(50));
+        assert_eq!(history.get_at_point(PointInTime::MinedPast(10u64.into())), Some(50));
+        assert_eq!(history.get_at_point(PointInTime::Mined), Some(50));
+    }
+}
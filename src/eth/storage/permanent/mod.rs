@@ -1,11 +1,28 @@
+#[cfg(feature = "chaos")]
+pub use self::fault_injection::set_storage_faults;
+#[cfg(feature = "chaos")]
+pub use self::fault_injection::storage_faults;
+#[cfg(feature = "chaos")]
+pub use self::fault_injection::FaultInjectionPermanentStorage;
+#[cfg(feature = "chaos")]
+pub use self::fault_injection::StorageFault;
 pub use self::inmemory::InMemoryPermanentStorage;
+pub use self::read_only::ReadOnlyPermanentStorage;
 pub use self::redis::RedisPermanentStorage;
+#[cfg(feature = "rocks")]
 pub use self::rocks::RocksPermanentStorage;
+#[cfg(feature = "rocks")]
 pub use self::rocks::RocksStorageState;
+pub use self::slow_log::SlowLogPermanentStorage;
 
+#[cfg(feature = "chaos")]
+mod fault_injection;
 mod inmemory;
+mod read_only;
 mod redis;
+#[cfg(feature = "rocks")]
 pub mod rocks;
+mod slow_log;
 
 use std::str::FromStr;
 use std::time::Duration;
@@ -14,74 +31,152 @@ use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
+use crate::ext::not;
 use crate::ext::parse_duration;
 use crate::log_and_err;
 
 /// Permanent (committed) storage operations.
-pub trait PermanentStorage: Send + Sync + 'static {
+///
+/// Implemented by every type that implements both [`ReadStorage`] and [`WriteStorage`]. Components that only read committed data should depend on
+/// [`ReadStorage`] instead, using [`PermanentStorage::as_read`] to get it from a full storage.
+///
+/// Failures are classified by [`StratusError::class`], so callers can retry transient failures of the storage backend.
+pub trait PermanentStorage: ReadStorage + WriteStorage {
+    /// Returns a read-only view of the storage.
+    fn as_read(&self) -> &dyn ReadStorage;
+}
+
+impl<T: ReadStorage + WriteStorage> PermanentStorage for T {
+    fn as_read(&self) -> &dyn ReadStorage {
+        self
+    }
+}
+
+/// Read-only permanent storage operations.
+pub trait ReadStorage: Send + Sync + 'static {
+    /// Kind of the storage implementation.
+    fn kind(&self) -> PermanentStorageKind;
+
+    /// Checks if the storage rejects writes, so its data is written by another process and can change at any time.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     // -------------------------------------------------------------------------
     // Block number
     // -------------------------------------------------------------------------
 
-    /// Sets the last mined block number.
-    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()>;
-
     // Retrieves the last mined block number.
-    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber>;
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError>;
+
+    /// Retrieves the last finalized block number. Returns Option when no block was finalized.
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError>;
 
     // -------------------------------------------------------------------------
     // Block
     // -------------------------------------------------------------------------
 
-    /// Persists atomically changes from block.
-    fn save_block(&self, block: Block) -> anyhow::Result<()>;
-
-    /// Persists atomically changes from blocks.
-    fn save_block_batch(&self, blocks: Vec<Block>) -> anyhow::Result<()> {
-        blocks.into_iter().try_for_each(|block| self.save_block(block))
-    }
-
     /// Retrieves a block from the storage.
-    fn read_block(&self, block_filter: BlockFilter) -> anyhow::Result<Option<Block>>;
+    fn read_block(&self, block_filter: BlockFilter) -> Result<Option<Block>, StratusError>;
 
     /// Retrieves a transaction from the storage.
-    fn read_transaction(&self, hash: Hash) -> anyhow::Result<Option<TransactionMined>>;
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError>;
 
     /// Retrieves logs from the storage.
-    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>>;
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError>;
 
     // -------------------------------------------------------------------------
     // Account and slots
     // -------------------------------------------------------------------------
 
-    /// Persists initial accounts (test accounts or genesis accounts).
-    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()>;
-
     /// Retrieves an account from the storage. Returns Option when not found.
-    fn read_account(&self, address: Address, point_in_time: PointInTime) -> anyhow::Result<Option<Account>>;
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError>;
 
     /// Retrieves an slot from the storage. Returns Option when not found.
-    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> anyhow::Result<Option<Slot>>;
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError>;
 
-    // -------------------------------------------------------------------------
-    // Global state
-    // -------------------------------------------------------------------------
+    /// Retrieves up to `limit` current slots of an account, starting at the specified index and following the storage iteration order.
+    ///
+    /// The order is stable for a given storage, so the index following the last returned slot can be used to continue the iteration.
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError>;
+
+    /// Retrieves balance changes of an account starting at the specified block, including all changes of up to `limit` blocks.
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError>;
+
+    /// Retrieves value transferred by internal calls of transactions in a block.
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError>;
+
+    /// Retrieves internal transfers sent or received by an account starting at the specified block, including all transfers of up to `limit` blocks.
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError>;
+
+    /// Checks if token transfers are indexed when saving blocks.
+    fn token_transfers_indexed(&self) -> bool;
+
+    /// Retrieves token transfers sent or received by an account starting at the specified block, including all transfers of up to `limit` blocks.
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError>;
+
+    /// Retrieves token transfers of a token contract starting at the specified block, including all transfers of up to `limit` blocks.
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError>;
+
+    /// Retrieves the transaction that deployed a contract. Returns Option when not found.
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError>;
+
+    /// Retrieves the account changes of a block, compacted to the original and last modified values of each account. Returns Option when the block
+    /// or its changes are not found.
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError>;
+
+    /// Counts all current accounts and slots. Iterates over the whole state, so it should be used only by background jobs.
+    fn read_state_count(&self) -> Result<StateCount, StratusError>;
+
+    /// Retrieves the gas usage aggregated by the gas analytics. Returns Option when it was never saved.
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError>;
+}
+
+/// Permanent storage operations that change the committed state.
+pub trait WriteStorage: ReadStorage {
+    /// Sets the last mined block number.
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError>;
+
+    /// Sets the last finalized block number. It must not be greater than the mined block number.
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError>;
+
+    /// Persists atomically changes from block.
+    fn save_block(&self, block: Block) -> Result<(), StratusError>;
+
+    /// Persists atomically changes from blocks.
+    fn save_block_batch(&self, blocks: Vec<Block>) -> Result<(), StratusError> {
+        blocks.into_iter().try_for_each(|block| self.save_block(block))
+    }
+
+    /// Persists initial accounts (test accounts or genesis accounts).
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError>;
+
+    /// Persists the gas usage aggregated by the gas analytics, replacing the previous one.
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError>;
 
     #[cfg(feature = "dev")]
     /// Resets all state to a specific block number.
-    fn reset(&self) -> anyhow::Result<()>;
+    fn reset(&self) -> Result<(), StratusError>;
 }
 
 // -----------------------------------------------------------------------------
@@ -99,6 +194,41 @@ pub struct PermanentStorageConfig {
     #[arg(long = "perm-storage-url", env = "PERM_STORAGE_URL", required_if_eq_any([("perm_storage_kind", "redis")]))]
     pub perm_storage_url: Option<String>,
 
+    /// Stream where a notification is appended after each block is saved, so external services can react to new blocks without polling.
+    ///
+    /// Only supported by Redis storage. The notification is written in the same transaction as the block, so it is not lost if subscribers
+    /// are disconnected: they read the stream from the last entry they processed with `XREAD` or consumer groups. The stream is trimmed to
+    /// approximately the last 100 000 notifications.
+    #[arg(long = "perm-storage-notify-stream", env = "PERM_STORAGE_NOTIFY_STREAM")]
+    pub perm_storage_notify_stream: Option<String>,
+
+    /// Decodes ERC-20, ERC-721 and ERC-1155 transfer events when saving blocks, so they can be queried by account or token with `stratus_getTokenTransfers`.
+    #[arg(long = "perm-storage-token-transfers", env = "PERM_STORAGE_TOKEN_TRANSFERS", default_value = "false")]
+    pub perm_storage_token_transfers: bool,
+
+    /// Number of most recent blocks checked at startup to be chained by their parent hashes. Zero disables the check.
+    #[arg(long = "perm-storage-check-blocks", env = "PERM_STORAGE_CHECK_BLOCKS", default_value = "100")]
+    pub perm_storage_check_blocks: u64,
+
+    /// Repairs the mined block number at startup when it does not match the last saved block. If disabled, Stratus refuses to start instead.
+    #[arg(long = "perm-storage-repair-block-number", env = "PERM_STORAGE_REPAIR_BLOCK_NUMBER", default_value = "true")]
+    pub perm_storage_repair_block_number: bool,
+
+    /// Opens the permanent storage without writing to it, rejecting blocks and accounts saves. Used by archive nodes.
+    ///
+    /// RocksDB is opened as a read-only instance that does not see data written after it was opened.
+    #[arg(long = "perm-storage-read-only", env = "PERM_STORAGE_READ_ONLY", default_value = "false")]
+    pub perm_storage_read_only: bool,
+
+    /// Logs a warning with the parameters of permanent storage operations that take longer than this threshold.
+    #[arg(long = "perm-storage-slow-threshold", env = "PERM_STORAGE_SLOW_THRESHOLD", value_parser=parse_duration)]
+    pub perm_storage_slow_threshold: Option<Duration>,
+
+    /// Faults injected in permanent storage methods, in the format `method:kind[@probability]`. Can be changed at runtime with `stratus_setStorageFaults`.
+    #[cfg(feature = "chaos")]
+    #[arg(long = "perm-storage-faults", env = "PERM_STORAGE_FAULTS", value_delimiter = ',')]
+    pub perm_storage_faults: Vec<StorageFault>,
+
     /// RocksDB storage path prefix to execute multiple local Stratus instances.
     #[arg(long = "rocks-path-prefix", env = "ROCKS_PATH_PREFIX")]
     pub rocks_path_prefix: Option<String>,
@@ -116,15 +246,19 @@ pub struct PermanentStorageConfig {
     pub rocks_disable_sync_write: bool,
 }
 
-#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[derive(DebugAsJson, Clone, Copy, strum::Display, serde::Serialize)]
 pub enum PermanentStorageKind {
     #[serde(rename = "inmemory")]
+    #[strum(to_string = "inmemory")]
     InMemory,
 
     #[serde(rename = "redis")]
+    #[strum(to_string = "redis")]
     Redis,
 
+    #[cfg(feature = "rocks")]
     #[serde(rename = "rocks")]
+    #[strum(to_string = "rocks")]
     Rocks,
 }
 
@@ -133,27 +267,109 @@ impl PermanentStorageConfig {
     pub fn init(&self) -> anyhow::Result<Box<dyn PermanentStorage>> {
         tracing::info!(config = ?self, "creating permanent storage");
 
-        let perm: Box<dyn PermanentStorage> = match self.perm_storage_kind {
-            PermanentStorageKind::InMemory => Box::<InMemoryPermanentStorage>::default(),
+        if self.perm_storage_notify_stream.is_some() && not(matches!(self.perm_storage_kind, PermanentStorageKind::Redis)) {
+            return log_and_err!("block notifications are only supported by redis permanent storage");
+        }
+
+        let mut perm: Box<dyn PermanentStorage> = match self.perm_storage_kind {
+            PermanentStorageKind::InMemory => Box::new(InMemoryPermanentStorage::new(self.perm_storage_token_transfers)),
 
             PermanentStorageKind::Redis => {
                 let Some(url) = self.perm_storage_url.as_deref() else {
                     return log_and_err!("redis connection url not provided when it was expected to be present");
                 };
-                Box::new(RedisPermanentStorage::new(url)?)
+                Box::new(RedisPermanentStorage::new(
+                    url,
+                    self.perm_storage_notify_stream.clone(),
+                    self.perm_storage_token_transfers,
+                )?)
             }
 
+            #[cfg(feature = "rocks")]
             PermanentStorageKind::Rocks => Box::new(RocksPermanentStorage::new(
                 self.rocks_path_prefix.clone(),
                 self.rocks_shutdown_timeout,
                 self.rocks_cache_size_multiplier,
                 !self.rocks_disable_sync_write,
+                self.perm_storage_token_transfers,
+                self.perm_storage_read_only,
             )?),
         };
+
+        #[cfg(feature = "chaos")]
+        {
+            perm = Box::new(FaultInjectionPermanentStorage::new(perm, self.perm_storage_faults.clone()));
+        }
+
+        if let Some(threshold) = self.perm_storage_slow_threshold {
+            perm = Box::new(SlowLogPermanentStorage::new(perm, threshold));
+        }
+
+        if self.perm_storage_read_only {
+            perm = Box::new(ReadOnlyPermanentStorage::new(perm));
+        }
+
+        // read-only storage cannot be repaired, so a mismatched block number fails the check
+        let repair_block_number = self.perm_storage_repair_block_number && not(self.perm_storage_read_only);
+        check_integrity(&*perm, self.perm_storage_check_blocks, repair_block_number)?;
         Ok(perm)
     }
 }
 
+/// Checks that the mined block number matches the last saved block and that the last saved blocks are chained by their parent hashes.
+///
+/// Saved blocks are the source of truth, so a mismatched block number can be repaired. A broken chain cannot, and must be investigated.
+pub fn check_integrity(storage: &dyn PermanentStorage, check_blocks: u64, repair_block_number: bool) -> anyhow::Result<()> {
+    let mined_number = storage.read_mined_block_number()?;
+    let Some(last_block) = storage.read_block(BlockFilter::Latest)? else {
+        if mined_number.is_zero() {
+            return Ok(());
+        }
+        return repair_mined_block_number(storage, mined_number, BlockNumber::ZERO, repair_block_number);
+    };
+
+    // mined block number
+    let last_number = last_block.number();
+    if mined_number != last_number {
+        repair_mined_block_number(storage, mined_number, last_number, repair_block_number)?;
+    }
+
+    // parent hashes, starting from the last block until the first saved one
+    let first_number = match storage.read_block(BlockFilter::Earliest)? {
+        Some(first_block) => first_block.number().max(last_number.as_u64().saturating_sub(check_blocks).into()),
+        None => last_number,
+    };
+    let mut child = last_block;
+    for parent_number in (first_number.as_u64()..last_number.as_u64()).rev() {
+        let Some(parent) = storage.read_block(BlockFilter::Number(parent_number.into()))? else {
+            return log_and_err!(payload = parent_number, "storage integrity check failed because a block is missing");
+        };
+        if child.header.parent_hash != parent.hash() {
+            return log_and_err!(
+                payload = (child.number(), child.header.parent_hash, parent.hash()),
+                "storage integrity check failed because the parent hash of a block does not match the hash of the previous block"
+            );
+        }
+        child = parent;
+    }
+
+    tracing::info!(%mined_number, %first_number, %last_number, "storage integrity check succeeded");
+    Ok(())
+}
+
+/// Sets the mined block number to the last saved block, or fails if repairing is disabled.
+fn repair_mined_block_number(storage: &dyn PermanentStorage, mined_number: BlockNumber, last_number: BlockNumber, repair: bool) -> anyhow::Result<()> {
+    if not(repair) {
+        return log_and_err!(
+            payload = (mined_number, last_number),
+            "storage integrity check failed because the mined block number does not match the last saved block, enable block number repair to fix it"
+        );
+    }
+    tracing::warn!(%mined_number, %last_number, "repairing mined block number that does not match the last saved block");
+    storage.set_mined_block_number(last_number)?;
+    Ok(())
+}
+
 impl FromStr for PermanentStorageKind {
     type Err = anyhow::Error;
 
@@ -161,8 +377,730 @@ impl FromStr for PermanentStorageKind {
         match s {
             "inmemory" => Ok(Self::InMemory),
             "redis" => Ok(Self::Redis),
+            #[cfg(feature = "rocks")]
             "rocks" => Ok(Self::Rocks),
             s => Err(anyhow!("unknown permanent storage: {}", s)),
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+
+    use ethereum_types::H256;
+    use ethers_core::utils::keccak256;
+    use fake::Fake;
+    use fake::Faker;
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::ExecutionAccountChanges;
+    use crate::eth::primitives::ExecutionChanges;
+    use crate::eth::primitives::ExecutionValueChange;
+    use crate::eth::primitives::InternalTransfer;
+    use crate::eth::primitives::InternalTransferKind;
+    use crate::eth::primitives::Log;
+    use crate::eth::primitives::LogTopic;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+
+    /// Creates a block with a single transaction that changes the specified slots of an account.
+    fn block_with_slots(number: u64, address: Address, slots: &[Slot]) -> Block {
+        let mut block = Block::new(number.into(), UnixTime::from(number));
+        if slots.is_empty() {
+            return block;
+        }
+
+        let mut tx: TransactionMined = Faker.fake();
+        tx.block_number = block.number();
+        tx.block_hash = block.hash();
+        tx.logs.clear();
+        tx.execution.changes = HashMap::from([(
+            address,
+            ExecutionAccountChanges {
+                new_account: false,
+                address,
+                nonce: ExecutionValueChange::from_original(Faker.fake()),
+                balance: ExecutionValueChange::from_original(Faker.fake()),
+                bytecode: ExecutionValueChange::from_original(None),
+                code_hash: Faker.fake(),
+                slots: slots.iter().map(|slot| (slot.index, ExecutionValueChange::from_modified(*slot))).collect(),
+            },
+        )]);
+        block.transactions.push(tx);
+        block
+    }
+
+    /// Saves blocks that change a slot at blocks 2 and 5 and checks slots are read the same way by all storages.
+    fn check_read_slot_at_past_blocks(storage: &dyn PermanentStorage) {
+        let address: Address = Faker.fake();
+        let index: SlotIndex = Faker.fake();
+        let missing_index: SlotIndex = Faker.fake();
+        let values: [SlotValue; 2] = [Faker.fake(), Faker.fake()];
+
+        for number in 0..=6u64 {
+            let slots = match number {
+                2 => vec![Slot::new(index, values[0])],
+                5 => vec![Slot::new(index, values[1])],
+                _ => vec![],
+            };
+            storage.save_block(block_with_slots(number, address, &slots)).unwrap();
+            storage.set_mined_block_number(number.into()).unwrap();
+        }
+
+        let read_at = |index: SlotIndex, point_in_time: PointInTime| storage.read_slot(address, index, point_in_time).unwrap();
+
+        // slots without value are missing, whatever the point-in-time
+        for point_in_time in [PointInTime::MinedPast(1u64.into()), PointInTime::MinedPast(5u64.into()), PointInTime::Mined] {
+            assert_eq!(read_at(missing_index, point_in_time), None);
+        }
+        assert_eq!(read_at(index, PointInTime::MinedPast(1u64.into())), None);
+
+        // changes are visible from the block where they happened, until the next change
+        for (number, value) in [(2u64, values[0]), (4, values[0]), (5, values[1]), (6, values[1])] {
+            let slot = read_at(index, PointInTime::MinedPast(number.into()));
+            assert_eq!(slot, Some(Slot::new(index, value)), "slot at block {}", number);
+        }
+        assert_eq!(read_at(index, PointInTime::Mined), Some(Slot::new(index, values[1])));
+    }
+
+    /// Saves slots of two accounts and checks paginating over the slots of one account returns each of its slots once.
+    fn check_read_slots_range(storage: &dyn PermanentStorage) {
+        let address: Address = Faker.fake();
+        let other_address: Address = Faker.fake();
+        let slots: Vec<Slot> = (0..25).map(|_| Faker.fake::<Slot>()).unique_by(|slot| slot.index).collect();
+        let other_slots: Vec<Slot> = (0..5).map(|_| Faker.fake()).collect();
+
+        storage.save_block(block_with_slots(1, address, &slots)).unwrap();
+        storage.save_block(block_with_slots(2, other_address, &other_slots)).unwrap();
+
+        // paginate like the rpc does, reading one extra slot to find the next page
+        let limit = 10;
+        let mut read = Vec::new();
+        let mut start = Some(SlotIndex::ZERO);
+        while let Some(next_start) = start {
+            let mut page = storage.read_slots_range(address, next_start, limit + 1).unwrap();
+            start = if page.len() > limit { page.pop().map(|slot| slot.index) } else { None };
+            read.extend(page);
+        }
+
+        assert_eq!(read.len(), slots.len());
+        for slot in &slots {
+            assert!(read.contains(slot), "slot {} not read", slot.index);
+        }
+        assert!(storage.read_slots_range(Faker.fake(), SlotIndex::ZERO, limit).unwrap().is_empty());
+    }
+
+    /// Saves slots of two accounts and checks they are counted per account.
+    fn check_read_state_count(storage: &dyn PermanentStorage) {
+        let address: Address = Faker.fake();
+        let other_address: Address = Faker.fake();
+        let slots: Vec<Slot> = (0..25).map(|_| Faker.fake::<Slot>()).unique_by(|slot| slot.index).collect();
+        let other_slots: Vec<Slot> = (0..5).map(|_| Faker.fake::<Slot>()).unique_by(|slot| slot.index).collect();
+
+        storage.save_block(block_with_slots(1, address, &slots)).unwrap();
+        storage.save_block(block_with_slots(2, other_address, &other_slots)).unwrap();
+
+        let count = storage.read_state_count().unwrap();
+        assert_eq!(count.contracts[&address].slots, slots.len() as u64);
+        assert_eq!(count.contracts[&other_address].slots, other_slots.len() as u64);
+    }
+
+    /// Saves blocks that change the balance of an account and checks the journal is paginated by block.
+    fn check_read_balance_changes(storage: &dyn PermanentStorage) {
+        let address: Address = Faker.fake();
+
+        // balance changes twice in blocks 2 and 4
+        let mut balance = Wei::ZERO;
+        for number in 1..=5u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            if number % 2 == 0 {
+                for _ in 0..2 {
+                    let mut tx: TransactionMined = Faker.fake();
+                    tx.block_number = block.number();
+                    tx.block_hash = block.hash();
+                    tx.transaction_index = (block.transactions.len() as u64).into();
+                    tx.logs.clear();
+
+                    let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, balance));
+                    balance = balance + Wei::ONE;
+                    changes.balance.set_modified(balance);
+                    tx.execution.changes = HashMap::from([(address, changes)]);
+                    block.transactions.push(tx);
+                }
+            }
+            storage.save_block(block).unwrap();
+        }
+
+        let read = |from_block: u64, limit: usize| storage.read_balance_changes(address, from_block.into(), limit).unwrap();
+
+        let all = read(0, 10);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all.iter().map(|change| change.balance).collect_vec(), (1..=4u64).map(Wei::from).collect_vec());
+        assert!(all.iter().all(|change| change.delta() == "1"));
+
+        // limit is applied to blocks, not to changes
+        let first_block = read(0, 1);
+        assert_eq!(first_block.len(), 2);
+        assert!(first_block.iter().all(|change| change.block_number == 2u64.into()));
+        assert_eq!(read(3, 10).len(), 2);
+        assert!(read(5, 10).is_empty());
+    }
+
+    /// Saves blocks with internal transfers between three accounts and checks they are indexed by block and by account.
+    fn check_read_internal_transfers(storage: &dyn PermanentStorage) {
+        let (a, b, c): (Address, Address, Address) = (Faker.fake(), Faker.fake(), Faker.fake());
+        let transfer = |kind: InternalTransferKind, from: Address, to: Address| InternalTransfer {
+            kind,
+            from,
+            to,
+            value: Wei::ONE,
+            depth: 1,
+        };
+
+        // block 2 has a -> b -> c, block 3 has c -> a
+        for number in 1..=3u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            let internal_transfers = match number {
+                2 => vec![transfer(InternalTransferKind::Call, a, b), transfer(InternalTransferKind::Call, b, c)],
+                3 => vec![transfer(InternalTransferKind::Selfdestruct, c, a)],
+                _ => vec![],
+            };
+            let mut tx: TransactionMined = Faker.fake();
+            tx.block_number = block.number();
+            tx.block_hash = block.hash();
+            tx.transaction_index = 0u64.into();
+            tx.logs.clear();
+            tx.execution.changes.clear();
+            tx.execution.internal_transfers = internal_transfers;
+            block.transactions.push(tx);
+            storage.save_block(block).unwrap();
+        }
+
+        // by block
+        assert!(storage.read_internal_transfers_by_block(1u64.into()).unwrap().is_empty());
+        let block_transfers = storage.read_internal_transfers_by_block(2u64.into()).unwrap();
+        assert_eq!(block_transfers.len(), 2);
+        assert_eq!((block_transfers[0].transfer.from, block_transfers[0].transfer.to), (a, b));
+        assert_eq!((block_transfers[1].transfer.from, block_transfers[1].transfer.to), (b, c));
+        assert!(block_transfers.iter().all(|transfer| transfer.block_number == 2u64.into()));
+
+        // by address
+        let read = |address: Address, from_block: u64, limit: usize| storage.read_internal_transfers_by_address(address, from_block.into(), limit).unwrap();
+        assert_eq!(read(a, 0, 10).len(), 2);
+        assert_eq!(read(b, 0, 10).len(), 2);
+        assert_eq!(read(a, 0, 1).len(), 1);
+        assert_eq!(read(a, 3, 10)[0].transfer.kind, InternalTransferKind::Selfdestruct);
+        assert!(read(b, 3, 10).is_empty());
+        assert!(read(Faker.fake(), 0, 10).is_empty());
+    }
+
+    /// Saves a block that changes slots of an account and checks the state diff of each block.
+    fn check_read_state_diff(storage: &dyn PermanentStorage) {
+        let address: Address = Faker.fake();
+        let slots = [Slot::new(1u64.into(), 10u64.into()), Slot::new(2u64.into(), 20u64.into())];
+        storage.save_block(block_with_slots(1, address, &[])).unwrap();
+        storage.save_block(block_with_slots(2, address, &slots)).unwrap();
+
+        assert_eq!(storage.read_state_diff(1u64.into()).unwrap(), Some(vec![]));
+        assert!(storage.read_state_diff(3u64.into()).unwrap().is_none());
+
+        let state_diff = storage.read_state_diff(2u64.into()).unwrap().unwrap();
+        let [changes] = state_diff.as_slice() else {
+            panic!("expected changes of a single account, found {}", state_diff.len());
+        };
+        assert_eq!(changes.address, address);
+        assert!(not(changes.is_account_modified()));
+        for slot in slots {
+            assert_eq!(changes.slots[&slot.index].take_modified_ref(), Some(&slot));
+        }
+    }
+
+    fn check_read_token_transfers(storage: &dyn PermanentStorage) {
+        let (token, a, b): (Address, Address, Address) = (Faker.fake(), Faker.fake(), Faker.fake());
+        let transfer_topic = LogTopic::new(H256::from(keccak256("Transfer(address,address,uint256)")));
+        let transfer_log = |from: Address, to: Address, log_index: u64, block: &Block| LogMined {
+            log: Log {
+                address: token,
+                topic0: Some(transfer_topic),
+                topic1: Some(from.into()),
+                topic2: Some(to.into()),
+                topic3: None,
+                data: Bytes::from(H256::from_low_u64_be(100).as_bytes().to_vec()),
+            },
+            transaction_hash: Faker.fake(),
+            transaction_index: 0u64.into(),
+            log_index: log_index.into(),
+            block_number: block.number(),
+            block_hash: block.hash(),
+        };
+
+        // block 2 has a -> b, block 3 has b -> a and an unrelated log
+        for number in 1..=3u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            let mut tx: TransactionMined = Faker.fake();
+            tx.block_number = block.number();
+            tx.block_hash = block.hash();
+            tx.transaction_index = 0u64.into();
+            tx.execution.changes.clear();
+            tx.logs = match number {
+                2 => vec![transfer_log(a, b, 0, &block)],
+                3 => {
+                    let mut unrelated = transfer_log(b, a, 1, &block);
+                    unrelated.log.topic0 = Some(Faker.fake());
+                    vec![transfer_log(b, a, 0, &block), unrelated]
+                }
+                _ => vec![],
+            };
+            block.transactions.push(tx);
+            storage.save_block(block).unwrap();
+        }
+
+        // by account
+        let read = |address: Address, from_block: u64, limit: usize| storage.read_token_transfers_by_account(address, from_block.into(), limit).unwrap();
+        assert_eq!(read(a, 0, 10).len(), 2);
+        assert_eq!(read(b, 0, 10).len(), 2);
+        assert_eq!(read(a, 0, 1).len(), 1);
+        assert_eq!(read(a, 0, 1)[0].block_number, 2u64.into());
+        assert_eq!((read(a, 3, 10)[0].transfer.from, read(a, 3, 10)[0].transfer.to), (b, a));
+        assert!(read(Faker.fake(), 0, 10).is_empty());
+
+        // by token
+        let token_transfers = storage.read_token_transfers_by_token(token, 0u64.into(), 10).unwrap();
+        assert_eq!(token_transfers.len(), 2);
+        assert!(token_transfers.iter().all(|transfer| transfer.transfer.value == 100u64.into()));
+        assert_eq!(storage.read_token_transfers_by_token(token, 3u64.into(), 10).unwrap().len(), 1);
+        assert!(storage.read_token_transfers_by_token(a, 0u64.into(), 10).unwrap().is_empty());
+    }
+
+    fn check_read_contract_creation(storage: &dyn PermanentStorage) {
+        let (contract, creator): (Address, Address) = (Faker.fake(), Faker.fake());
+
+        // block 1 has a regular transaction, block 2 deploys the contract
+        for number in 1..=2u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            let mut tx: TransactionMined = Faker.fake();
+            tx.block_number = block.number();
+            tx.block_hash = block.hash();
+            tx.transaction_index = 0u64.into();
+            tx.input.signer = creator;
+            tx.logs.clear();
+            tx.execution.changes.clear();
+            tx.execution.deployed_contract_address = (number == 2).then_some(contract);
+            block.transactions.push(tx);
+            storage.save_block(block).unwrap();
+        }
+
+        let creation = storage.read_contract_creation(contract).unwrap().unwrap();
+        assert_eq!((creation.address, creation.creator), (contract, creator));
+        assert_eq!(creation.block_number, 2u64.into());
+        let deploy_tx = storage.read_transaction(creation.transaction_hash).unwrap().unwrap();
+        assert_eq!(deploy_tx.execution.deployed_contract_address, Some(contract));
+        assert!(storage.read_contract_creation(creator).unwrap().is_none());
+    }
+
+    fn check_read_finality_tags(storage: &dyn PermanentStorage) {
+        for number in 1..=3u64 {
+            storage.save_block(Block::new(number.into(), UnixTime::from(number))).unwrap();
+        }
+        let read_number = |filter| storage.read_block(filter).unwrap().map(|block| block.number());
+
+        // the safe block follows the mined block number instead of the last saved block
+        storage.set_mined_block_number(2u64.into()).unwrap();
+        assert_eq!(read_number(BlockFilter::Safe), Some(2u64.into()));
+        assert_eq!(read_number(BlockFilter::Latest), Some(3u64.into()));
+
+        // the finalized block is tracked apart from both
+        assert_eq!(storage.read_finalized_block_number().unwrap(), None);
+        assert_eq!(read_number(BlockFilter::Finalized), None);
+        storage.set_finalized_block_number(1u64.into()).unwrap();
+        assert_eq!(storage.read_finalized_block_number().unwrap(), Some(1u64.into()));
+        assert_eq!(read_number(BlockFilter::Finalized), Some(1u64.into()));
+    }
+
+    /// Saves blocks with a mismatched mined block number and checks it is repaired only when enabled.
+    fn check_integrity_block_number(storage: &dyn PermanentStorage) {
+        for number in 0..=3u64 {
+            storage.save_block(Block::new(number.into(), UnixTime::from(number))).unwrap();
+        }
+        storage.set_mined_block_number(1u64.into()).unwrap();
+
+        assert!(check_integrity(storage, 10, false).is_err());
+        assert_eq!(storage.read_mined_block_number().unwrap(), 1u64.into());
+
+        check_integrity(storage, 10, true).unwrap();
+        assert_eq!(storage.read_mined_block_number().unwrap(), 3u64.into());
+    }
+
+    /// Saves blocks with a wrong parent hash in block 2 and checks it is detected only when the block is checked.
+    fn check_integrity_parent_hash(storage: &dyn PermanentStorage) {
+        for number in 0..=5u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            if number == 2 {
+                block.header.parent_hash = Faker.fake();
+            }
+            storage.save_block(block).unwrap();
+            storage.set_mined_block_number(number.into()).unwrap();
+        }
+
+        check_integrity(storage, 3, false).unwrap();
+        assert!(check_integrity(storage, 4, false).is_err());
+        assert!(check_integrity(storage, 100, false).is_err());
+    }
+
+    // -------------------------------------------------------------------------
+    // Conformance
+    // -------------------------------------------------------------------------
+
+    /// Number of blocks generated for each conformance run.
+    const CONFORMANCE_BLOCKS: u64 = 20;
+
+    /// Number of conformance runs, each one with a different seed.
+    const CONFORMANCE_RUNS: usize = 5;
+
+    /// Generates blocks with transactions changing a small set of accounts and slots, so the same values change many times across blocks.
+    ///
+    /// Transactions also emit token transfers of the first account, send internal transfers between the accounts and deploy new contracts.
+    fn conformance_blocks(rng: &mut SmallRng, accounts: &[Address], indexes: &[SlotIndex]) -> Vec<Block> {
+        let token = accounts[0];
+        let transfer_topic = LogTopic::new(H256::from(keccak256("Transfer(address,address,uint256)")));
+        let mut current_accounts: HashMap<Address, Account> = HashMap::new();
+
+        let mut blocks = Vec::new();
+        for number in 0..CONFORMANCE_BLOCKS {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            let mut log_index = 0u64;
+            let mut cumulative_gas_used = 0u64;
+
+            for tx_index in 0..rng.gen_range(0..4u64) {
+                let mut tx: TransactionMined = Faker.fake_with_rng(rng);
+                tx.block_number = block.number();
+                tx.block_hash = block.hash();
+                tx.transaction_index = tx_index.into();
+                cumulative_gas_used = cumulative_gas_used.saturating_add(tx.execution.gas.as_u64());
+                tx.cumulative_gas_used = cumulative_gas_used.into();
+                tx.input.signer = *accounts.choose(rng).unwrap();
+
+                // account and slot changes, starting from the current values of the account
+                let mut changes = ExecutionChanges::default();
+                for &address in accounts.choose_multiple(rng, 2) {
+                    let account = current_accounts.entry(address).or_insert_with(|| Account::new_empty(address));
+                    let mut account_changes = ExecutionAccountChanges::from_original_values(account.clone());
+                    if rng.gen_bool(0.5) {
+                        account.nonce = account.nonce.next_nonce();
+                        account_changes.nonce.set_modified(account.nonce);
+                    }
+                    if rng.gen_bool(0.5) {
+                        account.balance = Faker.fake_with_rng(rng);
+                        account_changes.balance.set_modified(account.balance);
+                    }
+                    if rng.gen_bool(0.1) {
+                        account.bytecode = Some(Faker.fake_with_rng(rng));
+                        account_changes.bytecode.set_modified(account.bytecode.clone());
+                    }
+                    let slots_count = rng.gen_range(0..=3);
+                    for &index in indexes.choose_multiple(rng, slots_count) {
+                        let slot = Slot::new(index, Faker.fake_with_rng(rng));
+                        account_changes.slots.insert(index, ExecutionValueChange::from_modified(slot));
+                    }
+                    changes.insert(address, account_changes);
+                }
+                tx.execution.changes = changes;
+
+                // internal transfers between the accounts
+                let internal_transfers_count = rng.gen_range(0..3);
+                tx.execution.internal_transfers = (0..internal_transfers_count)
+                    .map(|_| InternalTransfer {
+                        kind: Faker.fake_with_rng(rng),
+                        from: *accounts.choose(rng).unwrap(),
+                        to: *accounts.choose(rng).unwrap(),
+                        value: Faker.fake_with_rng(rng),
+                        depth: rng.gen_range(0..4),
+                    })
+                    .collect();
+
+                // logs, half of them token transfers between the accounts
+                let logs_count = rng.gen_range(0..3);
+                tx.logs = (0..logs_count)
+                    .map(|_| {
+                        let log = if rng.gen_bool(0.5) {
+                            Log {
+                                address: token,
+                                topic0: Some(transfer_topic),
+                                topic1: Some((*accounts.choose(rng).unwrap()).into()),
+                                topic2: Some((*accounts.choose(rng).unwrap()).into()),
+                                topic3: None,
+                                data: Bytes::from(H256::from_low_u64_be(rng.gen()).as_bytes().to_vec()),
+                            }
+                        } else {
+                            Faker.fake_with_rng(rng)
+                        };
+                        log_index += 1;
+                        LogMined {
+                            log,
+                            transaction_hash: tx.input.hash,
+                            transaction_index: tx.transaction_index,
+                            log_index: (log_index - 1).into(),
+                            block_number: block.number(),
+                            block_hash: block.hash(),
+                        }
+                    })
+                    .collect();
+                tx.execution.logs = tx.logs.iter().map(|log| log.log.clone()).collect();
+
+                // contract deployments
+                tx.execution.deployed_contract_address = rng.gen_bool(0.1).then(|| Faker.fake_with_rng(rng));
+
+                block.transactions.push(tx);
+            }
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    /// Transaction without the data that storages index separately instead of keeping with the transaction.
+    fn persisted_transaction(mut tx: TransactionMined) -> TransactionMined {
+        tx.execution.changes.clear();
+        tx.execution.internal_transfers.clear();
+        tx
+    }
+
+    /// Block without the data that storages index separately instead of keeping with its transactions.
+    fn persisted_block(mut block: Block) -> Block {
+        block.transactions = block.transactions.into_iter().map(persisted_transaction).collect();
+        block
+    }
+
+    /// Account values kept by all storages.
+    ///
+    /// Accounts that only had slots changed may be missing or empty, and the code hash is not tracked the same way by all storages.
+    fn persisted_account(address: Address, account: Option<Account>) -> (Nonce, Wei, Option<Bytes>) {
+        let account = account.unwrap_or_else(|| Account::new_empty(address));
+        (account.nonce, account.balance, account.bytecode)
+    }
+
+    /// Reference storage and storage checked against it, both with the same saved blocks.
+    struct Conformance<'a> {
+        reference: &'a dyn PermanentStorage,
+        storage: &'a dyn PermanentStorage,
+        seed: u64,
+    }
+
+    impl Conformance<'_> {
+        /// Asserts a read from the checked storage returns the same as the read from the reference storage.
+        fn assert<T: PartialEq + Debug>(&self, operation: &str, read: impl Fn(&dyn PermanentStorage) -> T) {
+            assert_eq!(
+                read(self.storage),
+                read(self.reference),
+                "{} diverged from reference storage (seed {})",
+                operation,
+                self.seed
+            );
+        }
+    }
+
+    /// Saves the same generated blocks to a reference storage and to the checked storage and asserts every read returns the same values.
+    ///
+    /// Permanent storages must conform to [`InMemoryPermanentStorage`] with token transfers indexed. The seed is included in failures so they can be
+    /// reproduced.
+    fn check_conformance(reference: &dyn PermanentStorage, storage: &dyn PermanentStorage, seed: u64) {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let accounts: Vec<Address> = (0..5).map(|_| Faker.fake_with_rng(&mut rng)).collect();
+        let indexes: Vec<SlotIndex> = (0..5).map(|_| Faker.fake_with_rng(&mut rng)).collect();
+        let blocks = conformance_blocks(&mut rng, &accounts, &indexes);
+
+        // save blocks
+        let block_hashes = blocks.iter().map(|block| block.hash()).collect_vec();
+        let tx_hashes = blocks.iter().flat_map(|block| &block.transactions).map(|tx| tx.input.hash).collect_vec();
+        let contracts = blocks
+            .iter()
+            .flat_map(|block| block.contract_creations())
+            .map(|creation| creation.address)
+            .collect_vec();
+        for block in blocks {
+            for storage in [reference, storage] {
+                storage.save_block(block.clone()).unwrap();
+                storage.set_mined_block_number(block.number()).unwrap();
+            }
+        }
+        let check = Conformance { reference, storage, seed };
+
+        // block number
+        check.assert("read_mined_block_number", |s| s.read_mined_block_number().unwrap());
+
+        // blocks and transactions
+        let read_block = |s: &dyn PermanentStorage, filter: BlockFilter| s.read_block(filter).unwrap().map(persisted_block);
+        for number in 0..=CONFORMANCE_BLOCKS {
+            check.assert("read_block by number", |s| read_block(s, BlockFilter::Number(number.into())));
+        }
+        for &hash in block_hashes.iter().chain([&Faker.fake()]) {
+            check.assert("read_block by hash", |s| read_block(s, BlockFilter::Hash(hash)));
+        }
+        check.assert("read_block latest", |s| read_block(s, BlockFilter::Latest));
+        check.assert("read_block earliest", |s| read_block(s, BlockFilter::Earliest));
+        for &hash in tx_hashes.iter().chain([&Faker.fake()]) {
+            check.assert("read_transaction", |s| s.read_transaction(hash).unwrap().map(persisted_transaction));
+        }
+
+        // logs
+        let middle_block = BlockNumber::from(CONFORMANCE_BLOCKS / 2);
+        for (from_block, to_block, addresses) in [
+            (BlockNumber::ZERO, None, vec![]),
+            (middle_block, None, vec![]),
+            (BlockNumber::ZERO, Some(middle_block), vec![]),
+            (BlockNumber::ZERO, None, vec![accounts[0]]),
+        ] {
+            let filter = LogFilter {
+                from_block,
+                to_block,
+                addresses,
+                ..Default::default()
+            };
+            check.assert("read_logs", |s| s.read_logs(&filter).unwrap());
+        }
+
+        // accounts and slots at all points-in-time
+        let points_in_time = (0..=CONFORMANCE_BLOCKS)
+            .map(|number| PointInTime::MinedPast(number.into()))
+            .chain([PointInTime::Mined])
+            .collect_vec();
+        for &address in accounts.iter().chain([&Faker.fake()]) {
+            for &point_in_time in &points_in_time {
+                check.assert("read_account", |s| persisted_account(address, s.read_account(address, point_in_time).unwrap()));
+                for &index in indexes.iter().chain([&Faker.fake()]) {
+                    check.assert("read_slot", |s| s.read_slot(address, index, point_in_time).unwrap());
+                }
+            }
+
+            // slots are iterated in a storage specific order
+            check.assert("read_slots_range", |s| {
+                let slots = s.read_slots_range(address, SlotIndex::ZERO, usize::MAX).unwrap();
+                slots.into_iter().sorted_by_key(|slot| slot.index).collect_vec()
+            });
+        }
+
+        // journals and indexes
+        for &address in accounts.iter().chain([&Faker.fake()]) {
+            for (from_block, limit) in [(BlockNumber::ZERO, usize::MAX), (BlockNumber::ZERO, 1), (middle_block, 3)] {
+                check.assert("read_balance_changes", |s| s.read_balance_changes(address, from_block, limit).unwrap());
+                check.assert("read_internal_transfers_by_address", |s| {
+                    s.read_internal_transfers_by_address(address, from_block, limit).unwrap()
+                });
+                check.assert("read_token_transfers_by_account", |s| {
+                    s.read_token_transfers_by_account(address, from_block, limit).unwrap()
+                });
+                check.assert("read_token_transfers_by_token", |s| {
+                    s.read_token_transfers_by_token(address, from_block, limit).unwrap()
+                });
+            }
+        }
+        for number in 0..=CONFORMANCE_BLOCKS {
+            check.assert("read_internal_transfers_by_block", |s| {
+                s.read_internal_transfers_by_block(number.into()).unwrap()
+            });
+        }
+        for &address in accounts.iter().chain(&contracts) {
+            check.assert("read_contract_creation", |s| s.read_contract_creation(address).unwrap());
+        }
+        for number in 0..=CONFORMANCE_BLOCKS + 1 {
+            check.assert("read_state_diff", |s| s.read_state_diff(number.into()).unwrap());
+        }
+    }
+
+    #[cfg(feature = "rocks")]
+    fn rocks_in_testdir() -> (RocksPermanentStorage, tempfile::TempDir) {
+        let test_dir = tempfile::tempdir().unwrap();
+        let prefix = format!("{}/perm", test_dir.path().display());
+        (
+            RocksPermanentStorage::new(Some(prefix), Duration::ZERO, None, true, true, false).unwrap(),
+            test_dir,
+        )
+    }
+
+    /// Generates a unit test for each permanent storage that runs the same `check_` function against it.
+    macro_rules! gen_test_storages {
+        ($($check:ident),+ $(,)?) => {
+            paste::paste! {
+                $(
+                    #[test]
+                    fn [<test_inmemory_ $check>]() {
+                        [<check_ $check>](&InMemoryPermanentStorage::new(true));
+                    }
+
+                    #[test]
+                    #[cfg(feature = "rocks")]
+                    fn [<test_rocks_ $check>]() {
+                        let (storage, _test_dir) = rocks_in_testdir();
+                        [<check_ $check>](&storage);
+                    }
+                )+
+            }
+        };
+    }
+
+    gen_test_storages!(
+        read_slot_at_past_blocks,
+        read_slots_range,
+        read_state_count,
+        read_balance_changes,
+        read_internal_transfers,
+        read_state_diff,
+        read_token_transfers,
+        read_contract_creation,
+        read_finality_tags,
+        integrity_block_number,
+        integrity_parent_hash,
+    );
+
+    #[test]
+    #[cfg(feature = "rocks")]
+    fn test_rocks_read_only() {
+        let (storage, test_dir) = rocks_in_testdir();
+        for number in 0..=2u64 {
+            storage.save_block(Block::new(number.into(), UnixTime::from(number))).unwrap();
+            storage.set_mined_block_number(number.into()).unwrap();
+        }
+        drop(storage);
+
+        let prefix = format!("{}/perm", test_dir.path().display());
+        let rocks = RocksPermanentStorage::new(Some(prefix), Duration::ZERO, None, true, true, true).unwrap();
+        let storage = ReadOnlyPermanentStorage::new(Box::new(rocks));
+        check_integrity(&storage, 10, false).unwrap();
+        assert_eq!(storage.read_mined_block_number().unwrap(), 2u64.into());
+        assert!(storage.read_block(BlockFilter::Number(1u64.into())).unwrap().is_some());
+
+        let result = storage.save_block(Block::new(3u64.into(), UnixTime::from(3)));
+        assert!(matches!(result, Err(StratusError::StorageReadOnly { operation: "save_block" })));
+        assert!(storage.set_mined_block_number(3u64.into()).is_err());
+        assert_eq!(storage.read_mined_block_number().unwrap(), 2u64.into());
+    }
+
+    #[test]
+    fn test_slow_log_conformance() {
+        for _ in 0..CONFORMANCE_RUNS {
+            let storage = SlowLogPermanentStorage::new(Box::new(InMemoryPermanentStorage::new(true)), Duration::from_secs(60));
+            check_conformance(&InMemoryPermanentStorage::new(true), &storage, rand::random());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rocks")]
+    fn test_rocks_conformance() {
+        for _ in 0..CONFORMANCE_RUNS {
+            let (storage, _test_dir) = rocks_in_testdir();
+            check_conformance(&InMemoryPermanentStorage::new(true), &storage, rand::random());
+        }
+    }
+}
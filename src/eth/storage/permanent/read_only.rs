@@ -0,0 +1,167 @@
+use super::PermanentStorage;
+use super::PermanentStorageKind;
+use super::ReadStorage;
+use super::WriteStorage;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
+use crate::eth::primitives::TransactionMined;
+
+/// Permanent storage wrapper that serves reads and rejects every write, used by archive nodes.
+pub struct ReadOnlyPermanentStorage {
+    inner: Box<dyn PermanentStorage>,
+}
+
+impl ReadOnlyPermanentStorage {
+    pub fn new(inner: Box<dyn PermanentStorage>) -> Self {
+        tracing::info!(storage = %inner.kind(), "permanent storage is read-only");
+        Self { inner }
+    }
+
+    /// Rejects a write operation.
+    fn reject<T>(operation: &'static str) -> Result<T, StratusError> {
+        tracing::error!(%operation, "rejected write to read-only permanent storage");
+        Err(StratusError::StorageReadOnly { operation })
+    }
+}
+
+impl ReadStorage for ReadOnlyPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        self.inner.kind()
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
+        self.inner.read_mined_block_number()
+    }
+
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        self.inner.read_finalized_block_number()
+    }
+
+    // -------------------------------------------------------------------------
+    // Block
+    // -------------------------------------------------------------------------
+
+    fn read_block(&self, block_filter: BlockFilter) -> Result<Option<Block>, StratusError> {
+        self.inner.read_block(block_filter)
+    }
+
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
+        self.inner.read_transaction(hash)
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
+        self.inner.read_logs(filter)
+    }
+
+    // -------------------------------------------------------------------------
+    // Account and slots
+    // -------------------------------------------------------------------------
+
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
+        self.inner.read_account(address, point_in_time)
+    }
+
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
+        self.inner.read_slot(address, index, point_in_time)
+    }
+
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        self.inner.read_slots_range(address, start, limit)
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        self.inner.read_balance_changes(address, from_block, limit)
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.inner.read_internal_transfers_by_block(number)
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.inner.read_internal_transfers_by_address(address, from_block, limit)
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.inner.token_transfers_indexed()
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.inner.read_token_transfers_by_account(address, from_block, limit)
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.inner.read_token_transfers_by_token(token, from_block, limit)
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        self.inner.read_contract_creation(address)
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        self.inner.read_state_diff(number)
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        self.inner.read_state_count()
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        self.inner.read_gas_usage()
+    }
+}
+
+impl WriteStorage for ReadOnlyPermanentStorage {
+    fn set_mined_block_number(&self, _: BlockNumber) -> Result<(), StratusError> {
+        Self::reject("set_mined_block_number")
+    }
+
+    fn set_finalized_block_number(&self, _: BlockNumber) -> Result<(), StratusError> {
+        Self::reject("set_finalized_block_number")
+    }
+
+    fn save_block(&self, _: Block) -> Result<(), StratusError> {
+        Self::reject("save_block")
+    }
+
+    fn save_block_batch(&self, _: Vec<Block>) -> Result<(), StratusError> {
+        Self::reject("save_block_batch")
+    }
+
+    fn save_accounts(&self, _: Vec<Account>) -> Result<(), StratusError> {
+        Self::reject("save_accounts")
+    }
+
+    fn save_gas_usage(&self, _: GasUsageSnapshot) -> Result<(), StratusError> {
+        Self::reject("save_gas_usage")
+    }
+
+    #[cfg(feature = "dev")]
+    fn reset(&self) -> Result<(), StratusError> {
+        Self::reject("reset")
+    }
+}
@@ -4,19 +4,29 @@ use redis::Commands;
 use redis::Connection as RedisConnection;
 use redis::RedisResult;
 
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
-use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::PermanentStorageKind;
+use crate::eth::storage::ReadStorage;
+use crate::eth::storage::WriteStorage;
 use crate::ext::from_json_str;
 use crate::ext::to_json_object;
 use crate::ext::to_json_string;
@@ -29,41 +39,68 @@ type RedisOptString = RedisResult<Option<String>>;
 type RedisOptUsize = RedisResult<Option<usize>>;
 type RedisVoid = RedisResult<()>;
 
+/// Approximate number of notifications kept in the notify stream.
+const NOTIFY_STREAM_MAX_LEN: usize = 100_000;
+
 pub struct RedisPermanentStorage {
     client: redis::Client,
+
+    /// Stream where saved blocks are announced.
+    notify_stream: Option<String>,
+
+    /// Should decode and index token transfers when saving blocks?
+    index_token_transfers: bool,
 }
 
 impl RedisPermanentStorage {
-    pub fn new(url: &str) -> anyhow::Result<Self> {
+    pub fn new(url: &str, notify_stream: Option<String>, index_token_transfers: bool) -> anyhow::Result<Self> {
         let client = match RedisClient::open(url) {
             Ok(client) => client,
             Err(e) => return log_and_err!(reason = e, "failed to create redis client"),
         };
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            notify_stream,
+            index_token_transfers,
+        })
     }
 
-    fn conn(&self) -> anyhow::Result<RedisConnection> {
+    /// Gets a connection to Redis. Failures are transient, so the operation can be retried when Redis is reachable again.
+    fn conn(&self) -> Result<RedisConnection, StratusError> {
         match self.client.get_connection() {
             Ok(conn) => Ok(conn),
-            Err(e) => log_and_err!(reason = e, "failed to get redis connection"),
+            Err(e) => {
+                tracing::error!(reason = ?e, "failed to get redis connection");
+                Err(StratusError::StorageUnavailable { reason: e.to_string() })
+            }
         }
     }
-}
 
-impl PermanentStorage for RedisPermanentStorage {
-    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
-        // execute command
+    /// Reads token transfers of an index key starting at the specified block, including all transfers of up to `limit` blocks.
+    fn read_token_transfers(&self, key: String, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        // execute
         let mut conn = self.conn()?;
-        let set: RedisVoid = conn.set("number::mined", number.to_string());
+        let mut cmd = redis::cmd("ZRANGE");
+        cmd.arg(key).arg(from_block.as_u64()).arg("+inf").arg("BYSCORE").arg("LIMIT").arg(0).arg(limit);
+        let redis_token_transfers: RedisVecString = cmd.query(&mut conn);
 
         // parse
-        match set {
-            Ok(_) => Ok(()),
-            Err(e) => log_and_err!(reason = e, "failed to write mined number to redis"),
+        match redis_token_transfers {
+            Ok(vec_json) => Ok(vec_json
+                .into_iter()
+                .flat_map(|json| from_json_str::<Vec<TokenTransferMined>>(&json))
+                .collect_vec()),
+            Err(e) => log_and_err!(reason = e, "failed to read token transfers from redis").map_err(Into::into),
         }
     }
+}
+
+impl ReadStorage for RedisPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        PermanentStorageKind::Redis
+    }
 
-    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber> {
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
         // execute command
         let mut conn = self.conn()?;
         let value: RedisOptUsize = conn.get("number::mined");
@@ -72,99 +109,31 @@ impl PermanentStorage for RedisPermanentStorage {
         match value {
             Ok(Some(value)) => Ok(value.into()),
             Ok(None) => Ok(BlockNumber::ZERO),
-            Err(e) => log_and_err!(reason = e, "failed to read miner block number from redis"),
+            Err(e) => log_and_err!(reason = e, "failed to read miner block number from redis").map_err(Into::into),
         }
     }
 
-    fn save_block(&self, block: Block) -> anyhow::Result<()> {
-        // generate block keys
-        let key_block_number = key_block_by_number(block.number());
-        let key_block_hash = key_block_by_hash(block.hash());
-
-        // generate values
-        let block_json = to_json_string(&block);
-
-        // blocks
-        let mut mset_values = vec![
-            (key_block_number, block_json.clone()),
-            (key_block_hash, block_json.clone()),
-            ("block::latest".to_owned(), block_json),
-        ];
-        let mut zadd_values = vec![];
-
-        // transactions
-        for tx in &block.transactions {
-            let tx_key = key_tx(tx.input.hash);
-            let tx_value = to_json_string(&tx);
-            mset_values.push((tx_key, tx_value));
-        }
-
-        // changes
-        for changes in block.compact_account_changes() {
-            // account
-            if changes.is_account_modified() {
-                let mut account = Account {
-                    address: changes.address,
-                    ..Account::default()
-                };
-                if let Some(nonce) = changes.nonce.take() {
-                    account.nonce = nonce;
-                }
-                if let Some(balance) = changes.balance.take() {
-                    account.balance = balance;
-                }
-                if let Some(bytecode) = changes.bytecode.take() {
-                    account.bytecode = bytecode;
-                }
-
-                // add block number to force slot modification
-                let mut account_value = to_json_object(&account);
-                account_value.insert("block".to_owned(), to_json_value(block.number()));
-                let account_value = to_json_string(&account_value);
-
-                mset_values.push((key_account(account.address), account_value.clone()));
-                zadd_values.push((key_account_history(account.address), account_value, block.number().as_u64()));
-            }
-
-            // slots
-            for slot in changes.slots.into_values() {
-                if let Some(slot) = slot.take() {
-                    // add block number to force slot modification
-                    let mut slot_value = to_json_value(slot);
-                    slot_value.as_object_mut().unwrap().insert("block".to_owned(), to_json_value(block.number()));
-                    let slot_value = to_json_string(&slot_value);
-
-                    mset_values.push((key_slot(changes.address, slot.index), slot_value.clone()));
-                    zadd_values.push((key_slot_history(changes.address, slot.index), slot_value, block.number().as_u64()));
-                }
-            }
-        }
-
-        // execute mset command
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        // execute command
         let mut conn = self.conn()?;
-        let set: RedisVoid = conn.mset(&mset_values);
-        if let Err(e) = set {
-            return log_and_err!(reason = e, "failed to write block mset to redis");
-        }
-
-        // execute zadd commands
-        for (key, value, score) in zadd_values {
-            let mut cmd = redis::cmd("ZADD");
-            cmd.arg(key).arg("NX").arg(score).arg(value);
+        let value: RedisOptUsize = conn.get("number::finalized");
 
-            let zadd: RedisVoid = cmd.exec(&mut conn);
-            if let Err(e) = zadd {
-                return log_and_err!(reason = e, "failed to write block zadd to redis");
-            }
+        // parse
+        match value {
+            Ok(value) => Ok(value.map(Into::into)),
+            Err(e) => log_and_err!(reason = e, "failed to read finalized block number from redis").map_err(Into::into),
         }
-
-        Ok(())
     }
 
-    fn read_block(&self, block_filter: BlockFilter) -> anyhow::Result<Option<Block>> {
+    fn read_block(&self, block_filter: BlockFilter) -> Result<Option<Block>, StratusError> {
         // prepare keys
         let block_key = match block_filter {
             BlockFilter::Latest | BlockFilter::Pending => "block::latest".to_owned(),
+            BlockFilter::Safe => key_block_by_number(self.read_mined_block_number()?),
+            BlockFilter::Finalized => match self.read_finalized_block_number()? {
+                Some(number) => key_block_by_number(number),
+                None => return Ok(None),
+            },
             BlockFilter::Earliest => "block::earliest".to_owned(),
             BlockFilter::Hash(hash) => key_block_by_hash(hash),
             BlockFilter::Number(number) => key_block_by_number(number),
@@ -178,11 +147,11 @@ impl PermanentStorage for RedisPermanentStorage {
         match redis_block {
             Ok(Some(json)) => Ok(from_json_str(&json)),
             Ok(None) => Ok(None),
-            Err(e) => log_and_err!(reason = e, "failed to read block from redis"),
+            Err(e) => log_and_err!(reason = e, "failed to read block from redis").map_err(Into::into),
         }
     }
 
-    fn read_transaction(&self, hash: Hash) -> anyhow::Result<Option<TransactionMined>> {
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
         // prepare keys
         let tx_key = key_tx(hash);
 
@@ -194,11 +163,11 @@ impl PermanentStorage for RedisPermanentStorage {
         match redis_transaction {
             Ok(Some(json)) => Ok(from_json_str(&json)),
             Ok(None) => Ok(None),
-            Err(e) => log_and_err!(reason = e, "failed to read transaction from redis"),
+            Err(e) => log_and_err!(reason = e, "failed to read transaction from redis").map_err(Into::into),
         }
     }
 
-    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>> {
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
         // prepare keys
         let from_block = filter.from_block.as_u64();
         let to_block = match filter.to_block {
@@ -219,7 +188,7 @@ impl PermanentStorage for RedisPermanentStorage {
         // parse
         let blocks: Vec<Block> = match blocks {
             Ok(vec_json) => vec_json.into_iter().flatten().map(|json| from_json_str(&json)).collect_vec(),
-            Err(e) => return log_and_err!(reason = e, "failed to read logs from redis"),
+            Err(e) => return log_and_err!(reason = e, "failed to read logs from redis").map_err(Into::into),
         };
 
         // filter
@@ -233,34 +202,7 @@ impl PermanentStorage for RedisPermanentStorage {
         Ok(logs)
     }
 
-    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
-        // exit if no accounts
-        if accounts.is_empty() {
-            return Ok(());
-        }
-
-        // prepare values
-        let redis_accounts = accounts
-            .into_iter()
-            .map(|acc| {
-                let account_key = key_account(acc.address);
-                let account_value = to_json_string(&acc);
-                (account_key, account_value)
-            })
-            .collect_vec();
-
-        // execute command
-        let mut conn = self.conn()?;
-        let set: RedisVoid = conn.mset(&redis_accounts);
-
-        // parse
-        match set {
-            Ok(_) => Ok(()),
-            Err(e) => log_and_err!(reason = e, "failed to write accounts to redis"),
-        }
-    }
-
-    fn read_account(&self, address: Address, point_in_time: PointInTime) -> anyhow::Result<Option<Account>> {
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
         let mut conn = self.conn()?;
         match point_in_time {
             PointInTime::Mined | PointInTime::Pending => {
@@ -274,7 +216,7 @@ impl PermanentStorage for RedisPermanentStorage {
                 match redis_account {
                     Ok(Some(json)) => Ok(Some(from_json_str(&json))),
                     Ok(None) => Ok(None),
-                    Err(e) => log_and_err!(reason = e, "failed to read account from redis current value"),
+                    Err(e) => log_and_err!(reason = e, "failed to read account from redis current value").map_err(Into::into),
                 }
             }
             PointInTime::MinedPast(number) => {
@@ -299,13 +241,13 @@ impl PermanentStorage for RedisPermanentStorage {
                         Some(json) => Ok(Some(from_json_str(json))),
                         None => Ok(None),
                     },
-                    Err(e) => log_and_err!(reason = e, "failed to read account from redis historical value"),
+                    Err(e) => log_and_err!(reason = e, "failed to read account from redis historical value").map_err(Into::into),
                 }
             }
         }
     }
 
-    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> anyhow::Result<Option<Slot>> {
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
         // execute command and parse
         let mut conn = self.conn()?;
         match point_in_time {
@@ -320,7 +262,7 @@ impl PermanentStorage for RedisPermanentStorage {
                 match redis_slot {
                     Ok(Some(json)) => Ok(Some(from_json_str(&json))),
                     Ok(None) => Ok(None),
-                    Err(e) => log_and_err!(reason = e, "failed to read slot from redis current value"),
+                    Err(e) => log_and_err!(reason = e, "failed to read slot from redis current value").map_err(Into::into),
                 }
             }
             PointInTime::MinedPast(number) => {
@@ -345,23 +287,404 @@ impl PermanentStorage for RedisPermanentStorage {
                         Some(json) => Ok(Some(from_json_str(json))),
                         None => Ok(None),
                     },
-                    Err(e) => log_and_err!(reason = e, "failed to read account from redis historical value"),
+                    Err(e) => log_and_err!(reason = e, "failed to read account from redis historical value").map_err(Into::into),
+                }
+            }
+        }
+    }
+
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        // list slot keys of the account
+        // slots are not indexed by account, so all keys are scanned and sorted by index to have a stable order
+        let mut conn = self.conn()?;
+        let keys: RedisResult<Vec<String>> = conn.scan_match::<_, String>(key_slot_pattern(address)).map(|iter| iter.collect());
+        let keys = match keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to scan slot keys from redis").map_err(Into::into),
+        };
+        let slot_keys = keys
+            .into_iter()
+            .filter_map(|key| {
+                let index = SlotIndex::from_hex_str(key.rsplit("::").next()?).ok()?;
+                (index >= start).then_some((index, key))
+            })
+            .sorted_by_key(|(index, _)| *index)
+            .take(limit)
+            .map(|(_, key)| key)
+            .collect_vec();
+
+        // exit if no keys
+        if slot_keys.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // execute command
+        let redis_slots: RedisVecOptString = conn.mget(slot_keys);
+
+        // parse
+        match redis_slots {
+            Ok(vec_json) => Ok(vec_json.into_iter().flatten().map(|json| from_json_str(&json)).collect_vec()),
+            Err(e) => log_and_err!(reason = e, "failed to read slots range from redis").map_err(Into::into),
+        }
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        // prepare key
+        let balance_changes_key = key_balance_changes(address);
+
+        // execute
+        let mut conn = self.conn()?;
+        let mut cmd = redis::cmd("ZRANGE");
+        cmd.arg(balance_changes_key)
+            .arg(from_block.as_u64())
+            .arg("+inf")
+            .arg("BYSCORE")
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit);
+        let redis_balance_changes: RedisVecString = cmd.query(&mut conn);
+
+        // parse
+        match redis_balance_changes {
+            Ok(vec_json) => Ok(vec_json.into_iter().flat_map(|json| from_json_str::<Vec<BalanceChange>>(&json)).collect_vec()),
+            Err(e) => log_and_err!(reason = e, "failed to read balance changes from redis").map_err(Into::into),
+        }
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        let block = self.read_block(BlockFilter::Number(number))?;
+        Ok(block.map(|block| block.internal_transfers()).unwrap_or_default())
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        // prepare key
+        let internal_transfers_key = key_internal_transfers(address);
+
+        // execute
+        let mut conn = self.conn()?;
+        let mut cmd = redis::cmd("ZRANGE");
+        cmd.arg(internal_transfers_key)
+            .arg(from_block.as_u64())
+            .arg("+inf")
+            .arg("BYSCORE")
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit);
+        let redis_internal_transfers: RedisVecString = cmd.query(&mut conn);
+
+        // parse
+        match redis_internal_transfers {
+            Ok(vec_json) => Ok(vec_json
+                .into_iter()
+                .flat_map(|json| from_json_str::<Vec<InternalTransferMined>>(&json))
+                .collect_vec()),
+            Err(e) => log_and_err!(reason = e, "failed to read internal transfers from redis").map_err(Into::into),
+        }
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.index_token_transfers
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.read_token_transfers(key_token_transfers_by_account(address), from_block, limit)
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.read_token_transfers(key_token_transfers_by_token(token), from_block, limit)
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        // prepare keys
+        let contract_creation_key = key_contract_creation(address);
+
+        // execute command
+        let mut conn = self.conn()?;
+        let redis_contract_creation: RedisOptString = conn.get(contract_creation_key);
+
+        // parse
+        match redis_contract_creation {
+            Ok(Some(json)) => Ok(from_json_str(&json)),
+            Ok(None) => Ok(None),
+            Err(e) => log_and_err!(reason = e, "failed to read contract creation from redis").map_err(Into::into),
+        }
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        let block = self.read_block(BlockFilter::Number(number))?;
+        Ok(block.map(|block| block.state_diff()))
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        let mut conn = self.conn()?;
+        let mut count = StateCount::default();
+
+        // accounts
+        let account_keys: RedisResult<Vec<String>> = conn.scan_match::<_, String>(key_account_pattern()).map(|iter| iter.collect());
+        let account_keys = match account_keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to scan account keys from redis").map_err(Into::into),
+        };
+        for chunk in account_keys.chunks(1000) {
+            let redis_accounts: RedisVecOptString = conn.mget(chunk);
+            let redis_accounts = match redis_accounts {
+                Ok(vec_json) => vec_json,
+                Err(e) => return log_and_err!(reason = e, "failed to read accounts from redis").map_err(Into::into),
+            };
+            for json in redis_accounts.into_iter().flatten() {
+                let account: Account = from_json_str(&json);
+                count.count_account(account.address, account.bytecode.map(|bytecode| bytecode.len()).unwrap_or_default());
+            }
+        }
+
+        // slots
+        let slot_keys: RedisResult<Vec<String>> = conn.scan_match::<_, String>(key_slots_pattern()).map(|iter| iter.collect());
+        let slot_keys = match slot_keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to scan slot keys from redis").map_err(Into::into),
+        };
+        for key in slot_keys {
+            let Some(address) = key.split("::").nth(1).and_then(|address| address.parse::<Address>().ok()) else {
+                continue;
+            };
+            count.count_slot(address);
+        }
+
+        Ok(count)
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        // execute command
+        let mut conn = self.conn()?;
+        let redis_gas_usage: RedisOptString = conn.get("analytics::gas_usage");
+
+        // parse
+        match redis_gas_usage {
+            Ok(Some(json)) => Ok(Some(from_json_str(&json))),
+            Ok(None) => Ok(None),
+            Err(e) => log_and_err!(reason = e, "failed to read gas usage from redis").map_err(Into::into),
+        }
+    }
+}
+
+impl WriteStorage for RedisPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set("number::mined", number.to_string());
+
+        // parse
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write mined number to redis").map_err(Into::into),
+        }
+    }
+
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set("number::finalized", number.to_string());
+
+        // parse
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write finalized number to redis").map_err(Into::into),
+        }
+    }
+
+    fn save_block(&self, block: Block) -> Result<(), StratusError> {
+        // generate block keys
+        let key_block_number = key_block_by_number(block.number());
+        let key_block_hash = key_block_by_hash(block.hash());
+
+        // generate values
+        let block_json = to_json_string(&block);
+
+        // blocks
+        let mut mset_values = vec![
+            (key_block_number, block_json.clone()),
+            (key_block_hash, block_json.clone()),
+            ("block::latest".to_owned(), block_json),
+        ];
+        let mut zadd_values = vec![];
+
+        // transactions
+        for tx in &block.transactions {
+            let tx_key = key_tx(tx.input.hash);
+            let tx_value = to_json_string(&tx);
+            mset_values.push((tx_key, tx_value));
+        }
+
+        // contract creations
+        for contract_creation in block.contract_creations() {
+            mset_values.push((key_contract_creation(contract_creation.address), to_json_string(&contract_creation)));
+        }
+
+        // changes
+        for changes in block.compact_account_changes() {
+            // account
+            if changes.is_account_modified() {
+                let mut account = Account {
+                    address: changes.address,
+                    ..Account::default()
+                };
+                if let Some(nonce) = changes.nonce.take() {
+                    account.nonce = nonce;
+                }
+                if let Some(balance) = changes.balance.take() {
+                    account.balance = balance;
+                }
+                if let Some(bytecode) = changes.bytecode.take() {
+                    account.bytecode = bytecode;
+                }
+
+                // add block number to force slot modification
+                let mut account_value = to_json_object(&account);
+                account_value.insert("block".to_owned(), to_json_value(block.number()));
+                let account_value = to_json_string(&account_value);
+
+                mset_values.push((key_account(account.address), account_value.clone()));
+                zadd_values.push((key_account_history(account.address), account_value, block.number().as_u64()));
+            }
+
+            // slots
+            for slot in changes.slots.into_values() {
+                if let Some(slot) = slot.take() {
+                    // add block number to force slot modification
+                    let mut slot_value = to_json_value(slot);
+                    slot_value.as_object_mut().unwrap().insert("block".to_owned(), to_json_value(block.number()));
+                    let slot_value = to_json_string(&slot_value);
+
+                    mset_values.push((key_slot(changes.address, slot.index), slot_value.clone()));
+                    zadd_values.push((key_slot_history(changes.address, slot.index), slot_value, block.number().as_u64()));
                 }
             }
         }
+
+        // balance changes
+        let balance_changes = block.balance_changes().into_iter().into_group_map_by(|balance_change| balance_change.address);
+        for (address, balance_changes) in balance_changes {
+            zadd_values.push((key_balance_changes(address), to_json_string(&balance_changes), block.number().as_u64()));
+        }
+
+        // internal transfers
+        // transfers by block are read from the block itself
+        for (address, internal_transfers) in block.internal_transfers_by_account() {
+            zadd_values.push((key_internal_transfers(address), to_json_string(&internal_transfers), block.number().as_u64()));
+        }
+
+        // token transfers
+        if self.index_token_transfers {
+            for (address, token_transfers) in block.token_transfers_by_account() {
+                zadd_values.push((
+                    key_token_transfers_by_account(address),
+                    to_json_string(&token_transfers),
+                    block.number().as_u64(),
+                ));
+            }
+            for (token, token_transfers) in block.token_transfers().into_iter().into_group_map_by(|transfer| transfer.transfer.token) {
+                zadd_values.push((key_token_transfers_by_token(token), to_json_string(&token_transfers), block.number().as_u64()));
+            }
+        }
+
+        // execute mset command
+        // the notification is written in the same transaction, so it exists if and only if the block was saved
+        let mut conn = self.conn()?;
+        let mut pipe = redis::pipe();
+        pipe.atomic().mset(&mset_values).ignore();
+        if let Some(ref stream) = self.notify_stream {
+            pipe.add_command(cmd_notify_block(stream, &block)).ignore();
+        }
+        let set: RedisVoid = pipe.query(&mut conn);
+        if let Err(e) = set {
+            return log_and_err!(reason = e, "failed to write block mset to redis").map_err(Into::into);
+        }
+
+        // execute zadd commands
+        for (key, value, score) in zadd_values {
+            let mut cmd = redis::cmd("ZADD");
+            cmd.arg(key).arg("NX").arg(score).arg(value);
+
+            let zadd: RedisVoid = cmd.exec(&mut conn);
+            if let Err(e) = zadd {
+                return log_and_err!(reason = e, "failed to write block zadd to redis").map_err(Into::into);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
+        // exit if no accounts
+        if accounts.is_empty() {
+            return Ok(());
+        }
+
+        // prepare values
+        let redis_accounts = accounts
+            .into_iter()
+            .map(|acc| {
+                let account_key = key_account(acc.address);
+                let account_value = to_json_string(&acc);
+                (account_key, account_value)
+            })
+            .collect_vec();
+
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.mset(&redis_accounts);
+
+        // parse
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write accounts to redis").map_err(Into::into),
+        }
+    }
+
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set("analytics::gas_usage", to_json_string(&usage));
+
+        // parse
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write gas usage to redis").map_err(Into::into),
+        }
     }
 
     #[cfg(feature = "dev")]
-    fn reset(&self) -> anyhow::Result<()> {
+    fn reset(&self) -> Result<(), StratusError> {
         let mut conn = self.conn()?;
         let flush: RedisVoid = redis::cmd("FLUSHDB").exec(&mut conn);
         match flush {
             Ok(_) => Ok(()),
-            Err(e) => log_and_err!(reason = e, "failed to clear all redis keys"),
+            Err(e) => log_and_err!(reason = e, "failed to clear all redis keys").map_err(Into::into),
         }
     }
 }
 
+// -----------------------------------------------------------------------------
+// Commands helpers
+// -----------------------------------------------------------------------------
+
+/// Generates the command that appends the notification of a saved block to the notify stream.
+fn cmd_notify_block(stream: &str, block: &Block) -> redis::Cmd {
+    let mut cmd = redis::cmd("XADD");
+    cmd.arg(stream)
+        .arg("MAXLEN")
+        .arg("~")
+        .arg(NOTIFY_STREAM_MAX_LEN)
+        .arg("*")
+        .arg("number")
+        .arg(block.number().as_u64())
+        .arg("hash")
+        .arg(block.hash().to_string())
+        .arg("transactions_len")
+        .arg(block.transactions.len());
+    cmd
+}
+
 // -----------------------------------------------------------------------------
 // Keys helpers
 // -----------------------------------------------------------------------------
@@ -381,6 +704,11 @@ fn key_account(address: Address) -> String {
     format!("account::{}", address)
 }
 
+/// Generates a pattern for scanning all accounts.
+fn key_account_pattern() -> String {
+    "account::*".to_owned()
+}
+
 /// Generates a key for accessing an account history.
 fn key_account_history(address: Address) -> String {
     format!("account_history::{}", address)
@@ -391,12 +719,88 @@ fn key_slot(address: Address, index: SlotIndex) -> String {
     format!("slot::{}::{}", address, index)
 }
 
+/// Generates a pattern for scanning all slots of an account.
+fn key_slot_pattern(address: Address) -> String {
+    format!("slot::{}::*", address)
+}
+
+/// Generates a pattern for scanning all slots of all accounts.
+fn key_slots_pattern() -> String {
+    "slot::*".to_owned()
+}
+
 /// Generates a key for accessing a slot history.
 fn key_slot_history(address: Address, index: SlotIndex) -> String {
     format!("slot_history::{}::{}", address, index)
 }
 
+/// Generates a key for accessing the balance changes of an account.
+fn key_balance_changes(address: Address) -> String {
+    format!("balance_changes::{}", address)
+}
+
+/// Generates a key for accessing the internal transfers sent or received by an account.
+fn key_internal_transfers(address: Address) -> String {
+    format!("internal_transfers::{}", address)
+}
+
+/// Generates a key for accessing the token transfers sent or received by an account.
+fn key_token_transfers_by_account(address: Address) -> String {
+    format!("token_transfers::account::{}", address)
+}
+
+/// Generates a key for accessing the token transfers of a token contract.
+fn key_token_transfers_by_token(token: Address) -> String {
+    format!("token_transfers::token::{}", token)
+}
+
+/// Generates a key for accessing the deployment of a contract.
+fn key_contract_creation(address: Address) -> String {
+    format!("contract_creation::{}", address)
+}
+
 /// Generates a key for accessing a transaction.
 fn key_tx(hash: Hash) -> String {
     format!("tx::{}", hash)
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use redis::Arg;
+
+    use super::*;
+    use crate::eth::primitives::UnixTime;
+
+    #[test]
+    fn test_cmd_notify_block() {
+        let block = Block::new(BlockNumber::from(7u64), UnixTime::from(1234567891));
+
+        let args = cmd_notify_block("blocks", &block)
+            .args_iter()
+            .map(|arg| match arg {
+                Arg::Simple(arg) => String::from_utf8(arg.to_vec()).unwrap(),
+                Arg::Cursor => panic!("unexpected cursor argument"),
+            })
+            .collect_vec();
+
+        let hash = block.hash().to_string();
+        let expected = [
+            "XADD",
+            "blocks",
+            "MAXLEN",
+            "~",
+            "100000",
+            "*",
+            "number",
+            "7",
+            "hash",
+            &hash,
+            "transactions_len",
+            "0",
+        ];
+        assert_eq!(args, expected);
+    }
+}
@@ -14,13 +14,25 @@ use strum::IntoStaticStr;
 use strum::VariantNames;
 
 use super::types::AccountRocksdb;
+use super::types::BalanceChangesRocksdb;
 use super::types::BlockNumberRocksdb;
 use super::types::BlockRocksdb;
+use super::types::ContractCreationRocksdb;
+use super::types::GasUsageSnapshotRocksdb;
+use super::types::InternalTransfersRocksdb;
 use super::types::SlotValueRocksdb;
+use super::types::StateDiffRocksdb;
+use super::types::TokenTransfersRocksdb;
+use crate::eth::analytics::GasUsageSnapshot;
 use crate::eth::primitives::Account;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::TokenTransferMined;
 
 macro_rules! impl_single_version_cf_value {
     ($name:ident, $inner_type:ty, $non_rocks_equivalent: ty) => {
@@ -83,6 +95,15 @@ impl_single_version_cf_value!(CfTransactionsValue, BlockNumberRocksdb, BlockNumb
 impl_single_version_cf_value!(CfBlocksByNumberValue, BlockRocksdb, Block);
 impl_single_version_cf_value!(CfBlocksByHashValue, BlockNumberRocksdb, BlockNumber);
 impl_single_version_cf_value!(CfLogsValue, BlockNumberRocksdb, BlockNumber);
+impl_single_version_cf_value!(CfBalanceChangesValue, BalanceChangesRocksdb, Vec<BalanceChange>);
+impl_single_version_cf_value!(CfInternalTransfersValue, InternalTransfersRocksdb, Vec<InternalTransferMined>);
+impl_single_version_cf_value!(CfInternalTransfersByAddressValue, InternalTransfersRocksdb, Vec<InternalTransferMined>);
+impl_single_version_cf_value!(CfTokenTransfersByAccountValue, TokenTransfersRocksdb, Vec<TokenTransferMined>);
+impl_single_version_cf_value!(CfTokenTransfersByTokenValue, TokenTransfersRocksdb, Vec<TokenTransferMined>);
+impl_single_version_cf_value!(CfContractCreationsValue, ContractCreationRocksdb, ContractCreation);
+impl_single_version_cf_value!(CfStateDiffsValue, StateDiffRocksdb, Vec<ExecutionAccountChanges>);
+impl_single_version_cf_value!(CfGasUsageValue, GasUsageSnapshotRocksdb, GasUsageSnapshot);
+impl_single_version_cf_value!(CfFinalizedBlockNumberValue, BlockNumberRocksdb, BlockNumber);
 
 #[cfg_attr(not(test), allow(dead_code))]
 trait ToCfName {
@@ -105,6 +126,15 @@ impl_to_cf_name!(CfTransactionsValue, "transactions");
 impl_to_cf_name!(CfBlocksByNumberValue, "blocks_by_number");
 impl_to_cf_name!(CfBlocksByHashValue, "blocks_by_hash");
 impl_to_cf_name!(CfLogsValue, "logs");
+impl_to_cf_name!(CfBalanceChangesValue, "balance_changes");
+impl_to_cf_name!(CfInternalTransfersValue, "internal_transfers");
+impl_to_cf_name!(CfInternalTransfersByAddressValue, "internal_transfers_by_address");
+impl_to_cf_name!(CfTokenTransfersByAccountValue, "token_transfers_by_account");
+impl_to_cf_name!(CfTokenTransfersByTokenValue, "token_transfers_by_token");
+impl_to_cf_name!(CfContractCreationsValue, "contract_creations");
+impl_to_cf_name!(CfStateDiffsValue, "state_diffs");
+impl_to_cf_name!(CfGasUsageValue, "gas_usage");
+impl_to_cf_name!(CfFinalizedBlockNumberValue, "finalized_block_number");
 
 /// Test that deserialization works for each variant of the enum.
 ///
@@ -147,6 +177,22 @@ mod tests {
     use fake::Faker;
 
     use super::*;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::Index;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::primitives::SlotIndex;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::permanent::rocks::types::AccountChangesRocksdb;
+    use crate::eth::storage::permanent::rocks::types::BalanceChangeRocksdb;
+    use crate::eth::storage::permanent::rocks::types::BytesRocksdb;
+    use crate::eth::storage::permanent::rocks::types::FunctionGasUsageRocksdb;
+    use crate::eth::storage::permanent::rocks::types::GasUsageRocksdb;
+    use crate::eth::storage::permanent::rocks::types::InternalTransferKindRocksdb;
+    use crate::eth::storage::permanent::rocks::types::InternalTransferMinedRocksdb;
+    use crate::eth::storage::permanent::rocks::types::TokenStandardRocksdb;
+    use crate::eth::storage::permanent::rocks::types::TokenTransferMinedRocksdb;
+    use crate::eth::storage::permanent::rocks::types::ValueChangeRocksdb;
     use crate::ext::not;
     use crate::ext::type_basename;
     use crate::utils::test_utils::fake_first;
@@ -214,6 +260,113 @@ mod tests {
         glob_to_string_paths(pattern).context("failed to get all bincode snapshots from folder")
     }
 
+    // -------------------------------------------------------------------------
+    // Explicit snapshot values
+    // -------------------------------------------------------------------------
+
+    const SNAPSHOT_ADDRESS_A: Address = Address::new([0x11; 20]);
+    const SNAPSHOT_ADDRESS_B: Address = Address::new([0x22; 20]);
+    const SNAPSHOT_ADDRESS_C: Address = Address::new([0x33; 20]);
+    const SNAPSHOT_HASH: Hash = Hash::new([0xaa; 32]);
+    const SNAPSHOT_CODE_HASH: [u8; 32] = [0xbb; 32];
+    const SNAPSHOT_BLOCK_NUMBER: u64 = 5;
+
+    fn snapshot_balance_changes() -> BalanceChangesRocksdb {
+        BalanceChangesRocksdb(vec![BalanceChangeRocksdb {
+            address: SNAPSHOT_ADDRESS_A.into(),
+            block_number: BlockNumber::from(SNAPSHOT_BLOCK_NUMBER).into(),
+            transaction_hash: SNAPSHOT_HASH.into(),
+            transaction_index: Index::new(1).into(),
+            previous_balance: Wei::from(1000u64).into(),
+            balance: Wei::from(2000u64).into(),
+        }])
+    }
+
+    fn snapshot_internal_transfers() -> InternalTransfersRocksdb {
+        InternalTransfersRocksdb(vec![InternalTransferMinedRocksdb {
+            kind: InternalTransferKindRocksdb::Call,
+            from: SNAPSHOT_ADDRESS_A.into(),
+            to: SNAPSHOT_ADDRESS_B.into(),
+            value: Wei::from(1000u64).into(),
+            depth: 1,
+            transaction_hash: SNAPSHOT_HASH.into(),
+            transaction_index: Index::new(1).into(),
+            block_number: BlockNumber::from(SNAPSHOT_BLOCK_NUMBER).into(),
+        }])
+    }
+
+    fn snapshot_token_transfers() -> TokenTransfersRocksdb {
+        TokenTransfersRocksdb(vec![TokenTransferMinedRocksdb {
+            standard: TokenStandardRocksdb::Erc721,
+            token: SNAPSHOT_ADDRESS_C.into(),
+            from: SNAPSHOT_ADDRESS_A.into(),
+            to: SNAPSHOT_ADDRESS_B.into(),
+            token_id: Some([7, 0, 0, 0]),
+            value: [1, 0, 0, 0],
+            transaction_hash: SNAPSHOT_HASH.into(),
+            transaction_index: Index::new(1).into(),
+            log_index: Index::new(2).into(),
+            block_number: BlockNumber::from(SNAPSHOT_BLOCK_NUMBER).into(),
+        }])
+    }
+
+    fn snapshot_contract_creation() -> ContractCreationRocksdb {
+        ContractCreationRocksdb {
+            address: SNAPSHOT_ADDRESS_C.into(),
+            creator: SNAPSHOT_ADDRESS_A.into(),
+            transaction_hash: SNAPSHOT_HASH.into(),
+            transaction_index: Index::new(1).into(),
+            block_number: BlockNumber::from(SNAPSHOT_BLOCK_NUMBER).into(),
+        }
+    }
+
+    fn snapshot_state_diff() -> StateDiffRocksdb {
+        StateDiffRocksdb(vec![AccountChangesRocksdb {
+            new_account: true,
+            address: SNAPSHOT_ADDRESS_A.into(),
+            nonce: ValueChangeRocksdb {
+                original: Some(Nonce::from(0u64).into()),
+                modified: Some(Nonce::from(1u64).into()),
+            },
+            balance: ValueChangeRocksdb {
+                original: Some(Wei::from(1000u64).into()),
+                modified: Some(Wei::from(2000u64).into()),
+            },
+            bytecode: ValueChangeRocksdb {
+                original: Some(None),
+                modified: Some(Some(BytesRocksdb(vec![0x60, 0x80]))),
+            },
+            code_hash: SNAPSHOT_CODE_HASH,
+            slots: vec![(
+                SlotIndex::from(1u64).into(),
+                ValueChangeRocksdb {
+                    original: None,
+                    modified: Some(SlotValue::from(2u64).into()),
+                },
+            )],
+        }])
+    }
+
+    fn snapshot_gas_usage() -> GasUsageSnapshotRocksdb {
+        GasUsageSnapshotRocksdb {
+            first_block: Some(BlockNumber::from(1u64).into()),
+            last_block: Some(BlockNumber::from(SNAPSHOT_BLOCK_NUMBER).into()),
+            blocks: SNAPSHOT_BLOCK_NUMBER,
+            usage: GasUsageRocksdb {
+                transactions: 2,
+                gas_used: 50_000,
+            },
+            functions: vec![FunctionGasUsageRocksdb {
+                contract: SNAPSHOT_ADDRESS_C.into(),
+                selector: [0xa9, 0x05, 0x9c, 0xbb],
+                usage: GasUsageRocksdb {
+                    transactions: 1,
+                    gas_used: 29_000,
+                },
+            }],
+        }
+    }
+
     /// Store snapshots of the current serialization format for each version.
     #[test]
     fn test_snapshot_bincode_deserialization_for_single_version_enums() {
@@ -223,7 +376,13 @@ mod tests {
             F: FnOnce(Inner) -> CfValue,
             Inner: Dummy<Faker>,
         {
-            let expected: CfValue = inner_to_cf_value(fake_first::<Inner>());
+            test_snapshot(inner_to_cf_value(fake_first::<Inner>()))
+        }
+
+        fn test_snapshot<CfValue>(expected: CfValue) -> Result<TestRunConfirmation<CfValue>>
+        where
+            CfValue: for<'de> Deserialize<'de> + Serialize + Clone + Debug + PartialEq + Into<&'static str> + ToCfName,
+        {
             let variant_name: &'static str = expected.clone().into();
             let cf_name = CfValue::CF_NAME;
 
@@ -275,6 +434,15 @@ mod tests {
         let mut blocks_by_number_checker = EnumCoverageDropBombChecker::<CfBlocksByNumberValue>::new();
         let mut blocks_by_hash_checker = EnumCoverageDropBombChecker::<CfBlocksByHashValue>::new();
         let mut logs_checker = EnumCoverageDropBombChecker::<CfLogsValue>::new();
+        let mut balance_changes_checker = EnumCoverageDropBombChecker::<CfBalanceChangesValue>::new();
+        let mut internal_transfers_checker = EnumCoverageDropBombChecker::<CfInternalTransfersValue>::new();
+        let mut internal_transfers_by_address_checker = EnumCoverageDropBombChecker::<CfInternalTransfersByAddressValue>::new();
+        let mut token_transfers_by_account_checker = EnumCoverageDropBombChecker::<CfTokenTransfersByAccountValue>::new();
+        let mut token_transfers_by_token_checker = EnumCoverageDropBombChecker::<CfTokenTransfersByTokenValue>::new();
+        let mut contract_creations_checker = EnumCoverageDropBombChecker::<CfContractCreationsValue>::new();
+        let mut state_diffs_checker = EnumCoverageDropBombChecker::<CfStateDiffsValue>::new();
+        let mut gas_usage_checker = EnumCoverageDropBombChecker::<CfGasUsageValue>::new();
+        let mut finalized_block_number_checker = EnumCoverageDropBombChecker::<CfFinalizedBlockNumberValue>::new();
 
         accounts_checker.add(test_deserialization::<_, AccountRocksdb, _>(CfAccountsValue::V1).unwrap());
         accounts_history_checker.add(test_deserialization::<_, AccountRocksdb, _>(CfAccountsHistoryValue::V1).unwrap());
@@ -284,5 +452,16 @@ mod tests {
         blocks_by_number_checker.add(test_deserialization::<_, BlockRocksdb, _>(CfBlocksByNumberValue::V1).unwrap());
         blocks_by_hash_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfBlocksByHashValue::V1).unwrap());
         logs_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfLogsValue::V1).unwrap());
+        finalized_block_number_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfFinalizedBlockNumberValue::V1).unwrap());
+
+        // values of the column families below are written explicitly instead of faked, so their snapshots can be checked byte by byte
+        balance_changes_checker.add(test_snapshot(CfBalanceChangesValue::V1(snapshot_balance_changes())).unwrap());
+        internal_transfers_checker.add(test_snapshot(CfInternalTransfersValue::V1(snapshot_internal_transfers())).unwrap());
+        internal_transfers_by_address_checker.add(test_snapshot(CfInternalTransfersByAddressValue::V1(snapshot_internal_transfers())).unwrap());
+        token_transfers_by_account_checker.add(test_snapshot(CfTokenTransfersByAccountValue::V1(snapshot_token_transfers())).unwrap());
+        token_transfers_by_token_checker.add(test_snapshot(CfTokenTransfersByTokenValue::V1(snapshot_token_transfers())).unwrap());
+        contract_creations_checker.add(test_snapshot(CfContractCreationsValue::V1(snapshot_contract_creation())).unwrap());
+        state_diffs_checker.add(test_snapshot(CfStateDiffsValue::V1(snapshot_state_diff())).unwrap());
+        gas_usage_checker.add(test_snapshot(CfGasUsageValue::V1(snapshot_gas_usage())).unwrap());
     }
 }
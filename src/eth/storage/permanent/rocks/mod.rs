@@ -21,5 +21,8 @@ mod rocks_config;
 /// Functionalities related to the whole database.
 mod rocks_db;
 
+/// Schema versioning and migrations.
+mod rocks_schema;
+
 /// All types to be serialized and desserialized in the db.
 pub mod types;
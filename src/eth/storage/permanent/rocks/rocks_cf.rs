@@ -184,6 +184,25 @@ where
         Ok(())
     }
 
+    pub fn prepare_batch_deletion<I>(&self, deletions: I, batch: &mut WriteBatch) -> Result<()>
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let cf = self.handle();
+        for key in deletions {
+            let serialized_key = self
+                .serialize_key_with_context(&key)
+                .with_context(|| format!("failed to prepare batch deletion for CF: '{}'", self.column_family))?;
+            batch.delete_cf(&cf, serialized_key);
+        }
+        Ok(())
+    }
+
+    /// Compacts all data of the CF, discarding deleted and overwritten values.
+    pub fn compact(&self) {
+        self.db.compact_range_cf(&self.handle(), None::<&[u8]>, None::<&[u8]>);
+    }
+
     #[allow(dead_code)]
     pub fn iter_start(&self) -> RocksCfIter<K, V> {
         let cf = self.handle();
@@ -3,6 +3,7 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
 
+use anyhow::bail;
 use anyhow::Context;
 use rocksdb::Options;
 use rocksdb::DB;
@@ -55,3 +56,32 @@ pub fn create_or_open_db(path: impl AsRef<Path>, cf_configs: &HashMap<&'static s
 
     Ok((Arc::new(db), db_opts))
 }
+
+/// Open an existing Database in read-only mode, with the configs applied to all column families.
+///
+/// The database is not created, repaired or written to, and data written by other instances after it is opened is not visible.
+#[tracing::instrument(skip_all, fields(path = ?path.as_ref()))]
+pub fn open_db_read_only(path: impl AsRef<Path>, cf_configs: &HashMap<&'static str, Options>) -> anyhow::Result<(Arc<DB>, Options)> {
+    let path = path.as_ref();
+    let cf_config_iter = cf_configs.iter().map(|(name, opts)| (*name, opts.clone()));
+    let db_opts = DbConfig::Default.to_options(CacheSetting::Disabled);
+
+    if !path.exists() {
+        bail!("RocksDB at path {:?} doesn't exist and read-only databases cannot be created", path);
+    }
+
+    tracing::debug!("attempting to open RocksDB in read-only mode");
+    let instant = Instant::now();
+    let db = DB::open_cf_with_opts_for_read_only(&db_opts, path, cf_config_iter, false).context("when opening RocksDB in read-only mode")?;
+
+    let waited_for = instant.elapsed();
+    tracing::info!(?waited_for, db_path = ?path, "successfully opened RocksDB in read-only mode");
+
+    #[cfg(feature = "metrics")]
+    {
+        let db_name = path.file_name().with_context(|| format!("invalid db path without name '{path:?}'"))?.to_str();
+        metrics::set_rocks_last_startup_delay_millis(waited_for.as_millis() as u64, db_name);
+    }
+
+    Ok((Arc::new(db), db_opts))
+}
@@ -6,19 +6,29 @@ use std::time::Duration;
 use anyhow::bail;
 
 use super::rocks_state::RocksStorageState;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
-use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::PermanentStorageKind;
+use crate::eth::storage::ReadStorage;
+use crate::eth::storage::WriteStorage;
 
 #[derive(Debug)]
 pub struct RocksPermanentStorage {
@@ -32,6 +42,8 @@ impl RocksPermanentStorage {
         shutdown_timeout: Duration,
         cache_size_multiplier: Option<f32>,
         enable_sync_write: bool,
+        index_token_transfers: bool,
+        read_only: bool,
     ) -> anyhow::Result<Self> {
         tracing::info!("setting up rocksdb storage");
 
@@ -53,7 +65,14 @@ impl RocksPermanentStorage {
             "data/rocksdb".to_string()
         };
 
-        let state = RocksStorageState::new(path, shutdown_timeout, cache_size_multiplier, enable_sync_write)?;
+        let state = RocksStorageState::new(
+            path,
+            shutdown_timeout,
+            cache_size_multiplier,
+            enable_sync_write,
+            index_token_transfers,
+            read_only,
+        )?;
         let block_number = state.preload_block_number()?;
 
         Ok(Self { state, block_number })
@@ -62,6 +81,15 @@ impl RocksPermanentStorage {
     // -------------------------------------------------------------------------
     // State methods
     // -------------------------------------------------------------------------
+    /// Removes all blocks after the specified block, restoring the state and the mined block number to it.
+    pub fn reset_to_block(&self, number: BlockNumber) -> anyhow::Result<()> {
+        self.state.reset_to_block(number).inspect_err(|e| {
+            tracing::error!(reason = ?e, %number, "failed to reset RocksPermanent DB to block");
+        })?;
+        self.block_number.store(number.as_u64(), Ordering::SeqCst);
+        Ok(())
+    }
+
     #[cfg(feature = "dev")]
     pub fn clear(&self) -> anyhow::Result<()> {
         self.state.clear().inspect_err(|e| {
@@ -72,87 +100,243 @@ impl RocksPermanentStorage {
     }
 }
 
-impl PermanentStorage for RocksPermanentStorage {
+impl ReadStorage for RocksPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        PermanentStorageKind::Rocks
+    }
+
     // -------------------------------------------------------------------------
     // Block number operations
     // -------------------------------------------------------------------------
 
-    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber> {
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
         Ok(self.block_number.load(Ordering::SeqCst).into())
     }
 
-    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
-        self.block_number.store(number.as_u64(), Ordering::SeqCst);
-        Ok(())
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        self.state
+            .read_finalized_block_number()
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read finalized block number in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
     // -------------------------------------------------------------------------
     // State operations
     // -------------------------------------------------------------------------
 
-    fn read_account(&self, address: Address, point_in_time: PointInTime) -> anyhow::Result<Option<Account>> {
-        self.state.read_account(address, point_in_time).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to read account in RocksPermanent");
-        })
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
+        self.state
+            .read_account(address, point_in_time)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read account in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
+        self.state
+            .read_slot(address, index, point_in_time)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read slot in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        self.state
+            .read_slots_range(address, start, limit)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read slots range in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        self.state
+            .read_balance_changes(address, from_block, limit)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read balance changes in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.state
+            .read_internal_transfers_by_block(number)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read internal transfers by block in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.state
+            .read_internal_transfers_by_address(address, from_block, limit)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read internal transfers by address in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.state.index_token_transfers
     }
 
-    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> anyhow::Result<Option<Slot>> {
-        self.state.read_slot(address, index, point_in_time).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to read slot in RocksPermanent");
-        })
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.state
+            .read_token_transfers_by_account(address, from_block, limit)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read token transfers by account in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
-    fn read_block(&self, selection: BlockFilter) -> anyhow::Result<Option<Block>> {
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.state
+            .read_token_transfers_by_token(token, from_block, limit)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read token transfers by token in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        self.state
+            .read_contract_creation(address)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read contract creation in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        self.state
+            .read_state_diff(number)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read state diff in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        self.state
+            .read_state_count()
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to count state in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        self.state
+            .read_gas_usage()
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read gas usage in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_block(&self, selection: BlockFilter) -> Result<Option<Block>, StratusError> {
+        // the mined block number is tracked here, so the state can only resolve the other filters
+        let selection = match selection {
+            BlockFilter::Safe => BlockFilter::Number(self.read_mined_block_number()?),
+            selection => selection,
+        };
         let block = self.state.read_block(selection).inspect_err(|e| {
             tracing::error!(reason = ?e, "failed to read block in RocksPermanent");
         });
         if let Ok(Some(block)) = &block {
             tracing::trace!(?selection, ?block, "block found");
         }
-        block
+        block.map_err(Into::into)
+    }
+
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
+        self.state
+            .read_transaction(hash)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read transaction in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
+        self.state
+            .read_logs(filter)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to read log in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
+}
 
-    fn read_transaction(&self, hash: Hash) -> anyhow::Result<Option<TransactionMined>> {
-        self.state.read_transaction(hash).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to read transaction in RocksPermanent");
-        })
+impl WriteStorage for RocksPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.block_number.store(number.as_u64(), Ordering::SeqCst);
+        Ok(())
     }
 
-    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>> {
-        self.state.read_logs(filter).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to read log in RocksPermanent");
-        })
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.state
+            .save_finalized_block_number(number)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, %number, "failed to save finalized block number in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
-    fn save_block(&self, block: Block) -> anyhow::Result<()> {
+    fn save_block(&self, block: Block) -> Result<(), StratusError> {
         #[cfg(feature = "metrics")]
         {
             self.state.export_metrics().inspect_err(|e| {
                 tracing::error!(reason = ?e, "failed to export metrics in RocksPermanent");
             })?;
         }
-        self.state.save_block(block).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to save block in RocksPermanent");
-        })
+        self.state
+            .save_block(block)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to save block in RocksPermanent");
+            })
+            .map_err(Into::into)
+    }
+
+    fn save_block_batch(&self, block_batch: Vec<Block>) -> Result<(), StratusError> {
+        self.state
+            .save_block_batch(block_batch)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to save block_batch in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
-    fn save_block_batch(&self, block_batch: Vec<Block>) -> anyhow::Result<()> {
-        self.state.save_block_batch(block_batch).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to save block_batch in RocksPermanent");
-        })
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
+        self.state
+            .save_accounts(accounts)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to save accounts in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
-    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
-        self.state.save_accounts(accounts).inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to save accounts in RocksPermanent");
-        })
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        self.state
+            .save_gas_usage(usage)
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to save gas usage in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 
     #[cfg(feature = "dev")]
-    fn reset(&self) -> anyhow::Result<()> {
+    fn reset(&self) -> Result<(), StratusError> {
         self.block_number.store(0u64, Ordering::SeqCst);
-        self.state.reset().inspect_err(|e| {
-            tracing::error!(reason = ?e, "failed to reset in RocksPermanent");
-        })
+        self.state
+            .reset()
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to reset in RocksPermanent");
+            })
+            .map_err(Into::into)
     }
 }
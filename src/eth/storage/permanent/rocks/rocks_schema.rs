@@ -0,0 +1,173 @@
+//! Schema versioning of the RocksDB database.
+//!
+//! The schema is the layout of the column families and the encoding of their keys. Its version is saved in the database and, when it is opened,
+//! migrations upgrade it from the saved version to the version supported by the binary.
+//!
+//! New value variants (see `cf_versions`) don't change the schema because old values remain readable. Changing keys, adding column families
+//! that must be backfilled or rewriting existing values does, and requires a new migration.
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+
+use super::rocks_state::RocksStorageState;
+
+/// Schema version supported by this binary.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Upgrade routine from the previous schema version to `version`.
+pub struct Migration {
+    pub version: u64,
+    pub description: &'static str,
+
+    /// Must be idempotent, because the process may stop after the routine runs and before the new version is saved.
+    pub run: fn(&RocksStorageState) -> Result<()>,
+}
+
+/// Migrations in version order.
+///
+/// Version 1 is the layout of databases created before the schema was versioned, so it has no migration.
+///
+/// Version 2 added column families derived from blocks. Only the ones derived from data kept in saved blocks are backfilled, the others start
+/// empty and are filled only for blocks saved after the upgrade:
+/// * `token_transfers_by_account` and `token_transfers_by_token`: backfilled from the logs of saved transactions.
+/// * `contract_creations`: backfilled from the deployed contract address of saved transactions.
+/// * `balance_changes` and `state_diffs`: start empty, because saved blocks keep only bytecode changes and discard balance, nonce and slot
+///   changes. A missing state diff is reported as unknown, not as a block without changes.
+/// * `internal_transfers` and `internal_transfers_by_address`: start empty, because internal transfers were not recorded by the executor for
+///   transactions saved before them.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    description: "backfill token transfers and contract creations indexes",
+    run: RocksStorageState::backfill_block_indexes,
+}];
+
+// every version after the first one is reached by a migration
+static_assertions::const_assert_eq!(MIGRATIONS.len() as u64, SCHEMA_VERSION - 1);
+
+/// Upgrades the database schema to the version supported by this binary.
+///
+/// Fails if the database was already upgraded by a newer binary, as its data may not be readable.
+pub fn migrate(state: &RocksStorageState) -> Result<()> {
+    migrate_to(state, MIGRATIONS, SCHEMA_VERSION)
+}
+
+fn migrate_to(state: &RocksStorageState, migrations: &[Migration], target_version: u64) -> Result<()> {
+    let saved_version = state.read_schema_version()?;
+    let mut version = match saved_version {
+        Some(version) => version,
+        // new databases are created with the current layout
+        None if state.is_empty()? => target_version,
+        // databases created before the schema was versioned
+        None => 1,
+    };
+
+    if version > target_version {
+        bail!(
+            "rocksdb schema version {} is newer than the version {} supported by this binary, use a newer stratus or a compatible database",
+            version,
+            target_version
+        );
+    }
+
+    for migration in migrations
+        .iter()
+        .filter(|migration| migration.version > version && migration.version <= target_version)
+    {
+        tracing::warn!(from = %version, to = %migration.version, description = %migration.description, "migrating rocksdb schema");
+        (migration.run)(state).with_context(|| format!("when migrating rocksdb schema to version {}", migration.version))?;
+        state.save_schema_version(migration.version)?;
+        version = migration.version;
+    }
+
+    if saved_version != Some(version) {
+        state.save_schema_version(version)?;
+    }
+    tracing::info!(%version, "rocksdb schema is up to date");
+    Ok(())
+}
+
+/// Checks that the database schema is the version supported by this binary, without migrating it.
+///
+/// Used when the database is opened read-only, so it must have been upgraded by a writer node first.
+pub fn check(state: &RocksStorageState) -> Result<()> {
+    let version = match state.read_schema_version()? {
+        Some(version) => version,
+        None if state.is_empty()? => SCHEMA_VERSION,
+        None => 1,
+    };
+    if version != SCHEMA_VERSION {
+        bail!(
+            "rocksdb schema version {} differs from the version {} supported by this binary, and read-only databases cannot be migrated",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    tracing::info!(%version, "rocksdb schema is compatible");
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::eth::primitives::Block;
+
+    thread_local! {
+        /// Versions of the test migrations that ran in the current test thread, so tests running in parallel do not see each other.
+        static MIGRATIONS_RAN: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn migration(version: u64) -> Migration {
+        Migration {
+            version,
+            description: "test migration",
+            run: |state| {
+                let version = state.read_schema_version()?.unwrap_or_default() + 1;
+                MIGRATIONS_RAN.with_borrow_mut(|ran| ran.push(version));
+                Ok(())
+            },
+        }
+    }
+
+    #[test]
+    fn test_new_database_is_created_with_current_version() {
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        assert_eq!(state.read_schema_version().unwrap(), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_version_1_databases_are_migrated_to_current_version() {
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        state.save_block(Block::genesis()).unwrap();
+
+        // read-only nodes cannot open version 1 databases
+        state.save_schema_version(1).unwrap();
+        assert!(check(&state).is_err());
+
+        migrate(&state).unwrap();
+        assert_eq!(state.read_schema_version().unwrap(), Some(SCHEMA_VERSION));
+        check(&state).unwrap();
+    }
+
+    #[test]
+    fn test_migrations_run_once_in_order() {
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        state.save_schema_version(1).unwrap();
+        let migrations = [migration(2), migration(3)];
+
+        migrate_to(&state, &migrations[..1], 2).unwrap();
+        migrate_to(&state, &migrations, 3).unwrap();
+        migrate_to(&state, &migrations, 3).unwrap();
+        assert_eq!(MIGRATIONS_RAN.with_borrow(Clone::clone), vec![2, 3]);
+        assert_eq!(state.read_schema_version().unwrap(), Some(3));
+
+        // older binaries refuse to open the database
+        state.save_schema_version(SCHEMA_VERSION + 1).unwrap();
+        assert!(migrate(&state).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::atomic::AtomicU64;
@@ -9,6 +10,7 @@ use std::time::Instant;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
+use itertools::Itertools;
 use rocksdb::Direction;
 use rocksdb::Options;
 use rocksdb::WaitForCompactOptions;
@@ -23,14 +25,26 @@ use super::cf_versions::CfAccountSlotsHistoryValue;
 use super::cf_versions::CfAccountSlotsValue;
 use super::cf_versions::CfAccountsHistoryValue;
 use super::cf_versions::CfAccountsValue;
+use super::cf_versions::CfBalanceChangesValue;
 use super::cf_versions::CfBlocksByHashValue;
 use super::cf_versions::CfBlocksByNumberValue;
+use super::cf_versions::CfContractCreationsValue;
+use super::cf_versions::CfFinalizedBlockNumberValue;
+use super::cf_versions::CfGasUsageValue;
+use super::cf_versions::CfInternalTransfersByAddressValue;
+use super::cf_versions::CfInternalTransfersValue;
 use super::cf_versions::CfLogsValue;
+use super::cf_versions::CfStateDiffsValue;
+use super::cf_versions::CfTokenTransfersByAccountValue;
+use super::cf_versions::CfTokenTransfersByTokenValue;
 use super::cf_versions::CfTransactionsValue;
 use super::rocks_cf::RocksCfRef;
 use super::rocks_config::CacheSetting;
 use super::rocks_config::DbConfig;
 use super::rocks_db::create_or_open_db;
+use super::rocks_db::open_db_read_only;
+use super::rocks_schema;
+use super::types::into_block_transactions;
 use super::types::AccountRocksdb;
 use super::types::AddressRocksdb;
 use super::types::BlockNumberRocksdb;
@@ -38,19 +52,26 @@ use super::types::HashRocksdb;
 use super::types::IndexRocksdb;
 use super::types::SlotIndexRocksdb;
 use super::types::SlotValueRocksdb;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
 use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionMined;
+use crate::ext::not;
 use crate::ext::OptionExt;
 use crate::log_and_err;
 use crate::utils::GIGABYTE;
@@ -85,6 +106,16 @@ fn generate_cf_options_map(cache_multiplier: Option<f32>) -> HashMap<&'static st
         "blocks_by_number" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
         "blocks_by_hash" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
         "logs" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
+        "balance_changes" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "internal_transfers" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "internal_transfers_by_address" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "token_transfers_by_account" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "token_transfers_by_token" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "contract_creations" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "state_diffs" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
+        "schema_version" => DbConfig::Default.to_options(CacheSetting::Disabled),
+        "gas_usage" => DbConfig::Default.to_options(CacheSetting::Disabled),
+        "finalized_block_number" => DbConfig::Default.to_options(CacheSetting::Disabled),
     }
 }
 
@@ -104,6 +135,20 @@ where
     RocksCfRef::new(Arc::clone(db), column_family)
 }
 
+/// Adds the deletion of all entries of an index keyed by account and block that are after the specified block to the batch.
+fn prepare_batch_deletion_after<V>(cf: &RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), V>, number: BlockNumberRocksdb, batch: &mut WriteBatch) -> Result<()>
+where
+    V: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+{
+    for next in cf.iter_start().keys() {
+        let key = next?;
+        if key.1 > number {
+            cf.prepare_batch_deletion([key], batch)?;
+        }
+    }
+    Ok(())
+}
+
 /// State handler for our RocksDB storage, separating "tables" by column families.
 ///
 /// With data separated by column families, writing and reading should be done via the `RocksCfRef` fields.
@@ -118,6 +163,19 @@ pub struct RocksStorageState {
     pub blocks_by_number: RocksCfRef<BlockNumberRocksdb, CfBlocksByNumberValue>,
     blocks_by_hash: RocksCfRef<HashRocksdb, CfBlocksByHashValue>,
     logs: RocksCfRef<(HashRocksdb, IndexRocksdb), CfLogsValue>,
+    balance_changes: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), CfBalanceChangesValue>,
+    internal_transfers: RocksCfRef<BlockNumberRocksdb, CfInternalTransfersValue>,
+    internal_transfers_by_address: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), CfInternalTransfersByAddressValue>,
+    token_transfers_by_account: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), CfTokenTransfersByAccountValue>,
+    token_transfers_by_token: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), CfTokenTransfersByTokenValue>,
+    contract_creations: RocksCfRef<AddressRocksdb, CfContractCreationsValue>,
+    state_diffs: RocksCfRef<BlockNumberRocksdb, CfStateDiffsValue>,
+    /// Single value with the schema version, not versioned by variant so it is always readable.
+    schema_version: RocksCfRef<(), u64>,
+    /// Single value with the gas usage aggregated by the gas analytics.
+    gas_usage: RocksCfRef<(), CfGasUsageValue>,
+    /// Single value with the last finalized block number.
+    finalized_block_number: RocksCfRef<(), CfFinalizedBlockNumberValue>,
     /// Last collected stats for a histogram
     #[cfg(feature = "metrics")]
     prev_stats: Mutex<HashMap<HistogramInt, (Sum, Count)>>,
@@ -129,16 +187,31 @@ pub struct RocksStorageState {
     db_options: Options,
     shutdown_timeout: Duration,
     enable_sync_write: bool,
+    /// Opened in read-only mode, so the schema is not migrated and nothing is flushed at shutdown.
+    read_only: bool,
+    /// Should decode and index token transfers when saving blocks?
+    pub index_token_transfers: bool,
 }
 
 impl RocksStorageState {
-    pub fn new(path: String, shutdown_timeout: Duration, cache_multiplier: Option<f32>, enable_sync_write: bool) -> Result<Self> {
+    pub fn new(
+        path: String,
+        shutdown_timeout: Duration,
+        cache_multiplier: Option<f32>,
+        enable_sync_write: bool,
+        index_token_transfers: bool,
+        read_only: bool,
+    ) -> Result<Self> {
         tracing::debug!("creating (or opening an existing) database with the specified column families");
 
         let cf_options_map = generate_cf_options_map(cache_multiplier);
 
         #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
-        let (db, db_options) = create_or_open_db(&path, &cf_options_map).context("when trying to create (or open) rocksdb")?;
+        let (db, db_options) = if read_only {
+            open_db_read_only(&path, &cf_options_map).context("when trying to open rocksdb in read-only mode")?
+        } else {
+            create_or_open_db(&path, &cf_options_map).context("when trying to create (or open) rocksdb")?
+        };
 
         if db.path().to_str().is_none() {
             bail!("db path doesn't isn't valid UTF-8: {:?}", db.path());
@@ -157,6 +230,16 @@ impl RocksStorageState {
             blocks_by_number: new_cf_ref(&db, "blocks_by_number", &cf_options_map)?,
             blocks_by_hash: new_cf_ref(&db, "blocks_by_hash", &cf_options_map)?,
             logs: new_cf_ref(&db, "logs", &cf_options_map)?,
+            balance_changes: new_cf_ref(&db, "balance_changes", &cf_options_map)?,
+            internal_transfers: new_cf_ref(&db, "internal_transfers", &cf_options_map)?,
+            internal_transfers_by_address: new_cf_ref(&db, "internal_transfers_by_address", &cf_options_map)?,
+            token_transfers_by_account: new_cf_ref(&db, "token_transfers_by_account", &cf_options_map)?,
+            token_transfers_by_token: new_cf_ref(&db, "token_transfers_by_token", &cf_options_map)?,
+            contract_creations: new_cf_ref(&db, "contract_creations", &cf_options_map)?,
+            state_diffs: new_cf_ref(&db, "state_diffs", &cf_options_map)?,
+            schema_version: new_cf_ref(&db, "schema_version", &cf_options_map)?,
+            gas_usage: new_cf_ref(&db, "gas_usage", &cf_options_map)?,
+            finalized_block_number: new_cf_ref(&db, "finalized_block_number", &cf_options_map)?,
             #[cfg(feature = "metrics")]
             prev_stats: Mutex::default(),
             #[cfg(feature = "metrics")]
@@ -164,9 +247,16 @@ impl RocksStorageState {
             db,
             shutdown_timeout,
             enable_sync_write,
+            read_only,
+            index_token_transfers,
         };
 
         tracing::debug!("opened database successfully");
+        if read_only {
+            rocks_schema::check(&state).context("when checking rocksdb schema")?;
+        } else {
+            rocks_schema::migrate(&state).context("when migrating rocksdb schema")?;
+        }
         Ok(state)
     }
 
@@ -175,7 +265,7 @@ impl RocksStorageState {
     pub fn new_in_testdir() -> anyhow::Result<(Self, tempfile::TempDir)> {
         let test_dir = tempfile::tempdir()?;
         let path = test_dir.as_ref().display().to_string();
-        let state = Self::new(path, Duration::ZERO, None, true)?;
+        let state = Self::new(path, Duration::ZERO, None, true, true, false)?;
         Ok((state, test_dir))
     }
 
@@ -185,6 +275,23 @@ impl RocksStorageState {
         self.db_path.rsplit('/').next().unwrap_or(&self.db_path)
     }
 
+    /// Reads the schema version of the database. Returns `None` if it was not saved yet.
+    pub fn read_schema_version(&self) -> Result<Option<u64>> {
+        self.schema_version.get(&())
+    }
+
+    /// Saves the schema version of the database.
+    pub fn save_schema_version(&self, version: u64) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        self.schema_version.prepare_batch_insertion([((), version)], &mut batch)?;
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    /// Checks if the database has no blocks and no accounts.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.blocks_by_number.last_key()?.is_none() && self.accounts.first_value()?.is_none())
+    }
+
     pub fn preload_block_number(&self) -> Result<AtomicU64> {
         let block_number = self.blocks_by_number.last_key()?.unwrap_or_default();
         tracing::info!(%block_number, "preloaded block_number");
@@ -201,6 +308,15 @@ impl RocksStorageState {
         self.blocks_by_number.clear()?;
         self.blocks_by_hash.clear()?;
         self.logs.clear()?;
+        self.balance_changes.clear()?;
+        self.internal_transfers.clear()?;
+        self.internal_transfers_by_address.clear()?;
+        self.token_transfers_by_account.clear()?;
+        self.token_transfers_by_token.clear()?;
+        self.contract_creations.clear()?;
+        self.state_diffs.clear()?;
+        self.gas_usage.clear()?;
+        self.finalized_block_number.clear()?;
         Ok(())
     }
 
@@ -215,7 +331,11 @@ impl RocksStorageState {
 
             if change.is_account_modified() {
                 let address: AddressRocksdb = change.address.into();
-                let mut account_info_entry = self.accounts.get(&address)?.unwrap_or(AccountRocksdb::default().into());
+                // accounts not saved yet start from their original values, that may have been read from a fork
+                let mut account_info_entry = match self.accounts.get(&address)? {
+                    Some(entry) => entry,
+                    None => AccountRocksdb::from(change.to_account()).into(),
+                };
 
                 if let Some(nonce) = change.nonce.take_modified() {
                     account_info_entry.nonce = nonce.into();
@@ -258,12 +378,12 @@ impl RocksStorageState {
                 .with_context(|| format!("block_number = {:?} tx_hash = {}", block_number, tx_hash));
         };
 
-        let transaction = block.into_inner().transactions.into_iter().find(|tx| Hash::from(tx.input.hash) == tx_hash);
+        let transaction = into_block_transactions(block.into_inner().transactions).find(|tx| tx.input.hash == tx_hash);
 
         match transaction {
             Some(tx) => {
                 tracing::trace!(%tx_hash, "transaction found");
-                Ok(Some(tx.into()))
+                Ok(Some(tx))
             }
             None => log_and_err!("rocks error, transaction wasn't found in block where the index pointed at")
                 .with_context(|| format!("block_number = {:?} tx_hash = {}", block_number, tx_hash)),
@@ -341,6 +461,148 @@ impl RocksStorageState {
         }
     }
 
+    /// Reads current slots of an account in key order, which is the order of the serialized index, not the numeric one.
+    pub fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>> {
+        if address.is_coinbase() || limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let rocks_address: AddressRocksdb = address.into();
+        let mut slots = Vec::with_capacity(limit.min(1024));
+        for next in self.account_slots.iter_from((rocks_address, start.into()), rocksdb::Direction::Forward)? {
+            let ((next_address, next_index), value) = next?;
+            if next_address != rocks_address {
+                break;
+            }
+            slots.push(Slot {
+                index: next_index.into(),
+                value: value.into_inner().into(),
+            });
+            if slots.len() >= limit {
+                break;
+            }
+        }
+        Ok(slots)
+    }
+
+    pub fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>> {
+        let rocks_address: AddressRocksdb = address.into();
+        let mut balance_changes = Vec::new();
+        for next in self
+            .balance_changes
+            .iter_from((rocks_address, from_block.into()), Direction::Forward)?
+            .take(limit)
+        {
+            let ((next_address, _), block_balance_changes) = next?;
+            if next_address != rocks_address {
+                break;
+            }
+            balance_changes.extend(Vec::<BalanceChange>::from(block_balance_changes.into_inner()));
+        }
+        Ok(balance_changes)
+    }
+
+    pub fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>> {
+        let state_diff = self.state_diffs.get(&number.into())?;
+        Ok(state_diff.map(|value| value.into_inner().into()))
+    }
+
+    pub fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>> {
+        let internal_transfers = self.internal_transfers.get(&number.into())?;
+        Ok(internal_transfers.map(|value| value.into_inner().into()).unwrap_or_default())
+    }
+
+    pub fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>> {
+        let rocks_address: AddressRocksdb = address.into();
+        let mut internal_transfers = Vec::new();
+        for next in self
+            .internal_transfers_by_address
+            .iter_from((rocks_address, from_block.into()), Direction::Forward)?
+            .take(limit)
+        {
+            let ((next_address, _), block_internal_transfers) = next?;
+            if next_address != rocks_address {
+                break;
+            }
+            internal_transfers.extend(Vec::<InternalTransferMined>::from(block_internal_transfers.into_inner()));
+        }
+        Ok(internal_transfers)
+    }
+
+    pub fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>> {
+        let rocks_address: AddressRocksdb = address.into();
+        let mut token_transfers = Vec::new();
+        for next in self
+            .token_transfers_by_account
+            .iter_from((rocks_address, from_block.into()), Direction::Forward)?
+            .take(limit)
+        {
+            let ((next_address, _), block_token_transfers) = next?;
+            if next_address != rocks_address {
+                break;
+            }
+            token_transfers.extend(Vec::<TokenTransferMined>::from(block_token_transfers.into_inner()));
+        }
+        Ok(token_transfers)
+    }
+
+    pub fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>> {
+        let rocks_token: AddressRocksdb = token.into();
+        let mut token_transfers = Vec::new();
+        for next in self
+            .token_transfers_by_token
+            .iter_from((rocks_token, from_block.into()), Direction::Forward)?
+            .take(limit)
+        {
+            let ((next_token, _), block_token_transfers) = next?;
+            if next_token != rocks_token {
+                break;
+            }
+            token_transfers.extend(Vec::<TokenTransferMined>::from(block_token_transfers.into_inner()));
+        }
+        Ok(token_transfers)
+    }
+
+    pub fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>> {
+        let contract_creation = self.contract_creations.get(&address.into())?;
+        Ok(contract_creation.map(|value| value.into_inner().into()))
+    }
+
+    pub fn read_state_count(&self) -> Result<StateCount> {
+        let mut count = StateCount::default();
+        for next in self.accounts.iter_start() {
+            let (address, account) = next?;
+            let bytecode_size = account.into_inner().bytecode.map(|bytecode| bytecode.len()).unwrap_or_default();
+            count.count_account(address.into(), bytecode_size);
+        }
+        for next in self.account_slots.iter_start().keys() {
+            let (address, _) = next?;
+            count.count_slot(address.into());
+        }
+        Ok(count)
+    }
+
+    pub fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>> {
+        Ok(self.gas_usage.get(&())?.map(|usage| usage.into_inner().into()))
+    }
+
+    pub fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        self.gas_usage.prepare_batch_insertion([((), usage.into())], &mut batch)?;
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    pub fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>> {
+        Ok(self.finalized_block_number.get(&())?.map(|number| number.into_inner().into()))
+    }
+
+    pub fn save_finalized_block_number(&self, number: BlockNumber) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        self.finalized_block_number
+            .prepare_batch_insertion([((), BlockNumberRocksdb::from(number).into())], &mut batch)?;
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
     pub fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>> {
         if address.is_coinbase() || address.is_zero() {
             return Ok(None);
@@ -376,6 +638,12 @@ impl RocksStorageState {
 
         let block = match selection {
             BlockFilter::Latest | BlockFilter::Pending => self.blocks_by_number.last_value(),
+            // the safe block depends on the mined block number, that is tracked by the permanent storage
+            BlockFilter::Safe => bail!("safe block must be resolved to a number before reading from rocks state"),
+            BlockFilter::Finalized => match self.read_finalized_block_number()? {
+                Some(number) => self.blocks_by_number.get(&number.into()),
+                None => Ok(None),
+            },
             BlockFilter::Earliest => self.blocks_by_number.first_value(),
             BlockFilter::Number(block_number) => self.blocks_by_number.get(&block_number.into()),
             BlockFilter::Hash(block_hash) =>
@@ -443,6 +711,34 @@ impl RocksStorageState {
         self.logs.prepare_batch_insertion(logs_batch, batch)?;
 
         let number = block.number();
+
+        // balance changes are grouped by account, keeping the execution order inside the block
+        let balance_changes_batch = block
+            .balance_changes()
+            .into_iter()
+            .into_group_map_by(|balance_change| balance_change.address)
+            .into_iter()
+            .map(|(address, balance_changes)| ((address.into(), number.into()), balance_changes.into()));
+        self.balance_changes.prepare_batch_insertion(balance_changes_batch, batch)?;
+
+        // internal transfers are saved for the whole block and for each account that sent or received value
+        let internal_transfers = block.internal_transfers();
+        if not(internal_transfers.is_empty()) {
+            self.internal_transfers
+                .prepare_batch_insertion([(number.into(), internal_transfers.into())], batch)?;
+            let internal_transfers_by_address_batch = block
+                .internal_transfers_by_account()
+                .into_iter()
+                .map(|(address, internal_transfers)| ((address.into(), number.into()), internal_transfers.into()));
+            self.internal_transfers_by_address
+                .prepare_batch_insertion(internal_transfers_by_address_batch, batch)?;
+        }
+
+        self.prepare_batch_with_block_indexes(&block, batch)?;
+
+        // state diff is saved even if empty, so blocks without changes are not confused with blocks saved before diffs were recorded
+        self.state_diffs.prepare_batch_insertion([(number.into(), block.state_diff().into())], batch)?;
+
         let block_hash = block.hash();
 
         // this is an optimization, instead of saving the entire block into the database,
@@ -467,6 +763,60 @@ impl RocksStorageState {
         Ok(())
     }
 
+    /// Indexes the token transfers and deployed contracts of a block, which are derived only from data kept in the saved block.
+    fn prepare_batch_with_block_indexes(&self, block: &Block, batch: &mut WriteBatch) -> Result<()> {
+        let number = block.number();
+
+        // token transfers are indexed for each account that sent or received tokens and for each token
+        if self.index_token_transfers {
+            let token_transfers_by_account_batch = block
+                .token_transfers_by_account()
+                .into_iter()
+                .map(|(address, token_transfers)| ((address.into(), number.into()), token_transfers.into()));
+            self.token_transfers_by_account
+                .prepare_batch_insertion(token_transfers_by_account_batch, batch)?;
+            let token_transfers_by_token_batch = block
+                .token_transfers()
+                .into_iter()
+                .into_group_map_by(|transfer| transfer.transfer.token)
+                .into_iter()
+                .map(|(token, token_transfers)| ((token.into(), number.into()), token_transfers.into()));
+            self.token_transfers_by_token.prepare_batch_insertion(token_transfers_by_token_batch, batch)?;
+        }
+
+        // deployed contracts are indexed by address
+        let contract_creations_batch = block
+            .contract_creations()
+            .into_iter()
+            .map(|contract_creation| (contract_creation.address.into(), contract_creation.into()));
+        self.contract_creations.prepare_batch_insertion(contract_creations_batch, batch)?;
+
+        Ok(())
+    }
+
+    /// Rebuilds the token transfers and contract creations indexes of all saved blocks.
+    ///
+    /// Entries are overwritten with the same values, so it can run again if interrupted.
+    pub fn backfill_block_indexes(&self) -> Result<()> {
+        const BLOCKS_PER_BATCH: usize = 1_000;
+
+        let mut batch = WriteBatch::default();
+        let mut blocks_in_batch = 0;
+        for next in self.blocks_by_number.iter_start() {
+            let (_, block) = next?;
+            let block: Block = block.into_inner().into();
+            self.prepare_batch_with_block_indexes(&block, &mut batch)?;
+
+            blocks_in_batch += 1;
+            if blocks_in_batch == BLOCKS_PER_BATCH {
+                tracing::info!(block_number = %block.number(), "backfilled block indexes");
+                self.write_in_batch_for_multiple_cfs(std::mem::take(&mut batch))?;
+                blocks_in_batch = 0;
+            }
+        }
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
     /// Write to DB in a batch
     pub fn write_in_batch_for_multiple_cfs(&self, batch: WriteBatch) -> Result<()> {
         tracing::debug!("writing batch");
@@ -489,6 +839,110 @@ impl RocksStorageState {
             })
     }
 
+    /// Removes all blocks after the specified block and restores accounts and slots to the values they had at it.
+    ///
+    /// Account and slot history and the indexes keyed by account are scanned entirely, so it is intended only for offline maintenance.
+    pub fn reset_to_block(&self, number: BlockNumber) -> Result<()> {
+        let rocks_number: BlockNumberRocksdb = number.into();
+        let mut batch = WriteBatch::default();
+
+        // blocks and the indexes derived from their transactions
+        for next in self.blocks_by_number.iter_from(number.next_block_number().into(), Direction::Forward)? {
+            let (block_number, block) = next?;
+            let block: Block = block.into_inner().into();
+
+            self.blocks_by_number.prepare_batch_deletion([block_number], &mut batch)?;
+            self.blocks_by_hash.prepare_batch_deletion([block.hash().into()], &mut batch)?;
+            self.internal_transfers.prepare_batch_deletion([block_number], &mut batch)?;
+            self.state_diffs.prepare_batch_deletion([block_number], &mut batch)?;
+            for tx in &block.transactions {
+                self.transactions.prepare_batch_deletion([tx.input.hash.into()], &mut batch)?;
+                self.logs
+                    .prepare_batch_deletion(tx.logs.iter().map(|log| (tx.input.hash.into(), log.log_index.into())), &mut batch)?;
+            }
+            self.contract_creations
+                .prepare_batch_deletion(block.contract_creations().into_iter().map(|creation| creation.address.into()), &mut batch)?;
+        }
+
+        // indexes keyed by account and block
+        prepare_batch_deletion_after(&self.balance_changes, rocks_number, &mut batch)?;
+        prepare_batch_deletion_after(&self.internal_transfers_by_address, rocks_number, &mut batch)?;
+        prepare_batch_deletion_after(&self.token_transfers_by_account, rocks_number, &mut batch)?;
+        prepare_batch_deletion_after(&self.token_transfers_by_token, rocks_number, &mut batch)?;
+
+        // accounts changed after the block are restored from their history
+        let mut reset_accounts = HashSet::new();
+        for next in self.accounts_history.iter_start().keys() {
+            let (address, block_number) = next?;
+            if block_number > rocks_number {
+                self.accounts_history.prepare_batch_deletion([(address, block_number)], &mut batch)?;
+                reset_accounts.insert(address);
+            }
+        }
+        for address in reset_accounts {
+            match self.read_account(address.into(), PointInTime::MinedPast(number))? {
+                Some(account) => self
+                    .accounts
+                    .prepare_batch_insertion([(address, AccountRocksdb::from(account).into())], &mut batch)?,
+                None => self.accounts.prepare_batch_deletion([address], &mut batch)?,
+            }
+        }
+
+        // slots changed after the block are restored from their history
+        let mut reset_slots = HashSet::new();
+        for next in self.account_slots_history.iter_start().keys() {
+            let (address, index, block_number) = next?;
+            if block_number > rocks_number {
+                self.account_slots_history
+                    .prepare_batch_deletion([(address, index, block_number)], &mut batch)?;
+                reset_slots.insert((address, index));
+            }
+        }
+        for (address, index) in reset_slots {
+            match self.read_slot(address.into(), index.into(), PointInTime::MinedPast(number))? {
+                Some(slot) => self
+                    .account_slots
+                    .prepare_batch_insertion([((address, index), SlotValueRocksdb::from(slot.value).into())], &mut batch)?,
+                None => self.account_slots.prepare_batch_deletion([(address, index)], &mut batch)?,
+            }
+        }
+
+        // gas usage aggregated over removed blocks is discarded and aggregated again when the node starts
+        if let Some(usage) = self.read_gas_usage()? {
+            if usage.last_block.is_some_and(|last_block| last_block > number) {
+                self.gas_usage.prepare_batch_deletion([()], &mut batch)?;
+            }
+        }
+
+        // finalized blocks are removed like the others, so the finalized block number cannot point after the last block
+        if self.read_finalized_block_number()?.is_some_and(|finalized| finalized > number) {
+            self.finalized_block_number.prepare_batch_insertion([((), rocks_number.into())], &mut batch)?;
+        }
+
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    /// Compacts all column families, reclaiming space of deleted and overwritten values.
+    pub fn compact(&self) {
+        self.accounts.compact();
+        self.accounts_history.compact();
+        self.account_slots.compact();
+        self.account_slots_history.compact();
+        self.transactions.compact();
+        self.blocks_by_number.compact();
+        self.blocks_by_hash.compact();
+        self.logs.compact();
+        self.balance_changes.compact();
+        self.internal_transfers.compact();
+        self.internal_transfers_by_address.compact();
+        self.token_transfers_by_account.compact();
+        self.token_transfers_by_token.compact();
+        self.contract_creations.compact();
+        self.state_diffs.compact();
+        self.gas_usage.compact();
+        self.finalized_block_number.compact();
+    }
+
     /// Writes slots to state (does not write to slot history)
     #[cfg(feature = "dev")]
     pub fn write_slots(&self, slots: Vec<(Address, Slot)>) -> Result<()> {
@@ -521,6 +975,17 @@ impl RocksStorageState {
         self.blocks_by_hash.clear().context("when clearing blocks_by_hash")?;
         self.blocks_by_number.clear().context("when clearing blocks_by_number")?;
         self.logs.clear().context("when clearing logs")?;
+        self.balance_changes.clear().context("when clearing balance_changes")?;
+        self.internal_transfers.clear().context("when clearing internal_transfers")?;
+        self.internal_transfers_by_address
+            .clear()
+            .context("when clearing internal_transfers_by_address")?;
+        self.token_transfers_by_account.clear().context("when clearing token_transfers_by_account")?;
+        self.token_transfers_by_token.clear().context("when clearing token_transfers_by_token")?;
+        self.contract_creations.clear().context("when clearing contract_creations")?;
+        self.state_diffs.clear().context("when clearing state_diffs")?;
+        self.gas_usage.clear().context("when clearing gas_usage")?;
+        self.finalized_block_number.clear().context("when clearing finalized_block_number")?;
         Ok(())
     }
 }
@@ -587,7 +1052,16 @@ impl RocksStorageState {
         self.blocks_by_hash.export_metrics();
         self.blocks_by_number.export_metrics();
         self.logs.export_metrics();
+        self.balance_changes.export_metrics();
+        self.internal_transfers.export_metrics();
+        self.internal_transfers_by_address.export_metrics();
+        self.token_transfers_by_account.export_metrics();
+        self.token_transfers_by_token.export_metrics();
+        self.contract_creations.export_metrics();
+        self.state_diffs.export_metrics();
         self.transactions.export_metrics();
+        self.gas_usage.export_metrics();
+        self.finalized_block_number.export_metrics();
         Ok(())
     }
 
@@ -613,6 +1087,10 @@ impl RocksStorageState {
 
 impl Drop for RocksStorageState {
     fn drop(&mut self) {
+        if self.read_only {
+            return;
+        }
+
         let mut options = WaitForCompactOptions::default();
         // if background jobs are paused, it makes no sense to keep waiting indefinitely
         options.set_abort_on_pause(true);
@@ -776,4 +1254,155 @@ mod tests {
         let history = state.read_all_historical_accounts().unwrap();
         assert_eq!(history.len(), 3);
     }
+
+    #[test]
+    fn test_read_account_at_past_blocks_boundaries() {
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        let address: Address = Faker.fake();
+
+        let change = |nonce: u64, balance: u64| ExecutionAccountChanges {
+            new_account: false,
+            address,
+            nonce: ExecutionValueChange::from_modified(nonce.into()),
+            balance: ExecutionValueChange::from_modified(balance.into()),
+            bytecode: ExecutionValueChange::from_original(None),
+            code_hash: Faker.fake(),
+            slots: HashMap::new(),
+        };
+
+        // account changes at blocks 2 and 5, each block written separately like in production
+        for (block_number, nonce, balance) in [(2u64, 1u64, 100u64), (5, 2, 50)] {
+            let mut batch = WriteBatch::default();
+            state
+                .prepare_batch_with_execution_changes([change(nonce, balance)], block_number.into(), &mut batch)
+                .unwrap();
+            state.write_in_batch_for_multiple_cfs(batch).unwrap();
+        }
+
+        let read_at = |number: u64| state.read_account(address, PointInTime::MinedPast(number.into())).unwrap();
+
+        // before the first change the account does not exist
+        assert!(read_at(1).is_none());
+
+        // changes are visible from the block where they happened, until the next change
+        for (number, nonce, balance) in [(2u64, 1u64, 100u64), (4, 1, 100), (5, 2, 50), (10, 2, 50)] {
+            let account = read_at(number).unwrap();
+            assert_eq!(account.nonce, nonce.into(), "nonce at block {}", number);
+            assert_eq!(account.balance, balance.into(), "balance at block {}", number);
+        }
+
+        // current state matches the last change
+        let account = state.read_account(address, PointInTime::Mined).unwrap().unwrap();
+        assert_eq!(account.nonce, 2u64.into());
+        assert_eq!(account.balance, 50u64.into());
+    }
+
+    #[test]
+    fn test_reset_to_block() {
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        let address: Address = Faker.fake();
+
+        let change = |nonce: u64| ExecutionAccountChanges {
+            new_account: false,
+            address,
+            nonce: ExecutionValueChange::from_modified(nonce.into()),
+            balance: ExecutionValueChange::from_original(Faker.fake()),
+            bytecode: ExecutionValueChange::from_original(None),
+            code_hash: Faker.fake(),
+            slots: HashMap::new(),
+        };
+
+        // blocks 0 to 5, with account changes at blocks 2 and 5
+        for number in 0..=5u64 {
+            let mut batch = WriteBatch::default();
+            if number == 2 || number == 5 {
+                state.prepare_batch_with_execution_changes([change(number)], number.into(), &mut batch).unwrap();
+            }
+            state.write_in_batch_for_multiple_cfs(batch).unwrap();
+            let block = Block {
+                header: BlockHeader {
+                    number: number.into(),
+                    ..Faker.fake()
+                },
+                transactions: vec![],
+            };
+            state.save_block(block).unwrap();
+        }
+
+        state.reset_to_block(3.into()).unwrap();
+
+        // blocks after the reset block are removed
+        assert!(state.read_block(BlockFilter::Number(3.into())).unwrap().is_some());
+        assert!(state.read_block(BlockFilter::Number(4.into())).unwrap().is_none());
+        assert_eq!(state.read_block(BlockFilter::Latest).unwrap().unwrap().number(), 3.into());
+
+        // current state and history are restored to the reset block
+        let account = state.read_account(address, PointInTime::Mined).unwrap().unwrap();
+        assert_eq!(account.nonce, 2u64.into());
+        let account = state.read_account(address, PointInTime::MinedPast(5.into())).unwrap().unwrap();
+        assert_eq!(account.nonce, 2u64.into());
+    }
+
+    #[test]
+    fn test_backfill_block_indexes() {
+        use ethereum_types::H256;
+        use ethers_core::utils::keccak256;
+
+        use crate::eth::primitives::Bytes;
+        use crate::eth::primitives::Log;
+        use crate::eth::primitives::LogTopic;
+        use crate::eth::primitives::UnixTime;
+
+        let (state, _test_dir) = RocksStorageState::new_in_testdir().unwrap();
+        let (token, contract, from, to): (Address, Address, Address, Address) = (Faker.fake(), Faker.fake(), Faker.fake(), Faker.fake());
+
+        // block with a contract deployment and a token transfer
+        let mut block = Block::new(1u64.into(), UnixTime::from(1u64));
+        let mut tx: TransactionMined = Faker.fake();
+        tx.block_number = block.number();
+        tx.block_hash = block.hash();
+        tx.execution.changes.clear();
+        tx.execution.deployed_contract_address = Some(contract);
+        tx.logs = vec![LogMined {
+            log: Log {
+                address: token,
+                topic0: Some(LogTopic::new(H256::from(keccak256("Transfer(address,address,uint256)")))),
+                topic1: Some(from.into()),
+                topic2: Some(to.into()),
+                topic3: None,
+                data: Bytes::from(H256::from_low_u64_be(100).as_bytes().to_vec()),
+            },
+            transaction_hash: tx.input.hash,
+            transaction_index: tx.transaction_index,
+            log_index: 0u64.into(),
+            block_number: block.number(),
+            block_hash: block.hash(),
+        }];
+        block.transactions.push(tx);
+        state.save_block(block).unwrap();
+
+        // databases before the indexes existed have only the block
+        let number: BlockNumberRocksdb = 1u64.into();
+        let mut batch = WriteBatch::default();
+        state
+            .token_transfers_by_account
+            .prepare_batch_deletion([(from.into(), number), (to.into(), number)], &mut batch)
+            .unwrap();
+        state
+            .token_transfers_by_token
+            .prepare_batch_deletion([(token.into(), number)], &mut batch)
+            .unwrap();
+        state.contract_creations.prepare_batch_deletion([contract.into()], &mut batch).unwrap();
+        state.write_in_batch_for_multiple_cfs(batch).unwrap();
+        assert!(state.read_token_transfers_by_token(token, 0u64.into(), 10).unwrap().is_empty());
+        assert!(state.read_contract_creation(contract).unwrap().is_none());
+
+        // backfill is idempotent
+        state.backfill_block_indexes().unwrap();
+        state.backfill_block_indexes().unwrap();
+        assert!(state.read_contract_creation(contract).unwrap().is_some());
+        assert_eq!(state.read_token_transfers_by_account(from, 0u64.into(), 10).unwrap().len(), 1);
+        assert_eq!(state.read_token_transfers_by_account(to, 0u64.into(), 10).unwrap().len(), 1);
+        assert_eq!(state.read_token_transfers_by_token(token, 0u64.into(), 10).unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,60 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::block_number::BlockNumberRocksdb;
+use super::hash::HashRocksdb;
+use super::index::IndexRocksdb;
+use super::wei::WeiRocksdb;
+use crate::eth::primitives::BalanceChange;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct BalanceChangeRocksdb {
+    pub address: AddressRocksdb,
+    pub block_number: BlockNumberRocksdb,
+    pub transaction_hash: HashRocksdb,
+    pub transaction_index: IndexRocksdb,
+    pub previous_balance: WeiRocksdb,
+    pub balance: WeiRocksdb,
+}
+
+impl From<BalanceChange> for BalanceChangeRocksdb {
+    fn from(item: BalanceChange) -> Self {
+        Self {
+            address: item.address.into(),
+            block_number: item.block_number.into(),
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            previous_balance: item.previous_balance.into(),
+            balance: item.balance.into(),
+        }
+    }
+}
+
+impl From<BalanceChangeRocksdb> for BalanceChange {
+    fn from(item: BalanceChangeRocksdb) -> Self {
+        Self {
+            address: item.address.into(),
+            block_number: item.block_number.into(),
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            previous_balance: item.previous_balance.into(),
+            balance: item.balance.into(),
+        }
+    }
+}
+
+/// Balance changes of an account in a single block, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct BalanceChangesRocksdb(pub Vec<BalanceChangeRocksdb>);
+
+impl From<Vec<BalanceChange>> for BalanceChangesRocksdb {
+    fn from(items: Vec<BalanceChange>) -> Self {
+        Self(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<BalanceChangesRocksdb> for Vec<BalanceChange> {
+    fn from(items: BalanceChangesRocksdb) -> Self {
+        items.0.into_iter().map(Into::into).collect()
+    }
+}
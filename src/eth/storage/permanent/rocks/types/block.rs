@@ -4,13 +4,13 @@ use super::address::AddressRocksdb;
 use super::block_header::BlockHeaderRocksdb;
 use super::block_number::BlockNumberRocksdb;
 use super::hash::HashRocksdb;
+use super::transaction_mined::into_block_transactions;
 use super::transaction_mined::TransactionMinedRocksdb;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
-use crate::eth::primitives::TransactionMined;
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
 pub struct BlockRocksdb {
@@ -69,7 +69,7 @@ impl From<BlockRocksdb> for Block {
                 total_difficulty: item.header.total_difficulty.into(),
                 nonce: item.header.nonce.into(),
             },
-            transactions: item.transactions.into_iter().map(TransactionMined::from).collect(),
+            transactions: into_block_transactions(item.transactions).collect(),
         }
     }
 }
@@ -33,12 +33,12 @@ impl Debug for BytesRocksdb {
 
 impl From<Bytes> for BytesRocksdb {
     fn from(value: Bytes) -> Self {
-        Self(value.0)
+        Self(value.into())
     }
 }
 
 impl From<BytesRocksdb> for Bytes {
     fn from(value: BytesRocksdb) -> Self {
-        Self(value.0)
+        value.0.into()
     }
 }
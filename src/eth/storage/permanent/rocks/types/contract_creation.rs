@@ -0,0 +1,40 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::block_number::BlockNumberRocksdb;
+use super::hash::HashRocksdb;
+use super::index::IndexRocksdb;
+use crate::eth::primitives::ContractCreation;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct ContractCreationRocksdb {
+    pub address: AddressRocksdb,
+    pub creator: AddressRocksdb,
+    pub transaction_hash: HashRocksdb,
+    pub transaction_index: IndexRocksdb,
+    pub block_number: BlockNumberRocksdb,
+}
+
+impl From<ContractCreation> for ContractCreationRocksdb {
+    fn from(item: ContractCreation) -> Self {
+        Self {
+            address: item.address.into(),
+            creator: item.creator.into(),
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
+
+impl From<ContractCreationRocksdb> for ContractCreation {
+    fn from(item: ContractCreationRocksdb) -> Self {
+        Self {
+            address: item.address.into(),
+            creator: item.creator.into(),
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
@@ -46,6 +46,7 @@ impl From<ExecutionRocksdb> for EvmExecution {
             gas: item.gas.into(),
             changes: HashMap::default(),
             deployed_contract_address: item.deployed_contract_address.map_into(),
+            internal_transfers: Vec::new(),
         }
     }
 }
@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::block_number::BlockNumberRocksdb;
+use crate::eth::analytics::FunctionGasUsage;
+use crate::eth::analytics::GasUsage;
+use crate::eth::analytics::GasUsageSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct GasUsageSnapshotRocksdb {
+    pub first_block: Option<BlockNumberRocksdb>,
+    pub last_block: Option<BlockNumberRocksdb>,
+    pub blocks: u64,
+    pub usage: GasUsageRocksdb,
+    pub functions: Vec<FunctionGasUsageRocksdb>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct GasUsageRocksdb {
+    pub transactions: u64,
+    pub gas_used: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct FunctionGasUsageRocksdb {
+    pub contract: AddressRocksdb,
+    pub selector: [u8; 4],
+    pub usage: GasUsageRocksdb,
+}
+
+impl From<GasUsageSnapshot> for GasUsageSnapshotRocksdb {
+    fn from(item: GasUsageSnapshot) -> Self {
+        Self {
+            first_block: item.first_block.map(Into::into),
+            last_block: item.last_block.map(Into::into),
+            blocks: item.blocks,
+            usage: item.usage.into(),
+            functions: item.functions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GasUsageSnapshotRocksdb> for GasUsageSnapshot {
+    fn from(item: GasUsageSnapshotRocksdb) -> Self {
+        Self {
+            first_block: item.first_block.map(Into::into),
+            last_block: item.last_block.map(Into::into),
+            blocks: item.blocks,
+            usage: item.usage.into(),
+            functions: item.functions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GasUsage> for GasUsageRocksdb {
+    fn from(item: GasUsage) -> Self {
+        Self {
+            transactions: item.transactions,
+            gas_used: item.gas_used,
+        }
+    }
+}
+
+impl From<GasUsageRocksdb> for GasUsage {
+    fn from(item: GasUsageRocksdb) -> Self {
+        Self {
+            transactions: item.transactions,
+            gas_used: item.gas_used,
+        }
+    }
+}
+
+impl From<FunctionGasUsage> for FunctionGasUsageRocksdb {
+    fn from(item: FunctionGasUsage) -> Self {
+        Self {
+            contract: item.contract.into(),
+            selector: item.selector,
+            usage: item.usage.into(),
+        }
+    }
+}
+
+impl From<FunctionGasUsageRocksdb> for FunctionGasUsage {
+    fn from(item: FunctionGasUsageRocksdb) -> Self {
+        Self {
+            contract: item.contract.into(),
+            selector: item.selector,
+            usage: item.usage.into(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::block_number::BlockNumberRocksdb;
+use super::hash::HashRocksdb;
+use super::index::IndexRocksdb;
+use super::wei::WeiRocksdb;
+use crate::eth::primitives::InternalTransfer;
+use crate::eth::primitives::InternalTransferKind;
+use crate::eth::primitives::InternalTransferMined;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub enum InternalTransferKindRocksdb {
+    Call,
+    Create,
+    Selfdestruct,
+}
+
+impl From<InternalTransferKind> for InternalTransferKindRocksdb {
+    fn from(item: InternalTransferKind) -> Self {
+        match item {
+            InternalTransferKind::Call => Self::Call,
+            InternalTransferKind::Create => Self::Create,
+            InternalTransferKind::Selfdestruct => Self::Selfdestruct,
+        }
+    }
+}
+
+impl From<InternalTransferKindRocksdb> for InternalTransferKind {
+    fn from(item: InternalTransferKindRocksdb) -> Self {
+        match item {
+            InternalTransferKindRocksdb::Call => Self::Call,
+            InternalTransferKindRocksdb::Create => Self::Create,
+            InternalTransferKindRocksdb::Selfdestruct => Self::Selfdestruct,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct InternalTransferMinedRocksdb {
+    pub kind: InternalTransferKindRocksdb,
+    pub from: AddressRocksdb,
+    pub to: AddressRocksdb,
+    pub value: WeiRocksdb,
+    pub depth: u64,
+    pub transaction_hash: HashRocksdb,
+    pub transaction_index: IndexRocksdb,
+    pub block_number: BlockNumberRocksdb,
+}
+
+impl From<InternalTransferMined> for InternalTransferMinedRocksdb {
+    fn from(item: InternalTransferMined) -> Self {
+        Self {
+            kind: item.transfer.kind.into(),
+            from: item.transfer.from.into(),
+            to: item.transfer.to.into(),
+            value: item.transfer.value.into(),
+            depth: item.transfer.depth as u64,
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
+
+impl From<InternalTransferMinedRocksdb> for InternalTransferMined {
+    fn from(item: InternalTransferMinedRocksdb) -> Self {
+        Self {
+            transfer: InternalTransfer {
+                kind: item.kind.into(),
+                from: item.from.into(),
+                to: item.to.into(),
+                value: item.value.into(),
+                depth: item.depth as usize,
+            },
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
+
+/// Internal transfers of a block or of an account in a single block, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct InternalTransfersRocksdb(pub Vec<InternalTransferMinedRocksdb>);
+
+impl From<Vec<InternalTransferMined>> for InternalTransfersRocksdb {
+    fn from(items: Vec<InternalTransferMined>) -> Self {
+        Self(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<InternalTransfersRocksdb> for Vec<InternalTransferMined> {
+    fn from(items: InternalTransfersRocksdb) -> Self {
+        items.0.into_iter().map(Into::into).collect()
+    }
+}
@@ -1,16 +1,20 @@
 mod account;
 mod address;
+mod balance_change;
 mod block;
 mod block_header;
 mod block_number;
 mod bytes;
 mod chain_id;
+mod contract_creation;
 mod difficulty;
 mod execution;
 mod execution_result;
 mod gas;
+mod gas_usage;
 mod hash;
 mod index;
+mod internal_transfer;
 mod log;
 mod log_mined;
 mod logs_bloom;
@@ -18,6 +22,8 @@ mod miner_nonce;
 mod nonce;
 mod size;
 mod slot;
+mod state_diff;
+mod token_transfer;
 mod transaction_input;
 mod transaction_mined;
 mod unix_time;
@@ -25,17 +31,36 @@ mod wei;
 
 pub use account::AccountRocksdb;
 pub use address::AddressRocksdb;
+pub use balance_change::BalanceChangeRocksdb;
+pub use balance_change::BalanceChangesRocksdb;
 pub use block::BlockRocksdb;
 pub use block_number::BlockNumberRocksdb;
+pub use bytes::BytesRocksdb;
+pub use contract_creation::ContractCreationRocksdb;
+pub use gas_usage::FunctionGasUsageRocksdb;
+pub use gas_usage::GasUsageRocksdb;
+pub use gas_usage::GasUsageSnapshotRocksdb;
 pub use hash::HashRocksdb;
 pub use index::IndexRocksdb;
+pub use internal_transfer::InternalTransferKindRocksdb;
+pub use internal_transfer::InternalTransferMinedRocksdb;
+pub use internal_transfer::InternalTransfersRocksdb;
+pub use nonce::NonceRocksdb;
 pub use slot::SlotIndexRocksdb;
 pub use slot::SlotValueRocksdb;
+pub use state_diff::AccountChangesRocksdb;
+pub use state_diff::StateDiffRocksdb;
+pub use state_diff::ValueChangeRocksdb;
+pub use token_transfer::TokenStandardRocksdb;
+pub use token_transfer::TokenTransferMinedRocksdb;
+pub use token_transfer::TokenTransfersRocksdb;
+pub use transaction_mined::into_block_transactions;
 pub use transaction_mined::TransactionMinedRocksdb;
 pub use unix_time::UnixTimeRocksdb;
 
 #[cfg(test)]
 mod tests {
+    use balance_change::BalanceChangeRocksdb;
     use block_header::BlockHeaderRocksdb;
     use bytes::BytesRocksdb;
     use chain_id::ChainIdRocksdb;
@@ -43,11 +68,16 @@ mod tests {
     use execution::ExecutionRocksdb;
     use execution_result::ExecutionResultRocksdb;
     use gas::GasRocksdb;
+    use internal_transfer::InternalTransferKindRocksdb;
+    use internal_transfer::InternalTransferMinedRocksdb;
     use log_mined::LogMinedRockdb;
     use logs_bloom::LogsBloomRocksdb;
     use miner_nonce::MinerNonceRocksdb;
     use nonce::NonceRocksdb;
     use size::SizeRocksdb;
+    use state_diff::AccountChangesRocksdb;
+    use token_transfer::TokenStandardRocksdb;
+    use token_transfer::TokenTransferMinedRocksdb;
     use transaction_input::TransactionInputRocksdb;
     use transaction_mined::TransactionMinedRocksdb;
     use unix_time::UnixTimeRocksdb;
@@ -57,19 +87,27 @@ mod tests {
     use super::*;
     use crate::gen_test_bincode;
 
+    gen_test_bincode!(AccountChangesRocksdb);
     gen_test_bincode!(AccountRocksdb);
     gen_test_bincode!(AddressRocksdb);
+    gen_test_bincode!(BalanceChangeRocksdb);
+    gen_test_bincode!(BalanceChangesRocksdb);
     gen_test_bincode!(BlockHeaderRocksdb);
     gen_test_bincode!(BlockNumberRocksdb);
     gen_test_bincode!(BlockRocksdb);
     gen_test_bincode!(BytesRocksdb);
     gen_test_bincode!(ChainIdRocksdb);
+    gen_test_bincode!(ContractCreationRocksdb);
     gen_test_bincode!(DifficultyRocksdb);
     gen_test_bincode!(ExecutionResultRocksdb);
     gen_test_bincode!(ExecutionRocksdb);
     gen_test_bincode!(GasRocksdb);
+    gen_test_bincode!(GasUsageSnapshotRocksdb);
     gen_test_bincode!(HashRocksdb);
     gen_test_bincode!(IndexRocksdb);
+    gen_test_bincode!(InternalTransferKindRocksdb);
+    gen_test_bincode!(InternalTransferMinedRocksdb);
+    gen_test_bincode!(InternalTransfersRocksdb);
     gen_test_bincode!(LogMinedRockdb);
     gen_test_bincode!(LogRocksdb);
     gen_test_bincode!(LogsBloomRocksdb);
@@ -78,6 +116,10 @@ mod tests {
     gen_test_bincode!(SizeRocksdb);
     gen_test_bincode!(SlotIndexRocksdb);
     gen_test_bincode!(SlotValueRocksdb);
+    gen_test_bincode!(StateDiffRocksdb);
+    gen_test_bincode!(TokenStandardRocksdb);
+    gen_test_bincode!(TokenTransferMinedRocksdb);
+    gen_test_bincode!(TokenTransfersRocksdb);
     gen_test_bincode!(TransactionInputRocksdb);
     gen_test_bincode!(TransactionMinedRocksdb);
     gen_test_bincode!(UnixTimeRocksdb);
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use itertools::Itertools;
+
+use super::address::AddressRocksdb;
+use super::bytes::BytesRocksdb;
+use super::nonce::NonceRocksdb;
+use super::slot::SlotIndexRocksdb;
+use super::slot::SlotValueRocksdb;
+use super::wei::WeiRocksdb;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionValueChange;
+use crate::eth::primitives::Slot;
+use crate::ext::OptionExt;
+
+/// Original and modified values of an account field, any of them may be not set.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct ValueChangeRocksdb<T> {
+    pub original: Option<T>,
+    pub modified: Option<T>,
+}
+
+impl<T> ValueChangeRocksdb<T> {
+    fn from_change<U>(change: ExecutionValueChange<U>, convert: impl Fn(U) -> T) -> Self
+    where
+        U: PartialEq + serde::Serialize,
+    {
+        let (original, modified) = change.take_both();
+        Self {
+            original: original.map(&convert),
+            modified: modified.map(&convert),
+        }
+    }
+
+    fn into_change<U>(self, convert: impl Fn(T) -> U) -> ExecutionValueChange<U>
+    where
+        U: PartialEq + serde::Serialize,
+    {
+        ExecutionValueChange::from_parts(self.original.map(&convert), self.modified.map(&convert))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct AccountChangesRocksdb {
+    pub new_account: bool,
+    pub address: AddressRocksdb,
+    pub nonce: ValueChangeRocksdb<NonceRocksdb>,
+    pub balance: ValueChangeRocksdb<WeiRocksdb>,
+    pub bytecode: ValueChangeRocksdb<Option<BytesRocksdb>>,
+    pub code_hash: [u8; 32],
+    pub slots: Vec<(SlotIndexRocksdb, ValueChangeRocksdb<SlotValueRocksdb>)>,
+}
+
+impl From<ExecutionAccountChanges> for AccountChangesRocksdb {
+    fn from(item: ExecutionAccountChanges) -> Self {
+        Self {
+            new_account: item.new_account,
+            address: item.address.into(),
+            nonce: ValueChangeRocksdb::from_change(item.nonce, Into::into),
+            balance: ValueChangeRocksdb::from_change(item.balance, Into::into),
+            bytecode: ValueChangeRocksdb::from_change(item.bytecode, |bytecode| bytecode.map_into()),
+            code_hash: item.code_hash.0 .0,
+            slots: item
+                .slots
+                .into_iter()
+                .sorted_by_key(|(index, _)| *index)
+                .map(|(index, slot)| (index.into(), ValueChangeRocksdb::from_change(slot, |slot| slot.value.into())))
+                .collect(),
+        }
+    }
+}
+
+impl From<AccountChangesRocksdb> for ExecutionAccountChanges {
+    fn from(item: AccountChangesRocksdb) -> Self {
+        Self {
+            new_account: item.new_account,
+            address: item.address.into(),
+            nonce: item.nonce.into_change(Into::into),
+            balance: item.balance.into_change(Into::into),
+            bytecode: item.bytecode.into_change(|bytecode| bytecode.map_into()),
+            code_hash: CodeHash::from(item.code_hash),
+            slots: item
+                .slots
+                .into_iter()
+                .map(|(index, slot)| (index.into(), slot.into_change(|value| Slot::new(index.into(), value.into()))))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+}
+
+/// Compacted account changes of a single block, sorted by address.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct StateDiffRocksdb(pub Vec<AccountChangesRocksdb>);
+
+impl From<Vec<ExecutionAccountChanges>> for StateDiffRocksdb {
+    fn from(items: Vec<ExecutionAccountChanges>) -> Self {
+        Self(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<StateDiffRocksdb> for Vec<ExecutionAccountChanges> {
+    fn from(items: StateDiffRocksdb) -> Self {
+        items.0.into_iter().map(Into::into).collect()
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::block_number::BlockNumberRocksdb;
+use super::hash::HashRocksdb;
+use super::index::IndexRocksdb;
+use crate::eth::primitives::TokenStandard;
+use crate::eth::primitives::TokenTransfer;
+use crate::eth::primitives::TokenTransferMined;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub enum TokenStandardRocksdb {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+impl From<TokenStandard> for TokenStandardRocksdb {
+    fn from(item: TokenStandard) -> Self {
+        match item {
+            TokenStandard::Erc20 => Self::Erc20,
+            TokenStandard::Erc721 => Self::Erc721,
+            TokenStandard::Erc1155 => Self::Erc1155,
+        }
+    }
+}
+
+impl From<TokenStandardRocksdb> for TokenStandard {
+    fn from(item: TokenStandardRocksdb) -> Self {
+        match item {
+            TokenStandardRocksdb::Erc20 => Self::Erc20,
+            TokenStandardRocksdb::Erc721 => Self::Erc721,
+            TokenStandardRocksdb::Erc1155 => Self::Erc1155,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct TokenTransferMinedRocksdb {
+    pub standard: TokenStandardRocksdb,
+    pub token: AddressRocksdb,
+    pub from: AddressRocksdb,
+    pub to: AddressRocksdb,
+    pub token_id: Option<[u64; 4]>,
+    pub value: [u64; 4],
+    pub transaction_hash: HashRocksdb,
+    pub transaction_index: IndexRocksdb,
+    pub log_index: IndexRocksdb,
+    pub block_number: BlockNumberRocksdb,
+}
+
+impl From<TokenTransferMined> for TokenTransferMinedRocksdb {
+    fn from(item: TokenTransferMined) -> Self {
+        Self {
+            standard: item.transfer.standard.into(),
+            token: item.transfer.token.into(),
+            from: item.transfer.from.into(),
+            to: item.transfer.to.into(),
+            token_id: item.transfer.token_id.map(Into::into),
+            value: item.transfer.value.into(),
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            log_index: item.log_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
+
+impl From<TokenTransferMinedRocksdb> for TokenTransferMined {
+    fn from(item: TokenTransferMinedRocksdb) -> Self {
+        Self {
+            transfer: TokenTransfer {
+                standard: item.standard.into(),
+                token: item.token.into(),
+                from: item.from.into(),
+                to: item.to.into(),
+                token_id: item.token_id.map(Into::into),
+                value: item.value.into(),
+            },
+            transaction_hash: item.transaction_hash.into(),
+            transaction_index: item.transaction_index.into(),
+            log_index: item.log_index.into(),
+            block_number: item.block_number.into(),
+        }
+    }
+}
+
+/// Token transfers of an account or of a token in a single block, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct TokenTransfersRocksdb(pub Vec<TokenTransferMinedRocksdb>);
+
+impl From<Vec<TokenTransferMined>> for TokenTransfersRocksdb {
+    fn from(items: Vec<TokenTransferMined>) -> Self {
+        Self(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<TokenTransfersRocksdb> for Vec<TokenTransferMined> {
+    fn from(items: TokenTransfersRocksdb) -> Self {
+        items.0.into_iter().map(Into::into).collect()
+    }
+}
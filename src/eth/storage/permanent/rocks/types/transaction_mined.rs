@@ -6,6 +6,7 @@ use super::hash::HashRocksdb;
 use super::index::IndexRocksdb;
 use super::log_mined::LogMinedRockdb;
 use super::transaction_input::TransactionInputRocksdb;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::TransactionMined;
 
@@ -32,15 +33,28 @@ impl From<TransactionMined> for TransactionMinedRocksdb {
     }
 }
 
-impl From<TransactionMinedRocksdb> for TransactionMined {
-    fn from(item: TransactionMinedRocksdb) -> Self {
-        Self {
-            input: item.input.into(),
-            execution: item.execution.into(),
-            logs: item.logs.into_iter().map(LogMined::from).collect(),
-            transaction_index: item.transaction_index.into(),
-            block_number: item.block_number.into(),
-            block_hash: item.block_hash.into(),
+impl TransactionMinedRocksdb {
+    /// Converts to a mined transaction.
+    ///
+    /// The cumulative gas used is not stored because it can be derived from the previous transactions of the block.
+    pub fn into_transaction_mined(self, cumulative_gas_used: Gas) -> TransactionMined {
+        TransactionMined {
+            input: self.input.into(),
+            execution: self.execution.into(),
+            logs: self.logs.into_iter().map(LogMined::from).collect(),
+            transaction_index: self.transaction_index.into(),
+            cumulative_gas_used,
+            block_number: self.block_number.into(),
+            block_hash: self.block_hash.into(),
         }
     }
 }
+
+/// Converts the transactions of a block, accumulating the gas used in block order.
+pub fn into_block_transactions(transactions: Vec<TransactionMinedRocksdb>) -> impl Iterator<Item = TransactionMined> {
+    let mut cumulative_gas_used = 0u64;
+    transactions.into_iter().map(move |tx| {
+        cumulative_gas_used = cumulative_gas_used.saturating_add(Gas::from(tx.execution.gas.clone()).as_u64());
+        tx.into_transaction_mined(cumulative_gas_used.into())
+    })
+}
@@ -0,0 +1,258 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+use super::PermanentStorage;
+use super::PermanentStorageKind;
+use super::ReadStorage;
+use super::WriteStorage;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::PointInTime;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
+use crate::eth::primitives::TransactionMined;
+use crate::ext::DisplayExt;
+use crate::infra::metrics;
+
+/// Permanent storage wrapper that logs operations slower than a threshold with their parameters.
+pub struct SlowLogPermanentStorage {
+    inner: Box<dyn PermanentStorage>,
+    threshold: Duration,
+}
+
+impl SlowLogPermanentStorage {
+    pub fn new(inner: Box<dyn PermanentStorage>, threshold: Duration) -> Self {
+        tracing::info!(threshold = %threshold.to_string_ext(), "logging slow permanent storage operations");
+        Self { inner, threshold }
+    }
+
+    /// Executes a storage operation, logging it if it takes longer than the threshold.
+    fn measure<P: Debug, T>(&self, method: &'static str, params: P, operation: impl FnOnce() -> Result<T, StratusError>) -> Result<T, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("storage::perm", %method).entered();
+
+        let start = metrics::now();
+        let result = operation();
+        let elapsed = start.elapsed();
+
+        if elapsed > self.threshold {
+            tracing::warn!(
+                storage = %self.inner.kind(),
+                %method,
+                ?params,
+                elapsed = %elapsed.to_string_ext(),
+                threshold = %self.threshold.to_string_ext(),
+                success = %result.is_ok(),
+                "slow permanent storage operation"
+            );
+        }
+        result
+    }
+}
+
+impl ReadStorage for SlowLogPermanentStorage {
+    fn kind(&self) -> PermanentStorageKind {
+        self.inner.kind()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
+        self.measure("read_mined_block_number", (), || self.inner.read_mined_block_number())
+    }
+
+    fn read_finalized_block_number(&self) -> Result<Option<BlockNumber>, StratusError> {
+        self.measure("read_finalized_block_number", (), || self.inner.read_finalized_block_number())
+    }
+
+    // -------------------------------------------------------------------------
+    // Block
+    // -------------------------------------------------------------------------
+
+    fn read_block(&self, block_filter: BlockFilter) -> Result<Option<Block>, StratusError> {
+        self.measure("read_block", block_filter, || self.inner.read_block(block_filter))
+    }
+
+    fn read_transaction(&self, hash: Hash) -> Result<Option<TransactionMined>, StratusError> {
+        self.measure("read_transaction", hash, || self.inner.read_transaction(hash))
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
+        self.measure("read_logs", filter, || self.inner.read_logs(filter))
+    }
+
+    // -------------------------------------------------------------------------
+    // Account and slots
+    // -------------------------------------------------------------------------
+
+    fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Option<Account>, StratusError> {
+        self.measure("read_account", (address, point_in_time), || self.inner.read_account(address, point_in_time))
+    }
+
+    fn read_slot(&self, address: Address, index: SlotIndex, point_in_time: PointInTime) -> Result<Option<Slot>, StratusError> {
+        self.measure("read_slot", (address, index, point_in_time), || {
+            self.inner.read_slot(address, index, point_in_time)
+        })
+    }
+
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        self.measure("read_slots_range", (address, start, limit), || {
+            self.inner.read_slots_range(address, start, limit)
+        })
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        self.measure("read_balance_changes", (address, from_block, limit), || {
+            self.inner.read_balance_changes(address, from_block, limit)
+        })
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.measure("read_internal_transfers_by_block", number, || {
+            self.inner.read_internal_transfers_by_block(number)
+        })
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        self.measure("read_internal_transfers_by_address", (address, from_block, limit), || {
+            self.inner.read_internal_transfers_by_address(address, from_block, limit)
+        })
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.inner.token_transfers_indexed()
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.measure("read_token_transfers_by_account", (address, from_block, limit), || {
+            self.inner.read_token_transfers_by_account(address, from_block, limit)
+        })
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        self.measure("read_token_transfers_by_token", (token, from_block, limit), || {
+            self.inner.read_token_transfers_by_token(token, from_block, limit)
+        })
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        self.measure("read_contract_creation", address, || self.inner.read_contract_creation(address))
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        self.measure("read_state_diff", number, || self.inner.read_state_diff(number))
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        self.measure("read_state_count", (), || self.inner.read_state_count())
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        self.measure("read_gas_usage", (), || self.inner.read_gas_usage())
+    }
+}
+
+impl WriteStorage for SlowLogPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.measure("set_mined_block_number", number, || self.inner.set_mined_block_number(number))
+    }
+
+    fn set_finalized_block_number(&self, number: BlockNumber) -> Result<(), StratusError> {
+        self.measure("set_finalized_block_number", number, || self.inner.set_finalized_block_number(number))
+    }
+
+    fn save_block(&self, block: Block) -> Result<(), StratusError> {
+        let params = (block.number(), block.transactions.len());
+        self.measure("save_block", params, || self.inner.save_block(block))
+    }
+
+    fn save_block_batch(&self, blocks: Vec<Block>) -> Result<(), StratusError> {
+        let params = (blocks.first().map(Block::number), blocks.len());
+        self.measure("save_block_batch", params, || self.inner.save_block_batch(blocks))
+    }
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
+        let params = accounts.len();
+        self.measure("save_accounts", params, || self.inner.save_accounts(accounts))
+    }
+
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        let params = usage.functions.len();
+        self.measure("save_gas_usage", params, || self.inner.save_gas_usage(usage))
+    }
+
+    #[cfg(feature = "dev")]
+    fn reset(&self) -> Result<(), StratusError> {
+        self.measure("reset", (), || self.inner.reset())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::ext::not;
+    use crate::infra::tracing::CapturedLogs;
+
+    /// Executes an operation that takes the given duration and returns its result and the logs it produced.
+    fn measure_operation(threshold: Duration, duration: Duration, result: Result<(), StratusError>) -> (Result<(), StratusError>, String) {
+        let storage = SlowLogPermanentStorage::new(Box::new(InMemoryPermanentStorage::default()), threshold);
+
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+        let result = storage.measure("read_block", BlockNumber::from(7u64), || {
+            thread::sleep(duration);
+            result
+        });
+        (result, logs.output())
+    }
+
+    #[test]
+    fn test_slow_operations_are_logged_with_params() {
+        let (result, logs) = measure_operation(Duration::from_millis(1), Duration::from_millis(10), Ok(()));
+        assert!(result.is_ok());
+        assert!(logs.contains("slow permanent storage operation"), "{}", logs);
+        assert!(logs.contains("method=read_block"), "{}", logs);
+        assert!(logs.contains(r#"params="0x7""#), "{}", logs);
+        assert!(logs.contains("success=true"), "{}", logs);
+    }
+
+    #[test]
+    fn test_slow_operations_keep_their_errors() {
+        let (result, logs) = measure_operation(Duration::from_millis(1), Duration::from_millis(10), Err(StratusError::StratusNotReady));
+        assert!(matches!(result, Err(StratusError::StratusNotReady)));
+        assert!(logs.contains("success=false"), "{}", logs);
+    }
+
+    #[test]
+    fn test_fast_operations_are_not_logged() {
+        let (result, logs) = measure_operation(Duration::from_secs(60), Duration::ZERO, Ok(()));
+        assert!(result.is_ok());
+        assert!(not(logs.contains("slow permanent storage operation")), "{}", logs);
+    }
+}
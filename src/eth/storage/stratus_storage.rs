@@ -1,14 +1,26 @@
-use anyhow::anyhow;
+use std::sync::Arc;
+
 use tracing::Span;
 
+use super::BlockHeaderCache;
+use super::CacheInvalidation;
+use super::CachedBlockHeader;
 use super::Storage;
 use super::StorageCache;
+use super::StorageFork;
+use super::StorageReader;
+use crate::eth::analytics::GasUsageSnapshot;
+use crate::eth::analytics::StateCount;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::InternalTransferMined;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::PendingBlock;
@@ -17,9 +29,12 @@ use crate::eth::primitives::PointInTime;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TokenTransferMined;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionStage;
+use crate::eth::storage::BlockCandidateId;
 use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::PermanentStorageKind;
 use crate::eth::storage::TemporaryStorage;
 use crate::ext::not;
 use crate::infra::metrics;
@@ -30,24 +45,41 @@ mod label {
     pub(super) const TEMP: &str = "temporary";
     pub(super) const PERM: &str = "permanent";
     pub(super) const CACHE: &str = "cache";
+    pub(super) const FORK: &str = "fork";
 }
 
 /// Proxy that simplifies interaction with permanent and temporary storages.
 ///
 /// Additionaly it tracks metrics that are independent of the storage implementation.
+///
+/// Blocks move between the storages in the following order, so a crash at any step never applies a block twice:
+///
+/// 1. The pending block is finished in the temporary storage, that keeps its state readable.
+/// 2. The block is saved in the permanent storage, which is atomic for each block.
+/// 3. The state of the finished block is released from the temporary storage.
+/// 4. The mined block number is updated in the permanent storage.
+///
+/// The temporary storage is kept in memory, so a crash loses the pending block and the finished blocks not saved in the permanent storage yet
+/// (before step 2). Their transactions are not mined and must be sent again, and the pending block number is resumed from the permanent storage.
+/// A block saved before the mined block number was updated is detected by the permanent storage integrity check at startup, that repairs the
+/// mined block number, so the block is not imported or mined again.
 pub struct StratusStorage {
     temp: Box<dyn TemporaryStorage>,
     cache: StorageCache,
+    header_cache: BlockHeaderCache,
     perm: Box<dyn PermanentStorage>,
+    fork: Option<StorageFork>,
 }
 
 impl StratusStorage {
-    /// Creates a new storage with the specified temporary and permanent implementations.
-    pub fn new(temp: Box<dyn TemporaryStorage>, perm: Box<dyn PermanentStorage>) -> Result<Self, StratusError> {
+    /// Creates a new storage with the specified temporary and permanent implementations, optionally forking an upstream chain.
+    pub fn new(temp: Box<dyn TemporaryStorage>, perm: Box<dyn PermanentStorage>, fork: Option<StorageFork>) -> Result<Self, StratusError> {
         let this = Self {
             temp,
             cache: StorageCache::default(),
+            header_cache: BlockHeaderCache::default(),
             perm,
+            fork,
         };
 
         // create genesis block and accounts if necessary
@@ -62,16 +94,73 @@ impl StratusStorage {
         Ok(this)
     }
 
+    /// Returns a read-only handle to the storage, for components that must not change its state.
+    pub fn reader(self: &Arc<Self>) -> Arc<dyn StorageReader> {
+        Arc::<Self>::clone(self)
+    }
+
+    /// Configures the timestamps of the next pending blocks. See [`TemporaryStorage::configure_block_clock`].
+    pub fn configure_block_clock(&self, fixed_delta: Option<u64>) {
+        self.temp.configure_block_clock(fixed_delta);
+    }
+
     #[cfg(test)]
     pub fn new_test() -> Result<Self, StratusError> {
         let perm = Box::new(super::InMemoryPermanentStorage::default());
         let temp = Box::new(super::InMemoryTemporaryStorage::new(0.into()));
 
-        Self::new(temp, perm)
+        Self::new(temp, perm, None)
+    }
+
+    /// Kind of the permanent storage, used to tag metrics by backend.
+    pub fn perm_kind(&self) -> PermanentStorageKind {
+        self.perm.kind()
+    }
+
+    /// Evicts the accounts and slots touched by a block committed elsewhere from the cache.
+    pub fn invalidate_cache(&self, invalidation: &CacheInvalidation) {
+        tracing::debug!(storage = %label::CACHE, accounts = %invalidation.accounts.len(), slots = %invalidation.slots.len(), "invalidating cache");
+        self.cache.invalidate(invalidation);
+    }
+
+    /// Releases the state of finished blocks from the temporary storage after they are persisted in the permanent storage.
+    fn release_persisted_blocks(&self, block_number: BlockNumber) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::TEMP, %block_number, "releasing persisted blocks");
+        self.temp.release_persisted_blocks(block_number).map_err(|e| {
+            tracing::error!(reason = ?e, %block_number, "failed to release persisted blocks from temporary storage");
+            e.into()
+        })
+    }
+
+    /// Resolves the `safe` and `finalized` tags to the number of the last block that reached the finality level.
+    fn resolve_finality_tag(&self, filter: BlockFilter) -> Result<BlockFilter, StratusError> {
+        match filter {
+            BlockFilter::Safe | BlockFilter::Finalized => Ok(BlockFilter::Number(self.read_finality_block_number(filter)?)),
+            filter => Ok(filter),
+        }
+    }
+
+    /// Reads the number of the last block that reached the finality level of the `safe` or `finalized` tag.
+    ///
+    /// A block is safe once it is committed in the permanent storage of this node, that never reorganizes blocks, so the safe block is the mined
+    /// block. A block is finalized once it is committed by the leader and verified by this node, which is persisted apart from the mined block
+    /// number. Fails if no block was finalized yet, instead of falling back to a block that is not final.
+    fn read_finality_block_number(&self, tag: BlockFilter) -> Result<BlockNumber, StratusError> {
+        match tag {
+            BlockFilter::Finalized => self
+                .perm
+                .read_finalized_block_number()?
+                .ok_or(StratusError::RpcBlockFinalityUnavailable { filter: tag }),
+            _ => self.read_mined_block_number(),
+        }
     }
 }
 
-impl Storage for StratusStorage {
+impl StorageReader for StratusStorage {
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
     fn read_block_number_to_resume_import(&self) -> Result<BlockNumber, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::read_block_number_to_resume_import").entered();
@@ -91,8 +180,21 @@ impl Storage for StratusStorage {
     fn read_mined_block_number(&self) -> Result<BlockNumber, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::read_mined_block_number").entered();
-        tracing::debug!(storage = %label::PERM, "reading mined block number");
 
+        // read from cache
+        // read-only storages are written by another process, so the cached number would never be updated
+        let cacheable = not(self.perm.is_read_only());
+        if cacheable {
+            tracing::debug!(storage = %label::CACHE, "reading mined block number");
+            if let Some(block_number) = timed(|| self.header_cache.get_mined_number()).with(|m| {
+                metrics::inc_storage_read_mined_block_number(m.elapsed, label::CACHE, true);
+            }) {
+                return Ok(block_number);
+            }
+        }
+
+        // read from perm
+        tracing::debug!(storage = %label::PERM, "reading mined block number");
         timed(|| self.perm.read_mined_block_number())
             .with(|m| {
                 metrics::inc_storage_read_mined_block_number(m.elapsed, label::PERM, m.result.is_ok());
@@ -100,52 +202,17 @@ impl Storage for StratusStorage {
                     tracing::error!(reason = ?e, "failed to read miner block number");
                 }
             })
-            .map_err(Into::into)
-    }
-
-    fn set_mined_block_number(&self, block_number: BlockNumber) -> Result<(), StratusError> {
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("storage::set_mined_block_number", %block_number).entered();
-        tracing::debug!(storage = %label::PERM, %block_number, "setting mined block number");
-
-        timed(|| self.perm.set_mined_block_number(block_number))
-            .with(|m| {
-                metrics::inc_storage_set_mined_block_number(m.elapsed, label::PERM, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to set miner block number");
+            .inspect(|block_number| {
+                if cacheable {
+                    self.header_cache.init_mined_number(*block_number);
                 }
             })
-            .map_err(Into::into)
     }
 
     // -------------------------------------------------------------------------
     // Accounts and slots
     // -------------------------------------------------------------------------
 
-    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("storage::save_accounts").entered();
-
-        // keep only accounts that does not exist in permanent storage
-        let mut missing_accounts = Vec::new();
-        for account in accounts {
-            let perm_account = self.perm.read_account(account.address, PointInTime::Mined)?;
-            if perm_account.is_none() {
-                missing_accounts.push(account);
-            }
-        }
-
-        tracing::debug!(storage = %label::PERM, accounts = ?missing_accounts, "saving initial accounts");
-        timed(|| self.perm.save_accounts(missing_accounts))
-            .with(|m| {
-                metrics::inc_storage_save_accounts(m.elapsed, label::PERM, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to save accounts");
-                }
-            })
-            .map_err(Into::into)
-    }
-
     fn read_account(&self, address: Address, point_in_time: PointInTime) -> Result<Account, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::debug_span!("storage::read_account", %address, %point_in_time).entered();
@@ -185,10 +252,16 @@ impl Storage for StratusStorage {
                     tracing::debug!(storage = %label::PERM, %address, ?account, "account found in permanent storage");
                     account
                 }
-                None => {
-                    tracing::debug!(storage = %label::PERM, %address, "account not found, assuming default value");
-                    Account::new_empty(address)
-                }
+                None => match self.fork {
+                    Some(ref fork) => {
+                        tracing::debug!(storage = %label::FORK, %address, "account not found, reading from fork");
+                        fork.read_account(address)?
+                    }
+                    None => {
+                        tracing::debug!(storage = %label::PERM, %address, "account not found, assuming default value");
+                        Account::new_empty(address)
+                    }
+                },
             }
         };
 
@@ -238,10 +311,16 @@ impl Storage for StratusStorage {
                     tracing::debug!(storage = %label::PERM, %address, %index, value = %slot.value, "slot found in permanent storage");
                     slot
                 }
-                None => {
-                    tracing::debug!(storage = %label::PERM, %address, %index, "slot not found, assuming default value");
-                    Slot::new_empty(index)
-                }
+                None => match self.fork {
+                    Some(ref fork) => {
+                        tracing::debug!(storage = %label::FORK, %address, %index, "slot not found, reading from fork");
+                        fork.read_slot(address, index)?
+                    }
+                    None => {
+                        tracing::debug!(storage = %label::PERM, %address, %index, "slot not found, assuming default value");
+                        Slot::new_empty(index)
+                    }
+                },
             }
         };
 
@@ -251,6 +330,319 @@ impl Storage for StratusStorage {
         Ok(slot)
     }
 
+    fn read_slots_range(&self, address: Address, start: SlotIndex, limit: usize) -> Result<Vec<Slot>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_slots_range", %address, %start, %limit).entered();
+        tracing::debug!(storage = %label::PERM, %address, %start, %limit, "reading slots range");
+
+        timed(|| self.perm.read_slots_range(address, start, limit)).with(|m| {
+            metrics::inc_storage_read_slots_range(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read slots range");
+            }
+        })
+    }
+
+    fn read_balance_changes(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<BalanceChange>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_balance_changes", %address, %from_block, %limit).entered();
+        tracing::debug!(storage = %label::PERM, %address, %from_block, %limit, "reading balance changes");
+
+        timed(|| self.perm.read_balance_changes(address, from_block, limit)).with(|m| {
+            metrics::inc_storage_read_balance_changes(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read balance changes");
+            }
+        })
+    }
+
+    fn read_internal_transfers_by_block(&self, number: BlockNumber) -> Result<Vec<InternalTransferMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_internal_transfers_by_block", %number).entered();
+        tracing::debug!(storage = %label::PERM, %number, "reading internal transfers by block");
+
+        timed(|| self.perm.read_internal_transfers_by_block(number)).with(|m| {
+            metrics::inc_storage_read_internal_transfers(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read internal transfers by block");
+            }
+        })
+    }
+
+    fn read_internal_transfers_by_address(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<InternalTransferMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_internal_transfers_by_address", %address, %from_block, %limit).entered();
+        tracing::debug!(storage = %label::PERM, %address, %from_block, %limit, "reading internal transfers by address");
+
+        timed(|| self.perm.read_internal_transfers_by_address(address, from_block, limit)).with(|m| {
+            metrics::inc_storage_read_internal_transfers(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read internal transfers by address");
+            }
+        })
+    }
+
+    fn token_transfers_indexed(&self) -> bool {
+        self.perm.token_transfers_indexed()
+    }
+
+    fn read_token_transfers_by_account(&self, address: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_token_transfers_by_account", %address, %from_block, %limit).entered();
+        tracing::debug!(storage = %label::PERM, %address, %from_block, %limit, "reading token transfers by account");
+
+        timed(|| self.perm.read_token_transfers_by_account(address, from_block, limit)).with(|m| {
+            metrics::inc_storage_read_token_transfers(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read token transfers by account");
+            }
+        })
+    }
+
+    fn read_token_transfers_by_token(&self, token: Address, from_block: BlockNumber, limit: usize) -> Result<Vec<TokenTransferMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_token_transfers_by_token", %token, %from_block, %limit).entered();
+        tracing::debug!(storage = %label::PERM, %token, %from_block, %limit, "reading token transfers by token");
+
+        timed(|| self.perm.read_token_transfers_by_token(token, from_block, limit)).with(|m| {
+            metrics::inc_storage_read_token_transfers(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read token transfers by token");
+            }
+        })
+    }
+
+    fn read_contract_creation(&self, address: Address) -> Result<Option<ContractCreation>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_contract_creation", %address).entered();
+        tracing::debug!(storage = %label::PERM, %address, "reading contract creation");
+
+        timed(|| self.perm.read_contract_creation(address)).with(|m| {
+            metrics::inc_storage_read_contract_creation(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read contract creation");
+            }
+        })
+    }
+
+    fn read_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<ExecutionAccountChanges>>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_state_diff", %number).entered();
+        tracing::debug!(storage = %label::PERM, %number, "reading state diff");
+
+        timed(|| self.perm.read_state_diff(number)).with(|m| {
+            metrics::inc_storage_read_state_diff(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read state diff");
+            }
+        })
+    }
+
+    fn read_state_count(&self) -> Result<StateCount, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_state_count").entered();
+        tracing::debug!(storage = %label::PERM, "counting state");
+
+        self.perm.read_state_count().inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to count state");
+        })
+    }
+
+    fn read_gas_usage(&self) -> Result<Option<GasUsageSnapshot>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_gas_usage").entered();
+        tracing::debug!(storage = %label::PERM, "reading gas usage");
+
+        self.perm.read_gas_usage().inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read gas usage");
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Blocks
+    // -------------------------------------------------------------------------
+
+    /// Retrieves pending transactions being mined.
+    fn pending_transactions(&self) -> Vec<TransactionExecution> {
+        self.temp.read_pending_executions()
+    }
+
+    fn read_block(&self, filter: BlockFilter) -> Result<Option<Block>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_block", %filter).entered();
+        let filter = self.resolve_finality_tag(filter)?;
+        tracing::debug!(storage = %label::PERM, ?filter, "reading block");
+
+        timed(|| self.perm.read_block(filter)).with(|m| {
+            metrics::inc_storage_read_block(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read block");
+            }
+        })
+    }
+
+    fn read_block_header(&self, filter: BlockFilter) -> Result<Option<CachedBlockHeader>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_block_header", %filter).entered();
+        let filter = self.resolve_finality_tag(filter)?;
+
+        // read from cache
+        tracing::debug!(storage = %label::CACHE, ?filter, "reading block header");
+        if let Some(header) = timed(|| self.header_cache.get(filter)).with(|m| {
+            metrics::inc_storage_read_block(m.elapsed, label::CACHE, true);
+        }) {
+            tracing::debug!(storage = %label::CACHE, ?filter, "block header found in cache");
+            return Ok(Some(header));
+        }
+
+        // read from perm
+        let block = self.read_block(filter)?;
+        Ok(block.as_ref().map(CachedBlockHeader::from))
+    }
+
+    fn read_transaction(&self, tx_hash: Hash) -> Result<Option<TransactionStage>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_transaction", %tx_hash).entered();
+
+        // read from temp
+        tracing::debug!(storage = %label::TEMP, %tx_hash, "reading transaction");
+        let temp_tx = timed(|| self.temp.read_pending_execution(tx_hash)).with(|m| {
+            metrics::inc_storage_read_transaction(m.elapsed, label::TEMP, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read transaction from temporary storage");
+            }
+        })?;
+        if let Some(tx_temp) = temp_tx {
+            return Ok(Some(TransactionStage::new_executed(tx_temp)));
+        }
+
+        // read from perm
+        tracing::debug!(storage = %label::PERM, %tx_hash, "reading transaction");
+        let perm_tx = timed(|| self.perm.read_transaction(tx_hash)).with(|m| {
+            metrics::inc_storage_read_transaction(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read transaction from permanent storage");
+            }
+        })?;
+        match perm_tx {
+            Some(tx) => Ok(Some(TransactionStage::new_mined(tx))),
+            None => Ok(None),
+        }
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_logs", ?filter).entered();
+        tracing::debug!(storage = %label::PERM, ?filter, "reading logs");
+
+        timed(|| self.perm.read_logs(filter)).with(|m| {
+            metrics::inc_storage_read_logs(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read logs");
+            }
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Utils
+    // -------------------------------------------------------------------------
+
+    /// Translates a block filter to a specific storage point-in-time indicator.
+    fn translate_to_point_in_time(&self, block_filter: BlockFilter) -> Result<PointInTime, StratusError> {
+        match block_filter {
+            BlockFilter::Pending => Ok(PointInTime::Pending),
+            BlockFilter::Latest => Ok(PointInTime::Mined),
+            BlockFilter::Earliest => Ok(PointInTime::MinedPast(BlockNumber::ZERO)),
+            BlockFilter::Safe | BlockFilter::Finalized => Ok(PointInTime::MinedPast(self.read_finality_block_number(block_filter)?)),
+            BlockFilter::Number(number) => Ok(PointInTime::MinedPast(number)),
+            BlockFilter::Hash(_) => match self.read_block(block_filter)? {
+                Some(block) => Ok(PointInTime::MinedPast(block.header.number)),
+                None => Err(StratusError::RpcBlockFilterInvalid { filter: block_filter }),
+            },
+        }
+    }
+}
+
+impl Storage for StratusStorage {
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn set_mined_block_number(&self, block_number: BlockNumber) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::set_mined_block_number", %block_number).entered();
+        tracing::debug!(storage = %label::PERM, %block_number, "setting mined block number");
+
+        timed(|| self.perm.set_mined_block_number(block_number))
+            .with(|m| {
+                metrics::inc_storage_set_mined_block_number(m.elapsed, label::PERM, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to set miner block number");
+                }
+            })
+            .inspect(|_| self.header_cache.set_mined_number(block_number))
+    }
+
+    fn set_finalized_block_number(&self, block_number: BlockNumber) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::set_finalized_block_number", %block_number).entered();
+        tracing::debug!(storage = %label::PERM, %block_number, "setting finalized block number");
+
+        let mined_number = self.read_mined_block_number()?;
+        if block_number > mined_number {
+            return Err(StratusError::StorageInvariantViolated {
+                reason: format!("finalized block {} is after the mined block {}", block_number, mined_number),
+            });
+        }
+        if self.perm.read_finalized_block_number()?.is_some_and(|finalized| finalized >= block_number) {
+            return Ok(());
+        }
+
+        self.perm.set_finalized_block_number(block_number).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to set finalized block number");
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Accounts and slots
+    // -------------------------------------------------------------------------
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::save_accounts").entered();
+
+        // keep only accounts that does not exist in permanent storage
+        let mut missing_accounts = Vec::new();
+        for account in accounts {
+            let perm_account = self.perm.read_account(account.address, PointInTime::Mined)?;
+            if perm_account.is_none() {
+                missing_accounts.push(account);
+            }
+        }
+
+        tracing::debug!(storage = %label::PERM, accounts = ?missing_accounts, "saving initial accounts");
+        timed(|| self.perm.save_accounts(missing_accounts)).with(|m| {
+            metrics::inc_storage_save_accounts(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to save accounts");
+            }
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Analytics
+    // -------------------------------------------------------------------------
+
+    fn save_gas_usage(&self, usage: GasUsageSnapshot) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::save_gas_usage").entered();
+        tracing::debug!(storage = %label::PERM, last_block = ?usage.last_block, "saving gas usage");
+
+        self.perm.save_gas_usage(usage).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to save gas usage");
+        })
+    }
+
     // -------------------------------------------------------------------------
     // Blocks
     // -------------------------------------------------------------------------
@@ -277,11 +669,6 @@ impl Storage for StratusStorage {
             .inspect(|_| self.cache.cache_account_and_slots_from_changes(changes))
     }
 
-    /// Retrieves pending transactions being mined.
-    fn pending_transactions(&self) -> Vec<TransactionExecution> {
-        self.temp.read_pending_executions()
-    }
-
     fn finish_pending_block(&self) -> Result<PendingBlock, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::finish_pending_block", block_number = tracing::field::Empty).entered();
@@ -303,6 +690,48 @@ impl Storage for StratusStorage {
         result
     }
 
+    fn discard_pending_block(&self) -> Result<PendingBlock, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::discard_pending_block").entered();
+        tracing::debug!(storage = %label::TEMP, "discarding pending block");
+
+        let discarded = self.temp.discard_pending_block()?;
+
+        // the cache may hold values written by the discarded executions
+        self.cache.clear();
+
+        Ok(discarded)
+    }
+
+    fn propose_pending_block(&self, epoch: u64) -> Result<PendingBlock, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::propose_pending_block", %epoch).entered();
+        tracing::debug!(storage = %label::TEMP, %epoch, "proposing pending block");
+
+        self.temp.propose_pending_block(epoch).map_err(Into::into)
+    }
+
+    fn promote_block_candidate(&self, id: BlockCandidateId) -> Result<PendingBlock, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::promote_block_candidate", block_number = %id.number, epoch = %id.epoch).entered();
+        tracing::debug!(storage = %label::TEMP, block_number = %id.number, epoch = %id.epoch, "promoting block candidate");
+
+        self.temp.promote_block_candidate(id).map_err(Into::into)
+    }
+
+    fn discard_block_candidates(&self, from: BlockNumber) -> Result<Vec<PendingBlock>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::discard_block_candidates", block_number = %from).entered();
+        tracing::debug!(storage = %label::TEMP, block_number = %from, "discarding block candidates");
+
+        let discarded = self.temp.discard_block_candidates(from)?;
+
+        // the cache may hold values written by the discarded executions
+        self.cache.clear();
+
+        Ok(discarded)
+    }
+
     fn save_block(&self, block: Block) -> Result<(), StratusError> {
         let block_number = block.number();
 
@@ -339,14 +768,16 @@ impl Storage for StratusStorage {
 
         // save block
         let (label_size_by_tx, label_size_by_gas) = (block.label_size_by_transactions(), block.label_size_by_gas());
-        timed(|| self.perm.save_block(block))
-            .with(|m| {
-                metrics::inc_storage_save_block(m.elapsed, label::PERM, label_size_by_tx, label_size_by_gas, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, %block_number, "failed to save block");
-                }
-            })
-            .map_err(Into::into)
+        let header = CachedBlockHeader::from(&block);
+        timed(|| self.perm.save_block(block)).with(|m| {
+            metrics::inc_storage_save_block(m.elapsed, label::PERM, label_size_by_tx, label_size_by_gas, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, %block_number, "failed to save block");
+            }
+        })?;
+        self.header_cache.push(header);
+
+        self.release_persisted_blocks(block_number)
     }
 
     fn save_block_batch(&self, blocks: Vec<Block>) -> Result<(), StratusError> {
@@ -382,7 +813,9 @@ impl Storage for StratusStorage {
             let (previous, next) = (window[0].number(), window[1].number());
             if previous.next_block_number() != next {
                 tracing::error!(%previous, %next, "previous block number doesn't match next one");
-                return Err(anyhow!("consecutive blocks in batch aren't adjacent").into());
+                return Err(StratusError::StorageInvariantViolated {
+                    reason: format!("consecutive blocks in batch aren't adjacent: {previous} and {next}"),
+                });
             }
         }
 
@@ -393,67 +826,11 @@ impl Storage for StratusStorage {
             return Err(StratusError::StorageBlockConflict { number: first_number });
         }
 
-        self.perm.save_block_batch(blocks).map_err(Into::into)
-    }
-
-    fn read_block(&self, filter: BlockFilter) -> Result<Option<Block>, StratusError> {
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("storage::read_block", %filter).entered();
-        tracing::debug!(storage = %label::PERM, ?filter, "reading block");
-
-        timed(|| self.perm.read_block(filter))
-            .with(|m| {
-                metrics::inc_storage_read_block(m.elapsed, label::PERM, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to read block");
-                }
-            })
-            .map_err(Into::into)
-    }
-
-    fn read_transaction(&self, tx_hash: Hash) -> Result<Option<TransactionStage>, StratusError> {
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("storage::read_transaction", %tx_hash).entered();
-
-        // read from temp
-        tracing::debug!(storage = %label::TEMP, %tx_hash, "reading transaction");
-        let temp_tx = timed(|| self.temp.read_pending_execution(tx_hash)).with(|m| {
-            metrics::inc_storage_read_transaction(m.elapsed, label::TEMP, m.result.is_ok());
-            if let Err(ref e) = m.result {
-                tracing::error!(reason = ?e, "failed to read transaction from temporary storage");
-            }
-        })?;
-        if let Some(tx_temp) = temp_tx {
-            return Ok(Some(TransactionStage::new_executed(tx_temp)));
-        }
-
-        // read from perm
-        tracing::debug!(storage = %label::PERM, %tx_hash, "reading transaction");
-        let perm_tx = timed(|| self.perm.read_transaction(tx_hash)).with(|m| {
-            metrics::inc_storage_read_transaction(m.elapsed, label::PERM, m.result.is_ok());
-            if let Err(ref e) = m.result {
-                tracing::error!(reason = ?e, "failed to read transaction from permanent storage");
-            }
-        })?;
-        match perm_tx {
-            Some(tx) => Ok(Some(TransactionStage::new_mined(tx))),
-            None => Ok(None),
-        }
-    }
-
-    fn read_logs(&self, filter: &LogFilter) -> Result<Vec<LogMined>, StratusError> {
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("storage::read_logs", ?filter).entered();
-        tracing::debug!(storage = %label::PERM, ?filter, "reading logs");
-
-        timed(|| self.perm.read_logs(filter))
-            .with(|m| {
-                metrics::inc_storage_read_logs(m.elapsed, label::PERM, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to read logs");
-                }
-            })
-            .map_err(Into::into)
+        let last_number = blocks.last().map(Block::number).unwrap_or(first_number);
+        let headers = blocks.iter().map(CachedBlockHeader::from).collect::<Vec<_>>();
+        self.perm.save_block_batch(blocks)?;
+        headers.into_iter().for_each(|header| self.header_cache.push(header));
+        self.release_persisted_blocks(last_number)
     }
 
     // -------------------------------------------------------------------------
@@ -468,6 +845,7 @@ impl Storage for StratusStorage {
         use crate::eth::primitives::test_accounts;
 
         self.cache.clear();
+        self.header_cache.clear();
 
         tracing::info!("reseting storage to genesis state");
 
@@ -503,22 +881,138 @@ impl Storage for StratusStorage {
 
         Ok(())
     }
+}
 
-    // -------------------------------------------------------------------------
-    // Utils
-    // -------------------------------------------------------------------------
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::EvmInput;
+    use crate::eth::primitives::ExecutionAccountChanges;
+    use crate::eth::primitives::GasMode;
+    use crate::eth::primitives::LocalTransactionExecution;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::permanent::ReadOnlyPermanentStorage;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::WriteStorage;
+
+    /// Creates a permanent storage with the genesis block, so creating the storage does not reset it to genesis.
+    fn perm_with_genesis() -> InMemoryPermanentStorage {
+        let perm = InMemoryPermanentStorage::default();
+        perm.save_block(Block::new(BlockNumber::ZERO, UnixTime::ZERO)).unwrap();
+        perm
+    }
 
-    /// Translates a block filter to a specific storage point-in-time indicator.
-    fn translate_to_point_in_time(&self, block_filter: BlockFilter) -> Result<PointInTime, StratusError> {
-        match block_filter {
-            BlockFilter::Pending => Ok(PointInTime::Pending),
-            BlockFilter::Latest => Ok(PointInTime::Mined),
-            BlockFilter::Earliest => Ok(PointInTime::MinedPast(BlockNumber::ZERO)),
-            BlockFilter::Number(number) => Ok(PointInTime::MinedPast(number)),
-            BlockFilter::Hash(_) => match self.read_block(block_filter)? {
-                Some(block) => Ok(PointInTime::MinedPast(block.header.number)),
-                None => Err(StratusError::RpcBlockFilterInvalid { filter: block_filter }),
-            },
-        }
+    #[test]
+    fn test_mined_block_number_is_cached() {
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let storage = StratusStorage::new(temp, Box::new(perm_with_genesis()), None).unwrap();
+
+        assert_eq!(storage.read_mined_block_number().unwrap(), BlockNumber::ZERO);
+        assert_eq!(storage.header_cache.get_mined_number(), Some(BlockNumber::ZERO));
+    }
+
+    #[test]
+    fn test_mined_block_number_of_read_only_storage_is_not_cached() {
+        let temp = Box::new(InMemoryTemporaryStorage::new(1.into()));
+        let perm = ReadOnlyPermanentStorage::new(Box::new(perm_with_genesis()));
+        let storage = StratusStorage::new(temp, Box::new(perm), None).unwrap();
+
+        assert_eq!(storage.read_mined_block_number().unwrap(), BlockNumber::ZERO);
+        assert_eq!(storage.header_cache.get_mined_number(), None);
+    }
+
+    #[test]
+    fn test_finality_tags_are_read_from_permanent_storage() {
+        let perm = perm_with_genesis();
+        perm.save_block(Block::new(BlockNumber::ONE, UnixTime::ZERO)).unwrap();
+        perm.save_block(Block::new(2.into(), UnixTime::ZERO)).unwrap();
+        perm.set_mined_block_number(2.into()).unwrap();
+        let storage = StratusStorage::new(Box::new(InMemoryTemporaryStorage::new(3.into())), Box::new(perm), None).unwrap();
+        let read_number = |filter| storage.read_block(filter).map(|block| block.map(|block| block.number()));
+
+        // the safe block is the mined block, but no block is finalized yet
+        assert_eq!(read_number(BlockFilter::Safe).unwrap(), Some(2.into()));
+        assert!(matches!(
+            read_number(BlockFilter::Finalized),
+            Err(StratusError::RpcBlockFinalityUnavailable { .. })
+        ));
+        assert!(matches!(
+            storage.translate_to_point_in_time(BlockFilter::Finalized),
+            Err(StratusError::RpcBlockFinalityUnavailable { .. })
+        ));
+
+        // the finalized block is persisted apart from the mined block
+        storage.set_finalized_block_number(BlockNumber::ONE).unwrap();
+        assert_eq!(storage.perm.read_finalized_block_number().unwrap(), Some(BlockNumber::ONE));
+        assert_eq!(read_number(BlockFilter::Finalized).unwrap(), Some(BlockNumber::ONE));
+        assert_eq!(
+            storage.translate_to_point_in_time(BlockFilter::Finalized).unwrap(),
+            PointInTime::MinedPast(BlockNumber::ONE)
+        );
+
+        // finality never goes back
+        storage.set_finalized_block_number(2.into()).unwrap();
+        storage.set_finalized_block_number(BlockNumber::ONE).unwrap();
+        assert_eq!(read_number(BlockFilter::Finalized).unwrap(), Some(2.into()));
+
+        // blocks are not finalized before they are mined
+        assert!(matches!(
+            storage.set_finalized_block_number(3.into()),
+            Err(StratusError::StorageInvariantViolated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_finalized_block_is_available_after_restart() {
+        // finalized by a previous run of the node
+        let perm = perm_with_genesis();
+        perm.save_block(Block::new(BlockNumber::ONE, UnixTime::ZERO)).unwrap();
+        perm.set_mined_block_number(BlockNumber::ONE).unwrap();
+        perm.set_finalized_block_number(BlockNumber::ONE).unwrap();
+
+        let storage = StratusStorage::new(Box::new(InMemoryTemporaryStorage::new(2.into())), Box::new(perm), None).unwrap();
+        assert_eq!(storage.read_block(BlockFilter::Finalized).unwrap().unwrap().number(), BlockNumber::ONE);
+    }
+
+    /// Saves a local execution that changes the balance of an account after reading the original balance.
+    fn save_balance_execution(storage: &StratusStorage, address: Address, original: u64, modified: u64) {
+        let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, Wei::from(original)));
+        changes.balance.set_modified(Wei::from(modified));
+
+        let mut tx: LocalTransactionExecution = Faker.fake();
+        tx.evm_input = EvmInput::from_eth_transaction(&tx.input, &storage.read_pending_block_header(), GasMode::Free);
+        tx.result.execution.changes = [(address, changes)].into_iter().collect();
+        storage.save_execution(TransactionExecution::Local(tx), true).unwrap();
+    }
+
+    #[test]
+    fn test_block_candidates_keep_cached_state_consistent() {
+        let storage = StratusStorage::new(Box::new(InMemoryTemporaryStorage::new(1.into())), Box::new(perm_with_genesis()), None).unwrap();
+        let address = Address::new([1; 20]);
+        let read_balance = || storage.read_account(address, PointInTime::Pending).unwrap().balance;
+
+        // the promoted block 1 stays visible
+        save_balance_execution(&storage, address, 0, 10);
+        storage.propose_pending_block(1).unwrap();
+        storage.promote_block_candidate(BlockCandidateId { number: 1.into(), epoch: 1 }).unwrap();
+        assert_eq!(read_balance(), Wei::from(10u64));
+
+        // values cached by the discarded block 2 and the pending block built on top of it are evicted
+        save_balance_execution(&storage, address, 10, 20);
+        storage.propose_pending_block(1).unwrap();
+        save_balance_execution(&storage, address, 20, 30);
+        assert_eq!(read_balance(), Wei::from(30u64));
+
+        storage.discard_block_candidates(2.into()).unwrap();
+        assert_eq!(read_balance(), Wei::from(10u64));
     }
 }
@@ -1,19 +1,24 @@
 //! In-memory storage implementations.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 
 use parking_lot::RwLock;
 use parking_lot::RwLockUpgradableReadGuard;
 #[cfg(not(feature = "dev"))]
 use parking_lot::RwLockWriteGuard;
 
-use crate::eth::executor::EvmInput;
+use crate::eth::miner::BlockClock;
+use crate::eth::miner::SystemClock;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::EvmInput;
 use crate::eth::primitives::ExecutionConflicts;
 use crate::eth::primitives::ExecutionConflictsBuilder;
+use crate::eth::primitives::GasMode;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::PendingBlockHeader;
@@ -21,46 +26,73 @@ use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
-#[cfg(feature = "dev")]
 use crate::eth::primitives::UnixTime;
 #[cfg(feature = "dev")]
 use crate::eth::primitives::UnixTimeNow;
 use crate::eth::storage::AccountWithSlots;
+use crate::eth::storage::BlockCandidateId;
 use crate::eth::storage::TemporaryStorage;
+use crate::ext::not;
+use crate::infra::metrics;
+use crate::log_and_err;
 
 #[derive(Debug)]
 pub struct InMemoryTemporaryStorage {
     pub pending_block: RwLock<InMemoryTemporaryStorageState>,
-    pub latest_block: RwLock<Option<InMemoryTemporaryStorageState>>,
+
+    /// Blocks that were finished but not persisted yet, from the oldest to the newest.
+    ///
+    /// Their state is kept until the permanent storage has it, so reads never fall back to a permanent state that lacks them.
+    pub finished_blocks: RwLock<VecDeque<InMemoryTemporaryStorageState>>,
+
+    /// Blocks proposed but not confirmed yet, by their number. The pending block is built on top of them.
+    ///
+    /// Locks must be acquired in the order pending block, candidate blocks, finished blocks.
+    pub candidate_blocks: RwLock<BTreeMap<BlockNumber, InMemoryBlockCandidate>>,
+
+    /// Generates the timestamps of new pending blocks.
+    block_clock: BlockClock,
 }
 
 impl InMemoryTemporaryStorage {
     pub fn new(block_number: BlockNumber) -> Self {
+        Self::new_with_clock(block_number, BlockClock::new(Box::new(SystemClock)))
+    }
+
+    /// Creates a new [`InMemoryTemporaryStorage`] that generates the timestamps of new pending blocks with the specified clock.
+    pub fn new_with_clock(block_number: BlockNumber, block_clock: BlockClock) -> Self {
         Self {
-            pending_block: RwLock::new(InMemoryTemporaryStorageState {
-                block: PendingBlock::new_at_now(block_number),
-                accounts: HashMap::default(),
-            }),
-            latest_block: RwLock::new(None),
+            pending_block: RwLock::new(InMemoryTemporaryStorageState::new(block_number, block_clock.next_timestamp())),
+            finished_blocks: RwLock::new(VecDeque::new()),
+            candidate_blocks: RwLock::new(BTreeMap::new()),
+            block_clock,
         }
     }
 
-    fn check_conflicts(&self, execution: &EvmExecution) -> anyhow::Result<Option<ExecutionConflicts>> {
+    /// Creates the state of a new pending block with the next timestamp of the block clock.
+    fn new_state(&self, block_number: BlockNumber) -> InMemoryTemporaryStorageState {
+        InMemoryTemporaryStorageState::new(block_number, self.block_clock.next_timestamp())
+    }
+
+    /// Checks if the values read by an execution were changed by executions saved before it.
+    ///
+    /// Receives the already locked pending state, so it can be called while the pending block is being updated.
+    fn check_conflicts(&self, pending: &InMemoryTemporaryStorageState, execution: &EvmExecution) -> Option<ExecutionConflicts> {
         let mut conflicts = ExecutionConflictsBuilder::default();
 
         for (&address, change) in &execution.changes {
             // check account info conflicts
-            if let Some(account) = self.read_account(address)? {
+            if let Some(account) = self.read_account_from(pending, address) {
                 if let Some(expected) = change.nonce.take_original_ref() {
                     let original = &account.nonce;
                     if expected != original {
-                        conflicts.add_nonce(address, *original, *expected);
+                        conflicts.add_nonce(address, *expected, *original);
                     }
                 }
                 if let Some(expected) = change.balance.take_original_ref() {
                     let original = &account.balance;
                     if expected != original {
-                        conflicts.add_balance(address, *original, *expected);
+                        conflicts.add_balance(address, *expected, *original);
                     }
                 }
             }
@@ -68,16 +100,39 @@ impl InMemoryTemporaryStorage {
             // check slots conflicts
             for (&slot_index, slot_change) in &change.slots {
                 if let Some(expected) = slot_change.take_original_ref() {
-                    let Some(original) = self.read_slot(address, slot_index)? else {
+                    let Some(original) = self.read_slot_from(pending, address, slot_index) else {
                         continue;
                     };
                     if expected.value != original.value {
-                        conflicts.add_slot(address, slot_index, original.value, expected.value);
+                        conflicts.add_slot(address, slot_index, expected.value, original.value);
                     }
                 }
             }
         }
-        Ok(conflicts.build())
+        conflicts.build()
+    }
+
+    /// Reads an account from the pending state, falling back to the candidate blocks and then to the finished blocks.
+    fn read_account_from(&self, pending: &InMemoryTemporaryStorageState, address: Address) -> Option<Account> {
+        self.find_in_states(pending, |state| state.accounts.get(&address).map(|account| account.info.clone()))
+    }
+
+    /// Reads a slot from the pending state, falling back to the candidate blocks and then to the finished blocks.
+    fn read_slot_from(&self, pending: &InMemoryTemporaryStorageState, address: Address, index: SlotIndex) -> Option<Slot> {
+        self.find_in_states(pending, |state| {
+            state.accounts.get(&address).and_then(|account| account.slots.get(&index)).copied()
+        })
+    }
+
+    /// Searches states from the newest to the oldest: pending block, candidate blocks and finished blocks.
+    fn find_in_states<T>(&self, pending: &InMemoryTemporaryStorageState, find: impl Fn(&InMemoryTemporaryStorageState) -> Option<T>) -> Option<T> {
+        if let Some(found) = find(pending) {
+            return Some(found);
+        }
+        if let Some(found) = self.candidate_blocks.read().values().rev().find_map(|candidate| find(&candidate.state)) {
+            return Some(found);
+        }
+        self.finished_blocks.read().iter().rev().find_map(find)
     }
 }
 
@@ -95,17 +150,19 @@ pub struct InMemoryTemporaryStorageState {
 }
 
 impl InMemoryTemporaryStorageState {
-    pub fn new(block_number: BlockNumber) -> Self {
+    pub fn new(block_number: BlockNumber, timestamp: UnixTime) -> Self {
         Self {
-            block: PendingBlock::new_at_now(block_number),
+            block: PendingBlock::new(block_number, timestamp),
             accounts: HashMap::default(),
         }
     }
+}
 
-    pub fn reset(&mut self) {
-        self.block = PendingBlock::new_at_now(1.into());
-        self.accounts.clear();
-    }
+/// Block proposed in an epoch and awaiting confirmation.
+#[derive(Debug)]
+pub struct InMemoryBlockCandidate {
+    pub epoch: u64,
+    pub state: InMemoryTemporaryStorageState,
 }
 
 impl TemporaryStorage for InMemoryTemporaryStorage {
@@ -118,6 +175,11 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         self.pending_block.read().block.header.clone()
     }
 
+    fn configure_block_clock(&self, fixed_delta: Option<u64>) {
+        let pending_block = self.pending_block.read();
+        self.block_clock.configure(fixed_delta, *pending_block.block.header.timestamp);
+    }
+
     // -------------------------------------------------------------------------
     // Block and executions
     // -------------------------------------------------------------------------
@@ -127,7 +189,12 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         let pending_block = self.pending_block.upgradable_read();
         if let TransactionExecution::Local(tx) = &tx {
             if tx.evm_input != (&tx.input, &pending_block.block.header) {
-                let expected_input = EvmInput::from_eth_transaction(&tx.input, &pending_block.block.header);
+                // gas is not compared, so the expected input keeps the gas of the executed input
+                let expected_input = EvmInput {
+                    gas_limit: tx.evm_input.gas_limit,
+                    gas_price: tx.evm_input.gas_price,
+                    ..EvmInput::from_eth_transaction(&tx.input, &pending_block.block.header, GasMode::Free)
+                };
                 return Err(StratusError::TransactionEvmInputMismatch {
                     expected: Box::new(expected_input),
                     actual: Box::new(tx.evm_input.clone()),
@@ -138,7 +205,10 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         let mut pending_block = RwLockUpgradableReadGuard::<InMemoryTemporaryStorageState>::upgrade(pending_block);
 
         if check_conflicts {
-            if let Some(conflicts) = self.check_conflicts(tx.execution())? {
+            let start = metrics::now();
+            let conflicts = self.check_conflicts(&pending_block, tx.execution());
+            pending_block.block.conflict_check_time += start.elapsed();
+            if let Some(conflicts) = conflicts {
                 return Err(StratusError::TransactionConflict(conflicts.into()));
             }
         }
@@ -178,6 +248,12 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         Ok(())
     }
 
+    fn discard_pending_block(&self) -> anyhow::Result<PendingBlock> {
+        let mut pending_block = self.pending_block.write();
+        let next_state = self.new_state(pending_block.block.header.number);
+        Ok(std::mem::replace(&mut *pending_block, next_state).block)
+    }
+
     fn read_pending_executions(&self) -> Vec<TransactionExecution> {
         self.pending_block.read().block.transactions.iter().map(|(_, tx)| tx.clone()).collect()
     }
@@ -185,6 +261,11 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
     fn finish_pending_block(&self) -> anyhow::Result<PendingBlock> {
         let pending_block = self.pending_block.upgradable_read();
 
+        // the pending block is built on top of the candidates, so it cannot be finished before they are confirmed
+        if let Some(candidate) = self.candidate_blocks.read().keys().next() {
+            return log_and_err!(payload = candidate, "cannot finish the pending block while block candidates await confirmation");
+        }
+
         // This has to happen BEFORE creating the new state, because UnixTimeNow::default() may change the offset.
         #[cfg(feature = "dev")]
         let finished_block = {
@@ -197,24 +278,36 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
             finished_block
         };
 
-        let next_state = InMemoryTemporaryStorageState::new(pending_block.block.header.number.next_block_number());
+        let next_state = self.new_state(pending_block.block.header.number.next_block_number());
 
         let mut pending_block = RwLockUpgradableReadGuard::<InMemoryTemporaryStorageState>::upgrade(pending_block);
-        let mut latest = self.latest_block.write();
+        let mut finished_blocks = self.finished_blocks.write();
 
-        *latest = Some(std::mem::replace(&mut *pending_block, next_state));
+        finished_blocks.push_back(std::mem::replace(&mut *pending_block, next_state));
 
         drop(pending_block);
 
         #[cfg(not(feature = "dev"))]
         let finished_block = {
-            let latest = RwLockWriteGuard::<Option<InMemoryTemporaryStorageState>>::downgrade(latest);
-            latest.as_ref().expect("latest should be Some after finishing the pending block").block.clone()
+            let finished_blocks = RwLockWriteGuard::<VecDeque<InMemoryTemporaryStorageState>>::downgrade(finished_blocks);
+            finished_blocks
+                .back()
+                .expect("finished blocks should not be empty after finishing the pending block")
+                .block
+                .clone()
         };
 
         Ok(finished_block)
     }
 
+    fn release_persisted_blocks(&self, block_number: BlockNumber) -> anyhow::Result<()> {
+        let mut finished_blocks = self.finished_blocks.write();
+        while finished_blocks.front().is_some_and(|finished| finished.block.header.number <= block_number) {
+            finished_blocks.pop_front();
+        }
+        Ok(())
+    }
+
     fn read_pending_execution(&self, hash: Hash) -> anyhow::Result<Option<TransactionExecution>> {
         let pending_block = self.pending_block.read();
         match pending_block.block.transactions.get(&hash) {
@@ -223,42 +316,338 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         }
     }
 
+    // -------------------------------------------------------------------------
+    // Block candidates
+    // -------------------------------------------------------------------------
+
+    fn propose_pending_block(&self, epoch: u64) -> anyhow::Result<PendingBlock> {
+        let mut pending_block = self.pending_block.write();
+        let next_state = self.new_state(pending_block.block.header.number.next_block_number());
+        let state = std::mem::replace(&mut *pending_block, next_state);
+
+        let proposed_block = state.block.clone();
+        self.candidate_blocks
+            .write()
+            .insert(proposed_block.header.number, InMemoryBlockCandidate { epoch, state });
+
+        Ok(proposed_block)
+    }
+
+    fn promote_block_candidate(&self, id: BlockCandidateId) -> anyhow::Result<PendingBlock> {
+        let mut candidate_blocks = self.candidate_blocks.write();
+
+        // candidates are confirmed in order, because each one is built on top of the previous
+        let Some(oldest) = candidate_blocks.first_entry() else {
+            return log_and_err!(payload = id, "cannot promote block candidate because there are no candidates");
+        };
+        if *oldest.key() != id.number || oldest.get().epoch != id.epoch {
+            let oldest_id = BlockCandidateId {
+                number: *oldest.key(),
+                epoch: oldest.get().epoch,
+            };
+            return log_and_err!(payload = (id, oldest_id), "cannot promote block candidate because it is not the oldest one");
+        }
+
+        let candidate = oldest.remove();
+        let promoted_block = candidate.state.block.clone();
+        self.finished_blocks.write().push_back(candidate.state);
+
+        Ok(promoted_block)
+    }
+
+    fn discard_block_candidates(&self, from: BlockNumber) -> anyhow::Result<Vec<PendingBlock>> {
+        let mut pending_block = self.pending_block.write();
+        let mut candidate_blocks = self.candidate_blocks.write();
+        if not(candidate_blocks.contains_key(&from)) {
+            return log_and_err!(payload = from, "cannot discard block candidates because there is no candidate with the number");
+        }
+
+        let discarded_candidates = candidate_blocks.split_off(&from);
+        let discarded_pending = std::mem::replace(&mut *pending_block, self.new_state(from));
+
+        let mut discarded_blocks: Vec<PendingBlock> = discarded_candidates.into_values().map(|candidate| candidate.state.block).collect();
+        discarded_blocks.push(discarded_pending.block);
+        Ok(discarded_blocks)
+    }
+
+    fn read_block_candidates(&self) -> Vec<BlockCandidateId> {
+        self.candidate_blocks
+            .read()
+            .iter()
+            .map(|(number, candidate)| BlockCandidateId {
+                number: *number,
+                epoch: candidate.epoch,
+            })
+            .collect()
+    }
+
     // -------------------------------------------------------------------------
     // Accounts and Slots
     // -------------------------------------------------------------------------
 
     fn read_account(&self, address: Address) -> anyhow::Result<Option<Account>> {
-        Ok(match self.pending_block.read().accounts.get(&address) {
-            Some(pending_account) => Some(pending_account.info.clone()),
-            None => self
-                .latest_block
-                .read()
-                .as_ref()
-                .and_then(|latest| latest.accounts.get(&address))
-                .map(|account| account.info.clone()),
-        })
+        Ok(self.read_account_from(&self.pending_block.read(), address))
     }
 
     fn read_slot(&self, address: Address, index: SlotIndex) -> anyhow::Result<Option<Slot>> {
-        Ok(
-            match self.pending_block.read().accounts.get(&address).and_then(|account| account.slots.get(&index)) {
-                Some(pending_slot) => Some(*pending_slot),
-                None => self
-                    .latest_block
-                    .read()
-                    .as_ref()
-                    .and_then(|latest| latest.accounts.get(&address).and_then(|account| account.slots.get(&index)))
-                    .copied(),
-            },
-        )
+        Ok(self.read_slot_from(&self.pending_block.read(), address, index))
     }
 
     // -------------------------------------------------------------------------
     // Global state
     // -------------------------------------------------------------------------
     fn reset(&self) -> anyhow::Result<()> {
-        self.pending_block.write().reset();
-        *self.latest_block.write() = None;
+        *self.pending_block.write() = self.new_state(1.into());
+        self.candidate_blocks.write().clear();
+        self.finished_blocks.write().clear();
         Ok(())
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::eth::miner::Clock;
+    use crate::eth::primitives::ExecutionAccountChanges;
+    use crate::eth::primitives::ExecutionConflict;
+    use crate::eth::primitives::ExecutionValueChange;
+    use crate::eth::primitives::LocalTransactionExecution;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::Wei;
+
+    /// Finishes the pending block after setting the balance of an account in it.
+    fn finish_with_balance(storage: &InMemoryTemporaryStorage, address: Address, balance: u64) {
+        let mut account = AccountWithSlots::new(address);
+        account.info.balance = Wei::from(balance);
+        storage.pending_block.write().accounts.insert(address, account);
+        storage.finish_pending_block().unwrap();
+    }
+
+    fn read_balance(storage: &InMemoryTemporaryStorage, address: Address) -> Option<Wei> {
+        storage.read_account(address).unwrap().map(|account| account.balance)
+    }
+
+    /// Clock that never advances.
+    struct StoppedClock;
+
+    impl Clock for StoppedClock {
+        fn now(&self) -> UnixTime {
+            UnixTime::from(100)
+        }
+    }
+
+    #[test]
+    fn test_pending_block_timestamps_come_from_block_clock() {
+        let storage = InMemoryTemporaryStorage::new_with_clock(1.into(), BlockClock::new(Box::new(StoppedClock)));
+        assert_eq!(*storage.read_pending_block_header().timestamp, UnixTime::from(100));
+
+        // the clock does not advance, but timestamps are strictly increasing
+        storage.finish_pending_block().unwrap();
+        assert_eq!(*storage.read_pending_block_header().timestamp, UnixTime::from(101));
+
+        // a fixed delta continues from the pending block
+        storage.configure_block_clock(Some(10));
+        storage.finish_pending_block().unwrap();
+        assert_eq!(*storage.read_pending_block_header().timestamp, UnixTime::from(111));
+    }
+
+    #[test]
+    fn test_finished_blocks_are_readable_until_persisted() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let (first, second) = (Address::from([1; 20]), Address::from([2; 20]));
+
+        // blocks 1 and 2 are finished before being persisted
+        finish_with_balance(&storage, first, 10);
+        finish_with_balance(&storage, second, 20);
+        finish_with_balance(&storage, first, 30);
+        assert_eq!(read_balance(&storage, first), Some(Wei::from(30u64)));
+        assert_eq!(read_balance(&storage, second), Some(Wei::from(20u64)));
+
+        // block 2 is persisted
+        storage.release_persisted_blocks(2.into()).unwrap();
+        assert_eq!(read_balance(&storage, first), Some(Wei::from(30u64)));
+        assert_eq!(read_balance(&storage, second), None);
+
+        // block 3 is persisted
+        storage.release_persisted_blocks(3.into()).unwrap();
+        assert_eq!(read_balance(&storage, first), None);
+    }
+
+    /// Proposes the pending block after setting the balance of an account in it.
+    fn propose_with_balance(storage: &InMemoryTemporaryStorage, address: Address, balance: u64, epoch: u64) -> BlockCandidateId {
+        let mut account = AccountWithSlots::new(address);
+        account.info.balance = Wei::from(balance);
+        storage.pending_block.write().accounts.insert(address, account);
+        let block = storage.propose_pending_block(epoch).unwrap();
+        BlockCandidateId {
+            number: block.header.number,
+            epoch,
+        }
+    }
+
+    #[test]
+    fn test_block_candidates_promote_and_discard() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let address = Address::from([1; 20]);
+
+        // blocks 1, 2 and 3 are proposed on top of each other
+        let first = propose_with_balance(&storage, address, 10, 7);
+        let second = propose_with_balance(&storage, address, 20, 7);
+        propose_with_balance(&storage, address, 30, 7);
+        assert_eq!(storage.read_block_candidates().len(), 3);
+        assert_eq!(storage.read_pending_block_header().number, BlockNumber::from(4));
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(30u64)));
+        assert!(storage.finish_pending_block().is_err());
+
+        // candidates are promoted only in order and in the proposed epoch
+        assert!(storage.promote_block_candidate(second).is_err());
+        assert!(storage.promote_block_candidate(BlockCandidateId { epoch: 8, ..first }).is_err());
+        assert_eq!(storage.promote_block_candidate(first).unwrap().header.number, first.number);
+        assert_eq!(storage.read_block_candidates().len(), 2);
+
+        // discarding block 2 discards block 3 and the pending block built on top of them
+        let discarded = storage.discard_block_candidates(second.number).unwrap();
+        assert_eq!(discarded.len(), 3);
+        assert!(storage.read_block_candidates().is_empty());
+        assert_eq!(storage.read_pending_block_header().number, second.number);
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(10u64)));
+
+        // the promoted block is released after persisted
+        storage.release_persisted_blocks(first.number).unwrap();
+        assert_eq!(read_balance(&storage, address), None);
+    }
+
+    /// Creates a local execution that changes the balance of an account after reading the original balance.
+    fn balance_execution(storage: &InMemoryTemporaryStorage, address: Address, original: u64, modified: u64) -> TransactionExecution {
+        let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, Wei::from(original)));
+        changes.balance.set_modified(Wei::from(modified));
+
+        let mut tx: LocalTransactionExecution = Faker.fake();
+        tx.evm_input = EvmInput::from_eth_transaction(&tx.input, &storage.read_pending_block_header(), GasMode::Free);
+        tx.result.execution.changes = [(address, changes)].into_iter().collect();
+        TransactionExecution::Local(tx)
+    }
+
+    #[test]
+    fn test_pending_executions_interleaved_with_block_candidates() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let address = Address::from([1; 20]);
+        let save = |original, modified| storage.save_pending_execution(balance_execution(&storage, address, original, modified), true);
+
+        // block 1 is proposed with one execution
+        save(0, 10).unwrap();
+        assert_eq!(storage.propose_pending_block(7).unwrap().transactions.len(), 1);
+
+        // executions of block 2 are checked against the state of block 1
+        assert!(matches!(save(0, 15), Err(StratusError::TransactionConflict(_))));
+        save(10, 20).unwrap();
+        storage.propose_pending_block(7).unwrap();
+
+        // block 3 is executed on top of both candidates
+        save(20, 30).unwrap();
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(30u64)));
+
+        // promoting block 1 keeps the blocks built on top of it
+        let promoted = storage.promote_block_candidate(BlockCandidateId { number: 1.into(), epoch: 7 }).unwrap();
+        assert_eq!(promoted.transactions.len(), 1);
+        assert_eq!(storage.read_pending_executions().len(), 1);
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(30u64)));
+
+        // discarding block 2 also discards the executions of block 3
+        let discarded = storage.discard_block_candidates(2.into()).unwrap();
+        assert_eq!(discarded.iter().map(|block| block.transactions.len()).collect_vec(), vec![1, 1]);
+        assert!(storage.read_pending_executions().is_empty());
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(10u64)));
+
+        // the new block 2 is executed on top of the promoted block and finished normally
+        assert!(matches!(save(20, 25), Err(StratusError::TransactionConflict(_))));
+        save(10, 25).unwrap();
+        let finished = storage.finish_pending_block().unwrap();
+        assert_eq!(finished.header.number, BlockNumber::from(2));
+        assert_eq!(finished.transactions.len(), 1);
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(25u64)));
+    }
+
+    #[test]
+    fn test_discarded_block_candidate_is_proposed_again_in_new_epoch() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let address = Address::from([1; 20]);
+
+        storage.save_pending_execution(balance_execution(&storage, address, 0, 10), true).unwrap();
+        storage.propose_pending_block(1).unwrap();
+        storage.discard_block_candidates(1.into()).unwrap();
+
+        // the block is rebuilt with other executions and proposed in the next epoch
+        storage.save_pending_execution(balance_execution(&storage, address, 0, 20), true).unwrap();
+        storage.propose_pending_block(2).unwrap();
+        assert_eq!(storage.read_block_candidates(), vec![BlockCandidateId { number: 1.into(), epoch: 2 }]);
+
+        // only the candidate of the new epoch can be promoted
+        assert!(storage.promote_block_candidate(BlockCandidateId { number: 1.into(), epoch: 1 }).is_err());
+        storage.promote_block_candidate(BlockCandidateId { number: 1.into(), epoch: 2 }).unwrap();
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(20u64)));
+    }
+
+    #[test]
+    fn test_conflicts_report_read_and_pending_values() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let address = Address::from([1; 20]);
+        let index = SlotIndex::from(1u64);
+
+        // the pending block has values different from the ones read by the execution
+        let mut account = AccountWithSlots::new(address);
+        account.info.balance = Wei::from(20u64);
+        account.slots.insert(index, Slot::new(index, SlotValue::from(2u64)));
+        storage.pending_block.write().accounts.insert(address, account);
+
+        let mut changes = ExecutionAccountChanges::from_original_values(Account::new_with_balance(address, Wei::from(10u64)));
+        changes
+            .slots
+            .insert(index, ExecutionValueChange::from_original(Slot::new(index, SlotValue::from(1u64))));
+        let mut execution: EvmExecution = Faker.fake();
+        execution.changes = [(address, changes)].into_iter().collect();
+
+        let conflicts = storage.check_conflicts(&storage.pending_block.read(), &execution).unwrap();
+        assert_eq!(
+            conflicts.iter().collect_vec(),
+            vec![
+                &ExecutionConflict::Balance {
+                    address,
+                    expected: Wei::from(10u64),
+                    actual: Wei::from(20u64)
+                },
+                &ExecutionConflict::Slot {
+                    address,
+                    slot: index,
+                    expected: SlotValue::from(1u64),
+                    actual: SlotValue::from(2u64)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discard_pending_block() {
+        let storage = InMemoryTemporaryStorage::new(1.into());
+        let address = Address::from([1; 20]);
+        finish_with_balance(&storage, address, 10);
+
+        let mut account = AccountWithSlots::new(address);
+        account.info.balance = Wei::from(20u64);
+        storage.pending_block.write().accounts.insert(address, account);
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(20u64)));
+
+        // the pending block restarts at the same number on top of the finished block
+        let discarded = storage.discard_pending_block().unwrap();
+        assert_eq!(discarded.header.number, BlockNumber::from(2));
+        assert_eq!(storage.read_pending_block_header().number, BlockNumber::from(2));
+        assert_eq!(read_balance(&storage, address), Some(Wei::from(10u64)));
+    }
+}
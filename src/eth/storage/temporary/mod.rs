@@ -9,9 +9,10 @@ use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 
-use super::PermanentStorage;
+use super::ReadStorage;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::PendingBlockHeader;
@@ -29,22 +30,58 @@ pub trait TemporaryStorage: Send + Sync + 'static {
     // Retrieves the block number being mined.
     fn read_pending_block_header(&self) -> PendingBlockHeader;
 
+    /// Configures the timestamps of the next pending blocks to increase by a fixed delta, or to follow the system clock if `None`.
+    ///
+    /// The timestamps continue from the timestamp of the current pending block.
+    fn configure_block_clock(&self, fixed_delta: Option<u64>);
+
     // -------------------------------------------------------------------------
     // Block and executions
     // -------------------------------------------------------------------------
 
     /// Finishes the mining of the pending block and starts a new block.
+    ///
+    /// The state of the finished block remains readable until it is released by [`Self::release_persisted_blocks`].
     fn finish_pending_block(&self) -> anyhow::Result<PendingBlock>;
 
+    /// Discards the state of finished blocks up to the specified number after they are persisted in the permanent storage.
+    fn release_persisted_blocks(&self, block_number: BlockNumber) -> anyhow::Result<()>;
+
     /// Saves a transaction execution to the pending mined block.
     fn save_pending_execution(&self, tx: TransactionExecution, check_conflicts: bool) -> Result<(), StratusError>;
 
+    /// Discards the executions of the pending block, restarting it at the same number.
+    ///
+    /// Returns the discarded block.
+    fn discard_pending_block(&self) -> anyhow::Result<PendingBlock>;
+
     /// Retrieves all transaction executions from the pending block.
     fn read_pending_executions(&self) -> Vec<TransactionExecution>;
 
     /// Retrieves a single transaction execution from the pending block.
     fn read_pending_execution(&self, hash: Hash) -> anyhow::Result<Option<TransactionExecution>>;
 
+    // -------------------------------------------------------------------------
+    // Block candidates
+    // -------------------------------------------------------------------------
+
+    /// Finishes the mining of the pending block as a candidate awaiting confirmation in the specified epoch, and starts a new block on top of it.
+    ///
+    /// The state of the candidate remains readable until it is discarded, or promoted and released by [`Self::release_persisted_blocks`].
+    fn propose_pending_block(&self, epoch: u64) -> anyhow::Result<PendingBlock>;
+
+    /// Promotes the oldest candidate after it is confirmed, so it can be persisted like a block finished by [`Self::finish_pending_block`].
+    fn promote_block_candidate(&self, id: BlockCandidateId) -> anyhow::Result<PendingBlock>;
+
+    /// Discards the candidates from the specified number onwards and the pending block built on top of them, restarting the pending block at that
+    /// number.
+    ///
+    /// Returns the discarded blocks, including the pending one.
+    fn discard_block_candidates(&self, from: BlockNumber) -> anyhow::Result<Vec<PendingBlock>>;
+
+    /// Retrieves the identifiers of the candidates awaiting confirmation, from the oldest to the newest.
+    fn read_block_candidates(&self) -> Vec<BlockCandidateId>;
+
     // -------------------------------------------------------------------------
     // Accounts and slots
     // -------------------------------------------------------------------------
@@ -63,6 +100,13 @@ pub trait TemporaryStorage: Send + Sync + 'static {
     fn reset(&self) -> anyhow::Result<()>;
 }
 
+/// Identifies a block candidate proposed in an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct BlockCandidateId {
+    pub number: BlockNumber,
+    pub epoch: u64,
+}
+
 // -----------------------------------------------------------------------------
 // Config
 // -----------------------------------------------------------------------------
@@ -84,7 +128,7 @@ pub enum TemporaryStorageKind {
 
 impl TemporaryStorageConfig {
     /// Initializes temporary storage implementation.
-    pub fn init(&self, perm_storage: &dyn PermanentStorage) -> anyhow::Result<Box<dyn TemporaryStorage>> {
+    pub fn init(&self, perm_storage: &dyn ReadStorage) -> anyhow::Result<Box<dyn TemporaryStorage>> {
         tracing::info!(config = ?self, "creating temporary storage");
         let pending_block_number = perm_storage.read_mined_block_number()? + 1;
         match self.temp_storage_kind {
@@ -7,17 +7,28 @@ use std::time::Duration;
 use anyhow::anyhow;
 use chrono::DateTime;
 use chrono::Utc;
+#[cfg(feature = "node")]
 use jsonrpsee::types::SubscriptionId;
 use rust_decimal::Decimal;
 use serde::Serialize;
 use serde::Serializer;
+#[cfg(feature = "node")]
 use tokio::select;
+#[cfg(feature = "node")]
 use tokio::signal::unix::signal;
+#[cfg(feature = "node")]
 use tokio::signal::unix::SignalKind;
 
 use crate::eth::primitives::StratusError;
+#[cfg(feature = "node")]
+use crate::infra::task_registry;
+#[cfg(feature = "node")]
+use crate::infra::task_registry::TaskKind;
+#[cfg(feature = "node")]
 use crate::infra::tracing::info_task_spawn;
-use crate::log_and_err;
+#[cfg(feature = "node")]
+use crate::ChainState;
+#[cfg(feature = "node")]
 use crate::GlobalState;
 
 // -----------------------------------------------------------------------------
@@ -97,6 +108,7 @@ impl DisplayExt for std::time::Duration {
     }
 }
 
+#[cfg(feature = "node")]
 impl DisplayExt for SubscriptionId<'_> {
     fn to_string_ext(&self) -> String {
         match self {
@@ -161,6 +173,42 @@ impl InfallibleExt<DateTime<Utc>, ()> for Option<DateTime<Utc>> {
     }
 }
 
+/// Logs an error and also wrap the existing error with the provided message.
+#[macro_export]
+macro_rules! log_and_err {
+    // with reason: wrap the original error with provided message
+    (reason = $error:ident, payload = $payload:expr, $msg:expr) => {
+        {
+            use anyhow::Context;
+            tracing::error!(reason = ?$error, payload = ?$payload, message = %$msg);
+            Err($error).context($msg)
+        }
+    };
+    (reason = $error:ident, $msg:expr) => {
+        {
+            use anyhow::Context;
+            tracing::error!(reason = ?$error, message = %$msg);
+            Err($error).context($msg)
+        }
+    };
+    // without reason: generate a new error using provided message
+    (payload = $payload:expr, $msg:expr) => {
+        {
+            use anyhow::anyhow;
+            tracing::error!(payload = ?$payload, message = %$msg);
+            let message = format!("{} | payload={:?}", $msg, $payload);
+            Err(anyhow!(message))
+        }
+    };
+    ($msg:expr) => {
+        {
+            use anyhow::anyhow;
+            tracing::error!(message = %$msg);
+            Err(anyhow!($msg))
+        }
+    };
+}
+
 pub trait MutexResultExt<T> {
     fn map_lock_error(self, function_name: &str) -> Result<T, StratusError>;
 }
@@ -200,6 +248,7 @@ pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
 // -----------------------------------------------------------------------------
 
 /// Indicates why a sleep is happening.
+#[cfg(feature = "node")]
 #[derive(Debug, strum::Display)]
 pub enum SleepReason {
     /// Task is executed at predefined intervals.
@@ -216,7 +265,7 @@ pub enum SleepReason {
 }
 
 /// Sleeps the current task and tracks why it is sleeping.
-#[cfg(feature = "tracing")]
+#[cfg(all(feature = "node", feature = "tracing"))]
 #[inline(always)]
 pub async fn traced_sleep(duration: Duration, reason: SleepReason) {
     use tracing::Instrument;
@@ -230,13 +279,14 @@ pub async fn traced_sleep(duration: Duration, reason: SleepReason) {
     .await;
 }
 
-#[cfg(not(feature = "tracing"))]
+#[cfg(all(feature = "node", not(feature = "tracing")))]
 #[inline(always)]
 pub async fn traced_sleep(duration: Duration, _: SleepReason) {
     tokio::time::sleep(duration).await;
 }
 
-/// Spawns an async Tokio task with a name to be displayed in tokio-console.
+/// Spawns an async Tokio task with a name to be displayed in tokio-console and tracked by the task registry. Task runs in the current chain.
+#[cfg(feature = "node")]
 #[track_caller]
 pub fn spawn_named<T>(name: &str, task: impl std::future::Future<Output = T> + Send + 'static) -> tokio::task::JoinHandle<T>
 where
@@ -244,13 +294,22 @@ where
 {
     info_task_spawn(name);
 
-    tokio::task::Builder::new()
+    // task names are only available with tokio unstable features, which are enabled only when tokio-console is used
+    let task = ChainState::current().scope(task_registry::track_async(name, task));
+
+    #[cfg(tokio_unstable)]
+    let handle = tokio::task::Builder::new()
         .name(name)
         .spawn(task)
-        .expect("spawning named async task should not fail")
+        .expect("spawning named async task should not fail");
+    #[cfg(not(tokio_unstable))]
+    let handle = tokio::task::spawn(task);
+
+    handle
 }
 
-/// Spawns a blocking Tokio task with a name to be displayed in tokio-console.
+/// Spawns a blocking Tokio task with a name to be displayed in tokio-console and tracked by the task registry. Task runs in the current chain.
+#[cfg(feature = "node")]
 #[track_caller]
 pub fn spawn_blocking_named<T>(name: &str, task: impl FnOnce() -> T + Send + 'static) -> tokio::task::JoinHandle<T>
 where
@@ -258,13 +317,26 @@ where
 {
     info_task_spawn(name);
 
-    tokio::task::Builder::new()
+    let task = task_registry::track_blocking(name, TaskKind::Blocking, task);
+    let chain = ChainState::current();
+    let task = move || {
+        let _chain = chain.enter();
+        task()
+    };
+
+    #[cfg(tokio_unstable)]
+    let handle = tokio::task::Builder::new()
         .name(name)
         .spawn_blocking(task)
-        .expect("spawning named blocking task should not fail")
+        .expect("spawning named blocking task should not fail");
+    #[cfg(not(tokio_unstable))]
+    let handle = tokio::task::spawn_blocking(task);
+
+    handle
 }
 
-/// Spawns a thread with the given name. Thread has access to Tokio current runtime.
+/// Spawns a thread with the given name, tracked by the task registry. Thread has access to Tokio current runtime and runs in the current chain.
+#[cfg(feature = "node")]
 #[track_caller]
 pub fn spawn_thread<T>(name: &str, task: impl FnOnce() -> T + Send + 'static) -> std::thread::JoinHandle<T>
 where
@@ -272,17 +344,22 @@ where
 {
     info_task_spawn(name);
 
+    let task = task_registry::track_blocking(name, TaskKind::Thread, task);
+
     let runtime = tokio::runtime::Handle::current();
+    let chain = ChainState::current();
     std::thread::Builder::new()
         .name(name.into())
         .spawn(move || {
             let _runtime_guard = runtime.enter();
+            let _chain = chain.enter();
             task()
         })
         .expect("spawning background thread should not fail")
 }
 
 /// Spawns a handler that listens to system signals.
+#[cfg(feature = "node")]
 pub async fn spawn_signal_handler() -> anyhow::Result<()> {
     const TASK_NAME: &str = "signal-handler";
 
@@ -405,3 +482,38 @@ macro_rules! gen_test_bincode {
         }
     };
 }
+
+/// Number of random values checked by each test generated with [`gen_test_roundtrip`].
+pub const ROUNDTRIP_TEST_ITERATIONS: usize = 1_000;
+
+/// Generates unit test that checks that converting random values to another representation and back restores the original value.
+///
+/// Catches conversions that silently truncate or wrap values or that fail for valid values. When the other representation cannot hold every
+/// value, `domain` tells which values it can hold, and the conversion of values outside of it must fail instead of changing them.
+#[macro_export]
+macro_rules! gen_test_roundtrip {
+    ($type:ty, $name:ident, $roundtrip:expr) => {
+        $crate::gen_test_roundtrip!($type, $name, $roundtrip, domain = |_| true);
+    };
+    ($type:ty, $name:ident, $roundtrip:expr, domain = $domain:expr) => {
+        paste::paste! {
+            #[test]
+            pub fn [<roundtrip_ $name _ $type:snake>]() {
+                let roundtrip: fn($type) -> anyhow::Result<$type> = $roundtrip;
+                let domain: fn(&$type) -> bool = $domain;
+                for _ in 0..$crate::ext::ROUNDTRIP_TEST_ITERATIONS {
+                    let original = <fake::Faker as fake::Fake>::fake::<$type>(&fake::Faker);
+                    match roundtrip(original.clone()) {
+                        Ok(converted) => {
+                            assert!(domain(&original), concat!(stringify!($name), " roundtrip accepted a value of ", stringify!($type), " outside of its domain: {:?}"), original);
+                            assert_eq!(converted, original, concat!(stringify!($name), " roundtrip changed the value of ", stringify!($type)));
+                        }
+                        Err(e) => {
+                            assert!($crate::ext::not(domain(&original)), concat!(stringify!($name), " roundtrip failed for {:?}: {:?}"), original, e);
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
@@ -1,11 +1,18 @@
+use std::cell::RefCell;
 use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
 
 use chrono::DateTime;
 use chrono::Utc;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use pin_project::pin_project;
 use sentry::ClientInitGuard;
 use serde::Deserialize;
 use serde::Serialize;
@@ -22,6 +29,7 @@ use crate::eth::follower::importer::Importer;
 use crate::eth::rpc::RpcContext;
 use crate::ext::not;
 use crate::ext::spawn_signal_handler;
+use crate::infra::sentry::set_sentry_tag;
 use crate::infra::tracing::warn_task_cancellation;
 
 // -----------------------------------------------------------------------------
@@ -64,11 +72,15 @@ where
 
         // init tracing
         tokio.block_on(async {
-            common.tracing.init(&common.sentry).expect("failed to init tracing");
+            common.tracing.init(&common.sentry, &common.alerting).expect("failed to init tracing");
         });
 
         // init observability services
-        common.metrics.init().expect("failed to init metrics");
+        tokio.block_on(async {
+            let otlp_meter = common.tracing.init_metrics().expect("failed to init opentelemetry metrics");
+            common.metrics.init(otlp_meter).expect("failed to init metrics");
+        });
+        common.selector_registry.init().expect("failed to init selector registry");
 
         // init sentry
         let sentry_guard = common
@@ -102,13 +114,130 @@ pub enum NodeMode {
     /// Fake leader feches a block, re-executes its txs and then mines it's own block.
     #[strum(to_string = "fake-leader")]
     FakeLeader,
+
+    /// Archive serves historical data from a read-only permanent storage, without importing, mining or accepting transactions.
+    #[strum(to_string = "archive")]
+    Archive,
+}
+
+// -----------------------------------------------------------------------------
+// Chain state
+// -----------------------------------------------------------------------------
+
+/// State of a chain hosted by the process.
+///
+/// The `stratus` binary hosts a single chain, whose state is the process state. The `stratus-multi` binary hosts multiple chains, each one with
+/// its own state, so shutting down a chain, changing its node mode or disabling its transactions does not affect the others.
+///
+/// The chain being executed is tracked by thread. It is entered with [`ChainState::enter`] and [`ChainState::scope`], and it is propagated to
+/// tasks and threads spawned with the `ext` helpers. Code running outside a chain uses the process state.
+#[derive(Debug)]
+pub struct ChainState {
+    /// Name of the chain when the process hosts multiple chains.
+    name: Option<String>,
+
+    /// Cancelled when the chain is shutting down.
+    shutdown_signal: CancellationToken,
+
+    /// Transaction should be accepted?
+    transactions_enabled: AtomicBool,
+
+    /// Unknown clients can interact with the chain?
+    unknown_client_enabled: AtomicBool,
+
+    /// Current node mode.
+    node_mode: Mutex<NodeMode>,
+}
+
+/// State of the process, used when no chain was entered.
+static PROCESS_CHAIN: Lazy<Arc<ChainState>> = Lazy::new(|| Arc::new(ChainState::create(None, STRATUS_SHUTDOWN_SIGNAL.clone(), NodeMode::Follower, true)));
+
+thread_local! {
+    /// Chain entered by the current thread.
+    static CURRENT_CHAIN: RefCell<Option<Arc<ChainState>>> = const { RefCell::new(None) };
+}
+
+impl ChainState {
+    /// Creates the state of a named chain. The chain is shut down when the process is shut down, but it can also be shut down alone.
+    pub fn new(name: impl Into<String>, node_mode: NodeMode, unknown_client_enabled: bool) -> Arc<Self> {
+        Arc::new(Self::create(
+            Some(name.into()),
+            STRATUS_SHUTDOWN_SIGNAL.child_token(),
+            node_mode,
+            unknown_client_enabled,
+        ))
+    }
+
+    fn create(name: Option<String>, shutdown_signal: CancellationToken, node_mode: NodeMode, unknown_client_enabled: bool) -> Self {
+        Self {
+            name,
+            shutdown_signal,
+            transactions_enabled: AtomicBool::new(true),
+            unknown_client_enabled: AtomicBool::new(unknown_client_enabled),
+            node_mode: Mutex::new(node_mode),
+        }
+    }
+
+    /// Returns the state of the chain entered by the current thread, or the process state if no chain was entered.
+    pub fn current() -> Arc<ChainState> {
+        CURRENT_CHAIN
+            .with(|current| current.borrow().clone())
+            .unwrap_or_else(|| Arc::clone(&PROCESS_CHAIN))
+    }
+
+    /// Enters the chain in the current thread until the returned guard is dropped.
+    pub fn enter(self: &Arc<Self>) -> ChainStateGuard {
+        let previous = CURRENT_CHAIN.with(|current| current.replace(Some(Arc::clone(self))));
+        ChainStateGuard { previous }
+    }
+
+    /// Enters the chain every time the future is polled.
+    pub fn scope<F: Future>(self: Arc<Self>, future: F) -> ChainStateScope<F> {
+        ChainStateScope { chain: self, future }
+    }
+
+    /// Name of the chain when the process hosts multiple chains.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Restores the chain previously entered by the thread when dropped.
+pub struct ChainStateGuard {
+    previous: Option<Arc<ChainState>>,
+}
+
+impl Drop for ChainStateGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_CHAIN.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Future that executes inside a chain.
+#[pin_project]
+pub struct ChainStateScope<F> {
+    chain: Arc<ChainState>,
+    #[pin]
+    future: F,
+}
+
+impl<F: Future> Future for ChainStateScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _chain = this.chain.enter();
+        this.future.poll(cx)
+    }
 }
 
 // -----------------------------------------------------------------------------
 // Global state
 // -----------------------------------------------------------------------------
 
-pub static STRATUS_SHUTDOWN_SIGNAL: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+/// Cancelled when the process is shutting down.
+static STRATUS_SHUTDOWN_SIGNAL: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
 
 /// Importer is running or being shut-down?
 static IMPORTER_SHUTDOWN: AtomicBool = AtomicBool::new(true);
@@ -116,15 +245,6 @@ static IMPORTER_SHUTDOWN: AtomicBool = AtomicBool::new(true);
 /// A guard that is taken when importer is running.
 pub static IMPORTER_ONLINE_TASKS_SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(Importer::TASKS_COUNT));
 
-/// Transaction should be accepted?
-static TRANSACTIONS_ENABLED: AtomicBool = AtomicBool::new(true);
-
-/// Unknown clients can interact with the application?
-static UNKNOWN_CLIENT_ENABLED: AtomicBool = AtomicBool::new(true);
-
-/// Current node mode.
-static NODE_MODE: Mutex<NodeMode> = Mutex::new(NodeMode::Follower);
-
 static START_TIME: Lazy<DateTime<Utc>> = Lazy::new(Utc::now);
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -135,18 +255,24 @@ impl GlobalState {
     // Application Shutdown
     // -------------------------------------------------------------------------
 
-    /// Shutdown the application.
+    /// Shutdown the application, or only the current chain when the process hosts multiple chains.
     ///
     /// Returns the formatted reason for shutdown.
     pub fn shutdown_from(caller: &str, reason: &str) -> String {
-        tracing::warn!(%caller, %reason, "application is shutting down");
-        STRATUS_SHUTDOWN_SIGNAL.cancel();
+        let chain = ChainState::current();
+        tracing::warn!(%caller, %reason, chain = chain.name(), "application is shutting down");
+        chain.shutdown_signal.cancel();
         format!("{} {}", caller, reason)
     }
 
     /// Checks if the application is being shutdown.
     pub fn is_shutdown() -> bool {
-        STRATUS_SHUTDOWN_SIGNAL.is_cancelled()
+        ChainState::current().shutdown_signal.is_cancelled()
+    }
+
+    /// Returns the signal that is cancelled when the application is being shutdown.
+    pub fn shutdown_signal() -> CancellationToken {
+        ChainState::current().shutdown_signal.clone()
     }
 
     /// Checks if the application is being shutdown. Emits an warning with the task name in case it is.
@@ -160,7 +286,7 @@ impl GlobalState {
 
     /// Waits until a shutdown is signalled.
     pub async fn wait_shutdown() {
-        STRATUS_SHUTDOWN_SIGNAL.cancelled().await;
+        Self::shutdown_signal().cancelled().await;
     }
 
     /// Waits until a shutdown is signalled. Emits an warning with the task name when it is.
@@ -216,12 +342,12 @@ impl GlobalState {
 
     /// Sets whether transactions should be accepted.
     pub fn set_transactions_enabled(enabled: bool) {
-        TRANSACTIONS_ENABLED.store(enabled, Ordering::Relaxed);
+        ChainState::current().transactions_enabled.store(enabled, Ordering::Relaxed);
     }
 
     /// Checks if transactions are enabled.
     pub fn is_transactions_enabled() -> bool {
-        TRANSACTIONS_ENABLED.load(Ordering::Relaxed)
+        ChainState::current().transactions_enabled.load(Ordering::Relaxed)
     }
 
     // -------------------------------------------------------------------------
@@ -230,12 +356,12 @@ impl GlobalState {
 
     /// Enables or disables the unknown client.
     pub fn set_unknown_client_enabled(enabled: bool) {
-        UNKNOWN_CLIENT_ENABLED.store(enabled, Ordering::Relaxed);
+        ChainState::current().unknown_client_enabled.store(enabled, Ordering::Relaxed);
     }
 
     /// Checks if the unknown client is enabled.
     pub fn is_unknown_client_enabled() -> bool {
-        UNKNOWN_CLIENT_ENABLED.load(Ordering::Relaxed)
+        ChainState::current().unknown_client_enabled.load(Ordering::Relaxed)
     }
 
     // -------------------------------------------------------------------------
@@ -261,11 +387,17 @@ impl GlobalState {
     }
 
     pub fn set_node_mode(mode: NodeMode) {
-        *NODE_MODE.lock() = mode;
+        *ChainState::current().node_mode.lock() = mode;
+        set_sentry_tag("node_mode", mode);
     }
 
     pub fn get_node_mode() -> NodeMode {
-        *NODE_MODE.lock()
+        *ChainState::current().node_mode.lock()
+    }
+
+    /// Name of the current chain when the process hosts multiple chains.
+    pub fn get_chain_name() -> Option<String> {
+        ChainState::current().name.clone()
     }
 
     // -------------------------------------------------------------------------
@@ -284,6 +416,7 @@ impl GlobalState {
         };
 
         json!({
+            "chain": Self::get_chain_name(),
             "is_leader": Self::get_node_mode() == NodeMode::Leader,
             "is_shutdown": Self::is_shutdown(),
             "is_importer_shutdown": Self::is_importer_shutdown(),
@@ -304,3 +437,95 @@ impl GlobalState {
         Lazy::force(&START_TIME);
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext::spawn_blocking_named;
+    use crate::ext::spawn_named;
+    use crate::ext::spawn_thread;
+
+    #[test]
+    fn test_chains_have_isolated_state() {
+        let chain_a = ChainState::new("a", NodeMode::Leader, false);
+        let chain_b = ChainState::new("b", NodeMode::Follower, true);
+
+        {
+            let _chain = chain_a.enter();
+            GlobalState::set_transactions_enabled(false);
+            assert_eq!(GlobalState::get_chain_name().as_deref(), Some("a"));
+            assert_eq!(GlobalState::get_node_mode(), NodeMode::Leader);
+            assert!(not(GlobalState::is_transactions_enabled()));
+            assert!(not(GlobalState::is_unknown_client_enabled()));
+        }
+        {
+            let _chain = chain_b.enter();
+            assert_eq!(GlobalState::get_chain_name().as_deref(), Some("b"));
+            assert_eq!(GlobalState::get_node_mode(), NodeMode::Follower);
+            assert!(GlobalState::is_transactions_enabled());
+            assert!(GlobalState::is_unknown_client_enabled());
+        }
+        assert_eq!(GlobalState::get_chain_name(), None);
+    }
+
+    #[test]
+    fn test_entered_chains_are_restored_when_guards_are_dropped() {
+        let chain_a = ChainState::new("a", NodeMode::Leader, true);
+        let chain_b = ChainState::new("b", NodeMode::Leader, true);
+
+        let guard_a = chain_a.enter();
+        {
+            let _guard_b = chain_b.enter();
+            assert_eq!(GlobalState::get_chain_name().as_deref(), Some("b"));
+        }
+        assert_eq!(GlobalState::get_chain_name().as_deref(), Some("a"));
+        drop(guard_a);
+        assert_eq!(GlobalState::get_chain_name(), None);
+    }
+
+    #[test]
+    fn test_chain_shutdown_does_not_shutdown_other_chains() {
+        let process = CancellationToken::new();
+        let chain_a = Arc::new(ChainState::create(Some("a".into()), process.child_token(), NodeMode::Leader, true));
+        let chain_b = Arc::new(ChainState::create(Some("b".into()), process.child_token(), NodeMode::Leader, true));
+
+        // shutting down a chain affects only the chain
+        {
+            let _chain = chain_a.enter();
+            GlobalState::shutdown_from("test", "chain a");
+            assert!(GlobalState::is_shutdown());
+        }
+        {
+            let _chain = chain_b.enter();
+            assert!(not(GlobalState::is_shutdown()));
+        }
+        assert!(not(process.is_cancelled()));
+
+        // shutting down the process affects all chains
+        process.cancel();
+        let _chain = chain_b.enter();
+        assert!(GlobalState::is_shutdown());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_chain_is_propagated_to_spawned_tasks_and_threads() {
+        let chain = ChainState::new("a", NodeMode::Leader, true);
+
+        let (task, blocking, thread) = chain
+            .scope(async {
+                let task = spawn_named("test::task", async { GlobalState::get_chain_name() });
+                let blocking = spawn_blocking_named("test::blocking", GlobalState::get_chain_name);
+                let thread = spawn_thread("test::thread", GlobalState::get_chain_name);
+                (task.await.unwrap(), blocking.await.unwrap(), thread.join().unwrap())
+            })
+            .await;
+
+        assert_eq!(task.as_deref(), Some("a"));
+        assert_eq!(blocking.as_deref(), Some("a"));
+        assert_eq!(thread.as_deref(), Some("a"));
+    }
+}
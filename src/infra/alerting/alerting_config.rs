@@ -0,0 +1,79 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::ext::parse_duration;
+use crate::infra::alerting::AlertingLayer;
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct AlertingConfig {
+    /// Webhook URL where alerts are sent to, like a Slack incoming webhook or the PagerDuty Events API.
+    #[arg(long = "alert-webhook-url", env = "ALERT_WEBHOOK_URL", required = false)]
+    pub alert_webhook_url: String,
+
+    /// Payload format expected by the webhook.
+    #[arg(long = "alert-format", env = "ALERT_FORMAT", default_value = "slack")]
+    pub alert_format: AlertingFormat,
+
+    /// PagerDuty integration key, required when the payload format is `pagerduty`.
+    #[arg(long = "alert-pagerduty-routing-key", env = "ALERT_PAGERDUTY_ROUTING_KEY")]
+    pub alert_pagerduty_routing_key: Option<String>,
+
+    /// Case-insensitive patterns searched in the message of error events. Only events matching at least one pattern are alerted.
+    #[arg(
+        long = "alert-patterns",
+        env = "ALERT_PATTERNS",
+        value_delimiter = ',',
+        default_value = "conflict,importer-online failed,newHeads,leader,watchdog"
+    )]
+    pub alert_patterns: Vec<String>,
+
+    /// Minimum interval between alerts of the same pattern, so a storm of errors generates a single alert.
+    #[arg(long = "alert-cooldown", env = "ALERT_COOLDOWN", value_parser = parse_duration, default_value = "5m")]
+    pub alert_cooldown: Duration,
+}
+
+impl AlertingConfig {
+    /// Creates the tracing layer that forwards matching error events to the webhook.
+    ///
+    /// Must be called inside a Tokio runtime because webhook requests are sent by a background task.
+    pub fn init(&self) -> anyhow::Result<AlertingLayer> {
+        if self.alert_format.is_pager_duty() && self.alert_pagerduty_routing_key.is_none() {
+            return Err(anyhow!("alert format \"pagerduty\" requires --alert-pagerduty-routing-key"));
+        }
+        Ok(AlertingLayer::new(self.clone()))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Format
+// -----------------------------------------------------------------------------
+
+/// Payload format of alerts sent to the webhook.
+#[derive(DebugAsJson, strum::Display, strum::EnumIs, Clone, Copy, Eq, PartialEq, serde::Serialize)]
+pub enum AlertingFormat {
+    /// Slack incoming webhook message.
+    #[serde(rename = "slack")]
+    #[strum(to_string = "slack")]
+    Slack,
+
+    /// PagerDuty Events API v2 trigger event.
+    #[serde(rename = "pagerduty")]
+    #[strum(to_string = "pagerduty")]
+    PagerDuty,
+}
+
+impl FromStr for AlertingFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "slack" => Ok(Self::Slack),
+            "pagerduty" => Ok(Self::PagerDuty),
+            s => Err(anyhow!("unknown alert format: {}", s)),
+        }
+    }
+}
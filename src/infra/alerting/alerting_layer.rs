@@ -0,0 +1,227 @@
+//! Alerting of critical errors.
+//!
+//! Error events whose message matches one of the configured patterns are forwarded to a webhook, so on-call workflows are notified of conditions like
+//! storage conflict storms, importer stalls or loss of the leader. It complements Sentry, which collects all errors but does not page anyone.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tracing::Event;
+use tracing::Level;
+use tracing::Subscriber;
+use tracing_serde::fields::AsMap;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use crate::alias::JsonValue;
+use crate::ext::not;
+use crate::ext::spawn_named;
+use crate::ext::to_json_string;
+use crate::ext::to_json_value;
+use crate::infra::alerting::AlertingConfig;
+use crate::infra::alerting::AlertingFormat;
+use crate::infra::build_info;
+
+/// Timeout of each webhook request.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tracing layer that forwards error events matching configured patterns to a webhook.
+pub struct AlertingLayer {
+    /// Lowercase patterns searched in event messages.
+    patterns: Vec<String>,
+
+    /// Minimum interval between alerts of the same pattern.
+    cooldown: Duration,
+
+    /// When the last alert of each pattern was sent.
+    last_alerts: Mutex<HashMap<String, Instant>>,
+
+    /// Channel consumed by the task that sends webhook requests.
+    tx: mpsc::UnboundedSender<Alert>,
+}
+
+impl AlertingLayer {
+    /// Creates the layer and spawns the task that sends webhook requests.
+    pub fn new(config: AlertingConfig) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let layer = Self::with_sender(&config, tx);
+        spawn_named("alerting::webhook", send_alerts(config, rx));
+        layer
+    }
+
+    fn with_sender(config: &AlertingConfig, tx: mpsc::UnboundedSender<Alert>) -> Self {
+        Self {
+            patterns: config
+                .alert_patterns
+                .iter()
+                .map(|pattern| pattern.trim().to_lowercase())
+                .filter(|pattern| not(pattern.is_empty()))
+                .collect(),
+            cooldown: config.alert_cooldown,
+            last_alerts: Mutex::new(HashMap::new()),
+            tx,
+        }
+    }
+
+    /// Returns the first pattern found in the message.
+    fn matching_pattern(&self, message: &str) -> Option<&str> {
+        let message = message.to_lowercase();
+        self.patterns.iter().find(|pattern| message.contains(pattern.as_str())).map(String::as_str)
+    }
+
+    /// Checks if the cooldown of the pattern expired, and if so, restarts it.
+    fn try_start_cooldown(&self, pattern: &str, now: Instant) -> bool {
+        let mut last_alerts = self.last_alerts.lock();
+        match last_alerts.get(pattern) {
+            Some(last) if now.duration_since(*last) < self.cooldown => false,
+            _ => {
+                last_alerts.insert(pattern.to_owned(), now);
+                true
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for AlertingLayer
+where
+    S: Subscriber,
+{
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+
+        let mut fields = to_json_value(event.field_map());
+        let Some(JsonValue::String(message)) = fields.as_object_mut().and_then(|fields| fields.remove("message")) else {
+            return;
+        };
+        let Some(pattern) = self.matching_pattern(&message) else { return };
+        if not(self.try_start_cooldown(pattern, Instant::now())) {
+            return;
+        }
+
+        let alert = Alert {
+            pattern: pattern.to_owned(),
+            target: event.metadata().target().to_owned(),
+            message,
+            fields,
+        };
+        let _ = self.tx.send(alert);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Alert
+// -----------------------------------------------------------------------------
+
+/// Error event that must be alerted.
+#[derive(Debug, Clone)]
+struct Alert {
+    pattern: String,
+    target: String,
+    message: String,
+    fields: JsonValue,
+}
+
+impl Alert {
+    /// Builds the webhook request body in the configured format.
+    fn to_payload(&self, config: &AlertingConfig, service: &str) -> JsonValue {
+        match config.alert_format {
+            AlertingFormat::Slack => json!({
+                "text": format!("*{}* error matching `{}`: {}\n```{}```", service, self.pattern, self.message, to_json_string(&self.fields)),
+            }),
+            AlertingFormat::PagerDuty => json!({
+                "routing_key": config.alert_pagerduty_routing_key,
+                "event_action": "trigger",
+                "dedup_key": format!("{}:{}", service, self.pattern),
+                "payload": {
+                    "summary": self.message,
+                    "source": service,
+                    "severity": "critical",
+                    "component": self.target,
+                    "custom_details": self.fields,
+                },
+            }),
+        }
+    }
+}
+
+/// Sends alerts to the webhook until the layer is dropped.
+///
+/// Failures are logged as warnings instead of errors, so they are never alerted themselves.
+async fn send_alerts(config: AlertingConfig, mut rx: mpsc::UnboundedReceiver<Alert>) {
+    let client = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!(reason = ?e, "failed to create alerting webhook client");
+            return;
+        }
+    };
+    let service = build_info::service_name();
+
+    while let Some(alert) = rx.recv().await {
+        let payload = alert.to_payload(&config, &service);
+        let result = client
+            .post(&config.alert_webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!(reason = ?e, pattern = %alert.pattern, "failed to send alert to webhook");
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(format: AlertingFormat) -> AlertingConfig {
+        AlertingConfig {
+            alert_webhook_url: "http://localhost".to_owned(),
+            alert_format: format,
+            alert_pagerduty_routing_key: Some("key".to_owned()),
+            alert_patterns: vec!["Conflict".to_owned(), " leader ".to_owned(), "".to_owned()],
+            alert_cooldown: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_matching_pattern_and_cooldown() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let layer = AlertingLayer::with_sender(&config(AlertingFormat::Slack), tx);
+
+        assert_eq!(layer.matching_pattern("storage CONFLICT detected"), Some("conflict"));
+        assert_eq!(layer.matching_pattern("failed to reach leader"), Some("leader"));
+        assert_eq!(layer.matching_pattern("transaction failed"), None);
+
+        let now = Instant::now();
+        assert!(layer.try_start_cooldown("conflict", now));
+        assert!(!layer.try_start_cooldown("conflict", now + Duration::from_secs(59)));
+        assert!(layer.try_start_cooldown("leader", now));
+        assert!(layer.try_start_cooldown("conflict", now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_pagerduty_payload() {
+        let alert = Alert {
+            pattern: "leader".to_owned(),
+            target: "stratus::eth::follower".to_owned(),
+            message: "failed to reach leader".to_owned(),
+            fields: json!({ "attempts": 3 }),
+        };
+        let payload = alert.to_payload(&config(AlertingFormat::PagerDuty), "stratus");
+        assert_eq!(payload["routing_key"], "key");
+        assert_eq!(payload["dedup_key"], "stratus:leader");
+        assert_eq!(payload["payload"]["summary"], "failed to reach leader");
+        assert_eq!(payload["payload"]["custom_details"]["attempts"], 3);
+    }
+}
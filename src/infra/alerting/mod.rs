@@ -0,0 +1,6 @@
+mod alerting_config;
+mod alerting_layer;
+
+pub use alerting_config::AlertingConfig;
+pub use alerting_config::AlertingFormat;
+pub use alerting_layer::AlertingLayer;
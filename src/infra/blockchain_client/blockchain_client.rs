@@ -1,41 +1,73 @@
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
+use async_trait::async_trait;
 use jsonrpsee::core::client::ClientT;
 use jsonrpsee::core::client::Subscription;
 use jsonrpsee::core::client::SubscriptionClientT;
+use jsonrpsee::core::traits::ToRpcParams;
 use jsonrpsee::core::ClientError;
 use jsonrpsee::http_client::HttpClient;
 use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::types::ErrorObjectOwned;
 use jsonrpsee::ws_client::WsClient;
 use jsonrpsee::ws_client::WsClientBuilder;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::value::RawValue;
 use tokio::sync::RwLock;
-use tokio::sync::RwLockReadGuard;
 
 use crate::alias::EthersBytes;
 use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::Wei;
 use crate::eth::rpc::RpcClientApp;
+use crate::ext::not;
 use crate::ext::to_json_value;
+use crate::ext::traced_sleep;
 use crate::ext::DisplayExt;
+use crate::ext::SleepReason;
+use crate::infra::blockchain_client::middleware::Next;
+use crate::infra::blockchain_client::middleware::RpcEndpoint;
+use crate::infra::blockchain_client::middleware::RpcMiddleware;
+use crate::infra::blockchain_client::middleware::RpcRequest;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
 use crate::infra::tracing::TracingExt;
+use crate::infra::CircuitBreaker;
 use crate::log_and_err;
 use crate::GlobalState;
 
+/// Max attempts to reconnect the websocket client after the connection is lost.
+const WS_RECONNECT_MAX_ATTEMPTS: u32 = 5;
+
+/// Backoff before the second attempt to reconnect the websocket client, doubled after each failed attempt.
+const WS_RECONNECT_BACKOFF_BASE: Duration = Duration::from_millis(100);
+
+/// Max backoff between attempts to reconnect the websocket client.
+const WS_RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 pub struct BlockchainClient {
     http: HttpClient,
     pub http_url: String,
-    ws: Option<RwLock<WsClient>>,
+    ws: Option<RwLock<Arc<WsClient>>>,
     ws_url: Option<String>,
     timeout: Duration,
+    circuit_breaker: Option<CircuitBreaker>,
+    middlewares: Vec<Arc<dyn RpcMiddleware>>,
 }
 
 impl BlockchainClient {
@@ -53,7 +85,10 @@ impl BlockchainClient {
 
         // build ws provider
         let ws = if let Some(ws_url) = ws_url {
-            Some(RwLock::new(Self::build_ws_client(ws_url, timeout).await?))
+            let ws = Self::build_ws_client(ws_url, timeout).await?;
+            #[cfg(feature = "metrics")]
+            metrics::set_blockchain_client_ws_connected(1);
+            Some(RwLock::new(Arc::new(ws)))
         } else {
             None
         };
@@ -64,6 +99,8 @@ impl BlockchainClient {
             ws,
             ws_url: ws_url.map(|x| x.to_owned()),
             timeout,
+            circuit_breaker: None,
+            middlewares: Vec::new(),
         };
 
         // check health before assuming it is ok
@@ -72,6 +109,24 @@ impl BlockchainClient {
         Ok(client)
     }
 
+    /// Rejects HTTP requests while the node is failing, after `failure_threshold` consecutive failures, probing it again after `cooldown`.
+    ///
+    /// Only failures to reach the node count, errors returned by the node do not. A `failure_threshold` of zero disables the circuit breaker.
+    pub fn with_circuit_breaker(mut self, name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        if failure_threshold > 0 {
+            self.circuit_breaker = Some(CircuitBreaker::new(name, failure_threshold, cooldown));
+        }
+        self
+    }
+
+    /// Adds a middleware to the stack applied to typed requests sent with [`BlockchainClient::call`].
+    ///
+    /// Middlewares run in the order they are added, so the first added wraps all others.
+    pub fn with_middleware(mut self, middleware: impl RpcMiddleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
     fn build_http_client(url: &str, timeout: Duration) -> anyhow::Result<HttpClient> {
         tracing::info!(%url, timeout = %timeout.to_string_ext(), "creating blockchain http client");
         match HttpClientBuilder::default().request_timeout(timeout).build(url) {
@@ -109,14 +164,135 @@ impl BlockchainClient {
         self.ws.is_some()
     }
 
-    /// Validates it is connected to websocket and returns a reference to the websocket client.
-    async fn require_ws(&self) -> anyhow::Result<RwLockReadGuard<'_, WsClient>> {
+    /// Validates it is connected to websocket and returns the current websocket client.
+    async fn require_ws(&self) -> anyhow::Result<Arc<WsClient>> {
         match &self.ws {
-            Some(ws) => Ok(ws.read().await),
+            Some(ws) => Ok(Arc::clone(&*ws.read().await)),
             None => log_and_err!("blockchain client not connected to websocket"),
         }
     }
 
+    /// Reconnects the websocket client if the connection was lost, retrying with exponential backoff.
+    ///
+    /// Concurrent callers wait for the same reconnection instead of reconnecting again.
+    async fn reconnect_ws(&self) -> anyhow::Result<()> {
+        const TASK_NAME: &str = "blockchain::reconnect_ws";
+
+        let (Some(ws), Some(ws_url)) = (&self.ws, &self.ws_url) else {
+            return log_and_err!("blockchain client not connected to websocket");
+        };
+
+        let mut ws_write = ws.write().await;
+        if ws_write.is_connected() {
+            tracing::debug!("websocket client already reconnected");
+            return Ok(());
+        }
+        #[cfg(feature = "metrics")]
+        metrics::set_blockchain_client_ws_connected(0);
+
+        let mut backoff = WS_RECONNECT_BACKOFF_BASE;
+        for attempt in 1..=WS_RECONNECT_MAX_ATTEMPTS {
+            if GlobalState::is_shutdown_warn(TASK_NAME) {
+                return Err(anyhow::anyhow!("shutdown warning"));
+            }
+
+            let result = Self::build_ws_client(ws_url, self.timeout).await;
+            #[cfg(feature = "metrics")]
+            metrics::inc_blockchain_client_ws_reconnections(result.is_ok());
+
+            match result {
+                Ok(new_ws_client) => {
+                    tracing::info!(%attempt, "reconnected websocket client");
+                    *ws_write = Arc::new(new_ws_client);
+                    #[cfg(feature = "metrics")]
+                    metrics::set_blockchain_client_ws_connected(1);
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!(reason = ?e, %attempt, ?backoff, "failed to reconnect websocket client");
+                    if attempt < WS_RECONNECT_MAX_ATTEMPTS {
+                        traced_sleep(backoff, SleepReason::RetryBackoff).await;
+                        backoff = (backoff * 2).min(WS_RECONNECT_BACKOFF_MAX);
+                    }
+                }
+            }
+        }
+
+        log_and_err!("failed to reconnect websocket client after all attempts")
+    }
+
+    /// Runs an operation with the websocket client.
+    ///
+    /// If the connection is lost while the operation is in-flight, the client reconnects and runs it again, so it should only be used with
+    /// operations that are safe to repeat.
+    async fn ws_redispatch<T, F, Fut>(&self, method: &str, mut operation: F) -> anyhow::Result<Result<T, ClientError>>
+    where
+        F: FnMut(Arc<WsClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut redispatched = false;
+        loop {
+            let ws = self.require_ws().await?;
+            match operation(ws).await {
+                Err(ClientError::RestartNeeded(e)) if not(redispatched) => {
+                    tracing::warn!(reason = ?e, %method, "websocket connection lost with request in-flight. reconnecting to send it again.");
+                    self.reconnect_ws().await?;
+                    redispatched = true;
+                }
+                result => return Ok(result),
+            }
+        }
+    }
+
+    /// Sends a request through websocket, returning the result or the error returned by the node.
+    ///
+    /// If the connection is lost while the request is in-flight, the client reconnects and sends the request again, so it should only be used with
+    /// requests that are safe to repeat.
+    pub async fn ws_request<T: DeserializeOwned>(&self, method: &str, params: Option<JsonValue>) -> anyhow::Result<Result<T, ErrorObjectOwned>> {
+        tracing::debug!(%method, "sending websocket request");
+
+        let params = params.map(|params| serde_json::value::to_raw_value(&params)).transpose()?;
+        let result = self
+            .ws_redispatch(method, |ws| {
+                let params = params.clone();
+                async move { ws.request::<T, _>(method, RawParams(params)).await }
+            })
+            .await?;
+
+        match result {
+            Ok(result) => Ok(Ok(result)),
+            Err(ClientError::Call(response)) => Ok(Err(response.into_owned())),
+            Err(e) => log_and_err!(reason = e, "failed to send websocket request"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // HTTP
+    // -------------------------------------------------------------------------
+
+    /// Sends a HTTP request, short-circuiting it if the circuit breaker is open.
+    async fn request<T, P>(&self, method: &str, params: P) -> Result<T, ClientError>
+    where
+        T: DeserializeOwned,
+        P: ToRpcParams + Send,
+    {
+        let Some(ref circuit_breaker) = self.circuit_breaker else {
+            return self.http.request::<T, P>(method, params).await;
+        };
+        if let Err(e) = circuit_breaker.acquire() {
+            tracing::warn!(reason = ?e, %method, "request rejected by circuit breaker");
+            return Err(ClientError::Custom(e.to_string()));
+        }
+
+        let result = self.http.request::<T, P>(method, params).await;
+        match result {
+            // the node responded, even if with an error
+            Ok(_) | Err(ClientError::Call(_)) => circuit_breaker.record_success(),
+            Err(_) => circuit_breaker.record_failure(),
+        }
+        result
+    }
+
     // -------------------------------------------------------------------------
     // RPC queries
     // -------------------------------------------------------------------------
@@ -125,7 +301,7 @@ impl BlockchainClient {
     pub async fn fetch_listening(&self) -> anyhow::Result<()> {
         tracing::debug!("fetching listening status");
 
-        let result = self.http.request::<bool, _>("net_listening", [(); 0]).await;
+        let result = self.request::<bool, _>("net_listening", [(); 0]).await;
         match result {
             Ok(_) => Ok(()),
             Err(e) => log_and_err!(reason = e, "failed to fetch listening status"),
@@ -136,7 +312,7 @@ impl BlockchainClient {
     pub async fn fetch_block_number(&self) -> anyhow::Result<BlockNumber> {
         tracing::debug!("fetching block number");
 
-        let result = self.http.request::<BlockNumber, _>("eth_blockNumber", [(); 0]).await;
+        let result = self.request::<BlockNumber, _>("eth_blockNumber", [(); 0]).await;
 
         match result {
             Ok(number) => Ok(number),
@@ -144,28 +320,16 @@ impl BlockchainClient {
         }
     }
 
-    /// Fetches a block by number.
-    pub async fn fetch_block_and_receipts_with_temporary_endpoint(&self, block_number: BlockNumber) -> anyhow::Result<JsonValue> {
-        tracing::debug!(%block_number, "fetching block");
-
-        let number = to_json_value(block_number);
-        match self.http.request::<JsonValue, _>("stratus_getBlockAndReceipts", [number]).await {
-            Ok(json) => Ok(json),
-            Err(e) => log_and_err!(reason = e, "failed to fetch block by number"),
-        }
+    /// Fetches a block by number with its receipts and, optionally, the executions of its transactions.
+    pub async fn fetch_block_and_receipts_with_temporary_endpoint(&self, block_number: BlockNumber, with_executions: bool) -> anyhow::Result<JsonValue> {
+        tracing::debug!(%block_number, %with_executions, "fetching block");
+        self.call("stratus_getBlockAndReceipts", (block_number, with_executions)).await
     }
 
     /// Fetches a block by number.
     pub async fn fetch_block(&self, block_number: BlockNumber) -> anyhow::Result<JsonValue> {
         tracing::debug!(%block_number, "fetching block");
-
-        let number = to_json_value(block_number);
-        let result = self.http.request::<JsonValue, _>("eth_getBlockByNumber", [number, JsonValue::Bool(true)]).await;
-
-        match result {
-            Ok(block) => Ok(block),
-            Err(e) => log_and_err!(reason = e, "failed to fetch block by number"),
-        }
+        self.call("eth_getBlockByNumber", (block_number, true)).await
     }
 
     /// Fetches a transaction by hash.
@@ -174,7 +338,7 @@ impl BlockchainClient {
 
         let hash = to_json_value(tx_hash);
 
-        let result = self.http.request::<Option<EthersTransaction>, _>("eth_getTransactionByHash", [hash]).await;
+        let result = self.request::<Option<EthersTransaction>, _>("eth_getTransactionByHash", [hash]).await;
 
         match result {
             Ok(tx) => Ok(tx),
@@ -185,14 +349,13 @@ impl BlockchainClient {
     /// Fetches a receipt by hash.
     pub async fn fetch_receipt(&self, tx_hash: Hash) -> anyhow::Result<Option<ExternalReceipt>> {
         tracing::debug!(%tx_hash, "fetching transaction receipt");
+        self.call("eth_getTransactionReceipt", [tx_hash]).await
+    }
 
-        let hash = to_json_value(tx_hash);
-        let result = self.http.request::<Option<ExternalReceipt>, _>("eth_getTransactionReceipt", [hash]).await;
-
-        match result {
-            Ok(receipt) => Ok(receipt),
-            Err(e) => log_and_err!(reason = e, "failed to fetch transaction receipt by hash"),
-        }
+    /// Fetches all receipts of a block by number.
+    pub async fn fetch_block_receipts(&self, block_number: BlockNumber) -> anyhow::Result<Option<Vec<ExternalReceipt>>> {
+        tracing::debug!(%block_number, "fetching block receipts");
+        self.call("eth_getBlockReceipts", [block_number]).await
     }
 
     /// Fetches account balance by address and block number.
@@ -201,7 +364,7 @@ impl BlockchainClient {
 
         let address = to_json_value(address);
         let number = to_json_value(block_number);
-        let result = self.http.request::<Wei, _>("eth_getBalance", [address, number]).await;
+        let result = self.request::<Wei, _>("eth_getBalance", [address, number]).await;
 
         match result {
             Ok(receipt) => Ok(receipt),
@@ -209,6 +372,49 @@ impl BlockchainClient {
         }
     }
 
+    /// Fetches account nonce by address and block number.
+    pub async fn fetch_transaction_count(&self, address: Address, block_number: Option<BlockNumber>) -> anyhow::Result<Nonce> {
+        tracing::debug!(%address, block_number = %block_number.or_empty(), "fetching account nonce");
+
+        let address = to_json_value(address);
+        let number = to_json_value(block_number);
+        let result = self.request::<Nonce, _>("eth_getTransactionCount", [address, number]).await;
+
+        match result {
+            Ok(nonce) => Ok(nonce),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account nonce"),
+        }
+    }
+
+    /// Fetches account bytecode by address and block number.
+    pub async fn fetch_code(&self, address: Address, block_number: Option<BlockNumber>) -> anyhow::Result<Bytes> {
+        tracing::debug!(%address, block_number = %block_number.or_empty(), "fetching account bytecode");
+
+        let address = to_json_value(address);
+        let number = to_json_value(block_number);
+        let result = self.request::<Bytes, _>("eth_getCode", [address, number]).await;
+
+        match result {
+            Ok(bytecode) => Ok(bytecode),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account bytecode"),
+        }
+    }
+
+    /// Fetches account slot by address, slot index and block number.
+    pub async fn fetch_storage_at(&self, address: Address, index: SlotIndex, block_number: Option<BlockNumber>) -> anyhow::Result<SlotValue> {
+        tracing::debug!(%address, %index, block_number = %block_number.or_empty(), "fetching account slot");
+
+        let address = to_json_value(address);
+        let index = to_json_value(index);
+        let number = to_json_value(block_number);
+        let result = self.request::<SlotValue, _>("eth_getStorageAt", [address, index, number]).await;
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account slot"),
+        }
+    }
+
     // -------------------------------------------------------------------------
     // RPC mutations
     // -------------------------------------------------------------------------
@@ -219,7 +425,7 @@ impl BlockchainClient {
 
         let tx = to_json_value(tx);
         let rpc_client = to_json_value(rpc_client);
-        let result = self.http.request::<Hash, _>("eth_sendRawTransaction", [tx, rpc_client]).await;
+        let result = self.request::<Hash, _>("eth_sendRawTransaction", [tx, rpc_client]).await;
 
         match result {
             Ok(hash) => Ok(hash),
@@ -231,50 +437,210 @@ impl BlockchainClient {
         }
     }
 
+    // -------------------------------------------------------------------------
+    // RPC generic
+    // -------------------------------------------------------------------------
+
+    /// Sends a request with the specified method and params through the middleware stack, deserializing the result.
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: impl Serialize) -> anyhow::Result<T> {
+        let request = RpcRequest {
+            method: method.to_owned(),
+            params: Some(serde_json::value::to_raw_value(&params)?),
+        };
+
+        let result = match Next::new(&self.middlewares, self).run(&request).await {
+            Ok(result) => result,
+            Err(e) => return log_and_err!(reason = e, "failed to send typed request"),
+        };
+        match serde_json::from_value::<T>(result) {
+            Ok(result) => Ok(result),
+            Err(e) => log_and_err!(reason = e, "failed to deserialize typed request result"),
+        }
+    }
+
+    /// Sends a request with the specified method and params, returning the result or the error returned by the node.
+    pub async fn send_request(&self, method: &str, params: Option<JsonValue>) -> anyhow::Result<Result<JsonValue, ErrorObjectOwned>> {
+        tracing::debug!(%method, "sending request");
+
+        let params = RawParams(params.map(|params| serde_json::value::to_raw_value(&params)).transpose()?);
+        match self.request::<JsonValue, _>(method, params).await {
+            Ok(result) => Ok(Ok(result)),
+            Err(ClientError::Call(response)) => Ok(Err(response.into_owned())),
+            Err(e) => log_and_err!(reason = e, "failed to send request"),
+        }
+    }
+
     // -------------------------------------------------------------------------
     // RPC subscriptions
     // -------------------------------------------------------------------------
 
+    /// Subscribes to newHeads events, reconnecting once if the websocket connection was lost.
     pub async fn subscribe_new_heads(&self) -> anyhow::Result<Subscription<ExternalBlock>> {
         const TASK_NAME: &str = "blockchain::subscribe_new_heads";
         tracing::debug!("subscribing to newHeads event");
 
-        let mut first_attempt = true;
-        loop {
-            if GlobalState::is_shutdown_warn(TASK_NAME) {
-                return Err(anyhow::anyhow!("shutdown warning"));
-            };
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            return Err(anyhow::anyhow!("shutdown warning"));
+        };
 
-            let ws_read = self.require_ws().await?;
-            let result = ws_read
-                .subscribe::<ExternalBlock, _>("eth_subscribe", [JsonValue::String("newHeads".to_owned())], "eth_unsubscribe")
-                .await;
+        let result = self
+            .ws_redispatch("eth_subscribe", |ws| async move {
+                ws.subscribe::<ExternalBlock, _>("eth_subscribe", [JsonValue::String("newHeads".to_owned())], "eth_unsubscribe")
+                    .await
+            })
+            .await?;
 
-            match result {
-                // subscribed
-                Ok(sub) => return Ok(sub),
-
-                // failed and need to reconnect
-                e @ Err(ClientError::RestartNeeded(_)) => {
-                    // will try to reconnect websocket client only in first attempt
-                    if first_attempt {
-                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to newHeads event. trying to reconnect websocket client now.");
-                    } else {
-                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to newHeads event. will not try to reconnect websocket client.");
-                        return e.context("failed to subscribe to newHeads event");
-                    }
-                    first_attempt = false;
+        match result {
+            Ok(sub) => Ok(sub),
+            Err(e) => log_and_err!(reason = e, "failed to subscribe to newHeads event"),
+        }
+    }
+}
 
-                    // reconnect websocket client
-                    let new_ws_client = Self::build_ws_client(self.ws_url.as_ref().unwrap(), self.timeout).await?;
-                    drop(ws_read);
-                    let mut ws_write = self.ws.as_ref().unwrap().write().await;
-                    let _ = std::mem::replace(&mut *ws_write, new_ws_client);
-                }
+#[async_trait]
+impl RpcEndpoint for BlockchainClient {
+    async fn send(&self, request: &RpcRequest) -> Result<JsonValue, ClientError> {
+        self.request::<JsonValue, _>(&request.method, RawParams(request.params.clone())).await
+    }
+}
+
+/// Params already serialized, sent as they are.
+struct RawParams(Option<Box<RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        Ok(self.0)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use jsonrpsee::core::SubscriptionResult;
+    use jsonrpsee::server::Server;
+    use jsonrpsee::server::ServerHandle;
+    use jsonrpsee::PendingSubscriptionSink;
+    use jsonrpsee::RpcModule;
+    use tokio::runtime::Runtime;
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    /// Node running in its own runtime, so stopping it closes all its connections at once, including the ones with requests in-flight.
+    struct Node {
+        addr: SocketAddr,
+        runtime: Option<Runtime>,
+        _server: ServerHandle,
+    }
+
+    impl Node {
+        async fn start<Context: Send + Sync + 'static>(addr: SocketAddr, module: RpcModule<Context>) -> Self {
+            let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_all().build().unwrap();
+            let (addr, server) = runtime
+                .spawn(async move {
+                    // the address of a stopped node may take a while to be released
+                    let server = loop {
+                        match Server::builder().build(addr).await {
+                            Ok(server) => break server,
+                            Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                        }
+                    };
+                    (server.local_addr().unwrap(), server.start(module))
+                })
+                .await
+                .unwrap();
+
+            Self {
+                addr,
+                runtime: Some(runtime),
+                _server: server,
+            }
+        }
+
+        /// Module that answers `net_listening` and counts the `eth_blockNumber` requests it answers.
+        fn module(requests: Arc<AtomicUsize>) -> RpcModule<Arc<AtomicUsize>> {
+            let mut module = RpcModule::new(requests);
+            module.register_method("net_listening", |_, _, _| true).unwrap();
+            module
+                .register_method("eth_blockNumber", |_, requests, _| {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    "0x10"
+                })
+                .unwrap();
+            module
+                .register_subscription("eth_subscribe", "eth_subscription", "eth_unsubscribe", |_, pending, _, _| accept(pending))
+                .unwrap();
+            module
+        }
+
+        async fn client(&self) -> BlockchainClient {
+            let http_url = format!("http://{}", self.addr);
+            let ws_url = format!("ws://{}", self.addr);
+            BlockchainClient::new_http_ws(&http_url, Some(&ws_url), Duration::from_secs(2)).await.unwrap()
+        }
+    }
 
-                // failed and cannot do anything
-                Err(e) => return log_and_err!(reason = e, "failed to subscribe to newHeads event"),
+    impl Drop for Node {
+        fn drop(&mut self) {
+            if let Some(runtime) = self.runtime.take() {
+                runtime.shutdown_background();
             }
         }
     }
+
+    async fn accept(pending: PendingSubscriptionSink) -> SubscriptionResult {
+        pending.accept().await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ws_request_is_sent_again_after_reconnecting() {
+        // the first node receives the request but never answers it
+        let received = Arc::new(Notify::new());
+        let mut module = RpcModule::new(Arc::clone(&received));
+        module.register_method("net_listening", |_, _, _| true).unwrap();
+        module
+            .register_async_method("eth_blockNumber", |_, received, _| async move {
+                received.notify_one();
+                std::future::pending::<String>().await
+            })
+            .unwrap();
+        let node = Node::start("127.0.0.1:0".parse().unwrap(), module).await;
+        let addr = node.addr;
+        let client = Arc::new(node.client().await);
+
+        let request = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move { client.ws_request::<BlockNumber>("eth_blockNumber", None).await }
+        });
+
+        // the node stops with the request in-flight and a new one starts at the same address
+        received.notified().await;
+        drop(node);
+        let requests = Arc::new(AtomicUsize::new(0));
+        let _node = Node::start(addr, Node::module(Arc::clone(&requests))).await;
+
+        let number = request.await.unwrap().unwrap().unwrap();
+        assert_eq!(number, BlockNumber::from(16));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_subscribe_new_heads_after_reconnecting() {
+        let node = Node::start("127.0.0.1:0".parse().unwrap(), Node::module(Arc::default())).await;
+        let addr = node.addr;
+        let client = node.client().await;
+
+        // the connection is lost before subscribing
+        drop(node);
+        let _node = Node::start(addr, Node::module(Arc::default())).await;
+
+        assert!(client.subscribe_new_heads().await.is_ok());
+    }
 }
@@ -0,0 +1,223 @@
+//! Middlewares applied to typed requests sent with [`BlockchainClient::call`](super::BlockchainClient::call).
+//!
+//! Middlewares run in the order they were added to the client, each one wrapping the next, and the last one wraps the request sent to the node.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use jsonrpsee::core::ClientError;
+use serde_json::value::RawValue;
+
+use crate::alias::JsonValue;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Request sent through the middleware stack.
+#[derive(Debug, Clone)]
+pub struct RpcRequest {
+    pub method: String,
+    pub params: Option<Box<RawValue>>,
+}
+
+/// Sends a request to the node after all middlewares run.
+#[async_trait]
+pub trait RpcEndpoint: Send + Sync {
+    async fn send(&self, request: &RpcRequest) -> Result<JsonValue, ClientError>;
+}
+
+/// Middleware that can inspect, change, retry or short-circuit requests.
+#[async_trait]
+pub trait RpcMiddleware: Send + Sync + std::fmt::Debug {
+    async fn handle(&self, request: &RpcRequest, next: Next<'_>) -> Result<JsonValue, ClientError>;
+}
+
+/// Remaining middlewares of the stack, followed by the endpoint.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn RpcMiddleware>],
+    endpoint: &'a dyn RpcEndpoint,
+}
+
+impl<'a> Next<'a> {
+    pub fn new(middlewares: &'a [Arc<dyn RpcMiddleware>], endpoint: &'a dyn RpcEndpoint) -> Self {
+        Self { middlewares, endpoint }
+    }
+
+    /// Runs the next middleware, or sends the request to the endpoint if there are no middlewares left.
+    pub async fn run(self, request: &RpcRequest) -> Result<JsonValue, ClientError> {
+        match self.middlewares.split_first() {
+            Some((middleware, middlewares)) => middleware.handle(request, Next::new(middlewares, self.endpoint)).await,
+            None => self.endpoint.send(request).await,
+        }
+    }
+}
+
+/// Checks if the node could not be reached, so sending the request again may succeed. Errors returned by the node are not retryable.
+fn is_retryable(e: &ClientError) -> bool {
+    matches!(e, ClientError::Transport(_) | ClientError::RequestTimeout | ClientError::RestartNeeded(_))
+}
+
+// -----------------------------------------------------------------------------
+// Middlewares
+// -----------------------------------------------------------------------------
+
+/// Retries requests that failed because the node could not be reached, doubling the backoff after each attempt.
+#[derive(Debug, Clone)]
+pub struct RetryMiddleware {
+    pub max_retries: u32,
+    pub backoff: Duration,
+}
+
+#[async_trait]
+impl RpcMiddleware for RetryMiddleware {
+    async fn handle(&self, request: &RpcRequest, next: Next<'_>) -> Result<JsonValue, ClientError> {
+        let mut backoff = self.backoff;
+        let mut retry = 0;
+        loop {
+            match next.run(request).await {
+                Err(e) if is_retryable(&e) && retry < self.max_retries => {
+                    retry += 1;
+                    tracing::warn!(reason = ?e, method = %request.method, %retry, "request failed. retrying.");
+                    traced_sleep(backoff, SleepReason::RetryBackoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Fails requests that take longer than the timeout, including all middlewares after it.
+#[derive(Debug, Clone)]
+pub struct TimeoutMiddleware {
+    pub timeout: Duration,
+}
+
+#[async_trait]
+impl RpcMiddleware for TimeoutMiddleware {
+    async fn handle(&self, request: &RpcRequest, next: Next<'_>) -> Result<JsonValue, ClientError> {
+        match tokio::time::timeout(self.timeout, next.run(request)).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::warn!(method = %request.method, timeout = ?self.timeout, "request timed out");
+                Err(ClientError::RequestTimeout)
+            }
+        }
+    }
+}
+
+/// Logs requests and their results.
+#[derive(Debug, Clone)]
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl RpcMiddleware for LoggingMiddleware {
+    async fn handle(&self, request: &RpcRequest, next: Next<'_>) -> Result<JsonValue, ClientError> {
+        tracing::debug!(method = %request.method, params = ?request.params, "sending request");
+        let result = next.run(request).await;
+        match result {
+            Ok(_) => tracing::debug!(method = %request.method, "request succeeded"),
+            Err(ref e) => tracing::warn!(reason = ?e, method = %request.method, "request failed"),
+        }
+        result
+    }
+}
+
+/// Tracks the duration and result of requests.
+#[derive(Debug, Clone)]
+pub struct MetricsMiddleware;
+
+#[async_trait]
+impl RpcMiddleware for MetricsMiddleware {
+    async fn handle(&self, request: &RpcRequest, next: Next<'_>) -> Result<JsonValue, ClientError> {
+        #[cfg(feature = "metrics")]
+        let start = metrics::now();
+
+        let result = next.run(request).await;
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_blockchain_client_request(start.elapsed(), request.method.clone(), result.is_ok());
+
+        result
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    /// Endpoint that fails the first requests and then succeeds after a delay.
+    struct FakeEndpoint {
+        failures: u32,
+        delay: Duration,
+        requests: AtomicU32,
+    }
+
+    #[async_trait]
+    impl RpcEndpoint for FakeEndpoint {
+        async fn send(&self, _: &RpcRequest) -> Result<JsonValue, ClientError> {
+            let request = self.requests.fetch_add(1, Ordering::SeqCst);
+            if request < self.failures {
+                return Err(ClientError::Transport(anyhow::anyhow!("connection refused").into()));
+            }
+            tokio::time::sleep(self.delay).await;
+            Ok(JsonValue::from(request))
+        }
+    }
+
+    fn endpoint(failures: u32, delay: Duration) -> FakeEndpoint {
+        FakeEndpoint {
+            failures,
+            delay,
+            requests: AtomicU32::new(0),
+        }
+    }
+
+    fn request() -> RpcRequest {
+        RpcRequest {
+            method: "eth_blockNumber".to_owned(),
+            params: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_middleware() {
+        let retry: Arc<dyn RpcMiddleware> = Arc::new(RetryMiddleware {
+            max_retries: 2,
+            backoff: Duration::ZERO,
+        });
+        let stack = [retry];
+
+        let succeeds_after_retries = endpoint(2, Duration::ZERO);
+        let result = Next::new(&stack, &succeeds_after_retries).run(&request()).await;
+        assert_eq!(result.unwrap(), JsonValue::from(2));
+
+        let fails_after_retries = endpoint(3, Duration::ZERO);
+        let result = Next::new(&stack, &fails_after_retries).run(&request()).await;
+        assert!(matches!(result, Err(ClientError::Transport(_))));
+        assert_eq!(fails_after_retries.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_middleware() {
+        let timeout: Arc<dyn RpcMiddleware> = Arc::new(TimeoutMiddleware {
+            timeout: Duration::from_millis(10),
+        });
+        let stack = [timeout];
+
+        let result = Next::new(&stack, &endpoint(0, Duration::from_secs(1))).run(&request()).await;
+        assert!(matches!(result, Err(ClientError::RequestTimeout)));
+
+        let result = Next::new(&stack, &endpoint(0, Duration::ZERO)).run(&request()).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,4 +1,14 @@
 #[allow(clippy::module_inception)]
 pub mod blockchain_client;
+pub mod middleware;
+#[cfg(feature = "client")]
+pub mod stratus_client;
 
 pub use blockchain_client::BlockchainClient;
+pub use middleware::LoggingMiddleware;
+pub use middleware::MetricsMiddleware;
+pub use middleware::RetryMiddleware;
+pub use middleware::RpcMiddleware;
+pub use middleware::TimeoutMiddleware;
+#[cfg(feature = "client")]
+pub use stratus_client::StratusClient;
@@ -0,0 +1,365 @@
+//! Typed client of the `stratus_*` RPC namespace.
+//!
+//! Wraps a [`BlockchainClient`] and converts the JSON returned by Stratus-specific endpoints into primitives, so services that talk to a
+//! Stratus node do not need to hand-write JSON-RPC calls and parse their responses.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BalanceChange;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ContractCreation;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::InternalTransfer;
+use crate::eth::primitives::InternalTransferFilter;
+use crate::eth::primitives::InternalTransferKind;
+use crate::eth::primitives::InternalTransferMined;
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::TokenAmount;
+use crate::eth::primitives::TokenStandard;
+use crate::eth::primitives::TokenTransfer;
+use crate::eth::primitives::TokenTransferFilter;
+use crate::eth::primitives::TokenTransferMined;
+use crate::eth::primitives::Wei;
+use crate::infra::BlockchainClient;
+
+/// Typed client of the `stratus_*` RPC namespace.
+#[derive(Debug, Clone)]
+pub struct StratusClient {
+    chain: Arc<BlockchainClient>,
+}
+
+impl StratusClient {
+    /// Creates a new client that sends requests through the specified blockchain client, including its middlewares.
+    pub fn new(chain: Arc<BlockchainClient>) -> Self {
+        Self { chain }
+    }
+
+    /// Blockchain client used to send requests, for endpoints outside the `stratus_*` namespace.
+    pub fn chain(&self) -> &BlockchainClient {
+        &self.chain
+    }
+
+    // -------------------------------------------------------------------------
+    // Status
+    // -------------------------------------------------------------------------
+
+    /// Checks if the node is ready to serve requests. Fails with the error returned by the node if not.
+    pub async fn health(&self) -> anyhow::Result<bool> {
+        self.chain.call("stratus_health", [(); 0]).await
+    }
+
+    /// Fetches the build information of the node.
+    pub async fn version(&self) -> anyhow::Result<JsonValue> {
+        self.chain.call("stratus_version", [(); 0]).await
+    }
+
+    /// Fetches the global state of the node, like its mode and which features are enabled.
+    pub async fn state(&self) -> anyhow::Result<JsonValue> {
+        self.chain.call("stratus_state", [(); 0]).await
+    }
+
+    /// Fetches the number of transactions waiting to be mined.
+    pub async fn pending_transactions_count(&self) -> anyhow::Result<usize> {
+        self.chain.call("stratus_pendingTransactionsCount", [(); 0]).await
+    }
+
+    // -------------------------------------------------------------------------
+    // Admin
+    // -------------------------------------------------------------------------
+
+    /// Enables receiving transactions, returning if they are enabled.
+    pub async fn enable_transactions(&self) -> anyhow::Result<bool> {
+        self.chain.call("stratus_enableTransactions", [(); 0]).await
+    }
+
+    /// Disables receiving transactions, returning if they are enabled.
+    pub async fn disable_transactions(&self) -> anyhow::Result<bool> {
+        self.chain.call("stratus_disableTransactions", [(); 0]).await
+    }
+
+    /// Resumes mining, returning if the miner is enabled.
+    pub async fn enable_miner(&self) -> anyhow::Result<bool> {
+        self.chain.call("stratus_enableMiner", [(); 0]).await
+    }
+
+    /// Pauses mining, returning if the miner is enabled.
+    pub async fn disable_miner(&self) -> anyhow::Result<bool> {
+        self.chain.call("stratus_disableMiner", [(); 0]).await
+    }
+
+    // -------------------------------------------------------------------------
+    // Queries
+    // -------------------------------------------------------------------------
+
+    /// Fetches the account changes of a block. Returns `None` if the block does not exist or its changes were not recorded.
+    pub async fn get_state_diff(&self, filter: BlockFilter) -> anyhow::Result<Option<StateDiff>> {
+        self.chain.call("stratus_getStateDiff", (filter,)).await
+    }
+
+    /// Fetches the transaction that created a contract.
+    pub async fn get_contract_creation(&self, address: Address) -> anyhow::Result<Option<ContractCreation>> {
+        let creation: Option<ContractCreationJson> = self.chain.call("stratus_getContractCreation", (address,)).await?;
+        Ok(creation.map(|creation| ContractCreation {
+            address: creation.contract_address,
+            creator: creation.creator,
+            transaction_hash: creation.transaction_hash,
+            transaction_index: creation.transaction_index,
+            block_number: creation.block_number,
+        }))
+    }
+
+    /// Fetches a page of current slots of a contract, starting from the specified slot index.
+    pub async fn get_storage_range(&self, address: Address, start: Option<SlotIndex>, limit: Option<usize>) -> anyhow::Result<Page<Slot, SlotIndex>> {
+        let page: StorageRangeJson = self.chain.call("stratus_getStorageRange", (address, start, limit)).await?;
+        Ok(Page {
+            items: page.slots,
+            next: page.next_key,
+        })
+    }
+
+    /// Fetches a page of balance changes of an account, starting from the specified block.
+    pub async fn get_balance_history(
+        &self,
+        address: Address,
+        from_block: Option<BlockNumber>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Page<BalanceChange, BlockNumber>> {
+        let page: BalanceHistoryJson = self.chain.call("stratus_getBalanceHistory", (address, from_block, limit)).await?;
+        let changes = page
+            .changes
+            .into_iter()
+            .map(|change| BalanceChange {
+                address,
+                block_number: change.block_number,
+                transaction_hash: change.transaction_hash,
+                transaction_index: change.transaction_index,
+                previous_balance: change.previous_balance,
+                balance: change.balance,
+            })
+            .collect();
+        Ok(Page {
+            items: changes,
+            next: page.next_block,
+        })
+    }
+
+    /// Fetches internal transfers of a block, or a page of internal transfers of an account starting from the specified block.
+    pub async fn get_internal_transactions(
+        &self,
+        filter: InternalTransferFilter,
+        from_block: Option<BlockNumber>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Page<InternalTransferMined, BlockNumber>> {
+        let page: TransfersJson<InternalTransferJson> = match filter {
+            InternalTransferFilter::Block(_) => self.chain.call("stratus_getInternalTransactions", (filter,)).await?,
+            InternalTransferFilter::Address(_) => self.chain.call("stratus_getInternalTransactions", (filter, from_block, limit)).await?,
+        };
+        let transfers = page
+            .transfers
+            .into_iter()
+            .map(|transfer| InternalTransferMined {
+                transfer: InternalTransfer {
+                    kind: transfer.kind,
+                    from: transfer.from,
+                    to: transfer.to,
+                    value: transfer.value,
+                    depth: transfer.depth,
+                },
+                transaction_hash: transfer.transaction_hash,
+                transaction_index: transfer.transaction_index,
+                block_number: transfer.block_number,
+            })
+            .collect();
+        Ok(Page {
+            items: transfers,
+            next: page.next_block,
+        })
+    }
+
+    /// Fetches a page of token transfers of an account or token, starting from the specified block.
+    pub async fn get_token_transfers(
+        &self,
+        filter: TokenTransferFilter,
+        from_block: Option<BlockNumber>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Page<TokenTransferMined, BlockNumber>> {
+        let page: TransfersJson<TokenTransferJson> = self.chain.call("stratus_getTokenTransfers", (filter, from_block, limit)).await?;
+        let transfers = page
+            .transfers
+            .into_iter()
+            .map(|transfer| TokenTransferMined {
+                transfer: TokenTransfer {
+                    standard: transfer.standard,
+                    token: transfer.token,
+                    from: transfer.from,
+                    to: transfer.to,
+                    token_id: transfer.token_id,
+                    value: transfer.value,
+                },
+                transaction_hash: transfer.transaction_hash,
+                transaction_index: transfer.transaction_index,
+                log_index: transfer.log_index,
+                block_number: transfer.block_number,
+            })
+            .collect();
+        Ok(Page {
+            items: transfers,
+            next: page.next_block,
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Responses
+// -----------------------------------------------------------------------------
+
+/// Page of a paginated endpoint, with where the next page starts if there are more items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T, N> {
+    pub items: Vec<T>,
+    pub next: Option<N>,
+}
+
+/// Account changes of a block returned by `stratus_getStateDiff`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDiff {
+    pub block_number: BlockNumber,
+    pub state_diff: BTreeMap<Address, AccountDiff>,
+}
+
+/// Modified values of an account. Values that were not modified are not present.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AccountDiff {
+    #[serde(default)]
+    pub nonce: Option<ValueDiff<Nonce>>,
+
+    #[serde(default)]
+    pub balance: Option<ValueDiff<Wei>>,
+
+    #[serde(default)]
+    pub code: Option<ValueDiff<Option<Bytes>>>,
+
+    #[serde(default)]
+    pub storage: BTreeMap<SlotIndex, ValueDiff<SlotValue>>,
+}
+
+/// Value before and after a block. The value before is not present if it was not read.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ValueDiff<T> {
+    pub from: Option<T>,
+    pub to: Option<T>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContractCreationJson {
+    contract_address: Address,
+    creator: Address,
+    transaction_hash: Hash,
+    transaction_index: Index,
+    block_number: BlockNumber,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageRangeJson {
+    slots: Vec<Slot>,
+    next_key: Option<SlotIndex>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceHistoryJson {
+    changes: Vec<BalanceChangeJson>,
+    next_block: Option<BlockNumber>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceChangeJson {
+    block_number: BlockNumber,
+    transaction_hash: Hash,
+    transaction_index: Index,
+    previous_balance: Wei,
+    balance: Wei,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransfersJson<T> {
+    transfers: Vec<T>,
+    next_block: Option<BlockNumber>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InternalTransferJson {
+    block_number: BlockNumber,
+    transaction_hash: Hash,
+    transaction_index: Index,
+    #[serde(rename = "type")]
+    kind: InternalTransferKind,
+    from: Address,
+    to: Address,
+    value: Wei,
+    depth: usize,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenTransferJson {
+    block_number: BlockNumber,
+    transaction_hash: Hash,
+    transaction_index: Index,
+    log_index: Index,
+    standard: TokenStandard,
+    token: Address,
+    from: Address,
+    to: Address,
+    token_id: Option<TokenAmount>,
+    value: TokenAmount,
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_state_diff() {
+        let address = Address::new([1; 20]);
+        let index = SlotIndex::from(1u64);
+        let json = json!({
+            "blockNumber": BlockNumber::from(5u64),
+            "stateDiff": {
+                address.to_string(): {
+                    "balance": {"from": Wei::from(10u64), "to": Wei::from(5u64)},
+                    "storage": {index.to_string(): {"from": null, "to": SlotValue::from(2u64)}},
+                },
+            },
+        });
+
+        let diff: StateDiff = serde_json::from_value(json).unwrap();
+        assert_eq!(diff.block_number, BlockNumber::from(5u64));
+
+        let account = &diff.state_diff[&address];
+        assert_eq!(account.nonce, None);
+        assert_eq!(account.balance.as_ref().unwrap().to, Some(Wei::from(5u64)));
+        assert_eq!(account.storage[&index].from, None);
+        assert_eq!(account.storage[&index].to, Some(SlotValue::from(2u64)));
+    }
+}
@@ -0,0 +1,169 @@
+//! Circuit breaker that stops calling a dependency after consecutive failures.
+//!
+//! While the circuit is closed, calls are allowed and consecutive failures are counted. When they reach the threshold, the circuit opens and calls are
+//! rejected immediately, so callers do not wait for timeouts of a dependency that is down. After the cooldown, a single call is allowed to probe the
+//! dependency: if it succeeds the circuit closes, otherwise it opens again.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::ext::not;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Error returned when the circuit is open and calls to the dependency are rejected.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("circuit breaker of {name} is open")]
+pub struct CircuitBreakerOpen {
+    pub name: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircuitState {
+    /// Calls are allowed.
+    Closed { failures: u32 },
+
+    /// Calls are rejected until the cooldown ends.
+    Open { until: Instant },
+
+    /// A single call is probing the dependency. Other calls are rejected until it finishes or the cooldown ends again, in case its result is never
+    /// recorded.
+    HalfOpen { until: Instant },
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Dependency name used in logs, errors and metrics.
+    name: &'static str,
+
+    /// Consecutive failures that open the circuit.
+    failure_threshold: u32,
+
+    /// How long the circuit stays open before probing the dependency.
+    cooldown: Duration,
+
+    state: Mutex<CircuitState>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker.
+    pub fn new(name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(CircuitState::Closed { failures: 0 }),
+        }
+    }
+
+    /// Checks if a call to the dependency is allowed.
+    ///
+    /// When allowed, the caller must record the call result with [`CircuitBreaker::record_success`] or [`CircuitBreaker::record_failure`].
+    pub fn acquire(&self) -> Result<(), CircuitBreakerOpen> {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        match *state {
+            CircuitState::Closed { .. } => Ok(()),
+            CircuitState::Open { until } | CircuitState::HalfOpen { until } if now >= until => {
+                tracing::info!(name = %self.name, "circuit breaker probing dependency");
+                *state = CircuitState::HalfOpen { until: now + self.cooldown };
+                Ok(())
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => {
+                #[cfg(feature = "metrics")]
+                metrics::inc_circuit_breaker_rejected(self.name);
+                Err(CircuitBreakerOpen { name: self.name })
+            }
+        }
+    }
+
+    /// Records a successful call, closing the circuit if it was probing the dependency.
+    pub fn record_success(&self) {
+        let mut state = self.state.lock();
+        if matches!(*state, CircuitState::HalfOpen { .. } | CircuitState::Open { .. }) {
+            tracing::info!(name = %self.name, "circuit breaker closed");
+            #[cfg(feature = "metrics")]
+            metrics::set_circuit_breaker_open(0, self.name);
+        }
+        *state = CircuitState::Closed { failures: 0 };
+    }
+
+    /// Records a failed call, opening the circuit if the threshold is reached or if it was probing the dependency.
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock();
+        let open = match *state {
+            CircuitState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                *state = CircuitState::Closed { failures: failures + 1 };
+                false
+            }
+            CircuitState::Closed { .. } | CircuitState::HalfOpen { .. } => true,
+            CircuitState::Open { .. } => false,
+        };
+        if open {
+            tracing::warn!(name = %self.name, cooldown = ?self.cooldown, "circuit breaker opened");
+            *state = CircuitState::Open {
+                until: Instant::now() + self.cooldown,
+            };
+            #[cfg(feature = "metrics")]
+            metrics::set_circuit_breaker_open(1, self.name);
+        }
+    }
+
+    /// Checks if calls are currently being rejected.
+    pub fn is_open(&self) -> bool {
+        not(matches!(*self.state.lock(), CircuitState::Closed { .. }))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new("test", 3, Duration::from_secs(60));
+
+        // a success resets the consecutive failures
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.acquire().is_ok());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(breaker.acquire().is_err());
+    }
+
+    #[test]
+    fn test_probes_after_cooldown() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::ZERO);
+
+        // failed probe opens again
+        breaker.record_failure();
+        assert!(breaker.acquire().is_ok());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        // successful probe closes
+        assert!(breaker.acquire().is_ok());
+        breaker.record_success();
+        assert!(not(breaker.is_open()));
+    }
+
+    #[test]
+    fn test_single_probe_during_cooldown() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::from_secs(60));
+        breaker.record_failure();
+        *breaker.state.lock() = CircuitState::Open { until: Instant::now() };
+
+        assert!(breaker.acquire().is_ok());
+        assert!(breaker.acquire().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Clock used by background loops to wait between iterations and retries.
+//!
+//! Loops wait through a [`TaskClock`] instead of sleeping directly, so tests can replace the Tokio timer with a [`VirtualClock`] and drive
+//! intervals and backoffs deterministically, without waiting for real time to pass.
+
+use std::fmt::Debug;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+
+/// Source of waits for background loops.
+#[async_trait]
+pub trait TaskClock: Send + Sync + Debug {
+    /// Sleeps the current task and tracks why it is sleeping.
+    async fn sleep(&self, duration: Duration, reason: SleepReason);
+}
+
+/// Clock backed by the Tokio timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+#[async_trait]
+impl TaskClock for TokioClock {
+    async fn sleep(&self, duration: Duration, reason: SleepReason) {
+        traced_sleep(duration, reason).await;
+    }
+}
+
+/// Clock whose time only advances when [`VirtualClock::advance`] is called.
+#[derive(Debug)]
+pub struct VirtualClock {
+    /// Time elapsed since the clock was created.
+    elapsed: watch::Sender<Duration>,
+
+    /// Number of tasks sleeping right now.
+    sleeping: AtomicUsize,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self {
+            elapsed: watch::channel(Duration::ZERO).0,
+            sleeping: AtomicUsize::new(0),
+        }
+    }
+
+    /// Time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+
+    /// Advances the time, waking up all tasks whose sleep finished.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+
+    /// Number of tasks sleeping right now.
+    pub fn sleeping(&self) -> usize {
+        self.sleeping.load(Ordering::SeqCst)
+    }
+
+    /// Yields until at least `count` tasks are sleeping, so the time can be advanced after they reach their sleep.
+    pub async fn wait_sleeping(&self, count: usize) {
+        while self.sleeping() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+#[async_trait]
+impl TaskClock for VirtualClock {
+    async fn sleep(&self, duration: Duration, reason: SleepReason) {
+        tracing::debug!(duration_ms = %duration.as_millis(), %reason, "sleeping in virtual clock");
+
+        let wake_at = self.elapsed() + duration;
+        let mut elapsed = self.elapsed.subscribe();
+
+        self.sleeping.fetch_add(1, Ordering::SeqCst);
+        // the sender is owned by the clock, so it is never closed while the clock is borrowed here
+        let _ = elapsed.wait_for(|elapsed| *elapsed >= wake_at).await;
+        self.sleeping.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_virtual_clock_wakes_sleepers_only_when_advanced() {
+        let clock = Arc::new(VirtualClock::new());
+        let sleeper = tokio::spawn({
+            let clock = Arc::clone(&clock);
+            async move { clock.sleep(Duration::from_secs(60), SleepReason::Interval).await }
+        });
+
+        clock.wait_sleeping(1).await;
+        clock.advance(Duration::from_secs(59));
+        tokio::task::yield_now().await;
+        assert_eq!(clock.sleeping(), 1);
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(1));
+        sleeper.await.unwrap();
+        assert_eq!(clock.sleeping(), 0);
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,376 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::alias::EthersTransaction;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::GasMode;
+use crate::eth::storage::StorageReader;
+use crate::ext::to_json_string;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::infra::supervisor::spawn_supervised;
+use crate::infra::task_registry;
+use crate::infra::tracing::warn_task_rx_closed;
+use crate::log_and_err;
+use crate::GlobalState;
+
+/// Interval the publisher checks if it should shutdown while waiting for new blocks.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Message broker where chain data is published.
+#[async_trait]
+pub trait EventBusPublisher: Send + Sync + 'static {
+    /// Publishes a single message to the specified topic.
+    async fn publish(&self, topic: &str, key: &str, payload: &str) -> anyhow::Result<()>;
+}
+
+/// Topics where each kind of chain data is published.
+#[derive(Debug, Clone)]
+pub struct EventBusTopics {
+    pub blocks: String,
+    pub transactions: String,
+    pub receipts: String,
+    pub logs: String,
+}
+
+impl EventBusTopics {
+    /// Creates topics using the same prefix for all of them.
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self {
+            blocks: format!("{}.blocks", prefix),
+            transactions: format!("{}.transactions", prefix),
+            receipts: format!("{}.receipts", prefix),
+            logs: format!("{}.logs", prefix),
+        }
+    }
+}
+
+/// Publishes committed blocks to a message broker.
+///
+/// Blocks are published in order and without gaps. Blocks that were not received from the channel, because the publisher failed or lagged
+/// behind, are read from the storage and published before the next one.
+pub struct EventBus {
+    publisher: Box<dyn EventBusPublisher>,
+    storage: Arc<dyn StorageReader>,
+    topics: EventBusTopics,
+
+    /// How transactions pay for gas, used to compute the effective gas price of receipts.
+    gas_mode: GasMode,
+
+    /// Last block whose data was entirely published. Not set until the first block is published.
+    last_published: Mutex<Option<BlockNumber>>,
+}
+
+impl EventBus {
+    pub fn new(publisher: Box<dyn EventBusPublisher>, storage: Arc<dyn StorageReader>, topics: EventBusTopics, gas_mode: GasMode) -> Self {
+        Self {
+            publisher,
+            storage,
+            topics,
+            gas_mode,
+            last_published: Mutex::new(None),
+        }
+    }
+
+    /// Spawns a supervised task that publishes every committed block notified by the channel.
+    ///
+    /// If the task fails, it is restarted with a new subscription and first publishes the blocks committed after the last published one.
+    /// The application is shut down if it keeps failing.
+    pub fn spawn(self, notifier_blocks: broadcast::Sender<Block>) -> JoinHandle<()> {
+        const TASK_NAME: &str = "event-bus::publisher";
+        let event_bus = Arc::new(self);
+        spawn_supervised(TASK_NAME, move || {
+            let event_bus = Arc::clone(&event_bus);
+            let rx_blocks = notifier_blocks.subscribe();
+            async move { event_bus.run(TASK_NAME, rx_blocks).await }
+        })
+    }
+
+    /// Publishes every committed block received in the channel until the application is shut down.
+    async fn run(&self, task_name: &str, mut rx_blocks: broadcast::Receiver<Block>) -> anyhow::Result<()> {
+        // blocks committed before a restart may not be notified again
+        self.publish_missing_blocks(self.storage.read_mined_block_number()?).await?;
+
+        loop {
+            task_registry::heartbeat();
+
+            if GlobalState::is_shutdown_warn(task_name) {
+                return Ok(());
+            }
+
+            let block = match timeout(SHUTDOWN_CHECK_INTERVAL, rx_blocks.recv()).await {
+                Ok(Ok(block)) => block,
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    tracing::warn!(%skipped, "event bus publisher is lagging behind, skipped blocks will be read from storage");
+                    continue;
+                }
+                Ok(Err(RecvError::Closed)) => break,
+                Err(_timed_out) => continue,
+            };
+
+            // failing restarts the task, that publishes the block again
+            self.publish_next_block(block).await?;
+        }
+        warn_task_rx_closed(task_name);
+        Ok(())
+    }
+
+    /// Publishes a committed block after the blocks between it and the last published one, unless it was already published.
+    async fn publish_next_block(&self, block: Block) -> anyhow::Result<()> {
+        let block_number = block.number();
+        if self.last_published.lock().is_some_and(|last| block_number <= last) {
+            tracing::info!(%block_number, "block already published to event bus");
+            return Ok(());
+        }
+
+        if let Some(previous) = block_number.prev() {
+            self.publish_missing_blocks(previous).await?;
+        }
+        self.publish_and_track(block).await
+    }
+
+    /// Publishes the blocks after the last published one up to the specified block, reading them from storage.
+    ///
+    /// Does nothing if no block was published yet, as there is no gap to fill.
+    async fn publish_missing_blocks(&self, until: BlockNumber) -> anyhow::Result<()> {
+        let Some(last) = *self.last_published.lock() else { return Ok(()) };
+
+        let mut block_number = last.next_block_number();
+        while block_number <= until {
+            let Some(block) = self.storage.read_block(BlockFilter::Number(block_number))? else {
+                return log_and_err!(payload = block_number, "block not published to event bus was not found in storage");
+            };
+            self.publish_and_track(block).await?;
+            block_number = block_number.next_block_number();
+        }
+        Ok(())
+    }
+
+    /// Publishes a block and tracks it as the last published one.
+    async fn publish_and_track(&self, block: Block) -> anyhow::Result<()> {
+        let block_number = block.number();
+
+        #[cfg(feature = "metrics")]
+        if let Some(last) = *self.last_published.lock() {
+            metrics::set_event_bus_blocks_behind(block_number.as_u64().saturating_sub(last.as_u64()));
+        }
+
+        if let Err(e) = self.publish_block(block).await {
+            tracing::error!(reason = ?e, %block_number, "failed to publish block to event bus");
+            return Err(e);
+        }
+        *self.last_published.lock() = Some(block_number);
+
+        #[cfg(feature = "metrics")]
+        metrics::set_event_bus_blocks_behind(0);
+
+        Ok(())
+    }
+
+    /// Publishes transactions, receipts and logs of a block, then the block itself.
+    ///
+    /// The block is published last, so consumers reacting to it can expect all its data to be already published.
+    pub async fn publish_block(&self, block: Block) -> anyhow::Result<()> {
+        tracing::info!(block_number = %block.number(), transactions_len = %block.transactions.len(), "publishing block to event bus");
+
+        for (tx, ethers_receipt) in block.transactions.iter().zip(block.to_ethers_receipts(self.gas_mode)) {
+            let tx_key = tx.input.hash.to_string();
+
+            let ethers_tx: EthersTransaction = tx.clone().into();
+            self.publish(&self.topics.transactions, &tx_key, &to_json_string(&ethers_tx)).await?;
+
+            self.publish(&self.topics.receipts, &tx_key, &to_json_string(&ethers_receipt)).await?;
+
+            for log in &tx.logs {
+                self.publish(&self.topics.logs, &tx_key, &to_json_string(&log.clone().to_json_rpc_log()))
+                    .await?;
+            }
+        }
+
+        let block_key = block.number().to_string();
+        self.publish(&self.topics.blocks, &block_key, &to_json_string(&block.to_json_rpc_with_transactions_hashes()))
+            .await
+    }
+
+    async fn publish(&self, topic: &str, key: &str, payload: &str) -> anyhow::Result<()> {
+        #[cfg(feature = "metrics")]
+        let start = metrics::now();
+
+        let result = self.publisher.publish(topic, key, payload).await;
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_event_bus_publish(start.elapsed(), topic, result.is_ok());
+
+        result
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::LogMined;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::WriteStorage;
+
+    /// Records published messages and fails after a number of them or while unavailable.
+    #[derive(Default)]
+    struct RecordingPublisher {
+        messages: Arc<Mutex<Vec<(String, String)>>>,
+        fail_after: Option<usize>,
+        unavailable: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl EventBusPublisher for RecordingPublisher {
+        async fn publish(&self, topic: &str, key: &str, _payload: &str) -> anyhow::Result<()> {
+            let mut messages = self.messages.lock();
+            if self.fail_after.is_some_and(|fail_after| messages.len() >= fail_after) || self.unavailable.load(Ordering::Relaxed) {
+                anyhow::bail!("broker unavailable");
+            }
+            messages.push((topic.to_owned(), key.to_owned()));
+            Ok(())
+        }
+    }
+
+    fn block_with_logs(logs_per_tx: &[usize]) -> Block {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1));
+        for (index, logs_len) in logs_per_tx.iter().enumerate() {
+            let mut tx: TransactionMined = Faker.fake();
+            tx.block_number = block.number();
+            tx.block_hash = block.hash();
+            tx.transaction_index = (index as u64).into();
+            tx.logs = (0..*logs_len).map(|_| Faker.fake::<LogMined>()).collect();
+            block.transactions.push(tx);
+        }
+        block
+    }
+
+    /// Creates a storage with empty blocks mined up to the specified number.
+    fn storage_with_blocks(mined: u64) -> Arc<dyn StorageReader> {
+        let perm = InMemoryPermanentStorage::default();
+        for number in 0..=mined {
+            perm.save_block(Block::new(BlockNumber::from(number), UnixTime::from(number))).unwrap();
+        }
+        perm.set_mined_block_number(BlockNumber::from(mined)).unwrap();
+
+        let temp = InMemoryTemporaryStorage::new(BlockNumber::from(mined + 1));
+        Arc::new(StratusStorage::new(Box::new(temp), Box::new(perm), None).unwrap())
+    }
+
+    fn published_blocks(messages: &Mutex<Vec<(String, String)>>) -> Vec<String> {
+        messages
+            .lock()
+            .iter()
+            .filter(|(topic, _)| topic == "test.blocks")
+            .map(|(_, key)| key.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_publish_block_publishes_block_last() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let publisher = RecordingPublisher {
+            messages: Arc::clone(&messages),
+            ..Default::default()
+        };
+        let event_bus = EventBus::new(Box::new(publisher), storage_with_blocks(0), EventBusTopics::with_prefix("test"), GasMode::Free);
+
+        let block = block_with_logs(&[2, 0]);
+        let tx_keys = block.transactions.iter().map(|tx| tx.input.hash.to_string()).collect::<Vec<_>>();
+        event_bus.publish_block(block.clone()).await.unwrap();
+
+        let expected = vec![
+            ("test.transactions".to_owned(), tx_keys[0].clone()),
+            ("test.receipts".to_owned(), tx_keys[0].clone()),
+            ("test.logs".to_owned(), tx_keys[0].clone()),
+            ("test.logs".to_owned(), tx_keys[0].clone()),
+            ("test.transactions".to_owned(), tx_keys[1].clone()),
+            ("test.receipts".to_owned(), tx_keys[1].clone()),
+            ("test.blocks".to_owned(), block.number().to_string()),
+        ];
+        assert_eq!(*messages.lock(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_publish_block_does_not_publish_block_after_failure() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let publisher = RecordingPublisher {
+            messages: Arc::clone(&messages),
+            fail_after: Some(1),
+            ..Default::default()
+        };
+        let event_bus = EventBus::new(Box::new(publisher), storage_with_blocks(0), EventBusTopics::with_prefix("test"), GasMode::Free);
+
+        assert!(event_bus.publish_block(block_with_logs(&[1])).await.is_err());
+        let messages = messages.lock();
+        assert_eq!(messages.len(), 1);
+        assert!(messages.iter().all(|(topic, _)| topic != "test.blocks"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_next_block_republishes_blocks_missed_after_failure() {
+        let storage = storage_with_blocks(3);
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let unavailable = Arc::new(AtomicBool::new(false));
+        let publisher = RecordingPublisher {
+            messages: Arc::clone(&messages),
+            unavailable: Arc::clone(&unavailable),
+            ..Default::default()
+        };
+        let event_bus = EventBus::new(Box::new(publisher), Arc::clone(&storage), EventBusTopics::with_prefix("test"), GasMode::Free);
+        let block = |number: u64| storage.read_block(BlockFilter::Number(BlockNumber::from(number))).unwrap().unwrap();
+
+        // block 2 fails while the broker is unavailable
+        event_bus.publish_next_block(block(1)).await.unwrap();
+        unavailable.store(true, Ordering::Relaxed);
+        assert!(event_bus.publish_next_block(block(2)).await.is_err());
+        unavailable.store(false, Ordering::Relaxed);
+
+        // block 2 is published from storage before block 3, and duplicates are skipped
+        event_bus.publish_next_block(block(3)).await.unwrap();
+        event_bus.publish_next_block(block(2)).await.unwrap();
+        assert_eq!(published_blocks(&messages), vec!["1", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_publish_missing_blocks_publishes_blocks_committed_while_not_running() {
+        let storage = storage_with_blocks(3);
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let publisher = RecordingPublisher {
+            messages: Arc::clone(&messages),
+            ..Default::default()
+        };
+        let event_bus = EventBus::new(Box::new(publisher), Arc::clone(&storage), EventBusTopics::with_prefix("test"), GasMode::Free);
+
+        // nothing to catch up before the first block is published
+        event_bus.publish_missing_blocks(BlockNumber::from(3)).await.unwrap();
+        assert!(published_blocks(&messages).is_empty());
+
+        let block = storage.read_block(BlockFilter::Number(BlockNumber::ONE)).unwrap().unwrap();
+        event_bus.publish_next_block(block).await.unwrap();
+        event_bus.publish_missing_blocks(storage.read_mined_block_number().unwrap()).await.unwrap();
+        assert_eq!(published_blocks(&messages), vec!["1", "2", "3"]);
+    }
+}
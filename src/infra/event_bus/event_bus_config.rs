@@ -0,0 +1,156 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::miner::Miner;
+use crate::eth::primitives::GasMode;
+use crate::eth::storage::StorageReader;
+use crate::infra::event_bus::EventBus;
+use crate::infra::event_bus::EventBusPublisher;
+use crate::infra::event_bus::EventBusTopics;
+use crate::infra::event_bus::NatsConfig;
+use crate::infra::event_bus::NatsPublisher;
+use crate::infra::kafka::KafkaConfig;
+use crate::log_and_err;
+
+#[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
+pub struct EventBusConfig {
+    /// Message broker where mined blocks, transactions, receipts and logs are published. Publishing is disabled if not set.
+    ///
+    /// Kafka uses the same `--kafka-*` connection settings of the importer, including SASL and SSL.
+    #[arg(long = "event-bus", env = "EVENT_BUS")]
+    pub event_bus_kind: Option<EventBusKind>,
+
+    /// NATS server URL. Only used by NATS, as Kafka uses the `--kafka-bootstrap-servers` setting.
+    #[arg(long = "event-bus-url", env = "EVENT_BUS_URL")]
+    pub event_bus_url: Option<String>,
+
+    /// Prefix of the topics where data is published: `<prefix>.blocks`, `<prefix>.transactions`, `<prefix>.receipts` and `<prefix>.logs`.
+    #[arg(long = "event-bus-topic-prefix", env = "EVENT_BUS_TOPIC_PREFIX", default_value = "stratus")]
+    pub event_bus_topic_prefix: String,
+
+    #[clap(flatten)]
+    pub nats: NatsConfig,
+}
+
+impl EventBusConfig {
+    /// Spawns the event bus publisher if a message broker is configured.
+    pub async fn init(&self, miner: &Miner, storage: Arc<dyn StorageReader>, gas_mode: GasMode, kafka_config: Option<&KafkaConfig>) -> anyhow::Result<()> {
+        let Some(publisher) = self.publisher(kafka_config).await? else {
+            tracing::info!("no event bus configured, skipping event bus publisher");
+            return Ok(());
+        };
+
+        let event_bus = EventBus::new(publisher, storage, EventBusTopics::with_prefix(&self.event_bus_topic_prefix), gas_mode);
+        event_bus.spawn(miner.notifier_committed_blocks.clone());
+
+        Ok(())
+    }
+
+    /// Creates the publisher of the configured message broker.
+    async fn publisher(&self, kafka_config: Option<&KafkaConfig>) -> anyhow::Result<Option<Box<dyn EventBusPublisher>>> {
+        let Some(kind) = self.event_bus_kind else { return Ok(None) };
+        tracing::info!(config = ?self, "creating event bus publisher");
+
+        let publisher: Box<dyn EventBusPublisher> = match kind {
+            EventBusKind::Kafka => {
+                let Some(kafka_config) = kafka_config else {
+                    return log_and_err!("kafka event bus requires the kafka connection settings");
+                };
+                Box::new(kafka_config.init()?)
+            }
+            EventBusKind::Nats => {
+                let Some(url) = self.event_bus_url.as_deref() else {
+                    return log_and_err!("nats event bus requires the event bus url");
+                };
+                Box::new(NatsPublisher::connect(url, &self.nats).await?)
+            }
+        };
+        Ok(Some(publisher))
+    }
+}
+
+#[derive(DebugAsJson, Clone, Copy, serde::Serialize)]
+pub enum EventBusKind {
+    #[serde(rename = "kafka")]
+    Kafka,
+
+    #[serde(rename = "nats")]
+    Nats,
+}
+
+impl FromStr for EventBusKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "kafka" => Ok(Self::Kafka),
+            "nats" => Ok(Self::Nats),
+            s => Err(anyhow!("unknown event bus: {}", s)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_kafka_requires_kafka_config() {
+        let config = EventBusConfig::parse_from(["test", "--event-bus", "kafka"]);
+        assert!(config.publisher(None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_kafka_reuses_kafka_connector() {
+        let config = EventBusConfig::parse_from(["test", "--event-bus", "kafka"]);
+        let kafka_config = KafkaConfig::parse_from([
+            "test",
+            "--kafka-bootstrap-servers",
+            "localhost:9092",
+            "--kafka-topic",
+            "events",
+            "--kafka-client-id",
+            "stratus",
+            "--kafka-security-protocol",
+            "sasl-ssl",
+            "--kafka-sasl-mechanisms",
+            "PLAIN",
+            "--kafka-sasl-username",
+            "user",
+            "--kafka-sasl-password",
+            "password",
+        ]);
+        assert!(config.publisher(Some(&kafka_config)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_nats_requires_url() {
+        let config = EventBusConfig::parse_from(["test", "--event-bus", "nats"]);
+        assert!(config.publisher(None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_kind() {
+        let config = EventBusConfig::parse_from(["test"]);
+        assert!(config.publisher(None).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_nats_tls_client_certificate_requires_key() {
+        assert!(EventBusConfig::try_parse_from(["test", "--event-bus-nats-tls-cert", "cert.pem"]).is_err());
+        assert!(EventBusConfig::try_parse_from(["test", "--event-bus-nats-tls-cert", "cert.pem", "--event-bus-nats-tls-key", "key.pem"]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_nats_missing_credentials_file_fails() {
+        let config = EventBusConfig::parse_from(["test", "--event-bus-nats-credentials-file", "/nonexistent/stratus.creds"]);
+        assert!(config.nats.connect_options().await.is_err());
+    }
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::infra::event_bus::EventBusPublisher;
+use crate::infra::kafka::KafkaConnector;
+
+/// Publishes messages to Kafka topics using the same connection settings of the importer.
+#[async_trait]
+impl EventBusPublisher for KafkaConnector {
+    async fn publish(&self, topic: &str, key: &str, payload: &str) -> anyhow::Result<()> {
+        self.send_record(topic, key, payload).await
+    }
+}
@@ -0,0 +1,15 @@
+//! Publishes mined chain data to message brokers.
+
+#[allow(clippy::module_inception)]
+mod event_bus;
+mod event_bus_config;
+mod kafka_publisher;
+mod nats_publisher;
+
+pub use event_bus::EventBus;
+pub use event_bus::EventBusPublisher;
+pub use event_bus::EventBusTopics;
+pub use event_bus_config::EventBusConfig;
+pub use event_bus_config::EventBusKind;
+pub use nats_publisher::NatsConfig;
+pub use nats_publisher::NatsPublisher;
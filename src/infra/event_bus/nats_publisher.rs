@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use async_nats::Client;
+use async_nats::ConnectOptions;
+use async_nats::HeaderMap;
+use async_trait::async_trait;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::infra::event_bus::EventBusPublisher;
+use crate::log_and_err;
+
+/// Header where the message key is sent, as NATS messages do not have keys.
+const KEY_HEADER: &str = "Stratus-Key";
+
+#[derive(Parser, DebugAsJson, Clone, Default, serde::Serialize)]
+pub struct NatsConfig {
+    /// NATS username, used together with the password.
+    #[arg(long = "event-bus-nats-user", env = "EVENT_BUS_NATS_USER", requires = "nats_password")]
+    pub nats_user: Option<String>,
+
+    /// NATS password, used together with the username.
+    #[serde(skip_serializing)]
+    #[arg(long = "event-bus-nats-password", env = "EVENT_BUS_NATS_PASSWORD", requires = "nats_user")]
+    pub nats_password: Option<String>,
+
+    /// NATS authentication token.
+    #[serde(skip_serializing)]
+    #[arg(long = "event-bus-nats-token", env = "EVENT_BUS_NATS_TOKEN", conflicts_with = "nats_user")]
+    pub nats_token: Option<String>,
+
+    /// NATS credentials file with the user JWT and NKey seed.
+    #[arg(long = "event-bus-nats-credentials-file", env = "EVENT_BUS_NATS_CREDENTIALS_FILE", conflicts_with_all = ["nats_user", "nats_token"])]
+    pub nats_credentials_file: Option<PathBuf>,
+
+    /// Requires TLS even if the server does not ask for it.
+    #[arg(long = "event-bus-nats-require-tls", env = "EVENT_BUS_NATS_REQUIRE_TLS", default_value = "false")]
+    pub nats_require_tls: bool,
+
+    /// Root certificate used to verify the server certificate.
+    #[arg(long = "event-bus-nats-tls-ca", env = "EVENT_BUS_NATS_TLS_CA")]
+    pub nats_tls_ca: Option<PathBuf>,
+
+    /// Client certificate used for mutual TLS, used together with the client key.
+    #[arg(long = "event-bus-nats-tls-cert", env = "EVENT_BUS_NATS_TLS_CERT", requires = "nats_tls_key")]
+    pub nats_tls_cert: Option<PathBuf>,
+
+    /// Client key used for mutual TLS, used together with the client certificate.
+    #[arg(long = "event-bus-nats-tls-key", env = "EVENT_BUS_NATS_TLS_KEY", requires = "nats_tls_cert")]
+    pub nats_tls_key: Option<PathBuf>,
+}
+
+impl NatsConfig {
+    /// Creates the connection options with authentication and TLS settings.
+    ///
+    /// The client reconnects automatically when the connection is lost, buffering messages published while it is disconnected.
+    pub async fn connect_options(&self) -> anyhow::Result<ConnectOptions> {
+        let mut options = ConnectOptions::new().name("stratus").event_callback(|event| async move {
+            tracing::warn!(%event, "nats connection event");
+        });
+
+        // authentication
+        if let (Some(user), Some(password)) = (&self.nats_user, &self.nats_password) {
+            options = options.user_and_password(user.clone(), password.clone());
+        }
+        if let Some(token) = &self.nats_token {
+            options = options.token(token.clone());
+        }
+        if let Some(path) = &self.nats_credentials_file {
+            options = match options.credentials_file(path).await {
+                Ok(options) => options,
+                Err(e) => return log_and_err!(reason = e, "failed to read nats credentials file"),
+            };
+        }
+
+        // tls
+        options = options.require_tls(self.nats_require_tls);
+        if let Some(path) = &self.nats_tls_ca {
+            options = options.add_root_certificates(path.clone());
+        }
+        if let (Some(cert), Some(key)) = (&self.nats_tls_cert, &self.nats_tls_key) {
+            options = options.add_client_certificate(cert.clone(), key.clone());
+        }
+
+        Ok(options)
+    }
+}
+
+/// Publishes messages to NATS subjects.
+pub struct NatsPublisher {
+    client: Client,
+}
+
+impl NatsPublisher {
+    pub async fn connect(url: &str, config: &NatsConfig) -> anyhow::Result<Self> {
+        tracing::info!(%url, "creating nats event bus publisher");
+
+        match config.connect_options().await?.connect(url).await {
+            Ok(client) => Ok(Self { client }),
+            Err(e) => log_and_err!(reason = e, "failed to connect to nats server"),
+        }
+    }
+}
+
+#[async_trait]
+impl EventBusPublisher for NatsPublisher {
+    async fn publish(&self, topic: &str, key: &str, payload: &str) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(KEY_HEADER, key);
+        if let Err(e) = self.client.publish_with_headers(topic.to_owned(), headers, payload.to_owned().into()).await {
+            return log_and_err!(reason = e, "failed to publish nats message");
+        }
+        Ok(())
+    }
+}
@@ -17,7 +17,7 @@ use crate::ledger::events::Event;
 use crate::log_and_err;
 
 #[derive(Parser, DebugAsJson, Clone, serde::Serialize, serde::Deserialize, Default)]
-#[group(requires_all = ["bootstrap_servers", "topic", "client_id", "ImporterConfig"])]
+#[group(requires_all = ["bootstrap_servers", "topic", "client_id"])]
 pub struct KafkaConfig {
     #[arg(long = "kafka-bootstrap-servers", env = "KAFKA_BOOTSTRAP_SERVERS", required = false)]
     pub bootstrap_servers: String,
@@ -149,22 +149,32 @@ impl KafkaConnector {
         let key = event.event_key()?;
         let payload = event.event_payload()?;
 
+        // publish and handle response
+        tracing::info!(%key, %payload, ?headers, "publishing kafka event");
+        self.queue_record(&self.topic, &key, &payload, headers.iter().map(|(key, value)| (key.as_str(), value.as_str())))
+    }
+
+    /// Queues a message to any topic, not only the configured one.
+    pub fn queue_record<'a>(&self, topic: &str, key: &str, payload: &str, headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<DeliveryFuture> {
         // prepare kafka payload
-        let mut kafka_headers = OwnedHeaders::new_with_capacity(headers.len());
-        for (key, value) in headers.iter() {
+        let mut kafka_headers = OwnedHeaders::new();
+        for (key, value) in headers {
             let header = Header { key, value: Some(value) };
             kafka_headers = kafka_headers.insert(header);
         }
-        let kafka_record = FutureRecord::to(&self.topic).payload(&payload).key(&key).headers(kafka_headers);
+        let kafka_record = FutureRecord::to(topic).payload(payload).key(key).headers(kafka_headers);
 
-        // publis and handle response
-        tracing::info!(%key, %payload, ?headers, "publishing kafka event");
         match self.producer.send_result(kafka_record) {
             Err((e, _)) => log_and_err!(reason = e, "failed to queue kafka event"),
             Ok(fut) => Ok(fut),
         }
     }
 
+    /// Sends a message to any topic, not only the configured one, and waits for its delivery.
+    pub async fn send_record(&self, topic: &str, key: &str, payload: &str) -> Result<()> {
+        handle_delivery_result(self.queue_record(topic, key, payload, [])?.await)
+    }
+
     pub async fn send_event<T: Event>(&self, event: T) -> Result<()> {
         tracing::debug!(?event, "sending event");
         handle_delivery_result(self.queue_event(event)?.await)
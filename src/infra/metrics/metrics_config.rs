@@ -3,16 +3,25 @@ use std::stringify;
 
 use clap::Parser;
 use display_json::DebugAsJson;
+use opentelemetry::metrics::Meter;
 
+use crate::infra::metrics::metrics_for_block;
+use crate::infra::metrics::metrics_for_blockchain_client;
+use crate::infra::metrics::metrics_for_circuit_breaker;
 use crate::infra::metrics::metrics_for_consensus;
+use crate::infra::metrics::metrics_for_event_bus;
 use crate::infra::metrics::metrics_for_evm;
 use crate::infra::metrics::metrics_for_executor;
+use crate::infra::metrics::metrics_for_external_rpc;
 use crate::infra::metrics::metrics_for_importer_online;
 use crate::infra::metrics::metrics_for_json_rpc;
 use crate::infra::metrics::metrics_for_kafka;
+use crate::infra::metrics::metrics_for_relayer;
 use crate::infra::metrics::metrics_for_rocks;
+use crate::infra::metrics::metrics_for_state;
 use crate::infra::metrics::metrics_for_storage_read;
 use crate::infra::metrics::metrics_for_storage_write;
+use crate::infra::metrics::metrics_for_supervisor;
 
 #[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
 pub struct MetricsConfig {
@@ -23,7 +32,9 @@ pub struct MetricsConfig {
 
 impl MetricsConfig {
     /// Inits application global metrics exporter.
-    pub fn init(&self) -> anyhow::Result<()> {
+    ///
+    /// If an OpenTelemetry meter is provided, metrics are also recorded in it and exported via OTLP.
+    pub fn init(&self, otlp_meter: Option<Meter>) -> anyhow::Result<()> {
         tracing::info!(address = %self.metrics_exporter_address, "creating metrics exporter");
 
         // get metric definitions
@@ -32,14 +43,22 @@ impl MetricsConfig {
         metrics.extend(metrics_for_json_rpc());
         metrics.extend(metrics_for_executor());
         metrics.extend(metrics_for_evm());
+        metrics.extend(metrics_for_block());
         metrics.extend(metrics_for_storage_read());
         metrics.extend(metrics_for_storage_write());
         metrics.extend(metrics_for_rocks());
         metrics.extend(metrics_for_consensus());
         metrics.extend(metrics_for_kafka());
+        metrics.extend(metrics_for_event_bus());
+        metrics.extend(metrics_for_state());
+        metrics.extend(metrics_for_supervisor());
+        metrics.extend(metrics_for_blockchain_client());
+        metrics.extend(metrics_for_circuit_breaker());
+        metrics.extend(metrics_for_external_rpc());
+        metrics.extend(metrics_for_relayer());
 
         // init metric exporter
-        init_metrics_exporter(self.metrics_exporter_address);
+        init_metrics_exporter(self.metrics_exporter_address, otlp_meter);
 
         // init metric description (always after provider started)
         for metric in &metrics {
@@ -51,19 +70,43 @@ impl MetricsConfig {
 }
 
 #[cfg(feature = "metrics")]
-fn init_metrics_exporter(address: SocketAddr) {
-    tracing::info!(%address, "creating prometheus metrics exporter");
-    if let Err(e) = metrics_exporter_prometheus::PrometheusBuilder::new()
+fn init_metrics_exporter(address: SocketAddr, otlp_meter: Option<Meter>) {
+    use crate::ext::spawn_named;
+    use crate::infra::metrics::metrics_recorders::FanoutRecorder;
+    use crate::infra::metrics::metrics_recorders::OtlpRecorder;
+
+    let prometheus_builder = metrics_exporter_prometheus::PrometheusBuilder::new()
         .add_global_label("service", crate::infra::build_info::service_name())
         .add_global_label("version", crate::infra::build_info::version())
-        .with_http_listener(address)
-        .install()
-    {
-        tracing::error!(reason = ?e, %address, "failed to create metrics exporter");
+        .with_http_listener(address);
+
+    // prometheus only
+    let Some(otlp_meter) = otlp_meter else {
+        tracing::info!(%address, "creating prometheus metrics exporter");
+        if let Err(e) = prometheus_builder.install() {
+            tracing::error!(reason = ?e, %address, "failed to create metrics exporter");
+        }
+        return;
+    };
+
+    // prometheus and opentelemetry
+    tracing::info!(%address, "creating prometheus and opentelemetry metrics exporters");
+    let (prometheus_recorder, prometheus_exporter) = match prometheus_builder.build() {
+        Ok(built) => built,
+        Err(e) => {
+            tracing::error!(reason = ?e, %address, "failed to create metrics exporter");
+            return;
+        }
+    };
+    spawn_named("metrics::prometheus-exporter", prometheus_exporter);
+
+    let recorder = FanoutRecorder(vec![Box::new(prometheus_recorder), Box::new(OtlpRecorder::new(otlp_meter))]);
+    if metrics::set_global_recorder(recorder).is_err() {
+        tracing::error!("failed to install metrics recorder because another one is already installed");
     }
 }
 
 #[cfg(not(feature = "metrics"))]
-fn init_metrics_exporter(_: SocketAddr) {
+fn init_metrics_exporter(_: SocketAddr, _: Option<Meter>) {
     tracing::info!("creating noop metrics exporter");
 }
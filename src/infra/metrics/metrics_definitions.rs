@@ -13,11 +13,29 @@ metrics! {
     "Number of JSON-RPC requests that finished."
     histogram_duration rpc_requests_finished{client, method, contract, function, result, result_code, success},
 
+    "Number of gRPC requests that finished."
+    histogram_duration grpc_requests_finished{method, code},
+
     "Number of JSON-RPC subscriptions active right now."
     gauge rpc_subscriptions_active{subscription, client},
 
+    "Number of JSON-RPC subscriptions lagging right now because the subscriber is not consuming notifications fast enough."
+    gauge rpc_subscriptions_lagging{subscription, client},
+
+    "Number of JSON-RPC subscription notifications dropped because the subscriber queue was full."
+    counter rpc_subscriptions_dropped{subscription, client},
+
     "Number of times we respons a client with an error."
-    counter rpc_error_response{error_type, client, method}
+    counter rpc_error_response{error_type, client, method},
+
+    "Number of transactions rejected by address policies."
+    counter rpc_transaction_policy_rejected{policy},
+
+    "Number of raw transactions rejected by each validation step."
+    counter rpc_transaction_validation_rejected{step},
+
+    "Time JSON-RPC requests waited for a worker of their lane."
+    histogram_duration rpc_lane_wait{lane}
 }
 
 // Storage reads.
@@ -36,14 +54,38 @@ metrics! {
     "Time executing storage read_block operation."
     histogram_duration storage_read_block{storage, success},
 
+    "Time executing storage read_balance_changes operation."
+    histogram_duration storage_read_balance_changes{storage, success},
+
+    "Time executing storage read_internal_transfers operations."
+    histogram_duration storage_read_internal_transfers{storage, success},
+
+    "Time executing storage read_token_transfers operations."
+    histogram_duration storage_read_token_transfers{storage, success},
+
+    "Time executing storage read_contract_creation operation."
+    histogram_duration storage_read_contract_creation{storage, success},
+
+    "Time executing storage read_state_diff operation."
+    histogram_duration storage_read_state_diff{storage, success},
+
     "Time executing storage read_logs operation."
     histogram_duration storage_read_logs{storage, success},
 
     "Time executing storage read_slot operation."
     histogram_duration storage_read_slot{storage, point_in_time, success},
 
+    "Time executing storage read_slots_range operation."
+    histogram_duration storage_read_slots_range{storage, success},
+
     "Time executing storage read_transaction operation."
-    histogram_duration storage_read_transaction{storage, success}
+    histogram_duration storage_read_transaction{storage, success},
+
+    "Time fetching an account from the forked upstream chain."
+    histogram_duration storage_fork_read_account{success},
+
+    "Time fetching a slot from the forked upstream chain."
+    histogram_duration storage_fork_read_slot{success}
 }
 
 // Storage writes.
@@ -83,10 +125,46 @@ metrics! {
     histogram_duration import_online_mined_block{},
 
     "Number of transactions imported."
-    counter importer_online_transactions_total{}
+    counter importer_online_transactions_total{},
+
+    "Number of imported block header fields with values different from the expected for all blocks of the chain."
+    counter importer_invariant_violations{field},
+
+    "Number of imported block header fields that diverge from the ones calculated from the block reexecution."
+    counter importer_receipts_divergences{field}
 }
 
 // Execution metrics.
+metrics! {
+    group: external_rpc,
+
+    "Number of external RPC storage operations retried after transient errors."
+    counter external_rpc_storage_retries{operation, kind}
+}
+
+metrics! {
+    group: blockchain_client,
+
+    "Whether the blockchain client websocket is connected (1) or disconnected (0)."
+    gauge blockchain_client_ws_connected{},
+
+    "Number of attempts to reconnect the blockchain client websocket."
+    counter blockchain_client_ws_reconnections{success},
+
+    "Time to send typed requests of the blockchain client."
+    histogram_duration blockchain_client_request{method, success}
+}
+
+metrics! {
+    group: circuit_breaker,
+
+    "Whether the circuit breaker of a dependency is open (1) or closed (0)."
+    gauge circuit_breaker_open{name},
+
+    "Number of calls rejected because the circuit breaker of a dependency is open."
+    counter circuit_breaker_rejected{name}
+}
+
 metrics! {
     group: executor,
 
@@ -123,6 +201,9 @@ metrics! {
     "Gas spent executing a local transaction."
     histogram_counter executor_local_transaction_gas{success, contract, function},
 
+    "Number of times a local transaction was re-executed because of conflicts."
+    histogram_counter executor_local_transaction_conflict_retries{evm_route, success},
+
     "Time executing a transaction received with eth_call or eth_estimateGas."
     histogram_duration executor_local_call{success, contract, function},
 
@@ -136,6 +217,23 @@ metrics! {
     histogram_counter executor_local_call_gas{contract, function}
 }
 
+// Block pipeline metrics.
+metrics! {
+    group: block,
+
+    "Time executing all transactions of an imported block in the EVM."
+    histogram_duration block_evm_execution{backend},
+
+    "Time checking conflicts of all transactions of a mined block."
+    histogram_duration block_conflict_check{backend},
+
+    "Time writing a mined block to the permanent storage."
+    histogram_duration block_storage_write{backend},
+
+    "Gas per second executed by the EVM for each transaction of an imported block."
+    histogram_counter block_transaction_gas_per_second{backend}
+}
+
 metrics! {
     group: evm,
 
@@ -230,3 +328,68 @@ metrics! {
     "Time to run KafkaConnector::create_buffer"
     histogram_duration kafka_create_buffer{}
 }
+
+// State analytics metrics
+metrics! {
+    group: state,
+
+    "Number of accounts in the current state."
+    gauge state_accounts{},
+
+    "Number of slots in the current state."
+    gauge state_slots{},
+
+    "Approximate size in bytes of the current state."
+    gauge state_size{}
+}
+
+// Event bus metrics
+metrics! {
+    group: event_bus,
+
+    "Time to publish a message to the event bus."
+    histogram_duration event_bus_publish{topic, success},
+
+    "Number of committed blocks not published to the event bus yet."
+    gauge event_bus_blocks_behind{}
+}
+
+// Supervisor metrics
+metrics! {
+    group: supervisor,
+
+    "Number of times a supervised subsystem was restarted after failing."
+    counter supervisor_restarts{task, reason}
+}
+
+// Relayer metrics
+metrics! {
+    group: relayer,
+
+    "Time to relay a block, including the confirmation of its transactions by the target chain."
+    histogram_duration relayer_relay_block{},
+
+    "Number of transactions relayed to the target chain."
+    counter relayer_transactions_relayed{},
+
+    "Number of blocks relayed by iteration, adapted to the target chain confirmation latency and errors."
+    gauge relayer_blocks_to_fetch{},
+
+    "Number of multicall batches relayed to the target chain."
+    counter relayer_batches_relayed{},
+
+    "Number of the last block that reached each relay status."
+    gauge relayer_block_number{status},
+
+    "Number of blocks mined by Stratus after the last block whose relayed receipts were compared."
+    gauge relayer_lag_blocks{},
+
+    "Seconds from Stratus mining the last block whose relayed receipts were compared until they were compared."
+    gauge relayer_lag_seconds{},
+
+    "Number of relayed transactions whose receipt in the target chain differs from the Stratus one."
+    counter relayer_mismatches{},
+
+    "Number of relayed blocks changed by the reconciliation with Stratus."
+    counter relayer_reconciliation_blocks{action, dry_run}
+}
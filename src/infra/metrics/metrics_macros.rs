@@ -49,7 +49,8 @@ macro_rules! metrics_impl_fn_inc {
                 let labels = super::into_labels(
                     vec![
                         ("group", stringify!($group).into()),
-                        ("node_mode", stringify!($crate::globals::GlobalState::get_node_mode()).into()),
+                        ("chain", $crate::globals::GlobalState::get_chain_name().into()),
+                        ("node_mode", $crate::globals::GlobalState::get_node_mode().to_string().into()),
                         $(
                             (stringify!($label), $label.into()),
                         )*
@@ -67,7 +68,8 @@ macro_rules! metrics_impl_fn_inc {
                 let labels = super::into_labels(
                     vec![
                         ("group", stringify!($group).into()),
-                        ("node_mode", stringify!($crate::globals::GlobalState::get_node_mode()).into()),
+                        ("chain", $crate::globals::GlobalState::get_chain_name().into()),
+                        ("node_mode", $crate::globals::GlobalState::get_node_mode().to_string().into()),
                         $(
                             (stringify!($label), $label.into()),
                         )*
@@ -86,7 +88,8 @@ macro_rules! metrics_impl_fn_inc {
                 let labels = super::into_labels(
                     vec![
                         ("group", stringify!($group).into()),
-                        ("node_mode", stringify!($crate::globals::GlobalState::get_node_mode()).into()),
+                        ("chain", $crate::globals::GlobalState::get_chain_name().into()),
+                        ("node_mode", $crate::globals::GlobalState::get_node_mode().to_string().into()),
                         $(
                             (stringify!($label), $label.into()),
                         )*
@@ -105,7 +108,8 @@ macro_rules! metrics_impl_fn_inc {
                 let labels = super::into_labels(
                     vec![
                         ("group", stringify!($group).into()),
-                        ("node_mode", stringify!($crate::globals::GlobalState::get_node_mode()).into()),
+                        ("chain", $crate::globals::GlobalState::get_chain_name().into()),
+                        ("node_mode", $crate::globals::GlobalState::get_node_mode().to_string().into()),
                         $(
                             (stringify!($label), $label.into()),
                         )*
@@ -124,7 +128,8 @@ macro_rules! metrics_impl_fn_inc {
                 let labels = super::into_labels(
                     vec![
                         ("group", stringify!($group).into()),
-                        ("node_mode", stringify!($crate::globals::GlobalState::get_node_mode()).into()),
+                        ("chain", $crate::globals::GlobalState::get_chain_name().into()),
+                        ("node_mode", $crate::globals::GlobalState::get_node_mode().to_string().into()),
                         $(
                             (stringify!($label), $label.into()),
                         )*
@@ -0,0 +1,414 @@
+//! Metrics recorders used in addition to the Prometheus exporter.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use metrics::Counter;
+use metrics::CounterFn;
+use metrics::Gauge;
+use metrics::GaugeFn;
+use metrics::Histogram;
+use metrics::HistogramFn;
+use metrics::Key;
+use metrics::KeyName;
+use metrics::Metadata;
+use metrics::Recorder;
+use metrics::SharedString;
+use metrics::Unit;
+use opentelemetry::metrics::Meter;
+use opentelemetry::KeyValue;
+use parking_lot::Mutex;
+
+// -----------------------------------------------------------------------------
+// Fanout
+// -----------------------------------------------------------------------------
+
+/// Recorder that forwards all metrics to multiple recorders, because only one global recorder can be installed.
+pub struct FanoutRecorder(pub Vec<Box<dyn Recorder + Send + Sync>>);
+
+impl Recorder for FanoutRecorder {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.0 {
+            recorder.describe_counter(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.0 {
+            recorder.describe_gauge(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.0 {
+            recorder.describe_histogram(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        let counters = self.0.iter().map(|recorder| recorder.register_counter(key, metadata)).collect();
+        Counter::from_arc(Arc::new(FanoutMetric(counters)))
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        let gauges = self.0.iter().map(|recorder| recorder.register_gauge(key, metadata)).collect();
+        Gauge::from_arc(Arc::new(FanoutMetric(gauges)))
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        let histograms = self.0.iter().map(|recorder| recorder.register_histogram(key, metadata)).collect();
+        Histogram::from_arc(Arc::new(FanoutMetric(histograms)))
+    }
+}
+
+struct FanoutMetric<T>(Vec<T>);
+
+impl CounterFn for FanoutMetric<Counter> {
+    fn increment(&self, value: u64) {
+        self.0.iter().for_each(|counter| counter.increment(value));
+    }
+
+    fn absolute(&self, value: u64) {
+        self.0.iter().for_each(|counter| counter.absolute(value));
+    }
+}
+
+impl GaugeFn for FanoutMetric<Gauge> {
+    fn increment(&self, value: f64) {
+        self.0.iter().for_each(|gauge| gauge.increment(value));
+    }
+
+    fn decrement(&self, value: f64) {
+        self.0.iter().for_each(|gauge| gauge.decrement(value));
+    }
+
+    fn set(&self, value: f64) {
+        self.0.iter().for_each(|gauge| gauge.set(value));
+    }
+}
+
+impl HistogramFn for FanoutMetric<Histogram> {
+    fn record(&self, value: f64) {
+        self.0.iter().for_each(|histogram| histogram.record(value));
+    }
+}
+
+// -----------------------------------------------------------------------------
+// OpenTelemetry
+// -----------------------------------------------------------------------------
+
+/// Recorder that records metrics in OpenTelemetry instruments, which are periodically exported by the meter provider.
+///
+/// Instruments are created once per metric name and shared by all label combinations. Gauges are observable instruments, so their last value is
+/// kept here and reported when the provider collects them.
+pub struct OtlpRecorder {
+    meter: Meter,
+    descriptions: Mutex<HashMap<String, SharedString>>,
+    counters: Mutex<HashMap<String, opentelemetry::metrics::Counter<u64>>>,
+    gauges: Mutex<HashMap<String, OtlpGaugeValues>>,
+    histograms: Mutex<HashMap<String, opentelemetry::metrics::Histogram<f64>>>,
+}
+
+/// Last values of a gauge by label combination, stored as `f64` bits.
+type OtlpGaugeValues = Arc<Mutex<HashMap<Key, Arc<AtomicU64>>>>;
+
+impl OtlpRecorder {
+    pub fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            descriptions: Mutex::default(),
+            counters: Mutex::default(),
+            gauges: Mutex::default(),
+            histograms: Mutex::default(),
+        }
+    }
+
+    fn describe(&self, key: KeyName, description: SharedString) {
+        self.descriptions.lock().insert(key.as_str().to_owned(), description);
+    }
+
+    fn description(&self, name: &str) -> String {
+        self.descriptions
+            .lock()
+            .get(name)
+            .map(|description| description.to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl Recorder for OtlpRecorder {
+    fn describe_counter(&self, key: KeyName, _: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, _: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, _: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let counter = self
+            .counters
+            .lock()
+            .entry(key.name().to_owned())
+            .or_insert_with(|| {
+                self.meter
+                    .u64_counter(key.name().to_owned())
+                    .with_description(self.description(key.name()))
+                    .init()
+            })
+            .clone();
+        Counter::from_arc(Arc::new(OtlpCounter {
+            counter,
+            attributes: to_attributes(key),
+            last_absolute: AtomicU64::new(0),
+        }))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        let values = Arc::clone(self.gauges.lock().entry(key.name().to_owned()).or_insert_with(|| {
+            let values = OtlpGaugeValues::default();
+            let observed_values = Arc::clone(&values);
+            self.meter
+                .f64_observable_gauge(key.name().to_owned())
+                .with_description(self.description(key.name()))
+                .with_callback(move |observer| {
+                    for (key, value) in observed_values.lock().iter() {
+                        observer.observe(f64::from_bits(value.load(Ordering::Relaxed)), &to_attributes(key));
+                    }
+                })
+                .init();
+            values
+        }));
+        let value = Arc::clone(values.lock().entry(key.clone()).or_default());
+        Gauge::from_arc(Arc::new(OtlpGauge(value)))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        let histogram = self
+            .histograms
+            .lock()
+            .entry(key.name().to_owned())
+            .or_insert_with(|| {
+                self.meter
+                    .f64_histogram(key.name().to_owned())
+                    .with_description(self.description(key.name()))
+                    .init()
+            })
+            .clone();
+        Histogram::from_arc(Arc::new(OtlpHistogram {
+            histogram,
+            attributes: to_attributes(key),
+        }))
+    }
+}
+
+fn to_attributes(key: &Key) -> Vec<KeyValue> {
+    key.labels()
+        .map(|label| KeyValue::new(label.key().to_owned(), label.value().to_owned()))
+        .collect()
+}
+
+struct OtlpCounter {
+    counter: opentelemetry::metrics::Counter<u64>,
+    attributes: Vec<KeyValue>,
+    /// OpenTelemetry counters only accept increments, so absolute values are converted to the difference from the last one.
+    last_absolute: AtomicU64,
+}
+
+impl CounterFn for OtlpCounter {
+    fn increment(&self, value: u64) {
+        self.counter.add(value, &self.attributes);
+    }
+
+    fn absolute(&self, value: u64) {
+        let last = self.last_absolute.swap(value, Ordering::Relaxed);
+        self.counter.add(value.saturating_sub(last), &self.attributes);
+    }
+}
+
+struct OtlpGauge(Arc<AtomicU64>);
+
+impl OtlpGauge {
+    fn update(&self, f: impl Fn(f64) -> f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some(f(f64::from_bits(bits)).to_bits()));
+    }
+}
+
+impl GaugeFn for OtlpGauge {
+    fn increment(&self, value: f64) {
+        self.update(|current| current + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.update(|current| current - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+struct OtlpHistogram {
+    histogram: opentelemetry::metrics::Histogram<f64>,
+    attributes: Vec<KeyValue>,
+}
+
+impl HistogramFn for OtlpHistogram {
+    fn record(&self, value: f64) {
+        self.histogram.record(value, &self.attributes);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::Weak;
+
+    use metrics::Label;
+    use metrics::Level;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data;
+    use opentelemetry_sdk::metrics::data::ResourceMetrics;
+    use opentelemetry_sdk::metrics::data::Temporality;
+    use opentelemetry_sdk::metrics::reader::AggregationSelector;
+    use opentelemetry_sdk::metrics::reader::MetricReader;
+    use opentelemetry_sdk::metrics::reader::TemporalitySelector;
+    use opentelemetry_sdk::metrics::Aggregation;
+    use opentelemetry_sdk::metrics::InstrumentKind;
+    use opentelemetry_sdk::metrics::ManualReader;
+    use opentelemetry_sdk::metrics::Pipeline;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::Resource;
+
+    use super::*;
+
+    /// Manual reader shared with the meter provider, so tests can collect the metrics recorded through it.
+    #[derive(Debug, Clone, Default)]
+    struct SharedReader(Arc<ManualReader>);
+
+    impl TemporalitySelector for SharedReader {
+        fn temporality(&self, kind: InstrumentKind) -> Temporality {
+            self.0.temporality(kind)
+        }
+    }
+
+    impl AggregationSelector for SharedReader {
+        fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+            self.0.aggregation(kind)
+        }
+    }
+
+    impl MetricReader for SharedReader {
+        fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+            self.0.register_pipeline(pipeline);
+        }
+
+        fn collect(&self, rm: &mut ResourceMetrics) -> opentelemetry::metrics::Result<()> {
+            self.0.collect(rm)
+        }
+
+        fn force_flush(&self) -> opentelemetry::metrics::Result<()> {
+            self.0.force_flush()
+        }
+
+        fn shutdown(&self) -> opentelemetry::metrics::Result<()> {
+            self.0.shutdown()
+        }
+    }
+
+    /// Creates an OpenTelemetry recorder and the reader of the metrics it records.
+    ///
+    /// The provider must be kept alive, otherwise the reader is unregistered.
+    fn otlp_recorder() -> (OtlpRecorder, SharedReader, SdkMeterProvider) {
+        let reader = SharedReader::default();
+        let provider = SdkMeterProvider::builder().with_reader(reader.clone()).build();
+        (OtlpRecorder::new(provider.meter("test")), reader, provider)
+    }
+
+    /// Collects the metrics recorded with the reader and checks the aggregation of the metric with the given name.
+    fn collect<T: 'static>(reader: &SharedReader, name: &str, f: impl FnOnce(&T)) {
+        let mut rm = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![],
+        };
+        reader.collect(&mut rm).unwrap();
+
+        let metric = rm
+            .scope_metrics
+            .iter()
+            .flat_map(|scope| scope.metrics.iter())
+            .find(|metric| metric.name == name)
+            .unwrap_or_else(|| panic!("metric {} not collected", name));
+        f(metric.data.as_any().downcast_ref::<T>().unwrap());
+    }
+
+    const METADATA: Metadata<'static> = Metadata::new("test", Level::INFO, None);
+
+    fn key(name: &'static str) -> Key {
+        Key::from_parts(name, vec![Label::new("success", "true")])
+    }
+
+    #[test]
+    fn test_otlp_counter_converts_absolute_values_to_increments() {
+        let (recorder, reader, _provider) = otlp_recorder();
+        recorder.describe_counter(KeyName::from("test_counter"), None, SharedString::from("Test counter."));
+
+        let counter = recorder.register_counter(&key("test_counter"), &METADATA);
+        counter.increment(2);
+        counter.absolute(5);
+        counter.absolute(8);
+
+        collect::<data::Sum<u64>>(&reader, "test_counter", |sum| {
+            assert_eq!(sum.data_points.len(), 1);
+            assert_eq!(sum.data_points[0].value, 2 + 5 + 3);
+            assert!(sum.data_points[0]
+                .attributes
+                .iter()
+                .any(|(key, value)| key.as_str() == "success" && value.as_str() == "true"));
+        });
+    }
+
+    #[test]
+    fn test_otlp_gauge_reports_last_value() {
+        let (recorder, reader, _provider) = otlp_recorder();
+
+        let gauge = recorder.register_gauge(&key("test_gauge"), &METADATA);
+        gauge.set(3.0);
+        gauge.increment(2.0);
+        gauge.decrement(0.5);
+
+        collect::<data::Gauge<f64>>(&reader, "test_gauge", |gauge| {
+            assert_eq!(gauge.data_points.len(), 1);
+            assert_eq!(gauge.data_points[0].value, 4.5);
+        });
+    }
+
+    #[test]
+    fn test_fanout_forwards_to_all_recorders() {
+        let (first, first_reader, _first_provider) = otlp_recorder();
+        let (second, second_reader, _second_provider) = otlp_recorder();
+        let recorder = FanoutRecorder(vec![Box::new(first), Box::new(second)]);
+
+        let histogram = recorder.register_histogram(&key("test_histogram"), &METADATA);
+        histogram.record(1.5);
+        histogram.record(2.5);
+
+        for reader in [first_reader, second_reader] {
+            collect::<data::Histogram<f64>>(&reader, "test_histogram", |histogram| {
+                assert_eq!(histogram.data_points.len(), 1);
+                assert_eq!(histogram.data_points[0].count, 2);
+                assert_eq!(histogram.data_points[0].sum, 4.0);
+            });
+        }
+    }
+}
@@ -101,6 +101,15 @@ impl From<String> for MetricLabelValue {
     }
 }
 
+impl From<Option<String>> for MetricLabelValue {
+    fn from(value: Option<String>) -> Self {
+        match value {
+            Some(value) => Self::Some(value),
+            None => Self::None,
+        }
+    }
+}
+
 impl From<bool> for MetricLabelValue {
     fn from(value: bool) -> Self {
         Self::Some(value.to_string())
@@ -1,6 +1,8 @@
 mod metrics_config;
 mod metrics_definitions;
 mod metrics_macros;
+#[cfg(feature = "metrics")]
+mod metrics_recorders;
 mod metrics_types;
 
 use std::time::Instant;
@@ -1,10 +1,22 @@
 //! Shared infrastructure.
 
+pub mod alerting;
 pub mod blockchain_client;
 pub mod build_info;
+pub mod circuit_breaker;
+pub mod clock;
+#[cfg(all(test, feature = "postgres"))]
+pub mod docker;
+pub mod event_bus;
 pub mod kafka;
 pub mod metrics;
+#[cfg(feature = "parquet")]
+pub mod parquet;
 pub mod sentry;
+pub mod supervisor;
+pub mod task_registry;
 pub mod tracing;
+pub mod watchdog;
 
 pub use blockchain_client::BlockchainClient;
+pub use circuit_breaker::CircuitBreaker;
@@ -0,0 +1,28 @@
+//! Parquet files encoding and decoding.
+
+use arrow_array::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+
+/// Encodes a record batch as a Snappy-compressed Parquet file.
+pub fn write_parquet(batch: &RecordBatch) -> anyhow::Result<Vec<u8>> {
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// Decodes all record batches of a Parquet file.
+pub fn read_parquet<R: ChunkReader + 'static>(data: R) -> anyhow::Result<Vec<RecordBatch>> {
+    let mut batches = Vec::new();
+    for batch in ParquetRecordBatchReaderBuilder::try_new(data)?.build()? {
+        batches.push(batch?);
+    }
+    Ok(batches)
+}
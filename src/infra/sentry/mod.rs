@@ -1,3 +1,4 @@
 mod sentry_config;
 
+pub use sentry_config::set_sentry_tag;
 pub use sentry_config::SentryConfig;
@@ -1,3 +1,6 @@
+use std::fmt::Display;
+use std::thread;
+
 use clap::Parser;
 use display_json::DebugAsJson;
 use sentry::ClientInitGuard;
@@ -31,6 +34,25 @@ impl SentryConfig {
             tracing::error!(url = %self.sentry_url, %env, %release, "failed to create sentry exporter");
         }
 
+        // deployment context attached to all events
+        set_sentry_tag("version", build_info::version());
+        set_sentry_tag("service", build_info::service_name());
+
+        // panics are captured by the panic hook installed by sentry, which runs even when the panic happens inside a spawned task and the task is
+        // never joined; this hook runs before it and identifies the thread where the panic happened
+        let sentry_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            set_sentry_tag("thread", thread::current().name().unwrap_or("unnamed"));
+            sentry_hook(info);
+        }));
+
         Ok(guard)
     }
 }
+
+/// Sets a tag attached to all Sentry events sent after it, like the node mode or the chain id.
+///
+/// Does nothing if Sentry is not initialized.
+pub fn set_sentry_tag(key: &str, value: impl Display) {
+    sentry::configure_scope(|scope| scope.set_tag(key, value));
+}
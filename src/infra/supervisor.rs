@@ -0,0 +1,293 @@
+//! Supervision of background subsystems.
+//!
+//! A supervised subsystem runs in its own task, so a panic or an error inside it does not silently kill it while the process keeps serving
+//! requests. When the task fails, the supervisor restarts it after a backoff. If it fails more times than the restart budget allows, the
+//! supervisor gives up and shuts down the application, so the node is restarted by its orchestrator instead of running in a degraded state.
+
+use std::future::Future;
+use std::time::Duration;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use once_cell::sync::OnceCell;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::eth::primitives::StratusError;
+use crate::ext::parse_duration;
+use crate::ext::spawn_named;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::GlobalState;
+
+/// Restart policy used by all supervisors, configured at startup.
+static RESTART_POLICY: OnceCell<RestartPolicy> = OnceCell::new();
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct SupervisorConfig {
+    /// Max restarts of a subsystem inside the restart window before the application is shut down.
+    #[arg(long = "supervisor-max-restarts", env = "SUPERVISOR_MAX_RESTARTS", default_value = "5")]
+    pub supervisor_max_restarts: usize,
+
+    /// Window in which restarts of a subsystem are counted against the max restarts.
+    #[arg(long = "supervisor-restart-window", env = "SUPERVISOR_RESTART_WINDOW", value_parser = parse_duration, default_value = "10m")]
+    pub supervisor_restart_window: Duration,
+
+    /// Backoff before the first restart of a subsystem. It doubles on each restart inside the window.
+    #[arg(long = "supervisor-backoff-min", env = "SUPERVISOR_BACKOFF_MIN", value_parser = parse_duration, default_value = "1s")]
+    pub supervisor_backoff_min: Duration,
+
+    /// Max backoff before restarting a subsystem.
+    #[arg(long = "supervisor-backoff-max", env = "SUPERVISOR_BACKOFF_MAX", value_parser = parse_duration, default_value = "1m")]
+    pub supervisor_backoff_max: Duration,
+}
+
+impl SupervisorConfig {
+    /// Configures the restart policy of all supervisors. Supervisors spawned before it use the default policy.
+    pub fn init(&self) {
+        tracing::info!(config = ?self, "configuring supervisors");
+        let policy = RestartPolicy {
+            max_restarts: self.supervisor_max_restarts,
+            restart_window: self.supervisor_restart_window,
+            backoff_min: self.supervisor_backoff_min,
+            backoff_max: self.supervisor_backoff_max,
+        };
+        if RESTART_POLICY.set(policy).is_err() {
+            tracing::warn!("supervisors restart policy is already configured");
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Policy
+// -----------------------------------------------------------------------------
+
+/// How supervised subsystems are restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub max_restarts: usize,
+    pub restart_window: Duration,
+    pub backoff_min: Duration,
+    pub backoff_max: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            restart_window: Duration::from_secs(600),
+            backoff_min: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Policy configured at startup, or the default one if not configured.
+    pub fn global() -> Self {
+        RESTART_POLICY.get().copied().unwrap_or_default()
+    }
+
+    /// Backoff before the nth restart inside the window, starting from 1.
+    fn backoff(&self, restart: usize) -> Duration {
+        let exponent = restart.saturating_sub(1).min(16) as u32;
+        self.backoff_min.saturating_mul(2u32.pow(exponent)).min(self.backoff_max)
+    }
+}
+
+/// Restarts of a subsystem inside the restart window.
+#[derive(Debug, Default)]
+struct RestartBudget {
+    restarts: Vec<Instant>,
+}
+
+impl RestartBudget {
+    /// Records a restart at the given moment, returning how many restarts happened inside the window, or `None` if the budget is exhausted.
+    fn try_restart(&mut self, policy: &RestartPolicy, now: Instant) -> Option<usize> {
+        self.restarts.retain(|restart| now.duration_since(*restart) < policy.restart_window);
+        if self.restarts.len() >= policy.max_restarts {
+            return None;
+        }
+        self.restarts.push(now);
+        Some(self.restarts.len())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Supervisor
+// -----------------------------------------------------------------------------
+
+/// How a run of a supervised subsystem ended.
+#[derive(Debug, strum::Display)]
+enum RunOutcome {
+    #[strum(to_string = "finished")]
+    Finished,
+
+    #[strum(to_string = "error")]
+    Error(anyhow::Error),
+
+    #[strum(to_string = "panic")]
+    Panic(String),
+
+    #[strum(to_string = "cancelled")]
+    Cancelled,
+}
+
+impl RunOutcome {
+    /// Checks if the run failed with a fatal error, so restarting the subsystem would fail again or run it over an invalid state.
+    fn is_fatal(&self) -> bool {
+        match self {
+            RunOutcome::Error(e) => e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<StratusError>())
+                .is_some_and(StratusError::is_fatal),
+            _ => false,
+        }
+    }
+}
+
+/// Spawns a subsystem that is restarted when it fails with an error or panics.
+///
+/// The subsystem is created by `run` before each run. It is not restarted if it finishes successfully, is cancelled, or the application is
+/// shutting down. If it fails with a fatal error or more times than the restart budget allows, the application is shut down.
+pub fn spawn_supervised<F, Fut>(name: &'static str, mut run: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let supervisor_name = format!("{}::supervisor", name);
+    spawn_named(&supervisor_name, async move {
+        let policy = RestartPolicy::global();
+        let mut budget = RestartBudget::default();
+
+        loop {
+            let outcome = match spawn_named(name, run()).await {
+                Ok(Ok(())) => RunOutcome::Finished,
+                Ok(Err(e)) => RunOutcome::Error(e),
+                Err(e) if e.is_panic() => RunOutcome::Panic(panic_message(e.into_panic())),
+                Err(_) => RunOutcome::Cancelled,
+            };
+
+            match &outcome {
+                RunOutcome::Finished | RunOutcome::Cancelled => {
+                    tracing::info!(task = %name, %outcome, "supervised task stopped");
+                    return;
+                }
+                RunOutcome::Error(e) => tracing::error!(task = %name, reason = ?e, "supervised task failed"),
+                RunOutcome::Panic(message) => tracing::error!(task = %name, reason = %message, "supervised task panicked"),
+            }
+
+            if GlobalState::is_shutdown_warn(name) {
+                return;
+            }
+
+            if outcome.is_fatal() {
+                tracing::error!(task = %name, "supervised task failed with a fatal error");
+                GlobalState::shutdown_from(name, "supervised task failed with a fatal error");
+                return;
+            }
+
+            let Some(restart) = budget.try_restart(&policy, Instant::now()) else {
+                tracing::error!(task = %name, max_restarts = %policy.max_restarts, window = ?policy.restart_window, "supervised task exhausted its restart budget");
+                GlobalState::shutdown_from(name, "supervised task exhausted its restart budget");
+                return;
+            };
+
+            #[cfg(feature = "metrics")]
+            metrics::inc_supervisor_restarts(name, outcome.to_string());
+
+            let backoff = policy.backoff(restart);
+            tracing::warn!(task = %name, %restart, ?backoff, "restarting supervised task");
+            traced_sleep(backoff, SleepReason::RetryBackoff).await;
+
+            if GlobalState::is_shutdown_warn(name) {
+                return;
+            }
+        }
+    })
+}
+
+/// Extracts the message of a panic payload, which is usually a string.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return (*message).to_owned();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    "unknown panic".to_owned()
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::ext::not;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(100), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_restart_budget() {
+        let policy = RestartPolicy {
+            max_restarts: 2,
+            restart_window: Duration::from_secs(10),
+            ..RestartPolicy::default()
+        };
+        let mut budget = RestartBudget::default();
+        let now = Instant::now();
+
+        assert_eq!(budget.try_restart(&policy, now), Some(1));
+        assert_eq!(budget.try_restart(&policy, now + Duration::from_secs(5)), Some(2));
+        assert_eq!(budget.try_restart(&policy, now + Duration::from_secs(9)), None);
+        assert_eq!(budget.try_restart(&policy, now + Duration::from_secs(11)), Some(2));
+    }
+
+    #[test]
+    fn test_fatal_outcome() {
+        let fatal = anyhow::Error::new(StratusError::StorageInvariantViolated { reason: "gap".into() }).context("failed to import block");
+        assert!(RunOutcome::Error(fatal).is_fatal());
+
+        let transient = anyhow::Error::new(StratusError::StorageUnavailable { reason: "timeout".into() });
+        assert!(not(RunOutcome::Error(transient).is_fatal()));
+
+        assert!(not(RunOutcome::Error(anyhow::anyhow!("connection reset")).is_fatal()));
+        assert!(not(RunOutcome::Panic("boom".into()).is_fatal()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restarts_panicking_task() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let supervisor = spawn_supervised("test::supervised", {
+            let runs = Arc::clone(&runs);
+            move || {
+                let run = runs.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match run {
+                        0 => panic!("first run panics"),
+                        1 => Err(anyhow::anyhow!("second run fails")),
+                        _ => Ok(()),
+                    }
+                }
+            }
+        });
+
+        supervisor.await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(not(GlobalState::is_shutdown()));
+    }
+}
@@ -0,0 +1,162 @@
+//! Registry of named tasks.
+//!
+//! Every task spawned with a name is tracked from its spawn until it finishes, so operators can list the running background tasks and check
+//! when each one last reported progress without tokio-console. Long-lived loops report progress by calling [`heartbeat`] once per iteration.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use chrono::DateTime;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+/// Tasks currently running, by their id.
+static TASKS: Lazy<Mutex<BTreeMap<u64, TaskInfo>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Id assigned to the next registered task.
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    /// Id of the async task being polled.
+    static ASYNC_TASK_ID: u64;
+}
+
+thread_local! {
+    /// Id of the blocking task or thread running in the current thread.
+    static THREAD_TASK_ID: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// How a task is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskKind {
+    Async,
+    Blocking,
+    Thread,
+}
+
+/// Information about a running task.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskInfo {
+    pub id: u64,
+    pub name: String,
+    pub kind: TaskKind,
+    pub spawned_at: DateTime<Utc>,
+
+    /// Last time the task reported progress. Tasks that are not loops never report it.
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+/// Removes the task from the registry when the task finishes or is cancelled.
+struct TaskGuard(u64);
+
+impl TaskGuard {
+    fn register(name: &str, kind: TaskKind) -> Self {
+        let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+        let info = TaskInfo {
+            id,
+            name: name.to_owned(),
+            kind,
+            spawned_at: Utc::now(),
+            last_heartbeat: None,
+        };
+        TASKS.lock().insert(id, info);
+        Self(id)
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        TASKS.lock().remove(&self.0);
+    }
+}
+
+/// Wraps an async task so it is tracked while it runs.
+pub fn track_async<T>(name: &str, task: impl Future<Output = T>) -> impl Future<Output = T> {
+    let guard = TaskGuard::register(name, TaskKind::Async);
+    ASYNC_TASK_ID.scope(guard.0, async move {
+        let _guard = guard;
+        task.await
+    })
+}
+
+/// Wraps a blocking task or thread so it is tracked while it runs.
+pub fn track_blocking<T>(name: &str, kind: TaskKind, task: impl FnOnce() -> T) -> impl FnOnce() -> T {
+    let guard = TaskGuard::register(name, kind);
+    move || {
+        let previous_id = THREAD_TASK_ID.with(|id| id.replace(Some(guard.0)));
+        let result = task();
+        THREAD_TASK_ID.with(|id| id.set(previous_id));
+        drop(guard);
+        result
+    }
+}
+
+/// Records that the current task made progress. Does nothing if the current task is not tracked.
+pub fn heartbeat() {
+    let Some(id) = current_task_id() else { return };
+    if let Some(task) = TASKS.lock().get_mut(&id) {
+        task.last_heartbeat = Some(Utc::now());
+    }
+}
+
+/// Id of the tracked task running in the current context.
+pub fn current_task_id() -> Option<u64> {
+    ASYNC_TASK_ID.try_with(|id| *id).ok().or_else(|| THREAD_TASK_ID.with(Cell::get))
+}
+
+/// Lists the running tasks in the order they were spawned.
+pub fn tasks() -> Vec<TaskInfo> {
+    TASKS.lock().values().cloned().collect()
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(id: u64) -> Option<TaskInfo> {
+        tasks().into_iter().find(|task| task.id == id)
+    }
+
+    #[tokio::test]
+    async fn test_track_async() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let handle = tokio::spawn(track_async("test::async", async move {
+            heartbeat();
+            let id = current_task_id().unwrap();
+            let _ = tx.send(id);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            id
+        }));
+
+        let id = rx.await.unwrap();
+        let task = find(id).unwrap();
+        assert_eq!(task.name, "test::async");
+        assert_eq!(task.kind, TaskKind::Async);
+        assert!(task.last_heartbeat.is_some());
+
+        handle.await.unwrap();
+        assert!(find(id).is_none());
+    }
+
+    #[test]
+    fn test_track_blocking() {
+        let task = track_blocking("test::blocking", TaskKind::Thread, || {
+            let id = current_task_id().unwrap();
+            assert!(find(id).unwrap().last_heartbeat.is_none());
+            heartbeat();
+            assert!(find(id).unwrap().last_heartbeat.is_some());
+            id
+        });
+        let id = task();
+        assert!(find(id).is_none());
+        assert!(current_task_id().is_none());
+    }
+}
@@ -1,7 +1,11 @@
+#[cfg(test)]
+mod tracing_capture;
 mod tracing_config;
 mod tracing_entered_wrap;
 mod tracing_services;
 
+#[cfg(test)]
+pub use tracing_capture::CapturedLogs;
 pub use tracing_config::TracingConfig;
 pub use tracing_config::TracingLogFormat;
 pub use tracing_config::TracingProtocol;
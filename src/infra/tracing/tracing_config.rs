@@ -3,15 +3,20 @@ use std::io::stdout;
 use std::io::IsTerminal;
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use clap::Parser;
-use console_subscriber::ConsoleLayer;
 use display_json::DebugAsJson;
 use itertools::Itertools;
+use opentelemetry::global;
+use opentelemetry::metrics::Meter;
 use opentelemetry::KeyValue;
+use opentelemetry_otlp::HttpExporterBuilder;
+use opentelemetry_otlp::MetricsExporterBuilder;
 use opentelemetry_otlp::Protocol;
 use opentelemetry_otlp::SpanExporterBuilder;
+use opentelemetry_otlp::TonicExporterBuilder;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::runtime;
 use opentelemetry_sdk::trace;
@@ -21,12 +26,16 @@ use opentelemetry_sdk::Resource as SdkResource;
 use tonic::metadata::MetadataKey;
 use tonic::metadata::MetadataMap;
 use tracing_subscriber::fmt;
+#[cfg(not(feature = "tokio-console"))]
+use tracing_subscriber::layer::Identity;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
 
-use crate::ext::spawn_named;
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::infra::alerting::AlertingConfig;
 use crate::infra::build_info;
 use crate::infra::sentry::SentryConfig;
 use crate::infra::tracing::TracingContextLayer;
@@ -55,16 +64,28 @@ pub struct TracingConfig {
     #[arg(long = "tracing-log-format", env = "TRACING_LOG_FORMAT", default_value = "normal")]
     pub tracing_log_format: TracingLogFormat,
 
-    // Tokio Console GRPC server binding address.
-    #[arg(long = "tokio-console-address", env = "TRACING_TOKIO_CONSOLE_ADDRESS")]
-    pub tracing_tokio_console_address: Option<SocketAddr>,
+    /// Exports metrics to the OpenTelemetry server, in addition to the Prometheus exporter.
+    #[arg(long = "tracing-metrics", env = "TRACING_METRICS", default_value = "false")]
+    pub tracing_metrics: bool,
+
+    /// Interval between exports of OpenTelemetry metrics.
+    #[arg(long = "tracing-metrics-interval", env = "TRACING_METRICS_INTERVAL", value_parser = parse_duration, default_value = "15s")]
+    pub tracing_metrics_interval: Duration,
+
+    /// Enables the tokio-console exporter. Requires the application to be built with the `tokio-console` feature.
+    #[arg(long = "tokio-console", env = "TRACING_TOKIO_CONSOLE", default_value = "false")]
+    pub tracing_tokio_console: bool,
+
+    /// Tokio Console GRPC server binding address.
+    #[arg(long = "tokio-console-address", env = "TRACING_TOKIO_CONSOLE_ADDRESS", default_value = "127.0.0.1:6669")]
+    pub tracing_tokio_console_address: SocketAddr,
 }
 
 impl TracingConfig {
     /// Inits application global tracing registry.
     ///
     /// Uses println! to have information available in stdout before tracing is initialized.
-    pub fn init(&self, sentry_config: &Option<SentryConfig>) -> anyhow::Result<()> {
+    pub fn init(&self, sentry_config: &Option<SentryConfig>, alerting_config: &Option<AlertingConfig>) -> anyhow::Result<()> {
         println!("creating tracing registry");
 
         // configure tracing context layer
@@ -129,30 +150,47 @@ impl TracingConfig {
             }
         };
 
-        // configure tokio-console layer
-        let tokio_console_layer = match self.tracing_tokio_console_address {
-            Some(tokio_console_address) => {
-                println!("tracing registry: enabling tokio console exporter | address={}", tokio_console_address);
-
-                let (console_layer, console_server) = ConsoleLayer::builder().with_default_env().server_addr(tokio_console_address).build();
-                spawn_named("console::grpc-server", async move {
-                    if let Err(e) = console_server.serve().await {
-                        tracing::error!(reason = ?e, address = %tokio_console_address, "failed to create tokio-console server");
-                    };
-                });
-                Some(console_layer)
+        // configure alerting layer
+        let alerting_layer = match &alerting_config {
+            Some(alerting_config) => {
+                println!(
+                    "tracing registry: enabling alerting webhook | format={} patterns={}",
+                    alerting_config.alert_format,
+                    alerting_config.alert_patterns.join(",")
+                );
+                let layer = alerting_config.init()?.with_filter(EnvFilter::from_default_env());
+                Some(layer)
             }
             None => {
+                println!("tracing registry: skipping alerting webhook");
+                None
+            }
+        };
+
+        // configure tokio-console layer
+        #[cfg(feature = "tokio-console")]
+        let tokio_console_layer = match self.tracing_tokio_console {
+            true => Some(tokio_console_layer(self.tracing_tokio_console_address)),
+            false => {
                 println!("tracing registry: skipping tokio-console exporter");
                 None
             }
         };
+        #[cfg(not(feature = "tokio-console"))]
+        let tokio_console_layer: Option<Identity> = {
+            match self.tracing_tokio_console {
+                true => println!("tracing registry: skipping tokio-console exporter because the application was built without the tokio-console feature"),
+                false => println!("tracing registry: skipping tokio-console exporter"),
+            }
+            None
+        };
 
         let result = tracing_subscriber::registry()
             .with(tracing_context_layer)
             .with(stdout_layer)
             .with(opentelemetry_layer)
             .with(sentry_layer)
+            .with(alerting_layer)
             .with(tokio_console_layer)
             .try_init();
 
@@ -164,6 +202,47 @@ impl TracingConfig {
             }
         }
     }
+
+    /// Inits the OpenTelemetry meter provider used to export metrics, if enabled.
+    ///
+    /// Metrics are sent to the same server and with the same protocol and headers used for traces.
+    pub fn init_metrics(&self) -> anyhow::Result<Option<Meter>> {
+        if not(self.tracing_metrics) {
+            return Ok(None);
+        }
+        let Some(url) = &self.tracing_url else {
+            return Err(anyhow!("opentelemetry metrics require --tracing-url"));
+        };
+        tracing::info!(%url, protocol = %self.tracing_protocol, interval = ?self.tracing_metrics_interval, "creating opentelemetry metrics exporter");
+
+        let provider = opentelemetry_otlp::new_pipeline()
+            .metrics(runtime::Tokio)
+            .with_exporter(opentelemetry_exporter::<MetricsExporterBuilder>(
+                url,
+                self.tracing_protocol,
+                &self.tracing_headers,
+            ))
+            .with_resource(opentelemetry_resource())
+            .with_period(self.tracing_metrics_interval)
+            .build()?;
+
+        // the global provider keeps the pipeline alive for the whole application
+        global::set_meter_provider(provider);
+        Ok(Some(global::meter(build_info::service_name())))
+    }
+}
+
+#[cfg(feature = "tokio-console")]
+fn tokio_console_layer(address: SocketAddr) -> console_subscriber::ConsoleLayer {
+    println!("tracing registry: enabling tokio console exporter | address={}", address);
+
+    let (console_layer, console_server) = console_subscriber::ConsoleLayer::builder().with_default_env().server_addr(address).build();
+    crate::ext::spawn_named("console::grpc-server", async move {
+        if let Err(e) = console_server.serve().await {
+            tracing::error!(reason = ?e, %address, "failed to create tokio-console server");
+        };
+    });
+    console_layer
 }
 
 fn opentelemetry_tracer(url: &str, protocol: TracingProtocol, headers: &[String]) -> SdkTracer {
@@ -175,6 +254,26 @@ fn opentelemetry_tracer(url: &str, protocol: TracingProtocol, headers: &[String]
         build_info::service_name()
     );
 
+    let tracer_exporter = opentelemetry_exporter::<SpanExporterBuilder>(url, protocol, headers);
+
+    let tracer_config = trace::config().with_resource(opentelemetry_resource());
+
+    // configure pipeline
+    let batch_config = BatchConfigBuilder::default().with_max_queue_size(u16::MAX as usize).build();
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(tracer_exporter)
+        .with_trace_config(tracer_config)
+        .with_batch_config(batch_config)
+        .install_batch(runtime::Tokio)
+        .unwrap()
+}
+
+/// Creates an OTLP exporter builder for traces or metrics.
+fn opentelemetry_exporter<B>(url: &str, protocol: TracingProtocol, headers: &[String]) -> B
+where
+    B: From<TonicExporterBuilder> + From<HttpExporterBuilder>,
+{
     // configure headers
     let headers = headers
         .iter()
@@ -186,8 +285,8 @@ fn opentelemetry_tracer(url: &str, protocol: TracingProtocol, headers: &[String]
         })
         .collect_vec();
 
-    // configure tracer
-    let tracer_exporter: SpanExporterBuilder = match protocol {
+    // configure exporter
+    match protocol {
         TracingProtocol::Grpc => {
             let mut protocol_metadata = MetadataMap::new();
             for (key, value) in headers {
@@ -214,19 +313,11 @@ fn opentelemetry_tracer(url: &str, protocol: TracingProtocol, headers: &[String]
                 .with_headers(protocol_headers)
                 .into()
         }
-    };
-
-    let tracer_config = trace::config().with_resource(SdkResource::new(vec![KeyValue::new("service.name", build_info::service_name())]));
+    }
+}
 
-    // configure pipeline
-    let batch_config = BatchConfigBuilder::default().with_max_queue_size(u16::MAX as usize).build();
-    opentelemetry_otlp::new_pipeline()
-        .tracing()
-        .with_exporter(tracer_exporter)
-        .with_trace_config(tracer_config)
-        .with_batch_config(batch_config)
-        .install_batch(runtime::Tokio)
-        .unwrap()
+fn opentelemetry_resource() -> SdkResource {
+    SdkResource::new(vec![KeyValue::new("service.name", build_info::service_name())])
 }
 
 // -----------------------------------------------------------------------------
@@ -263,43 +263,6 @@ where
 // Tracing macros
 // -----------------------------------------------------------------------------
 
-/// Logs an error and also wrap the existing error with the provided message.
-#[macro_export]
-macro_rules! log_and_err {
-    // with reason: wrap the original error with provided message
-    (reason = $error:ident, payload = $payload:expr, $msg:expr) => {
-        {
-            use anyhow::Context;
-            tracing::error!(reason = ?$error, payload = ?$payload, message = %$msg);
-            Err($error).context($msg)
-        }
-    };
-    (reason = $error:ident, $msg:expr) => {
-        {
-            use anyhow::Context;
-            tracing::error!(reason = ?$error, message = %$msg);
-            Err($error).context($msg)
-        }
-    };
-    // without reason: generate a new error using provided message
-    (payload = $payload:expr, $msg:expr) => {
-        {
-            use anyhow::Context;
-            use anyhow::anyhow;
-            tracing::error!(payload = ?$payload, message = %$msg);
-            let message = format!("{} | payload={:?}", $msg, $payload);
-            Err(anyhow!(message))
-        }
-    };
-    ($msg:expr) => {
-        {
-            use anyhow::anyhow;
-            tracing::error!(message = %$msg);
-            Err(anyhow!($msg))
-        }
-    };
-}
-
 /// Dynamic event logging based on the provided level.
 ///
 /// <https://github.com/tokio-rs/tracing/issues/2730#issuecomment-1943022805>
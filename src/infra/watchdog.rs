@@ -0,0 +1,142 @@
+//! Watchdog of critical loops.
+//!
+//! Critical loops, like the interval miner and the importer tasks, report heartbeats to the task registry once per iteration. The watchdog
+//! periodically checks them and, if one of them has not reported a heartbeat within the deadline, it logs a critical error and fails readiness
+//! checks until the loop recovers. Optionally, it restarts the process, so a silent deadlock does not leave the node running without progress.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::ext::spawn_thread;
+use crate::infra::task_registry;
+use crate::infra::task_registry::TaskInfo;
+use crate::GlobalState;
+
+/// Whether all watched loops reported heartbeats within the deadline in the last check.
+static HEALTHY: AtomicBool = AtomicBool::new(true);
+
+/// Time given for a graceful shutdown after a restart is triggered, before the process exits forcibly.
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct WatchdogConfig {
+    /// Max time a critical loop can run without reporting a heartbeat. Disabled when not set.
+    #[arg(long = "watchdog-deadline", env = "WATCHDOG_DEADLINE", value_parser = parse_duration)]
+    pub watchdog_deadline: Option<Duration>,
+
+    /// Names of the tasks watched as critical loops.
+    #[arg(
+        long = "watchdog-tasks",
+        env = "WATCHDOG_TASKS",
+        value_delimiter = ',',
+        default_value = "miner::interval,importer::executor,importer::number-fetcher,importer::block-fetcher"
+    )]
+    pub watchdog_tasks: Vec<String>,
+
+    /// Restarts the process when a critical loop misses its deadline, instead of only failing readiness checks.
+    #[arg(long = "watchdog-restart", env = "WATCHDOG_RESTART", default_value = "false")]
+    pub watchdog_restart: bool,
+}
+
+impl WatchdogConfig {
+    /// Spawns the watchdog thread, if enabled.
+    ///
+    /// It runs in its own thread, so it keeps working even if the async runtime is blocked.
+    pub fn init(&self) {
+        let Some(deadline) = self.watchdog_deadline else { return };
+        tracing::info!(config = ?self, "creating watchdog");
+
+        let config = self.clone();
+        let check_interval = (deadline / 4).max(Duration::from_secs(1));
+        spawn_thread("watchdog", move || {
+            const TASK_NAME: &str = "watchdog";
+
+            while not(GlobalState::is_shutdown_warn(TASK_NAME)) {
+                thread::sleep(check_interval);
+
+                let stalled = stalled_tasks(&task_registry::tasks(), &config.watchdog_tasks, deadline, Utc::now());
+                HEALTHY.store(stalled.is_empty(), Ordering::Relaxed);
+                if stalled.is_empty() {
+                    continue;
+                }
+
+                for task in &stalled {
+                    tracing::error!(task = %task.name, id = %task.id, last_heartbeat = ?task.last_heartbeat, ?deadline, "watchdog detected a stalled critical loop");
+                }
+                if config.watchdog_restart {
+                    restart(&stalled);
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// Checks if all watched loops reported heartbeats within the deadline in the last check.
+pub fn is_healthy() -> bool {
+    HEALTHY.load(Ordering::Relaxed)
+}
+
+/// Filters watched tasks that did not report a heartbeat within the deadline. Tasks that never reported one are measured from their spawn.
+fn stalled_tasks(tasks: &[TaskInfo], watched: &[String], deadline: Duration, now: DateTime<Utc>) -> Vec<TaskInfo> {
+    tasks
+        .iter()
+        .filter(|task| watched.contains(&task.name))
+        .filter(|task| {
+            let last_progress = task.last_heartbeat.unwrap_or(task.spawned_at);
+            (now - last_progress).to_std().unwrap_or_default() > deadline
+        })
+        .cloned()
+        .collect()
+}
+
+/// Shuts down the application and exits the process if the shutdown does not finish in time, which is expected if a loop is deadlocked.
+fn restart(stalled: &[TaskInfo]) {
+    let names = stalled.iter().map(|task| task.name.as_str()).collect::<Vec<_>>().join(",");
+    GlobalState::shutdown_from("watchdog", &format!("critical loops stalled: {}", names));
+
+    thread::sleep(RESTART_GRACE_PERIOD);
+    tracing::error!(grace_period = ?RESTART_GRACE_PERIOD, "watchdog is exiting the process because the shutdown did not finish in time");
+    std::process::exit(1);
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::task_registry::TaskKind;
+
+    #[test]
+    fn test_stalled_tasks() {
+        let now = Utc::now();
+        let task = |id: u64, name: &str, spawned_secs_ago: i64, heartbeat_secs_ago: Option<i64>| TaskInfo {
+            id,
+            name: name.to_owned(),
+            kind: TaskKind::Async,
+            spawned_at: now - chrono::Duration::seconds(spawned_secs_ago),
+            last_heartbeat: heartbeat_secs_ago.map(|secs| now - chrono::Duration::seconds(secs)),
+        };
+        let tasks = [
+            task(1, "miner::interval", 100, Some(1)),
+            task(2, "importer::executor", 100, Some(30)),
+            task(3, "importer::block-fetcher", 30, None),
+            task(4, "importer::number-fetcher", 5, None),
+            task(5, "rpc::sub::sender", 100, None),
+        ];
+        let watched = ["miner::interval", "importer::executor", "importer::block-fetcher", "importer::number-fetcher"].map(String::from);
+
+        let stalled = stalled_tasks(&tasks, &watched, Duration::from_secs(10), now);
+        assert_eq!(stalled.iter().map(|task| task.id).collect::<Vec<_>>(), vec![2, 3]);
+    }
+}
@@ -226,7 +226,7 @@ pub fn transaction_to_events(block_timestamp: UnixTime, tx: Cow<TransactionMined
         .iter()
         .filter(|log| log.log.topic0.is_some_and(|topic0| topic0 == TRANSFER_EVENT))
         .filter_map(|log| {
-            let amount_bytes: [u8; 32] = match log.log.data.0.clone().try_into() {
+            let amount_bytes: [u8; 32] = match log.log.data.as_ref().try_into() {
                 Ok(amount_bytes) => amount_bytes,
                 Err(_) => {
                     tracing::error!(?log.transaction_hash, "bug: event identified as ERC-20 transfer should have the amount as 32 bytes in the data field");
@@ -393,21 +393,21 @@ mod tests {
 
         // 2. generate fake tx data
         let mut tx: TransactionMined = Fake::fake(&Faker);
-        tx.input.input = Bytes(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        tx.input.input = Bytes::from(vec![1, 2, 3, 4, 5, 6, 7, 8]);
 
         let mut log_transfer1: LogMined = Fake::fake(&Faker);
         log_transfer1.log.address = token_address;
         log_transfer1.log.topic0 = Some(TRANSFER_EVENT);
         log_transfer1.log.topic1 = Some(alice.address.into());
         log_transfer1.log.topic2 = Some(bob.address.into());
-        log_transfer1.log.data = Bytes(amount_bytes.to_vec());
+        log_transfer1.log.data = Bytes::from(amount_bytes.to_vec());
 
         let mut log_transfer2: LogMined = Fake::fake(&Faker);
         log_transfer2.log.address = token_address;
         log_transfer2.log.topic0 = Some(TRANSFER_EVENT);
         log_transfer2.log.topic1 = Some(bob.address.into());
         log_transfer2.log.topic2 = Some(charlie.address.into());
-        log_transfer2.log.data = Bytes(amount_bytes.to_vec());
+        log_transfer2.log.data = Bytes::from(amount_bytes.to_vec());
 
         let log_random: LogMined = Fake::fake(&Faker);
 
@@ -1,14 +1,29 @@
 // #![feature(mutex_unpoison)]
 
+#[cfg(all(feature = "tokio-console", not(tokio_unstable)))]
+compile_error!("tokio-console feature requires building with RUSTFLAGS=\"--cfg tokio_unstable\"");
+
 pub mod alias;
+#[cfg(feature = "node")]
 pub mod config;
 pub mod eth;
 pub mod ext;
+#[cfg(feature = "node")]
 mod globals;
+#[cfg(feature = "node")]
 pub mod infra;
+#[cfg(feature = "node")]
 pub mod ledger;
+#[cfg(feature = "node")]
+pub mod node;
+#[cfg(feature = "node")]
 pub mod utils;
 
+#[cfg(feature = "node")]
+pub use globals::ChainState;
+#[cfg(feature = "node")]
 pub use globals::GlobalServices;
+#[cfg(feature = "node")]
 pub use globals::GlobalState;
+#[cfg(feature = "node")]
 pub use globals::NodeMode;
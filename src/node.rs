@@ -0,0 +1,61 @@
+//! Initialization of the services of a Stratus node.
+
+use std::sync::Arc;
+
+use crate::config::StratusConfig;
+use crate::eth::rpc::serve_rpc;
+
+/// Initializes all services of a node and serves RPC until the application is shut down.
+pub async fn run(config: StratusConfig) -> anyhow::Result<()> {
+    // Init supervisors
+    config.supervisor.init();
+
+    // Init services
+    let storage = config.storage.init()?;
+
+    // Init miner
+    let miner = config.miner.init(Arc::clone(&storage)).await?;
+
+    // Init event bus
+    config
+        .event_bus
+        .init(&miner, storage.reader(), config.executor.executor_gas_mode, config.kafka_config.as_ref())
+        .await?;
+
+    // Init executor
+    let executor = config.executor.init(Arc::clone(&storage), Arc::clone(&miner));
+
+    // Init importer
+    let consensus = if let Some(importer_config) = &config.importer {
+        tracing::info!(?importer_config, "creating importer");
+        let kafka_connector = config.kafka_config.as_ref().map(|inner| inner.init()).transpose()?;
+        importer_config
+            .init(Arc::clone(&executor), Arc::clone(&miner), storage.reader(), kafka_connector)
+            .await?
+    } else {
+        tracing::info!("no importer config, skipping importer");
+        None
+    };
+
+    // Init watchdog
+    config.watchdog.init();
+
+    // Init RPC server
+    serve_rpc(
+        // Services
+        storage.reader(),
+        executor,
+        miner,
+        consensus,
+        // Config
+        config.clone(),
+        config.rpc_server,
+        config.executor.executor_chain_id.into(),
+    )
+    .await?;
+
+    // Explicitly block the `main` thread to drop the storage.
+    drop(storage);
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
-create table external_blocks(
+-- tables may exist in databases created before migrations were tracked
+create table if not exists external_blocks(
     number bigint primary key not null check (number >= 0),
     block jsonb not null,
     receipts jsonb[] not null
 );
 
-create table external_balances(
+create table if not exists external_balances(
     address bytea primary key not null check (length(address) = 20),
     balance numeric not null check (balance >= 0)
 );
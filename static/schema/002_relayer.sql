@@ -0,0 +1,20 @@
+-- blocks relayed from stratus to the target chain
+create table relayer_blocks(
+    number bigint primary key not null check (number >= 0),
+    hash bytea not null check (length(hash) = 32),
+    relayed_at timestamptz not null default now(),
+    -- set by the reconciliation when the block is not found in stratus anymore
+    orphaned_at timestamptz
+);
+
+-- relayed transactions whose receipt in the target chain differs from the stratus one
+create table relayer_mismatches(
+    hash bytea primary key not null check (length(hash) = 32),
+    block_number bigint not null check (block_number >= 0),
+    stratus_receipt jsonb not null,
+    -- null when the target chain did not confirm the transaction in time
+    target_receipt jsonb,
+    created_at timestamptz not null default now()
+);
+
+create index relayer_mismatches_block_number on relayer_mismatches(block_number);
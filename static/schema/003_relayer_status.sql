@@ -0,0 +1,21 @@
+-- relay status of each block (see relay_status.rs)
+alter table relayer_blocks
+    add column status text not null default 'pending' check (status in ('pending', 'relayed', 'confirmed', 'mismatched')),
+    add column mined_at timestamptz,
+    add column pending_at timestamptz,
+    add column checked_at timestamptz,
+    alter column relayed_at drop not null,
+    alter column relayed_at drop default;
+
+-- blocks relayed before statuses were tracked were already compared
+update relayer_blocks
+set
+    status = case when exists (select 1 from relayer_mismatches where block_number = number) then 'mismatched' else 'confirmed' end,
+    pending_at = relayed_at,
+    checked_at = relayed_at;
+
+alter table relayer_blocks
+    alter column pending_at set not null,
+    alter column pending_at set default now();
+
+create index relayer_blocks_unfinished on relayer_blocks(number) where status in ('pending', 'relayed');
@@ -0,0 +1,41 @@
+#!/bin/bash
+#
+# Captures a block and its receipts from a running client into the fixtures used by the external block compatibility tests.
+#
+# Usage: ./utils/capture-external-client-fixture.sh <client> <rpc-url> [block-number]
+# Example: ./utils/capture-external-client-fixture.sh geth http://localhost:8545 0x1
+
+set -e
+
+client=$1
+rpc_url=$2
+block_number=${3:-0x1}
+
+if [ -z "$client" ] || [ -z "$rpc_url" ]; then
+    echo "Usage: $0 <client> <rpc-url> [block-number]"
+    exit 1
+fi
+
+rpc() {
+    curl -s "$rpc_url" \
+        -H "Content-Type: application/json" \
+        -d "{\"jsonrpc\":\"2.0\",\"method\":\"$1\",\"params\":$2,\"id\":1}" | jq '.result'
+}
+
+echo "-> Capturing block $block_number from $client"
+block=$(rpc eth_getBlockByNumber "[\"$block_number\",true]")
+if [ "$block" == "null" ]; then
+    echo "Block $block_number not found"
+    exit 1
+fi
+
+echo "-> Capturing receipts"
+receipts="[]"
+for tx_hash in $(echo "$block" | jq -r '.transactions[].hash'); do
+    receipt=$(rpc eth_getTransactionReceipt "[\"$tx_hash\"]")
+    receipts=$(echo "$receipts" | jq --argjson receipt "$receipt" '. + [$receipt]')
+done
+
+output="tests/fixtures/external_clients/$client.json"
+jq -n --argjson block "$block" --argjson receipts "$receipts" '{block: $block, receipts: $receipts}' > "$output"
+echo "-> Saved to $output"